@@ -318,4 +318,4 @@ pub const MAX_CONTAINER_DEPTH: usize = 500;
 
 pub use de::{from_bytes, from_bytes_seed};
 pub use error::{Error, Result};
-pub use ser::{is_human_readable, serialize_into, to_bytes};
+pub use ser::{is_human_readable, serialize_into, serialized_size, to_bytes};
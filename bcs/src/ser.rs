@@ -65,21 +65,55 @@ where
     value.serialize(serializer)
 }
 
+/// Returns the size of the BCS serialization of the given data structure
+/// without allocating the output.
+///
+/// Fails in the same cases as `to_bytes`.
+pub fn serialized_size<T>(value: &T) -> Result<usize>
+where
+    T: ?Sized + Serialize,
+{
+    let mut counter = SizeCounter(0);
+    let serializer = Serializer::new(&mut counter, crate::MAX_CONTAINER_DEPTH);
+    value.serialize(serializer)?;
+    Ok(counter.0)
+}
+
 pub fn is_human_readable() -> bool {
     let mut output = Vec::new();
     let serializer = Serializer::new(&mut output, crate::MAX_CONTAINER_DEPTH);
     ser::Serializer::is_human_readable(&serializer)
 }
 
+/// Destination of the serialized bytes.
+trait Output {
+    fn write(&mut self, bytes: &[u8]);
+}
+
+impl Output for Vec<u8> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+/// Output which only counts the serialized bytes.
+struct SizeCounter(usize);
+
+impl Output for SizeCounter {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 += bytes.len();
+    }
+}
+
 /// Serialization implementation for BCS
-struct Serializer<'a> {
-    output: &'a mut Vec<u8>,
+struct Serializer<'a, W> {
+    output: &'a mut W,
     max_remaining_depth: usize,
 }
 
-impl<'a> Serializer<'a> {
+impl<'a, W: Output> Serializer<'a, W> {
     /// Creates a new `Serializer` which will emit LCS.
-    fn new(output: &'a mut Vec<u8>, max_remaining_depth: usize) -> Self {
+    fn new(output: &'a mut W, max_remaining_depth: usize) -> Self {
         Self {
             output,
             max_remaining_depth,
@@ -90,11 +124,11 @@ impl<'a> Serializer<'a> {
         while value >= 0x80 {
             // Write 7 (lowest) bits of data and set the 8th bit to 1.
             let byte = (value & 0x7f) as u8;
-            self.output.extend_from_slice(&[byte | 0x80]);
+            self.output.write(&[byte | 0x80]);
             value >>= 7;
         }
         // Write the remaining bits of data and set the highest bit to 0.
-        self.output.extend_from_slice(&[value as u8]);
+        self.output.write(&[value as u8]);
         Ok(())
     }
 
@@ -119,14 +153,14 @@ impl<'a> Serializer<'a> {
     }
 }
 
-impl<'a> ser::Serializer for Serializer<'a> {
+impl<'a, W: Output> ser::Serializer for Serializer<'a, W> {
     type Ok = ();
     type Error = Error;
     type SerializeSeq = Self;
     type SerializeTuple = Self;
     type SerializeTupleStruct = Self;
     type SerializeTupleVariant = Self;
-    type SerializeMap = MapSerializer<'a>;
+    type SerializeMap = MapSerializer<'a, W>;
     type SerializeStruct = Self;
     type SerializeStructVariant = Self;
 
@@ -155,27 +189,27 @@ impl<'a> ser::Serializer for Serializer<'a> {
     }
 
     fn serialize_u8(self, v: u8) -> Result<()> {
-        self.output.extend_from_slice(&[v]);
+        self.output.write(&[v]);
         Ok(())
     }
 
     fn serialize_u16(self, v: u16) -> Result<()> {
-        self.output.extend_from_slice(&v.to_le_bytes());
+        self.output.write(&v.to_le_bytes());
         Ok(())
     }
 
     fn serialize_u32(self, v: u32) -> Result<()> {
-        self.output.extend_from_slice(&v.to_le_bytes());
+        self.output.write(&v.to_le_bytes());
         Ok(())
     }
 
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.output.extend_from_slice(&v.to_le_bytes());
+        self.output.write(&v.to_le_bytes());
         Ok(())
     }
 
     fn serialize_u128(self, v: u128) -> Result<()> {
-        self.output.extend_from_slice(&v.to_le_bytes());
+        self.output.write(&v.to_le_bytes());
         Ok(())
     }
 
@@ -199,7 +233,7 @@ impl<'a> ser::Serializer for Serializer<'a> {
     // Serialize a byte array as an array of bytes.
     fn serialize_bytes(mut self, v: &[u8]) -> Result<()> {
         self.output_seq_len(v.len())?;
-        self.output.extend_from_slice(v);
+        self.output.write(v);
         Ok(())
     }
 
@@ -213,7 +247,7 @@ impl<'a> ser::Serializer for Serializer<'a> {
     where
         T: ?Sized + Serialize,
     {
-        self.output.extend_from_slice(&[1]);
+        self.output.write(&[1]);
         value.serialize(self)
     }
 
@@ -328,7 +362,7 @@ impl<'a> ser::Serializer for Serializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeSeq for Serializer<'a> {
+impl<'a, W: Output> ser::SerializeSeq for Serializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -344,7 +378,7 @@ impl<'a> ser::SerializeSeq for Serializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeTuple for Serializer<'a> {
+impl<'a, W: Output> ser::SerializeTuple for Serializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -360,7 +394,7 @@ impl<'a> ser::SerializeTuple for Serializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeTupleStruct for Serializer<'a> {
+impl<'a, W: Output> ser::SerializeTupleStruct for Serializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -376,7 +410,7 @@ impl<'a> ser::SerializeTupleStruct for Serializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeTupleVariant for Serializer<'a> {
+impl<'a, W: Output> ser::SerializeTupleVariant for Serializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -393,14 +427,14 @@ impl<'a> ser::SerializeTupleVariant for Serializer<'a> {
 }
 
 #[doc(hidden)]
-struct MapSerializer<'a> {
-    serializer: Serializer<'a>,
+struct MapSerializer<'a, W> {
+    serializer: Serializer<'a, W>,
     entries: Vec<(Vec<u8>, Vec<u8>)>,
     next_key: Option<Vec<u8>>,
 }
 
-impl<'a> MapSerializer<'a> {
-    fn new(serializer: Serializer<'a>) -> Self {
+impl<'a, W: Output> MapSerializer<'a, W> {
+    fn new(serializer: Serializer<'a, W>) -> Self {
         MapSerializer {
             serializer,
             entries: Vec::new(),
@@ -409,7 +443,7 @@ impl<'a> MapSerializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeMap for MapSerializer<'a> {
+impl<'a, W: Output> ser::SerializeMap for MapSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -459,15 +493,15 @@ impl<'a> ser::SerializeMap for MapSerializer<'a> {
         self.serializer.output_seq_len(len)?;
 
         for (key, value) in &self.entries {
-            self.serializer.output.extend_from_slice(key);
-            self.serializer.output.extend_from_slice(value);
+            self.serializer.output.write(key);
+            self.serializer.output.write(value);
         }
 
         Ok(())
     }
 }
 
-impl<'a> ser::SerializeStruct for Serializer<'a> {
+impl<'a, W: Output> ser::SerializeStruct for Serializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -483,7 +517,7 @@ impl<'a> ser::SerializeStruct for Serializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeStructVariant for Serializer<'a> {
+impl<'a, W: Output> ser::SerializeStructVariant for Serializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -13,13 +13,14 @@ use proptest::prelude::*;
 use proptest_derive::Arbitrary;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use bcs::{from_bytes, to_bytes, Error, MAX_CONTAINER_DEPTH, MAX_SEQUENCE_LENGTH};
+use bcs::{from_bytes, serialized_size, to_bytes, Error, MAX_CONTAINER_DEPTH, MAX_SEQUENCE_LENGTH};
 
 fn is_same<T>(t: T)
 where
     T: Serialize + DeserializeOwned + fmt::Debug + PartialEq,
 {
     let bytes = to_bytes(&t).unwrap();
+    assert_eq!(serialized_size(&t).unwrap(), bytes.len());
     let s: T = from_bytes(&bytes).unwrap();
     assert_eq!(t, s);
 }
@@ -12,6 +12,8 @@ use core::fmt::{Display, Formatter};
 use diem_crypto_derive::{BCSCryptoHash, CryptoHasher};
 use parity_scale_codec::{Decode, Encode};
 #[cfg(any(test, feature = "fuzzing"))]
+use proptest::{collection::vec, prelude::*};
+#[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
 
@@ -65,6 +67,59 @@ pub struct StructTag {
     pub type_params: Vec<TypeTag>,
 }
 
+/// The `Arbitrary` impl limits the nesting of the vector and struct tags.
+#[cfg(any(test, feature = "fuzzing"))]
+impl Arbitrary for TypeTag {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        let leaf = prop_oneof![
+            Just(TypeTag::Bool),
+            Just(TypeTag::U8),
+            Just(TypeTag::U64),
+            Just(TypeTag::U128),
+            Just(TypeTag::Address),
+            Just(TypeTag::Signer),
+        ];
+        leaf.prop_recursive(4, 16, 3, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|tag| TypeTag::Vector(Box::new(tag))),
+                struct_tag_strategy(inner).prop_map(TypeTag::Struct),
+            ]
+        })
+        .boxed()
+    }
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+impl Arbitrary for StructTag {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        struct_tag_strategy(any::<TypeTag>()).boxed()
+    }
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+fn struct_tag_strategy(
+    type_param: impl Strategy<Value = TypeTag>,
+) -> impl Strategy<Value = StructTag> {
+    (
+        any::<AccountAddress>(),
+        any::<Identifier>(),
+        any::<Identifier>(),
+        vec(type_param, 0..3),
+    )
+        .prop_map(|(address, module, name, type_params)| StructTag {
+            address,
+            module,
+            name,
+            type_params,
+        })
+}
+
 impl StructTag {
     #[allow(clippy::vec_init_then_push)]
     pub fn access_vector(&self) -> Vec<u8> {
@@ -42,6 +42,11 @@ pub static EXECUTION_STATUS_MIN_CODE: u64 = 4000;
 /// The maximum status code for runtim statuses
 pub static EXECUTION_STATUS_MAX_CODE: u64 = 4999;
 
+// Every status range reserves its last hundred codes (900-999, 1900-1999, ..., 4900-4999)
+// for the statuses of this vm which are not defined by the upstream Diem. New upstream
+// codes are taken from the lower part of the ranges, so the two sets never collide and
+// a code keeps its meaning when the upstream statuses are merged.
+
 /// A `VMStatus` is represented as either
 /// - `Executed` indicating successful execution
 /// - `Error` indicating an error from the VM itself
@@ -464,6 +469,20 @@ pub enum StatusCode {
     BAD_CHAIN_ID = 23,
     // The sequence number is too large and would overflow if the transaction were executed
     SEQUENCE_NUMBER_TOO_BIG = 24,
+    // Validation errors of the vm which are not defined by the upstream Diem: 900-999
+    // The vm is halted by the invariant violation circuit breaker
+    VM_HALTED = 900,
+    // The transaction is not valid before a later block timestamp
+    TRANSACTION_NOT_YET_VALID = 901,
+    // The script has more type arguments than the configured maximum
+    TOO_MANY_TYPE_ARGUMENTS = 902,
+    // Total size of the script arguments exceeds the configured maximum
+    EXCEEDED_MAX_ARGUMENTS_SIZE = 903,
+    // The local event consumer can't accept the events of the transaction
+    EVENT_CHANNEL_FULL = 904,
+    // The execution was stopped by the host interrupt. The interrupt depends on the node,
+    // so the transaction is discarded and no gas is charged.
+    EXECUTION_INTERRUPTED = 905,
 
     // When a code module/script is published it is verified. These are the
     // possible errors that can arise from the verification process.
@@ -555,6 +574,21 @@ pub enum StatusCode {
     // The sender is trying to publish a module named `M`, but the sender's account already
     // contains a module with this name.
     DUPLICATE_MODULE_NAME = 1095,
+    // Verification errors of the vm which are not defined by the upstream Diem: 1900-1999
+    // The published module exceeds the configured size limit.
+    MODULE_SIZE_LIMIT_EXCEEDED = 1900,
+    // The published module defines more functions than allowed.
+    TOO_MANY_FUNCTIONS = 1901,
+    // The published module defines more structs than allowed.
+    TOO_MANY_STRUCTS = 1902,
+    // The published module contains an identifier longer than allowed.
+    IDENTIFIER_TOO_LONG = 1903,
+    // The published module replaces a module with incompatible structs or public functions.
+    BACKWARD_INCOMPATIBLE_MODULE_UPDATE = 1904,
+    // The published module replaces a module which can not be upgraded.
+    IMMUTABLE_MODULE_UPDATE = 1905,
+    // The function called as a transaction entry point is not an entry function.
+    EXECUTE_ENTRY_FUNCTION_CALLED_ON_NON_ENTRY_FUNCTION = 1906,
 
     // These are errors that the VM might raise if a violation of internal
     // invariants takes place.
@@ -617,6 +651,13 @@ pub enum StatusCode {
     CALL_STACK_OVERFLOW = 4021,
     VM_MAX_TYPE_DEPTH_REACHED = 4024,
     VM_MAX_VALUE_DEPTH_REACHED = 4025,
+    // Execution errors of the vm which are not defined by the upstream Diem: 4900-4999
+    // The script executed more instructions than the configured limit.
+    INSTRUCTION_LIMIT_EXCEEDED = 4900,
+    // The transaction exceeded the maximum number or the total size of its events.
+    EVENT_LIMIT_EXCEEDED = 4901,
+    // The transaction accessed a host service disabled for the execution.
+    HOST_ACCESS_DENIED = 4902,
 
     // A reserved status to represent an unknown vm status.
     // this is std::u64::MAX, but we can't pattern match on that, so put the hardcoded value in
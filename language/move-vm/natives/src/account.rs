@@ -8,7 +8,7 @@ use alloc::vec::Vec;
 use move_core_types::account_address::AccountAddress;
 use move_core_types::language_storage::TypeTag;
 use move_core_types::vm_status::StatusCode;
-use move_vm_types::natives::balance::{BalanceOperation, WalletId};
+use move_vm_types::natives::balance::{BalanceOperation, MintBurnJournal, WalletId};
 use move_vm_types::values::{SignerRef, ValueImpl};
 use move_vm_types::{
     gas_schedule::NativeCostIndex,
@@ -62,6 +62,7 @@ pub fn native_deposit(
 
     if let Some(balance) = context.get_balance(&wallet_id) {
         if balance >= amount {
+            record_mint_burn(context, &wallet_id);
             context.save_balance_operation(wallet_id, BalanceOperation::Deposit(amount));
             let cost = native_gas(context.cost_table(), NativeCostIndex::DEPOSIT, 0);
             Ok(NativeResult::ok(cost, vec![create_balance(amount)]))
@@ -92,6 +93,7 @@ pub fn native_withdraw(
 
     let wallet_id = wallet_id(context, address, ty_args.pop().unwrap())?;
 
+    record_mint_burn(context, &wallet_id);
     context.save_balance_operation(wallet_id, BalanceOperation::Withdraw(balance));
 
     let cost = native_gas(context.cost_table(), NativeCostIndex::WITHDRAW, 0);
@@ -122,6 +124,15 @@ pub fn get_balance(
     }
 }
 
+/// Records the use of the mint and burn capability of the currency in the session.
+fn record_mint_burn(context: &mut impl NativeContext, wallet_id: &WalletId) {
+    context
+        .extensions()
+        .get_or_default::<MintBurnJournal>()
+        .0
+        .insert(wallet_id.tag.clone());
+}
+
 fn wallet_id(
     ctx: &impl NativeContext,
     address: AccountAddress,
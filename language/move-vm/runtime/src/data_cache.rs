@@ -2,10 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use alloc::collections::btree_map::BTreeMap;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 
-use hashbrown::HashMap;
-
 use move_core_types::{
     account_address::AccountAddress,
     language_storage::{ModuleId, StructTag, TypeTag},
@@ -13,6 +12,7 @@ use move_core_types::{
     vm_status::StatusCode,
 };
 use move_vm_types::natives::balance::{BalanceOperation, MasterOfCoin, NativeBalance, WalletId};
+use move_vm_types::natives::extensions::NativeExtensions;
 use move_vm_types::{
     data_store::DataStore,
     loaded_data::runtime_types::Type,
@@ -21,6 +21,7 @@ use move_vm_types::{
 use vm::errors::*;
 
 use crate::loader::Loader;
+use crate::move_vm::VMLimits;
 
 /// Trait for the Move VM to abstract storage operations.
 ///
@@ -48,11 +49,16 @@ pub trait RemoteCache {
         address: &AccountAddress,
         tag: &StructTag,
     ) -> PartialVMResult<Option<Vec<u8>>>;
+    /// Returns the time-weighted average of the `Coins::Price` resource `tag`
+    /// over the last `window` seconds, if the storage provides one.
+    fn get_twap(&self, _tag: &StructTag, _window: u64) -> PartialVMResult<Option<u128>> {
+        Ok(None)
+    }
 }
 
 pub struct AccountDataCache {
     data_map: BTreeMap<Type, (MoveTypeLayout, GlobalValue)>,
-    module_map: BTreeMap<ModuleId, Vec<u8>>,
+    module_map: BTreeMap<ModuleId, Arc<[u8]>>,
 }
 
 impl AccountDataCache {
@@ -77,9 +83,9 @@ impl AccountDataCache {
 /// The Move VM takes a `DataStore` in input and this is the default and correct implementation
 /// for a data store related to a transaction. Clients should create an instance of this type
 /// and pass it to the Move VM.
-pub(crate) struct TransactionDataCache<'r, 'l, R, B: NativeBalance> {
+pub(crate) struct TransactionDataCache<'r, R, B: NativeBalance> {
     remote: &'r R,
-    loader: &'l Loader,
+    loader: Loader,
     account_map: BTreeMap<AccountAddress, AccountDataCache>,
     event_data: Vec<(
         AccountAddress,
@@ -88,19 +94,27 @@ pub(crate) struct TransactionDataCache<'r, 'l, R, B: NativeBalance> {
         Value,
         Option<ModuleId>,
     )>,
+    event_bytes: usize,
+    max_events: usize,
+    max_event_bytes: usize,
     master_of_coin: MasterOfCoin<B>,
+    extensions: NativeExtensions,
 }
 
 /// Collection of side effects produced by a Session.
 ///
 /// The Move VM MUST guarantee that no duplicate entries exist.
+/// Struct tags of the resources are shared with the loader cache.
+/// Effects are ordered independently of the loader cache state, so every node produces
+/// the same effects: resources by the address and the struct tag, modules by the module id,
+/// events in the emission order and wallet operations by the wallet id.
 #[derive(Debug)]
 pub struct TransactionEffects {
     pub resources: Vec<(
         AccountAddress,
-        Vec<(StructTag, Option<(MoveTypeLayout, Value)>)>,
+        Vec<(Arc<StructTag>, Option<(MoveTypeLayout, Value)>)>,
     )>,
-    pub modules: Vec<(ModuleId, Vec<u8>)>,
+    pub modules: Vec<(ModuleId, Arc<[u8]>)>,
     pub events: Vec<(
         AccountAddress,
         TypeTag,
@@ -108,19 +122,25 @@ pub struct TransactionEffects {
         Value,
         Option<ModuleId>,
     )>,
-    pub wallet_ops: HashMap<WalletId, BalanceOperation>,
+    pub wallet_ops: BTreeMap<WalletId, BalanceOperation>,
+    /// Per-session state of the natives.
+    pub extensions: NativeExtensions,
 }
 
-impl<'r, 'l, R: RemoteCache, B: NativeBalance> TransactionDataCache<'r, 'l, R, B> {
+impl<'r, R: RemoteCache, B: NativeBalance> TransactionDataCache<'r, R, B> {
     /// Create a `TransactionDataCache` with a `RemoteCache` that provides access to data
     /// not updated in the transaction.
-    pub(crate) fn new(remote: &'r R, loader: &'l Loader, balance: B) -> Self {
+    pub(crate) fn new(remote: &'r R, loader: Loader, balance: B, limits: &VMLimits) -> Self {
         TransactionDataCache {
             remote,
             loader,
             account_map: BTreeMap::new(),
             event_data: vec![],
+            event_bytes: 0,
+            max_events: limits.max_events,
+            max_event_bytes: limits.max_event_bytes,
             master_of_coin: MasterOfCoin::new(balance),
+            extensions: NativeExtensions::default(),
         }
     }
 
@@ -136,25 +156,18 @@ impl<'r, 'l, R: RemoteCache, B: NativeBalance> TransactionDataCache<'r, 'l, R, B
             for (ty, (ty_layout, gv)) in account_cache.data_map {
                 match gv.into_effect()? {
                     GlobalValueEffect::None => (),
+                    // non-struct top-level value can't happen, `type_to_struct_tag` fails on it
                     GlobalValueEffect::Deleted => {
-                        if let TypeTag::Struct(s_tag) = self.loader.type_to_type_tag(&ty)? {
-                            vals.push((s_tag, None))
-                        } else {
-                            // non-struct top-level value; can't happen
-                            return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR));
-                        }
+                        vals.push((self.loader.type_to_struct_tag(&ty)?, None))
                     }
                     GlobalValueEffect::Changed(val) => {
-                        if let TypeTag::Struct(s_tag) = self.loader.type_to_type_tag(&ty)? {
-                            vals.push((s_tag, Some((ty_layout, val))))
-                        } else {
-                            // non-struct top-level value; can't happen
-                            return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR));
-                        }
+                        vals.push((self.loader.type_to_struct_tag(&ty)?, Some((ty_layout, val))))
                     }
                 }
             }
             if !vals.is_empty() {
+                // Loaded types are ordered by the loader cache indexes, which differ between nodes.
+                vals.sort_by(|(a, _), (b, _)| a.cmp(b));
                 resources.push((addr, vals));
             }
             modules.extend(
@@ -176,6 +189,7 @@ impl<'r, 'l, R: RemoteCache, B: NativeBalance> TransactionDataCache<'r, 'l, R, B
             modules,
             events,
             wallet_ops: self.master_of_coin.into(),
+            extensions: self.extensions,
         })
     }
 
@@ -204,7 +218,7 @@ impl<'r, 'l, R: RemoteCache, B: NativeBalance> TransactionDataCache<'r, 'l, R, B
 }
 
 // `DataStore` implementation for the `TransactionDataCache`
-impl<'r, 'l, C: RemoteCache, B: NativeBalance> DataStore for TransactionDataCache<'r, 'l, C, B> {
+impl<'r, C: RemoteCache, B: NativeBalance> DataStore for TransactionDataCache<'r, C, B> {
     // Retrieve data from the local cache or loads it from the remote cache into the local cache.
     // All operations on the global data are based on this API and they all load the data
     // into the cache.
@@ -218,14 +232,8 @@ impl<'r, 'l, C: RemoteCache, B: NativeBalance> DataStore for TransactionDataCach
         });
 
         if !account_cache.data_map.contains_key(ty) {
-            let ty_tag = match self.loader.type_to_type_tag(ty)? {
-                TypeTag::Struct(s_tag) => s_tag,
-                _ =>
-                // non-struct top-level value; can't happen
-                {
-                    return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR));
-                }
-            };
+            // non-struct top-level value can't happen, `type_to_struct_tag` fails on it
+            let ty_tag = self.loader.type_to_struct_tag(ty)?;
             let ty_layout = self.loader.type_to_type_layout(ty)?;
 
             let gv = match self.remote.get_resource(&addr, &ty_tag) {
@@ -270,10 +278,17 @@ impl<'r, 'l, C: RemoteCache, B: NativeBalance> DataStore for TransactionDataCach
             .expect("global value must exist"))
     }
 
+    fn is_resource_loaded(&self, addr: AccountAddress, ty: &Type) -> bool {
+        self.account_map
+            .get(&addr)
+            .map(|account_cache| account_cache.data_map.contains_key(ty))
+            .unwrap_or(false)
+    }
+
     fn load_module(&self, module_id: &ModuleId) -> VMResult<Vec<u8>> {
         if let Some(account_cache) = self.account_map.get(module_id.address()) {
             if let Some(blob) = account_cache.module_map.get(module_id) {
-                return Ok(blob.clone());
+                return Ok(blob.to_vec());
             }
         }
         match self.remote.get_module(module_id) {
@@ -296,7 +311,7 @@ impl<'r, 'l, C: RemoteCache, B: NativeBalance> DataStore for TransactionDataCach
         }
     }
 
-    fn publish_module(&mut self, module_id: &ModuleId, blob: Vec<u8>) -> VMResult<()> {
+    fn publish_module(&mut self, module_id: &ModuleId, blob: Arc<[u8]>) -> VMResult<()> {
         let account_cache =
             Self::get_mut_or_insert_with(&mut self.account_map, module_id.address(), || {
                 (*module_id.address(), AccountDataCache::new())
@@ -325,6 +340,16 @@ impl<'r, 'l, C: RemoteCache, B: NativeBalance> DataStore for TransactionDataCach
         caller: Option<ModuleId>,
     ) -> PartialVMResult<()> {
         let ty_layout = self.loader.type_to_type_layout(&ty)?;
+        let size = val
+            .simple_serialize(&ty_layout)
+            .ok_or_else(|| PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR))?
+            .len();
+        if self.event_data.len() >= self.max_events
+            || self.event_bytes + size > self.max_event_bytes
+        {
+            return Err(PartialVMError::new(StatusCode::EVENT_LIMIT_EXCEEDED));
+        }
+        self.event_bytes += size;
         Ok(self.event_data.push((address, ty, ty_layout, val, caller)))
     }
 
@@ -332,8 +357,24 @@ impl<'r, 'l, C: RemoteCache, B: NativeBalance> DataStore for TransactionDataCach
         self.master_of_coin.get_balance(wallet_id)
     }
 
+    fn read_remote_resource(
+        &self,
+        addr: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        self.remote.get_resource(addr, tag)
+    }
+
+    fn get_twap(&self, tag: &StructTag, window: u64) -> PartialVMResult<Option<u128>> {
+        self.remote.get_twap(tag, window)
+    }
+
     fn save_balance_operation(&mut self, wallet_id: WalletId, balance_op: BalanceOperation) {
         self.master_of_coin
             .save_balance_operation(wallet_id, balance_op)
     }
+
+    fn extensions(&mut self) -> &mut NativeExtensions {
+        &mut self.extensions
+    }
 }
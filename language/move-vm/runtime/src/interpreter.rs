@@ -4,6 +4,7 @@
 use crate::{
     loader::{Function, Loader, Resolver},
     logging::LogContext,
+    move_vm::{StorageAccessCosts, VMLimits},
     native_functions::FunctionContext,
 };
 use alloc::borrow::ToOwned;
@@ -15,7 +16,7 @@ use core::{cmp::min, fmt::Write};
 use move_core_types::language_storage::ModuleId;
 use move_core_types::{
     account_address::AccountAddress,
-    gas_schedule::{AbstractMemorySize, GasAlgebra, GasCarrier},
+    gas_schedule::{AbstractMemorySize, GasAlgebra, GasCarrier, GasUnits},
     vm_status::{StatusCode, StatusType},
 };
 use move_vm_types::{
@@ -66,13 +67,17 @@ pub(crate) struct Interpreter<L: LogContext> {
     operand_stack: Stack,
     /// The stack of active functions.
     call_stack: CallStack,
+    /// Maximum depth of the type arguments of a generic call.
+    max_type_depth: usize,
+    /// Gas of the cold and warm storage accesses.
+    access_costs: StorageAccessCosts,
     // Logger to report information to clients
     log_context: L,
 }
 
 impl<L: LogContext> Interpreter<L> {
     /// Entrypoint into the interpreter. All external calls need to be routed through this
-    /// function.
+    /// function. Returns the values returned by the function.
     pub(crate) fn entrypoint(
         function: Arc<Function>,
         ty_args: Vec<Type>,
@@ -80,24 +85,37 @@ impl<L: LogContext> Interpreter<L> {
         data_store: &mut impl DataStore,
         cost_strategy: &mut CostStrategy,
         loader: &Loader,
+        limits: &VMLimits,
+        access_costs: &StorageAccessCosts,
         log_context: &L,
-    ) -> VMResult<()> {
+    ) -> VMResult<Vec<Value>> {
         // We count the intrinsic cost of the transaction here, since that needs to also cover the
         // setup of the function.
-        let mut interp = Self::new(log_context.clone());
+        let mut interp = Self::new(limits, *access_costs, log_context.clone());
         interp.execute(loader, data_store, cost_strategy, function, ty_args, args)
     }
 
     /// Create a new instance of an `Interpreter` in the context of a transaction with a
     /// given module cache and gas schedule.
-    fn new(log_context: L) -> Self {
+    fn new(limits: &VMLimits, access_costs: StorageAccessCosts, log_context: L) -> Self {
         Interpreter {
             operand_stack: Stack::new(),
-            call_stack: CallStack::new(),
+            call_stack: CallStack::new(limits.max_call_depth),
+            max_type_depth: limits.max_type_depth,
+            access_costs,
             log_context,
         }
     }
 
+    /// Checks the depth of the type arguments against the limit.
+    fn check_type_depth(&self, ty_args: &[Type]) -> PartialVMResult<()> {
+        if ty_args.iter().any(|ty| ty.depth() > self.max_type_depth) {
+            Err(PartialVMError::new(StatusCode::VM_MAX_TYPE_DEPTH_REACHED))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Internal execution entry point.
     fn execute(
         &mut self,
@@ -107,7 +125,7 @@ impl<L: LogContext> Interpreter<L> {
         function: Arc<Function>,
         ty_args: Vec<Type>,
         args: Vec<Value>,
-    ) -> VMResult<()> {
+    ) -> VMResult<Vec<Value>> {
         // No unwinding of the call stack and value stack need to be done here -- the context will
         // take care of that.
         self.execute_main(loader, data_store, cost_strategy, function, ty_args, args)
@@ -129,8 +147,10 @@ impl<L: LogContext> Interpreter<L> {
         function: Arc<Function>,
         ty_args: Vec<Type>,
         args: Vec<Value>,
-    ) -> VMResult<()> {
+    ) -> VMResult<Vec<Value>> {
         verify_args(function.parameters(), &args).map_err(|e| self.set_location(e))?;
+        self.check_type_depth(&ty_args)
+            .map_err(|e| self.set_location(e))?;
         let mut locals = Locals::new(function.local_count());
         for (i, value) in args.into_iter().enumerate() {
             locals
@@ -155,7 +175,11 @@ impl<L: LogContext> Interpreter<L> {
                         current_frame = frame;
                         current_frame.pc += 1; // advance past the Call instruction in the caller
                     } else {
-                        return Ok(());
+                        // Values returned by the entry function are left on the operand stack.
+                        return self
+                            .operand_stack
+                            .popn(current_frame.function.return_count() as u16)
+                            .map_err(|e| set_err_info!(current_frame, e));
                     }
                 }
                 ExitCode::Call(fh_idx) => {
@@ -206,6 +230,10 @@ impl<L: LogContext> Interpreter<L> {
                         .map_err(|e| set_err_info!(current_frame, e))?;
                     let ty_args = resolver
                         .instantiate_generic_function(idx, current_frame.ty_args())
+                        .and_then(|ty_args| {
+                            self.check_type_depth(&ty_args)?;
+                            Ok(ty_args)
+                        })
                         .map_err(|e| set_err_info!(current_frame, e))?;
                     let func = resolver.function_from_instantiation(idx);
                     cost_strategy
@@ -306,7 +334,7 @@ impl<L: LogContext> Interpreter<L> {
         }
         let mut native_context =
             FunctionContext::new(self, data_store, cost_strategy, resolver, caller);
-        let native_function = function.get_native()?;
+        let native_function = resolver.loader().native_function(&function)?;
         let result = native_function.dispatch(&mut native_context, ty_args, arguments)?;
         cost_strategy.deduct_gas(result.cost)?;
         let values = result
@@ -369,13 +397,31 @@ impl<L: LogContext> Interpreter<L> {
         }
     }
 
+    /// Charges the cold or the warm access cost of the resource.
+    fn charge_access(
+        &self,
+        data_store: &impl DataStore,
+        cost_strategy: &mut CostStrategy,
+        addr: AccountAddress,
+        ty: &Type,
+    ) -> PartialVMResult<()> {
+        let cost = if data_store.is_resource_loaded(addr, ty) {
+            self.access_costs.warm
+        } else {
+            self.access_costs.cold
+        };
+        cost_strategy.deduct_gas(GasUnits::new(cost))
+    }
+
     /// BorrowGlobal (mutable and not) opcode.
     fn borrow_global(
         &mut self,
         data_store: &mut impl DataStore,
+        cost_strategy: &mut CostStrategy,
         addr: AccountAddress,
         ty: &Type,
     ) -> PartialVMResult<AbstractMemorySize<GasCarrier>> {
+        self.charge_access(data_store, cost_strategy, addr, ty)?;
         let g = Self::load_resource(data_store, addr, ty, &self.log_context)?.borrow_global()?;
         let size = g.size();
         self.operand_stack.push(g)?;
@@ -386,9 +432,11 @@ impl<L: LogContext> Interpreter<L> {
     fn exists(
         &mut self,
         data_store: &mut impl DataStore,
+        cost_strategy: &mut CostStrategy,
         addr: AccountAddress,
         ty: &Type,
     ) -> PartialVMResult<AbstractMemorySize<GasCarrier>> {
+        self.charge_access(data_store, cost_strategy, addr, ty)?;
         let gv = Self::load_resource(data_store, addr, ty, &self.log_context)?;
         let mem_size = gv.size();
         let exists = gv.exists()?;
@@ -400,9 +448,11 @@ impl<L: LogContext> Interpreter<L> {
     fn move_from(
         &mut self,
         data_store: &mut impl DataStore,
+        cost_strategy: &mut CostStrategy,
         addr: AccountAddress,
         ty: &Type,
     ) -> PartialVMResult<AbstractMemorySize<GasCarrier>> {
+        self.charge_access(data_store, cost_strategy, addr, ty)?;
         let resource = Self::load_resource(data_store, addr, ty, &self.log_context)?.move_from()?;
         let size = resource.size();
         self.operand_stack.push(resource)?;
@@ -413,10 +463,12 @@ impl<L: LogContext> Interpreter<L> {
     fn move_to(
         &mut self,
         data_store: &mut impl DataStore,
+        cost_strategy: &mut CostStrategy,
         addr: AccountAddress,
         ty: &Type,
         resource: Value,
     ) -> PartialVMResult<AbstractMemorySize<GasCarrier>> {
+        self.charge_access(data_store, cost_strategy, addr, ty)?;
         let size = resource.size();
         Self::load_resource(data_store, addr, ty, &self.log_context)?.move_to(resource)?;
         Ok(size)
@@ -516,7 +568,7 @@ impl<L: LogContext> Interpreter<L> {
         loader: &Loader,
     ) -> PartialVMResult<()> {
         debug_writeln!(buf, "Call Stack:")?;
-        for (i, frame) in self.call_stack.0.iter().enumerate() {
+        for (i, frame) in self.call_stack.frames.iter().enumerate() {
             self.debug_print_frame(buf, loader, i, frame)?;
         }
         debug_writeln!(buf, "Operand Stack:")?;
@@ -539,7 +591,7 @@ impl<L: LogContext> Interpreter<L> {
     #[allow(dead_code)]
     fn get_internal_state(&self, current_frame: &Frame) -> String {
         let mut internal_state = "Call stack:\n".to_string();
-        for (i, frame) in self.call_stack.0.iter().enumerate() {
+        for (i, frame) in self.call_stack.frames.iter().enumerate() {
             internal_state.push_str(
                 format!(
                     " frame #{}: {} [pc = {}]\n",
@@ -553,7 +605,7 @@ impl<L: LogContext> Interpreter<L> {
         internal_state.push_str(
             format!(
                 "*frame #{}: {} [pc = {}]:\n",
-                self.call_stack.0.len(),
+                self.call_stack.frames.len(),
                 current_frame.function.pretty_string(),
                 current_frame.pc,
             )
@@ -584,7 +636,6 @@ impl<L: LogContext> Interpreter<L> {
 
 // TODO Determine stack size limits based on gas limit
 const OPERAND_STACK_SIZE_LIMIT: usize = 1024;
-const CALL_STACK_SIZE_LIMIT: usize = 1024;
 
 /// The operand stack.
 struct Stack(Vec<Value>);
@@ -636,18 +687,24 @@ impl Stack {
 
 /// A call stack.
 #[derive(Debug)]
-struct CallStack(Vec<Frame>);
+struct CallStack {
+    frames: Vec<Frame>,
+    max_depth: usize,
+}
 
 impl CallStack {
     /// Create a new empty call stack.
-    fn new() -> Self {
-        CallStack(vec![])
+    fn new(max_depth: usize) -> Self {
+        CallStack {
+            frames: vec![],
+            max_depth,
+        }
     }
 
     /// Push a `Frame` on the call stack.
     fn push(&mut self, frame: Frame) -> ::core::result::Result<(), Frame> {
-        if self.0.len() < CALL_STACK_SIZE_LIMIT {
-            self.0.push(frame);
+        if self.frames.len() < self.max_depth {
+            self.frames.push(frame);
             Ok(())
         } else {
             Err(frame)
@@ -656,11 +713,11 @@ impl CallStack {
 
     /// Pop a `Frame` off the call stack.
     fn pop(&mut self) -> Option<Frame> {
-        self.0.pop()
+        self.frames.pop()
     }
 
     fn current_location(&self) -> Location {
-        let location_opt = self.0.last().map(|frame| frame.location());
+        let location_opt = self.frames.last().map(|frame| frame.location());
         location_opt.unwrap_or(Location::Undefined)
     }
 }
@@ -1039,33 +1096,35 @@ impl Frame {
                     Bytecode::MutBorrowGlobal(sd_idx) | Bytecode::ImmBorrowGlobal(sd_idx) => {
                         let addr = interpreter.operand_stack.pop_as::<AccountAddress>()?;
                         let ty = resolver.get_struct_type(*sd_idx);
-                        let size = interpreter.borrow_global(data_store, addr, &ty)?;
+                        let size =
+                            interpreter.borrow_global(data_store, cost_strategy, addr, &ty)?;
                         cost_strategy.charge_instr_with_size(Opcodes::MUT_BORROW_GLOBAL, size)?;
                     }
                     Bytecode::MutBorrowGlobalGeneric(si_idx)
                     | Bytecode::ImmBorrowGlobalGeneric(si_idx) => {
                         let addr = interpreter.operand_stack.pop_as::<AccountAddress>()?;
                         let ty = resolver.instantiate_generic_type(*si_idx, self.ty_args())?;
-                        let size = interpreter.borrow_global(data_store, addr, &ty)?;
+                        let size =
+                            interpreter.borrow_global(data_store, cost_strategy, addr, &ty)?;
                         cost_strategy
                             .charge_instr_with_size(Opcodes::MUT_BORROW_GLOBAL_GENERIC, size)?;
                     }
                     Bytecode::Exists(sd_idx) => {
                         let addr = interpreter.operand_stack.pop_as::<AccountAddress>()?;
                         let ty = resolver.get_struct_type(*sd_idx);
-                        let size = interpreter.exists(data_store, addr, &ty)?;
+                        let size = interpreter.exists(data_store, cost_strategy, addr, &ty)?;
                         cost_strategy.charge_instr_with_size(Opcodes::EXISTS, size)?;
                     }
                     Bytecode::ExistsGeneric(si_idx) => {
                         let addr = interpreter.operand_stack.pop_as::<AccountAddress>()?;
                         let ty = resolver.instantiate_generic_type(*si_idx, self.ty_args())?;
-                        let size = interpreter.exists(data_store, addr, &ty)?;
+                        let size = interpreter.exists(data_store, cost_strategy, addr, &ty)?;
                         cost_strategy.charge_instr_with_size(Opcodes::EXISTS_GENERIC, size)?;
                     }
                     Bytecode::MoveFrom(sd_idx) => {
                         let addr = interpreter.operand_stack.pop_as::<AccountAddress>()?;
                         let ty = resolver.get_struct_type(*sd_idx);
-                        let size = interpreter.move_from(data_store, addr, &ty)?;
+                        let size = interpreter.move_from(data_store, cost_strategy, addr, &ty)?;
                         // TODO: Have this calculate before pulling in the data based upon
                         // the size of the data that we are about to read in.
                         cost_strategy.charge_instr_with_size(Opcodes::MOVE_FROM, size)?;
@@ -1073,7 +1132,7 @@ impl Frame {
                     Bytecode::MoveFromGeneric(si_idx) => {
                         let addr = interpreter.operand_stack.pop_as::<AccountAddress>()?;
                         let ty = resolver.instantiate_generic_type(*si_idx, self.ty_args())?;
-                        let size = interpreter.move_from(data_store, addr, &ty)?;
+                        let size = interpreter.move_from(data_store, cost_strategy, addr, &ty)?;
                         // TODO: Have this calculate before pulling in the data based upon
                         // the size of the data that we are about to read in.
                         cost_strategy.charge_instr_with_size(Opcodes::MOVE_FROM_GENERIC, size)?;
@@ -1088,7 +1147,8 @@ impl Frame {
                             .value_as::<AccountAddress>()?;
                         let ty = resolver.get_struct_type(*sd_idx);
                         // REVIEW: Can we simplify Interpreter::move_to?
-                        let size = interpreter.move_to(data_store, addr, &ty, resource)?;
+                        let size =
+                            interpreter.move_to(data_store, cost_strategy, addr, &ty, resource)?;
                         cost_strategy.charge_instr_with_size(Opcodes::MOVE_TO, size)?;
                     }
                     Bytecode::MoveToGeneric(si_idx) => {
@@ -1100,7 +1160,8 @@ impl Frame {
                             .read_ref()?
                             .value_as::<AccountAddress>()?;
                         let ty = resolver.instantiate_generic_type(*si_idx, self.ty_args())?;
-                        let size = interpreter.move_to(data_store, addr, &ty, resource)?;
+                        let size =
+                            interpreter.move_to(data_store, cost_strategy, addr, &ty, resource)?;
                         cost_strategy.charge_instr_with_size(Opcodes::MOVE_TO_GENERIC, size)?;
                     }
                     Bytecode::FreezeRef => {
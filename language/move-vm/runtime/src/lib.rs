@@ -20,6 +20,7 @@ mod loader;
 pub mod logging;
 pub mod move_vm;
 mod native_functions;
+pub mod native_registry;
 mod runtime;
 pub mod session;
 
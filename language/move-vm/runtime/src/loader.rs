@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{logging::LogContext, native_functions::NativeFunction};
+use crate::{
+    logging::LogContext, move_vm::CacheStats, native_functions::NativeFunction,
+    native_registry::NativeRegistry,
+};
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
 use alloc::string::{String, ToString};
@@ -12,14 +15,14 @@ use bytecode_verifier::{
     CodeUnitVerifier, DependencyChecker, DuplicationChecker, InstructionConsistency,
     RecursiveStructDefChecker, ResourceTransitiveChecker, SignatureChecker,
 };
-use core::cell::RefCell;
 use core::{fmt::Debug, hash::Hash};
 use diem_crypto::HashValue;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use mirai_annotations::assume;
 use move_core_types::{
+    account_address::AccountAddress,
     identifier::{IdentStr, Identifier},
-    language_storage::{ModuleId, StructTag, TypeTag},
+    language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS},
     value::{MoveKind, MoveKindInfo, MoveStructLayout, MoveTypeLayout},
     vm_status::{StatusCode, StatusType},
 };
@@ -27,6 +30,7 @@ use move_vm_types::{
     data_store::DataStore,
     loaded_data::runtime_types::{StructType, Type},
 };
+use spin::RwLock;
 use vm::{
     access::{ModuleAccess, ScriptAccess},
     errors::{verification_error, Location, PartialVMError, PartialVMResult, VMError, VMResult},
@@ -72,6 +76,26 @@ where
             .get(&key)
             .and_then(|idx| self.binaries.get(*idx))
     }
+
+    fn len(&self) -> usize {
+        self.binaries.len()
+    }
+
+    // Returns a cache with the binaries accepted by `keep`.
+    fn retain(&self, keep: impl Fn(&V) -> bool) -> Self
+    where
+        K: Clone,
+    {
+        let mut cache = Self::new();
+        for (key, idx) in &self.id_map {
+            let binary = &self.binaries[*idx];
+            if keep(binary) {
+                cache.binaries.push(Arc::clone(binary));
+                cache.id_map.insert(key.clone(), cache.binaries.len() - 1);
+            }
+        }
+        cache
+    }
 }
 
 // A script cache is a map from the hash value of a script and the `Script` itself.
@@ -108,14 +132,18 @@ pub struct ModuleCache {
     modules: BinaryCache<ModuleId, Module>,
     structs: Vec<Arc<StructType>>,
     functions: Vec<Arc<Function>>,
+    core_address: AccountAddress,
+    natives: NativeRegistry,
 }
 
 impl ModuleCache {
-    fn new() -> Self {
+    fn new(core_address: AccountAddress, natives: NativeRegistry) -> Self {
         Self {
             modules: BinaryCache::new(),
             structs: vec![],
             functions: vec![],
+            core_address,
+            natives,
         }
     }
 
@@ -189,7 +217,7 @@ impl ModuleCache {
             })?;
         for (idx, func) in module.function_defs().iter().enumerate() {
             let findex = FunctionDefinitionIndex(idx as TableIndex);
-            let function = Function::new(findex, func, module);
+            let function = Function::new(findex, func, module, &self.core_address, &self.natives);
             self.functions.push(Arc::new(function));
         }
         Ok(())
@@ -408,30 +436,268 @@ impl ModuleCache {
 // Loader
 //
 
+/// Scripts, modules and types loaded by one generation of the cache.
+struct CacheGeneration {
+    generation: u64,
+    scripts: RwLock<ScriptCache>,
+    module_cache: RwLock<ModuleCache>,
+    type_cache: RwLock<TypeCache>,
+}
+
+impl CacheGeneration {
+    fn new(generation: u64, core_address: AccountAddress, natives: NativeRegistry) -> Self {
+        CacheGeneration {
+            generation,
+            scripts: RwLock::new(ScriptCache::new()),
+            module_cache: RwLock::new(ModuleCache::new(core_address, natives)),
+            type_cache: RwLock::new(TypeCache::new()),
+        }
+    }
+
+    // Returns the next generation with the entities of this one except the `ids` modules,
+    // the modules depending on them and the scripts using any of them.
+    // Types and functions keep their global indices, the evicted ones are left unreferenced.
+    fn evict(&self, ids: &[ModuleId]) -> Self {
+        let module_cache = self.module_cache.read();
+        let mut evicted = ids.iter().cloned().collect::<HashSet<_>>();
+        loop {
+            let count = evicted.len();
+            for module in &module_cache.modules.binaries {
+                if !evicted.contains(&module.id)
+                    && module.dependencies().any(|dep| evicted.contains(&dep))
+                {
+                    evicted.insert(module.id.clone());
+                }
+            }
+            if evicted.len() == count {
+                break;
+            }
+        }
+
+        let scripts = self
+            .scripts
+            .read()
+            .scripts
+            .retain(|script| !script.dependencies().any(|dep| evicted.contains(&dep)));
+        let modules = module_cache
+            .modules
+            .retain(|module| !evicted.contains(&module.id));
+        CacheGeneration {
+            generation: self.generation + 1,
+            scripts: RwLock::new(ScriptCache { scripts }),
+            module_cache: RwLock::new(ModuleCache {
+                modules,
+                structs: module_cache.structs.clone(),
+                functions: module_cache.functions.clone(),
+                core_address: module_cache.core_address,
+                natives: module_cache.natives.clone(),
+            }),
+            type_cache: RwLock::new(TypeCache::new()),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.scripts.read().scripts.len() == 0 && self.module_cache.read().modules.len() == 0
+    }
+}
+
+/// Loader cache of the scripts, modules and types.
+///
+/// The cache may be shared by several vm instances, see `MoveVM::with_shared_cache`.
+/// The instances sharing the cache must use the same core address and register the same natives.
+/// Host natives are dispatched to the natives registered by the calling instance.
+///
+/// A session loads from the generation of the cache current at its creation.
+/// Clearing the cache starts a new generation, the sessions in flight keep the previous one.
+pub struct SharedCache {
+    current: RwLock<Arc<CacheGeneration>>,
+}
+
+impl SharedCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        SharedCache {
+            current: RwLock::new(Arc::new(CacheGeneration::new(
+                0,
+                CORE_CODE_ADDRESS,
+                NativeRegistry::default(),
+            ))),
+        }
+    }
+
+    /// Returns the number of the current generation of the cache.
+    pub fn generation(&self) -> u64 {
+        self.current.read().generation
+    }
+
+    fn snapshot(&self) -> Arc<CacheGeneration> {
+        self.current.read().clone()
+    }
+
+    fn clear(&self, core_address: AccountAddress, natives: NativeRegistry) {
+        let mut current = self.current.write();
+        let generation = current.generation + 1;
+        *current = Arc::new(CacheGeneration::new(generation, core_address, natives));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.current.read().is_empty()
+    }
+
+    /// Returns `true` if the module is loaded into the current generation of the cache.
+    pub fn is_module_cached(&self, id: &ModuleId) -> bool {
+        self.snapshot().module_cache.read().module_at(id).is_some()
+    }
+
+    /// Evicts the modules, the modules depending on them and the scripts using them by starting
+    /// a new generation with the rest of the current one.
+    /// Returns `false` and keeps the generation if none of the modules is cached.
+    pub fn evict_modules(&self, ids: &[ModuleId]) -> bool {
+        let mut current = self.current.write();
+        let cached = {
+            let module_cache = current.module_cache.read();
+            ids.iter().any(|id| module_cache.module_at(id).is_some())
+        };
+        if cached {
+            *current = Arc::new(current.evict(ids));
+        }
+        cached
+    }
+}
+
+impl Default for SharedCache {
+    fn default() -> Self {
+        SharedCache::new()
+    }
+}
+
 // A Loader is responsible to load scripts and modules and holds the cache of all loaded
-// entities. Each cache is protected by a `Mutex`. Operation in the Loader must be thread safe
-// (operating on values on the stack) and when cache needs updating the mutex must be taken.
+// entities. Each cache is protected by a `RwLock`. Operation in the Loader must be thread safe
+// (operating on values on the stack) and when cache needs updating the lock must be taken.
 // The `pub(crate)` API is what a Loader offers to the runtime.
+// Entities are loaded into the pinned generation `cache` of the `shared_cache`,
+// see `Loader::snapshot`.
+#[derive(Clone)]
 pub(crate) struct Loader {
-    scripts: RefCell<ScriptCache>,
-    module_cache: RefCell<ModuleCache>,
-    type_cache: RefCell<TypeCache>,
+    shared_cache: Arc<SharedCache>,
+    cache: Arc<CacheGeneration>,
+    shared: bool,
+    core_address: AccountAddress,
+    natives: NativeRegistry,
 }
 
 impl Loader {
     pub(crate) fn new() -> Self {
+        let shared_cache = Arc::new(SharedCache::new());
         Self {
-            scripts: RefCell::new(ScriptCache::new()),
-            module_cache: RefCell::new(ModuleCache::new()),
-            type_cache: RefCell::new(TypeCache::new()),
+            cache: shared_cache.snapshot(),
+            shared_cache,
+            shared: false,
+            core_address: CORE_CODE_ADDRESS,
+            natives: NativeRegistry::default(),
+        }
+    }
+
+    /// Sets the natives registered by the host and clears the cache.
+    pub(crate) fn set_natives(&mut self, natives: NativeRegistry) {
+        self.natives = natives;
+        self.clear();
+    }
+
+    /// Sets the address of the standard library natives and clears the cache.
+    pub(crate) fn set_core_address(&mut self, core_address: AccountAddress) {
+        self.core_address = core_address;
+        self.clear();
+    }
+
+    /// Replaces the cache of the loader with the shared one.
+    /// An empty shared cache is reset with the natives of the loader.
+    pub(crate) fn set_shared_cache(&mut self, cache: Arc<SharedCache>) {
+        if cache.is_empty() {
+            cache.clear(self.core_address, self.natives.clone());
+        }
+        self.cache = cache.snapshot();
+        self.shared_cache = cache;
+        self.shared = true;
+    }
+
+    /// Returns a loader pinned to the current generation of the cache.
+    pub(crate) fn snapshot(&self) -> Loader {
+        Loader {
+            cache: self.shared_cache.snapshot(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a loader with the natives of this loader pinned to the current generation
+    /// of the `cache`. An empty cache is reset with the natives of the loader.
+    pub(crate) fn snapshot_of(&self, cache: &Arc<SharedCache>) -> Loader {
+        if cache.is_empty() {
+            self.clear_cache(cache);
+        }
+        Loader {
+            shared_cache: cache.clone(),
+            cache: cache.snapshot(),
+            shared: true,
+            core_address: self.core_address,
+            natives: self.natives.clone(),
         }
     }
 
-    /// Clears loader cache.
+    /// Returns the address of the standard library natives.
+    pub(crate) fn core_address(&self) -> AccountAddress {
+        self.core_address
+    }
+
+    /// Clears loader cache by starting a new generation.
     pub(crate) fn clear(&self) {
-        *self.scripts.borrow_mut() = ScriptCache::new();
-        *self.module_cache.borrow_mut() = ModuleCache::new();
-        *self.type_cache.borrow_mut() = TypeCache::new();
+        self.clear_cache(&self.shared_cache);
+    }
+
+    /// Clears the `cache` by starting a new generation with the natives of the loader.
+    pub(crate) fn clear_cache(&self, cache: &SharedCache) {
+        cache.clear(self.core_address, self.natives.clone());
+    }
+
+    /// Returns `true` if the module is loaded into the current generation of the cache.
+    pub(crate) fn is_module_cached(&self, id: &ModuleId) -> bool {
+        self.shared_cache.is_module_cached(id)
+    }
+
+    /// Evicts the modules and their dependents from the cache, see `SharedCache::evict_modules`.
+    pub(crate) fn evict_modules(&self, ids: &[ModuleId]) -> bool {
+        self.shared_cache.evict_modules(ids)
+    }
+
+    /// Returns statistics of the current generation of the cache.
+    pub(crate) fn cache_stats(&self) -> CacheStats {
+        let cache = self.shared_cache.snapshot();
+        let stats = CacheStats {
+            generation: cache.generation,
+            scripts: cache.scripts.read().scripts.len(),
+            modules: cache.module_cache.read().modules.len(),
+            type_layouts: cache.type_cache.read().layouts.len(),
+        };
+        stats
+    }
+
+    /// Returns the native implementation of the function.
+    /// Host natives of a shared cache are resolved with the natives of this loader.
+    pub(crate) fn native_function(&self, function: &Function) -> PartialVMResult<NativeFunction> {
+        match function.get_native()? {
+            NativeFunction::Host(native) if self.shared => function
+                .module_id()
+                .and_then(|id| {
+                    self.natives
+                        .resolve(id.address(), id.name().as_str(), function.name())
+                })
+                .map(NativeFunction::Host)
+                .ok_or_else(|| {
+                    PartialVMError::new(StatusCode::UNREACHABLE)
+                        .with_message(format!("Missing host native {}", native.name()))
+                }),
+            native => Ok(native),
+        }
     }
 
     //
@@ -455,15 +721,16 @@ impl Loader {
     ) -> VMResult<(Arc<Function>, Vec<Type>)> {
         // retrieve or load the script
         let hash_value = HashValue::sha3_256_of(script_blob);
-        let opt_main = self.scripts.borrow().get(&hash_value);
+        let opt_main = self.cache.scripts.read().get(&hash_value);
         let main = match opt_main {
             Some(main) => main,
             None => {
                 let ver_script =
                     self.deserialize_and_verify_script(script_blob, data_store, log_context)?;
-                let script = Script::new(ver_script, &hash_value, &self.module_cache.borrow())?;
-                self.scripts
-                    .borrow_mut()
+                let script = Script::new(ver_script, &hash_value, &self.cache.module_cache.read())?;
+                self.cache
+                    .scripts
+                    .write()
                     .insert(hash_value, script)
                     .map_err(|e| e.finish(Location::Script))?
             }
@@ -560,13 +827,14 @@ impl Loader {
     ) -> VMResult<(Arc<Function>, Vec<Type>)> {
         self.load_module_expect_no_missing_dependencies(module_id, data_store, log_context)?;
         let idx = self
+            .cache
             .module_cache
-            .borrow()
+            .read()
             .resolve_function_by_name(function_name, module_id)
             .map_err(|err| {
                 expect_no_verification_errors(err.finish(Location::Undefined), log_context)
             })?;
-        let func = self.module_cache.borrow().function_at(idx);
+        let func = self.cache.module_cache.read().function_at(idx);
 
         // verify type arguments
         let mut type_params = vec![];
@@ -617,7 +885,7 @@ impl Loader {
         RecursiveStructDefChecker::verify_module(&module)?;
         InstantiationLoopChecker::verify_module(&module)?;
         CodeUnitVerifier::verify_module(&module)?;
-        Self::check_natives(&module)?;
+        Self::check_natives(&module, &self.core_address, &self.natives)?;
 
         let deps = module_dependencies(&module);
         let loaded_deps = if verify_no_missing_modules {
@@ -642,8 +910,16 @@ impl Loader {
     }
 
     // All native functions must be known to the loader
-    fn check_natives(module: &CompiledModule) -> VMResult<()> {
-        fn check_natives_impl(module: &CompiledModule) -> PartialVMResult<()> {
+    fn check_natives(
+        module: &CompiledModule,
+        core_address: &AccountAddress,
+        natives: &NativeRegistry,
+    ) -> VMResult<()> {
+        fn check_natives_impl(
+            module: &CompiledModule,
+            core_address: &AccountAddress,
+            natives: &NativeRegistry,
+        ) -> PartialVMResult<()> {
             for (idx, native_function) in module
                 .function_defs()
                 .iter()
@@ -653,6 +929,8 @@ impl Loader {
                 let fh = module.function_handle_at(native_function.function);
                 let mh = module.module_handle_at(fh.module);
                 NativeFunction::resolve(
+                    core_address,
+                    natives,
                     module.address_identifier_at(mh.address),
                     module.identifier_at(mh.name).as_str(),
                     module.identifier_at(fh.name).as_str(),
@@ -678,7 +956,8 @@ impl Loader {
             }
             Ok(())
         }
-        check_natives_impl(module).map_err(|e| e.finish(Location::Module(module.self_id())))
+        check_natives_impl(module, core_address, natives)
+            .map_err(|e| e.finish(Location::Module(module.self_id())))
     }
 
     //
@@ -709,8 +988,9 @@ impl Loader {
                     log_context,
                 )?;
                 let (idx, struct_type) = self
+                    .cache
                     .module_cache
-                    .borrow()
+                    .read()
                     // GOOD module was loaded above
                     .resolve_struct_by_name(&struct_tag.name, &module_id)
                     .map_err(|e| e.finish(Location::Undefined))?;
@@ -769,7 +1049,7 @@ impl Loader {
             Ok(module)
         }
 
-        if let Some(module) = self.module_cache.borrow().module_at(id) {
+        if let Some(module) = self.cache.module_cache.read().module_at(id) {
             return Ok(module);
         }
 
@@ -784,8 +1064,9 @@ impl Loader {
 
         let module = deserialize_and_verify_module(self, bytes, data_store, log_context)
             .map_err(|err| expect_no_verification_errors(err, log_context))?;
-        self.module_cache
-            .borrow_mut()
+        self.cache
+            .module_cache
+            .write()
             .insert(id.clone(), module, log_context)
     }
 
@@ -864,17 +1145,18 @@ impl Loader {
     //
 
     fn function_at(&self, idx: usize) -> Arc<Function> {
-        self.module_cache.borrow().function_at(idx)
+        self.cache.module_cache.read().function_at(idx)
     }
 
     fn struct_at(&self, idx: usize) -> Arc<StructType> {
-        self.module_cache.borrow().struct_at(idx)
+        self.cache.module_cache.read().struct_at(idx)
     }
 
     fn get_module(&self, idx: &ModuleId) -> Arc<Module> {
         Arc::clone(
-            self.module_cache
-                .borrow()
+            self.cache
+                .module_cache
+                .read()
                 .modules
                 .get(idx)
                 .expect("ModuleId on Function must exist"),
@@ -883,8 +1165,9 @@ impl Loader {
 
     fn get_script(&self, hash: &HashValue) -> Arc<Script> {
         Arc::clone(
-            self.scripts
-                .borrow()
+            self.cache
+                .scripts
+                .read()
                 .scripts
                 .get(hash)
                 .expect("Script hash on Function must exist"),
@@ -893,9 +1176,9 @@ impl Loader {
 
     fn is_resource(&self, type_: &Type) -> bool {
         match type_ {
-            Type::Struct(idx) => self.module_cache.borrow().struct_at(*idx).is_resource,
+            Type::Struct(idx) => self.cache.module_cache.read().struct_at(*idx).is_resource,
             Type::StructInstantiation(idx, instantiation) => {
-                if self.module_cache.borrow().struct_at(*idx).is_resource {
+                if self.cache.module_cache.read().struct_at(*idx).is_resource {
                     true
                 } else {
                     for ty in instantiation {
@@ -1156,6 +1439,16 @@ pub(crate) struct Module {
 }
 
 impl Module {
+    // Ids of the modules used by the module, including the module itself.
+    fn dependencies(&self) -> impl Iterator<Item = ModuleId> + '_ {
+        self.module.module_handles().iter().map(move |handle| {
+            ModuleId::new(
+                *self.module.address_identifier_at(handle.address),
+                self.module.identifier_at(handle.name).to_owned(),
+            )
+        })
+    }
+
     fn new(
         module: CompiledModule,
         cache: &ModuleCache,
@@ -1440,6 +1733,8 @@ impl Script {
             native,
             scope,
             name,
+            is_public: true,
+            is_entry: true,
         });
 
         Ok(Self {
@@ -1455,6 +1750,16 @@ impl Script {
         self.main.clone()
     }
 
+    // Ids of the modules used by the script.
+    fn dependencies(&self) -> impl Iterator<Item = ModuleId> + '_ {
+        self.script.module_handles().iter().map(move |handle| {
+            ModuleId::new(
+                *self.script.address_identifier_at(handle.address),
+                self.script.identifier_at(handle.name).to_owned(),
+            )
+        })
+    }
+
     fn function_at(&self, idx: u16) -> usize {
         self.function_refs[idx as usize]
     }
@@ -1483,6 +1788,8 @@ pub(crate) struct Function {
     native: Option<NativeFunction>,
     scope: Scope,
     name: Identifier,
+    is_public: bool,
+    is_entry: bool,
 }
 
 impl Function {
@@ -1490,12 +1797,16 @@ impl Function {
         index: FunctionDefinitionIndex,
         def: &FunctionDefinition,
         module: &CompiledModule,
+        core_address: &AccountAddress,
+        natives: &NativeRegistry,
     ) -> Self {
         let handle = module.function_handle_at(def.function);
         let name = module.identifier_at(handle.name).to_owned();
         let module_id = module.self_id();
         let native = if def.is_native() {
             NativeFunction::resolve(
+                core_address,
+                natives,
                 module_id.address(),
                 module_id.name().as_str(),
                 name.as_str(),
@@ -1522,6 +1833,8 @@ impl Function {
         };
         let return_ = module.signature_at(handle.return_).clone();
         let type_parameters = handle.type_parameters.clone();
+        // Entry functions are public, return nothing and only take arguments a transaction can supply.
+        let is_entry = def.is_public && return_.is_empty() && parameters.is_entry_parameters();
         Self {
             index,
             code,
@@ -1532,6 +1845,8 @@ impl Function {
             native,
             scope,
             name,
+            is_public: def.is_public,
+            is_entry,
         }
     }
 
@@ -1583,6 +1898,10 @@ impl Function {
         &self.parameters
     }
 
+    pub(crate) fn return_count(&self) -> usize {
+        self.return_.len()
+    }
+
     pub(crate) fn pretty_string(&self) -> String {
         match &self.scope {
             Scope::Script(_) => "Script::main".into(),
@@ -1599,8 +1918,17 @@ impl Function {
         self.native.is_some()
     }
 
+    pub(crate) fn is_public(&self) -> bool {
+        self.is_public
+    }
+
+    // Entry functions and script entries are the entry points of the transactions.
+    pub(crate) fn is_entry(&self) -> bool {
+        self.is_entry
+    }
+
     pub(crate) fn get_native(&self) -> PartialVMResult<NativeFunction> {
-        self.native.ok_or_else(|| {
+        self.native.clone().ok_or_else(|| {
             PartialVMError::new(StatusCode::UNREACHABLE)
                 .with_message("Missing Native Function".to_string())
         })
@@ -1718,7 +2046,8 @@ fn expect_no_verification_errors(err: VMError, log_context: &impl LogContext) ->
 //
 
 struct StructInfo {
-    struct_tag: Option<StructTag>,
+    // Shared by the effects of all the transactions touching the struct.
+    struct_tag: Option<Arc<StructTag>>,
     struct_layout: Option<MoveStructLayout>,
     kind_info: Option<(MoveKind, Vec<MoveKindInfo>)>,
 }
@@ -1735,12 +2064,15 @@ impl StructInfo {
 
 pub(crate) struct TypeCache {
     structs: HashMap<usize, HashMap<Vec<Type>, StructInfo>>,
+    // Layouts of the types used by the data cache for resource and event (de)serialization.
+    layouts: HashMap<Type, MoveTypeLayout>,
 }
 
 impl TypeCache {
     fn new() -> Self {
         Self {
             structs: HashMap::new(),
+            layouts: HashMap::new(),
         }
     }
 }
@@ -1748,11 +2080,15 @@ impl TypeCache {
 const VALUE_DEPTH_MAX: usize = 256;
 
 impl Loader {
-    fn struct_gidx_to_type_tag(&self, gidx: usize, ty_args: &[Type]) -> PartialVMResult<StructTag> {
-        if let Some(struct_map) = self.type_cache.borrow().structs.get(&gidx) {
+    fn struct_gidx_to_struct_tag(
+        &self,
+        gidx: usize,
+        ty_args: &[Type],
+    ) -> PartialVMResult<Arc<StructTag>> {
+        if let Some(struct_map) = self.cache.type_cache.read().structs.get(&gidx) {
             if let Some(struct_info) = struct_map.get(ty_args) {
                 if let Some(struct_tag) = &struct_info.struct_tag {
-                    return Ok(struct_tag.clone());
+                    return Ok(Arc::clone(struct_tag));
                 }
             }
         }
@@ -1761,22 +2097,23 @@ impl Loader {
             .iter()
             .map(|ty| self.type_to_type_tag(ty))
             .collect::<PartialVMResult<Vec<_>>>()?;
-        let struct_type = self.module_cache.borrow().struct_at(gidx);
-        let struct_tag = StructTag {
+        let struct_type = self.cache.module_cache.read().struct_at(gidx);
+        let struct_tag = Arc::new(StructTag {
             address: *struct_type.module.address(),
             module: struct_type.module.name().to_owned(),
             name: struct_type.name.clone(),
             type_params: ty_arg_tags,
-        };
+        });
 
-        self.type_cache
-            .borrow_mut()
+        self.cache
+            .type_cache
+            .write()
             .structs
             .entry(gidx)
             .or_insert_with(HashMap::new)
             .entry(ty_args.to_vec())
             .or_insert_with(StructInfo::new)
-            .struct_tag = Some(struct_tag.clone());
+            .struct_tag = Some(Arc::clone(&struct_tag));
 
         Ok(struct_tag)
     }
@@ -1790,10 +2127,14 @@ impl Loader {
             Type::Address => TypeTag::Address,
             Type::Signer => TypeTag::Signer,
             Type::Vector(ty) => TypeTag::Vector(Box::new(self.type_to_type_tag(ty)?)),
-            Type::Struct(gidx) => TypeTag::Struct(self.struct_gidx_to_type_tag(*gidx, &[])?),
-            Type::StructInstantiation(gidx, ty_args) => {
-                TypeTag::Struct(self.struct_gidx_to_type_tag(*gidx, ty_args)?)
+            Type::Struct(gidx) => {
+                TypeTag::Struct(self.struct_gidx_to_struct_tag(*gidx, &[])?.as_ref().clone())
             }
+            Type::StructInstantiation(gidx, ty_args) => TypeTag::Struct(
+                self.struct_gidx_to_struct_tag(*gidx, ty_args)?
+                    .as_ref()
+                    .clone(),
+            ),
             Type::Reference(_) | Type::MutableReference(_) | Type::TyParam(_) => {
                 return Err(
                     PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
@@ -1809,7 +2150,7 @@ impl Loader {
         ty_args: &[Type],
         depth: usize,
     ) -> PartialVMResult<MoveStructLayout> {
-        if let Some(struct_map) = self.type_cache.borrow().structs.get(&gidx) {
+        if let Some(struct_map) = self.cache.type_cache.read().structs.get(&gidx) {
             if let Some(struct_info) = struct_map.get(ty_args) {
                 if let Some(layout) = &struct_info.struct_layout {
                     return Ok(layout.clone());
@@ -1817,7 +2158,7 @@ impl Loader {
             }
         }
 
-        let struct_type = self.module_cache.borrow().struct_at(gidx);
+        let struct_type = self.cache.module_cache.read().struct_at(gidx);
         let field_tys = struct_type
             .fields
             .iter()
@@ -1829,8 +2170,9 @@ impl Loader {
             .collect::<PartialVMResult<Vec<_>>>()?;
         let struct_layout = MoveStructLayout::new(field_layouts);
 
-        self.type_cache
-            .borrow_mut()
+        self.cache
+            .type_cache
+            .write()
             .structs
             .entry(gidx)
             .or_insert_with(HashMap::new)
@@ -1876,7 +2218,7 @@ impl Loader {
         ty_args: &[Type],
         depth: usize,
     ) -> PartialVMResult<(MoveKind, Vec<MoveKindInfo>)> {
-        if let Some(struct_map) = self.type_cache.borrow().structs.get(&gidx) {
+        if let Some(struct_map) = self.cache.type_cache.read().structs.get(&gidx) {
             if let Some(struct_info) = struct_map.get(ty_args) {
                 if let Some(kind_info) = &struct_info.kind_info {
                     return Ok(kind_info.clone());
@@ -1884,7 +2226,7 @@ impl Loader {
             }
         }
 
-        let struct_type = self.module_cache.borrow().struct_at(gidx);
+        let struct_type = self.cache.module_cache.read().struct_at(gidx);
 
         let mut is_resource = struct_type.is_resource;
         if !is_resource {
@@ -1905,8 +2247,9 @@ impl Loader {
             .collect::<PartialVMResult<Vec<_>>>()?;
         let kind_info = (MoveKind::from_bool(is_resource), field_kind_info);
 
-        self.type_cache
-            .borrow_mut()
+        self.cache
+            .type_cache
+            .write()
             .structs
             .entry(gidx)
             .or_insert_with(HashMap::new)
@@ -1956,8 +2299,50 @@ impl Loader {
     pub(crate) fn type_to_type_tag(&self, ty: &Type) -> PartialVMResult<TypeTag> {
         self.type_to_type_tag_impl(ty)
     }
+
+    /// Returns the layouts of the values returned by the module function
+    /// instantiated with the type arguments.
+    pub(crate) fn return_layouts(
+        &self,
+        func: &Function,
+        ty_args: &[Type],
+    ) -> PartialVMResult<Vec<MoveTypeLayout>> {
+        let module_id = func.module_id().ok_or_else(|| {
+            PartialVMError::new(StatusCode::UNREACHABLE)
+                .with_message("Scripts don't return values".to_string())
+        })?;
+        let module = self.get_module(module_id);
+        func.return_
+            .0
+            .iter()
+            .map(|tok| {
+                let ty = self.make_type(module.module(), tok)?.subst(ty_args)?;
+                self.type_to_type_layout(&ty)
+            })
+            .collect()
+    }
+    /// Returns the struct tag shared with the type cache.
+    pub(crate) fn type_to_struct_tag(&self, ty: &Type) -> PartialVMResult<Arc<StructTag>> {
+        match ty {
+            Type::Struct(gidx) => self.struct_gidx_to_struct_tag(*gidx, &[]),
+            Type::StructInstantiation(gidx, ty_args) => {
+                self.struct_gidx_to_struct_tag(*gidx, ty_args)
+            }
+            _ => Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)),
+        }
+    }
     pub(crate) fn type_to_type_layout(&self, ty: &Type) -> PartialVMResult<MoveTypeLayout> {
-        self.type_to_type_layout_impl(ty, 1)
+        if let Some(layout) = self.cache.type_cache.read().layouts.get(ty) {
+            return Ok(layout.clone());
+        }
+
+        let layout = self.type_to_type_layout_impl(ty, 1)?;
+        self.cache
+            .type_cache
+            .write()
+            .layouts
+            .insert(ty.clone(), layout.clone());
+        Ok(layout)
     }
     pub(crate) fn type_to_kind_info(&self, ty: &Type) -> PartialVMResult<MoveKindInfo> {
         self.type_to_kind_info_impl(ty, 1)
@@ -1,20 +1,188 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{data_cache::RemoteCache, runtime::VMRuntime, session::Session};
+use crate::{
+    data_cache::RemoteCache, native_registry::NativeRegistry, runtime::VMRuntime, session::Session,
+};
+use alloc::sync::Arc;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::ModuleId;
 use move_vm_types::natives::balance::NativeBalance;
 
+pub use crate::loader::SharedCache;
+
+/// Loader cache statistics.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of the cache generation, incremented by every clear and eviction.
+    pub generation: u64,
+    /// Number of cached scripts.
+    pub scripts: usize,
+    /// Number of cached modules.
+    pub modules: usize,
+    /// Number of cached type layouts.
+    pub type_layouts: usize,
+}
+
+/// Execution limits enforced by the interpreter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VMLimits {
+    /// Maximum depth of the call stack.
+    /// Exceeding the limit fails with `CALL_STACK_OVERFLOW`.
+    pub max_call_depth: usize,
+    /// Maximum depth of the type arguments of a generic call.
+    /// Exceeding the limit fails with `VM_MAX_TYPE_DEPTH_REACHED`.
+    pub max_type_depth: usize,
+    /// Maximum size of the published module in bytes.
+    pub max_module_size: usize,
+    /// Maximum number of functions defined by the published module.
+    pub max_functions: usize,
+    /// Maximum number of structs defined by the published module.
+    pub max_structs: usize,
+    /// Maximum length of an identifier of the published module.
+    pub max_identifier_length: usize,
+    /// Maximum number of events emitted by a transaction.
+    /// Exceeding the limit fails with `EVENT_LIMIT_EXCEEDED`.
+    pub max_events: usize,
+    /// Maximum total size of the events emitted by a transaction in bytes.
+    /// Exceeding the limit fails with `EVENT_LIMIT_EXCEEDED`.
+    pub max_event_bytes: usize,
+}
+
+impl Default for VMLimits {
+    fn default() -> Self {
+        VMLimits {
+            max_call_depth: 1024,
+            max_type_depth: 256,
+            max_module_size: 64 * 1024,
+            max_functions: 1024,
+            max_structs: 1024,
+            max_identifier_length: 255,
+            max_events: 1024,
+            max_event_bytes: 64 * 1024,
+        }
+    }
+}
+
+/// Gas charged for the bytecode verification of a published module.
+/// Costs are in internal gas units and are charged per function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationCosts {
+    /// Cost of a basic block of the control flow graph.
+    pub per_basic_block: u64,
+    /// Cost of an edge of the control flow graph.
+    pub per_edge: u64,
+    /// Cost of a local of the function.
+    pub per_local: u64,
+}
+
+impl Default for VerificationCosts {
+    fn default() -> Self {
+        VerificationCosts {
+            per_basic_block: 100,
+            per_edge: 50,
+            per_local: 20,
+        }
+    }
+}
+
+/// Gas charged for a global storage operation on a resource in addition to the instruction cost.
+/// The first access to a resource in a transaction is cold, the following accesses are warm.
+/// Costs are in internal gas units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageAccessCosts {
+    /// Cost of the first access.
+    pub cold: u64,
+    /// Cost of a repeated access.
+    pub warm: u64,
+}
+
+impl Default for StorageAccessCosts {
+    fn default() -> Self {
+        StorageAccessCosts {
+            cold: 1000,
+            warm: 100,
+        }
+    }
+}
+
 pub struct MoveVM {
     runtime: VMRuntime,
 }
 
 impl MoveVM {
     pub fn new() -> Self {
+        MoveVM::new_with_limits(VMLimits::default())
+    }
+
+    /// Creates a vm with the given execution limits.
+    pub fn new_with_limits(limits: VMLimits) -> Self {
         Self {
-            runtime: VMRuntime::new(),
+            runtime: VMRuntime::new(limits),
         }
     }
 
+    /// Sets the execution limits.
+    pub fn with_limits(mut self, limits: VMLimits) -> Self {
+        self.runtime.set_limits(limits);
+        self
+    }
+
+    /// Sets gas costs of the module verification.
+    pub fn with_verification_costs(mut self, costs: VerificationCosts) -> Self {
+        self.runtime.set_verification_costs(costs);
+        self
+    }
+
+    /// Sets gas costs of the cold and warm storage accesses.
+    pub fn with_access_costs(mut self, costs: StorageAccessCosts) -> Self {
+        self.runtime.set_access_costs(costs);
+        self
+    }
+
+    /// Sets the address the standard library natives are resolved at.
+    /// Defaults to `CORE_CODE_ADDRESS`.
+    pub fn with_core_address(mut self, core_address: AccountAddress) -> Self {
+        self.runtime.set_core_address(core_address);
+        self
+    }
+
+    /// Sets the natives registered by the host.
+    /// Registered natives take precedence over the built-in ones.
+    pub fn with_natives(mut self, natives: NativeRegistry) -> Self {
+        self.runtime.set_natives(natives);
+        self
+    }
+
+    /// Replaces the loader cache with the cache shared by several vm instances.
+    ///
+    /// Sets the natives and the core address before sharing the cache,
+    /// changing them afterwards clears the shared cache.
+    pub fn with_shared_cache(mut self, cache: Arc<SharedCache>) -> Self {
+        self.runtime.set_shared_cache(cache);
+        self
+    }
+
+    /// Returns the address the standard library natives are resolved at.
+    pub fn core_address(&self) -> AccountAddress {
+        self.runtime.core_address()
+    }
+
+    /// Returns execution limits.
+    pub fn limits(&self) -> VMLimits {
+        self.runtime.limits()
+    }
+
+    /// Returns gas costs of the module verification.
+    pub fn verification_costs(&self) -> VerificationCosts {
+        self.runtime.verification_costs()
+    }
+
+    /// Returns gas costs of the storage accesses.
+    pub fn access_costs(&self) -> StorageAccessCosts {
+        self.runtime.access_costs()
+    }
+
     /// Create a new Session backed by the given storage.
     ///
     /// Right now it is the caller's responsibility to ensure cache coherence of the Move VM Loader
@@ -37,10 +205,45 @@ impl MoveVM {
         self.runtime.new_session(remote, balance)
     }
 
+    /// Creates a new Session loading the modules into the `cache` instead of the vm cache,
+    /// e.g. to keep the modules of independent storage spaces apart.
+    /// The cache must be used only by the vms with the same core address and natives.
+    /// See `new_session` for the cache coherence.
+    pub fn new_session_with_cache<'r, R: RemoteCache, B: NativeBalance>(
+        &self,
+        cache: &Arc<SharedCache>,
+        remote: &'r R,
+        balance: B,
+    ) -> Session<'r, '_, R, B> {
+        self.runtime.new_session_with_cache(cache, remote, balance)
+    }
+
     /// Clears vm state.
     pub fn clear(&self) {
         self.runtime.clear();
     }
+
+    /// Clears the `cache` used with `new_session_with_cache`.
+    pub fn clear_cache(&self, cache: &SharedCache) {
+        self.runtime.clear_cache(cache);
+    }
+
+    /// Returns `true` if the module is loaded into the cache.
+    /// A cached module is not reloaded from the storage until it is evicted or the cache is cleared.
+    pub fn is_module_cached(&self, id: &ModuleId) -> bool {
+        self.runtime.is_module_cached(id)
+    }
+
+    /// Evicts the modules, the modules depending on them and the scripts using them from
+    /// the cache, see `SharedCache::evict_modules`. Returns `false` if none of the modules is cached.
+    pub fn evict_modules(&self, ids: &[ModuleId]) -> bool {
+        self.runtime.evict_modules(ids)
+    }
+
+    /// Returns loader cache statistics.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.runtime.cache_stats()
+    }
 }
 
 impl Default for MoveVM {
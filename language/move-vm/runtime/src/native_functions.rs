@@ -5,13 +5,16 @@ use alloc::collections::VecDeque;
 use alloc::string::String;
 use alloc::vec::Vec;
 
-use move_core_types::language_storage::{ModuleId, TypeTag};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
 use move_core_types::{
-    account_address::AccountAddress, gas_schedule::CostTable, language_storage::CORE_CODE_ADDRESS,
-    value::MoveTypeLayout, vm_status::StatusType,
+    account_address::AccountAddress,
+    gas_schedule::CostTable,
+    value::MoveTypeLayout,
+    vm_status::{StatusCode, StatusType},
 };
 use move_vm_natives::{account, bcs, debug, event, hash, signature, signer, u256, vector};
 use move_vm_types::natives::balance::{Balance, BalanceOperation, WalletId};
+use move_vm_types::natives::extensions::NativeExtensions;
 use move_vm_types::{
     data_store::DataStore,
     gas_schedule::CostStrategy,
@@ -21,7 +24,13 @@ use move_vm_types::{
 };
 use vm::errors::PartialVMResult;
 
-use crate::{interpreter::Interpreter, loader::Resolver, logging::LogContext};
+use crate::{
+    interpreter::Interpreter,
+    loader::Resolver,
+    logging::LogContext,
+    native_registry::{HostNative, NativeRegistry},
+};
+use alloc::sync::Arc;
 
 // The set of native functions the VM supports.
 // The functions can line in any crate linked in but the VM declares them here.
@@ -29,7 +38,8 @@ use crate::{interpreter::Interpreter, loader::Resolver, logging::LogContext};
 // - `resolve` which given a function unique name ModuleAddress::ModuleName::FunctionName
 // returns a `NativeFunction`
 // - `dispatch` which given a `NativeFunction` invokes the native
-#[derive(Debug, Clone, Copy)]
+// Natives registered by the host are resolved first and dispatched via `Host`.
+#[derive(Debug, Clone)]
 pub(crate) enum NativeFunction {
     HashSha2_256,
     HashSha3_256,
@@ -67,55 +77,69 @@ pub(crate) enum NativeFunction {
     WithdrawToNative,
     DepositFromNative,
     GetNativeBalance,
+
+    Host(Arc<HostNative>),
 }
 
 impl NativeFunction {
+    /// Resolves the native function registered by the host
+    /// or the native of the standard library published under `core_address`.
     pub(crate) fn resolve(
+        core_address: &AccountAddress,
+        registry: &NativeRegistry,
         module_address: &AccountAddress,
         module_name: &str,
         function_name: &str,
     ) -> Option<NativeFunction> {
         use NativeFunction::*;
 
-        let case = (module_address, module_name, function_name);
+        if let Some(native) = registry.resolve(module_address, module_name, function_name) {
+            return Some(Host(native));
+        }
+
+        if module_address != core_address {
+            return None;
+        }
+
+        let case = (module_name, function_name);
         Some(match case {
-            (&CORE_CODE_ADDRESS, "Hash", "sha2_256") => HashSha2_256,
-            (&CORE_CODE_ADDRESS, "Hash", "sha3_256") => HashSha3_256,
-            (&CORE_CODE_ADDRESS, "BCS", "to_bytes") => BCSToBytes,
-            (&CORE_CODE_ADDRESS, "Signature", "ed25519_validate_pubkey") => PubED25519Validate,
-            (&CORE_CODE_ADDRESS, "Signature", "ed25519_verify") => SigED25519Verify,
-            (&CORE_CODE_ADDRESS, "Vector", "length") => VectorLength,
-            (&CORE_CODE_ADDRESS, "Vector", "empty") => VectorEmpty,
-            (&CORE_CODE_ADDRESS, "Vector", "borrow") => VectorBorrow,
-            (&CORE_CODE_ADDRESS, "Vector", "borrow_mut") => VectorBorrowMut,
-            (&CORE_CODE_ADDRESS, "Vector", "push_back") => VectorPushBack,
-            (&CORE_CODE_ADDRESS, "Vector", "pop_back") => VectorPopBack,
-            (&CORE_CODE_ADDRESS, "Vector", "destroy_empty") => VectorDestroyEmpty,
-            (&CORE_CODE_ADDRESS, "Vector", "swap") => VectorSwap,
-            (&CORE_CODE_ADDRESS, "Event", "emit") => AccountWriteEvent,
-            (&CORE_CODE_ADDRESS, "Account", "create_signer") => CreateSigner,
-            (&CORE_CODE_ADDRESS, "Account", "destroy_signer") => DestroySigner,
-            (&CORE_CODE_ADDRESS, "Debug", "print") => DebugPrint,
-            (&CORE_CODE_ADDRESS, "Debug", "print_stack_trace") => DebugPrintStackTrace,
-            (&CORE_CODE_ADDRESS, "Signer", "borrow_address") => SignerBorrowAddress,
-            (&CORE_CODE_ADDRESS, "Pontem", "create_signer") => DfinanceCreateSigner,
-            (&CORE_CODE_ADDRESS, "Pontem", "destroy_signer") => DfinanceDestroySigner,
-
-            (&CORE_CODE_ADDRESS, "U256", "from_u8") => U256FromU8,
-            (&CORE_CODE_ADDRESS, "U256", "from_u64") => U256FromU64,
-            (&CORE_CODE_ADDRESS, "U256", "from_u128") => U256FromU128,
-            (&CORE_CODE_ADDRESS, "U256", "as_u8") => U256AsU8,
-            (&CORE_CODE_ADDRESS, "U256", "as_u64") => U256AsU64,
-            (&CORE_CODE_ADDRESS, "U256", "as_u128") => U256AsU128,
-
-            (&CORE_CODE_ADDRESS, "U256", "mul") => U256Mul,
-            (&CORE_CODE_ADDRESS, "U256", "div") => U256Div,
-            (&CORE_CODE_ADDRESS, "U256", "sub") => U256Sub,
-            (&CORE_CODE_ADDRESS, "U256", "add") => U256Add,
-
-            (&CORE_CODE_ADDRESS, "Account", "deposit_native") => DepositFromNative,
-            (&CORE_CODE_ADDRESS, "Account", "withdraw_native") => WithdrawToNative,
-            (&CORE_CODE_ADDRESS, "Account", "get_native_balance") => GetNativeBalance,
+            ("Hash", "sha2_256") => HashSha2_256,
+            ("Hash", "sha3_256") => HashSha3_256,
+            ("BCS", "to_bytes") => BCSToBytes,
+            ("Signature", "ed25519_validate_pubkey") => PubED25519Validate,
+            ("Signature", "ed25519_verify") => SigED25519Verify,
+            ("Vector", "length") => VectorLength,
+            ("Vector", "empty") => VectorEmpty,
+            ("Vector", "borrow") => VectorBorrow,
+            ("Vector", "borrow_mut") => VectorBorrowMut,
+            ("Vector", "push_back") => VectorPushBack,
+            ("Vector", "pop_back") => VectorPopBack,
+            ("Vector", "destroy_empty") => VectorDestroyEmpty,
+            ("Vector", "swap") => VectorSwap,
+            ("Event", "emit") => AccountWriteEvent,
+            ("Account", "create_signer") => CreateSigner,
+            ("Account", "destroy_signer") => DestroySigner,
+            ("Debug", "print") => DebugPrint,
+            ("Debug", "print_stack_trace") => DebugPrintStackTrace,
+            ("Signer", "borrow_address") => SignerBorrowAddress,
+            ("Pontem", "create_signer") => DfinanceCreateSigner,
+            ("Pontem", "destroy_signer") => DfinanceDestroySigner,
+
+            ("U256", "from_u8") => U256FromU8,
+            ("U256", "from_u64") => U256FromU64,
+            ("U256", "from_u128") => U256FromU128,
+            ("U256", "as_u8") => U256AsU8,
+            ("U256", "as_u64") => U256AsU64,
+            ("U256", "as_u128") => U256AsU128,
+
+            ("U256", "mul") => U256Mul,
+            ("U256", "div") => U256Div,
+            ("U256", "sub") => U256Sub,
+            ("U256", "add") => U256Add,
+
+            ("Account", "deposit_native") => DepositFromNative,
+            ("Account", "withdraw_native") => WithdrawToNative,
+            ("Account", "get_native_balance") => GetNativeBalance,
             _ => return None,
         })
     }
@@ -166,6 +190,7 @@ impl NativeFunction {
             Self::WithdrawToNative => account::native_withdraw(ctx, t, v),
             Self::DepositFromNative => account::native_deposit(ctx, t, v),
             Self::GetNativeBalance => account::get_balance(ctx, t, v),
+            Self::Host(native) => native.call(ctx, t, v),
         };
         result
     }
@@ -217,6 +242,7 @@ impl<'a, L: LogContext> NativeContext for FunctionContext<'a, L> {
         match self.data_store.emit_event(address, ty, val, caller) {
             Ok(()) => Ok(true),
             Err(e) if e.major_status().status_type() == StatusType::InvariantViolation => Err(e),
+            Err(e) if e.major_status() == StatusCode::EVENT_LIMIT_EXCEEDED => Err(e),
             Err(_) => Ok(false),
         }
     }
@@ -249,4 +275,20 @@ impl<'a, L: LogContext> NativeContext for FunctionContext<'a, L> {
         self.data_store
             .save_balance_operation(wallet_id, balance_op);
     }
+
+    fn read_remote_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        self.data_store.read_remote_resource(address, tag)
+    }
+
+    fn get_twap(&self, tag: &StructTag, window: u64) -> PartialVMResult<Option<u128>> {
+        self.data_store.get_twap(tag, window)
+    }
+
+    fn extensions(&mut self) -> &mut NativeExtensions {
+        self.data_store.extensions()
+    }
 }
@@ -0,0 +1,204 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Native functions registered by the host.
+//!
+//! The embedding chain can add chain-specific natives without forking the native table
+//! of the runtime. A registered native shadows a built-in one with the same name.
+
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_vm_types::{
+    loaded_data::runtime_types::Type,
+    natives::function::{NativeContext, NativeResult},
+    values::Value,
+};
+use vm::errors::PartialVMResult;
+
+/// Host native function.
+/// Receives the type arguments and the arguments (first argument at position 0).
+pub type NativeHandler = dyn Fn(&mut dyn NativeContext, Vec<Type>, VecDeque<Value>) -> PartialVMResult<NativeResult>
+    + Send
+    + Sync;
+
+/// Gas charged for a host native call in internal gas units,
+/// on top of the cost returned by the native itself.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NativeGasParams {
+    /// Cost of a call.
+    pub base: u64,
+    /// Cost of an abstract memory unit of the arguments.
+    pub per_byte: u64,
+}
+
+/// Native function registered by the host.
+pub struct HostNative {
+    address: AccountAddress,
+    module: String,
+    name: String,
+    gas: NativeGasParams,
+    deterministic: bool,
+    handler: Arc<NativeHandler>,
+}
+
+impl HostNative {
+    /// Returns `true` if the native is declared as `address::module::name`.
+    fn is(&self, address: &AccountAddress, module: &str, name: &str) -> bool {
+        &self.address == address && self.module == module && self.name == name
+    }
+
+    /// Returns gas parameters of the native.
+    pub fn gas(&self) -> NativeGasParams {
+        self.gas
+    }
+
+    /// Returns the name of the native as `address::module::name`.
+    pub fn name(&self) -> String {
+        format!("{}::{}::{}", self.address, self.module, self.name)
+    }
+
+    /// Returns `false` if the native is registered as nondeterministic.
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    pub(crate) fn call(
+        &self,
+        ctx: &mut dyn NativeContext,
+        ty_args: Vec<Type>,
+        args: VecDeque<Value>,
+    ) -> PartialVMResult<NativeResult> {
+        let size: u64 = args.iter().map(|arg| arg.size().get()).sum();
+        let mut result = (self.handler)(ctx, ty_args, args)?;
+        let cost = self
+            .gas
+            .base
+            .saturating_add(self.gas.per_byte.saturating_mul(size));
+        result.cost = result.cost.add(GasUnits::new(cost));
+        Ok(result)
+    }
+}
+
+impl fmt::Debug for HostNative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostNative")
+            .field("address", &self.address)
+            .field("module", &self.module)
+            .field("name", &self.name)
+            .field("gas", &self.gas)
+            .field("deterministic", &self.deterministic)
+            .finish()
+    }
+}
+
+/// Set of the host natives.
+/// Clones share the registered handlers.
+#[derive(Debug, Default, Clone)]
+pub struct NativeRegistry {
+    natives: Vec<Arc<HostNative>>,
+}
+
+impl NativeRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> NativeRegistry {
+        NativeRegistry::default()
+    }
+
+    /// Registers the native `address::module::name`.
+    /// Replaces the native registered under the same name.
+    pub fn register<F>(
+        self,
+        address: AccountAddress,
+        module: &str,
+        name: &str,
+        gas: NativeGasParams,
+        handler: F,
+    ) -> NativeRegistry
+    where
+        F: Fn(&mut dyn NativeContext, Vec<Type>, VecDeque<Value>) -> PartialVMResult<NativeResult>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.insert(address, module, name, gas, true, Arc::new(handler))
+    }
+
+    /// Registers the native `address::module::name` which results may differ between nodes,
+    /// e.g. it reads the wall clock or a local random source.
+    /// Such natives are rejected by the consensus mode of the vm.
+    pub fn register_nondeterministic<F>(
+        self,
+        address: AccountAddress,
+        module: &str,
+        name: &str,
+        gas: NativeGasParams,
+        handler: F,
+    ) -> NativeRegistry
+    where
+        F: Fn(&mut dyn NativeContext, Vec<Type>, VecDeque<Value>) -> PartialVMResult<NativeResult>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.insert(address, module, name, gas, false, Arc::new(handler))
+    }
+
+    fn insert(
+        mut self,
+        address: AccountAddress,
+        module: &str,
+        name: &str,
+        gas: NativeGasParams,
+        deterministic: bool,
+        handler: Arc<NativeHandler>,
+    ) -> NativeRegistry {
+        self.natives
+            .retain(|native| !native.is(&address, module, name));
+        self.natives.push(Arc::new(HostNative {
+            address,
+            module: module.to_string(),
+            name: name.to_string(),
+            gas,
+            deterministic,
+            handler,
+        }));
+        self
+    }
+
+    /// Returns the native `address::module::name`.
+    pub fn resolve(
+        &self,
+        address: &AccountAddress,
+        module: &str,
+        name: &str,
+    ) -> Option<Arc<HostNative>> {
+        self.natives
+            .iter()
+            .find(|native| native.is(address, module, name))
+            .cloned()
+    }
+
+    /// Returns the number of registered natives.
+    pub fn len(&self) -> usize {
+        self.natives.len()
+    }
+
+    /// Returns the natives registered as nondeterministic.
+    pub fn nondeterministic(&self) -> impl Iterator<Item = &HostNative> {
+        self.natives
+            .iter()
+            .filter(|native| !native.deterministic)
+            .map(|native| native.as_ref())
+    }
+
+    /// Returns `true` if no natives are registered.
+    pub fn is_empty(&self) -> bool {
+        self.natives.is_empty()
+    }
+}
@@ -2,68 +2,171 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use alloc::string::ToString;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 
+use bytecode_verifier::control_flow_graph::{ControlFlowGraph, VMControlFlowGraph};
 use move_core_types::{
     account_address::AccountAddress,
+    gas_schedule::{GasAlgebra, GasUnits},
     identifier::IdentStr,
     language_storage::{ModuleId, TypeTag},
     vm_status::StatusCode,
 };
 use move_vm_types::natives::balance::NativeBalance;
-use move_vm_types::{data_store::DataStore, gas_schedule::CostStrategy, values::Value};
+use move_vm_types::{
+    data_store::DataStore, gas_schedule::CostStrategy, loaded_data::runtime_types::Type,
+    values::Value,
+};
 use vm::{
     access::ModuleAccess,
+    compatibility::Compatibility,
     errors::{verification_error, Location, PartialVMError, PartialVMResult, VMResult},
-    file_format::SignatureToken,
-    CompiledModule, IndexKind,
+    file_format::{SignatureToken, StructFieldInformation},
+    normalized, CompiledModule, IndexKind,
 };
 
 use crate::{
     data_cache::{RemoteCache, TransactionDataCache},
     interpreter::Interpreter,
-    loader::Loader,
+    loader::{Function, Loader, SharedCache},
     logging::LogContext,
-    session::Session,
+    move_vm::{CacheStats, StorageAccessCosts, VMLimits, VerificationCosts},
+    native_registry::NativeRegistry,
+    session::{ModuleUpgrade, Session},
 };
 
 /// An instantiation of the MoveVM.
 pub(crate) struct VMRuntime {
     loader: Loader,
+    limits: VMLimits,
+    verification_costs: VerificationCosts,
+    access_costs: StorageAccessCosts,
 }
 
 impl VMRuntime {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(limits: VMLimits) -> Self {
         VMRuntime {
             loader: Loader::new(),
+            limits,
+            verification_costs: VerificationCosts::default(),
+            access_costs: StorageAccessCosts::default(),
         }
     }
 
+    /// Returns execution limits.
+    pub(crate) fn limits(&self) -> VMLimits {
+        self.limits
+    }
+
+    pub(crate) fn set_limits(&mut self, limits: VMLimits) {
+        self.limits = limits;
+    }
+
+    pub(crate) fn set_core_address(&mut self, core_address: AccountAddress) {
+        self.loader.set_core_address(core_address);
+    }
+
+    pub(crate) fn set_natives(&mut self, natives: NativeRegistry) {
+        self.loader.set_natives(natives);
+    }
+
+    pub(crate) fn set_shared_cache(&mut self, cache: Arc<SharedCache>) {
+        self.loader.set_shared_cache(cache);
+    }
+
+    /// Returns the address of the standard library natives.
+    pub(crate) fn core_address(&self) -> AccountAddress {
+        self.loader.core_address()
+    }
+
+    pub(crate) fn set_verification_costs(&mut self, costs: VerificationCosts) {
+        self.verification_costs = costs;
+    }
+
+    /// Returns gas costs of the module verification.
+    pub(crate) fn verification_costs(&self) -> VerificationCosts {
+        self.verification_costs
+    }
+
+    pub(crate) fn set_access_costs(&mut self, costs: StorageAccessCosts) {
+        self.access_costs = costs;
+    }
+
+    /// Returns gas costs of the storage accesses.
+    pub(crate) fn access_costs(&self) -> StorageAccessCosts {
+        self.access_costs
+    }
+
     pub fn new_session<'r, R: RemoteCache, B: NativeBalance>(
         &self,
         remote: &'r R,
         balance: B,
     ) -> Session<'r, '_, R, B> {
+        let loader = self.loader.snapshot();
         Session {
             runtime: self,
-            data_cache: TransactionDataCache::new(remote, &self.loader, balance),
+            data_cache: TransactionDataCache::new(remote, loader.clone(), balance, &self.limits),
+            loader,
         }
     }
 
     /// Clear loader.
+    /// Creates a session loading the modules into the `cache` instead of the vm cache.
+    pub(crate) fn new_session_with_cache<'r, R: RemoteCache, B: NativeBalance>(
+        &self,
+        cache: &Arc<SharedCache>,
+        remote: &'r R,
+        balance: B,
+    ) -> Session<'r, '_, R, B> {
+        let loader = self.loader.snapshot_of(cache);
+        Session {
+            runtime: self,
+            data_cache: TransactionDataCache::new(remote, loader.clone(), balance, &self.limits),
+            loader,
+        }
+    }
+
     pub(crate) fn clear(&self) {
         self.loader.clear();
     }
 
+    /// Clears the `cache` used by the sessions of `new_session_with_cache`.
+    pub(crate) fn clear_cache(&self, cache: &SharedCache) {
+        self.loader.clear_cache(cache);
+    }
+
+    /// Returns `true` if the module is loaded into the cache.
+    pub(crate) fn is_module_cached(&self, id: &ModuleId) -> bool {
+        self.loader.is_module_cached(id)
+    }
+
+    /// Evicts the modules and their dependents from the cache.
+    pub(crate) fn evict_modules(&self, ids: &[ModuleId]) -> bool {
+        self.loader.evict_modules(ids)
+    }
+
+    /// Returns loader cache statistics.
+    pub(crate) fn cache_stats(&self) -> CacheStats {
+        self.loader.cache_stats()
+    }
+
     // See Session::publish_module for what contracts to follow.
     pub(crate) fn publish_module(
         &self,
-        module: Vec<u8>,
+        module: Arc<[u8]>,
         sender: AccountAddress,
+        upgrade: ModuleUpgrade,
         data_store: &mut impl DataStore,
-        _cost_strategy: &mut CostStrategy,
+        loader: &Loader,
+        cost_strategy: &mut CostStrategy,
         log_context: &impl LogContext,
     ) -> VMResult<()> {
+        if module.len() > self.limits.max_module_size {
+            return Err(PartialVMError::new(StatusCode::MODULE_SIZE_LIMIT_EXCEEDED)
+                .finish(Location::Undefined));
+        }
+
         // deserialize the module. Perform bounds check. After this indexes can be
         // used with the `[]` operator
         let compiled_module = match CompiledModule::deserialize(&module) {
@@ -73,6 +176,10 @@ impl VMRuntime {
             }
         };
 
+        // bound the verifier work before the verification
+        self.check_module_limits(&compiled_module)
+            .map_err(|err| err.finish(Location::Undefined))?;
+
         // Make sure the module's self address matches the transaction sender. The self address is
         // where the module will actually be published. If we did not check this, the sender could
         // publish a module under anyone's account.
@@ -89,13 +196,32 @@ impl VMRuntime {
         // under the transaction sender's account.
         let module_id = compiled_module.self_id();
         if data_store.exists_module(&module_id)? {
-            return Err(
-                PartialVMError::new(StatusCode::DUPLICATE_MODULE_NAME).finish(Location::Undefined)
-            );
+            match upgrade {
+                ModuleUpgrade::Forbidden => {
+                    return Err(PartialVMError::new(StatusCode::DUPLICATE_MODULE_NAME)
+                        .finish(Location::Undefined));
+                }
+                ModuleUpgrade::Compatible => {
+                    let old_module =
+                        CompiledModule::deserialize(&data_store.load_module(&module_id)?)
+                            .map_err(|err| err.finish(Location::Undefined))?;
+                    if !is_compatible_upgrade(&old_module, &compiled_module) {
+                        return Err(PartialVMError::new(
+                            StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+                        )
+                        .finish(Location::Undefined));
+                    }
+                }
+                ModuleUpgrade::Arbitrary => {}
+            }
         };
 
+        // charge for the verification work before doing it
+        self.charge_verification(&compiled_module, cost_strategy)
+            .map_err(|err| err.finish(Location::Undefined))?;
+
         // perform bytecode and loading verification
-        self.loader.verify_module_verify_no_missing_dependencies(
+        loader.verify_module_verify_no_missing_dependencies(
             &compiled_module,
             data_store,
             log_context,
@@ -104,18 +230,63 @@ impl VMRuntime {
         data_store.publish_module(&module_id, module)
     }
 
+    fn check_module_limits(&self, module: &CompiledModule) -> PartialVMResult<()> {
+        if module.function_defs().len() > self.limits.max_functions {
+            return Err(PartialVMError::new(StatusCode::TOO_MANY_FUNCTIONS));
+        }
+        if module.struct_defs().len() > self.limits.max_structs {
+            return Err(PartialVMError::new(StatusCode::TOO_MANY_STRUCTS));
+        }
+        if module
+            .identifiers()
+            .iter()
+            .any(|ident| ident.as_str().len() > self.limits.max_identifier_length)
+        {
+            return Err(PartialVMError::new(StatusCode::IDENTIFIER_TOO_LONG));
+        }
+        Ok(())
+    }
+
+    fn charge_verification(
+        &self,
+        module: &CompiledModule,
+        cost_strategy: &mut CostStrategy,
+    ) -> PartialVMResult<()> {
+        let costs = &self.verification_costs;
+        let mut total: u64 = 0;
+        for code in module
+            .function_defs()
+            .iter()
+            .filter_map(|def| def.code.as_ref())
+        {
+            let cfg = VMControlFlowGraph::new(&code.code);
+            let edges = cfg
+                .blocks()
+                .into_iter()
+                .map(|block| cfg.successors(block).len() as u64)
+                .sum::<u64>();
+            let locals = module.signature_at(code.locals).len() as u64;
+            total = total
+                .saturating_add((cfg.num_blocks() as u64).saturating_mul(costs.per_basic_block))
+                .saturating_add(edges.saturating_mul(costs.per_edge))
+                .saturating_add(locals.saturating_mul(costs.per_local));
+        }
+        cost_strategy.deduct_gas(GasUnits::new(total))
+    }
+
     // See Session::execute_script for what contracts to follow.
     pub(crate) fn execute_script(
         &self,
-        script: Vec<u8>,
+        script: &[u8],
         ty_args: Vec<TypeTag>,
         mut args: Vec<Value>,
         senders: Vec<AccountAddress>,
         data_store: &mut impl DataStore,
+        loader: &Loader,
         cost_strategy: &mut CostStrategy,
         log_context: &impl LogContext,
     ) -> VMResult<()> {
-        // signer helper closure
+        // signer helper closures
         fn is_signer_reference(s: &SignatureToken) -> bool {
             use SignatureToken as S;
             match s {
@@ -123,18 +294,51 @@ impl VMRuntime {
                 _ => false,
             }
         }
+        fn is_signer_vector(s: &SignatureToken) -> bool {
+            use SignatureToken as S;
+            match s {
+                S::Vector(inner) => matches!(&**inner, S::Signer),
+                _ => false,
+            }
+        }
 
         // load the script, perform verification
-        let (main, type_params) =
-            self.loader
-                .load_script(&script, &ty_args, data_store, log_context)?;
+        let (main, type_params) = loader.load_script(script, &ty_args, data_store, log_context)?;
+
+        check_args(&args).map_err(|e| e.finish(Location::Script))?;
 
         // Build the arguments list for the main and check the arguments are of restricted types.
         // Signers are built up from left-to-right. Either all signer arguments are used, or no
         // signer arguments can be be used by a script.
+        // A `vector<signer>` parameter following the `&signer` parameters takes the rest of
+        // the senders, so a script can accept any number of senders.
         let parameters = &main.parameters().0;
-        let has_signer_parameters = parameters.get(0).map_or(false, is_signer_reference);
-        let mut signers_and_args = if has_signer_parameters {
+        let signer_references = parameters
+            .iter()
+            .take_while(|param| is_signer_reference(param))
+            .count();
+        let has_signer_vector = parameters
+            .get(signer_references)
+            .map_or(false, is_signer_vector);
+        let mut signers_and_args = if has_signer_vector {
+            if parameters.len() != args.len() + signer_references + 1
+                || senders.len() < signer_references
+            {
+                return Err(PartialVMError::new(StatusCode::TYPE_MISMATCH)
+                    .with_message(
+                        "Scripts must have a sender for each signer reference".to_string(),
+                    )
+                    .finish(Location::Script));
+            }
+            let mut senders = senders.into_iter();
+            let mut signers: Vec<Value> = senders
+                .by_ref()
+                .take(signer_references)
+                .map(Value::transaction_argument_signer_reference)
+                .collect();
+            signers.push(Value::vector_signer(senders));
+            signers
+        } else if signer_references > 0 {
             if parameters.len() != args.len() + senders.len() {
                 return Err(PartialVMError::new(StatusCode::TYPE_MISMATCH)
                     .with_message("Scripts must use all or no signers".to_string())
@@ -150,7 +354,6 @@ impl VMRuntime {
             vec![]
         };
         signers_and_args.append(&mut args);
-        check_args(&signers_and_args).map_err(|e| e.finish(Location::Script))?;
 
         // run the script
         Interpreter::entrypoint(
@@ -159,9 +362,12 @@ impl VMRuntime {
             signers_and_args,
             data_store,
             cost_strategy,
-            &self.loader,
+            loader,
+            &self.limits,
+            &self.access_costs,
             log_context,
         )
+        .map(|_| ())
     }
 
     // See Session::execute_function for what contracts to follow.
@@ -172,14 +378,157 @@ impl VMRuntime {
         ty_args: Vec<TypeTag>,
         args: Vec<Value>,
         data_store: &mut impl DataStore,
+        loader: &Loader,
         cost_strategy: &mut CostStrategy,
         log_context: &impl LogContext,
     ) -> VMResult<()> {
         // load the function in the given module, perform verification of the module and
         // its dependencies if the module was not loaded
         let (func, type_params) =
-            self.loader
-                .load_function(function_name, module, &ty_args, data_store, log_context)?;
+            loader.load_function(function_name, module, &ty_args, data_store, log_context)?;
+
+        self.call_function(
+            module,
+            func,
+            type_params,
+            args,
+            false,
+            data_store,
+            loader,
+            cost_strategy,
+            log_context,
+        )
+        .map(|_| ())
+    }
+
+    // See Session::execute_entry_function for what contracts to follow.
+    pub(crate) fn execute_entry_function(
+        &self,
+        module: &ModuleId,
+        function_name: &IdentStr,
+        ty_args: Vec<TypeTag>,
+        mut args: Vec<Value>,
+        senders: Vec<AccountAddress>,
+        data_store: &mut impl DataStore,
+        loader: &Loader,
+        cost_strategy: &mut CostStrategy,
+        log_context: &impl LogContext,
+    ) -> VMResult<()> {
+        let (func, type_params) =
+            loader.load_function(function_name, module, &ty_args, data_store, log_context)?;
+
+        // The senders are passed to the leading `&signer` parameters, one sender for each.
+        // Like scripts, a function uses all or no signers.
+        let signer_references = func
+            .parameters()
+            .0
+            .iter()
+            .take_while(|param| match param {
+                SignatureToken::Reference(inner) => matches!(&**inner, SignatureToken::Signer),
+                _ => false,
+            })
+            .count();
+        if signer_references > 0 && signer_references != senders.len() {
+            return Err(PartialVMError::new(StatusCode::TYPE_MISMATCH)
+                .with_message(format!(
+                    "{} takes {} signers, {} senders given",
+                    func.pretty_string(),
+                    signer_references,
+                    senders.len()
+                ))
+                .finish(Location::Module(module.clone())));
+        }
+        let mut signers_and_args: Vec<Value> = if signer_references > 0 {
+            senders
+                .into_iter()
+                .map(Value::transaction_argument_signer_reference)
+                .collect()
+        } else {
+            vec![]
+        };
+        signers_and_args.append(&mut args);
+
+        self.call_function(
+            module,
+            func,
+            type_params,
+            signers_and_args,
+            true,
+            data_store,
+            loader,
+            cost_strategy,
+            log_context,
+        )
+        .map(|_| ())
+    }
+
+    // See Session::execute_view_function for what contracts to follow.
+    pub(crate) fn execute_view_function(
+        &self,
+        module: &ModuleId,
+        function_name: &IdentStr,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Value>,
+        data_store: &mut impl DataStore,
+        loader: &Loader,
+        cost_strategy: &mut CostStrategy,
+        log_context: &impl LogContext,
+    ) -> VMResult<Vec<Vec<u8>>> {
+        let (func, type_params) =
+            loader.load_function(function_name, module, &ty_args, data_store, log_context)?;
+        if !func.is_public() {
+            return Err(PartialVMError::new(
+                StatusCode::EXECUTE_ENTRY_FUNCTION_CALLED_ON_NON_ENTRY_FUNCTION,
+            )
+            .with_message(format!("{} is not a public function", func.pretty_string()))
+            .finish(Location::Module(module.clone())));
+        }
+        let layouts = loader
+            .return_layouts(&func, &type_params)
+            .map_err(|e| e.finish(Location::Module(module.clone())))?;
+
+        let values = self.call_function(
+            module,
+            func,
+            type_params,
+            args,
+            false,
+            data_store,
+            loader,
+            cost_strategy,
+            log_context,
+        )?;
+        values
+            .into_iter()
+            .zip(layouts.iter())
+            .map(|(value, layout)| {
+                value.simple_serialize(layout).ok_or_else(|| {
+                    PartialVMError::new(StatusCode::VALUE_SERIALIZATION_ERROR)
+                        .finish(Location::Module(module.clone()))
+                })
+            })
+            .collect()
+    }
+
+    fn call_function(
+        &self,
+        module: &ModuleId,
+        func: Arc<Function>,
+        type_params: Vec<Type>,
+        args: Vec<Value>,
+        entry_only: bool,
+        data_store: &mut impl DataStore,
+        loader: &Loader,
+        cost_strategy: &mut CostStrategy,
+        log_context: &impl LogContext,
+    ) -> VMResult<Vec<Value>> {
+        if entry_only && !func.is_entry() {
+            return Err(PartialVMError::new(
+                StatusCode::EXECUTE_ENTRY_FUNCTION_CALLED_ON_NON_ENTRY_FUNCTION,
+            )
+            .with_message(format!("{} is not an entry function", func.pretty_string()))
+            .finish(Location::Module(module.clone())));
+        }
 
         // check the arguments provided are of restricted types
         check_args(&args).map_err(|e| e.finish(Location::Module(module.clone())))?;
@@ -191,7 +540,9 @@ impl VMRuntime {
             args,
             data_store,
             cost_strategy,
-            &self.loader,
+            loader,
+            &self.limits,
+            &self.access_costs,
             log_context,
         )
     }
@@ -209,3 +560,22 @@ fn check_args(args: &[Value]) -> PartialVMResult<()> {
     }
     Ok(())
 }
+
+/// Returns `true` if the new module keeps the structs and the public functions of the old one.
+/// Modules with native structs can not be compared, so they are never compatible.
+fn is_compatible_upgrade(old_module: &CompiledModule, new_module: &CompiledModule) -> bool {
+    let has_native_structs = |module: &CompiledModule| {
+        module
+            .struct_defs()
+            .iter()
+            .any(|def| matches!(def.field_information, StructFieldInformation::Native))
+    };
+    if has_native_structs(old_module) || has_native_structs(new_module) {
+        return false;
+    }
+    Compatibility::check(
+        &normalized::Module::new(old_module),
+        &normalized::Module::new(new_module),
+    )
+    .is_fully_compatible()
+}
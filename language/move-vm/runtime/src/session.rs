@@ -3,22 +3,40 @@
 
 use crate::{
     data_cache::{RemoteCache, TransactionDataCache, TransactionEffects},
+    loader::Loader,
     logging::LogContext,
     runtime::VMRuntime,
 };
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use move_core_types::{
     account_address::AccountAddress,
     identifier::IdentStr,
     language_storage::{ModuleId, TypeTag},
 };
+use move_vm_types::data_store::DataStore;
 use move_vm_types::natives::balance::NativeBalance;
+use move_vm_types::natives::extensions::NativeExtensions;
 use move_vm_types::{gas_schedule::CostStrategy, values::Value};
 use vm::errors::*;
 
+/// Replacement of an already published module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleUpgrade {
+    /// Publishing a module with the name of a published one fails with `DUPLICATE_MODULE_NAME`.
+    Forbidden,
+    /// The published module is replaced if the new one keeps its structs and public functions.
+    /// Fails with `BACKWARD_INCOMPATIBLE_MODULE_UPDATE` otherwise.
+    Compatible,
+    /// The published module is replaced without checks.
+    Arbitrary,
+}
+
 pub struct Session<'r, 'l, R, B: NativeBalance> {
     pub(crate) runtime: &'l VMRuntime,
-    pub(crate) data_cache: TransactionDataCache<'r, 'l, R, B>,
+    /// The loader pinned to the cache generation current at the session creation.
+    pub(crate) loader: Loader,
+    pub(crate) data_cache: TransactionDataCache<'r, R, B>,
 }
 
 impl<'r, 'l, R: RemoteCache, B: NativeBalance> Session<'r, 'l, R, B> {
@@ -53,6 +71,66 @@ impl<'r, 'l, R: RemoteCache, B: NativeBalance> Session<'r, 'l, R, B> {
             ty_args,
             args,
             &mut self.data_cache,
+            &self.loader,
+            cost_strategy,
+            log_context,
+        )
+    }
+
+    /// Execute an entry function of a module as the entry point of a transaction.
+    ///
+    /// Entry functions are public functions returning nothing whose parameters are leading
+    /// `&signer` references followed by transaction argument types. Calls to any other function
+    /// are refused with `EXECUTE_ENTRY_FUNCTION_CALLED_ON_NON_ENTRY_FUNCTION`.
+    ///
+    /// The senders are passed to the leading `&signer` parameters. Like a script, the function
+    /// must take a signer for each sender or no signers at all. The arguments follow the signers
+    /// and must follow the contracts of `execute_function`.
+    pub fn execute_entry_function(
+        &mut self,
+        module: &ModuleId,
+        function_name: &IdentStr,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Value>,
+        senders: Vec<AccountAddress>,
+        cost_strategy: &mut CostStrategy,
+        log_context: &impl LogContext,
+    ) -> VMResult<()> {
+        self.runtime.execute_entry_function(
+            module,
+            function_name,
+            ty_args,
+            args,
+            senders,
+            &mut self.data_cache,
+            &self.loader,
+            cost_strategy,
+            log_context,
+        )
+    }
+
+    /// Execute a public function of a module and return its BCS serialized return values.
+    ///
+    /// The call is refused with `EXECUTE_ENTRY_FUNCTION_CALLED_ON_NON_ENTRY_FUNCTION` if the
+    /// function is not public.
+    /// Functions returning references can't be executed as view functions.
+    /// Effects of the call are kept in the session, drop the session to discard them.
+    pub fn execute_view_function(
+        &mut self,
+        module: &ModuleId,
+        function_name: &IdentStr,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Value>,
+        cost_strategy: &mut CostStrategy,
+        log_context: &impl LogContext,
+    ) -> VMResult<Vec<Vec<u8>>> {
+        self.runtime.execute_view_function(
+            module,
+            function_name,
+            ty_args,
+            args,
+            &mut self.data_cache,
+            &self.loader,
             cost_strategy,
             log_context,
         )
@@ -65,6 +143,9 @@ impl<'r, 'l, R: RemoteCache, B: NativeBalance> Session<'r, 'l, R, B> {
     ///   - Type arguments refer to a non-existent type.
     ///   - Arguments (senders included) are invalid or fail to match the signature of the script.
     ///
+    /// The senders are passed to the leading `&signer` parameters. A `vector<signer>` parameter
+    /// following them receives the remaining senders.
+    ///
     /// If any other error occurs during execution, the Move VM MUST propagate that error back to the caller.
     /// Besides, no user input should cause the Move VM to return an invariant violation.
     ///
@@ -72,7 +153,7 @@ impl<'r, 'l, R: RemoteCache, B: NativeBalance> Session<'r, 'l, R, B> {
     /// not proceed with effect generation.
     pub fn execute_script(
         &mut self,
-        script: Vec<u8>,
+        script: &[u8],
         ty_args: Vec<TypeTag>,
         args: Vec<Value>,
         senders: Vec<AccountAddress>,
@@ -85,6 +166,7 @@ impl<'r, 'l, R: RemoteCache, B: NativeBalance> Session<'r, 'l, R, B> {
             args,
             senders,
             &mut self.data_cache,
+            &self.loader,
             cost_strategy,
             log_context,
         )
@@ -104,15 +186,41 @@ impl<'r, 'l, R: RemoteCache, B: NativeBalance> Session<'r, 'l, R, B> {
     /// not proceed with effect generation.
     pub fn publish_module(
         &mut self,
-        module: Vec<u8>,
+        module: Arc<[u8]>,
+        sender: AccountAddress,
+        cost_strategy: &mut CostStrategy,
+        log_context: &impl LogContext,
+    ) -> VMResult<()> {
+        self.runtime.publish_module(
+            module,
+            sender,
+            ModuleUpgrade::Forbidden,
+            &mut self.data_cache,
+            &self.loader,
+            cost_strategy,
+            log_context,
+        )
+    }
+
+    /// Publish the module replacing the published one as allowed by `upgrade`.
+    ///
+    /// The loader cache keeps the code of the replaced module,
+    /// so the cache must be cleared after the effects are applied.
+    /// See `publish_module` for the other contracts.
+    pub fn publish_module_with_upgrade(
+        &mut self,
+        module: Arc<[u8]>,
         sender: AccountAddress,
+        upgrade: ModuleUpgrade,
         cost_strategy: &mut CostStrategy,
         log_context: &impl LogContext,
     ) -> VMResult<()> {
         self.runtime.publish_module(
             module,
             sender,
+            upgrade,
             &mut self.data_cache,
+            &self.loader,
             cost_strategy,
             log_context,
         )
@@ -122,6 +230,12 @@ impl<'r, 'l, R: RemoteCache, B: NativeBalance> Session<'r, 'l, R, B> {
         self.data_cache.num_mutated_accounts(sender)
     }
 
+    /// Returns the native extensions recorded by the session so far,
+    /// e.g. to inspect the state of a failed execution before dropping the session.
+    pub fn extensions(&mut self) -> &mut NativeExtensions {
+        self.data_cache.extensions()
+    }
+
     /// Finish up the session and produce the side effects.
     ///
     /// This function should always succeed with no user errors returned, barring invariant violations.
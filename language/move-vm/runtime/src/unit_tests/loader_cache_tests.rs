@@ -0,0 +1,65 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::vm_arguments_tests::{make_script, Bank, RemoteStore};
+use crate::{logging::NoContextLog, move_vm::MoveVM};
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_vm_types::gas_schedule::{zero_cost_schedule, CostStrategy};
+use vm::file_format::Signature;
+
+#[test]
+fn clear_keeps_the_generation_of_sessions_in_flight() {
+    let move_vm = MoveVM::new();
+    let remote_view = RemoteStore {};
+    let log_context = NoContextLog::new();
+    let cost_table = zero_cost_schedule();
+    let mut cost_strategy = CostStrategy::system(&cost_table, GasUnits::new(0));
+    let script = make_script(Signature(vec![]));
+
+    let mut session = move_vm.new_session(&remote_view, Bank {});
+    session
+        .execute_script(
+            &script,
+            vec![],
+            vec![],
+            vec![],
+            &mut cost_strategy,
+            &log_context,
+        )
+        .expect("script must run");
+    let stats = move_vm.cache_stats();
+    assert_eq!(stats.generation, 0);
+    assert_eq!(stats.scripts, 1);
+
+    move_vm.clear();
+    let stats = move_vm.cache_stats();
+    assert_eq!(stats.generation, 1);
+    assert_eq!(stats.scripts, 0);
+
+    // the session keeps loading from the generation it was created with
+    session
+        .execute_script(
+            &script,
+            vec![],
+            vec![],
+            vec![],
+            &mut cost_strategy,
+            &log_context,
+        )
+        .expect("script must run");
+    assert_eq!(move_vm.cache_stats().scripts, 0);
+    session.finish().expect("session must finish");
+
+    let mut session = move_vm.new_session(&remote_view, Bank {});
+    session
+        .execute_script(
+            &script,
+            vec![],
+            vec![],
+            vec![],
+            &mut cost_strategy,
+            &log_context,
+        )
+        .expect("script must run");
+    assert_eq!(move_vm.cache_stats().scripts, 1);
+}
@@ -25,7 +25,7 @@ use vm::{
 
 // make a script with a given signature for main. The main just return, cannot
 // pass resources or the verifier will fail as being still on the stack (args)
-fn make_script(signature: Signature) -> Vec<u8> {
+pub(crate) fn make_script(signature: Signature) -> Vec<u8> {
     let mut blob = vec![];
     CompiledScriptMut {
         module_handles: vec![],
@@ -151,7 +151,7 @@ fn make_script_consuming_args(signature: Signature) -> Vec<u8> {
     blob
 }
 
-struct RemoteStore {}
+pub(crate) struct RemoteStore {}
 
 impl RemoteCache for RemoteStore {
     fn get_module(&self, _module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
@@ -167,7 +167,7 @@ impl RemoteCache for RemoteStore {
     }
 }
 
-struct Bank {}
+pub(crate) struct Bank {}
 
 impl NativeBalance for Bank {
     fn get_balance(&self, _: &WalletId) -> Option<Balance> {
@@ -189,7 +189,7 @@ fn call_script_with_args_ty_args_signers(
     let cost_table = zero_cost_schedule();
     let mut cost_strategy = CostStrategy::system(&cost_table, GasUnits::new(0));
     session.execute_script(
-        script,
+        &script,
         ty_args,
         args,
         signers,
@@ -29,7 +29,7 @@ pub fn convert_txn_effects_to_move_changeset_and_events(
     }
 
     for (module_id, module_blob) in txn_effects.modules {
-        changeset.publish_module(module_id, module_blob)?;
+        changeset.publish_module(module_id, module_blob.to_vec())?;
     }
 
     let events = txn_effects
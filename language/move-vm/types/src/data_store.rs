@@ -2,12 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::natives::balance::{Balance, BalanceOperation, WalletId};
+use crate::natives::extensions::NativeExtensions;
 use crate::{
     loaded_data::runtime_types::Type,
     values::{GlobalValue, Value},
 };
+use alloc::sync::Arc;
 use alloc::vec::Vec;
-use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+};
 use vm::errors::{PartialVMResult, VMResult};
 
 /// Provide an implementation for bytecodes related to data with a given data store.
@@ -29,11 +34,14 @@ pub trait DataStore {
         ty: &Type,
     ) -> PartialVMResult<&mut GlobalValue>;
 
+    /// Returns `true` if the resource was loaded by the transaction.
+    fn is_resource_loaded(&self, addr: AccountAddress, ty: &Type) -> bool;
+
     /// Get the serialized format of a `CompiledModule` given a `ModuleId`.
     fn load_module(&self, module_id: &ModuleId) -> VMResult<Vec<u8>>;
 
     /// Publish a module.
-    fn publish_module(&mut self, module_id: &ModuleId, blob: Vec<u8>) -> VMResult<()>;
+    fn publish_module(&mut self, module_id: &ModuleId, blob: Arc<[u8]>) -> VMResult<()>;
 
     /// Check if this module exists.
     fn exists_module(&self, module_id: &ModuleId) -> VMResult<bool>;
@@ -60,4 +68,27 @@ pub trait DataStore {
 
     /// Save balance operation.
     fn save_balance_operation(&mut self, wallet_id: WalletId, balance_op: BalanceOperation);
+
+    // ---
+    // Remote operations
+    // ---
+
+    /// Reads the serialized resource from the remote storage bypassing the transaction cache.
+    /// Writes of the current transaction are not visible.
+    fn read_remote_resource(
+        &self,
+        addr: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>>;
+
+    /// Returns the time-weighted average of the `Coins::Price` resource `tag`
+    /// over the last `window` seconds.
+    fn get_twap(&self, tag: &StructTag, window: u64) -> PartialVMResult<Option<u128>>;
+
+    // ---
+    // Native extensions
+    // ---
+
+    /// Per-session state of the natives, see `NativeExtensions`.
+    fn extensions(&mut self) -> &mut NativeExtensions;
 }
@@ -7,6 +7,7 @@
 //! operations or other native operations; the cost of each native operation will be returned by the
 //! native function itself.
 use alloc::vec::Vec;
+use core::fmt;
 use mirai_annotations::*;
 use move_core_types::{
     gas_schedule::{
@@ -24,17 +25,47 @@ use vm::{
     file_format_common::{instruction_key, Opcodes},
 };
 
+/// Number of instructions between two `Interrupt::should_interrupt` checks.
+pub const INTERRUPT_CHECK_INTERVAL: u64 = 1024;
+
+/// Host hook which allows to stop a running script regardless of the gas left.
+pub trait Interrupt {
+    /// Returns `true` if the execution must be stopped.
+    fn should_interrupt(&self) -> bool;
+}
+
+impl<F: Fn() -> bool> Interrupt for F {
+    fn should_interrupt(&self) -> bool {
+        self()
+    }
+}
+
 /// The Move VM implementation for gas charging.
 ///
 /// Initialize with a `CostTable` and the gas provided to the transaction.
 /// Provide all the proper guarantees about gas charging in the Move VM.
 ///
 /// Every client must use an instance of this type to interact with the Move VM.
-#[derive(Debug)]
 pub struct CostStrategy<'a> {
     cost_table: &'a CostTable,
     gas_left: GasUnits<GasCarrier>,
     charge: bool,
+    instructions: u64,
+    instruction_limit: Option<u64>,
+    interrupt: Option<&'a dyn Interrupt>,
+}
+
+impl fmt::Debug for CostStrategy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CostStrategy")
+            .field("cost_table", &self.cost_table)
+            .field("gas_left", &self.gas_left)
+            .field("charge", &self.charge)
+            .field("instructions", &self.instructions)
+            .field("instruction_limit", &self.instruction_limit)
+            .field("interrupt", &self.interrupt.is_some())
+            .finish()
+    }
 }
 
 impl<'a> CostStrategy<'a> {
@@ -47,6 +78,9 @@ impl<'a> CostStrategy<'a> {
             gas_left: gas_left.map(|x| x * cost_table.gas_constants.gas_unit_scaling_factor),
             cost_table,
             charge: true,
+            instructions: 0,
+            instruction_limit: None,
+            interrupt: None,
         }
     }
 
@@ -59,7 +93,47 @@ impl<'a> CostStrategy<'a> {
             gas_left: gas_left.map(|x| x * cost_table.gas_constants.gas_unit_scaling_factor),
             cost_table,
             charge: false,
+            instructions: 0,
+            instruction_limit: None,
+            interrupt: None,
+        }
+    }
+
+    /// Limits the number of executed instructions independently of the gas.
+    /// Exceeding the limit fails with `INSTRUCTION_LIMIT_EXCEEDED`.
+    pub fn with_instruction_limit(mut self, limit: u64) -> Self {
+        self.instruction_limit = Some(limit);
+        self
+    }
+
+    /// Sets the hook which is checked every `INTERRUPT_CHECK_INTERVAL` instructions.
+    /// The execution fails with `EXECUTION_INTERRUPTED` once the hook returns `true`.
+    pub fn with_interrupt(mut self, interrupt: &'a dyn Interrupt) -> Self {
+        self.interrupt = Some(interrupt);
+        self
+    }
+
+    /// Return the number of executed instructions.
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    /// Count an executed instruction and fail if the execution must be stopped.
+    fn count_instr(&mut self) -> PartialVMResult<()> {
+        self.instructions += 1;
+        if let Some(limit) = self.instruction_limit {
+            if self.instructions > limit {
+                return Err(PartialVMError::new(StatusCode::INSTRUCTION_LIMIT_EXCEEDED)
+                    .with_message(format!("instruction limit {} exceeded", limit)));
+            }
+        }
+        if let Some(interrupt) = self.interrupt {
+            if self.instructions % INTERRUPT_CHECK_INTERVAL == 0 && interrupt.should_interrupt() {
+                return Err(PartialVMError::new(StatusCode::EXECUTION_INTERRUPTED)
+                    .with_message(format!("execution interrupted by host")));
+            }
         }
+        Ok(())
     }
 
     /// Return the `CostTable` behind this `CostStrategy`.
@@ -97,6 +171,7 @@ impl<'a> CostStrategy<'a> {
         opcode: Opcodes,
         size: AbstractMemorySize<GasCarrier>,
     ) -> PartialVMResult<()> {
+        self.count_instr()?;
         // Make sure that the size is always non-zero
         let size = size.map(|x| core::cmp::max(1, x));
         debug_assert!(size.get() > 0);
@@ -110,6 +185,7 @@ impl<'a> CostStrategy<'a> {
 
     /// Charge an instruction and fail if not enough gas units are left.
     pub fn charge_instr(&mut self, opcode: Opcodes) -> PartialVMResult<()> {
+        self.count_instr()?;
         self.deduct_gas(self.cost_table.instruction_cost(opcode as u8).total())
     }
 
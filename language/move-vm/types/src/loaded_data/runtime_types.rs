@@ -90,4 +90,22 @@ impl Type {
             1,
         )
     }
+
+    /// Returns the nesting depth of the type. Primitive types have depth 1.
+    pub fn depth(&self) -> usize {
+        match self {
+            Type::Bool
+            | Type::U8
+            | Type::U64
+            | Type::U128
+            | Type::Address
+            | Type::Signer
+            | Type::Struct(_)
+            | Type::TyParam(_) => 1,
+            Type::Vector(ty) | Type::Reference(ty) | Type::MutableReference(ty) => 1 + ty.depth(),
+            Type::StructInstantiation(_, ty_args) => {
+                1 + ty_args.iter().map(Type::depth).max().unwrap_or(0)
+            }
+        }
+    }
 }
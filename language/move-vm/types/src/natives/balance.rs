@@ -1,11 +1,12 @@
 use core::fmt;
 use core::fmt::{Display, Formatter};
 
-use hashbrown::HashMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use move_core_types::account_address::AccountAddress;
 use move_core_types::language_storage::StructTag;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct WalletId {
     pub address: AccountAddress,
     pub tag: StructTag,
@@ -29,11 +30,17 @@ impl Display for WalletId {
 
 pub type Balance = u128;
 
+/// Currencies of the native balances minted or burned in Move by a session, kept in its
+/// native extensions: `Pontem::deposit_from_native` mints the Move coins of the deposited
+/// native coins and `Pontem::withdraw_to_native` burns the withdrawn ones.
+#[derive(Debug, Default)]
+pub struct MintBurnJournal(pub BTreeSet<StructTag>);
+
 pub trait NativeBalance {
     fn get_balance(&self, address: &WalletId) -> Option<Balance>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BalanceOperation {
     Deposit(Balance),
     Withdraw(Balance),
@@ -74,7 +81,7 @@ impl BalanceOperation {
 
 pub struct MasterOfCoin<B: NativeBalance> {
     native_balances: B,
-    bank: HashMap<WalletId, BalanceOperation>,
+    bank: BTreeMap<WalletId, BalanceOperation>,
 }
 
 impl<B> MasterOfCoin<B>
@@ -122,7 +129,7 @@ where
     }
 }
 
-impl<B: NativeBalance> From<MasterOfCoin<B>> for HashMap<WalletId, BalanceOperation> {
+impl<B: NativeBalance> From<MasterOfCoin<B>> for BTreeMap<WalletId, BalanceOperation> {
     fn from(moc: MasterOfCoin<B>) -> Self {
         moc.bank
     }
@@ -0,0 +1,51 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-session state of the native functions.
+//!
+//! Natives recording effects applied by the embedder, e.g. the operations of host assets,
+//! keep them in the extensions of the session instead of a state shared between the sessions.
+//! The extensions are returned with the effects of the session and dropped with a discarded one.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use core::any::{Any, TypeId};
+use core::fmt;
+
+/// Values of the native extensions by their type.
+#[derive(Default)]
+pub struct NativeExtensions {
+    values: BTreeMap<TypeId, Box<dyn Any + Send>>,
+}
+
+impl NativeExtensions {
+    /// Returns the extension of the type `T`, inserting the default value if it is missing.
+    pub fn get_or_default<T: Any + Send + Default>(&mut self) -> &mut T {
+        self.values
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(T::default()))
+            .downcast_mut::<T>()
+            .expect("extension is stored under its type id")
+    }
+
+    /// Removes the extension of the type `T`.
+    pub fn remove<T: Any>(&mut self) -> Option<T> {
+        self.values
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast::<T>().ok())
+            .map(|value| *value)
+    }
+
+    /// Returns `true` if no extension is set.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Debug for NativeExtensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeExtensions")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
@@ -24,10 +24,11 @@ use move_core_types::{
 use vm::errors::PartialVMResult;
 
 use crate::natives::balance::{Balance, BalanceOperation, WalletId};
+use crate::natives::extensions::NativeExtensions;
 use alloc::string::String;
 use alloc::vec::Vec;
 use move_core_types::account_address::AccountAddress;
-use move_core_types::language_storage::{ModuleId, TypeTag};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
 pub use move_core_types::vm_status::StatusCode;
 pub use vm::errors::PartialVMError;
 
@@ -62,6 +63,17 @@ pub trait NativeContext {
     fn get_balance(&self, wallet_id: &WalletId) -> Option<Balance>;
     /// Save balance operation.
     fn save_balance_operation(&mut self, wallet_id: WalletId, balance_op: BalanceOperation);
+    /// Reads the serialized resource from the remote storage bypassing the transaction cache.
+    fn read_remote_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>>;
+    /// Returns the time-weighted average of the `Coins::Price` resource `tag`
+    /// over the last `window` seconds.
+    fn get_twap(&self, tag: &StructTag, window: u64) -> PartialVMResult<Option<u128>>;
+    /// Per-session state of the natives, returned with the effects of the session.
+    fn extensions(&mut self) -> &mut NativeExtensions;
 }
 
 /// Result of a native function execution requires charges for execution cost.
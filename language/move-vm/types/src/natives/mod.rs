@@ -2,4 +2,5 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod balance;
+pub mod extensions;
 pub mod function;
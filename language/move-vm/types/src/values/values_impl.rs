@@ -1251,6 +1251,17 @@ impl Value {
         ))))
     }
 
+    /// Create a `vector<signer>` of the transaction senders.
+    pub fn vector_signer(it: impl IntoIterator<Item = AccountAddress>) -> Self {
+        Self(ValueImpl::Container(Container::VecR(Rc::new(
+            RefCell::new(
+                it.into_iter()
+                    .map(|x| ValueImpl::Container(Container::signer(x)))
+                    .collect(),
+            ),
+        ))))
+    }
+
     // Creates a vector with an iterator of values and a signature that describes
     // the values. Only creates a vector of constant values and so a constant itself.
     pub fn constant_vector_generic(
@@ -1811,7 +1822,7 @@ fn check_elem_layout(
         | (Type::U128, Container::VecU128(_))
         | (Type::Bool, Container::VecBool(_))
         | (Type::Address, Container::VecAddress(_))
-        | (Type::Signer, Container::StructR(_)) => Ok(()),
+        | (Type::Signer, Container::VecR(_)) => Ok(()),
 
         (Type::Vector(_), Container::VecR(_)) if is_resource => Ok(()),
         (Type::Vector(_), Container::VecC(_)) if !is_resource => Ok(()),
@@ -2654,6 +2665,36 @@ impl Value {
         })
         .ok()
     }
+
+    /// Returns the size of the value serialized with `simple_serialize`.
+    pub fn serialized_size(&self, layout: &MoveTypeLayout) -> Option<usize> {
+        bcs::serialized_size(&AnnotatedValue {
+            layout,
+            val: &self.0,
+        })
+        .ok()
+    }
+
+    /// Appends the value serialized with `simple_serialize` to the buffer.
+    /// The buffer is left unchanged on failure.
+    pub fn simple_serialize_into(
+        &self,
+        layout: &MoveTypeLayout,
+        buffer: &mut Vec<u8>,
+    ) -> Option<()> {
+        let len = buffer.len();
+        let result = bcs::serialize_into(
+            buffer,
+            &AnnotatedValue {
+                layout,
+                val: &self.0,
+            },
+        );
+        if result.is_err() {
+            buffer.truncate(len);
+        }
+        result.ok()
+    }
 }
 
 impl Struct {
@@ -0,0 +1,89 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::normalized::Module;
+
+/// The result of a linking and layout compatibility check. Here is what the different combinations
+/// mean:
+/// `{ struct_and_function_linking: true, struct_layout: true }`: fully backward compatible
+/// `{ struct_and_function_linking: true, struct_layout: false }`: Dependent modules that reference
+/// functions or types in this module may not link. However, fixing, recompiling, and redeploying
+/// all dependent modules will work--no data migration needed.
+/// `{ type_and_function_linking: true, struct_layout: false }`: Attempting to read structs published
+/// by this module will now fail at runtime. However, dependent modules will continue to link.
+/// Requires data migration, but no changes to dependent modules.
+/// `{ type_and_function_linking: false, struct_layout: false }`: Everything is broken. Need both a
+/// data migration and changes to dependent modules.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Compatibility {
+    /// If false, dependent modules that reference functions or structs in this module may not link
+    pub struct_and_function_linking: bool,
+    /// If false, attempting to read structs previously published by this module will fail at runtime
+    pub struct_layout: bool,
+}
+
+impl Compatibility {
+    /// Return true if the two module s compared in the compatiblity check are both linking and
+    /// layout compatible.
+    pub fn is_fully_compatible(&self) -> bool {
+        self.struct_and_function_linking && self.struct_layout
+    }
+
+    /// Return compatibility assessment for `new_module` relative to `old_module`.
+    pub fn check(old_module: &Module, new_module: &Module) -> Compatibility {
+        let mut struct_and_function_linking = true;
+        let mut struct_layout = true;
+
+        // module's name and address are unchanged
+        if old_module.address != new_module.address || old_module.name != new_module.name {
+            struct_and_function_linking = false;
+        }
+
+        // old module's structs are a subset of the new module's structs
+        for old_struct in &old_module.structs {
+            match new_module
+                .structs
+                .iter()
+                .find(|new_struct| new_struct.name == old_struct.name)
+            {
+                Some(new_struct) => {
+                    if new_struct.kind != old_struct.kind
+                        || new_struct.type_parameters != old_struct.type_parameters
+                    {
+                        // Declared kind and/or type parameters changed. Existing modules that
+                        // depend on this struct will fail to link with the new version of the
+                        // module.
+                        struct_and_function_linking = false;
+                    }
+                    if new_struct.fields != old_struct.fields {
+                        // Fields changed. Code in this module will fail at runtime if it tries to
+                        // read a previously published struct value.
+                        struct_layout = false;
+                    }
+                }
+                None => {
+                    // Struct not present in new. Existing modules that depend on this struct will
+                    // fail to link with the new version of the module.
+                    struct_and_function_linking = false;
+                    // Note: we intentionally do *not* set struct_layout = false here. Struct
+                    // layout can only be broken if the struct exists in both versions of the
+                    // module.
+                }
+            }
+        }
+
+        // old module's public functions are a subset of the new module's public functions
+        for old_func in &old_module.public_functions {
+            if !new_module.public_functions.contains(old_func) {
+                // Public function not present in new. Existing modules that depend on this
+                // function will fail to link with the new version of the module.
+                struct_and_function_linking = false;
+            }
+        }
+
+        Compatibility {
+            struct_and_function_linking,
+            struct_layout,
+        }
+    }
+}
@@ -485,6 +485,18 @@ impl Signature {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Whether the parameters can be supplied by a transaction: leading `&signer` references
+    /// followed by values representable as transaction arguments.
+    pub fn is_entry_parameters(&self) -> bool {
+        self.0
+            .iter()
+            .skip_while(|token| match token {
+                SignatureToken::Reference(inner) => inner.is_signer(),
+                _ => false,
+            })
+            .all(SignatureToken::is_valid_for_constant)
+    }
 }
 
 /// Type parameters are encoded as indices. This index can also be used to lookup the kind of a
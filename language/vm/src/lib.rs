@@ -12,6 +12,7 @@ use core::fmt;
 
 pub mod access;
 pub mod check_bounds;
+pub mod compatibility;
 #[macro_use]
 pub mod errors;
 pub mod constant;
@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use move_core_types::account_address::AccountAddress;
+use mvm::bench::workloads::{gas, loop_tx, publish_module_tx, transfer_tx, vm};
+use mvm::data::ExecutionContext;
+use mvm::Vm;
+
+fn publish_module(c: &mut Criterion) {
+    let (vm, _, _) = vm();
+    c.bench_function("publish_module", |b| {
+        b.iter(|| vm.publish_module(gas(), black_box(publish_module_tx()), true))
+    });
+}
+
+fn transfer(c: &mut Criterion) {
+    let (vm, _, bank) = vm();
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    bank.set_balance(&alice, "PONT", u128::MAX);
+
+    c.bench_function("transfer", |b| {
+        b.iter(|| {
+            vm.execute_script(
+                gas(),
+                ExecutionContext::new(100, 100),
+                black_box(transfer_tx(alice, bob, 10)),
+                false,
+            )
+        })
+    });
+}
+
+fn heavy_loop(c: &mut Criterion) {
+    let (vm, _, _) = vm();
+    c.bench_function("loop_10000", |b| {
+        b.iter(|| {
+            vm.execute_script(
+                gas(),
+                ExecutionContext::new(100, 100),
+                black_box(loop_tx(10_000)),
+                true,
+            )
+        })
+    });
+}
+
+criterion_group!(benches, publish_module, transfer, heavy_loop);
+criterion_main!(benches);
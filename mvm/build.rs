@@ -0,0 +1,30 @@
+//! Builds the Move assets compiled into the crate by the `embedded-stdlib` and `bench` features.
+//! The assets are built with `dove`, see `tests/assets/build_assets.sh`.
+
+use std::env;
+use std::process::Command;
+
+const ASSETS: &str = "tests/assets";
+
+fn main() {
+    for path in &["Dove.toml", "build_assets.sh", "modules", "scripts"] {
+        println!("cargo:rerun-if-changed={}/{}", ASSETS, path);
+    }
+    if env::var_os("CARGO_FEATURE_EMBEDDED_STDLIB").is_none()
+        && env::var_os("CARGO_FEATURE_BENCH").is_none()
+    {
+        return;
+    }
+
+    let status = Command::new("sh")
+        .arg("build_assets.sh")
+        .current_dir(ASSETS)
+        .status()
+        .unwrap_or_else(|err| panic!("Failed to run {}/build_assets.sh: {}", ASSETS, err));
+    if !status.success() {
+        panic!(
+            "{}/build_assets.sh failed with {}, `dove` is required to build the assets",
+            ASSETS, status
+        );
+    }
+}
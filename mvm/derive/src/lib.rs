@@ -0,0 +1,147 @@
+#![forbid(unsafe_code)]
+
+//! Derive macro for `mvm::value::AsMoveValue`.
+
+extern crate proc_macro;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, Ident, Meta,
+    NestedMeta, Result,
+};
+
+/// Derives `AsMoveValue` for a struct with named fields.
+///
+/// Fields are converted in the declaration order, which must match the order of the fields
+/// of the Move struct. The Move field names can be listed with `#[move_fields(a, b)]`:
+/// the derive fails to compile if the Rust fields are declared in another order.
+#[proc_macro_derive(AsMoveValue, attributes(move_fields))]
+pub fn derive_as_move_value(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive_as_move_value_impl(input) {
+        Ok(token_stream) => proc_macro::TokenStream::from(token_stream),
+        Err(err) => proc_macro::TokenStream::from(err.to_compile_error()),
+    }
+}
+
+fn derive_as_move_value_impl(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .map(|field| (field.ident.clone().unwrap(), field.ty.clone()))
+                .collect::<Vec<_>>(),
+            _ => {
+                return Err(Error::new(
+                    input.span(),
+                    "#[derive(AsMoveValue)] requires named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "#[derive(AsMoveValue)] can only be used on a struct",
+            ))
+        }
+    };
+    if let Some(move_fields) = move_fields(&input.attrs)? {
+        check_field_order(&fields, &move_fields)?;
+    }
+
+    let name = input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let count = fields.len();
+    let names = fields.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    let types = fields.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+
+    Ok(quote! {
+        impl #impl_generics ::mvm::value::AsMoveValue for #name #ty_generics #where_clause {
+            fn layout() -> ::mvm::value::MoveTypeLayout {
+                ::mvm::value::MoveTypeLayout::Struct(::mvm::value::MoveStructLayout::new(
+                    ::core::iter::empty()
+                        #(.chain(::core::iter::once(
+                            <#types as ::mvm::value::AsMoveValue>::layout()
+                        )))*
+                        .collect(),
+                ))
+            }
+
+            fn as_move_value(&self) -> ::mvm::value::MoveValue {
+                ::mvm::value::MoveValue::Struct(::mvm::value::MoveStruct::new(
+                    ::core::iter::empty()
+                        #(.chain(::core::iter::once(
+                            ::mvm::value::AsMoveValue::as_move_value(&self.#names)
+                        )))*
+                        .collect(),
+                ))
+            }
+
+            fn from_move_value(
+                value: ::mvm::value::MoveValue,
+            ) -> ::core::result::Result<Self, ::mvm::value::Error> {
+                let mut fields = ::mvm::value::StructFields::new(value, #count)?;
+                ::core::result::Result::Ok(#name {
+                    #(#names: fields.next_field()?,)*
+                })
+            }
+        }
+    })
+}
+
+/// Returns the field names listed in `#[move_fields(..)]`.
+fn move_fields(attrs: &[Attribute]) -> Result<Option<Vec<Ident>>> {
+    let attr = match attrs.iter().find(|attr| attr.path.is_ident("move_fields")) {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+    match attr.parse_meta()? {
+        Meta::List(list) => list
+            .nested
+            .iter()
+            .map(|nested| match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.get_ident().is_some() => {
+                    Ok(path.get_ident().unwrap().clone())
+                }
+                _ => Err(Error::new(nested.span(), "expected a field name")),
+            })
+            .collect::<Result<_>>()
+            .map(Some),
+        meta => Err(Error::new(
+            meta.span(),
+            "expected #[move_fields(field, ..)]",
+        )),
+    }
+}
+
+fn check_field_order(fields: &[(Ident, syn::Type)], move_fields: &[Ident]) -> Result<()> {
+    for (idx, (field, _)) in fields.iter().enumerate() {
+        match move_fields.get(idx) {
+            Some(expected) if expected == field => {}
+            Some(expected) => {
+                return Err(Error::new(
+                    field.span(),
+                    format!(
+                        "field `{}` is declared at the position of the Move field `{}`",
+                        field, expected
+                    ),
+                ))
+            }
+            None => {
+                return Err(Error::new(
+                    field.span(),
+                    format!("field `{}` is not a field of the Move struct", field),
+                ))
+            }
+        }
+    }
+    if let Some(missing) = move_fields.get(fields.len()) {
+        return Err(Error::new(
+            missing.span(),
+            format!("Move field `{}` is missing", missing),
+        ));
+    }
+    Ok(())
+}
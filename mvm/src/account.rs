@@ -0,0 +1,63 @@
+//! Account bootstrap.
+//!
+//! An account exists once the `0x1::Account::T` resource is published under its address.
+//! The vm publishes it with the private `Account::create_account` function of the standard library.
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use move_vm_types::values::{Reference, SignerRef};
+use vm::errors::PartialVMResult;
+
+/// Module of the account resources.
+pub const ACCOUNT_MODULE: &str = "Account";
+/// Resource published for every account.
+pub const ACCOUNT_RESOURCE: &str = "T";
+/// Function publishing the account resources for an address.
+pub const CREATE_ACCOUNT: &str = "create_account";
+/// Resource holding the coins of a currency deposited to the vm.
+pub const BALANCE_RESOURCE: &str = "Balance";
+
+/// Returns the id of the `Account` module.
+pub fn account_module(core_address: AccountAddress) -> ModuleId {
+    ModuleId::new(core_address, Identifier::new(ACCOUNT_MODULE).unwrap())
+}
+
+/// Returns the tag of the `Account::T` resource.
+pub fn account_tag(core_address: AccountAddress) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(ACCOUNT_MODULE).unwrap(),
+        name: Identifier::new(ACCOUNT_RESOURCE).unwrap(),
+        type_params: vec![],
+    }
+}
+
+/// Returns the tag of the `Account::Balance<coin>` resource.
+pub fn balance_tag(core_address: AccountAddress, coin: StructTag) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(ACCOUNT_MODULE).unwrap(),
+        name: Identifier::new(BALANCE_RESOURCE).unwrap(),
+        type_params: vec![TypeTag::Struct(coin)],
+    }
+}
+
+/// Decodes the coin value of the `Account::Balance` resource.
+pub(crate) fn decode_balance(blob: &[u8]) -> Option<u128> {
+    let mut bytes = [0; 16];
+    if blob.len() != bytes.len() {
+        return None;
+    }
+    bytes.copy_from_slice(blob);
+    Some(u128::from_le_bytes(bytes))
+}
+
+/// Returns the address of the signer passed to a native.
+pub(crate) fn signer_address(signer: SignerRef) -> PartialVMResult<AccountAddress> {
+    signer
+        .borrow_signer()?
+        .value_as::<Reference>()?
+        .read_ref()?
+        .value_as::<AccountAddress>()
+}
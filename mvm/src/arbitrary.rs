@@ -0,0 +1,153 @@
+//! Property-test strategies of the vm types, enabled with the `fuzzing` feature.
+//!
+//! The feature also enables the `Arbitrary` implementations of the Move types:
+//! `AccountAddress`, `Identifier`, `TypeTag`, `StructTag`, `ModuleId` and `StatusCode`.
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::vm_status::StatusCode;
+use move_vm_types::natives::balance::{BalanceOperation, WalletId};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::access_path::AccessPath;
+use crate::source_map::{ErrorLocation, SourceLocation};
+use crate::types::{BalanceChange, PriceRead, Ticker, VmResult};
+
+impl Arbitrary for AccessPath {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        prop_oneof![
+            any::<StructTag>().prop_map(|tag| AccessPath::resource_access_vec(&tag)),
+            vec(any::<u8>(), 0..64),
+        ]
+        .prop_flat_map(|path| {
+            any::<AccountAddress>().prop_map(move |address| AccessPath::new(address, path.clone()))
+        })
+        .boxed()
+    }
+}
+
+impl Arbitrary for Ticker {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        "[A-Z0-9_]{1,32}"
+            .prop_map(|ticker| Ticker::new(&ticker).unwrap())
+            .boxed()
+    }
+}
+
+impl Arbitrary for PriceRead {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (any::<Ticker>(), any::<Option<u128>>())
+            .prop_map(|(ticker, price)| PriceRead { ticker, price })
+            .boxed()
+    }
+}
+
+impl Arbitrary for BalanceChange {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (
+            any::<AccountAddress>(),
+            any::<StructTag>(),
+            any::<bool>(),
+            any::<u128>(),
+        )
+            .prop_map(|(address, tag, deposit, amount)| BalanceChange {
+                wallet_id: WalletId::new(address, tag),
+                operation: if deposit {
+                    BalanceOperation::Deposit(amount)
+                } else {
+                    BalanceOperation::Withdraw(amount)
+                },
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for ErrorLocation {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (any::<ModuleId>(), any::<u16>(), any::<u16>())
+            .prop_map(|(module, function, code_offset)| ErrorLocation {
+                module,
+                function,
+                code_offset,
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for SourceLocation {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        ("[a-zA-Z0-9_/]{1,32}\\.move", any::<u32>())
+            .prop_map(|(file, line)| SourceLocation { file, line })
+            .boxed()
+    }
+}
+
+impl Arbitrary for VmResult {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (
+            any::<StatusCode>(),
+            any::<Option<u64>>(),
+            any::<u64>(),
+            vec(any::<PriceRead>(), 0..4),
+            vec(any::<BalanceChange>(), 0..4),
+            any::<Option<String>>(),
+            any::<Option<ErrorLocation>>(),
+            any::<Option<SourceLocation>>(),
+            any::<Option<[u8; 32]>>(),
+            vec(vec(any::<u8>(), 1..48), 0..4),
+            any::<bool>(),
+        )
+            .prop_map(
+                |(
+                    status_code,
+                    sub_status,
+                    gas_used,
+                    price_reads,
+                    balance_changes,
+                    abort_message,
+                    location,
+                    source_location,
+                    write_set_digest,
+                    deletions,
+                    halted,
+                )| {
+                    VmResult {
+                        status_code,
+                        sub_status,
+                        gas_used,
+                        price_reads,
+                        balance_changes,
+                        abort_message,
+                        location,
+                        source_location,
+                        write_set_digest,
+                        deletions,
+                        halted,
+                    }
+                },
+            )
+            .boxed()
+    }
+}
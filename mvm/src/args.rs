@@ -0,0 +1,329 @@
+//! Transaction arguments built from the module metadata.
+//!
+//! `ArgsBuilder` takes the parameter types of an entry function from `ModuleMetadata` and converts
+//! user values into the `ScriptArg`s of a `ScriptTx`. Signer parameters are skipped: the signers
+//! are the transaction senders. Values are given as strings or, with the `std` feature, as JSON:
+//! - integers as decimal numbers, JSON strings are accepted for the values over `u64`;
+//! - booleans as `true` or `false`;
+//! - addresses as hex literals, e.g. `0x1`;
+//! - `vector<u8>` as a hex string, e.g. `0x0102`, or as a list;
+//! - other vectors as lists, e.g. `[1, 2, 3]`.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use move_core_types::account_address::AccountAddress;
+
+use crate::metadata::ModuleMetadata;
+use crate::types::ScriptArg;
+
+/// Error of the argument conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgError {
+    /// The module has no function with the name.
+    UnknownFunction(String),
+    /// The function is not an entry function and can't be called by a transaction.
+    NotEntry(String),
+    /// The number of values differs from the number of the non-signer parameters.
+    ArgumentCount { expected: usize, actual: usize },
+    /// The parameter type can't be passed as a transaction argument.
+    UnsupportedType { index: usize, type_: String },
+    /// The value doesn't match the parameter type.
+    InvalidValue {
+        index: usize,
+        type_: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgError::UnknownFunction(name) => write!(f, "Unknown function {}", name),
+            ArgError::NotEntry(name) => write!(f, "Function {} is not an entry function", name),
+            ArgError::ArgumentCount { expected, actual } => {
+                write!(f, "Expected {} arguments, got {}", expected, actual)
+            }
+            ArgError::UnsupportedType { index, type_ } => {
+                write!(f, "Argument {}: unsupported type {}", index, type_)
+            }
+            ArgError::InvalidValue {
+                index,
+                type_,
+                reason,
+            } => write!(f, "Argument {}: invalid {}: {}", index, type_, reason),
+        }
+    }
+}
+
+/// Type of a transaction argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArgType {
+    U8,
+    U64,
+    U128,
+    Bool,
+    Address,
+    Vector(ElemType),
+}
+
+/// Type of a vector element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElemType {
+    U8,
+    U64,
+    U128,
+    Bool,
+    Address,
+}
+
+impl ElemType {
+    fn parse(type_: &str) -> Option<ElemType> {
+        Some(match type_ {
+            "u8" => ElemType::U8,
+            "u64" => ElemType::U64,
+            "u128" => ElemType::U128,
+            "bool" => ElemType::Bool,
+            "address" => ElemType::Address,
+            _ => return None,
+        })
+    }
+}
+
+impl ArgType {
+    fn parse(type_: &str) -> Option<ArgType> {
+        if let Some(elem) = type_
+            .strip_prefix("vector<")
+            .and_then(|inner| inner.strip_suffix('>'))
+        {
+            return ElemType::parse(elem).map(ArgType::Vector);
+        }
+        Some(match ElemType::parse(type_)? {
+            ElemType::U8 => ArgType::U8,
+            ElemType::U64 => ArgType::U64,
+            ElemType::U128 => ArgType::U128,
+            ElemType::Bool => ArgType::Bool,
+            ElemType::Address => ArgType::Address,
+        })
+    }
+}
+
+/// Builder of the arguments of a function call.
+#[derive(Debug, Clone)]
+pub struct ArgsBuilder {
+    /// Types of the non-signer parameters.
+    types: Vec<String>,
+}
+
+impl ArgsBuilder {
+    /// Creates the builder of the arguments of the entry function of the module.
+    pub fn new(module: &ModuleMetadata, function: &str) -> Result<ArgsBuilder, ArgError> {
+        let function = module
+            .functions
+            .iter()
+            .find(|def| def.name == function)
+            .ok_or_else(|| ArgError::UnknownFunction(function.to_string()))?;
+        if !function.is_entry {
+            return Err(ArgError::NotEntry(function.name.clone()));
+        }
+        Ok(ArgsBuilder {
+            types: function
+                .parameters
+                .iter()
+                .filter(|type_| !is_signer(type_))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// Returns the types of the arguments, signers excluded.
+    pub fn types(&self) -> &[String] {
+        &self.types
+    }
+
+    /// Converts the string values into the arguments.
+    pub fn from_strings<V: AsRef<str>>(&self, values: &[V]) -> Result<Vec<ScriptArg>, ArgError> {
+        self.build(values, |arg_type, value| {
+            parse_str(arg_type, value.as_ref())
+        })
+    }
+
+    /// Converts the JSON values into the arguments.
+    #[cfg(feature = "std")]
+    pub fn from_json(&self, values: &[serde_json::Value]) -> Result<Vec<ScriptArg>, ArgError> {
+        self.build(values, json::parse)
+    }
+
+    fn build<V>(
+        &self,
+        values: &[V],
+        parse: impl Fn(ArgType, &V) -> Result<ScriptArg, String>,
+    ) -> Result<Vec<ScriptArg>, ArgError> {
+        if values.len() != self.types.len() {
+            return Err(ArgError::ArgumentCount {
+                expected: self.types.len(),
+                actual: values.len(),
+            });
+        }
+        self.types
+            .iter()
+            .zip(values)
+            .enumerate()
+            .map(|(index, (type_, value))| {
+                let arg_type = ArgType::parse(type_).ok_or_else(|| ArgError::UnsupportedType {
+                    index,
+                    type_: type_.clone(),
+                })?;
+                parse(arg_type, value).map_err(|reason| ArgError::InvalidValue {
+                    index,
+                    type_: type_.clone(),
+                    reason,
+                })
+            })
+            .collect()
+    }
+}
+
+fn is_signer(type_: &str) -> bool {
+    type_ == "signer" || type_ == "&signer"
+}
+
+fn parse_str(arg_type: ArgType, value: &str) -> Result<ScriptArg, String> {
+    let value = value.trim();
+    Ok(match arg_type {
+        ArgType::U8 => ScriptArg::U8(parse_int(value)?),
+        ArgType::U64 => ScriptArg::U64(parse_int(value)?),
+        ArgType::U128 => ScriptArg::U128(parse_int(value)?),
+        ArgType::Bool => ScriptArg::Bool(parse_bool(value)?),
+        ArgType::Address => ScriptArg::Address(parse_address(value)?),
+        ArgType::Vector(ElemType::U8) if value.starts_with("0x") => {
+            ScriptArg::VectorU8(hex::decode(&value[2..]).map_err(|err| err.to_string())?)
+        }
+        ArgType::Vector(elem) => {
+            let items = value
+                .strip_prefix('[')
+                .and_then(|list| list.strip_suffix(']'))
+                .ok_or_else(|| "expected a list in brackets".to_string())?
+                .trim();
+            let items = if items.is_empty() {
+                vec![]
+            } else {
+                items.split(',').map(str::trim).collect()
+            };
+            match elem {
+                ElemType::U8 => ScriptArg::VectorU8(parse_items(&items, parse_int)?),
+                ElemType::U64 => ScriptArg::VectorU64(parse_items(&items, parse_int)?),
+                ElemType::U128 => ScriptArg::VectorU128(parse_items(&items, parse_int)?),
+                ElemType::Bool => ScriptArg::VectorBool(parse_items(&items, parse_bool)?),
+                ElemType::Address => ScriptArg::VectorAddress(parse_items(&items, parse_address)?),
+            }
+        }
+    })
+}
+
+fn parse_items<T>(
+    items: &[&str],
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Vec<T>, String> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| parse(item).map_err(|err| format!("item {}: {}", idx, err)))
+        .collect()
+}
+
+fn parse_int<T: core::str::FromStr>(value: &str) -> Result<T, String>
+where
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|err: T::Err| err.to_string())
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err("expected true or false".to_string()),
+    }
+}
+
+fn parse_address(value: &str) -> Result<AccountAddress, String> {
+    AccountAddress::from_hex_literal(value).map_err(|err| err.to_string())
+}
+
+#[cfg(feature = "std")]
+mod json {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    use move_core_types::account_address::AccountAddress;
+    use serde_json::Value;
+
+    use super::{parse_address, parse_int, ArgType, ElemType};
+    use crate::types::ScriptArg;
+
+    pub(super) fn parse(arg_type: ArgType, value: &Value) -> Result<ScriptArg, String> {
+        Ok(match arg_type {
+            ArgType::U8 => ScriptArg::U8(int(value)?),
+            ArgType::U64 => ScriptArg::U64(int(value)?),
+            ArgType::U128 => ScriptArg::U128(int(value)?),
+            ArgType::Bool => ScriptArg::Bool(boolean(value)?),
+            ArgType::Address => ScriptArg::Address(address(value)?),
+            ArgType::Vector(ElemType::U8) if value.is_string() => {
+                let hex_str = value
+                    .as_str()
+                    .and_then(|hex_str| hex_str.strip_prefix("0x"))
+                    .ok_or_else(|| "expected a 0x prefixed hex string".to_string())?;
+                ScriptArg::VectorU8(hex::decode(hex_str).map_err(|err| err.to_string())?)
+            }
+            ArgType::Vector(elem) => {
+                let items = value
+                    .as_array()
+                    .ok_or_else(|| "expected an array".to_string())?;
+                match elem {
+                    ElemType::U8 => ScriptArg::VectorU8(list(items, int)?),
+                    ElemType::U64 => ScriptArg::VectorU64(list(items, int)?),
+                    ElemType::U128 => ScriptArg::VectorU128(list(items, int)?),
+                    ElemType::Bool => ScriptArg::VectorBool(list(items, boolean)?),
+                    ElemType::Address => ScriptArg::VectorAddress(list(items, address)?),
+                }
+            }
+        })
+    }
+
+    fn list<T>(
+        items: &[Value],
+        parse: impl Fn(&Value) -> Result<T, String>,
+    ) -> Result<Vec<T>, String> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| parse(item).map_err(|err| format!("item {}: {}", idx, err)))
+            .collect()
+    }
+
+    fn int<T: core::str::FromStr>(value: &Value) -> Result<T, String>
+    where
+        T::Err: core::fmt::Display,
+    {
+        match value {
+            Value::Number(number) => parse_int(&number.to_string()),
+            Value::String(number) => parse_int(number),
+            _ => Err("expected a number".to_string()),
+        }
+    }
+
+    fn boolean(value: &Value) -> Result<bool, String> {
+        value
+            .as_bool()
+            .ok_or_else(|| "expected true or false".to_string())
+    }
+
+    fn address(value: &Value) -> Result<AccountAddress, String> {
+        value
+            .as_str()
+            .ok_or_else(|| "expected a hex string".to_string())
+            .and_then(parse_address)
+    }
+}
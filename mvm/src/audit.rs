@@ -0,0 +1,97 @@
+//! Out-of-gas determinism audit.
+//!
+//! A transaction which runs out of gas is re-executed with a higher gas limit.
+//! The resource reads of the failed run must be a prefix of the reads of the re-run,
+//! otherwise the out-of-gas path depends on something besides the transaction and the state.
+//! Module reads are not compared: the loader reads modules only on cache misses.
+
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_vm_runtime::data_cache::RemoteCache;
+use vm::errors::{PartialVMResult, VMResult};
+
+/// Resource read made by the vm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceRead {
+    pub address: AccountAddress,
+    pub tag: StructTag,
+}
+
+/// Mismatch between the out-of-gas run and the re-run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OogDivergence {
+    /// Index of the first mismatched read.
+    pub index: usize,
+    /// Read made by the out-of-gas run.
+    pub expected: ResourceRead,
+    /// Read made by the re-run at the same index.
+    pub actual: Option<ResourceRead>,
+}
+
+/// Returns the first read of `oog` which does not match the `rerun` read at the same index.
+pub fn find_divergence(oog: &[ResourceRead], rerun: &[ResourceRead]) -> Option<OogDivergence> {
+    oog.iter().enumerate().find_map(|(index, expected)| {
+        let actual = rerun.get(index);
+        if actual == Some(expected) {
+            None
+        } else {
+            Some(OogDivergence {
+                index,
+                expected: expected.clone(),
+                actual: actual.cloned(),
+            })
+        }
+    })
+}
+
+/// Remote cache which records resource reads in order.
+pub(crate) struct ReadRecorder<'a, R: RemoteCache> {
+    remote: &'a R,
+    reads: Option<RefCell<Vec<ResourceRead>>>,
+}
+
+impl<'a, R: RemoteCache> ReadRecorder<'a, R> {
+    /// Creates a recorder. Reads are passed through without recording if `record` is `false`.
+    pub fn new(remote: &'a R, record: bool) -> ReadRecorder<'a, R> {
+        ReadRecorder {
+            remote,
+            reads: if record {
+                Some(RefCell::new(Vec::new()))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Returns recorded reads.
+    pub fn into_reads(self) -> Vec<ResourceRead> {
+        self.reads.map(RefCell::into_inner).unwrap_or_default()
+    }
+}
+
+impl<R: RemoteCache> RemoteCache for ReadRecorder<'_, R> {
+    fn get_module(&self, module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
+        self.remote.get_module(module_id)
+    }
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        if let Some(reads) = &self.reads {
+            reads.borrow_mut().push(ResourceRead {
+                address: *address,
+                tag: tag.clone(),
+            });
+        }
+        self.remote.get_resource(address, tag)
+    }
+
+    fn get_twap(&self, tag: &StructTag, window: u64) -> PartialVMResult<Option<u128>> {
+        self.remote.get_twap(tag, window)
+    }
+}
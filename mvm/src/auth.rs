@@ -0,0 +1,152 @@
+//! Transaction authentication hook.
+//!
+//! Native of `0x1::Auth`: `rotate_key(account: &signer, new_key: vector<u8>)`.
+//! The key is stored in the `Auth::AuthKey` resource of the signer together with the
+//! transaction effects and is passed to `Authenticator::authenticate_with_keys` for the
+//! following transactions of the account. The vm does not interpret the key.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::StructTag;
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::pop_arg;
+use move_vm_types::values::{SignerRef, Value};
+use vm::errors::{Location, PartialVMError, VMError};
+
+use crate::account::signer_address;
+use crate::data::{AccessKey, Storage};
+use crate::hash::Digest;
+
+/// Module of the key rotation native.
+pub const AUTH_MODULE: &str = "Auth";
+/// Name of the key rotation native.
+pub const ROTATE_KEY: &str = "rotate_key";
+/// Resource holding the authentication key of an account.
+pub const AUTH_KEY: &str = "AuthKey";
+/// Gas charged for a key rotation in internal gas units.
+pub const ROTATE_KEY_GAS: NativeGasParams = NativeGasParams {
+    base: 500,
+    per_byte: 1,
+};
+
+/// Authenticates script senders before the execution.
+///
+/// Lets the host implement multisig, session keys or signature scheme rotation
+/// at the vm boundary. The proof is an opaque blob carried by `ScriptTx`.
+pub trait Authenticator {
+    /// Returns `Err` with the host-defined reason code if the proof does not authorize the senders
+    /// to send the transaction with the `message`, see `ScriptTx::signing_message`.
+    /// The reason code is reported as the sub status of `INVALID_SIGNATURE`.
+    fn authenticate(
+        &self,
+        message: &Digest,
+        senders: &[AccountAddress],
+        proof: &[u8],
+    ) -> Result<(), u64>;
+
+    /// Same as `authenticate` with the keys of the senders set by `Auth::rotate_key`,
+    /// `None` for a sender which never rotated its key.
+    /// The vm calls this method, by default it ignores the keys.
+    fn authenticate_with_keys(
+        &self,
+        message: &Digest,
+        senders: &[AccountAddress],
+        _keys: &[Option<Vec<u8>>],
+        proof: &[u8],
+    ) -> Result<(), u64> {
+        self.authenticate(message, senders, proof)
+    }
+}
+
+impl<F> Authenticator for F
+where
+    F: Fn(&Digest, &[AccountAddress], &[u8]) -> Result<(), u64>,
+{
+    fn authenticate(
+        &self,
+        message: &Digest,
+        senders: &[AccountAddress],
+        proof: &[u8],
+    ) -> Result<(), u64> {
+        self(message, senders, proof)
+    }
+}
+
+/// Keys rotated by the session, kept in its native extensions. The last rotation of an
+/// account wins.
+#[derive(Debug, Default)]
+pub(crate) struct KeyRotations(pub BTreeMap<AccountAddress, Vec<u8>>);
+
+/// Returns the tag of `Auth::AuthKey`.
+pub fn auth_key_tag(core_address: AccountAddress) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(AUTH_MODULE).unwrap(),
+        name: Identifier::new(AUTH_KEY).unwrap(),
+        type_params: vec![],
+    }
+}
+
+/// Registers the key rotation native at the core address.
+pub(crate) fn register_auth(
+    natives: NativeRegistry,
+    core_address: AccountAddress,
+) -> NativeRegistry {
+    natives.register(
+        core_address,
+        AUTH_MODULE,
+        ROTATE_KEY,
+        ROTATE_KEY_GAS,
+        |context: &mut dyn NativeContext, _: Vec<Type>, mut args: VecDeque<Value>| {
+            let new_key = pop_arg!(args, Vec<u8>);
+            let address = signer_address(pop_arg!(args, SignerRef))?;
+            context
+                .extensions()
+                .get_or_default::<KeyRotations>()
+                .0
+                .insert(address, new_key);
+            Ok(NativeResult::ok(GasUnits::new(0), vec![]))
+        },
+    )
+}
+
+/// Returns the writes of the `Auth::AuthKey` resources of the rotated keys.
+pub(crate) fn key_writes(
+    core_address: AccountAddress,
+    rotations: BTreeMap<AccountAddress, Vec<u8>>,
+) -> Vec<(AccessKey, Option<Vec<u8>>)> {
+    rotations
+        .into_iter()
+        .map(|(address, key)| {
+            (
+                AccessKey::from((&address, &auth_key_tag(core_address))),
+                Some(bcs::to_bytes(&key).expect("Key serialization must not fail")),
+            )
+        })
+        .collect()
+}
+
+/// Loads the authentication key of the account, `None` if the key was never rotated.
+pub fn load_auth_key<S: Storage>(
+    storage: &S,
+    core_address: AccountAddress,
+    address: &AccountAddress,
+) -> Result<Option<Vec<u8>>, VMError> {
+    let key = AccessKey::from((address, &auth_key_tag(core_address)));
+    storage
+        .get(key.as_ref())
+        .map(|blob| {
+            bcs::from_bytes(&blob).map_err(|_| {
+                PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE)
+                    .finish(Location::Undefined)
+            })
+        })
+        .transpose()
+}
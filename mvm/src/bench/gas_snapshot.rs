@@ -0,0 +1,215 @@
+//! Golden-file snapshots of the gas used by transactions.
+//!
+//! A snapshot maps the transaction names to the gas they used and is stored as text,
+//! one `<name> <gas used>` line per transaction. `GasSnapshot::check_file` compares
+//! the recorded gas with the golden file, so accidental cost changes fail the tests.
+//! The golden file is committed with the tests and is only written when the
+//! `UPDATE_GAS_SNAPSHOTS` environment variable is set, a missing file fails the check.
+//!
+//! `canonical_snapshot` records the gas of the `workloads`. Chains can pin it or record
+//! their own transactions.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use anyhow::Error;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::vm_status::StatusCode;
+
+use crate::bench::workloads::{gas, loop_tx, publish_module_tx, transfer_tx, vm};
+use crate::data::ExecutionContext;
+use crate::types::VmResult;
+use crate::Vm;
+
+/// Environment variable which makes `GasSnapshot::check_file` rewrite the golden file.
+pub const UPDATE_GAS_SNAPSHOTS: &str = "UPDATE_GAS_SNAPSHOTS";
+
+/// Gas used by the named transactions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasSnapshot {
+    entries: BTreeMap<String, u64>,
+}
+
+/// Gas change of a transaction. `None` if the transaction is missing from the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasChange {
+    pub name: String,
+    pub expected: Option<u64>,
+    pub actual: Option<u64>,
+}
+
+impl fmt::Display for GasChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gas = |gas: Option<u64>| gas.map(|gas| gas.to_string()).unwrap_or_else(|| "-".into());
+        write!(
+            f,
+            "{}: expected {}, actual {}",
+            self.name,
+            gas(self.expected),
+            gas(self.actual)
+        )
+    }
+}
+
+impl GasSnapshot {
+    pub fn new() -> GasSnapshot {
+        GasSnapshot::default()
+    }
+
+    /// Records the gas used by the transaction.
+    pub fn record(&mut self, name: &str, gas_used: u64) {
+        self.entries.insert(name.to_string(), gas_used);
+    }
+
+    /// Records the gas used by the executed transaction.
+    /// Returns an error if the transaction failed: the gas of a failure is not a stable cost.
+    pub fn record_result(&mut self, name: &str, result: &VmResult) -> Result<(), Error> {
+        anyhow::ensure!(
+            result.status_code == StatusCode::EXECUTED,
+            "Transaction {} failed: {}",
+            name,
+            result
+        );
+        self.record(name, result.gas_used);
+        Ok(())
+    }
+
+    /// Returns the gas used by the transaction.
+    pub fn gas_used(&self, name: &str) -> Option<u64> {
+        self.entries.get(name).copied()
+    }
+
+    /// Parses the snapshot text. Empty lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<GasSnapshot, Error> {
+        let mut snapshot = GasSnapshot::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(name), Some(gas_used), None) => {
+                    let gas_used = gas_used.parse().map_err(|err| {
+                        Error::msg(format!("Invalid gas at line {}: {}", idx + 1, err))
+                    })?;
+                    snapshot.record(name, gas_used);
+                }
+                _ => {
+                    return Err(Error::msg(format!(
+                        "Expected `<name> <gas used>` at line {}",
+                        idx + 1
+                    )))
+                }
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Returns the gas changes against the expected snapshot ordered by the transaction name.
+    pub fn diff(&self, expected: &GasSnapshot) -> Vec<GasChange> {
+        let mut names = self.entries.keys().collect::<Vec<_>>();
+        names.extend(expected.entries.keys());
+        names.sort();
+        names.dedup();
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let actual = self.gas_used(name);
+                let expected = expected.gas_used(name);
+                if actual == expected {
+                    None
+                } else {
+                    Some(GasChange {
+                        name: name.clone(),
+                        expected,
+                        actual,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Compares the snapshot with the golden file.
+    /// Writes the golden file instead if `UPDATE_GAS_SNAPSHOTS` is set.
+    pub fn check_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        if std::env::var_os(UPDATE_GAS_SNAPSHOTS).is_some() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(Error::msg)?;
+            }
+            return fs::write(path, self.to_string()).map_err(Error::msg);
+        }
+        if !path.exists() {
+            return Err(Error::msg(format!(
+                "Golden file {} is missing, run with {}=1 to create it",
+                path.display(),
+                UPDATE_GAS_SNAPSHOTS
+            )));
+        }
+
+        let expected = GasSnapshot::parse(&fs::read_to_string(path).map_err(Error::msg)?)?;
+        let changes = self.diff(&expected);
+        if changes.is_empty() {
+            Ok(())
+        } else {
+            let changes = changes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n");
+            Err(Error::msg(format!(
+                "Gas costs changed, rerun with {}=1 to update {}:\n{}",
+                UPDATE_GAS_SNAPSHOTS,
+                path.display(),
+                changes
+            )))
+        }
+    }
+}
+
+impl fmt::Display for GasSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, gas_used) in &self.entries {
+            writeln!(f, "{} {}", name, gas_used)?;
+        }
+        Ok(())
+    }
+}
+
+/// Records the gas used by the canonical workloads on a new `workloads::vm()`.
+pub fn canonical_snapshot() -> Result<GasSnapshot, Error> {
+    let (vm, _, bank) = vm();
+    let alice = AccountAddress::new([0xa; AccountAddress::LENGTH]);
+    let bob = AccountAddress::new([0xb; AccountAddress::LENGTH]);
+    bank.set_balance(&alice, "PONT", 1_000);
+    let context = || ExecutionContext::new(100, 100);
+
+    let mut snapshot = GasSnapshot::new();
+    snapshot.record_result(
+        "publish_module",
+        &vm.publish_module(gas(), publish_module_tx(), true),
+    )?;
+    snapshot.record_result(
+        "transfer",
+        &vm.execute_script(gas(), context(), transfer_tx(alice, bob, 10), false),
+    )?;
+    snapshot.record_result(
+        "transfer_to_existing",
+        &vm.execute_script(gas(), context(), transfer_tx(alice, bob, 10), false),
+    )?;
+    snapshot.record_result(
+        "loop_10",
+        &vm.execute_script(gas(), context(), loop_tx(10), true),
+    )?;
+    snapshot.record_result(
+        "loop_1000",
+        &vm.execute_script(gas(), context(), loop_tx(1000), true),
+    )?;
+    Ok(snapshot)
+}
@@ -0,0 +1,6 @@
+//! Benchmark helpers.
+
+pub mod gas_snapshot;
+#[cfg(feature = "benchmarking")]
+pub mod weights;
+pub mod workloads;
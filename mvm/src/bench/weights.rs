@@ -0,0 +1,321 @@
+//! Worst-case transactions for the weight benchmarks of the host chains.
+//!
+//! Every generator is parameterized by a component of the weight formula: the number of basic
+//! blocks of the published module, the call depth of the script or the size of the written
+//! resource. The `bench_*` functions execute the transaction on a fresh `vm()` and report the used
+//! gas and the execution time, so benchmark pipelines can fit the weight formulas to the reports.
+
+use std::fmt;
+use std::string::String;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use anyhow::Error;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use move_core_types::vm_status::StatusCode;
+use vm::file_format::{
+    empty_module, AddressIdentifierIndex, Bytecode, CodeUnit, CompiledScriptMut, FieldDefinition,
+    FunctionDefinition, FunctionHandle, FunctionHandleIndex, IdentifierIndex, ModuleHandle,
+    ModuleHandleIndex, Signature, SignatureIndex, SignatureToken, StructDefinition,
+    StructDefinitionIndex, StructFieldInformation, StructHandle, StructHandleIndex, TypeSignature,
+};
+
+use crate::bench::workloads::{gas, vm};
+use crate::data::ExecutionContext;
+use crate::types::{ModuleTx, ScriptArg, ScriptTx, VmResult};
+use crate::Vm;
+
+/// Module of the call and write benchmarks.
+pub const BENCH_MODULE: &str = "Bench";
+/// Module of the publish benchmark.
+pub const PADDING_MODULE: &str = "Padding";
+
+/// Measured execution of a benchmark transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    /// Name of the benchmark.
+    pub name: String,
+    /// Value of the measured component, e.g. the call depth.
+    pub component: u64,
+    pub gas_used: u64,
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.name,
+            self.component,
+            self.gas_used,
+            self.elapsed.as_nanos()
+        )
+    }
+}
+
+/// Executes the transaction and measures its execution time.
+/// Returns an error if the transaction failed.
+pub fn measure(
+    name: &str,
+    component: u64,
+    tx: impl FnOnce() -> VmResult,
+) -> Result<Measurement, Error> {
+    let start = Instant::now();
+    let result = tx();
+    let elapsed = start.elapsed();
+    anyhow::ensure!(
+        result.status_code == StatusCode::EXECUTED,
+        "Benchmark {} failed: {}",
+        name,
+        result
+    );
+    Ok(Measurement {
+        name: name.into(),
+        component,
+        gas_used: result.gas_used,
+        elapsed,
+    })
+}
+
+/// Module `address::Padding` declaring `public fun pad()` of `blocks` basic blocks.
+/// Every block is a jump to the next one, so the module maximizes the verification cost per byte.
+pub fn padding_module(address: AccountAddress, blocks: u16) -> Result<ModuleTx, Error> {
+    let mut m = empty_module();
+    m.address_identifiers = vec![address];
+    m.identifiers = vec![Identifier::new(PADDING_MODULE)?, Identifier::new("pad")?];
+    m.signatures = vec![Signature(vec![])];
+    m.function_handles = vec![FunctionHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(1),
+        parameters: SignatureIndex(0),
+        return_: SignatureIndex(0),
+        type_parameters: vec![],
+    }];
+    let mut code = (1..blocks).map(Bytecode::Branch).collect::<Vec<_>>();
+    code.push(Bytecode::Ret);
+    m.function_defs = vec![FunctionDefinition {
+        function: FunctionHandleIndex(0),
+        is_public: true,
+        acquires_global_resources: vec![],
+        code: Some(CodeUnit {
+            locals: SignatureIndex(0),
+            code,
+        }),
+    }];
+
+    let mut code = vec![];
+    m.serialize(&mut code)?;
+    Ok(ModuleTx::new(code, address))
+}
+
+/// Module `address::Bench` declaring:
+/// ```move
+/// resource struct Blob { data: vector<u8> }
+/// public fun recurse(n: u64) { if (n != 0) recurse(n - 1) }
+/// public fun store(account: &signer, data: vector<u8>) { move_to(account, Blob { data }) }
+/// ```
+pub fn bench_module(address: AccountAddress) -> Result<ModuleTx, Error> {
+    use SignatureToken::*;
+
+    let mut m = empty_module();
+    m.address_identifiers = vec![address];
+    m.identifiers = vec![
+        Identifier::new(BENCH_MODULE)?,
+        Identifier::new("Blob")?,
+        Identifier::new("data")?,
+        Identifier::new("recurse")?,
+        Identifier::new("store")?,
+    ];
+    m.struct_handles = vec![StructHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(1),
+        is_nominal_resource: true,
+        type_parameters: vec![],
+    }];
+    m.struct_defs = vec![StructDefinition {
+        struct_handle: StructHandleIndex(0),
+        field_information: StructFieldInformation::Declared(vec![FieldDefinition {
+            name: IdentifierIndex(2),
+            signature: TypeSignature(Vector(Box::new(U8))),
+        }]),
+    }];
+    m.signatures = vec![
+        Signature(vec![]),
+        Signature(vec![U64]),
+        Signature(vec![Reference(Box::new(Signer)), Vector(Box::new(U8))]),
+    ];
+    m.function_handles = vec![
+        FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(3),
+            parameters: SignatureIndex(1),
+            return_: SignatureIndex(0),
+            type_parameters: vec![],
+        },
+        FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(4),
+            parameters: SignatureIndex(2),
+            return_: SignatureIndex(0),
+            type_parameters: vec![],
+        },
+    ];
+    let blob = StructDefinitionIndex(0);
+    m.function_defs = vec![
+        FunctionDefinition {
+            function: FunctionHandleIndex(0),
+            is_public: true,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code: vec![
+                    Bytecode::CopyLoc(0),
+                    Bytecode::LdU64(0),
+                    Bytecode::Eq,
+                    Bytecode::BrFalse(5),
+                    Bytecode::Ret,
+                    Bytecode::MoveLoc(0),
+                    Bytecode::LdU64(1),
+                    Bytecode::Sub,
+                    Bytecode::Call(FunctionHandleIndex(0)),
+                    Bytecode::Ret,
+                ],
+            }),
+        },
+        FunctionDefinition {
+            function: FunctionHandleIndex(1),
+            is_public: true,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code: vec![
+                    Bytecode::MoveLoc(0),
+                    Bytecode::MoveLoc(1),
+                    Bytecode::Pack(blob),
+                    Bytecode::MoveTo(blob),
+                    Bytecode::Ret,
+                ],
+            }),
+        },
+    ];
+
+    let mut code = vec![];
+    m.serialize(&mut code)?;
+    Ok(ModuleTx::new(code, address))
+}
+
+/// Script calling `address::Bench::recurse`, the call stack grows to `depth + 2` frames.
+pub fn deep_call_tx(address: AccountAddress, depth: u64) -> Result<ScriptTx, Error> {
+    let code = bench_script(address, "recurse", vec![SignatureToken::U64])?;
+    Ok(ScriptTx::new(
+        code,
+        vec![ScriptArg::U64(depth)],
+        vec![],
+        vec![],
+    ))
+}
+
+/// Script storing `bytes` bytes under the `sender` with `address::Bench::store`.
+/// The sender must not have the blob yet.
+pub fn storage_write_tx(
+    address: AccountAddress,
+    sender: AccountAddress,
+    bytes: usize,
+) -> Result<ScriptTx, Error> {
+    use SignatureToken::*;
+
+    let code = bench_script(
+        address,
+        "store",
+        vec![Reference(Box::new(Signer)), Vector(Box::new(U8))],
+    )?;
+    Ok(ScriptTx::new(
+        code,
+        vec![ScriptArg::VectorU8(vec![0xAB; bytes])],
+        vec![],
+        vec![sender],
+    ))
+}
+
+/// Script passing its arguments to `address::Bench::function`.
+fn bench_script(
+    address: AccountAddress,
+    function: &str,
+    params: Vec<SignatureToken>,
+) -> Result<Vec<u8>, Error> {
+    let code = (0..params.len())
+        .map(|idx| Bytecode::MoveLoc(idx as u8))
+        .chain(vec![Bytecode::Call(FunctionHandleIndex(0)), Bytecode::Ret])
+        .collect();
+    let script = CompiledScriptMut {
+        module_handles: vec![ModuleHandle {
+            address: AddressIdentifierIndex(0),
+            name: IdentifierIndex(0),
+        }],
+        struct_handles: vec![],
+        function_handles: vec![FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(1),
+            parameters: SignatureIndex(1),
+            return_: SignatureIndex(0),
+            type_parameters: vec![],
+        }],
+        function_instantiations: vec![],
+        signatures: vec![Signature(vec![]), Signature(params)],
+        identifiers: vec![Identifier::new(BENCH_MODULE)?, Identifier::new(function)?],
+        address_identifiers: vec![address],
+        constant_pool: vec![],
+        type_parameters: vec![],
+        parameters: SignatureIndex(1),
+        code: CodeUnit {
+            locals: SignatureIndex(0),
+            code,
+        },
+    };
+
+    let mut code = vec![];
+    script.serialize(&mut code)?;
+    Ok(code)
+}
+
+/// Measures the publication of the padding module of `blocks` basic blocks.
+/// The component is the module size in bytes.
+pub fn bench_publish(blocks: u16) -> Result<Measurement, Error> {
+    let (vm, _, _) = vm();
+    let module = padding_module(CORE_CODE_ADDRESS, blocks)?;
+    let size = module.code().len() as u64;
+    measure("publish", size, || vm.publish_module(gas(), module, false))
+}
+
+/// Measures the script calling the recursive function `depth` times.
+pub fn bench_deep_call(depth: u64) -> Result<Measurement, Error> {
+    let (vm, _, _) = vm();
+    let result = vm.publish_module(gas(), bench_module(CORE_CODE_ADDRESS)?, false);
+    anyhow::ensure!(
+        result.status_code == StatusCode::EXECUTED,
+        "Failed to publish the bench module: {}",
+        result
+    );
+    let tx = deep_call_tx(CORE_CODE_ADDRESS, depth)?;
+    measure("deep_call", depth, || {
+        vm.execute_script(gas(), ExecutionContext::new(100, 100), tx, false)
+    })
+}
+
+/// Measures the script writing a resource of `bytes` bytes.
+pub fn bench_storage_write(bytes: usize) -> Result<Measurement, Error> {
+    let (vm, _, _) = vm();
+    let result = vm.publish_module(gas(), bench_module(CORE_CODE_ADDRESS)?, false);
+    anyhow::ensure!(
+        result.status_code == StatusCode::EXECUTED,
+        "Failed to publish the bench module: {}",
+        result
+    );
+    let tx = storage_write_tx(CORE_CODE_ADDRESS, AccountAddress::random(), bytes)?;
+    measure("storage_write", bytes as u64, || {
+        vm.execute_script(gas(), ExecutionContext::new(100, 100), tx, false)
+    })
+}
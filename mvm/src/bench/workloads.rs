@@ -0,0 +1,125 @@
+//! Representative transactions to measure vm performance.
+
+use std::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+use vm::file_format::{
+    Bytecode, CodeUnit, CompiledScriptMut, Signature, SignatureIndex, SignatureToken,
+};
+
+use crate::builder::MvmBuilder;
+use crate::data::MemoryStorage;
+use crate::mvm::Mvm;
+use crate::stdlib::stdlib_package;
+use crate::testkit::mock::{BankMock, EventHandlerMock, OracleMock, Utils};
+use crate::types::{Gas, ModuleTx, PublishPackageTx, ScriptArg, ScriptTx};
+
+/// Move VM over `MemoryStorage`.
+pub type BenchVm = Mvm<MemoryStorage, EventHandlerMock, OracleMock, BankMock>;
+
+/// Gas large enough for all workloads.
+pub fn gas() -> Gas {
+    Gas::new(100_000_000, 1).unwrap()
+}
+
+/// Standard library package.
+pub fn stdlib() -> PublishPackageTx {
+    stdlib_package().into_tx(CORE_CODE_ADDRESS)
+}
+
+/// Creates vm with the published standard library and registered `PONT` coin.
+pub fn vm() -> (BenchVm, MemoryStorage, BankMock) {
+    let store = MemoryStorage::new();
+    let bank = BankMock::default();
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(bank.clone())
+        .build()
+        .unwrap();
+
+    vm.pub_package(stdlib());
+    vm.exec(register_pont_tx());
+    (vm, store, bank)
+}
+
+/// Module publication transaction.
+/// The module is not part of the standard library, so it can be published once on top of `vm()`.
+pub fn publish_module_tx() -> ModuleTx {
+    ModuleTx::new(
+        include_bytes!("../../tests/assets/target/modules/Store.mv").to_vec(),
+        CORE_CODE_ADDRESS,
+    )
+}
+
+/// `PONT` coin registration transaction.
+pub fn register_pont_tx() -> ScriptTx {
+    ScriptTx::new(
+        include_bytes!("../../tests/assets/target/scripts/register_coin.mv").to_vec(),
+        vec![ScriptArg::VectorU8(b"PONT".to_vec()), ScriptArg::U8(2)],
+        vec![TypeTag::Struct(StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("PONT").unwrap(),
+            name: Identifier::new("T").unwrap(),
+            type_params: vec![],
+        })],
+        vec![CORE_CODE_ADDRESS],
+    )
+}
+
+/// `PONT` transfer transaction. The sender must have at least `amount` native `PONT`.
+pub fn transfer_tx(from: AccountAddress, to: AccountAddress, amount: u128) -> ScriptTx {
+    ScriptTx::new(
+        include_bytes!("../../tests/assets/target/scripts/test_balance_transfer.mv").to_vec(),
+        vec![ScriptArg::Address(to), ScriptArg::U128(amount)],
+        vec![],
+        vec![from],
+    )
+}
+
+/// Script which increments a local variable `iterations` times.
+pub fn loop_tx(iterations: u64) -> ScriptTx {
+    ScriptTx::new(
+        loop_script(),
+        vec![ScriptArg::U64(iterations)],
+        vec![],
+        vec![],
+    )
+}
+
+fn loop_script() -> Vec<u8> {
+    let mut blob = vec![];
+    CompiledScriptMut {
+        module_handles: vec![],
+        struct_handles: vec![],
+        function_handles: vec![],
+        function_instantiations: vec![],
+        signatures: vec![Signature(vec![SignatureToken::U64])],
+        identifiers: vec![],
+        address_identifiers: vec![],
+        constant_pool: vec![],
+        type_parameters: vec![],
+        parameters: SignatureIndex(0),
+        code: CodeUnit {
+            locals: SignatureIndex(0),
+            code: vec![
+                Bytecode::LdU64(0),
+                Bytecode::StLoc(1),
+                Bytecode::CopyLoc(1),
+                Bytecode::CopyLoc(0),
+                Bytecode::Lt,
+                Bytecode::BrFalse(11),
+                Bytecode::CopyLoc(1),
+                Bytecode::LdU64(1),
+                Bytecode::Add,
+                Bytecode::StLoc(1),
+                Bytecode::Branch(2),
+                Bytecode::Ret,
+            ],
+        },
+    }
+    .serialize(&mut blob)
+    .expect("loop script must serialize");
+    blob
+}
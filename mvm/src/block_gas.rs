@@ -0,0 +1,71 @@
+//! Gas budget of a block.
+//!
+//! `VmConfig::max_block_gas` limits the gas used by all the transactions of a block.
+//! The host creates a meter with `Mvm::block_gas_meter` at the start of the block and passes it
+//! to every `Mvm::execute_block` call of the block. A transaction is admitted if its max gas
+//! amount fits into the remaining budget, so the block never exceeds the limit. Rejected
+//! transactions fail with `MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND` and are not charged.
+
+use alloc::vec::Vec;
+
+use move_core_types::vm_status::StatusCode;
+
+use crate::types::{Gas, VmResult};
+
+/// Gas accounting of a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockGasMeter {
+    limit: Option<u64>,
+    used: u64,
+    transactions: Vec<u64>,
+}
+
+impl BlockGasMeter {
+    /// Creates the meter of a block. `None` doesn't limit the block gas.
+    pub fn new(limit: Option<u64>) -> BlockGasMeter {
+        BlockGasMeter {
+            limit,
+            used: 0,
+            transactions: vec![],
+        }
+    }
+
+    /// Returns the gas limit of the block.
+    pub fn limit(&self) -> Option<u64> {
+        self.limit
+    }
+
+    /// Returns the gas used by the block.
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    /// Returns the remaining gas of the block or `None` if the block gas is not limited.
+    pub fn remaining(&self) -> Option<u64> {
+        self.limit.map(|limit| limit.saturating_sub(self.used))
+    }
+
+    /// Returns the gas used by the transactions of the block in the execution order.
+    /// Rejected transactions are not included.
+    pub fn transactions(&self) -> &[u64] {
+        &self.transactions
+    }
+
+    /// Returns `Err` with the failed result if the transaction doesn't fit into the block.
+    pub(crate) fn admit(&self, gas: &Gas) -> Result<(), VmResult> {
+        match self.remaining() {
+            Some(remaining) if gas.max_gas_amount() > remaining => Err(VmResult::new(
+                StatusCode::MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND,
+                None,
+                0,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Charges the gas used by the executed transaction.
+    pub(crate) fn charge(&mut self, result: &VmResult) {
+        self.used = self.used.saturating_add(result.gas_used);
+        self.transactions.push(result.gas_used);
+    }
+}
@@ -0,0 +1,86 @@
+//! Message passing between Move and other pallets or chains.
+//!
+//! `0x1::Bridge::send(dest: vector<u8>, payload: vector<u8>)` buffers an outbound message.
+//! Messages of a successful transaction are passed to the host `MessageQueue`,
+//! messages of a failed or dry-run transaction are dropped.
+//! Inbound messages are delivered to the Move handler with `Mvm::deliver_message`.
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::pop_arg;
+use move_vm_types::values::Value;
+use serde::{Deserialize, Serialize};
+use vm::errors::PartialVMResult;
+
+/// Module of the bridge natives.
+pub const BRIDGE_MODULE: &str = "Bridge";
+/// Name of the send native.
+pub const BRIDGE_SEND: &str = "send";
+/// Gas charged for a sent message in internal gas units.
+pub const BRIDGE_SEND_GAS: NativeGasParams = NativeGasParams {
+    base: 1000,
+    per_byte: 1,
+};
+
+/// Cross-pallet or cross-chain message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    /// Host-defined destination (outbound) or source (inbound) of the message.
+    pub peer: Vec<u8>,
+    /// Message payload.
+    pub payload: Vec<u8>,
+}
+
+/// Host queue of the outbound messages.
+pub trait MessageQueue {
+    /// Enqueues the message sent by a successful transaction.
+    fn enqueue(&self, message: Message);
+}
+
+impl<F> MessageQueue for F
+where
+    F: Fn(Message),
+{
+    fn enqueue(&self, message: Message) {
+        self(message)
+    }
+}
+
+/// Messages sent by the session, kept in its native extensions.
+#[derive(Debug, Default)]
+pub(crate) struct Outbox(pub Vec<Message>);
+
+/// Registers `Bridge::send` at the core address buffering messages into the outbox.
+pub(crate) fn register_bridge_send(
+    natives: NativeRegistry,
+    core_address: AccountAddress,
+) -> NativeRegistry {
+    natives.register(
+        core_address,
+        BRIDGE_MODULE,
+        BRIDGE_SEND,
+        BRIDGE_SEND_GAS,
+        bridge_send,
+    )
+}
+
+fn bridge_send(
+    context: &mut dyn NativeContext,
+    _: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    let payload = pop_arg!(args, Vec<u8>);
+    let peer = pop_arg!(args, Vec<u8>);
+    context
+        .extensions()
+        .get_or_default::<Outbox>()
+        .0
+        .push(Message { peer, payload });
+    Ok(NativeResult::ok(GasUnits::new(0), vec![]))
+}
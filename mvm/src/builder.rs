@@ -0,0 +1,281 @@
+//! Move VM builder.
+//!
+//! `MvmBuilder` requires only the storage and the event handler. The oracle, the bank and the
+//! metrics default to `NoOracle`, `NoBank` and `NoMetrics`, other options to the `Mvm` defaults.
+//! The logger is process-global: `build` installs it and fails if another logger is installed.
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use anyhow::Error;
+use log::{LevelFilter, Log};
+use move_vm_runtime::move_vm::{SharedCache, VMLimits};
+use move_vm_runtime::native_registry::NativeRegistry;
+
+use crate::compression::Compression;
+use crate::data::{BalanceAccess, EventHandler, NoBank, NoOracle, Oracle, State, Storage};
+use crate::key_codec::KeyCodec;
+use crate::metrics::{Metrics, NoMetrics};
+use crate::mvm::Mvm;
+use crate::types::TxLimits;
+use crate::vm_config::AddressesConfig;
+
+/// Move VM builder.
+pub struct MvmBuilder<S, E, O = NoOracle, B = NoBank, M = NoMetrics>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle,
+    B: BalanceAccess,
+    M: Metrics,
+{
+    store: S,
+    event_handler: E,
+    oracle: O,
+    bank: B,
+    metrics: M,
+    addresses: AddressesConfig,
+    key_prefix: Vec<u8>,
+    key_codec: Option<Box<dyn KeyCodec + Send + Sync>>,
+    tenant_spaces: bool,
+    natives: Option<NativeRegistry>,
+    compression: Compression,
+    instruction_limit: Option<u64>,
+    limits: Option<VMLimits>,
+    tx_limits: Option<TxLimits>,
+    logger: Option<(&'static dyn Log, LevelFilter)>,
+    uninitialized_mode: bool,
+    shared_cache: Option<Arc<SharedCache>>,
+}
+
+impl<S, E> MvmBuilder<S, E>
+where
+    S: Storage,
+    E: EventHandler,
+{
+    /// Creates a builder of the vm over the storage.
+    pub fn new(store: S, event_handler: E) -> MvmBuilder<S, E> {
+        MvmBuilder {
+            store,
+            event_handler,
+            oracle: NoOracle,
+            bank: NoBank,
+            metrics: NoMetrics,
+            addresses: AddressesConfig::default(),
+            key_prefix: Vec::new(),
+            key_codec: None,
+            tenant_spaces: false,
+            natives: None,
+            compression: Compression::default(),
+            instruction_limit: None,
+            limits: None,
+            tx_limits: None,
+            logger: None,
+            uninitialized_mode: false,
+            shared_cache: None,
+        }
+    }
+}
+
+impl<S, E, O, B, M> MvmBuilder<S, E, O, B, M>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle,
+    B: BalanceAccess,
+    M: Metrics,
+{
+    /// Sets the price oracle.
+    pub fn with_oracle<O2: Oracle>(self, oracle: O2) -> MvmBuilder<S, E, O2, B, M> {
+        MvmBuilder {
+            store: self.store,
+            event_handler: self.event_handler,
+            oracle,
+            bank: self.bank,
+            metrics: self.metrics,
+            addresses: self.addresses,
+            key_prefix: self.key_prefix,
+            key_codec: self.key_codec,
+            tenant_spaces: self.tenant_spaces,
+            natives: self.natives,
+            compression: self.compression,
+            instruction_limit: self.instruction_limit,
+            limits: self.limits,
+            tx_limits: self.tx_limits,
+            logger: self.logger,
+            uninitialized_mode: self.uninitialized_mode,
+            shared_cache: self.shared_cache,
+        }
+    }
+
+    /// Sets the access to the native balances.
+    pub fn with_bank<B2: BalanceAccess>(self, bank: B2) -> MvmBuilder<S, E, O, B2, M> {
+        MvmBuilder {
+            store: self.store,
+            event_handler: self.event_handler,
+            oracle: self.oracle,
+            bank,
+            metrics: self.metrics,
+            addresses: self.addresses,
+            key_prefix: self.key_prefix,
+            key_codec: self.key_codec,
+            tenant_spaces: self.tenant_spaces,
+            natives: self.natives,
+            compression: self.compression,
+            instruction_limit: self.instruction_limit,
+            limits: self.limits,
+            tx_limits: self.tx_limits,
+            logger: self.logger,
+            uninitialized_mode: self.uninitialized_mode,
+            shared_cache: self.shared_cache,
+        }
+    }
+
+    /// Sets the metrics.
+    pub fn with_metrics<M2: Metrics>(self, metrics: M2) -> MvmBuilder<S, E, O, B, M2> {
+        MvmBuilder {
+            store: self.store,
+            event_handler: self.event_handler,
+            oracle: self.oracle,
+            bank: self.bank,
+            metrics,
+            addresses: self.addresses,
+            key_prefix: self.key_prefix,
+            key_codec: self.key_codec,
+            tenant_spaces: self.tenant_spaces,
+            natives: self.natives,
+            compression: self.compression,
+            instruction_limit: self.instruction_limit,
+            limits: self.limits,
+            tx_limits: self.tx_limits,
+            logger: self.logger,
+            uninitialized_mode: self.uninitialized_mode,
+            shared_cache: self.shared_cache,
+        }
+    }
+
+    /// Sets the addresses of the standard library and the vm config.
+    pub fn with_addresses(mut self, addresses: AddressesConfig) -> Self {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Prepends the prefix to all storage keys, see `State::with_key_prefix`.
+    pub fn with_key_prefix(mut self, prefix: Vec<u8>) -> Self {
+        self.key_prefix = prefix;
+        self
+    }
+
+    /// Sets the codec of the storage keys, see `State::with_key_codec`.
+    pub fn with_key_codec<C>(mut self, codec: C) -> Self
+    where
+        C: KeyCodec + Send + Sync + 'static,
+    {
+        self.key_codec = Some(Box::new(codec));
+        self
+    }
+
+    /// Enables the storage spaces of the tenants, see `State::with_tenant_spaces`.
+    pub fn with_tenant_spaces(mut self) -> Self {
+        self.tenant_spaces = true;
+        self
+    }
+
+    /// Registers additional native functions of the embedding chain, see `Mvm::with_natives`.
+    pub fn with_natives(mut self, natives: NativeRegistry) -> Self {
+        self.natives = Some(natives);
+        self
+    }
+
+    /// Sets compression mode of the published modules, see `Mvm::with_compression`.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Limits the number of instructions executed by a script, see `Mvm::with_instruction_limit`.
+    pub fn with_instruction_limit(mut self, limit: u64) -> Self {
+        self.instruction_limit = Some(limit);
+        self
+    }
+
+    /// Overrides the execution limits of the on-chain config, see `Mvm::with_limits`.
+    pub fn with_limits(mut self, limits: VMLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Overrides the script transaction limits of the on-chain config, see `Mvm::with_tx_limits`.
+    pub fn with_tx_limits(mut self, limits: TxLimits) -> Self {
+        self.tx_limits = Some(limits);
+        self
+    }
+
+    /// Installs the logger of the `log` records with the maximum level on `build`.
+    pub fn with_logger(mut self, logger: &'static dyn Log, level: LevelFilter) -> Self {
+        self.logger = Some((logger, level));
+        self
+    }
+
+    /// Enables the uninitialized mode, see `Mvm::with_uninitialized_mode`.
+    pub fn with_uninitialized_mode(mut self) -> Self {
+        self.uninitialized_mode = true;
+        self
+    }
+
+    /// Shares the loader cache with other vm instances, see `Mvm::with_shared_cache`.
+    pub fn with_shared_cache(mut self, cache: Arc<SharedCache>) -> Self {
+        self.shared_cache = Some(cache);
+        self
+    }
+
+    /// Returns the builder without the natives and the natives.
+    pub(crate) fn take_natives(mut self) -> (Self, Option<NativeRegistry>) {
+        let natives = self.natives.take();
+        (self, natives)
+    }
+
+    /// Creates the vm. Fails if the on-chain vm config can't be decoded.
+    /// A missing config is replaced with the default one, see `Mvm::is_initialized`.
+    pub fn build(self) -> Result<Mvm<S, E, O, B, M>, Error> {
+        if let Some((logger, level)) = self.logger {
+            log::set_logger(logger).map_err(|_| Error::msg("A logger is already installed"))?;
+            log::set_max_level(level);
+        }
+        let mut state = State::new(self.store, self.oracle).with_key_prefix(self.key_prefix);
+        if let Some(codec) = self.key_codec {
+            state = state.with_key_codec(codec);
+        }
+        if self.tenant_spaces {
+            state = state.with_tenant_spaces();
+        }
+        let mut mvm = Mvm::from_state(
+            state,
+            self.event_handler,
+            self.bank,
+            self.metrics,
+            self.addresses,
+        )?
+        .with_compression(self.compression);
+        if let Some(natives) = self.natives {
+            mvm = mvm.with_natives(natives);
+        }
+        if let Some(limit) = self.instruction_limit {
+            mvm = mvm.with_instruction_limit(limit);
+        }
+        if let Some(limits) = self.limits {
+            mvm = mvm.with_limits(limits);
+        }
+        if let Some(limits) = self.tx_limits {
+            mvm = mvm.with_tx_limits(limits);
+        }
+        if self.uninitialized_mode {
+            mvm = mvm.with_uninitialized_mode();
+        }
+        if let Some(cache) = self.shared_cache {
+            mvm = mvm.with_shared_cache(cache);
+        }
+        Ok(mvm)
+    }
+}
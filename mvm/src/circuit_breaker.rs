@@ -0,0 +1,50 @@
+//! Invariant violation circuit breaker.
+//!
+//! An invariant violation means that the vm state may be corrupted.
+//! Once tripped, the breaker halts the vm until the governance resumes it.
+//!
+//! The transaction which trips the breaker is kept in the block instead of being discarded:
+//! its result is marked with `VmResult::halted` and its write set stores the halt flag under
+//! the config address, so every node executing the block halts at the same transaction and
+//! a restarted node stays halted.
+//!
+//! Governance scripts, see `Mvm::execute_governance_script`, run on the halted vm in any safe
+//! mode. An executed governance script resumes the vm: its write set deletes the halt flag.
+
+use move_core_types::account_address::AccountAddress;
+
+/// Transactions accepted by the halted vm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafeMode {
+    /// All transactions but the governance scripts are rejected.
+    RejectAll,
+    /// Only transactions signed by the governance account and the governance scripts
+    /// are accepted.
+    GovernanceOnly,
+}
+
+/// Halts the vm after an invariant violation.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    mode: SafeMode,
+}
+
+impl CircuitBreaker {
+    /// Creates a circuit breaker with the safe mode of the halted vm.
+    pub fn new(mode: SafeMode) -> CircuitBreaker {
+        CircuitBreaker { mode }
+    }
+
+    /// Returns the safe mode of the halted vm.
+    pub fn mode(&self) -> SafeMode {
+        self.mode
+    }
+
+    /// Returns `true` if the halted vm can execute the transaction signed by the senders.
+    pub fn allows(&self, senders: &[AccountAddress], governance: &AccountAddress) -> bool {
+        match self.mode {
+            SafeMode::RejectAll => false,
+            SafeMode::GovernanceOnly => senders.contains(governance),
+        }
+    }
+}
@@ -0,0 +1,426 @@
+//! Native balances as Move coins.
+//!
+//! Natives of `0x1::CoinBridge`:
+//! `wrap<Coin>(account: &signer, amount: u64): CoinBridge::Coin<Coin>` takes `amount` coins
+//! from the native balance of the account, `unwrap<Coin>(account: &signer, coin:
+//! CoinBridge::Coin<Coin>)` returns the coins to the native balance and
+//! `native_balance<Coin>(addr: address): u128` reads the balance.
+//! `CoinBridge::Coin<Coin> { value: u64 }` is declared by the bridge module itself, so only the
+//! bridge packs and unpacks the wrapped coins; the declaration is checked when the module is
+//! published, see `check_bridge_module`. `Coin` is a native currency, see
+//! `data::decode_wallet_id`.
+//!
+//! Wrapped coins are locked by the bridge in `CoinBridge::Locked<Coin> { value: u128 }`
+//! under the core address. A coin can be unwrapped only if the bridge holds enough locked
+//! coins. The locked amounts are written with the resources of the transaction effects,
+//! see `locked_writes`.
+//!
+//! With `VmConfig::check_balance_conservation` the coins deposited from the native balances by
+//! a transaction must be withdrawn back by the same transaction unless they are locked by the
+//! bridge or the transaction uses the mint and burn capability of the currency, see
+//! `check_conservation`.
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::balance::{BalanceOperation, WalletId};
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::pop_arg;
+use move_vm_types::values::{SignerRef, Struct, Value};
+use vm::access::ModuleAccess;
+use vm::errors::{Location, PartialVMError, PartialVMResult, VMError};
+use vm::file_format::{SignatureToken, StructDefinition, StructFieldInformation, TypeSignature};
+use vm::CompiledModule;
+
+use crate::account::{decode_balance, signer_address};
+use crate::data::{AccessKey, Storage};
+use crate::types::BalanceChange;
+
+/// Module of the bridge natives.
+pub const COIN_BRIDGE_MODULE: &str = "CoinBridge";
+/// Name of the native wrapping native coins.
+pub const WRAP: &str = "wrap";
+/// Name of the native unwrapping coins to the native balance.
+pub const UNWRAP: &str = "unwrap";
+/// Name of the native balance read.
+pub const NATIVE_BALANCE: &str = "native_balance";
+/// Resource of the wrapped coins declared by the bridge module.
+pub const COIN_RESOURCE: &str = "Coin";
+/// Resource holding the locked coins of a currency.
+pub const LOCKED_RESOURCE: &str = "Locked";
+/// Abort code of a currency without the native balance.
+pub const BALANCE_NOT_FOUND: u64 = 1;
+/// Abort code of a wrap exceeding the native balance.
+pub const INSUFFICIENT_BALANCE: u64 = 2;
+/// Abort code of an unwrap exceeding the locked coins.
+pub const UNBACKED_COIN: u64 = 3;
+/// Gas charged for a wrap in internal gas units.
+pub const WRAP_GAS: NativeGasParams = NativeGasParams {
+    base: 1000,
+    per_byte: 0,
+};
+/// Gas charged for an unwrap in internal gas units.
+pub const UNWRAP_GAS: NativeGasParams = NativeGasParams {
+    base: 1000,
+    per_byte: 0,
+};
+/// Gas charged for a balance read in internal gas units.
+pub const NATIVE_BALANCE_GAS: NativeGasParams = NativeGasParams {
+    base: 500,
+    per_byte: 0,
+};
+
+/// Coins of a currency moved through the bridge by the current transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct CoinFlow {
+    /// Locked coins before the transaction.
+    pub locked: u128,
+    pub wrapped: u128,
+    pub unwrapped: u128,
+}
+
+impl CoinFlow {
+    /// Returns the locked coins after the transaction or `None` if the transaction unwraps
+    /// more coins than are locked.
+    fn locked_after(&self) -> Option<u128> {
+        self.locked
+            .checked_add(self.wrapped)?
+            .checked_sub(self.unwrapped)
+    }
+}
+
+/// Coin flows of the session by the currency, kept in its native extensions.
+#[derive(Debug, Default)]
+pub(crate) struct CoinFlows(pub BTreeMap<StructTag, CoinFlow>);
+
+/// Returns the tag of `CoinBridge::Locked<coin>`.
+pub fn locked_tag(core_address: AccountAddress, coin: StructTag) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(COIN_BRIDGE_MODULE).unwrap(),
+        name: Identifier::new(LOCKED_RESOURCE).unwrap(),
+        type_params: vec![TypeTag::Struct(coin)],
+    }
+}
+
+/// Returns the coins of the currency locked by the bridge.
+pub fn load_locked<S: Storage>(
+    storage: &S,
+    core_address: AccountAddress,
+    coin: &StructTag,
+) -> Result<u128, VMError> {
+    let key = AccessKey::from((&core_address, &locked_tag(core_address, coin.clone())));
+    match storage.get(key.as_ref()) {
+        Some(blob) => decode_balance(&blob).ok_or_else(|| {
+            PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE)
+                .finish(Location::Undefined)
+        }),
+        None => Ok(0),
+    }
+}
+
+/// Returns the writes of the locked coins after the transaction, added to the resources of
+/// the transaction effects. The locked coins before the transaction are read by the session.
+/// Fails if the transaction unwraps more coins of a currency than are locked, so the locked
+/// coins and the native balances sum up to the same amount before and after the transaction.
+pub(crate) fn locked_writes(
+    core_address: AccountAddress,
+    flows: &BTreeMap<StructTag, CoinFlow>,
+) -> Result<Vec<(AccessKey, Option<Vec<u8>>)>, VMError> {
+    flows
+        .iter()
+        .map(|(coin, flow)| {
+            let locked = flow.locked_after().ok_or_else(|| {
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message(format!("Unbacked coins of {} unwrapped", coin))
+                    .finish(Location::Undefined)
+            })?;
+            let key = AccessKey::from((&core_address, &locked_tag(core_address, coin.clone())));
+            if locked == 0 {
+                Ok((key, None))
+            } else {
+                Ok((key, Some(locked.to_le_bytes().to_vec())))
+            }
+        })
+        .collect()
+}
+
+/// Checks that the balance changes of a transaction conserve the coins of every currency:
+/// the deposited coins equal the withdrawn coins plus the coins locked by the bridge.
+/// The currencies minted or burned with the mint and burn capability, see `MintBurnJournal`,
+/// are exempt.
+/// Backstop against bugs of the natives moving the native balances, a violation fails the
+/// transaction with `UNKNOWN_INVARIANT_VIOLATION_ERROR`.
+pub(crate) fn check_conservation(
+    balance_changes: &[BalanceChange],
+    flows: &BTreeMap<StructTag, CoinFlow>,
+    minted: &BTreeSet<StructTag>,
+) -> Result<(), VMError> {
+    let mut deposited = BTreeMap::<&StructTag, (u128, u128)>::new();
+    for change in balance_changes {
+        let (deposit, withdraw) = deposited.entry(&change.wallet_id.tag).or_default();
+        let (total, amount) = match change.operation {
+            BalanceOperation::Deposit(amount) => (deposit, amount),
+            BalanceOperation::Withdraw(amount) => (withdraw, amount),
+        };
+        *total = total
+            .checked_add(amount)
+            .ok_or_else(|| violation(&change.wallet_id.tag))?;
+    }
+    for (coin, flow) in flows {
+        let (deposit, withdraw) = deposited.entry(coin).or_default();
+        *withdraw = withdraw
+            .checked_add(flow.wrapped)
+            .ok_or_else(|| violation(coin))?;
+        *deposit = deposit
+            .checked_add(flow.unwrapped)
+            .ok_or_else(|| violation(coin))?;
+    }
+    match deposited
+        .into_iter()
+        .filter(|(coin, _)| !minted.contains(*coin))
+        .find(|(_, (deposit, withdraw))| deposit != withdraw)
+    {
+        Some((coin, _)) => Err(violation(coin)),
+        None => Ok(()),
+    }
+}
+
+/// Checks the declaration of the bridge module before it is published: `wrap` and `unwrap`
+/// must be natives moving `CoinBridge::Coin<Coin>`, a resource with a single `u64` field
+/// declared by the module, and the module can't declare the `Locked` resource.
+/// The coins passed to `unwrap` are thereby packed by the bridge only.
+pub(crate) fn check_bridge_module(module: &CompiledModule) -> Result<(), VMError> {
+    let struct_name = |def: &StructDefinition| {
+        module
+            .identifier_at(module.struct_handle_at(def.struct_handle).name)
+            .as_str()
+    };
+    let invalid = |msg: &str| {
+        PartialVMError::new(StatusCode::TYPE_MISMATCH)
+            .with_message(format!("Invalid {} module: {}", COIN_BRIDGE_MODULE, msg))
+            .finish(Location::Module(module.self_id()))
+    };
+    if module
+        .struct_defs()
+        .iter()
+        .any(|def| struct_name(def) == LOCKED_RESOURCE)
+    {
+        return Err(invalid("the locked coins can't be declared"));
+    }
+    let coin = module
+        .struct_defs()
+        .iter()
+        .find(|def| struct_name(def) == COIN_RESOURCE)
+        .filter(|def| is_coin_resource(module, def))
+        .map(|def| {
+            SignatureToken::StructInstantiation(
+                def.struct_handle,
+                vec![SignatureToken::TypeParameter(0)],
+            )
+        });
+
+    for def in module.function_defs() {
+        let handle = module.function_handle_at(def.function);
+        let name = module.identifier_at(handle.name).as_str();
+        if name != WRAP && name != UNWRAP {
+            continue;
+        }
+        let coin = coin
+            .clone()
+            .ok_or_else(|| invalid("the coin resource is not declared"))?;
+        let signer = SignatureToken::Reference(Box::new(SignatureToken::Signer));
+        let (params, returns) = if name == WRAP {
+            (vec![signer, SignatureToken::U64], vec![coin])
+        } else {
+            (vec![signer, coin], vec![])
+        };
+        if !def.is_native()
+            || handle.type_parameters.len() != 1
+            || module.signature_at(handle.parameters).0 != params
+            || module.signature_at(handle.return_).0 != returns
+        {
+            return Err(invalid(&format!("unexpected declaration of {}", name)));
+        }
+    }
+    Ok(())
+}
+
+fn is_coin_resource(module: &CompiledModule, def: &StructDefinition) -> bool {
+    let handle = module.struct_handle_at(def.struct_handle);
+    handle.is_nominal_resource
+        && handle.type_parameters.len() == 1
+        && match &def.field_information {
+            StructFieldInformation::Declared(fields) => {
+                fields.len() == 1 && fields[0].signature == TypeSignature(SignatureToken::U64)
+            }
+            StructFieldInformation::Native => false,
+        }
+}
+
+fn violation(coin: &StructTag) -> VMError {
+    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+        .with_message(format!("Coins of {} are not conserved", coin))
+        .finish(Location::Undefined)
+}
+
+/// Registers the bridge natives at the core address recording the coin flows of the session
+/// in its `CoinFlows`.
+pub(crate) fn register_coin_bridge(
+    natives: NativeRegistry,
+    core_address: AccountAddress,
+) -> NativeRegistry {
+    natives
+        .register(
+            core_address,
+            COIN_BRIDGE_MODULE,
+            WRAP,
+            WRAP_GAS,
+            move |ctx: &mut dyn NativeContext, ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                let amount = pop_arg!(args, u64);
+                let address = signer_address(pop_arg!(args, SignerRef))?;
+                wrap(ctx, core_address, ty_args, address, amount)
+            },
+        )
+        .register(
+            core_address,
+            COIN_BRIDGE_MODULE,
+            UNWRAP,
+            UNWRAP_GAS,
+            move |ctx: &mut dyn NativeContext, ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                let amount = coin_value(pop_arg!(args, Struct))?;
+                let address = signer_address(pop_arg!(args, SignerRef))?;
+                unwrap(ctx, core_address, ty_args, address, amount)
+            },
+        )
+        .register(
+            core_address,
+            COIN_BRIDGE_MODULE,
+            NATIVE_BALANCE,
+            NATIVE_BALANCE_GAS,
+            move |ctx: &mut dyn NativeContext, ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                let address = pop_arg!(args, AccountAddress);
+                let wallet_id = WalletId::new(address, coin_tag(ctx, ty_args)?);
+                let cost = GasUnits::new(0);
+                Ok(match ctx.get_balance(&wallet_id) {
+                    Some(balance) => NativeResult::ok(cost, vec![Value::u128(balance)]),
+                    None => NativeResult::err(cost, BALANCE_NOT_FOUND),
+                })
+            },
+        )
+}
+
+fn wrap(
+    ctx: &mut dyn NativeContext,
+    core_address: AccountAddress,
+    ty_args: Vec<Type>,
+    address: AccountAddress,
+    amount: u64,
+) -> PartialVMResult<NativeResult> {
+    let cost = GasUnits::new(0);
+    let coin = coin_tag(ctx, ty_args)?;
+    let wallet_id = WalletId::new(address, coin.clone());
+    match ctx.get_balance(&wallet_id) {
+        None => return Ok(NativeResult::err(cost, BALANCE_NOT_FOUND)),
+        Some(balance) if balance < amount as u128 => {
+            return Ok(NativeResult::err(cost, INSUFFICIENT_BALANCE))
+        }
+        Some(_) => {}
+    }
+
+    let flow = coin_flow(ctx, core_address, coin)?;
+    flow.wrapped = flow
+        .wrapped
+        .checked_add(amount as u128)
+        .ok_or_else(|| PartialVMError::new(StatusCode::ARITHMETIC_ERROR))?;
+    ctx.save_balance_operation(wallet_id, BalanceOperation::Deposit(amount as u128));
+
+    let coin = Struct::pack(vec![Value::u64(amount)], true);
+    Ok(NativeResult::ok(cost, vec![Value::struct_(coin)]))
+}
+
+fn unwrap(
+    ctx: &mut dyn NativeContext,
+    core_address: AccountAddress,
+    ty_args: Vec<Type>,
+    address: AccountAddress,
+    amount: u64,
+) -> PartialVMResult<NativeResult> {
+    let cost = GasUnits::new(0);
+    let coin = coin_tag(ctx, ty_args)?;
+    let wallet_id = WalletId::new(address, coin.clone());
+
+    let flow = coin_flow(ctx, core_address, coin)?;
+    let unwrapped = CoinFlow {
+        unwrapped: flow.unwrapped.saturating_add(amount as u128),
+        ..*flow
+    };
+    if unwrapped.locked_after().is_none() {
+        return Ok(NativeResult::err(cost, UNBACKED_COIN));
+    }
+    *flow = unwrapped;
+    ctx.save_balance_operation(wallet_id, BalanceOperation::Withdraw(amount as u128));
+    Ok(NativeResult::ok(cost, vec![]))
+}
+
+/// Returns the flow of the currency loading the locked coins on the first access.
+fn coin_flow<'a>(
+    ctx: &'a mut dyn NativeContext,
+    core_address: AccountAddress,
+    coin: StructTag,
+) -> PartialVMResult<&'a mut CoinFlow> {
+    if !ctx
+        .extensions()
+        .get_or_default::<CoinFlows>()
+        .0
+        .contains_key(&coin)
+    {
+        let locked = ctx
+            .read_remote_resource(&core_address, &locked_tag(core_address, coin.clone()))?
+            .map(|blob| {
+                decode_balance(&blob)
+                    .ok_or_else(|| PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        ctx.extensions().get_or_default::<CoinFlows>().0.insert(
+            coin.clone(),
+            CoinFlow {
+                locked,
+                ..CoinFlow::default()
+            },
+        );
+    }
+    Ok(ctx
+        .extensions()
+        .get_or_default::<CoinFlows>()
+        .0
+        .get_mut(&coin)
+        .expect("Loaded flow"))
+}
+
+fn coin_tag(ctx: &dyn NativeContext, mut ty_args: Vec<Type>) -> PartialVMResult<StructTag> {
+    let ty = ty_args
+        .pop()
+        .ok_or_else(|| PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR))?;
+    match ctx.type_to_type_tag(&ty)? {
+        TypeTag::Struct(tag) => Ok(tag),
+        _ => Err(PartialVMError::new(StatusCode::CALL_TYPE_MISMATCH_ERROR)
+            .with_message("Invalid type parameter. Structure is expected.".into())),
+    }
+}
+
+fn coin_value(coin: Struct) -> PartialVMResult<u64> {
+    coin.unpack()?
+        .next()
+        .ok_or_else(|| PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR))?
+        .value_as::<u64>()
+}
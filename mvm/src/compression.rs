@@ -0,0 +1,70 @@
+//! Module blob compression.
+//!
+//! Uncompressed modules always start with the bytecode magic (`0xA11CEB0B`), so a compressed
+//! blob is marked with a leading format byte which can't be the first byte of a raw module.
+//! This keeps modules stored before compression was enabled readable.
+//! Resources are stored as is: their encoding has no reserved prefix to mark the format.
+
+use alloc::borrow::{Cow, ToOwned};
+use alloc::vec::Vec;
+
+use move_core_types::vm_status::StatusCode;
+use vm::errors::{Location, PartialVMError, VMResult};
+use vm::file_format_common::BinaryConstants;
+
+/// Format byte of the lz4 compressed module.
+const LZ4_FORMAT: u8 = 0x01;
+
+/// Module compression mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Modules are stored as is.
+    None,
+    /// Modules are compressed with lz4.
+    Lz4,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression::None
+    }
+}
+
+impl Compression {
+    /// Encodes module blob for the storage.
+    /// Returns the raw blob if compression does not reduce its size.
+    pub fn compress<'a>(&self, blob: &'a [u8]) -> Cow<'a, [u8]> {
+        match self {
+            Compression::None => Cow::Borrowed(blob),
+            Compression::Lz4 => {
+                let compressed = lz4_flex::compress_prepend_size(blob);
+                if compressed.len() + 1 < blob.len() {
+                    let mut encoded = Vec::with_capacity(compressed.len() + 1);
+                    encoded.push(LZ4_FORMAT);
+                    encoded.extend_from_slice(&compressed);
+                    Cow::Owned(encoded)
+                } else {
+                    Cow::Borrowed(blob)
+                }
+            }
+        }
+    }
+}
+
+/// Decodes module blob from the storage regardless of the current compression mode.
+pub fn decompress(blob: Vec<u8>) -> VMResult<Vec<u8>> {
+    if blob.starts_with(&BinaryConstants::DIEM_MAGIC) {
+        return Ok(blob);
+    }
+
+    match blob.split_first() {
+        Some((&LZ4_FORMAT, compressed)) => {
+            lz4_flex::decompress_size_prepended(compressed).map_err(|_| {
+                PartialVMError::new(StatusCode::STORAGE_ERROR)
+                    .with_message("Failed to decompress module.".to_owned())
+                    .finish(Location::Undefined)
+            })
+        }
+        _ => Ok(blob),
+    }
+}
@@ -0,0 +1,287 @@
+//! Consensus mode.
+//!
+//! Nodes of a chain must execute transactions with identical results.
+//! `ConsensusMode` wraps the vm and exposes only the hooks which can not bring
+//! nondeterminism into the execution:
+//! the oracle, the balances and the host handlers must implement `DeterministicSource`,
+//! natives registered with `NativeRegistry::register_nondeterministic` are rejected and
+//! wall-clock hooks like `Mvm::with_interrupt` are not available.
+//! The vm is created with `MvmBuilder::build_consensus`.
+
+use core::ops::Deref;
+
+use alloc::vec::Vec;
+use anyhow::Error;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::ModuleId;
+use move_vm_runtime::native_registry::NativeRegistry;
+use move_vm_types::natives::balance::Balance;
+
+use crate::access_path::AccessPath;
+use crate::bridge::MessageQueue;
+use crate::builder::MvmBuilder;
+use crate::circuit_breaker::SafeMode;
+use crate::compression::Compression;
+use crate::data::{BalanceAccess, EventHandler, ExecutionContext, Oracle, Storage};
+use crate::host::HostHandler;
+use crate::metrics::{Metrics, NoMetrics};
+use crate::mvm::Mvm;
+use crate::types::{BatchTx, Gas, ModuleTx, PublishPackageTx, ScriptTx, Ticker, VmResult};
+use crate::vm_config::AddressesConfig;
+use crate::{BatchVm, Vm};
+
+/// Marker of the host sources which results depend only on the chain state,
+/// so they are the same on all the nodes.
+/// Implementors must not read the wall clock, local random sources or floating point results.
+pub trait DeterministicSource {}
+
+/// Marks the wrapped source as deterministic.
+/// Forwards `Oracle`, `BalanceAccess` and `HostHandler` to the source.
+#[derive(Debug, Default, Clone)]
+pub struct Deterministic<T>(pub T);
+
+impl<T> DeterministicSource for Deterministic<T> {}
+
+impl<T: Oracle> Oracle for Deterministic<T> {
+    fn get_price(&self, ticker: &Ticker) -> Option<u128> {
+        self.0.get_price(ticker)
+    }
+
+    fn get_twap(&self, ticker: &Ticker, window: u64) -> Option<u128> {
+        self.0.get_twap(ticker, window)
+    }
+}
+
+impl<T: BalanceAccess> BalanceAccess for Deterministic<T> {
+    fn get_balance(&self, address: &AccountAddress, ticker: &Ticker) -> Option<Balance> {
+        self.0.get_balance(address, ticker)
+    }
+
+    fn deposit(&self, address: &AccountAddress, ticker: &Ticker, amount: Balance) {
+        self.0.deposit(address, ticker, amount)
+    }
+
+    fn withdraw(&self, address: &AccountAddress, ticker: &Ticker, amount: Balance) {
+        self.0.withdraw(address, ticker, amount)
+    }
+}
+
+impl<T: HostHandler> HostHandler for Deterministic<T> {
+    fn call(&self, payload: &[u8]) -> Result<Vec<u8>, u64> {
+        self.0.call(payload)
+    }
+}
+
+/// Vm which accepts only deterministic host hooks.
+pub struct ConsensusMode<S, E, O, B, M = NoMetrics>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle + DeterministicSource,
+    B: BalanceAccess + DeterministicSource,
+    M: Metrics,
+{
+    vm: Mvm<S, E, O, B, M>,
+}
+
+impl<S, E, O, B> ConsensusMode<S, E, O, B>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle + DeterministicSource,
+    B: BalanceAccess + DeterministicSource,
+{
+    /// Creates a new move vm in consensus mode with given store and event handler.
+    pub fn new(
+        store: S,
+        event_handler: E,
+        oracle: O,
+        balance: B,
+    ) -> Result<ConsensusMode<S, E, O, B>, Error> {
+        MvmBuilder::new(store, event_handler)
+            .with_oracle(oracle)
+            .with_bank(balance)
+            .build_consensus()
+    }
+}
+
+impl<S, E, O, B, M> ConsensusMode<S, E, O, B, M>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle + DeterministicSource,
+    B: BalanceAccess + DeterministicSource,
+    M: Metrics,
+{
+    /// Creates a new move vm in consensus mode with the standard library and the vm config
+    /// at the given addresses.
+    pub fn new_with_addresses(
+        store: S,
+        event_handler: E,
+        oracle: O,
+        balance: B,
+        metrics: M,
+        addresses: AddressesConfig,
+    ) -> Result<ConsensusMode<S, E, O, B, M>, Error> {
+        MvmBuilder::new(store, event_handler)
+            .with_oracle(oracle)
+            .with_bank(balance)
+            .with_metrics(metrics)
+            .with_addresses(addresses)
+            .build_consensus()
+    }
+
+    /// See `Mvm::with_compression`.
+    pub fn with_compression(self, compression: Compression) -> Self {
+        self.map(|vm| vm.with_compression(compression))
+    }
+
+    /// See `Mvm::with_instruction_limit`.
+    pub fn with_instruction_limit(self, limit: u64) -> Self {
+        self.map(|vm| vm.with_instruction_limit(limit))
+    }
+
+    /// Registers additional native functions of the embedding chain.
+    /// Fails if any of the natives is registered as nondeterministic.
+    pub fn with_natives(self, natives: NativeRegistry) -> Result<Self, Error> {
+        if let Some(native) = natives.nondeterministic().next() {
+            return Err(Error::msg(format!(
+                "Native {} is nondeterministic",
+                native.name()
+            )));
+        }
+        Ok(self.map(|vm| vm.with_natives(natives)))
+    }
+
+    /// See `Mvm::with_host_handler`.
+    pub fn with_host_handler<H>(self, id: u64, handler: H) -> Self
+    where
+        H: HostHandler + DeterministicSource + Send + Sync + 'static,
+    {
+        self.map(|vm| vm.with_host_handler(id, handler))
+    }
+
+    /// See `Mvm::with_message_queue`.
+    pub fn with_message_queue<Q>(self, queue: Q) -> Self
+    where
+        Q: MessageQueue + Send + Sync + 'static,
+    {
+        self.map(|vm| vm.with_message_queue(queue))
+    }
+
+    /// See `Mvm::with_message_handler`.
+    pub fn with_message_handler(self, module: ModuleId, function: Identifier) -> Self {
+        self.map(|vm| vm.with_message_handler(module, function))
+    }
+
+    /// See `Mvm::with_circuit_breaker`.
+    pub fn with_circuit_breaker(self, mode: SafeMode) -> Self {
+        self.map(|vm| vm.with_circuit_breaker(mode))
+    }
+
+    /// See `Mvm::with_config_path`.
+    pub fn with_config_path(self, path: AccessPath) -> Self {
+        self.map(|vm| vm.with_config_path(path))
+    }
+
+    fn map<F>(self, f: F) -> Self
+    where
+        F: FnOnce(Mvm<S, E, O, B, M>) -> Mvm<S, E, O, B, M>,
+    {
+        ConsensusMode { vm: f(self.vm) }
+    }
+}
+
+impl<S, E, O, B, M> MvmBuilder<S, E, O, B, M>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle + DeterministicSource,
+    B: BalanceAccess + DeterministicSource,
+    M: Metrics,
+{
+    /// Creates the vm in consensus mode.
+    /// Fails if any of the natives is registered as nondeterministic, see
+    /// `ConsensusMode::with_natives`.
+    pub fn build_consensus(self) -> Result<ConsensusMode<S, E, O, B, M>, Error> {
+        let (builder, natives) = self.take_natives();
+        let vm = ConsensusMode {
+            vm: builder.build()?,
+        };
+        match natives {
+            Some(natives) => vm.with_natives(natives),
+            None => Ok(vm),
+        }
+    }
+}
+
+impl<S, E, O, B, M> Deref for ConsensusMode<S, E, O, B, M>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle + DeterministicSource,
+    B: BalanceAccess + DeterministicSource,
+    M: Metrics,
+{
+    type Target = Mvm<S, E, O, B, M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.vm
+    }
+}
+
+impl<S, E, O, B, M> Vm for ConsensusMode<S, E, O, B, M>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle + DeterministicSource,
+    B: BalanceAccess + DeterministicSource,
+    M: Metrics,
+{
+    fn publish_module(&self, gas: Gas, module: ModuleTx, dry_run: bool) -> VmResult {
+        self.vm.publish_module(gas, module, dry_run)
+    }
+
+    fn publish_module_package(
+        &self,
+        gas: Gas,
+        package: PublishPackageTx,
+        dry_run: bool,
+    ) -> VmResult {
+        self.vm.publish_module_package(gas, package, dry_run)
+    }
+
+    fn execute_script(
+        &self,
+        gas: Gas,
+        context: ExecutionContext,
+        tx: ScriptTx,
+        dry_run: bool,
+    ) -> VmResult {
+        self.vm.execute_script(gas, context, tx, dry_run)
+    }
+
+    fn clear(&self) {
+        self.vm.clear()
+    }
+}
+
+impl<S, E, O, B, M> BatchVm for ConsensusMode<S, E, O, B, M>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle + DeterministicSource,
+    B: BalanceAccess + DeterministicSource,
+    M: Metrics,
+{
+    fn execute_batch(
+        &self,
+        gas: Gas,
+        context: ExecutionContext,
+        tx: BatchTx,
+        dry_run: bool,
+    ) -> VmResult {
+        self.vm.execute_batch(gas, context, tx, dry_run)
+    }
+}
@@ -1,15 +1,36 @@
 use alloc::borrow::ToOwned;
-use alloc::string::String;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::ops::Bound;
 
 use move_core_types::account_address::AccountAddress;
-use move_core_types::language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{
+    ModuleId, StructTag, TypeTag, CODE_TAG, CORE_CODE_ADDRESS,
+};
 use move_core_types::vm_status::StatusCode;
-use move_vm_runtime::data_cache::RemoteCache;
-use move_vm_types::natives::balance::{Balance, NativeBalance, WalletId};
+use move_vm_runtime::data_cache::{RemoteCache, TransactionEffects};
+use move_vm_types::natives::balance::{Balance, BalanceOperation, NativeBalance, WalletId};
 use move_vm_types::natives::function::PartialVMError;
+use parity_scale_codec::Decode;
+use spin::RwLock;
 use vm::errors::{Location, PartialVMResult, VMError, VMResult};
 
+use crate::access_path::AccessPath;
+use crate::compression;
+use crate::events::Topic;
+use crate::key_codec::{CodecStorage, KeyCodec, RawKeys};
+use crate::oracle::{decode_oracle_acl, history_tag, PriceHistory, ORACLE_ADDRESS, ORACLE_MODULE};
+use crate::panic::guard;
+use crate::tenant::{SpaceStorage, TenantId, HOST_KEY_TAG};
+use crate::types::{BalanceChange, PriceRead, Ticker};
+use crate::vm_config::loader::oracle_acl_tag;
+use crate::vm_config::{RegisteredCurrencies, CONFIG_ADDRESS};
+
 pub trait Storage {
     /// Returns the data for `key` in the storage or `None` if the key can not be found.
     fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
@@ -17,16 +38,348 @@ pub trait Storage {
     fn insert(&self, key: &[u8], value: &[u8]);
     /// Clear the storage of the given `key` and its value.
     fn remove(&self, key: &[u8]);
+    /// Returns the first key after `key` in the byte order or `None` if there is no such key.
+    /// Storages that can't iterate over the keys keep the default implementation.
+    fn next_key(&self, _key: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+    /// Returns `true` if the storage implements `next_key` in the byte order of the keys.
+    fn supports_next_key(&self) -> bool {
+        false
+    }
+}
+
+/// Thread-safe in-memory storage.
+/// Clones share the same underlying data.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStorage {
+    data: Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    /// Creates an empty storage.
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+
+    /// Returns an independent copy of the current storage state.
+    pub fn snapshot(&self) -> MemoryStorage {
+        MemoryStorage {
+            data: Arc::new(RwLock::new(self.data.read().clone())),
+        }
+    }
+
+    /// Replaces the storage state with the given snapshot.
+    pub fn restore(&self, snapshot: &MemoryStorage) {
+        let data = snapshot.data.read().clone();
+        *self.data.write() = data;
+    }
+
+    /// Returns the number of stored keys.
+    pub fn len(&self) -> usize {
+        self.data.read().len()
+    }
+
+    /// Returns `true` if the storage contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.data.read().is_empty()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.data.read().get(key).cloned()
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) {
+        self.data.write().insert(key.to_owned(), value.to_owned());
+    }
+
+    fn remove(&self, key: &[u8]) {
+        self.data.write().remove(key);
+    }
+
+    fn next_key(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.data
+            .read()
+            .range::<[u8], _>((Bound::Excluded(key), Bound::Unbounded))
+            .next()
+            .map(|(next, _)| next.clone())
+    }
+
+    fn supports_next_key(&self) -> bool {
+        true
+    }
+}
+
+/// Storage that keeps all writes in an in-memory layer on top of the `base` storage.
+/// Writes reach the `base` storage only on `commit`.
+#[derive(Debug, Clone)]
+pub struct OverlayStorage<Base: Storage> {
+    base: Base,
+    layer: Arc<RwLock<BTreeMap<Vec<u8>, Option<Vec<u8>>>>>,
+}
+
+impl<Base: Storage> OverlayStorage<Base> {
+    /// Creates an empty write layer over the `base` storage.
+    pub fn new(base: Base) -> OverlayStorage<Base> {
+        OverlayStorage {
+            base,
+            layer: Default::default(),
+        }
+    }
+
+    /// Returns the base storage.
+    pub fn base(&self) -> &Base {
+        &self.base
+    }
+
+    /// Returns `true` if the write layer contains no changes.
+    pub fn is_clean(&self) -> bool {
+        self.layer.read().is_empty()
+    }
+
+    /// Applies all changes of the write layer to the base storage in the key order
+    /// and clears the layer.
+    pub fn commit(&self) {
+        let layer = core::mem::take(&mut *self.layer.write());
+        for (key, value) in layer {
+            match value {
+                Some(value) => self.base.insert(&key, &value),
+                None => self.base.remove(&key),
+            }
+        }
+    }
+
+    /// Drops all changes of the write layer.
+    pub fn discard(&self) {
+        self.layer.write().clear();
+    }
+}
+
+impl<Base: Storage> Storage for OverlayStorage<Base> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.layer.read().get(key) {
+            Some(value) => value.clone(),
+            None => self.base.get(key),
+        }
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) {
+        self.layer
+            .write()
+            .insert(key.to_owned(), Some(value.to_owned()));
+    }
+
+    fn remove(&self, key: &[u8]) {
+        self.layer.write().insert(key.to_owned(), None);
+    }
+
+    fn next_key(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let layer = self.layer.read();
+        let mut base_key = self.base.next_key(key);
+        // Skip the keys removed in the write layer.
+        while let Some(next) = &base_key {
+            match layer.get(next) {
+                Some(None) => base_key = self.base.next_key(next),
+                _ => break,
+            }
+        }
+        let layer_key = layer
+            .range::<[u8], _>((Bound::Excluded(key), Bound::Unbounded))
+            .find(|(_, value)| value.is_some())
+            .map(|(next, _)| next.clone());
+        match (base_key, layer_key) {
+            (Some(base_key), Some(layer_key)) => Some(base_key.min(layer_key)),
+            (base_key, layer_key) => base_key.or(layer_key),
+        }
+    }
+
+    fn supports_next_key(&self) -> bool {
+        self.base.supports_next_key()
+    }
+}
+
+/// Keys accessed during execution.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReadWriteSet {
+    /// Keys read from the storage.
+    pub reads: BTreeSet<Vec<u8>>,
+    /// Keys inserted or removed from the storage.
+    pub writes: BTreeSet<Vec<u8>>,
+}
+
+impl ReadWriteSet {
+    /// Returns `true` if the sets conflict with the `other` sets,
+    /// i.e. one of them writes a key the other one reads or writes.
+    pub fn conflicts_with(&self, other: &ReadWriteSet) -> bool {
+        !self.writes.is_disjoint(&other.writes)
+            || !self.writes.is_disjoint(&other.reads)
+            || !self.reads.is_disjoint(&other.writes)
+    }
+}
+
+/// Storage decorator that records every key read and written through it.
+#[derive(Debug, Clone)]
+pub struct RecordingStorage<S: Storage> {
+    inner: S,
+    record: Arc<RwLock<ReadWriteSet>>,
+}
+
+impl<S: Storage> RecordingStorage<S> {
+    /// Wraps the `inner` storage.
+    pub fn new(inner: S) -> RecordingStorage<S> {
+        RecordingStorage {
+            inner,
+            record: Default::default(),
+        }
+    }
+
+    /// Returns the wrapped storage.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns keys read from the storage.
+    pub fn reads(&self) -> BTreeSet<Vec<u8>> {
+        self.record.read().reads.clone()
+    }
+
+    /// Returns keys inserted or removed from the storage.
+    pub fn writes(&self) -> BTreeSet<Vec<u8>> {
+        self.record.read().writes.clone()
+    }
+
+    /// Returns the recorded sets and starts a new recording.
+    pub fn take(&self) -> ReadWriteSet {
+        core::mem::take(&mut *self.record.write())
+    }
+}
+
+impl<S: Storage> Storage for RecordingStorage<S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.record.write().reads.insert(key.to_owned());
+        self.inner.get(key)
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) {
+        self.record.write().writes.insert(key.to_owned());
+        self.inner.insert(key, value);
+    }
+
+    fn remove(&self, key: &[u8]) {
+        self.record.write().writes.insert(key.to_owned());
+        self.inner.remove(key);
+    }
+
+    fn next_key(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.inner.next_key(key)
+    }
+
+    fn supports_next_key(&self) -> bool {
+        self.inner.supports_next_key()
+    }
+}
+
+/// Storage view that prepends the prefix to every key.
+/// Vms with different prefixes coexist in one backing store, see `State::with_key_prefix`.
+/// A non-empty prefix is stored after its ULEB128 encoded length, so the key spaces of
+/// different prefixes never overlap, even if one prefix starts another one.
+/// The empty prefix leaves the keys unchanged: a vm without a prefix must not share
+/// the backing store with the prefixed ones.
+#[derive(Debug, Clone)]
+pub struct PrefixedStorage<S: Storage> {
+    inner: S,
+    prefix: Vec<u8>,
+    key_prefix: Vec<u8>,
+}
+
+impl<S: Storage> PrefixedStorage<S> {
+    /// Wraps the `inner` storage.
+    pub fn new(inner: S, prefix: Vec<u8>) -> PrefixedStorage<S> {
+        let mut key_prefix = Vec::new();
+        if !prefix.is_empty() {
+            let mut len = prefix.len();
+            while len >= 0x80 {
+                key_prefix.push((len as u8 & 0x7F) | 0x80);
+                len >>= 7;
+            }
+            key_prefix.push(len as u8);
+            key_prefix.extend_from_slice(&prefix);
+        }
+        PrefixedStorage {
+            inner,
+            prefix,
+            key_prefix,
+        }
+    }
+
+    /// Returns the wrapped storage.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the key prefix.
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// Returns the prefix of the keys in the backing store: the prefix after its length.
+    pub fn key_prefix(&self) -> &[u8] {
+        &self.key_prefix
+    }
+
+    fn with_key<R>(&self, key: &[u8], f: impl FnOnce(&[u8]) -> R) -> R {
+        if self.key_prefix.is_empty() {
+            return f(key);
+        }
+        let mut prefixed = Vec::with_capacity(self.key_prefix.len() + key.len());
+        prefixed.extend_from_slice(&self.key_prefix);
+        prefixed.extend_from_slice(key);
+        f(&prefixed)
+    }
+}
+
+impl<S: Storage> Storage for PrefixedStorage<S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.with_key(key, |key| self.inner.get(key))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) {
+        self.with_key(key, |key| self.inner.insert(key, value))
+    }
+
+    fn remove(&self, key: &[u8]) {
+        self.with_key(key, |key| self.inner.remove(key))
+    }
+
+    fn next_key(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.with_key(key, |key| self.inner.next_key(key))
+            .filter(|next| next.starts_with(&self.key_prefix))
+            .map(|next| next[self.key_prefix.len()..].to_vec())
+    }
+
+    fn supports_next_key(&self) -> bool {
+        self.inner.supports_next_key()
+    }
 }
 
 pub trait WriteEffects {
     fn delete(&self, path: AccessKey);
-    fn insert(&self, path: AccessKey, blob: Vec<u8>);
+    fn insert(&self, path: AccessKey, blob: &[u8]);
 }
 
-pub struct State<S, O: Oracle> {
-    store: S,
+/// Storage of a space of the state: the encoded keys in the space under the key prefix.
+pub(crate) type StateStorage<'a, S> =
+    CodecStorage<SpaceStorage<'a, PrefixedStorage<S>>, &'a (dyn KeyCodec + Send + Sync)>;
+
+pub struct State<S: Storage, O: Oracle> {
+    store: PrefixedStorage<S>,
+    codec: Box<dyn KeyCodec + Send + Sync>,
+    /// Prefix the host keys to share the store with the tenants, see `tenant`.
+    tenant_spaces: bool,
     oracle: OracleView<O>,
+    core_address: AccountAddress,
 }
 
 pub trait EventHandler {
@@ -37,6 +390,45 @@ pub trait EventHandler {
         message: Vec<u8>,
         caller: Option<ModuleId>,
     );
+
+    /// Handles the event with its subscription topics, see `events`.
+    /// Defaults to `on_event` dropping the topics.
+    fn on_indexed_event(
+        &self,
+        address: AccountAddress,
+        ty_tag: TypeTag,
+        message: Vec<u8>,
+        caller: Option<ModuleId>,
+        _topics: Vec<Topic>,
+    ) {
+        self.on_event(address, ty_tag, message, caller)
+    }
+
+    /// Handles the event emitted in the storage space of the tenant, see `tenant`.
+    /// Defaults to `on_indexed_event` dropping the tenant.
+    fn on_tenant_event(
+        &self,
+        _tenant: TenantId,
+        address: AccountAddress,
+        ty_tag: TypeTag,
+        message: Vec<u8>,
+        caller: Option<ModuleId>,
+        topics: Vec<Topic>,
+    ) {
+        self.on_indexed_event(address, ty_tag, message, caller, topics)
+    }
+
+    /// Reserves room for all the `events` events of a transaction, emitted right after.
+    /// Returns `false` if the handler can't accept them: the transaction is then discarded
+    /// with `EVENT_CHANNEL_FULL` before its effects are applied. The result depends on the
+    /// local consumer, so handlers of the nodes reaching consensus on the results must accept
+    /// every transaction. Defaults to `true`.
+    fn can_accept(&self, _events: usize) -> bool {
+        true
+    }
+
+    /// Releases the room reserved by `can_accept` once the events are emitted.
+    fn release(&self, _events: usize) {}
 }
 
 impl<S, O> State<S, O>
@@ -46,8 +438,85 @@ where
 {
     pub fn new(store: S, oracle: O) -> State<S, O> {
         State {
-            store,
+            store: PrefixedStorage::new(store, Vec::new()),
+            codec: Box::new(RawKeys),
+            tenant_spaces: false,
             oracle: OracleView::new(oracle),
+            core_address: CORE_CODE_ADDRESS,
+        }
+    }
+
+    /// Sets the address of the oracle prices. Defaults to `CORE_CODE_ADDRESS`.
+    pub fn with_core_address(mut self, core_address: AccountAddress) -> State<S, O> {
+        self.core_address = core_address;
+        self.oracle = self.oracle.with_core_address(core_address);
+        self
+    }
+
+    /// Sets the address of the `Oracle::Price<Ticker>` resources. Defaults to `ORACLE_ADDRESS`.
+    pub fn with_oracle_address(mut self, oracle_address: AccountAddress) -> State<S, O> {
+        self.oracle = self.oracle.with_oracle_address(oracle_address);
+        self
+    }
+
+    /// Prepends the prefix to all storage keys, so several independent vms (e.g. test and main
+    /// instances or multiple tenants) can share one backing store. Defaults to no prefix.
+    pub fn with_key_prefix(mut self, prefix: Vec<u8>) -> State<S, O> {
+        self.store = PrefixedStorage::new(self.store.inner, prefix);
+        self
+    }
+
+    /// Sets the codec of the storage keys, see `key_codec`. Defaults to `RawKeys`.
+    pub fn with_key_codec<C>(mut self, codec: C) -> State<S, O>
+    where
+        C: KeyCodec + Send + Sync + 'static,
+    {
+        self.codec = Box::new(codec);
+        self
+    }
+
+    /// Enables the storage spaces of the tenants, see `tenant`.
+    /// The keys of the host are prefixed with `HOST_KEY_TAG`, so the state of a vm without
+    /// the tenant spaces is not readable with them.
+    pub fn with_tenant_spaces(mut self) -> State<S, O> {
+        self.tenant_spaces = true;
+        self
+    }
+
+    /// Returns `true` if the storage spaces of the tenants are enabled.
+    pub fn has_tenant_spaces(&self) -> bool {
+        self.tenant_spaces
+    }
+
+    /// Returns the address of the oracle prices.
+    pub fn core_address(&self) -> AccountAddress {
+        self.core_address
+    }
+
+    /// Returns the storage key prefix.
+    pub fn key_prefix(&self) -> &[u8] {
+        self.store.prefix()
+    }
+
+    /// Returns the storage of the space of the tenant or of the host with the key prefixes
+    /// and the key codec applied.
+    pub(crate) fn storage(&self, tenant: Option<TenantId>) -> StateStorage<'_, S> {
+        let prefix = match tenant {
+            Some(tenant) => tenant.key_prefix(),
+            None if self.tenant_spaces => vec![HOST_KEY_TAG],
+            None => Vec::new(),
+        };
+        CodecStorage::new(SpaceStorage::new(&self.store, prefix), self.codec.as_ref())
+    }
+
+    /// Returns the view of the space of the tenant or of the host.
+    pub(crate) fn space(&self, tenant: Option<TenantId>) -> StateSpace<'_, S, O> {
+        StateSpace {
+            store: self.storage(tenant),
+            oracle: match tenant {
+                Some(_) => None,
+                None => Some(&self.oracle),
+            },
         }
     }
 }
@@ -58,7 +527,7 @@ where
     O: Oracle,
 {
     fn get_module(&self, module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
-        Ok(self.store.get(AccessKey::from(module_id).as_ref()))
+        self.space(None).get_module(module_id)
     }
 
     fn get_resource(
@@ -66,39 +535,124 @@ where
         address: &AccountAddress,
         tag: &StructTag,
     ) -> PartialVMResult<Option<Vec<u8>>> {
-        if address == &CORE_CODE_ADDRESS {
-            if let Some(ticker) = self.oracle.get_ticker(tag) {
-                return Ok(self.oracle.get_price(&ticker));
+        self.space(None).get_resource(address, tag)
+    }
+
+    fn get_twap(&self, tag: &StructTag, window: u64) -> PartialVMResult<Option<u128>> {
+        self.space(None).get_twap(tag, window)
+    }
+}
+
+impl<S, O> WriteEffects for State<S, O>
+where
+    S: Storage,
+    O: Oracle,
+{
+    fn delete(&self, key: AccessKey) {
+        self.space(None).delete(key)
+    }
+
+    fn insert(&self, key: AccessKey, blob: &[u8]) {
+        self.space(None).insert(key, blob)
+    }
+}
+
+/// View of the storage space of a tenant or of the host, see `State::space`.
+/// Oracle prices are synthesized only in the host space.
+pub(crate) struct StateSpace<'a, S: Storage, O: Oracle> {
+    store: StateStorage<'a, S>,
+    oracle: Option<&'a OracleView<O>>,
+}
+
+impl<S, O> RemoteCache for StateSpace<'_, S, O>
+where
+    S: Storage,
+    O: Oracle,
+{
+    fn get_module(&self, module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
+        enter_span!(TRACE, "get_module", module = %module_id);
+        guard("Storage", || {
+            self.store.get(AccessKey::from(module_id).as_ref())
+        })
+        .map_err(|err| err.finish(Location::Undefined))?
+        .map(compression::decompress)
+        .transpose()
+    }
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        enter_span!(TRACE, "get_resource", address = %address, tag = %tag);
+        if let Some(oracle) = self.oracle {
+            if let Some(ticker) = guard("Oracle", || oracle.get_ticker_at(address, tag))? {
+                return guard("Oracle", || oracle.get_price(&ticker));
             }
         }
 
-        Ok(self.store.get(AccessKey::from((address, tag)).as_ref()))
+        guard("Storage", || {
+            self.store.get(AccessKey::from((address, tag)).as_ref())
+        })
+    }
+
+    fn get_twap(&self, tag: &StructTag, window: u64) -> PartialVMResult<Option<u128>> {
+        match self.oracle {
+            Some(oracle) => guard("Oracle", || {
+                oracle
+                    .get_ticker(tag)
+                    .and_then(|ticker| oracle.get_twap(&ticker, window))
+            }),
+            None => Ok(None),
+        }
     }
 }
 
-impl<S, O> WriteEffects for State<S, O>
+impl<S, O> WriteEffects for StateSpace<'_, S, O>
 where
     S: Storage,
     O: Oracle,
 {
     fn delete(&self, key: AccessKey) {
+        enter_span!(TRACE, "delete");
         self.store.remove(key.as_ref());
     }
 
-    fn insert(&self, key: AccessKey, blob: Vec<u8>) {
-        self.store.insert(key.as_ref(), &blob);
+    fn insert(&self, key: AccessKey, blob: &[u8]) {
+        enter_span!(TRACE, "insert", size = blob.len());
+        self.store.insert(key.as_ref(), blob);
     }
 }
 
 pub trait Oracle {
-    fn get_price(&self, ticker: &str) -> Option<u128>;
+    fn get_price(&self, ticker: &Ticker) -> Option<u128>;
+
+    /// Returns the time-weighted average price over the last `window` seconds.
+    /// Without it the VM computes the average from the recorded `PriceHistory`.
+    fn get_twap(&self, _ticker: &Ticker, _window: u64) -> Option<u128> {
+        None
+    }
+}
+
+/// Oracle without prices.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoOracle;
+
+impl Oracle for NoOracle {
+    fn get_price(&self, _ticker: &Ticker) -> Option<u128> {
+        None
+    }
 }
 
 pub struct OracleView<O: Oracle> {
     oracle: O,
+    core_address: AccountAddress,
+    oracle_address: AccountAddress,
 }
 
 const PONT: &str = "PONT";
+/// Ticker of the coin used to pay transaction fees.
+pub const FEE_TICKER: &str = PONT;
 const COINS: &str = "Coins";
 
 impl<O> OracleView<O>
@@ -106,52 +660,154 @@ where
     O: Oracle,
 {
     pub fn new(oracle: O) -> OracleView<O> {
-        OracleView { oracle }
-    }
-
-    pub fn get_ticker(&self, tag: &StructTag) -> Option<String> {
-        fn extract_name(tag: &TypeTag) -> Option<String> {
-            match tag {
-                TypeTag::Struct(tg) => Some(if tg.module.as_str() == PONT {
-                    PONT.to_owned()
-                } else {
-                    tg.name.as_str().to_owned()
-                }),
-                _ => None,
-            }
+        OracleView {
+            oracle,
+            core_address: CORE_CODE_ADDRESS,
+            oracle_address: ORACLE_ADDRESS,
         }
+    }
 
-        if tag.address == CORE_CODE_ADDRESS
-            && tag.module.as_str() == "Coins"
-            && tag.name.as_str() == "Price"
-        {
-            if tag.type_params.len() == 2 {
-                let first_part = extract_name(&tag.type_params[0])?;
-                let second_part = extract_name(&tag.type_params[1])?;
-
-                Some(format!(
-                    "{}_{}",
-                    first_part.to_uppercase(),
-                    second_part.to_uppercase()
-                ))
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+    /// Sets the address of the `Coins::Price` resources. Defaults to `CORE_CODE_ADDRESS`.
+    pub fn with_core_address(mut self, core_address: AccountAddress) -> OracleView<O> {
+        self.core_address = core_address;
+        self
+    }
+
+    /// Sets the address of the `Oracle::Price<Ticker>` resources. Defaults to `ORACLE_ADDRESS`.
+    pub fn with_oracle_address(mut self, oracle_address: AccountAddress) -> OracleView<O> {
+        self.oracle_address = oracle_address;
+        self
     }
 
-    pub fn get_price(&self, ticker: &str) -> Option<Vec<u8>> {
+    pub fn get_ticker(&self, tag: &StructTag) -> Option<Ticker> {
+        price_ticker(tag, &self.core_address)
+    }
+
+    /// Returns the ticker of the price resource synthesized under the address.
+    pub fn get_ticker_at(&self, address: &AccountAddress, tag: &StructTag) -> Option<Ticker> {
+        synthesized_price(address, tag, &self.core_address, &self.oracle_address)
+    }
+
+    pub fn get_price(&self, ticker: &Ticker) -> Option<Vec<u8>> {
         self.oracle
             .get_price(ticker)
             .map(|price| price.to_le_bytes().to_vec())
     }
+
+    pub fn get_twap(&self, ticker: &Ticker, window: u64) -> Option<u128> {
+        self.oracle.get_twap(ticker, window)
+    }
+}
+
+/// Returns the ticker of the price resource published under the core address.
+///
+/// Prices are synthesized from the oracle as `Coins::Price<A, B>` of the currency pair and
+/// as `Oracle::Price<T>`, where `T` is a struct named after the ticker, e.g. `Tickers::ETH_BTC`.
+fn price_ticker(tag: &StructTag, core_address: &AccountAddress) -> Option<Ticker> {
+    if &tag.address != core_address || tag.name.as_str() != "Price" {
+        return None;
+    }
+    match (tag.module.as_str(), tag.type_params.as_slice()) {
+        (COINS, [first, second]) => pair_ticker(first, second),
+        (ORACLE_MODULE, [TypeTag::Struct(ticker)]) if ticker.type_params.is_empty() => {
+            Ticker::new(ticker.name.as_str()).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Returns the ticker of the price resource synthesized under the address:
+/// `Coins::Price<A, B>` under the core address and `Oracle::Price<T>` under the oracle address.
+fn synthesized_price(
+    address: &AccountAddress,
+    tag: &StructTag,
+    core_address: &AccountAddress,
+    oracle_address: &AccountAddress,
+) -> Option<Ticker> {
+    let expected = if tag.module.as_str() == ORACLE_MODULE {
+        oracle_address
+    } else {
+        core_address
+    };
+    if address != expected {
+        return None;
+    }
+    price_ticker(tag, core_address)
+}
+
+/// Returns the ticker of the currency pair, e.g. `ETH_BTC` for `Coins::ETH` and `Coins::BTC`.
+pub(crate) fn pair_ticker(first: &TypeTag, second: &TypeTag) -> Option<Ticker> {
+    fn extract_name(tag: &TypeTag) -> Option<String> {
+        match tag {
+            TypeTag::Struct(tg) => Some(if tg.module.as_str() == PONT {
+                PONT.to_owned()
+            } else {
+                tg.name.as_str().to_owned()
+            }),
+            _ => None,
+        }
+    }
+
+    let first_part = extract_name(first)?;
+    let second_part = extract_name(second)?;
+    Ticker::new(&format!("{}_{}", first_part, second_part)).ok()
+}
+
+/// Returns the tag of the `Coins::Price` resource of the currency pair.
+pub(crate) fn price_tag(
+    core_address: AccountAddress,
+    first: TypeTag,
+    second: TypeTag,
+) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(COINS).unwrap(),
+        name: Identifier::new("Price").unwrap(),
+        type_params: vec![first, second],
+    }
+}
+
+pub(crate) fn decode_price(blob: &[u8]) -> Option<u128> {
+    let mut bytes = [0; 16];
+    if blob.len() != bytes.len() {
+        return None;
+    }
+    bytes.copy_from_slice(blob);
+    Some(u128::from_le_bytes(bytes))
+}
+
+/// Module and struct names of the block height resource.
+const BLOCK_METADATA: (&str, &str) = ("Block", "BlockMetadata");
+/// Module and struct names of the block timestamp resource.
+const CURRENT_TIMESTAMP: (&str, &str) = ("Time", "CurrentTimestamp");
+
+/// Value provided by the oracle to a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum OracleRead {
+    /// Price resource.
+    Price(StructTag, Option<Vec<u8>>),
+    /// Average price over the window in seconds.
+    Twap(StructTag, u64, Option<u128>),
 }
 
+/// Remote cache for a single transaction.
+///
+/// Synthesizes `0x1::Block::BlockMetadata` and `0x1::Time::CurrentTimestamp` under the core
+/// address from the `ExecutionContext`, so the host never writes them to the storage.
+/// Values stored under these keys are shadowed.
+/// Records oracle prices read by the transaction.
+/// Records the values provided by the oracle, see `take_oracle_reads`.
+/// Synthesized prices are restricted by the default access of the `OracleAcl`.
+/// Computes average prices from the `PriceHistory` under the config address
+/// if the host oracle does not provide them.
 pub struct StateSession<'r, R: RemoteCache> {
     remote: &'r R,
     context: ExecutionContext,
+    price_reads: RefCell<Vec<PriceRead>>,
+    oracle_reads: RefCell<Vec<OracleRead>>,
+    core_address: AccountAddress,
+    config_address: AccountAddress,
+    oracle_address: AccountAddress,
 }
 
 impl<R> StateSession<'_, R>
@@ -159,7 +815,60 @@ where
     R: RemoteCache,
 {
     pub fn new(remote: &R, context: ExecutionContext) -> StateSession<'_, R> {
-        StateSession { remote, context }
+        StateSession {
+            remote,
+            context,
+            price_reads: RefCell::new(vec![]),
+            oracle_reads: RefCell::new(vec![]),
+            core_address: CORE_CODE_ADDRESS,
+            config_address: CONFIG_ADDRESS,
+            oracle_address: ORACLE_ADDRESS,
+        }
+    }
+
+    /// Sets the address of the synthesized resources. Defaults to `CORE_CODE_ADDRESS`.
+    pub fn with_core_address(mut self, core_address: AccountAddress) -> Self {
+        self.core_address = core_address;
+        self
+    }
+
+    /// Sets the address of the price histories. Defaults to `CONFIG_ADDRESS`.
+    pub fn with_config_address(mut self, config_address: AccountAddress) -> Self {
+        self.config_address = config_address;
+        self
+    }
+
+    /// Sets the address of the `Oracle::Price<Ticker>` resources. Defaults to `ORACLE_ADDRESS`.
+    pub fn with_oracle_address(mut self, oracle_address: AccountAddress) -> Self {
+        self.oracle_address = oracle_address;
+        self
+    }
+
+    /// Takes oracle prices read so far.
+    pub fn take_price_reads(&self) -> Vec<PriceRead> {
+        self.price_reads.replace(vec![])
+    }
+
+    /// Takes the values provided by the oracle so far.
+    pub(crate) fn take_oracle_reads(&self) -> Vec<OracleRead> {
+        self.oracle_reads.replace(vec![])
+    }
+
+    /// Checks that the default access of the `OracleAcl` allows to read the price.
+    /// The storage doesn't know the calling module, so the module entries don't apply.
+    fn check_oracle_acl(&self, ticker: &Ticker) -> PartialVMResult<()> {
+        let acl_tag = oracle_acl_tag(self.config_address);
+        let acl = decode_oracle_acl(self.remote.get_resource(&self.config_address, &acl_tag)?)?;
+        if acl.access(None).allows(ticker) {
+            Ok(())
+        } else {
+            Err(
+                PartialVMError::new(StatusCode::HOST_ACCESS_DENIED).with_message(format!(
+                    "Price of {} is denied by the oracle access control list",
+                    ticker
+                )),
+            )
+        }
     }
 }
 
@@ -176,21 +885,76 @@ where
         address: &AccountAddress,
         tag: &StructTag,
     ) -> PartialVMResult<Option<Vec<u8>>> {
-        if address == &CORE_CODE_ADDRESS && tag.address == CORE_CODE_ADDRESS {
-            if tag.module.as_str() == "Block" && tag.name.as_str() == "BlockMetadata" {
-                return Ok(Some(self.context.block_height.to_le_bytes().to_vec()));
-            } else if tag.module.as_str() == "Time" && tag.name.as_str() == "CurrentTimestamp" {
-                return Ok(Some(self.context.timestamp.to_le_bytes().to_vec()));
+        if address == &self.core_address && tag.address == self.core_address {
+            match (tag.module.as_str(), tag.name.as_str()) {
+                BLOCK_METADATA => {
+                    return Ok(Some(self.context.block_height.to_le_bytes().to_vec()));
+                }
+                CURRENT_TIMESTAMP => {
+                    return Ok(Some(self.context.timestamp.to_le_bytes().to_vec()));
+                }
+                _ => {}
+            }
+        }
+        if let Some(ticker) =
+            synthesized_price(address, tag, &self.core_address, &self.oracle_address)
+        {
+            if !self.context.host_policy().oracle {
+                return Err(host_access_denied("Oracle"));
             }
+            self.check_oracle_acl(&ticker)?;
+            let price = self.remote.get_resource(address, tag)?;
+            self.oracle_reads
+                .borrow_mut()
+                .push(OracleRead::Price(tag.clone(), price.clone()));
+            self.price_reads.borrow_mut().push(PriceRead {
+                ticker,
+                price: price.as_ref().and_then(|blob| decode_price(blob)),
+            });
+            return Ok(price);
         }
         self.remote.get_resource(address, tag)
     }
+
+    fn get_twap(&self, tag: &StructTag, window: u64) -> PartialVMResult<Option<u128>> {
+        if !self.context.host_policy().oracle {
+            return Err(host_access_denied("Oracle"));
+        }
+        let twap = self.remote.get_twap(tag, window)?;
+        self.oracle_reads
+            .borrow_mut()
+            .push(OracleRead::Twap(tag.clone(), window, twap));
+        if twap.is_some() {
+            return Ok(twap);
+        }
+        if price_ticker(tag, &self.core_address).is_none() {
+            return Ok(None);
+        }
+        let history_tag = history_tag(self.config_address, tag.type_params.clone());
+        Ok(
+            match self
+                .remote
+                .get_resource(&self.config_address, &history_tag)?
+            {
+                Some(blob) => PriceHistory::decode(&mut blob.as_slice())
+                    .map_err(|err| {
+                        PartialVMError::new(StatusCode::STORAGE_ERROR).with_message(err.to_string())
+                    })?
+                    .twap(self.context.timestamp, window),
+                None => None,
+            },
+        )
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExecutionContext {
     pub timestamp: u64,
     pub block_height: u64,
+    /// Tenant of the transaction, see `tenant`. `None` for the transactions of the host.
+    pub tenant: Option<TenantId>,
+    /// Host services available to the transaction.
+    pub policy: SessionPolicy,
 }
 
 impl ExecutionContext {
@@ -198,66 +962,398 @@ impl ExecutionContext {
         ExecutionContext {
             timestamp,
             block_height,
+            tenant: None,
+            policy: SessionPolicy::default(),
+        }
+    }
+
+    /// Executes the transaction in the storage space of the tenant.
+    pub fn with_tenant(mut self, tenant: TenantId) -> ExecutionContext {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Restricts the host services available to the transaction.
+    pub fn with_policy(mut self, policy: SessionPolicy) -> ExecutionContext {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the host services available to the transaction.
+    /// Tenant transactions have no access to the native balances and the oracle of the host.
+    pub fn host_policy(&self) -> SessionPolicy {
+        match self.tenant {
+            Some(_) => SessionPolicy::sandbox(),
+            None => self.policy,
+        }
+    }
+}
+
+/// Host services available to a transaction, e.g. none for public simulations.
+/// A transaction reading a disabled service fails with `HOST_ACCESS_DENIED`
+/// and its effects are not applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionPolicy {
+    /// Native balances can be read and changed.
+    pub bank: bool,
+    /// Oracle prices can be read.
+    pub oracle: bool,
+}
+
+impl SessionPolicy {
+    /// Policy of a sandboxed execution without the bank and the oracle.
+    pub fn sandbox() -> SessionPolicy {
+        SessionPolicy {
+            bank: false,
+            oracle: false,
+        }
+    }
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        SessionPolicy {
+            bank: true,
+            oracle: true,
         }
     }
 }
 
 pub trait BalanceAccess {
-    fn get_balance(&self, address: &AccountAddress, ticker: &str) -> Option<Balance>;
-    fn deposit(&self, address: &AccountAddress, ticker: &str, amount: Balance);
-    fn withdraw(&self, address: &AccountAddress, ticker: &str, amount: Balance);
+    fn get_balance(&self, address: &AccountAddress, ticker: &Ticker) -> Option<Balance>;
+    fn deposit(&self, address: &AccountAddress, ticker: &Ticker, amount: Balance);
+    fn withdraw(&self, address: &AccountAddress, ticker: &Ticker, amount: Balance);
+}
+
+/// Balance access of a chain without native balances.
+/// Accounts have no native balance, so native deposits and withdrawals never happen.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoBank;
+
+impl BalanceAccess for NoBank {
+    fn get_balance(&self, _address: &AccountAddress, _ticker: &Ticker) -> Option<Balance> {
+        None
+    }
+
+    fn deposit(&self, _address: &AccountAddress, _ticker: &Ticker, _amount: Balance) {}
+
+    fn withdraw(&self, _address: &AccountAddress, _ticker: &Ticker, _amount: Balance) {}
 }
 
 pub struct Bank<B: BalanceAccess> {
     access: B,
+    core_address: AccountAddress,
+    currencies: RwLock<Option<RegisteredCurrencies>>,
 }
 
 impl<B: BalanceAccess> Bank<B> {
     pub fn new(access: B) -> Bank<B> {
-        Bank { access }
+        Bank {
+            access,
+            core_address: CORE_CODE_ADDRESS,
+            currencies: RwLock::new(None),
+        }
+    }
+
+    /// Restricts operations to the registered currencies. `None` accepts any currency.
+    pub fn set_registered_currencies(&self, currencies: Option<RegisteredCurrencies>) {
+        *self.currencies.write() = currencies;
+    }
+
+    /// Returns `true` if the currency is registered or no currencies are registered at all.
+    pub fn is_registered(&self, ticker: &Ticker) -> bool {
+        self.currencies
+            .read()
+            .as_ref()
+            .map(|currencies| currencies.contains(ticker))
+            .unwrap_or(true)
+    }
+
+    /// Returns an error if the wallet currency is not registered.
+    pub fn check_registered(&self, wallet_id: &WalletId) -> Result<(), VMError> {
+        match ticker(wallet_id, &self.core_address) {
+            Some(ticker) if !self.is_registered(&ticker) => {
+                Err(PartialVMError::new(StatusCode::RESOURCE_DOES_NOT_EXIST)
+                    .with_message(format!("Currency {} is not registered", ticker))
+                    .finish(Location::Undefined))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks without changing any balance that the balance changes can be applied in order:
+    /// every deposit is covered by the native balance left by the previous changes.
+    pub fn check_changes<'a>(
+        &self,
+        changes: impl IntoIterator<Item = &'a BalanceChange>,
+    ) -> Result<(), VMError> {
+        let mut balances = BTreeMap::new();
+        for change in changes {
+            let ticker = ticker(&change.wallet_id, &self.core_address).ok_or_else(|| {
+                PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR).finish(Location::Undefined)
+            })?;
+            let address = change.wallet_id.address;
+            let balance = match balances.get(&(address, ticker.clone())) {
+                Some(balance) => *balance,
+                None => self.balance(&address, &ticker).unwrap_or_default(),
+            };
+            let balance = match change.operation {
+                BalanceOperation::Deposit(amount) => balance.checked_sub(amount),
+                BalanceOperation::Withdraw(amount) => balance.checked_add(amount),
+            }
+            .ok_or_else(|| {
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message(format!(
+                        "Native balance of {} {} is insufficient",
+                        address, ticker
+                    ))
+                    .finish(Location::Undefined)
+            })?;
+            balances.insert((address, ticker), balance);
+        }
+        Ok(())
+    }
+
+    /// Sets the address of the native currencies. Defaults to `CORE_CODE_ADDRESS`.
+    pub fn with_core_address(mut self, core_address: AccountAddress) -> Bank<B> {
+        self.core_address = core_address;
+        self
+    }
+
+    /// Decodes the wallet id into the account address and the currency ticker.
+    /// See `decode_wallet_id`.
+    pub fn decode_wallet_id(&self, wallet_id: &WalletId) -> Option<(AccountAddress, Ticker)> {
+        decode_wallet_id(wallet_id, &self.core_address)
     }
 
     pub fn deposit(&self, wallet_id: &WalletId, amount: Balance) -> Result<(), VMError> {
-        if let Some(ticker) = ticker(wallet_id) {
-            self.access.deposit(&wallet_id.address, ticker, amount);
-            Ok(())
+        if let Some(ticker) = ticker(wallet_id, &self.core_address) {
+            guard("BalanceAccess", || {
+                self.access.deposit(&wallet_id.address, &ticker, amount)
+            })
+            .map_err(|err| err.finish(Location::Undefined))
         } else {
             Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR).finish(Location::Undefined))
         }
     }
 
     pub fn withdraw(&self, wallet_id: &WalletId, amount: Balance) -> Result<(), VMError> {
-        if let Some(ticker) = ticker(wallet_id) {
-            self.access.withdraw(&wallet_id.address, ticker, amount);
-            Ok(())
+        if let Some(ticker) = ticker(wallet_id, &self.core_address) {
+            guard("BalanceAccess", || {
+                self.access.withdraw(&wallet_id.address, &ticker, amount)
+            })
+            .map_err(|err| err.finish(Location::Undefined))
         } else {
             Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR).finish(Location::Undefined))
         }
     }
+
+    /// Returns `true` if fees can be paid in the currency: a registered currency or,
+    /// if no currencies are registered, the fee currency, see `fee_ticker`.
+    pub fn is_gas_currency(&self, ticker: &Ticker) -> bool {
+        match self.currencies.read().as_ref() {
+            Some(currencies) => currencies.contains(ticker),
+            None => ticker == &fee_ticker(),
+        }
+    }
+
+    /// Returns `true` if the payer has enough coins of the currency to pay the fee.
+    pub fn can_pay_fee(&self, payer: &AccountAddress, ticker: &Ticker, fee: Balance) -> bool {
+        self.balance(payer, ticker)
+            .map(|balance| balance >= fee)
+            .unwrap_or(fee == 0)
+    }
+
+    /// Returns the native balance of the currency.
+    pub fn native_balance(&self, address: &AccountAddress, ticker: &Ticker) -> Option<Balance> {
+        self.balance(address, ticker)
+    }
+
+    /// Returns `true` if the address has a positive native balance of the fee currency.
+    pub fn has_native_balance(&self, address: &AccountAddress) -> bool {
+        self.balance(address, &fee_ticker())
+            .map(|balance| balance > 0)
+            .unwrap_or(false)
+    }
+
+    /// Returns the balance change taking the fee in the currency from the native balance
+    /// of the payer, `None` if there is nothing to pay.
+    /// Like a deposit to the vm, the fee leaves the native balance.
+    pub fn fee_change(
+        &self,
+        payer: &AccountAddress,
+        ticker: &Ticker,
+        fee: Balance,
+    ) -> Option<BalanceChange> {
+        if fee == 0 {
+            return None;
+        }
+        coin_tag(self.core_address, ticker).map(|tag| BalanceChange {
+            wallet_id: WalletId::new(*payer, tag),
+            operation: BalanceOperation::Deposit(fee),
+        })
+    }
+
+    /// Returns the balance, treating a panic of the balance access as a missing balance.
+    fn balance(&self, address: &AccountAddress, ticker: &Ticker) -> Option<Balance> {
+        guard("BalanceAccess", || self.access.get_balance(address, ticker))
+            .ok()
+            .flatten()
+    }
 }
 
 impl<B: BalanceAccess> NativeBalance for &Bank<B> {
     fn get_balance(&self, wallet_id: &WalletId) -> Option<Balance> {
-        if let Some(ticker) = ticker(wallet_id) {
-            self.access.get_balance(&wallet_id.address, ticker)
+        match ticker(wallet_id, &self.core_address) {
+            Some(ticker) if self.is_registered(&ticker) => {
+                self.balance(&wallet_id.address, &ticker)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Native balances of a session restricted by `SessionPolicy::bank`.
+pub(crate) struct SessionBank<'a, B: BalanceAccess> {
+    bank: &'a Bank<B>,
+    allowed: bool,
+    /// A balance was read while the bank is disabled.
+    denied: Cell<bool>,
+    /// Balances read by the session.
+    reads: RefCell<Vec<(WalletId, Option<Balance>)>>,
+    /// Fee payer, the fee currency and the max fee hidden from the session.
+    reserved: Option<(AccountAddress, Ticker, Balance)>,
+}
+
+impl<'a, B: BalanceAccess> SessionBank<'a, B> {
+    pub fn new(bank: &'a Bank<B>, policy: SessionPolicy) -> SessionBank<'a, B> {
+        SessionBank {
+            bank,
+            allowed: policy.bank,
+            denied: Cell::new(false),
+            reads: RefCell::new(vec![]),
+            reserved: None,
+        }
+    }
+
+    /// Reserves the max fee: the session sees the fee currency balance of the payer without it,
+    /// so the transaction can't spend the coins needed to pay the fee.
+    pub fn with_reserved_fee(
+        mut self,
+        payer: Option<&AccountAddress>,
+        ticker: &Ticker,
+        max_fee: Balance,
+    ) -> SessionBank<'a, B> {
+        self.reserved = payer.map(|payer| (*payer, ticker.clone(), max_fee));
+        self
+    }
+
+    /// Takes the balances read so far.
+    pub fn take_balance_reads(&self) -> Vec<(WalletId, Option<Balance>)> {
+        self.reads.replace(vec![])
+    }
+
+    /// Fails with `HOST_ACCESS_DENIED` if the session read a balance of the disabled bank,
+    /// whatever the result of the session, e.g. the script aborted on the missing balance.
+    pub fn check_denied(&self) -> VMResult<()> {
+        if self.denied.get() {
+            Err(host_access_denied("Bank").finish(Location::Undefined))
         } else {
-            None
+            Ok(())
+        }
+    }
+
+    /// Fails with `HOST_ACCESS_DENIED` if the session used the disabled bank:
+    /// read a balance or changed one, e.g. withdrew the coins held by the vm.
+    pub fn check_access(&self, effects: &TransactionEffects) -> VMResult<()> {
+        if self.allowed || (!self.denied.get() && effects.wallet_ops.is_empty()) {
+            Ok(())
+        } else {
+            Err(host_access_denied("Bank").finish(Location::Undefined))
         }
     }
 }
 
-fn ticker(wallet_id: &WalletId) -> Option<&str> {
-    if wallet_id.tag.address == CORE_CODE_ADDRESS {
-        match wallet_id.tag.module.as_str() {
-            PONT => Some(PONT),
-            COINS => Some(wallet_id.tag.name.as_str()),
-            _ => None,
+impl<B: BalanceAccess> NativeBalance for &SessionBank<'_, B> {
+    fn get_balance(&self, wallet_id: &WalletId) -> Option<Balance> {
+        if self.allowed {
+            let balance = NativeBalance::get_balance(&self.bank, wallet_id);
+            self.reads.borrow_mut().push((wallet_id.clone(), balance));
+            match &self.reserved {
+                Some((payer, currency, max_fee))
+                    if &wallet_id.address == payer
+                        && ticker(wallet_id, &self.bank.core_address).as_ref()
+                            == Some(currency) =>
+                {
+                    balance.map(|balance| balance.saturating_sub(*max_fee))
+                }
+                _ => balance,
+            }
+        } else {
+            self.denied.set(true);
+            None
         }
-    } else {
-        None
     }
 }
 
+fn host_access_denied(host: &str) -> PartialVMError {
+    PartialVMError::new(StatusCode::HOST_ACCESS_DENIED)
+        .with_message(format!("{} is disabled by the session policy", host))
+}
+
+/// Decodes the wallet id into the account address and the currency ticker.
+///
+/// Native currencies are `PONT::T` and the `Coins` structs named after the ticker
+/// (uppercase ASCII letters and digits) published under the core code address,
+/// see `AddressesConfig::core_code_address`. Returns `None` for any other struct.
+pub fn decode_wallet_id(
+    wallet_id: &WalletId,
+    core_address: &AccountAddress,
+) -> Option<(AccountAddress, Ticker)> {
+    ticker(wallet_id, core_address).map(|ticker| (wallet_id.address, ticker))
+}
+
+/// Returns the ticker of the coin used to pay transaction fees.
+pub fn fee_ticker() -> Ticker {
+    Ticker::new(FEE_TICKER).expect("Valid fee ticker")
+}
+
+fn ticker(wallet_id: &WalletId, core_address: &AccountAddress) -> Option<Ticker> {
+    let tag = &wallet_id.tag;
+    if &tag.address != core_address || !tag.type_params.is_empty() {
+        return None;
+    }
+
+    let ticker = match tag.module.as_str() {
+        PONT if tag.name.as_str() == "T" => PONT,
+        COINS if is_valid_ticker(tag.name.as_str()) => tag.name.as_str(),
+        _ => return None,
+    };
+    Ticker::new(ticker).ok()
+}
+
+/// Returns the tag of the native currency struct, the inverse of `decode_wallet_id`.
+pub(crate) fn coin_tag(core_address: AccountAddress, ticker: &Ticker) -> Option<StructTag> {
+    let (module, name) = match ticker.as_str() {
+        PONT => (PONT, "T"),
+        ticker if is_valid_ticker(ticker) => (COINS, ticker),
+        _ => return None,
+    };
+    Some(StructTag {
+        address: core_address,
+        module: Identifier::new(module).ok()?,
+        name: Identifier::new(name).ok()?,
+        type_params: vec![],
+    })
+}
+
+fn is_valid_ticker(ticker: &str) -> bool {
+    !ticker.is_empty()
+        && ticker
+            .chars()
+            .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit())
+}
+
 pub struct AccessKey(Vec<u8>);
 
 impl From<(&AccountAddress, &StructTag)> for AccessKey {
@@ -270,12 +1366,31 @@ impl From<(&AccountAddress, &StructTag)> for AccessKey {
     }
 }
 
+impl From<&AccessPath> for AccessKey {
+    fn from(path: &AccessPath) -> Self {
+        let mut key = Vec::with_capacity(AccountAddress::LENGTH + path.path.len());
+        key.extend_from_slice(path.address.as_ref());
+        key.extend_from_slice(&path.path);
+        AccessKey(key)
+    }
+}
+
 impl From<&ModuleId> for AccessKey {
     fn from(id: &ModuleId) -> Self {
         AccessKey(id.access_vector())
     }
 }
 
+impl AccessKey {
+    /// Returns the module id if the key is the key of a module.
+    pub fn module_id(&self) -> Option<ModuleId> {
+        match self.0.split_first() {
+            Some((&CODE_TAG, id)) => bcs::from_bytes(id).ok(),
+            _ => None,
+        }
+    }
+}
+
 impl AsRef<[u8]> for AccessKey {
     fn as_ref(&self) -> &[u8] {
         &self.0
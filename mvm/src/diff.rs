@@ -0,0 +1,107 @@
+use alloc::vec::Vec;
+
+use anyhow::{Error, Result};
+use move_core_types::language_storage::ResourceKey;
+use serde::de::DeserializeOwned;
+
+use crate::data::{AccessKey, Storage};
+
+/// Resource state change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Resource does not exist in the `before` state.
+    Added(Vec<u8>),
+    /// Resource does not exist in the `after` state.
+    Removed(Vec<u8>),
+    /// Resource exists in both states with different values.
+    Changed { before: Vec<u8>, after: Vec<u8> },
+}
+
+/// Resource diff entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceDiff {
+    /// Resource key.
+    pub key: ResourceKey,
+    /// Resource change.
+    pub change: Change,
+}
+
+impl ResourceDiff {
+    /// Returns decoded value of the resource before the change.
+    pub fn before<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        match &self.change {
+            Change::Added(_) => Ok(None),
+            Change::Removed(before) | Change::Changed { before, .. } => decode(before).map(Some),
+        }
+    }
+
+    /// Returns decoded value of the resource after the change.
+    pub fn after<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        match &self.change {
+            Change::Removed(_) => Ok(None),
+            Change::Added(after) | Change::Changed { after, .. } => decode(after).map(Some),
+        }
+    }
+}
+
+/// Difference between two states.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StateDiff {
+    pub added: Vec<ResourceDiff>,
+    pub removed: Vec<ResourceDiff>,
+    pub changed: Vec<ResourceDiff>,
+}
+
+impl StateDiff {
+    /// Returns `true` if the states are equal for all the filtered resources.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Returns diff entry of the given resource.
+    pub fn get(&self, key: &ResourceKey) -> Option<&ResourceDiff> {
+        self.added
+            .iter()
+            .chain(self.removed.iter())
+            .chain(self.changed.iter())
+            .find(|diff| &diff.key == key)
+    }
+}
+
+/// Compares the `filter` resources of the `before` and `after` states.
+/// `Storage` does not provide key enumeration, so only the listed resources are compared.
+pub fn state_diff<B, A>(before: &B, after: &A, filter: &[ResourceKey]) -> StateDiff
+where
+    B: Storage,
+    A: Storage,
+{
+    let mut diff = StateDiff::default();
+
+    for key in filter {
+        let access_key = AccessKey::from((&key.address, &key.type_));
+        match (
+            before.get(access_key.as_ref()),
+            after.get(access_key.as_ref()),
+        ) {
+            (None, Some(after)) => diff.added.push(ResourceDiff {
+                key: key.clone(),
+                change: Change::Added(after),
+            }),
+            (Some(before), None) => diff.removed.push(ResourceDiff {
+                key: key.clone(),
+                change: Change::Removed(before),
+            }),
+            (Some(before), Some(after)) if before != after => diff.changed.push(ResourceDiff {
+                key: key.clone(),
+                change: Change::Changed { before, after },
+            }),
+            _ => {}
+        }
+    }
+
+    diff
+}
+
+fn decode<T: DeserializeOwned>(blob: &[u8]) -> Result<T> {
+    bcs::from_bytes(blob).map_err(Error::msg)
+}
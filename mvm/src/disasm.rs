@@ -0,0 +1,217 @@
+//! Module bytecode disassembler, enabled with the `disasm` feature.
+//!
+//! Produces a readable listing of the module: structs with their fields and functions with
+//! their bytecode. Function, struct and field references are resolved to their names,
+//! constants are rendered with their values.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use anyhow::{Error, Result};
+use vm::access::ModuleAccess;
+use vm::file_format::{
+    Bytecode, FieldHandleIndex, FunctionHandleIndex, SignatureIndex, StructDefinitionIndex,
+    StructFieldInformation,
+};
+use vm::CompiledModule;
+
+use crate::metadata::{struct_name, type_name};
+
+/// Disassembles the module bytecode.
+pub fn disassemble(bytecode: &[u8]) -> Result<String> {
+    let module = CompiledModule::deserialize(bytecode)
+        .map_err(|err| Error::msg(format!("Failed to deserialize module: {:?}", err)))?;
+    disassemble_module(&module).map_err(|_| Error::msg("Failed to format module"))
+}
+
+/// Disassembles the compiled module.
+pub fn disassemble_module(module: &CompiledModule) -> Result<String, core::fmt::Error> {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "module 0x{}::{} {{",
+        module.address().short_str_lossless(),
+        module.name()
+    )?;
+
+    for def in module.struct_defs() {
+        let handle = module.struct_handle_at(def.struct_handle);
+        let kind = if handle.is_nominal_resource {
+            "resource struct"
+        } else {
+            "struct"
+        };
+        let name = module.identifier_at(handle.name);
+        let type_params = type_parameters(handle.type_parameters.len());
+        match &def.field_information {
+            StructFieldInformation::Native => {
+                writeln!(out, "    native {} {}{};", kind, name, type_params)?
+            }
+            StructFieldInformation::Declared(fields) => {
+                writeln!(out, "    {} {}{} {{", kind, name, type_params)?;
+                for field in fields {
+                    writeln!(
+                        out,
+                        "        {}: {},",
+                        module.identifier_at(field.name),
+                        type_name(module, &field.signature.0)
+                    )?;
+                }
+                writeln!(out, "    }}")?;
+            }
+        }
+    }
+
+    for def in module.function_defs() {
+        let handle = module.function_handle_at(def.function);
+        writeln!(out)?;
+        write!(
+            out,
+            "    {}{}fun {}{}({})",
+            if def.is_native() { "native " } else { "" },
+            if def.is_public { "public " } else { "" },
+            module.identifier_at(handle.name),
+            type_parameters(handle.type_parameters.len()),
+            types(module, handle.parameters)
+        )?;
+        if !module.signature_at(handle.return_).0.is_empty() {
+            write!(out, ": {}", types(module, handle.return_))?;
+        }
+        let code = match &def.code {
+            Some(code) => code,
+            None => {
+                writeln!(out, ";")?;
+                continue;
+            }
+        };
+        writeln!(out, " {{")?;
+        if !module.signature_at(code.locals).0.is_empty() {
+            writeln!(out, "        locals: {}", types(module, code.locals))?;
+        }
+        for (offset, instruction) in code.code.iter().enumerate() {
+            writeln!(
+                out,
+                "        {}: {}",
+                offset,
+                instruction_name(module, instruction)
+            )?;
+        }
+        writeln!(out, "    }}")?;
+    }
+    writeln!(out, "}}")?;
+    Ok(out)
+}
+
+fn instruction_name(module: &CompiledModule, instruction: &Bytecode) -> String {
+    let def_name =
+        |idx: StructDefinitionIndex| struct_name(module, module.struct_def_at(idx).struct_handle);
+    let inst_name = |name: &str, idx| {
+        let inst = module.struct_instantiation_at(idx);
+        format!(
+            "{} {}<{}>",
+            name,
+            def_name(inst.def),
+            types(module, inst.type_parameters)
+        )
+    };
+    let function_name = |idx: FunctionHandleIndex| {
+        let handle = module.function_handle_at(idx);
+        let module_handle = module.module_handle_at(handle.module);
+        format!(
+            "0x{}::{}::{}",
+            module
+                .address_identifier_at(module_handle.address)
+                .short_str_lossless(),
+            module.identifier_at(module_handle.name),
+            module.identifier_at(handle.name)
+        )
+    };
+    let field_name = |idx: FieldHandleIndex, type_args: String| {
+        let handle = module.field_handle_at(idx);
+        let def = module.struct_def_at(handle.owner);
+        let field = match &def.field_information {
+            StructFieldInformation::Declared(fields) => fields
+                .get(handle.field as usize)
+                .map(|field| module.identifier_at(field.name).to_string()),
+            StructFieldInformation::Native => None,
+        };
+        format!(
+            "{}{}.{}",
+            struct_name(module, def.struct_handle),
+            type_args,
+            field.unwrap_or_else(|| handle.field.to_string())
+        )
+    };
+
+    match instruction {
+        Bytecode::LdConst(idx) => {
+            let constant = module.constant_at(*idx);
+            match constant.deserialize_constant() {
+                Some(value) => format!("LdConst {:?}", value),
+                None => format!("LdConst 0x{}", hex::encode(&constant.data)),
+            }
+        }
+        Bytecode::Call(idx) => format!("Call {}", function_name(*idx)),
+        Bytecode::CallGeneric(idx) => {
+            let inst = module.function_instantiation_at(*idx);
+            format!(
+                "Call {}<{}>",
+                function_name(inst.handle),
+                types(module, inst.type_parameters)
+            )
+        }
+        Bytecode::Pack(idx) => format!("Pack {}", def_name(*idx)),
+        Bytecode::PackGeneric(idx) => inst_name("Pack", *idx),
+        Bytecode::Unpack(idx) => format!("Unpack {}", def_name(*idx)),
+        Bytecode::UnpackGeneric(idx) => inst_name("Unpack", *idx),
+        Bytecode::MutBorrowField(idx) => {
+            format!("MutBorrowField {}", field_name(*idx, String::new()))
+        }
+        Bytecode::ImmBorrowField(idx) => {
+            format!("ImmBorrowField {}", field_name(*idx, String::new()))
+        }
+        Bytecode::MutBorrowFieldGeneric(idx) | Bytecode::ImmBorrowFieldGeneric(idx) => {
+            let inst = module.field_instantiation_at(*idx);
+            let name = if let Bytecode::MutBorrowFieldGeneric(_) = instruction {
+                "MutBorrowField"
+            } else {
+                "ImmBorrowField"
+            };
+            let type_args = format!("<{}>", types(module, inst.type_parameters));
+            format!("{} {}", name, field_name(inst.handle, type_args))
+        }
+        Bytecode::MutBorrowGlobal(idx) => format!("MutBorrowGlobal {}", def_name(*idx)),
+        Bytecode::MutBorrowGlobalGeneric(idx) => inst_name("MutBorrowGlobal", *idx),
+        Bytecode::ImmBorrowGlobal(idx) => format!("ImmBorrowGlobal {}", def_name(*idx)),
+        Bytecode::ImmBorrowGlobalGeneric(idx) => inst_name("ImmBorrowGlobal", *idx),
+        Bytecode::Exists(idx) => format!("Exists {}", def_name(*idx)),
+        Bytecode::ExistsGeneric(idx) => inst_name("Exists", *idx),
+        Bytecode::MoveFrom(idx) => format!("MoveFrom {}", def_name(*idx)),
+        Bytecode::MoveFromGeneric(idx) => inst_name("MoveFrom", *idx),
+        Bytecode::MoveTo(idx) => format!("MoveTo {}", def_name(*idx)),
+        Bytecode::MoveToGeneric(idx) => inst_name("MoveTo", *idx),
+        instruction => format!("{:?}", instruction),
+    }
+}
+
+fn types(module: &CompiledModule, idx: SignatureIndex) -> String {
+    module
+        .signature_at(idx)
+        .0
+        .iter()
+        .map(|token| type_name(module, token))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn type_parameters(count: usize) -> String {
+    if count == 0 {
+        return String::new();
+    }
+    let params = (0..count)
+        .map(|idx| format!("T{}", idx))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("<{}>", params)
+}
@@ -0,0 +1,332 @@
+//! Compact errors of the failed transactions for `DispatchError::Module`.
+//!
+//! `ErrorKind` maps every `StatusCode` except `EXECUTED` to one byte, the `error` of
+//! `DispatchError::Module`. Statuses the user can act upon have their own variants, the rest
+//! are grouped by the status type. The discriminants are stable: variants are never renumbered
+//! and new variants take unused indices.
+//! `VmError` carries the kind with the sub status, e.g. the abort code, for the pallets
+//! reporting it next to the dispatch error, e.g. in an event.
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use move_core_types::vm_status::{StatusCode, StatusType};
+
+use crate::types::VmResult;
+
+/// Kind of the error of a failed transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ErrorKind {
+    /// Unknown status.
+    Unknown = 0,
+
+    // Validation.
+    /// The authenticator rejected the transaction proof.
+    InvalidSignature = 1,
+    /// The sender has no role required by the transaction.
+    NoAccountRole = 2,
+    /// The sender account doesn't exist.
+    AccountDoesNotExist = 3,
+    /// The validity window of the transaction is over.
+    TransactionExpired = 4,
+    /// The validity window of the transaction has not started yet.
+    TransactionNotYetValid = 5,
+    /// The transaction is signed for another chain.
+    BadChainId = 6,
+    /// The vm is halted by the governance.
+    VmHalted = 7,
+    /// The gas limit or the gas price is out of bounds.
+    InvalidGas = 8,
+    /// The transaction, its arguments or type arguments exceed the limits.
+    TransactionTooLarge = 9,
+    /// The sender is not allowed to publish the module.
+    InvalidModulePublisher = 10,
+    /// The script or the module is not known.
+    UnknownCode = 11,
+    /// Other validation errors.
+    ValidationError = 19,
+
+    // Verification.
+    /// A dependency of the module or the script is not published.
+    LinkerError = 20,
+    /// A module with the name is published by another package.
+    DuplicateModuleName = 21,
+    /// The module update is not compatible with the published module.
+    IncompatibleModuleUpdate = 22,
+    /// The published module is immutable.
+    ImmutableModuleUpdate = 23,
+    /// The module exceeds the size limits.
+    ModuleTooLarge = 24,
+    /// The called function is not an entry function.
+    NotEntryFunction = 25,
+    /// The type arguments don't match the type parameters.
+    TypeArgumentsMismatch = 26,
+    /// Other bytecode verification errors.
+    VerificationError = 39,
+
+    // Deserialization.
+    /// The bytecode or a value can't be deserialized.
+    DeserializationError = 40,
+
+    // Invariant violation.
+    /// The host storage failed.
+    StorageError = 50,
+    /// Other invariant violations, a bug of the vm.
+    InvariantViolation = 59,
+
+    // Execution.
+    /// The transaction aborted, the abort code is the sub status.
+    Aborted = 60,
+    /// The transaction ran out of gas.
+    OutOfGas = 61,
+    /// The resource doesn't exist.
+    ResourceDoesNotExist = 62,
+    /// The resource already exists.
+    ResourceAlreadyExists = 63,
+    /// The data is missing in the storage.
+    MissingData = 64,
+    /// The data in the storage can't be decoded.
+    DataFormatError = 65,
+    /// Arithmetic overflow, underflow or division by zero.
+    ArithmeticError = 66,
+    /// The call stack or a value is too deep.
+    StackOverflow = 67,
+    /// The host interrupted the execution, the transaction is discarded.
+    ExecutionInterrupted = 68,
+    /// The transaction emitted too many events.
+    EventLimitExceeded = 69,
+    /// The transaction accessed the bank or the oracle disabled by the session policy.
+    HostAccessDenied = 70,
+    /// The script exceeded the instruction limit.
+    InstructionLimitExceeded = 71,
+    /// Other execution errors.
+    ExecutionError = 79,
+}
+
+/// All errors in the order of the indices.
+const ERRORS: &[ErrorKind] = &[
+    ErrorKind::Unknown,
+    ErrorKind::InvalidSignature,
+    ErrorKind::NoAccountRole,
+    ErrorKind::AccountDoesNotExist,
+    ErrorKind::TransactionExpired,
+    ErrorKind::TransactionNotYetValid,
+    ErrorKind::BadChainId,
+    ErrorKind::VmHalted,
+    ErrorKind::InvalidGas,
+    ErrorKind::TransactionTooLarge,
+    ErrorKind::InvalidModulePublisher,
+    ErrorKind::UnknownCode,
+    ErrorKind::ValidationError,
+    ErrorKind::LinkerError,
+    ErrorKind::DuplicateModuleName,
+    ErrorKind::IncompatibleModuleUpdate,
+    ErrorKind::ImmutableModuleUpdate,
+    ErrorKind::ModuleTooLarge,
+    ErrorKind::NotEntryFunction,
+    ErrorKind::TypeArgumentsMismatch,
+    ErrorKind::VerificationError,
+    ErrorKind::DeserializationError,
+    ErrorKind::StorageError,
+    ErrorKind::InvariantViolation,
+    ErrorKind::Aborted,
+    ErrorKind::OutOfGas,
+    ErrorKind::ResourceDoesNotExist,
+    ErrorKind::ResourceAlreadyExists,
+    ErrorKind::MissingData,
+    ErrorKind::DataFormatError,
+    ErrorKind::ArithmeticError,
+    ErrorKind::StackOverflow,
+    ErrorKind::ExecutionInterrupted,
+    ErrorKind::EventLimitExceeded,
+    ErrorKind::HostAccessDenied,
+    ErrorKind::InstructionLimitExceeded,
+    ErrorKind::ExecutionError,
+];
+
+impl ErrorKind {
+    /// Returns the error of the status or `None` for `EXECUTED`.
+    pub fn from_status(status: StatusCode) -> Option<ErrorKind> {
+        use StatusCode::*;
+
+        Some(match status {
+            EXECUTED => return None,
+
+            INVALID_SIGNATURE | INVALID_AUTH_KEY => ErrorKind::InvalidSignature,
+            NO_ACCOUNT_ROLE => ErrorKind::NoAccountRole,
+            SENDING_ACCOUNT_DOES_NOT_EXIST => ErrorKind::AccountDoesNotExist,
+            TRANSACTION_EXPIRED => ErrorKind::TransactionExpired,
+            TRANSACTION_NOT_YET_VALID => ErrorKind::TransactionNotYetValid,
+            BAD_CHAIN_ID => ErrorKind::BadChainId,
+            VM_HALTED => ErrorKind::VmHalted,
+            MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND
+            | MAX_GAS_UNITS_BELOW_MIN_TRANSACTION_GAS_UNITS
+            | GAS_UNIT_PRICE_BELOW_MIN_BOUND
+            | GAS_UNIT_PRICE_ABOVE_MAX_BOUND
+            | INVALID_GAS_SPECIFIER => ErrorKind::InvalidGas,
+            EXCEEDED_MAX_TRANSACTION_SIZE
+            | EXCEEDED_MAX_ARGUMENTS_SIZE
+            | TOO_MANY_TYPE_ARGUMENTS => ErrorKind::TransactionTooLarge,
+            INVALID_MODULE_PUBLISHER | MODULE_ADDRESS_DOES_NOT_MATCH_SENDER => {
+                ErrorKind::InvalidModulePublisher
+            }
+            UNKNOWN_SCRIPT | UNKNOWN_MODULE => ErrorKind::UnknownCode,
+
+            LINKER_ERROR | MISSING_DEPENDENCY | LOOKUP_FAILED | FUNCTION_RESOLUTION_FAILURE => {
+                ErrorKind::LinkerError
+            }
+            DUPLICATE_MODULE_NAME => ErrorKind::DuplicateModuleName,
+            BACKWARD_INCOMPATIBLE_MODULE_UPDATE => ErrorKind::IncompatibleModuleUpdate,
+            IMMUTABLE_MODULE_UPDATE => ErrorKind::ImmutableModuleUpdate,
+            MODULE_SIZE_LIMIT_EXCEEDED
+            | TOO_MANY_FUNCTIONS
+            | TOO_MANY_STRUCTS
+            | IDENTIFIER_TOO_LONG => ErrorKind::ModuleTooLarge,
+            EXECUTE_ENTRY_FUNCTION_CALLED_ON_NON_ENTRY_FUNCTION => ErrorKind::NotEntryFunction,
+            NUMBER_OF_TYPE_ARGUMENTS_MISMATCH | CONSTRAINT_KIND_MISMATCH => {
+                ErrorKind::TypeArgumentsMismatch
+            }
+
+            STORAGE_ERROR => ErrorKind::StorageError,
+
+            ABORTED => ErrorKind::Aborted,
+            OUT_OF_GAS => ErrorKind::OutOfGas,
+            RESOURCE_DOES_NOT_EXIST => ErrorKind::ResourceDoesNotExist,
+            RESOURCE_ALREADY_EXISTS => ErrorKind::ResourceAlreadyExists,
+            MISSING_DATA => ErrorKind::MissingData,
+            DATA_FORMAT_ERROR => ErrorKind::DataFormatError,
+            ARITHMETIC_ERROR => ErrorKind::ArithmeticError,
+            EXECUTION_STACK_OVERFLOW
+            | CALL_STACK_OVERFLOW
+            | VM_MAX_TYPE_DEPTH_REACHED
+            | VM_MAX_VALUE_DEPTH_REACHED => ErrorKind::StackOverflow,
+            EXECUTION_INTERRUPTED => ErrorKind::ExecutionInterrupted,
+            EVENT_LIMIT_EXCEEDED => ErrorKind::EventLimitExceeded,
+            HOST_ACCESS_DENIED => ErrorKind::HostAccessDenied,
+            INSTRUCTION_LIMIT_EXCEEDED => ErrorKind::InstructionLimitExceeded,
+
+            status => match status.status_type() {
+                StatusType::Validation => ErrorKind::ValidationError,
+                StatusType::Verification => ErrorKind::VerificationError,
+                StatusType::Deserialization => ErrorKind::DeserializationError,
+                StatusType::InvariantViolation => ErrorKind::InvariantViolation,
+                StatusType::Execution => ErrorKind::ExecutionError,
+                StatusType::Unknown => ErrorKind::Unknown,
+            },
+        })
+    }
+
+    /// Returns the error with the index.
+    pub fn from_index(index: u8) -> Option<ErrorKind> {
+        ERRORS.iter().copied().find(|error| error.index() == index)
+    }
+
+    /// Returns the stable index of the error, the `error` of `DispatchError::Module`.
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the name of the error.
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Unknown => "Unknown",
+            ErrorKind::InvalidSignature => "InvalidSignature",
+            ErrorKind::NoAccountRole => "NoAccountRole",
+            ErrorKind::AccountDoesNotExist => "AccountDoesNotExist",
+            ErrorKind::TransactionExpired => "TransactionExpired",
+            ErrorKind::TransactionNotYetValid => "TransactionNotYetValid",
+            ErrorKind::BadChainId => "BadChainId",
+            ErrorKind::VmHalted => "VmHalted",
+            ErrorKind::InvalidGas => "InvalidGas",
+            ErrorKind::TransactionTooLarge => "TransactionTooLarge",
+            ErrorKind::InvalidModulePublisher => "InvalidModulePublisher",
+            ErrorKind::UnknownCode => "UnknownCode",
+            ErrorKind::ValidationError => "ValidationError",
+            ErrorKind::LinkerError => "LinkerError",
+            ErrorKind::DuplicateModuleName => "DuplicateModuleName",
+            ErrorKind::IncompatibleModuleUpdate => "IncompatibleModuleUpdate",
+            ErrorKind::ImmutableModuleUpdate => "ImmutableModuleUpdate",
+            ErrorKind::ModuleTooLarge => "ModuleTooLarge",
+            ErrorKind::NotEntryFunction => "NotEntryFunction",
+            ErrorKind::TypeArgumentsMismatch => "TypeArgumentsMismatch",
+            ErrorKind::VerificationError => "VerificationError",
+            ErrorKind::DeserializationError => "DeserializationError",
+            ErrorKind::StorageError => "StorageError",
+            ErrorKind::InvariantViolation => "InvariantViolation",
+            ErrorKind::Aborted => "Aborted",
+            ErrorKind::OutOfGas => "OutOfGas",
+            ErrorKind::ResourceDoesNotExist => "ResourceDoesNotExist",
+            ErrorKind::ResourceAlreadyExists => "ResourceAlreadyExists",
+            ErrorKind::MissingData => "MissingData",
+            ErrorKind::DataFormatError => "DataFormatError",
+            ErrorKind::ArithmeticError => "ArithmeticError",
+            ErrorKind::StackOverflow => "StackOverflow",
+            ErrorKind::ExecutionInterrupted => "ExecutionInterrupted",
+            ErrorKind::EventLimitExceeded => "EventLimitExceeded",
+            ErrorKind::HostAccessDenied => "HostAccessDenied",
+            ErrorKind::InstructionLimitExceeded => "InstructionLimitExceeded",
+            ErrorKind::ExecutionError => "ExecutionError",
+        }
+    }
+
+    /// Returns all errors ordered by the index.
+    pub fn all() -> &'static [ErrorKind] {
+        ERRORS
+    }
+}
+
+impl TryFrom<u8> for ErrorKind {
+    type Error = u8;
+
+    fn try_from(index: u8) -> Result<Self, Self::Error> {
+        ErrorKind::from_index(index).ok_or(index)
+    }
+}
+
+impl From<ErrorKind> for u8 {
+    fn from(error: ErrorKind) -> u8 {
+        error.index()
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Error of a failed transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VmError {
+    /// Kind of the error.
+    pub kind: ErrorKind,
+    /// Sub status of the failed transaction, e.g. the abort code.
+    pub sub_status: Option<u64>,
+}
+
+impl VmError {
+    /// Returns the error of the status or `None` for `EXECUTED`.
+    pub fn from_status(status: StatusCode, sub_status: Option<u64>) -> Option<VmError> {
+        ErrorKind::from_status(status).map(|kind| VmError { kind, sub_status })
+    }
+
+    /// Returns the error of the transaction result or `None` if the transaction is executed.
+    pub fn from_result(result: &VmResult) -> Option<VmError> {
+        VmError::from_status(result.status_code, result.sub_status)
+    }
+
+    /// Returns the stable index of the error kind, the `error` of `DispatchError::Module`.
+    pub fn index(&self) -> u8 {
+        self.kind.index()
+    }
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.sub_status {
+            Some(sub_status) => write!(f, "{}({})", self.kind, sub_status),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Epochs and reconfiguration.
+//!
+//! A transaction which writes a registered config path starts a new epoch:
+//! the epoch number is incremented and stored under the config address,
+//! the cached configs are reloaded and the `NewEpoch` event is emitted.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use move_core_types::account_address::AccountAddress;
+
+use crate::access_path::AccessPath;
+
+/// Module of the new epoch event.
+pub const EPOCH_MODULE: &str = "Epoch";
+/// Name of the new epoch event. The event message is the BCS encoded epoch number.
+pub const NEW_EPOCH_EVENT: &str = "NewEpoch";
+
+/// Tracks the current epoch and the config paths which trigger reconfiguration.
+#[derive(Debug, Default)]
+pub struct EpochManager {
+    epoch: AtomicU64,
+    config_keys: Vec<Vec<u8>>,
+}
+
+impl EpochManager {
+    /// Creates a manager starting at the given epoch.
+    pub fn new(epoch: u64) -> EpochManager {
+        EpochManager {
+            epoch: AtomicU64::new(epoch),
+            config_keys: vec![],
+        }
+    }
+
+    /// Registers the config path. Writes to the path start a new epoch.
+    pub fn register(&mut self, path: &AccessPath) {
+        let key = storage_key(path);
+        if !self.config_keys.contains(&key) {
+            self.config_keys.push(key);
+        }
+    }
+
+    /// Returns `true` if the storage key is a registered config path.
+    pub fn is_config_key(&self, key: &[u8]) -> bool {
+        self.config_keys
+            .iter()
+            .any(|config| config.as_slice() == key)
+    }
+
+    /// Returns the current epoch.
+    pub fn current(&self) -> u64 {
+        self.epoch.load(Ordering::SeqCst)
+    }
+
+    /// Starts the next epoch and returns its number.
+    pub fn next(&self) -> u64 {
+        self.epoch.fetch_add(1, Ordering::SeqCst) + 1
+    }
+}
+
+fn storage_key(path: &AccessPath) -> Vec<u8> {
+    let mut key = Vec::with_capacity(AccountAddress::LENGTH + path.path.len());
+    key.extend_from_slice(path.address.as_ref());
+    key.extend_from_slice(&path.path);
+    key
+}
@@ -0,0 +1,76 @@
+//! Aborts with a message.
+//!
+//! `0x1::Errors::abort_with_message(code: u64, message: vector<u8>)` aborts the transaction
+//! with `code` as `abort` does and attaches the message to the `VmResult`.
+//! The message is decoded as UTF-8 (invalid sequences are replaced) and truncated
+//! to `MAX_ABORT_MESSAGE_LEN` bytes.
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::extensions::NativeExtensions;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::pop_arg;
+use move_vm_types::values::Value;
+use vm::errors::PartialVMResult;
+
+/// Module of the abort natives.
+pub const ERRORS_MODULE: &str = "Errors";
+/// Name of the abort with message native.
+pub const ABORT_WITH_MESSAGE: &str = "abort_with_message";
+/// Max length of the abort message in bytes.
+pub const MAX_ABORT_MESSAGE_LEN: usize = 256;
+/// Gas charged for an abort with message in internal gas units.
+pub const ABORT_WITH_MESSAGE_GAS: NativeGasParams = NativeGasParams {
+    base: 100,
+    per_byte: 1,
+};
+
+/// Message of the abort raised by the session, kept in its native extensions.
+#[derive(Debug, Default)]
+pub(crate) struct AbortMessage(Option<String>);
+
+impl AbortMessage {
+    /// Takes the message of the abort raised by the session.
+    pub fn take(extensions: &mut NativeExtensions) -> Option<String> {
+        extensions
+            .remove::<AbortMessage>()
+            .and_then(|message| message.0)
+    }
+}
+
+/// Registers `Errors::abort_with_message` at the core address.
+pub(crate) fn register_abort_with_message(
+    natives: NativeRegistry,
+    core_address: AccountAddress,
+) -> NativeRegistry {
+    natives.register(
+        core_address,
+        ERRORS_MODULE,
+        ABORT_WITH_MESSAGE,
+        ABORT_WITH_MESSAGE_GAS,
+        abort_with_message,
+    )
+}
+
+fn abort_with_message(
+    context: &mut dyn NativeContext,
+    _: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    let message = pop_arg!(args, Vec<u8>);
+    let code = pop_arg!(args, u64);
+    context.extensions().get_or_default::<AbortMessage>().0 = Some(decode_message(&message));
+    Ok(NativeResult::err(GasUnits::new(0), code))
+}
+
+/// Decodes the message truncating it to `MAX_ABORT_MESSAGE_LEN` bytes.
+fn decode_message(message: &[u8]) -> String {
+    let len = core::cmp::min(message.len(), MAX_ABORT_MESSAGE_LEN);
+    String::from_utf8_lossy(&message[..len]).into_owned()
+}
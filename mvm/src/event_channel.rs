@@ -0,0 +1,214 @@
+//! Events passed to an asynchronous consumer.
+//!
+//! `ChannelEventHandler` sends the events into a bounded channel read by `EventReceiver`,
+//! so the memory held by the pending events is limited by the capacity of the channel.
+//! `OverflowPolicy` decides what happens when the consumer falls behind.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{
+    sync_channel, Receiver, RecvTimeoutError, SyncSender, TryRecvError, TrySendError,
+};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::{ModuleId, TypeTag};
+
+use crate::data::EventHandler;
+use crate::events::Topic;
+
+/// Event emitted by a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub address: AccountAddress,
+    pub ty_tag: TypeTag,
+    pub message: Vec<u8>,
+    pub caller: Option<ModuleId>,
+    /// Subscription topics, see `events`.
+    pub topics: Vec<Topic>,
+}
+
+/// Handling of an event sent into the full channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait up to the timeout until the consumer receives an event, then drop the event.
+    /// The timeout bounds the stall if the consumer runs on the executing thread.
+    Block(Duration),
+    /// Drop the event, see `ChannelEventHandler::dropped`.
+    Drop,
+    /// Discard the transaction with `EVENT_CHANNEL_FULL` before its effects are applied
+    /// if the channel can't hold all the events of the transaction.
+    /// Whether a transaction is discarded depends on the local consumer, so the policy is
+    /// only for the nodes whose results are not part of consensus, e.g. the RPC nodes.
+    /// Events emitted by the vm itself, e.g. `NewEpoch`, are dropped on overflow.
+    AbortTx,
+}
+
+/// Event handler sending the events into a bounded channel.
+pub struct ChannelEventHandler {
+    sender: SyncSender<Event>,
+    policy: OverflowPolicy,
+    capacity: usize,
+    /// Events sent and not received yet.
+    pending: Arc<AtomicUsize>,
+    /// Room reserved for the events of the transactions being applied, see `can_accept`.
+    reserved: AtomicUsize,
+    dropped: AtomicU64,
+}
+
+/// Receiving side of the `ChannelEventHandler`.
+pub struct EventReceiver {
+    receiver: Receiver<Event>,
+    pending: Arc<AtomicUsize>,
+}
+
+impl ChannelEventHandler {
+    /// Creates the handler with a channel of `capacity` events and its receiver.
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> (ChannelEventHandler, EventReceiver) {
+        let (sender, receiver) = sync_channel(capacity);
+        let pending = Arc::new(AtomicUsize::new(0));
+        let handler = ChannelEventHandler {
+            sender,
+            policy,
+            capacity,
+            pending: pending.clone(),
+            reserved: AtomicUsize::new(0),
+            dropped: AtomicU64::new(0),
+        };
+        (handler, EventReceiver { receiver, pending })
+    }
+
+    /// Returns the overflow policy.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Returns the number of events dropped on overflow or after the receiver was dropped.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn send(&self, event: Event) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        let sent = match self.policy {
+            OverflowPolicy::Block(timeout) => self.send_timeout(event, timeout),
+            OverflowPolicy::Drop | OverflowPolicy::AbortTx => self.sender.try_send(event).is_ok(),
+        };
+        if !sent {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            log::warn!("Event channel overflow, the event is dropped");
+        }
+    }
+
+    fn send_timeout(&self, mut event: Event, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.sender.try_send(event) {
+                Ok(()) => return true,
+                Err(TrySendError::Full(back)) if Instant::now() < deadline => {
+                    event = back;
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Err(_) => return false,
+            }
+        }
+    }
+}
+
+impl EventHandler for ChannelEventHandler {
+    fn on_event(
+        &self,
+        address: AccountAddress,
+        ty_tag: TypeTag,
+        message: Vec<u8>,
+        caller: Option<ModuleId>,
+    ) {
+        self.on_indexed_event(address, ty_tag, message, caller, vec![])
+    }
+
+    fn on_indexed_event(
+        &self,
+        address: AccountAddress,
+        ty_tag: TypeTag,
+        message: Vec<u8>,
+        caller: Option<ModuleId>,
+        topics: Vec<Topic>,
+    ) {
+        self.send(Event {
+            address,
+            ty_tag,
+            message,
+            caller,
+            topics,
+        })
+    }
+
+    fn can_accept(&self, events: usize) -> bool {
+        match self.policy {
+            OverflowPolicy::Block(_) | OverflowPolicy::Drop => true,
+            // The room is reserved atomically, so concurrent transactions can't overfill
+            // the channel. The reserved room is counted until the events are released.
+            OverflowPolicy::AbortTx => self
+                .reserved
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |reserved| {
+                    let total = self
+                        .pending
+                        .load(Ordering::SeqCst)
+                        .saturating_add(reserved)
+                        .saturating_add(events);
+                    if total <= self.capacity {
+                        Some(reserved + events)
+                    } else {
+                        None
+                    }
+                })
+                .is_ok(),
+        }
+    }
+
+    fn release(&self, events: usize) {
+        if self.policy == OverflowPolicy::AbortTx {
+            let _ = self
+                .reserved
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |reserved| {
+                    Some(reserved.saturating_sub(events))
+                });
+        }
+    }
+}
+
+impl EventReceiver {
+    /// Waits for the next event.
+    /// Returns `None` if the handler is dropped and all the events are received.
+    pub fn recv(&self) -> Option<Event> {
+        self.receiver.recv().ok().map(|event| self.received(event))
+    }
+
+    /// Returns the next event without waiting or `None` if the channel is empty.
+    pub fn try_recv(&self) -> Option<Event> {
+        match self.receiver.try_recv() {
+            Ok(event) => Some(self.received(event)),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Waits for the next event up to the `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Event> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => Some(self.received(event)),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// Returns the number of events waiting in the channel.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    fn received(&self, event: Event) -> Event {
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+        event
+    }
+}
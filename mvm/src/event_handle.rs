@@ -0,0 +1,224 @@
+//! Event handles of the Diem standard library.
+//!
+//! Natives of `0x1::Event`:
+//! `new_event_handle<T: copyable>(account: &signer): EventHandle<T>` and
+//! `write_to_event_store<T: copyable>(guid: vector<u8>, count: u64, msg: T)`.
+//! The `Event` module must declare
+//! `resource struct EventHandle<T: copyable> { counter: u64, guid: vector<u8> }`.
+//!
+//! The guid of a handle is its event key: the creation number of the handle followed by the
+//! account address, see `event_key`. Creation numbers are counted per account by
+//! `Event::EventHandleGenerator { counter: u64, addr: address }` stored under the account.
+//! The generator is maintained by the vm and updated with the transaction effects, so the keys
+//! are stable across nodes and the keys of a failed transaction are handed out again.
+//! Events written to the store are emitted at the creator address of the key like `Event::emit`.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::StructTag;
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::pop_arg;
+use move_vm_types::values::{SignerRef, Struct, Value};
+use serde::{Deserialize, Serialize};
+use vm::errors::{Location, PartialVMError, PartialVMResult, VMError};
+
+use crate::account::signer_address;
+use crate::data::{AccessKey, Storage};
+
+/// Module of the event handle natives.
+pub const EVENT_MODULE: &str = "Event";
+/// Name of the handle creation native.
+pub const NEW_EVENT_HANDLE: &str = "new_event_handle";
+/// Name of the native emitting an event of a handle.
+pub const WRITE_TO_EVENT_STORE: &str = "write_to_event_store";
+/// Resource counting the handles created by an account.
+pub const EVENT_HANDLE_GENERATOR: &str = "EventHandleGenerator";
+/// Abort code of an event written with a malformed key.
+pub const INVALID_EVENT_KEY: u64 = 1;
+/// Length of an event key.
+pub const EVENT_KEY_LENGTH: usize = 8 + AccountAddress::LENGTH;
+/// Gas charged for a created handle in internal gas units.
+pub const NEW_EVENT_HANDLE_GAS: NativeGasParams = NativeGasParams {
+    base: 500,
+    per_byte: 0,
+};
+/// Gas charged for an event written to the store in internal gas units.
+pub const WRITE_TO_EVENT_STORE_GAS: NativeGasParams = NativeGasParams {
+    base: 100,
+    per_byte: 1,
+};
+
+/// Counter of the event handles created by an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventHandleGenerator {
+    /// Creation number of the next handle.
+    pub counter: u64,
+    /// Address of the account.
+    pub addr: AccountAddress,
+}
+
+/// Creation numbers of the next handles of the accounts touched by the session,
+/// kept in its native extensions.
+#[derive(Debug, Default)]
+pub(crate) struct EventCounters(pub BTreeMap<AccountAddress, u64>);
+
+/// Returns the tag of `Event::EventHandleGenerator`.
+pub fn generator_tag(core_address: AccountAddress) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(EVENT_MODULE).unwrap(),
+        name: Identifier::new(EVENT_HANDLE_GENERATOR).unwrap(),
+        type_params: vec![],
+    }
+}
+
+/// Returns the key of the `counter`-th event handle created by the account.
+pub fn event_key(address: &AccountAddress, counter: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(EVENT_KEY_LENGTH);
+    key.extend_from_slice(&counter.to_le_bytes());
+    key.extend_from_slice(address.as_ref());
+    key
+}
+
+/// Returns the creator address and the creation number of the event key
+/// or `None` if the key is malformed.
+pub fn parse_event_key(key: &[u8]) -> Option<(AccountAddress, u64)> {
+    if key.len() != EVENT_KEY_LENGTH {
+        return None;
+    }
+    let (counter, address) = key.split_at(8);
+    let counter = u64::from_le_bytes(counter.try_into().ok()?);
+    let address = AccountAddress::new(address.try_into().ok()?);
+    Some((address, counter))
+}
+
+/// Returns the number of the event handles created by the account.
+pub fn load_event_counter<S: Storage>(
+    storage: &S,
+    core_address: AccountAddress,
+    address: &AccountAddress,
+) -> Result<u64, VMError> {
+    let key = AccessKey::from((address, &generator_tag(core_address)));
+    match storage.get(key.as_ref()) {
+        Some(blob) => decode_counter(&blob).map_err(|err| err.finish(Location::Undefined)),
+        None => Ok(0),
+    }
+}
+
+/// Returns the generator writes of the counters.
+pub(crate) fn counter_writes(
+    core_address: AccountAddress,
+    counters: BTreeMap<AccountAddress, u64>,
+) -> Vec<(AccessKey, Vec<u8>)> {
+    counters
+        .into_iter()
+        .map(|(addr, counter)| {
+            let key = AccessKey::from((&addr, &generator_tag(core_address)));
+            let generator = EventHandleGenerator { counter, addr };
+            let blob = bcs::to_bytes(&generator).expect("Generator serialization must not fail");
+            (key, blob)
+        })
+        .collect()
+}
+
+/// Registers the event handle natives at the core address counting the handles of the session
+/// in its `EventCounters`.
+pub(crate) fn register_event_handles(
+    natives: NativeRegistry,
+    core_address: AccountAddress,
+) -> NativeRegistry {
+    natives
+        .register(
+            core_address,
+            EVENT_MODULE,
+            NEW_EVENT_HANDLE,
+            NEW_EVENT_HANDLE_GAS,
+            move |ctx: &mut dyn NativeContext, _: Vec<Type>, mut args: VecDeque<Value>| {
+                let address = signer_address(pop_arg!(args, SignerRef))?;
+                new_event_handle(ctx, core_address, address)
+            },
+        )
+        .register(
+            core_address,
+            EVENT_MODULE,
+            WRITE_TO_EVENT_STORE,
+            WRITE_TO_EVENT_STORE_GAS,
+            move |ctx: &mut dyn NativeContext,
+                  mut ty_args: Vec<Type>,
+                  mut args: VecDeque<Value>| {
+                let cost = GasUnits::new(0);
+                let msg = args.pop_back().ok_or_else(missing_argument)?;
+                let _count = pop_arg!(args, u64);
+                let guid = pop_arg!(args, Vec<u8>);
+                let ty = ty_args.pop().ok_or_else(missing_argument)?;
+                let address = match parse_event_key(&guid) {
+                    Some((address, _)) => address,
+                    None => return Ok(NativeResult::err(cost, INVALID_EVENT_KEY)),
+                };
+                let caller = ctx.caller().cloned();
+                if ctx.save_event(address, ty, msg, caller)? {
+                    Ok(NativeResult::ok(cost, vec![]))
+                } else {
+                    Ok(NativeResult::err(cost, 0))
+                }
+            },
+        )
+}
+
+fn new_event_handle(
+    ctx: &mut dyn NativeContext,
+    core_address: AccountAddress,
+    address: AccountAddress,
+) -> PartialVMResult<NativeResult> {
+    let known = ctx
+        .extensions()
+        .get_or_default::<EventCounters>()
+        .0
+        .get(&address)
+        .copied();
+    let counter = match known {
+        Some(counter) => counter,
+        None => ctx
+            .read_remote_resource(&address, &generator_tag(core_address))?
+            .map(|blob| decode_counter(&blob))
+            .transpose()?
+            .unwrap_or_default(),
+    };
+    let next = counter
+        .checked_add(1)
+        .ok_or_else(|| PartialVMError::new(StatusCode::ARITHMETIC_ERROR))?;
+    ctx.extensions()
+        .get_or_default::<EventCounters>()
+        .0
+        .insert(address, next);
+
+    let handle = Struct::pack(
+        vec![
+            Value::u64(0),
+            Value::vector_u8(event_key(&address, counter)),
+        ],
+        true,
+    );
+    Ok(NativeResult::ok(
+        GasUnits::new(0),
+        vec![Value::struct_(handle)],
+    ))
+}
+
+fn decode_counter(blob: &[u8]) -> PartialVMResult<u64> {
+    bcs::from_bytes::<EventHandleGenerator>(blob)
+        .map(|generator| generator.counter)
+        .map_err(|_| PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE))
+}
+
+fn missing_argument() -> PartialVMError {
+    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+}
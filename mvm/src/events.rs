@@ -0,0 +1,106 @@
+//! Event topics.
+//!
+//! Events are passed to the host with topics for subscriptions, in order:
+//! the hash of the event type, the hash of the event type and the event address
+//! and the hash of each indexed field value.
+//! Fields of the event struct are declared indexed with the `indexed_` name prefix.
+
+use alloc::vec::Vec;
+
+use diem_crypto::hash::HashValue;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::TypeTag;
+use move_core_types::value::{MoveTypeLayout, MoveValue};
+use vm::access::ModuleAccess;
+use vm::file_format::StructFieldInformation;
+use vm::CompiledModule;
+
+use crate::hash::Digest;
+
+/// Name prefix of the indexed fields.
+pub const INDEXED_FIELD_PREFIX: &str = "indexed_";
+
+/// Event topic.
+pub type Topic = Digest;
+
+/// Returns the topic of the event type.
+pub fn type_topic(ty_tag: &TypeTag) -> Topic {
+    sha3_256(&bcs::to_bytes(ty_tag).expect("Type tag serialization must not fail"))
+}
+
+/// Returns the topic of the event type emitted at the address.
+pub fn address_topic(ty_tag: &TypeTag, address: &AccountAddress) -> Topic {
+    let mut blob = bcs::to_bytes(ty_tag).expect("Type tag serialization must not fail");
+    blob.extend_from_slice(address.as_ref());
+    sha3_256(&blob)
+}
+
+/// Returns the topic of the serialized value of an indexed field.
+pub fn field_topic(value: &[u8]) -> Topic {
+    sha3_256(value)
+}
+
+/// Returns the topics of the event with the serialized values of its indexed fields.
+pub fn event_topics(
+    address: &AccountAddress,
+    ty_tag: &TypeTag,
+    indexed_values: &[Vec<u8>],
+) -> Vec<Topic> {
+    let mut topics = Vec::with_capacity(2 + indexed_values.len());
+    topics.push(type_topic(ty_tag));
+    topics.push(address_topic(ty_tag, address));
+    topics.extend(indexed_values.iter().map(|value| field_topic(value)));
+    topics
+}
+
+/// Returns the positions of the indexed fields of the struct `name` declared in the module.
+pub fn indexed_fields(module: &CompiledModule, name: &str) -> Vec<usize> {
+    module
+        .struct_defs()
+        .iter()
+        .find(|def| {
+            module
+                .identifier_at(module.struct_handle_at(def.struct_handle).name)
+                .as_str()
+                == name
+        })
+        .and_then(|def| match &def.field_information {
+            StructFieldInformation::Native => None,
+            StructFieldInformation::Declared(fields) => Some(
+                fields
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, field)| {
+                        module
+                            .identifier_at(field.name)
+                            .as_str()
+                            .starts_with(INDEXED_FIELD_PREFIX)
+                    })
+                    .map(|(idx, _)| idx)
+                    .collect(),
+            ),
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the serialized values of the `fields` of the event message.
+pub fn indexed_values(message: &[u8], layout: &MoveTypeLayout, fields: &[usize]) -> Vec<Vec<u8>> {
+    if fields.is_empty() {
+        return vec![];
+    }
+    let value = match MoveValue::simple_deserialize(message, layout) {
+        Ok(MoveValue::Struct(value)) => value,
+        _ => return vec![],
+    };
+    fields
+        .iter()
+        .filter_map(|idx| value.fields().get(*idx))
+        .filter_map(MoveValue::simple_serialize)
+        .collect()
+}
+
+fn sha3_256(blob: &[u8]) -> Topic {
+    let hash = HashValue::sha3_256_of(blob);
+    let digest: &Digest = hash.as_ref();
+    *digest
+}
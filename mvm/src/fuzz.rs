@@ -0,0 +1,93 @@
+//! Fuzzing entry points, enabled with the `fuzzing` feature.
+//!
+//! The harness functions take arbitrary bytes and run them through deserialization,
+//! verification and execution against a fixed in-memory state. They never panic on invalid
+//! input: a panic or a failed round-trip assertion is a bug found by the fuzzer.
+//!
+//! A `cargo fuzz` target calls the function with the fuzzer input:
+//! `fuzz_target!(|data: &[u8]| mvm::fuzz::fuzz_execute_script(data));`
+
+use core::convert::TryFrom;
+use std::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use move_core_types::vm_status::VMStatus;
+use parity_scale_codec::{Decode, Encode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::data::ExecutionContext;
+use crate::testkit::{addr, gas, MockVm, VmBuilder};
+use crate::types::{ModuleTx, PriceRead, ScriptArg, ScriptTx, Transaction};
+use crate::Vm;
+
+/// Sender of the fuzzed transactions.
+const SENDER: &str = "0x110";
+
+/// Creates the vm with the fixed state the inputs are executed against.
+fn fuzz_vm() -> MockVm {
+    let (vm, _, _, _, _) = VmBuilder::new()
+        .with_balance(addr(SENDER), "PONT", 1_000_000)
+        .with_balance(addr(SENDER), "BTC", 1_000)
+        .with_price("BTC_PONT", 1_000)
+        .build();
+    vm
+}
+
+/// Publishes the bytes as a module.
+/// SCALE encoded module transactions are decoded and published as well.
+pub fn fuzz_publish_module(bytes: &[u8]) {
+    let vm = fuzz_vm();
+    vm.publish_module(gas(), ModuleTx::new(bytes.to_vec(), addr(SENDER)), false);
+
+    if let Ok(module) = ModuleTx::decode(&mut &bytes[..]) {
+        vm.publish_module(gas(), module, false);
+    }
+}
+
+/// Executes the bytes as a script.
+/// BCS encoded transactions are executed with their arguments, other bytes as the script code.
+pub fn fuzz_execute_script(bytes: &[u8]) {
+    let vm = fuzz_vm();
+    let tx = match Transaction::try_from(bytes) {
+        Ok(tx) => {
+            let signers = (0..tx.signers_count())
+                .map(|idx| AccountAddress::new([idx; AccountAddress::LENGTH]))
+                .collect();
+            match tx.into_script(signers) {
+                Ok(tx) => tx,
+                Err(_) => return,
+            }
+        }
+        Err(_) => ScriptTx::new(bytes.to_vec(), vec![], vec![], vec![addr(SENDER)]),
+    };
+    vm.execute_script(gas(), ExecutionContext::new(100, 100), tx, false);
+}
+
+/// Decodes the bytes as the vm types and checks that the encoding of the decoded values
+/// gives back the same bytes.
+pub fn fuzz_bcs_roundtrip(bytes: &[u8]) {
+    bcs_roundtrip::<AccountAddress>(bytes);
+    bcs_roundtrip::<TypeTag>(bytes);
+    bcs_roundtrip::<StructTag>(bytes);
+    bcs_roundtrip::<ModuleId>(bytes);
+    bcs_roundtrip::<VMStatus>(bytes);
+    bcs_roundtrip::<ScriptArg>(bytes);
+    bcs_roundtrip::<Vec<ScriptArg>>(bytes);
+    bcs_roundtrip::<PriceRead>(bytes);
+    bcs_roundtrip::<Transaction>(bytes);
+
+    if let Ok(module) = ModuleTx::decode(&mut &bytes[..]) {
+        let encoded = module.encode();
+        let decoded = ModuleTx::decode(&mut encoded.as_slice()).expect("Failed to decode ModuleTx");
+        assert_eq!(decoded.encode(), encoded);
+    }
+}
+
+fn bcs_roundtrip<T: Serialize + DeserializeOwned>(bytes: &[u8]) {
+    if let Ok(value) = bcs::from_bytes::<T>(bytes) {
+        let encoded = bcs::to_bytes(&value).expect("Failed to serialize the decoded value");
+        assert_eq!(encoded, bytes, "BCS encoding is not canonical");
+    }
+}
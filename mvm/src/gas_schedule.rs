@@ -1,7 +1,11 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
-use move_core_types::gas_schedule::{CostTable, GasCost};
+use anyhow::{ensure, Error};
+use move_core_types::gas_schedule::{CostTable, GasAlgebra, GasConstants, GasCost, GasUnits};
 use move_vm_types::gas_schedule::new_from_instructions;
 use move_vm_types::gas_schedule::NativeCostIndex as N;
+use serde::{Deserialize, Serialize};
 use vm::file_format::Bytecode::*;
 use vm::file_format::*;
 use vm::file_format_common::instruction_key;
@@ -169,3 +173,301 @@ pub fn cost_table() -> CostTable {
 
     new_from_instructions(instrs, raw_native_table)
 }
+
+/// Names of the instructions in the order of the instruction table.
+pub const INSTRUCTION_NAMES: [&str; Bytecode::NUM_INSTRUCTIONS] = [
+    "Pop",
+    "Ret",
+    "BrTrue",
+    "BrFalse",
+    "Branch",
+    "LdU64",
+    "LdConst",
+    "LdTrue",
+    "LdFalse",
+    "CopyLoc",
+    "MoveLoc",
+    "StLoc",
+    "MutBorrowLoc",
+    "ImmBorrowLoc",
+    "MutBorrowField",
+    "ImmBorrowField",
+    "Call",
+    "Pack",
+    "Unpack",
+    "ReadRef",
+    "WriteRef",
+    "Add",
+    "Sub",
+    "Mul",
+    "Mod",
+    "Div",
+    "BitOr",
+    "BitAnd",
+    "Xor",
+    "Or",
+    "And",
+    "Not",
+    "Eq",
+    "Neq",
+    "Lt",
+    "Gt",
+    "Le",
+    "Ge",
+    "Abort",
+    "Nop",
+    "Exists",
+    "MutBorrowGlobal",
+    "ImmBorrowGlobal",
+    "MoveFrom",
+    "MoveTo",
+    "FreezeRef",
+    "Shl",
+    "Shr",
+    "LdU8",
+    "LdU128",
+    "CastU8",
+    "CastU64",
+    "CastU128",
+    "MutBorrowFieldGeneric",
+    "ImmBorrowFieldGeneric",
+    "CallGeneric",
+    "PackGeneric",
+    "UnpackGeneric",
+    "ExistsGeneric",
+    "MutBorrowGlobalGeneric",
+    "ImmBorrowGlobalGeneric",
+    "MoveFromGeneric",
+    "MoveToGeneric",
+];
+
+/// Names of the natives in the order of the native table (`NativeCostIndex`).
+pub const NATIVE_NAMES: [&str; 31] = [
+    "sha2_256",
+    "sha3_256",
+    "ed25519_verify",
+    "ed25519_threshold_verify",
+    "bcs_to_bytes",
+    "length",
+    "empty",
+    "borrow",
+    "borrow_mut",
+    "push_back",
+    "pop_back",
+    "destroy_empty",
+    "swap",
+    "ed25519_validate_key",
+    "signer_borrow",
+    "create_signer",
+    "destroy_signer",
+    "emit_event",
+    "u256_from_u8",
+    "u256_from_u64",
+    "u256_from_u128",
+    "u256_as_u8",
+    "u256_as_u64",
+    "u256_as_u128",
+    "u256_mul",
+    "u256_div",
+    "u256_sub",
+    "u256_add",
+    "deposit",
+    "withdraw",
+    "get_balance",
+];
+
+/// Cost of an instruction or a native in gas units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedCost {
+    pub instruction: u64,
+    pub memory: u64,
+}
+
+impl From<&GasCost> for NamedCost {
+    fn from(cost: &GasCost) -> Self {
+        NamedCost {
+            instruction: cost.instruction_gas.get(),
+            memory: cost.memory_gas.get(),
+        }
+    }
+}
+
+impl From<NamedCost> for GasCost {
+    fn from(cost: NamedCost) -> Self {
+        GasCost::new(cost.instruction, cost.memory)
+    }
+}
+
+/// Human readable gas schedule with the costs keyed by the instruction and native names.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasSchedule {
+    pub instructions: BTreeMap<String, NamedCost>,
+    pub natives: BTreeMap<String, NamedCost>,
+    pub constants: GasConstants,
+}
+
+impl GasSchedule {
+    /// Converts the schedule to the cost table. Fails if an instruction or a native is missing or unknown.
+    pub fn into_cost_table(self) -> Result<CostTable, Error> {
+        let instruction_table = table(&INSTRUCTION_NAMES, &self.instructions, "instruction")?;
+        let native_table = table(&NATIVE_NAMES, &self.natives, "native")?;
+        Ok(CostTable {
+            instruction_table,
+            native_table,
+            gas_constants: self.constants,
+        })
+    }
+
+    /// Checks that every instruction and native has a cost and all the names are known.
+    pub fn validate(&self) -> Result<(), Error> {
+        table(&INSTRUCTION_NAMES, &self.instructions, "instruction")?;
+        table(&NATIVE_NAMES, &self.natives, "native")?;
+        Ok(())
+    }
+
+    /// Returns the schedule as pretty printed JSON with the costs ordered by the names,
+    /// so the schedules of governance proposals can be diffed line by line.
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(Error::msg)
+    }
+
+    /// Parses the JSON schedule produced by `to_json`. Fails if the schedule is invalid.
+    #[cfg(feature = "std")]
+    pub fn from_json(json: &str) -> Result<GasSchedule, Error> {
+        let schedule: GasSchedule = serde_json::from_str(json).map_err(Error::msg)?;
+        schedule.validate()?;
+        Ok(schedule)
+    }
+}
+
+impl From<&CostTable> for GasSchedule {
+    fn from(table: &CostTable) -> Self {
+        GasSchedule {
+            instructions: named(&INSTRUCTION_NAMES, &table.instruction_table),
+            natives: named(&NATIVE_NAMES, &table.native_table),
+            constants: table.gas_constants.clone(),
+        }
+    }
+}
+
+fn named(names: &[&str], costs: &[GasCost]) -> BTreeMap<String, NamedCost> {
+    names
+        .iter()
+        .zip(costs)
+        .map(|(name, cost)| (name.to_string(), NamedCost::from(cost)))
+        .collect()
+}
+
+fn table(
+    names: &[&str],
+    costs: &BTreeMap<String, NamedCost>,
+    kind: &str,
+) -> Result<Vec<GasCost>, Error> {
+    if let Some(unknown) = costs.keys().find(|name| !names.contains(&name.as_str())) {
+        return Err(Error::msg(format!("Unknown {} {}", kind, unknown)));
+    }
+    names
+        .iter()
+        .map(|name| {
+            costs
+                .get(*name)
+                .map(|cost| GasCost::from(*cost))
+                .ok_or_else(|| Error::msg(format!("Missing cost of {} {}", kind, name)))
+        })
+        .collect()
+}
+
+/// Builder of the cost table with the named parameters.
+#[derive(Debug, Clone)]
+pub struct GasScheduleBuilder {
+    schedule: GasSchedule,
+}
+
+impl GasScheduleBuilder {
+    /// Creates a builder with the default dvm cost table.
+    pub fn new() -> GasScheduleBuilder {
+        GasScheduleBuilder::from_cost_table(&cost_table())
+    }
+
+    /// Creates a builder with the costs of the table.
+    pub fn from_cost_table(table: &CostTable) -> GasScheduleBuilder {
+        GasScheduleBuilder {
+            schedule: GasSchedule::from(table),
+        }
+    }
+
+    /// Sets the cost of the instruction, e.g. `MoveTo`.
+    pub fn instruction(mut self, name: &str, cost: GasCost) -> Result<GasScheduleBuilder, Error> {
+        ensure!(
+            INSTRUCTION_NAMES.contains(&name),
+            "Unknown instruction {}",
+            name
+        );
+        self.schedule
+            .instructions
+            .insert(name.to_string(), NamedCost::from(&cost));
+        Ok(self)
+    }
+
+    /// Sets the cost of the native, e.g. `sha3_256`.
+    pub fn native(mut self, name: &str, cost: GasCost) -> Result<GasScheduleBuilder, Error> {
+        ensure!(NATIVE_NAMES.contains(&name), "Unknown native {}", name);
+        self.schedule
+            .natives
+            .insert(name.to_string(), NamedCost::from(&cost));
+        Ok(self)
+    }
+
+    /// Sets the gas per byte of transactions over the large transaction cutoff.
+    pub fn intrinsic_gas_per_byte(mut self, gas: u64) -> GasScheduleBuilder {
+        self.schedule.constants.intrinsic_gas_per_byte = GasUnits::new(gas);
+        self
+    }
+
+    /// Sets the minimum gas of a transaction.
+    pub fn min_transaction_gas_units(mut self, gas: u64) -> GasScheduleBuilder {
+        self.schedule.constants.min_transaction_gas_units = GasUnits::new(gas);
+        self
+    }
+
+    /// Sets the gas per byte written to the storage.
+    pub fn write_byte_cost(mut self, gas: u64) -> GasScheduleBuilder {
+        self.schedule.constants.global_memory_per_byte_write_cost = GasUnits::new(gas);
+        self
+    }
+
+    /// Sets the gas per byte read from the storage.
+    pub fn read_byte_cost(mut self, gas: u64) -> GasScheduleBuilder {
+        self.schedule.constants.global_memory_per_byte_cost = GasUnits::new(gas);
+        self
+    }
+
+    /// Sets the gas constants.
+    pub fn constants(mut self, constants: GasConstants) -> GasScheduleBuilder {
+        self.schedule.constants = constants;
+        self
+    }
+
+    /// Returns the named schedule.
+    pub fn schedule(&self) -> &GasSchedule {
+        &self.schedule
+    }
+
+    /// Builds the cost table. Fails if the cost of an instruction or a native is missing.
+    pub fn build(self) -> Result<CostTable, Error> {
+        self.schedule.into_cost_table()
+    }
+}
+
+impl Default for GasScheduleBuilder {
+    fn default() -> Self {
+        GasScheduleBuilder::new()
+    }
+}
+
+impl From<GasSchedule> for GasScheduleBuilder {
+    fn from(schedule: GasSchedule) -> Self {
+        GasScheduleBuilder { schedule }
+    }
+}
@@ -0,0 +1,35 @@
+//! Governance scripts.
+//!
+//! `Mvm::execute_governance_script` runs a script with the governance account, the config
+//! address, as the implicit first signer. On-chain parameters are changed by the Move code
+//! of the standard library instead of raw storage writes.
+//! The host approves governance transactions with the `GovernanceOrigin` check.
+//! The senders of the transaction are passed after the governance account and must be
+//! authenticated by the `Authenticator`; without it only the scripts without senders run.
+//!
+//! `Mvm::force_publish_module` lets the governance replace a module bypassing the compatibility
+//! and immutability checks. Every forced publish is recorded with a `ForcedUpgradeEvent`.
+
+use crate::types::{ModuleTx, ScriptTx};
+
+/// Approves governance scripts, e.g. by the origin of the runtime call.
+pub trait GovernanceOrigin {
+    /// Returns `Err` with the host-defined reason code if the script is not approved.
+    /// The reason code is reported as the sub status of `NO_ACCOUNT_ROLE`.
+    fn ensure_governance(&self, tx: &ScriptTx) -> Result<(), u64>;
+
+    /// Returns `Err` with the host-defined reason code if the forced module publish
+    /// is not approved. Forced publishes are denied unless the host overrides the check.
+    fn ensure_force_publish(&self, _module: &ModuleTx) -> Result<(), u64> {
+        Err(0)
+    }
+}
+
+impl<F> GovernanceOrigin for F
+where
+    F: Fn(&ScriptTx) -> Result<(), u64>,
+{
+    fn ensure_governance(&self, tx: &ScriptTx) -> Result<(), u64> {
+        self(tx)
+    }
+}
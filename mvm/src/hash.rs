@@ -0,0 +1,132 @@
+//! Canonical digests of the transaction bytecode.
+//!
+//! The digest is the SHA3-256 of the uncompressed bytecode as it is sent in the transaction.
+//! Tooling must use these functions to get the same digests as the vm.
+
+use alloc::vec::Vec;
+
+use diem_crypto::hash::HashValue;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, TypeTag};
+use serde::Serialize;
+
+use crate::types::{BatchTx, ModuleTx, ScriptArg, ScriptTx};
+
+/// SHA3-256 digest.
+pub type Digest = [u8; HashValue::LENGTH];
+
+/// Returns the digest of the script bytecode.
+pub fn script_hash(code: &[u8]) -> Digest {
+    sha3_256(code)
+}
+
+/// Returns the digest of the module bytecode.
+pub fn module_hash(code: &[u8]) -> Digest {
+    sha3_256(code)
+}
+
+/// Returns the digest of the BCS encoded script arguments.
+pub(crate) fn args_hash(args: &[ScriptArg]) -> Digest {
+    sha3_256(&bcs::to_bytes(args).expect("script arguments must be serializable"))
+}
+
+fn sha3_256(code: &[u8]) -> Digest {
+    let hash = HashValue::sha3_256_of(code);
+    let digest: &Digest = hash.as_ref();
+    *digest
+}
+
+impl ScriptTx {
+    /// Returns the digest of the script bytecode.
+    pub fn hash(&self) -> Digest {
+        script_hash(self.code())
+    }
+
+    /// Returns the message signed by the proof of the transaction, see `auth::Authenticator`:
+    /// the digest of the BCS encoded bytecode digest, arguments digest, type arguments, senders,
+    /// fee payer, validity window, sequence number and chain id.
+    pub fn signing_message(&self) -> Digest {
+        let payload = SigningPayload {
+            code_hash: self.hash(),
+            args_hash: *self.args_hash(),
+            type_args: self.type_parameters(),
+            senders: self.senders(),
+            fee_payer: self.fee_payer(),
+            expiration_timestamp: self.expiration_timestamp(),
+            not_before: self.not_before(),
+            sequence: self.sequence_number(),
+            chain_id: self.chain_id(),
+        };
+        sha3_256(&bcs::to_bytes(&payload).expect("signing payload must be serializable"))
+    }
+}
+
+/// Transaction content bound by the signing message.
+#[derive(Serialize)]
+struct SigningPayload<'a> {
+    code_hash: Digest,
+    args_hash: Digest,
+    type_args: &'a [TypeTag],
+    senders: &'a [AccountAddress],
+    fee_payer: Option<&'a AccountAddress>,
+    expiration_timestamp: Option<u64>,
+    not_before: Option<u64>,
+    sequence: Option<(u64, u64)>,
+    chain_id: Option<u8>,
+}
+
+impl BatchTx {
+    /// Returns the message signed by the proof of the batch, see `auth::Authenticator`:
+    /// the digest of the BCS encoded calls with their arguments digests, senders, fee payer,
+    /// validity window, sequence number and chain id.
+    pub fn signing_message(&self) -> Digest {
+        let payload = BatchSigningPayload {
+            calls: self
+                .calls()
+                .iter()
+                .map(|call| CallPayload {
+                    module: call.module(),
+                    function: call.function(),
+                    args_hash: *call.args_hash(),
+                    type_args: call.type_parameters(),
+                })
+                .collect(),
+            senders: self.senders(),
+            fee_payer: self.fee_payer(),
+            expiration_timestamp: self.expiration_timestamp(),
+            not_before: self.not_before(),
+            sequence: self.sequence_number(),
+            chain_id: self.chain_id(),
+        };
+        sha3_256(&bcs::to_bytes(&payload).expect("signing payload must be serializable"))
+    }
+}
+
+/// Batch content bound by the signing message.
+#[derive(Serialize)]
+struct BatchSigningPayload<'a> {
+    calls: Vec<CallPayload<'a>>,
+    senders: &'a [AccountAddress],
+    fee_payer: Option<&'a AccountAddress>,
+    expiration_timestamp: Option<u64>,
+    not_before: Option<u64>,
+    sequence: Option<(u64, u64)>,
+    chain_id: Option<u8>,
+}
+
+/// Entry function call bound by the signing message of the batch.
+#[derive(Serialize)]
+struct CallPayload<'a> {
+    module: &'a ModuleId,
+    function: &'a Identifier,
+    args_hash: Digest,
+    type_args: &'a [TypeTag],
+}
+
+impl ModuleTx {
+    /// Returns the digest of the module bytecode.
+    pub fn hash(&self) -> Digest {
+        module_hash(self.code())
+    }
+}
@@ -0,0 +1,79 @@
+//! Host calls from Move.
+//!
+//! `0x1::Host::call(id: u64, payload: vector<u8>): vector<u8>` dispatches to the handler
+//! registered under `id` with `Mvm::with_host_handler`.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::pop_arg;
+use move_vm_types::values::Value;
+use vm::errors::PartialVMResult;
+
+/// Module of the host call native.
+pub const HOST_MODULE: &str = "Host";
+/// Name of the host call native.
+pub const HOST_CALL: &str = "call";
+/// Abort code of a call to an unregistered handler.
+pub const UNKNOWN_HOST_HANDLER: u64 = 1;
+/// Gas charged for a host call in internal gas units.
+pub const HOST_CALL_GAS: NativeGasParams = NativeGasParams {
+    base: 1000,
+    per_byte: 1,
+};
+
+/// Handler of the host calls.
+pub trait HostHandler {
+    /// Returns the response to the payload or `Err` with the abort code.
+    fn call(&self, payload: &[u8]) -> Result<Vec<u8>, u64>;
+}
+
+impl<F> HostHandler for F
+where
+    F: Fn(&[u8]) -> Result<Vec<u8>, u64>,
+{
+    fn call(&self, payload: &[u8]) -> Result<Vec<u8>, u64> {
+        self(payload)
+    }
+}
+
+/// Host handlers by id.
+pub(crate) type HostHandlers = BTreeMap<u64, Arc<dyn HostHandler + Send + Sync>>;
+
+/// Registers `Host::call` at the core address dispatching to the handlers.
+pub(crate) fn register_host_call(
+    natives: NativeRegistry,
+    core_address: AccountAddress,
+    handlers: HostHandlers,
+) -> NativeRegistry {
+    natives.register(
+        core_address,
+        HOST_MODULE,
+        HOST_CALL,
+        HOST_CALL_GAS,
+        move |_: &mut dyn NativeContext, _: Vec<Type>, args: VecDeque<Value>| {
+            host_call(&handlers, args)
+        },
+    )
+}
+
+fn host_call(handlers: &HostHandlers, mut args: VecDeque<Value>) -> PartialVMResult<NativeResult> {
+    let payload = pop_arg!(args, Vec<u8>);
+    let id = pop_arg!(args, u64);
+    let cost = GasUnits::new(0);
+
+    let handler = match handlers.get(&id) {
+        Some(handler) => handler,
+        None => return Ok(NativeResult::err(cost, UNKNOWN_HOST_HANDLER)),
+    };
+    Ok(match handler.call(&payload) {
+        Ok(response) => NativeResult::ok(cost, vec![Value::vector_u8(response)]),
+        Err(code) => NativeResult::err(cost, code),
+    })
+}
@@ -0,0 +1,155 @@
+//! Storage key codecs.
+//!
+//! The vm derives a storage key from every access path: modules are stored under
+//! `ModuleId::access_vector`, resources and other paths under the address followed by the path.
+//! A `KeyCodec` set with `State::with_key_codec` maps these keys to the storage layout of the host,
+//! e.g. `Blake2_128Concat` for Substrate storage maps and child tries, so the host proofs cover
+//! the vm keys. The key prefix and the prefix of the tenant space are prepended to the encoded
+//! keys.
+//! `Storage::next_key` follows the order of the encoded keys: a `CodecStorage` supports the key
+//! iteration only with a codec preserving the key order, such as `RawKeys`, so
+//! `Mvm::resources_page` fails with the hashing codecs.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::data::Storage;
+
+/// Maps the vm storage keys to the keys of the host storage.
+pub trait KeyCodec {
+    /// Returns the host key of the vm key.
+    fn encode(&self, key: &[u8]) -> Vec<u8>;
+    /// Returns the vm key of the host key or `None` if the key is not produced by `encode`.
+    fn decode(&self, key: &[u8]) -> Option<Vec<u8>>;
+    /// Returns `true` if the encoded keys keep the byte order of the vm keys.
+    fn preserves_order(&self) -> bool {
+        false
+    }
+}
+
+impl<C: KeyCodec + ?Sized> KeyCodec for Box<C> {
+    fn encode(&self, key: &[u8]) -> Vec<u8> {
+        (**self).encode(key)
+    }
+
+    fn decode(&self, key: &[u8]) -> Option<Vec<u8>> {
+        (**self).decode(key)
+    }
+
+    fn preserves_order(&self) -> bool {
+        (**self).preserves_order()
+    }
+}
+
+impl<C: KeyCodec + ?Sized> KeyCodec for &C {
+    fn encode(&self, key: &[u8]) -> Vec<u8> {
+        (**self).encode(key)
+    }
+
+    fn decode(&self, key: &[u8]) -> Option<Vec<u8>> {
+        (**self).decode(key)
+    }
+
+    fn preserves_order(&self) -> bool {
+        (**self).preserves_order()
+    }
+}
+
+/// Keeps the vm keys as they are. The default codec.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RawKeys;
+
+impl KeyCodec for RawKeys {
+    fn encode(&self, key: &[u8]) -> Vec<u8> {
+        key.to_vec()
+    }
+
+    fn decode(&self, key: &[u8]) -> Option<Vec<u8>> {
+        Some(key.to_vec())
+    }
+
+    fn preserves_order(&self) -> bool {
+        true
+    }
+}
+
+/// Prepends the 128-bit BLAKE2b hash to the key, the `Blake2_128Concat` hasher of Substrate.
+/// Keys are spread evenly in the trie and stay recoverable from the host key.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Blake2_128Concat;
+
+/// Length of the hash of `Blake2_128Concat`.
+pub const BLAKE2_128_LENGTH: usize = 16;
+
+impl KeyCodec for Blake2_128Concat {
+    fn encode(&self, key: &[u8]) -> Vec<u8> {
+        let hash = blake2_rfc::blake2b::blake2b(BLAKE2_128_LENGTH, &[], key);
+        let mut encoded = Vec::with_capacity(BLAKE2_128_LENGTH + key.len());
+        encoded.extend_from_slice(hash.as_bytes());
+        encoded.extend_from_slice(key);
+        encoded
+    }
+
+    fn decode(&self, key: &[u8]) -> Option<Vec<u8>> {
+        if key.len() < BLAKE2_128_LENGTH {
+            return None;
+        }
+        let decoded = &key[BLAKE2_128_LENGTH..];
+        if self.encode(decoded) == key {
+            Some(decoded.to_vec())
+        } else {
+            None
+        }
+    }
+}
+
+/// Storage view that encodes every key with the codec.
+pub struct CodecStorage<S: Storage, C: KeyCodec> {
+    inner: S,
+    codec: C,
+}
+
+impl<S: Storage, C: KeyCodec> CodecStorage<S, C> {
+    /// Wraps the `inner` storage.
+    pub fn new(inner: S, codec: C) -> CodecStorage<S, C> {
+        CodecStorage { inner, codec }
+    }
+
+    /// Returns the wrapped storage.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Unwraps the storage.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Storage, C: KeyCodec> Storage for CodecStorage<S, C> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.inner.get(&self.codec.encode(key))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) {
+        self.inner.insert(&self.codec.encode(key), value)
+    }
+
+    fn remove(&self, key: &[u8]) {
+        self.inner.remove(&self.codec.encode(key))
+    }
+
+    fn next_key(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let mut next = self.inner.next_key(&self.codec.encode(key))?;
+        loop {
+            match self.codec.decode(&next) {
+                Some(key) => return Some(key),
+                None => next = self.inner.next_key(&next)?,
+            }
+        }
+    }
+
+    fn supports_next_key(&self) -> bool {
+        self.codec.preserves_order() && self.inner.supports_next_key()
+    }
+}
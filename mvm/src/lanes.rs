@@ -0,0 +1,88 @@
+//! Sequence number lanes.
+//!
+//! An account has independent sequence numbers per lane, so the transactions sent on the
+//! different lanes don't conflict and can be submitted concurrently.
+//! A script with a sequence number must use the next sequence number of its lane,
+//! the numbers are incremented by every executed or failed transaction which is kept in the block.
+//! Next sequence numbers of the lanes of an account are stored in its
+//! `Lanes::SequenceNumbers` resource.
+
+use alloc::vec::Vec;
+
+use anyhow::Error;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::StructTag;
+use serde::{Deserialize, Serialize};
+
+use crate::data::{AccessKey, Storage};
+
+/// Module of the sequence number lanes.
+pub const LANES_MODULE: &str = "Lanes";
+/// Resource with the sequence numbers of an account.
+pub const SEQUENCE_NUMBERS: &str = "SequenceNumbers";
+
+/// Next sequence number of a lane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lane {
+    pub id: u64,
+    pub sequence_number: u64,
+}
+
+/// Next sequence numbers of the lanes of an account ordered by the lane id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceNumbers {
+    pub lanes: Vec<Lane>,
+}
+
+impl SequenceNumbers {
+    /// Returns the next sequence number of the lane or 0 if the lane was never used.
+    pub fn sequence_number(&self, lane: u64) -> u64 {
+        self.lanes
+            .binary_search_by(|entry| entry.id.cmp(&lane))
+            .map(|idx| self.lanes[idx].sequence_number)
+            .unwrap_or(0)
+    }
+
+    /// Sets the next sequence number of the lane.
+    pub fn set(&mut self, lane: u64, sequence_number: u64) {
+        match self.lanes.binary_search_by(|entry| entry.id.cmp(&lane)) {
+            Ok(idx) => self.lanes[idx].sequence_number = sequence_number,
+            Err(idx) => self.lanes.insert(
+                idx,
+                Lane {
+                    id: lane,
+                    sequence_number,
+                },
+            ),
+        }
+    }
+}
+
+/// Returns the tag of `Lanes::SequenceNumbers`.
+pub fn sequence_numbers_tag(core_address: AccountAddress) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(LANES_MODULE).unwrap(),
+        name: Identifier::new(SEQUENCE_NUMBERS).unwrap(),
+        type_params: vec![],
+    }
+}
+
+/// Returns the storage key of the sequence numbers of the account.
+pub fn sequence_numbers_key(core_address: AccountAddress, address: &AccountAddress) -> AccessKey {
+    AccessKey::from((address, &sequence_numbers_tag(core_address)))
+}
+
+/// Loads the sequence numbers of the account.
+pub fn load_sequence_numbers<S: Storage>(
+    storage: &S,
+    core_address: AccountAddress,
+    address: &AccountAddress,
+) -> Result<SequenceNumbers, Error> {
+    match storage.get(sequence_numbers_key(core_address, address).as_ref()) {
+        Some(blob) => bcs::from_bytes(&blob).map_err(Error::msg),
+        None => Ok(SequenceNumbers::default()),
+    }
+}
@@ -4,14 +4,70 @@
 extern crate alloc;
 
 use crate::data::ExecutionContext;
-use crate::types::{Gas, ModuleTx, PublishPackageTx, ScriptTx, VmResult};
+use crate::types::{BatchTx, Gas, ModuleTx, PublishPackageTx, ScriptTx, VmResult};
+
+#[macro_use]
+mod trace;
 
 pub mod access_path;
+pub mod account;
+#[cfg(feature = "fuzzing")]
+pub mod arbitrary;
+pub mod args;
+pub mod audit;
+pub mod auth;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod block_gas;
+pub mod bridge;
+pub mod builder;
+pub mod circuit_breaker;
+pub mod coin_bridge;
+pub mod compression;
+pub mod consensus;
 pub mod data;
+pub mod diff;
+#[cfg(feature = "disasm")]
+pub mod disasm;
+#[cfg(feature = "dispatch-error")]
+pub mod dispatch;
+pub mod epoch;
+pub mod errors;
+#[cfg(feature = "std")]
+pub mod event_channel;
+pub mod event_handle;
+pub mod events;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
 pub mod gas_schedule;
+pub mod governance;
+pub mod hash;
+pub mod host;
+pub mod key_codec;
+pub mod lanes;
+pub mod metadata;
+pub mod metrics;
 pub mod mvm;
+pub mod nft;
+pub mod oracle;
+pub mod package;
+mod panic;
+pub mod proof;
+pub mod publish;
+pub mod source_map;
+pub mod speculative;
+#[cfg(feature = "embedded-stdlib")]
+pub mod stdlib;
+pub mod tenant;
+#[cfg(feature = "test-helpers")]
+pub mod testkit;
+pub mod tokens;
+pub mod tx_builder;
 pub mod types;
+pub mod value;
+pub mod view;
 pub mod vm_config;
+pub mod write_set;
 
 pub trait Vm {
     /// Publishes module to the chain.
@@ -34,3 +90,16 @@ pub trait Vm {
     /// Clear vm cache.
     fn clear(&self);
 }
+
+/// Vms executing batches of entry function calls.
+pub trait BatchVm: Vm {
+    /// Execute entry functions atomically in one session with the shared gas meter.
+    /// Effects are applied only if all the calls succeed.
+    fn execute_batch(
+        &self,
+        gas: Gas,
+        context: ExecutionContext,
+        tx: BatchTx,
+        dry_run: bool,
+    ) -> VmResult;
+}
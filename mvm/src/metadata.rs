@@ -0,0 +1,239 @@
+//! Module metadata extraction for indexers.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use anyhow::{Error, Result};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::value::MoveValue;
+use serde::{Deserialize, Serialize};
+use vm::access::ModuleAccess;
+use vm::file_format::{SignatureToken, StructFieldInformation, StructHandleIndex};
+use vm::CompiledModule;
+
+/// Declared constants and structs of the module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleMetadata {
+    pub address: AccountAddress,
+    pub name: String,
+    pub constants: Vec<ConstantMetadata>,
+    pub structs: Vec<StructMetadata>,
+    pub functions: Vec<FunctionMetadata>,
+}
+
+/// Constant pool entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstantMetadata {
+    /// Index in the constant pool. Constants are referenced by the index in bytecode.
+    pub index: u16,
+    /// Constant type, e.g. `u64` or `vector<u8>`.
+    pub type_: String,
+    /// Decoded value.
+    pub value: ConstantValue,
+}
+
+/// Constant value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConstantValue {
+    U8(u8),
+    U64(u64),
+    U128(u128),
+    Bool(bool),
+    Address(AccountAddress),
+    Vector(Vec<ConstantValue>),
+}
+
+/// Struct definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructMetadata {
+    pub name: String,
+    pub is_resource: bool,
+    pub type_parameters: usize,
+    /// Fields in declaration order. Empty for native structs.
+    pub fields: Vec<FieldMetadata>,
+    pub is_native: bool,
+}
+
+/// Struct field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldMetadata {
+    pub name: String,
+    /// Field type, e.g. `u128` or `0x1::Coins::Coin<T0>`.
+    pub type_: String,
+}
+
+/// Function visibility.
+/// The bytecode version has no friend declarations, so functions are either private or public.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Visibility {
+    Private,
+    Public,
+}
+
+/// Function definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionMetadata {
+    pub name: String,
+    pub visibility: Visibility,
+    /// Entry functions can be called by the vm as the transaction entry point: public functions
+    /// returning nothing whose parameters are `&signer` references followed by argument types.
+    /// Calls of the other functions fail with `EXECUTE_ENTRY_FUNCTION_CALLED_ON_NON_ENTRY_FUNCTION`.
+    pub is_entry: bool,
+    pub is_native: bool,
+    pub type_parameters: usize,
+    /// Parameter types, e.g. `&signer` or `u64`.
+    pub parameters: Vec<String>,
+    pub returns: Vec<String>,
+}
+
+impl ModuleMetadata {
+    /// Extracts metadata from the module bytecode.
+    pub fn from_bytes(bytecode: &[u8]) -> Result<ModuleMetadata> {
+        let module = CompiledModule::deserialize(bytecode)
+            .map_err(|err| Error::msg(format!("Failed to deserialize module: {:?}", err)))?;
+        ModuleMetadata::from_module(&module)
+    }
+
+    /// Extracts metadata from the compiled module.
+    pub fn from_module(module: &CompiledModule) -> Result<ModuleMetadata> {
+        let constants = module
+            .constant_pool()
+            .iter()
+            .enumerate()
+            .map(|(index, constant)| {
+                let value = constant
+                    .deserialize_constant()
+                    .ok_or_else(|| Error::msg(format!("Invalid constant {}", index)))
+                    .and_then(constant_value)?;
+                Ok(ConstantMetadata {
+                    index: index as u16,
+                    type_: type_name(module, &constant.type_),
+                    value,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let structs = module
+            .struct_defs()
+            .iter()
+            .map(|def| {
+                let handle = module.struct_handle_at(def.struct_handle);
+                let (fields, is_native) = match &def.field_information {
+                    StructFieldInformation::Native => (vec![], true),
+                    StructFieldInformation::Declared(fields) => (
+                        fields
+                            .iter()
+                            .map(|field| FieldMetadata {
+                                name: module.identifier_at(field.name).to_string(),
+                                type_: type_name(module, &field.signature.0),
+                            })
+                            .collect(),
+                        false,
+                    ),
+                };
+                StructMetadata {
+                    name: module.identifier_at(handle.name).to_string(),
+                    is_resource: handle.is_nominal_resource,
+                    type_parameters: handle.type_parameters.len(),
+                    fields,
+                    is_native,
+                }
+            })
+            .collect();
+
+        let functions = module
+            .function_defs()
+            .iter()
+            .map(|def| {
+                let handle = module.function_handle_at(def.function);
+                let types = |idx| {
+                    module
+                        .signature_at(idx)
+                        .0
+                        .iter()
+                        .map(|token| type_name(module, token))
+                        .collect()
+                };
+                FunctionMetadata {
+                    name: module.identifier_at(handle.name).to_string(),
+                    visibility: if def.is_public {
+                        Visibility::Public
+                    } else {
+                        Visibility::Private
+                    },
+                    is_entry: def.is_public
+                        && module.signature_at(handle.return_).is_empty()
+                        && module.signature_at(handle.parameters).is_entry_parameters(),
+                    is_native: def.is_native(),
+                    type_parameters: handle.type_parameters.len(),
+                    parameters: types(handle.parameters),
+                    returns: types(handle.return_),
+                }
+            })
+            .collect();
+
+        Ok(ModuleMetadata {
+            address: *module.address(),
+            name: module.name().to_string(),
+            constants,
+            structs,
+            functions,
+        })
+    }
+}
+
+fn constant_value(value: MoveValue) -> Result<ConstantValue> {
+    Ok(match value {
+        MoveValue::U8(val) => ConstantValue::U8(val),
+        MoveValue::U64(val) => ConstantValue::U64(val),
+        MoveValue::U128(val) => ConstantValue::U128(val),
+        MoveValue::Bool(val) => ConstantValue::Bool(val),
+        MoveValue::Address(val) => ConstantValue::Address(val),
+        MoveValue::Vector(vals) => ConstantValue::Vector(
+            vals.into_iter()
+                .map(constant_value)
+                .collect::<Result<_>>()?,
+        ),
+        MoveValue::Struct(_) | MoveValue::Signer(_) => {
+            return Err(Error::msg("Unsupported constant type"))
+        }
+    })
+}
+
+pub(crate) fn type_name(module: &CompiledModule, token: &SignatureToken) -> String {
+    match token {
+        SignatureToken::Bool => "bool".to_string(),
+        SignatureToken::U8 => "u8".to_string(),
+        SignatureToken::U64 => "u64".to_string(),
+        SignatureToken::U128 => "u128".to_string(),
+        SignatureToken::Address => "address".to_string(),
+        SignatureToken::Signer => "signer".to_string(),
+        SignatureToken::Vector(inner) => format!("vector<{}>", type_name(module, inner)),
+        SignatureToken::Struct(idx) => struct_name(module, *idx),
+        SignatureToken::StructInstantiation(idx, type_args) => format!(
+            "{}<{}>",
+            struct_name(module, *idx),
+            type_args
+                .iter()
+                .map(|ty| type_name(module, ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        SignatureToken::Reference(inner) => format!("&{}", type_name(module, inner)),
+        SignatureToken::MutableReference(inner) => format!("&mut {}", type_name(module, inner)),
+        SignatureToken::TypeParameter(idx) => format!("T{}", idx),
+    }
+}
+
+pub(crate) fn struct_name(module: &CompiledModule, idx: StructHandleIndex) -> String {
+    let handle = module.struct_handle_at(idx);
+    let module_handle = module.module_handle_at(handle.module);
+    format!(
+        "0x{}::{}::{}",
+        module
+            .address_identifier_at(module_handle.address)
+            .short_str_lossless(),
+        module.identifier_at(module_handle.name),
+        module.identifier_at(handle.name)
+    )
+}
@@ -0,0 +1,131 @@
+use alloc::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_vm_runtime::data_cache::RemoteCache;
+use vm::errors::{PartialVMResult, VMResult};
+
+use crate::audit::OogDivergence;
+use crate::types::VmResult;
+
+/// Transaction kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxKind {
+    PublishModule,
+    PublishPackage,
+    Script,
+    Batch,
+    Message,
+    CreateAccount,
+}
+
+/// Allocations of the serialized transaction effects.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SerializationStats {
+    /// Number of the serialized resources and events.
+    pub values: usize,
+    /// Total size of the serialized values.
+    pub bytes: usize,
+    /// Allocations of the serialized values.
+    pub allocations: usize,
+    /// Allocations of serializing each value into its own growing buffer.
+    pub per_value_allocations: usize,
+}
+
+impl SerializationStats {
+    pub(crate) fn add_value(&mut self, size: usize) {
+        self.values += 1;
+        self.bytes += size;
+        self.per_value_allocations += growth_allocations(size);
+    }
+}
+
+/// Returns the number of allocations of a byte vector growing from empty to `size`.
+/// The vector allocates 8 bytes first and doubles its capacity afterwards.
+fn growth_allocations(size: usize) -> usize {
+    let mut allocations = 0;
+    let mut capacity = 0;
+    while capacity < size {
+        capacity = core::cmp::max(8, capacity * 2);
+        allocations += 1;
+    }
+    allocations
+}
+
+/// Vm observability hooks.
+/// All methods do nothing by default, so implementors override only the hooks they need.
+pub trait Metrics {
+    /// Called before the transaction execution.
+    /// Latency can be measured as the time between `on_tx_start` and `on_tx_end`.
+    fn on_tx_start(&self, _kind: TxKind) {}
+
+    /// Called after the transaction execution with its result (status and gas used).
+    fn on_tx_end(&self, _kind: TxKind, _result: &VmResult) {}
+
+    /// Called when the vm reads a module from the storage.
+    /// The loader reads modules only on cache misses.
+    fn on_module_read(&self, _id: &ModuleId, _found: bool) {}
+
+    /// Called when the vm reads a resource from the storage.
+    fn on_resource_read(&self, _address: &AccountAddress, _tag: &StructTag, _found: bool) {}
+
+    /// Called when the out-of-gas audit detects a nondeterministic out-of-gas path.
+    fn on_oog_divergence(&self, _divergence: &OogDivergence) {}
+
+    /// Called when the effects of a transaction are serialized for the storage.
+    /// Compares the allocations with the per-value serialization.
+    fn on_effects_serialized(&self, _stats: &SerializationStats) {}
+
+    /// Called when a transaction fails with an invariant violation.
+    /// Critical: the vm state may be corrupted.
+    fn on_invariant_violation(&self, _result: &VmResult) {}
+}
+
+/// Metrics which discards everything.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoMetrics;
+
+impl Metrics for NoMetrics {}
+
+/// Remote cache which reports storage reads to the metrics.
+pub(crate) struct MeteredCache<'a, R: RemoteCache, M: Metrics> {
+    remote: &'a R,
+    metrics: &'a M,
+}
+
+impl<'a, R, M> MeteredCache<'a, R, M>
+where
+    R: RemoteCache,
+    M: Metrics,
+{
+    pub fn new(remote: &'a R, metrics: &'a M) -> MeteredCache<'a, R, M> {
+        MeteredCache { remote, metrics }
+    }
+}
+
+impl<R, M> RemoteCache for MeteredCache<'_, R, M>
+where
+    R: RemoteCache,
+    M: Metrics,
+{
+    fn get_module(&self, module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
+        let module = self.remote.get_module(module_id)?;
+        self.metrics.on_module_read(module_id, module.is_some());
+        Ok(module)
+    }
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        let resource = self.remote.get_resource(address, tag)?;
+        self.metrics
+            .on_resource_read(address, tag, resource.is_some());
+        Ok(resource)
+    }
+
+    fn get_twap(&self, tag: &StructTag, window: u64) -> PartialVMResult<Option<u128>> {
+        self.remote.get_twap(tag, window)
+    }
+}
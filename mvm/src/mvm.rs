@@ -1,5 +1,11 @@
 use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::btree_map::Entry;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::Error;
 
@@ -7,38 +13,128 @@ use move_core_types::account_address::AccountAddress;
 use move_core_types::gas_schedule::CostTable;
 use move_core_types::gas_schedule::{AbstractMemorySize, GasAlgebra, GasUnits};
 use move_core_types::identifier::Identifier;
-use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS, NONE_ADDRESS};
-use move_core_types::vm_status::{AbortLocation, StatusCode, VMStatus};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag, NONE_ADDRESS, RESOURCE_TAG};
+use move_core_types::value::MoveTypeLayout;
+use move_core_types::vm_status::{AbortLocation, StatusCode, StatusType, VMStatus};
 use move_vm_runtime::data_cache::{RemoteCache, TransactionEffects};
 use move_vm_runtime::logging::NoContextLog;
-use move_vm_runtime::move_vm::MoveVM;
-use move_vm_runtime::session::Session;
-use move_vm_types::gas_schedule::CostStrategy;
-use move_vm_types::natives::balance::{BalanceOperation, NativeBalance};
+use move_vm_runtime::move_vm::{CacheStats, MoveVM, SharedCache, VMLimits};
+use move_vm_runtime::native_registry::NativeRegistry;
+use move_vm_runtime::session::{ModuleUpgrade, Session};
+use move_vm_types::gas_schedule::{CostStrategy, Interrupt};
+use move_vm_types::natives::balance::{Balance, BalanceOperation, NativeBalance};
+use move_vm_types::values::Value;
+use parity_scale_codec::{Decode, Encode};
+use spin::RwLock;
+use vm::access::ModuleAccess;
 use vm::errors::{Location, PartialVMError, VMError, VMResult};
+use vm::CompiledModule;
 
-use crate::data::AccessKey;
+use crate::access_path::AccessPath;
+use crate::account::{account_module, account_tag, balance_tag, decode_balance, CREATE_ACCOUNT};
+use crate::audit::{find_divergence, ReadRecorder, ResourceRead};
+use crate::auth::{load_auth_key, register_auth, Authenticator};
+use crate::block_gas::BlockGasMeter;
+use crate::bridge::{register_bridge_send, Message, MessageQueue};
+use crate::builder::MvmBuilder;
+use crate::circuit_breaker::{CircuitBreaker, SafeMode};
+use crate::coin_bridge::{
+    check_bridge_module, check_conservation, register_coin_bridge, COIN_BRIDGE_MODULE,
+};
+use crate::compression::Compression;
+use crate::data::{coin_tag, decode_price, price_tag, AccessKey, OracleRead};
 use crate::data::{
-    BalanceAccess, Bank, EventHandler, ExecutionContext, Oracle, State, StateSession, Storage,
-    WriteEffects,
+    BalanceAccess, Bank, EventHandler, ExecutionContext, Oracle, SessionBank, State, StateSession,
+    Storage, WriteEffects,
+};
+use crate::epoch::{EpochManager, EPOCH_MODULE, NEW_EPOCH_EVENT};
+use crate::errors::{register_abort_with_message, AbortMessage};
+use crate::event_handle::{counter_writes, register_event_handles};
+use crate::events::{event_topics, indexed_fields, indexed_values};
+use crate::governance::GovernanceOrigin;
+use crate::hash::{module_hash, Digest};
+use crate::host::{register_host_call, HostHandler, HostHandlers};
+use crate::lanes::{load_sequence_numbers, sequence_numbers_key};
+use crate::metrics::{MeteredCache, Metrics, NoMetrics, TxKind};
+use crate::nft::{load_nft, load_token_store, register_nft, Nft, NftWrites};
+use crate::oracle::{history_tag, register_oracle, PriceHistory};
+use crate::package::{load_packages, packages_key, PackageManifest, PackageRelease, UpgradePolicy};
+use crate::panic::guard;
+use crate::proof::{ResourceProof, StateProof};
+use crate::publish::{
+    forced_upgrade_tag, immutable_key, load_immutable_modules, load_module_versions,
+    module_published_tag, versions_key, ForcedUpgradeEvent, ModulePublishedEvent,
+};
+use crate::source_map::{load_source_map, source_map_key, ErrorLocation, SourceMap};
+use crate::speculative::{HostReads, SerializedEffects, Simulation, Speculation, SpeculativeCache};
+use crate::tenant::{Space, TenantContext, TenantId};
+use crate::tokens::token_tag;
+use crate::types::{
+    BalanceBreakdown, BalanceChange, BatchTx, Gas, GasBounds, GasError, ModuleTx, PublishPackageTx,
+    ResourcesPage, ScriptTx, Ticker, TxLimits, VmResult,
+};
+use crate::view::{ViewCache, ViewCall, ViewRead};
+use crate::vm_config::loader::{
+    access_path_for_config, access_path_for_epoch, access_path_for_halted,
+    access_path_for_publisher_policy, access_path_for_registered_currencies, has_vm_config_at,
+    load_config_view_at, load_epoch_at, load_halted_at, load_publisher_policy_at,
+    load_registered_currencies_at, load_vm_config_at,
 };
-use crate::types::{Gas, ModuleTx, PublishPackageTx, ScriptTx, VmResult};
-use crate::vm_config::loader::load_vm_config;
-use crate::Vm;
+use crate::vm_config::{AddressesConfig, ConfigView, PublishPermission};
+use crate::write_set::{
+    ResourceDeleted, WriteOp, WriteSet, RESOURCE_DELETED_EVENT, STORAGE_MODULE,
+};
+use crate::{BatchVm, Vm};
 
 /// MoveVM.
-pub struct Mvm<S, E, O, B>
+pub struct Mvm<S, E, O, B, M = NoMetrics>
 where
     S: Storage,
     E: EventHandler,
     O: Oracle,
     B: BalanceAccess,
+    M: Metrics,
 {
     vm: MoveVM,
-    cost_table: CostTable,
+    cost_table: RwLock<Arc<CostTable>>,
+    /// Create missing accounts of the script senders, see `VmConfig::lazy_accounts`.
+    lazy_accounts: AtomicBool,
+    /// Check the coin conservation of the transactions, see `VmConfig::check_balance_conservation`.
+    check_balance_conservation: AtomicBool,
+    /// The vm config is stored, see `is_initialized`.
+    initialized: AtomicBool,
+    /// Accept only system transactions until the vm is initialized.
+    uninitialized_mode: bool,
+    /// Chain id of the accepted transactions, see `VmConfig::chain_id`.
+    chain_id: Option<u8>,
+    tx_limits: TxLimits,
+    /// Bounds of the transaction gas, see `VmConfig::gas_bounds`.
+    gas_bounds: RwLock<GasBounds>,
     state: State<S, O>,
     event_handler: E,
     bank: Bank<B>,
+    metrics: M,
+    compression: Compression,
+    instruction_limit: Option<u64>,
+    interrupt: Option<Box<dyn Interrupt + Send + Sync>>,
+    oog_audit: Option<u64>,
+    authenticator: Option<Box<dyn Authenticator + Send + Sync>>,
+    governance: Option<Box<dyn GovernanceOrigin + Send + Sync>>,
+    addresses: AddressesConfig,
+    natives: NativeRegistry,
+    host_handlers: HostHandlers,
+    message_queue: Option<Box<dyn MessageQueue + Send + Sync>>,
+    /// Host-local storage of the source maps, not part of the consensus state.
+    source_maps: Option<Box<dyn Storage + Send + Sync>>,
+    message_handler: Option<(ModuleId, Identifier)>,
+    circuit_breaker: Option<CircuitBreaker>,
+    /// Emit `ResourceDeleted` events of the deleted keys, see `write_set`.
+    deletion_events: bool,
+    epochs: EpochManager,
+    speculative_cache: Option<SpeculativeCache>,
+    view_cache: Option<ViewCache>,
+    /// Loader caches and configs of the tenants, see `tenant`.
+    tenants: RwLock<BTreeMap<TenantId, Arc<TenantContext>>>,
 }
 
 impl<S, E, O, B> Mvm<S, E, O, B>
@@ -49,144 +145,2704 @@ where
     B: BalanceAccess,
 {
     /// Creates a new move vm with given store and event handler.
+    #[deprecated(note = "use `MvmBuilder`")]
     pub fn new(
         store: S,
         event_handler: E,
         oracle: O,
         balance: B,
     ) -> Result<Mvm<S, E, O, B>, Error> {
-        let config = load_vm_config(&store)?;
+        MvmBuilder::new(store, event_handler)
+            .with_oracle(oracle)
+            .with_bank(balance)
+            .build()
+    }
+}
+
+impl<S, E, O, B, M> Mvm<S, E, O, B, M>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle,
+    B: BalanceAccess,
+    M: Metrics,
+{
+    /// Creates a new move vm with given store, event handler and metrics.
+    #[deprecated(note = "use `MvmBuilder::with_metrics`")]
+    pub fn new_with_metrics(
+        store: S,
+        event_handler: E,
+        oracle: O,
+        balance: B,
+        metrics: M,
+    ) -> Result<Mvm<S, E, O, B, M>, Error> {
+        MvmBuilder::new(store, event_handler)
+            .with_oracle(oracle)
+            .with_bank(balance)
+            .with_metrics(metrics)
+            .build()
+    }
+
+    /// Creates a new move vm with the standard library and the vm config
+    /// at the given addresses.
+    #[deprecated(note = "use `MvmBuilder::with_addresses`")]
+    pub fn new_with_addresses(
+        store: S,
+        event_handler: E,
+        oracle: O,
+        balance: B,
+        metrics: M,
+        addresses: AddressesConfig,
+    ) -> Result<Mvm<S, E, O, B, M>, Error> {
+        MvmBuilder::new(store, event_handler)
+            .with_oracle(oracle)
+            .with_bank(balance)
+            .with_metrics(metrics)
+            .with_addresses(addresses)
+            .build()
+    }
+
+    /// Creates a new move vm which prepends the prefix to all its storage keys,
+    /// see `State::with_key_prefix`.
+    #[deprecated(note = "use `MvmBuilder::with_key_prefix`")]
+    pub fn new_with_key_prefix(
+        store: S,
+        event_handler: E,
+        oracle: O,
+        balance: B,
+        metrics: M,
+        addresses: AddressesConfig,
+        key_prefix: Vec<u8>,
+    ) -> Result<Mvm<S, E, O, B, M>, Error> {
+        MvmBuilder::new(store, event_handler)
+            .with_oracle(oracle)
+            .with_bank(balance)
+            .with_metrics(metrics)
+            .with_addresses(addresses)
+            .with_key_prefix(key_prefix)
+            .build()
+    }
+
+    /// Creates a new move vm over the state, e.g. the state with a key codec,
+    /// see `State::with_key_codec`.
+    #[deprecated(note = "use `MvmBuilder::with_key_codec` and `MvmBuilder::with_tenant_spaces`")]
+    pub fn new_with_state(
+        state: State<S, O>,
+        event_handler: E,
+        balance: B,
+        metrics: M,
+        addresses: AddressesConfig,
+    ) -> Result<Mvm<S, E, O, B, M>, Error> {
+        Mvm::from_state(state, event_handler, balance, metrics, addresses)
+    }
+
+    /// Creates the vm over the state, see `MvmBuilder::build`.
+    pub(crate) fn from_state(
+        state: State<S, O>,
+        event_handler: E,
+        balance: B,
+        metrics: M,
+        addresses: AddressesConfig,
+    ) -> Result<Mvm<S, E, O, B, M>, Error> {
+        let state = state
+            .with_core_address(addresses.core_code_address)
+            .with_oracle_address(addresses.oracle_address);
+        let store = &state.storage(None);
+        let initialized = has_vm_config_at(store, addresses.config_address);
+        let config = load_vm_config_at(store, addresses.config_address)?;
+        let mut epochs = EpochManager::new(load_epoch_at(store, addresses.config_address)?);
+        epochs.register(&access_path_for_config(addresses.config_address));
+        epochs.register(&access_path_for_publisher_policy(addresses.config_address));
+        epochs.register(&access_path_for_registered_currencies(
+            addresses.config_address,
+        ));
+        let bank = Bank::new(balance).with_core_address(addresses.core_code_address);
+        bank.set_registered_currencies(load_registered_currencies_at(
+            store,
+            addresses.config_address,
+        )?);
+
+        let mvm = Mvm {
+            vm: MoveVM::new_with_limits(config.limits())
+                .with_verification_costs(config.verification_costs())
+                .with_access_costs(config.access_costs())
+                .with_core_address(addresses.core_code_address),
+            lazy_accounts: AtomicBool::new(config.lazy_accounts),
+            check_balance_conservation: AtomicBool::new(config.check_balance_conservation),
+            initialized: AtomicBool::new(initialized),
+            uninitialized_mode: false,
+            chain_id: config.chain_id,
+            tx_limits: config.tx_limits(),
+            gas_bounds: RwLock::new(config.gas_bounds()),
+            cost_table: RwLock::new(Arc::new(config.gas_schedule)),
+            state,
+            event_handler,
+            bank,
+            metrics,
+            compression: Compression::default(),
+            instruction_limit: None,
+            interrupt: None,
+            oog_audit: None,
+            authenticator: None,
+            governance: None,
+            addresses,
+            natives: NativeRegistry::new(),
+            host_handlers: HostHandlers::new(),
+            message_queue: None,
+            source_maps: None,
+            message_handler: None,
+            circuit_breaker: None,
+            deletion_events: false,
+            epochs,
+            speculative_cache: None,
+            view_cache: None,
+            tenants: RwLock::new(BTreeMap::new()),
+        };
+        Ok(mvm.update_natives())
+    }
+
+    /// Sets compression mode of the published modules.
+    /// Modules are readable regardless of the mode they were stored with.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Limits the number of instructions executed by a script regardless of the gas.
+    /// Protects against under-priced operations in the gas table.
+    /// A script which exceeds the limit fails with `INSTRUCTION_LIMIT_EXCEEDED` and is charged:
+    /// the limit is the same on every node.
+    pub fn with_instruction_limit(mut self, limit: u64) -> Self {
+        self.instruction_limit = Some(limit);
+        self
+    }
+
+    /// Overrides the execution limits of the on-chain vm config, e.g. for a local node.
+    /// The limits are kept on the next epochs. All nodes of a chain must use the same limits.
+    pub fn with_limits(mut self, limits: VMLimits) -> Self {
+        self.vm = self.vm.with_limits(limits);
+        self
+    }
+
+    /// Overrides the script transaction limits of the on-chain vm config, see `with_limits`.
+    pub fn with_tx_limits(mut self, limits: TxLimits) -> Self {
+        self.tx_limits = limits;
+        self
+    }
+
+    /// Shares the loader cache with other vm instances, e.g. the vms of the RPC workers,
+    /// so the modules are deserialized and kept in memory once.
+    /// The instances must use the same addresses and natives: call it after the other options.
+    /// Clearing the cache of one instance clears it for all of them.
+    pub fn with_shared_cache(mut self, cache: Arc<SharedCache>) -> Self {
+        self.vm = self.vm.with_shared_cache(cache);
+        self
+    }
+
+    /// Sets the host hook which is periodically checked during the script execution.
+    /// A script fails with `EXECUTION_INTERRUPTED` once the hook returns `true`.
+    /// The interrupt is local to the node, so the interrupted transaction is discarded.
+    pub fn with_interrupt<I>(mut self, interrupt: I) -> Self
+    where
+        I: Interrupt + Send + Sync + 'static,
+    {
+        self.interrupt = Some(Box::new(interrupt));
+        self
+    }
+
+    /// Enables the out-of-gas audit mode.
+    /// A script which runs out of gas is re-executed with `extra_gas_percent` more gas
+    /// and its resource reads are compared with the failed run.
+    /// Divergences are logged and reported to `Metrics::on_oog_divergence`.
+    /// Intended for canary nodes: the re-run doubles the cost of failed transactions.
+    pub fn with_oog_audit(mut self, extra_gas_percent: u64) -> Self {
+        self.oog_audit = Some(extra_gas_percent);
+        self
+    }
+
+    /// Sets the authenticator consulted before the script execution.
+    /// A script rejected by the authenticator fails with `INVALID_SIGNATURE` without execution.
+    pub fn with_authenticator<A>(mut self, authenticator: A) -> Self
+    where
+        A: Authenticator + Send + Sync + 'static,
+    {
+        self.authenticator = Some(Box::new(authenticator));
+        self
+    }
+
+    /// Sets the check approving the scripts executed with `execute_governance_script`.
+    /// Without the check governance scripts fail with `NO_ACCOUNT_ROLE`.
+    pub fn with_governance_origin<G>(mut self, origin: G) -> Self
+    where
+        G: GovernanceOrigin + Send + Sync + 'static,
+    {
+        self.governance = Some(Box::new(origin));
+        self
+    }
+
+    /// Registers additional native functions of the embedding chain.
+    /// Modules declaring the natives can be published after the registration.
+    pub fn with_natives(mut self, natives: NativeRegistry) -> Self {
+        self.natives = natives;
+        self.update_natives()
+    }
+
+    /// Registers the handler of `0x1::Host::call` with the given id.
+    /// Enables the `Host::call` native.
+    pub fn with_host_handler<H>(mut self, id: u64, handler: H) -> Self
+    where
+        H: HostHandler + Send + Sync + 'static,
+    {
+        self.host_handlers.insert(id, Arc::new(handler));
+        self.update_natives()
+    }
+
+    /// Sets the queue of the messages sent with `0x1::Bridge::send`.
+    /// Enables the `Bridge::send` native.
+    pub fn with_message_queue<Q>(mut self, queue: Q) -> Self
+    where
+        Q: MessageQueue + Send + Sync + 'static,
+    {
+        self.message_queue = Some(Box::new(queue));
+        self.update_natives()
+    }
+
+    /// Sets the host-local storage of the source maps of the published modules.
+    /// The source maps are not written to the consensus state; without the storage
+    /// they are only used to resolve the errors of the publish transaction itself.
+    pub fn with_source_map_storage<T>(mut self, storage: T) -> Self
+    where
+        T: Storage + Send + Sync + 'static,
+    {
+        self.source_maps = Some(Box::new(storage));
+        self
+    }
+
+    /// Sets the Move function receiving messages delivered with `deliver_message`.
+    /// The handler is an entry function called as
+    /// `handler(source: vector<u8>, payload: vector<u8>)`, no signer is passed.
+    pub fn with_message_handler(mut self, module: ModuleId, function: Identifier) -> Self {
+        self.message_handler = Some((module, function));
+        self
+    }
+
+    /// Enables the invariant violation circuit breaker.
+    /// After a transaction fails with an invariant violation the vm accepts only
+    /// the transactions allowed by the safe mode, others fail with `VM_HALTED`.
+    /// The governance account is the config address.
+    pub fn with_circuit_breaker(mut self, mode: SafeMode) -> Self {
+        self.circuit_breaker = Some(CircuitBreaker::new(mode));
+        self
+    }
+
+    /// Emits the `write_set::ResourceDeleted` event under the address of every key deleted
+    /// by a transaction, after the events of the transaction and in the key order.
+    pub fn with_deletion_events(mut self) -> Self {
+        self.deletion_events = true;
+        self
+    }
+
+    /// Enables the speculative execution cache keeping up to `capacity` simulated scripts.
+    /// See `simulate_script` and `execute_speculated`.
+    pub fn with_speculative_cache(mut self, capacity: usize) -> Self {
+        self.speculative_cache = Some(SpeculativeCache::new(capacity));
+        self
+    }
+
+    /// Enables the cache of the view function results keeping up to `capacity` results
+    /// for `ttl` in the units of the execution context timestamp. See `view_function`.
+    pub fn with_view_cache(mut self, capacity: usize, ttl: u64) -> Self {
+        self.view_cache = Some(ViewCache::new(capacity, ttl));
+        self
+    }
+
+    /// Enables the uninitialized mode for the genesis bootstrapping.
+    /// Until the vm config is stored, the vm runs with the default config and accepts
+    /// only transactions of the core code and the config addresses, others fail with `VM_HALTED`.
+    /// The vm is initialized at the start of the epoch following the config write.
+    pub fn with_uninitialized_mode(mut self) -> Self {
+        self.uninitialized_mode = true;
+        self
+    }
+
+    /// Returns `true` if the vm config is stored.
+    /// An uninitialized vm runs with the default config, see `VmConfig::default`.
+    pub fn is_initialized(&self) -> bool {
+        self.initialized.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the vm is halted by the circuit breaker.
+    /// The halt flag is stored under the config address, see `circuit_breaker`.
+    pub fn is_halted(&self) -> bool {
+        if self.circuit_breaker.is_none() {
+            return false;
+        }
+        load_halted_at(&self.state.storage(None), self.addresses.config_address).unwrap_or_else(
+            |err| {
+                log::error!("Failed to load halt flag: {:?}", err);
+                true
+            },
+        )
+    }
+
+    /// Registers the on-chain config path.
+    /// A transaction which writes the path starts a new epoch.
+    /// The vm config and the publisher policy paths are registered by default.
+    pub fn with_config_path(mut self, path: AccessPath) -> Self {
+        self.epochs.register(&path);
+        self
+    }
+
+    /// Returns the current epoch.
+    pub fn current_epoch(&self) -> u64 {
+        self.epochs.current()
+    }
+
+    /// Returns all on-chain configs of the vm.
+    pub fn config_view(&self) -> Result<ConfigView, Error> {
+        load_config_view_at(&self.state.storage(None), self.addresses.config_address)
+    }
+
+    /// Registers the currency in the on-chain `RegisteredCurrencies` list
+    /// under the config address and starts a new epoch.
+    /// The list is written by a transaction of the config address, so the result has
+    /// the digest of its write set with the epoch number.
+    /// Fails if the currency is already registered.
+    pub fn register_currency(&self, ticker: Ticker) -> Result<VmResult, Error> {
+        let config_address = self.addresses.config_address;
+        let mut currencies =
+            load_registered_currencies_at(&self.state.storage(None), config_address)?
+                .unwrap_or_default();
+        if !currencies.register(ticker.clone()) {
+            return Err(Error::msg(format!(
+                "Currency {} is already registered",
+                ticker
+            )));
+        }
+        if let Err(result) = self.check_halted(&[config_address]) {
+            return Ok(result);
+        }
+        let path = access_path_for_registered_currencies(config_address);
+        let write = (AccessKey::from(&path), Some(currencies.encode()));
+        let space = Space::host();
+        let state = self.state.space(None);
+        let effects = self.new_session(&space, &state, &self.bank).finish();
+        Ok(self.apply_vm_result(
+            &space,
+            config_address,
+            0,
+            effects.and_then(|effects| self.serialize_effects(effects)),
+            HostWrites::executed(vec![write]),
+            None,
+        ))
+    }
+
+    /// Appends the current oracle price of the `first`/`second` currency pair
+    /// to its `PriceHistory` under the config address.
+    /// Should be called by the host once per block before executing transactions.
+    pub fn record_price(
+        &self,
+        first: TypeTag,
+        second: TypeTag,
+        timestamp: u64,
+    ) -> Result<(), Error> {
+        let core_address = self.addresses.core_code_address;
+        let config_address = self.addresses.config_address;
+        let tag = price_tag(core_address, first, second);
+        let price = self
+            .state
+            .get_resource(&core_address, &tag)
+            .map_err(|err| Error::msg(format!("{:?}", err)))?
+            .and_then(|blob| decode_price(&blob))
+            .ok_or_else(|| Error::msg("Price of the currency pair not found"))?;
+
+        let key = AccessKey::from((
+            &config_address,
+            &history_tag(config_address, tag.type_params),
+        ));
+        let mut history = match &self.state.storage(None).get(key.as_ref()) {
+            Some(blob) => PriceHistory::decode(&mut blob.as_slice())
+                .map_err(|err| Error::msg(format!("Failed to decode price history: {:?}", err)))?,
+            None => PriceHistory::default(),
+        };
+        if !history.push(timestamp, price) {
+            return Err(Error::msg("Timestamp precedes the last recorded price"));
+        }
+        if let Some(cache) = &self.view_cache {
+            cache.invalidate_key(key.as_ref());
+        }
+        self.state.insert(key, &history.encode());
+        Ok(())
+    }
+
+    /// Returns the current gas schedule of the space.
+    fn cost_table(&self, space: &Space) -> Arc<CostTable> {
+        match space.context() {
+            Some(context) => context.cost_table(),
+            None => self.cost_table.read().clone(),
+        }
+    }
+
+    /// Returns the storage space of the tenant or the host space without a tenant.
+    /// The epoch and the configs of the tenant are loaded on its first transaction.
+    /// Fails with `STORAGE_ERROR` if the tenant spaces are disabled,
+    /// see `State::with_tenant_spaces`.
+    fn space(&self, tenant: Option<TenantId>) -> Result<Space, VmResult> {
+        let tenant = match tenant {
+            Some(tenant) => tenant,
+            None => return Ok(Space::host()),
+        };
+        if let Some(context) = self.tenants.read().get(&tenant) {
+            return Ok(Space::of(tenant, context.clone()));
+        }
+        if !self.state.has_tenant_spaces() {
+            return Err(VmResult::new(StatusCode::STORAGE_ERROR, None, 0));
+        }
+        let epoch = load_epoch_at(
+            &self.state.storage(Some(tenant)),
+            self.addresses.config_address,
+        );
+        let context = epoch
+            .and_then(|epoch| Ok(TenantContext::new(epoch, self.load_cost_table(tenant)?)))
+            .map_err(|err| {
+                log::error!("Failed to load configs of tenant {:?}: {:?}", tenant, err);
+                VmResult::new(StatusCode::STORAGE_ERROR, None, 0)
+            })?;
+        let context = self
+            .tenants
+            .write()
+            .entry(tenant)
+            .or_insert_with(|| Arc::new(context))
+            .clone();
+        Ok(Space::of(tenant, context))
+    }
+
+    /// Loads the gas schedule of the vm config of the tenant from its storage space.
+    fn load_cost_table(&self, tenant: TenantId) -> Result<CostTable, Error> {
+        load_vm_config_at(
+            &self.state.storage(Some(tenant)),
+            self.addresses.config_address,
+        )
+        .map(|config| config.gas_schedule)
+    }
+
+    /// Creates a session loading the modules into the loader cache of the space.
+    fn new_session<'r, R, NB>(
+        &self,
+        space: &Space,
+        remote: &'r R,
+        balance: NB,
+    ) -> Session<'r, '_, R, NB>
+    where
+        R: RemoteCache,
+        NB: NativeBalance,
+    {
+        match space.context() {
+            Some(context) => self
+                .vm
+                .new_session_with_cache(&context.cache, remote, balance),
+            None => self.vm.new_session(remote, balance),
+        }
+    }
+
+    /// Starts a new epoch: stores the epoch number with the write of `epoch_write`, reloads
+    /// the gas schedule, the gas bounds, the lazy accounts, the coin conservation flag and
+    /// the registered currencies, and emits the `NewEpoch` event.
+    /// Execution limits of the vm are not reloaded.
+    fn start_new_epoch(&self, epoch_write: KeyWrite) {
+        let (key, blob) = epoch_write;
+        self.apply_write(&Space::host(), key, blob);
+        let epoch = self.epochs.next();
+        self.reload_config(&Space::host(), epoch);
+    }
+
+    /// Returns the write of the epoch number started by the transaction changing the configs
+    /// of the space.
+    fn epoch_write(&self, space: &Space) -> KeyWrite {
+        let path = access_path_for_epoch(self.addresses.config_address);
+        let current = match space.context() {
+            Some(context) => context.epoch(),
+            None => self.epochs.current(),
+        };
+        (AccessKey::from(&path), Some((current + 1).encode()))
+    }
+
+    /// Returns the write of the circuit breaker halt flag, the flag is deleted on resume.
+    fn halt_write(&self, halted: bool) -> KeyWrite {
+        let path = access_path_for_halted(self.addresses.config_address);
+        (
+            AccessKey::from(&path),
+            Some(halted.encode()).filter(|_| halted),
+        )
+    }
+
+    /// Starts the next epoch of the space and returns its number.
+    fn next_epoch(&self, space: &Space) -> u64 {
+        match space.context() {
+            Some(context) => context.next_epoch(),
+            None => self.epochs.next(),
+        }
+    }
+
+    /// Reloads the configs of the space on the started epoch and emits the `NewEpoch` event.
+    /// The configs of a tenant don't change the host configs and the bank.
+    fn reload_config(&self, space: &Space, epoch: u64) {
+        let config_address = self.addresses.config_address;
+        if let (Some(tenant), Some(context)) = (space.tenant(), space.context()) {
+            match self.load_cost_table(tenant) {
+                Ok(cost_table) => context.set_cost_table(cost_table),
+                Err(err) => log::error!("Failed to reload vm config of {:?}: {:?}", tenant, err),
+            }
+            self.invalidate_view_cache();
+            self.emit_new_epoch_event(space, epoch);
+            return;
+        }
+        self.initialized.store(
+            has_vm_config_at(&self.state.storage(None), config_address),
+            Ordering::Relaxed,
+        );
+        match load_vm_config_at(&self.state.storage(None), config_address) {
+            Ok(config) => {
+                self.lazy_accounts
+                    .store(config.lazy_accounts, Ordering::Relaxed);
+                self.check_balance_conservation
+                    .store(config.check_balance_conservation, Ordering::Relaxed);
+                *self.gas_bounds.write() = config.gas_bounds();
+                *self.cost_table.write() = Arc::new(config.gas_schedule);
+            }
+            Err(err) => log::error!("Failed to reload vm config: {:?}", err),
+        }
+        match load_registered_currencies_at(&self.state.storage(None), config_address) {
+            Ok(currencies) => self.bank.set_registered_currencies(currencies),
+            Err(err) => log::error!("Failed to reload registered currencies: {:?}", err),
+        }
+        // Cached view results may depend on the previous configs.
+        self.invalidate_view_cache();
+        self.emit_new_epoch_event(space, epoch);
+    }
+
+    /// Emits the `NewEpoch` event of the space under the config address.
+    fn emit_new_epoch_event(&self, space: &Space, epoch: u64) {
+        let tag = TypeTag::Struct(StructTag {
+            address: self.addresses.core_code_address,
+            module: Identifier::new(EPOCH_MODULE).unwrap(),
+            name: Identifier::new(NEW_EPOCH_EVENT).unwrap(),
+            type_params: vec![],
+        });
+        match bcs::to_bytes(&epoch) {
+            Ok(msg) => {
+                let config_address = self.addresses.config_address;
+                if let Err(err) =
+                    self.emit_event(space.tenant(), config_address, tag, msg, None, &[])
+                {
+                    log::warn!("Failed to emit new epoch event: {:?}", err);
+                }
+            }
+            Err(err) => log::warn!("Failed to generate new epoch event: {:?}", err),
+        }
+    }
+
+    /// Returns the host natives together with the natives provided by the vm.
+    fn all_natives(&self) -> NativeRegistry {
+        let mut natives = register_oracle(
+            self.natives.clone(),
+            self.addresses.core_code_address,
+            self.addresses.config_address,
+        );
+        natives = register_nft(natives, self.addresses.core_code_address);
+        natives = register_auth(natives, self.addresses.core_code_address);
+        natives = register_event_handles(natives, self.addresses.core_code_address);
+        natives = register_coin_bridge(natives, self.addresses.core_code_address);
+        natives = register_abort_with_message(natives, self.addresses.core_code_address);
+        if !self.host_handlers.is_empty() {
+            natives = register_host_call(
+                natives,
+                self.addresses.core_code_address,
+                self.host_handlers.clone(),
+            );
+        }
+        if self.message_queue.is_some() {
+            natives = register_bridge_send(natives, self.addresses.core_code_address);
+        }
+        natives
+    }
+
+    fn update_natives(mut self) -> Self {
+        let natives = self.all_natives();
+        self.vm = self.vm.with_natives(natives);
+        self
+    }
+
+    /// Returns the well-known addresses used by the vm.
+    pub fn addresses(&self) -> AddressesConfig {
+        self.addresses
+    }
+
+    /// Returns the prefix of the storage keys.
+    pub fn key_prefix(&self) -> &[u8] {
+        self.state.key_prefix()
+    }
+
+    /// Returns vm metrics.
+    pub fn metrics(&self) -> &M {
+        &self.metrics
+    }
+
+    /// Returns loader cache statistics.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.vm.cache_stats()
+    }
+
+    /// Evicts the modules, the modules depending on them and the scripts using them from the loader
+    /// cache, so the next transactions load them from the storage. Returns `true` if any of
+    /// the modules was cached.
+    ///
+    /// Published modules are invalidated by the vm: a module published by a transaction is
+    /// callable by the next transaction, an upgraded module replaces the cached one.
+    /// Call it after changing the modules in the storage outside of the vm.
+    pub fn invalidate_modules(&self, ids: &[ModuleId]) -> bool {
+        self.invalidate_space_modules(&Space::host(), ids)
+    }
+
+    /// Drops the loader cache of the space and the speculative and view results.
+    fn clear_space(&self, space: &Space) {
+        match space.context() {
+            Some(context) => self.vm.clear_cache(&context.cache),
+            None => self.vm.clear(),
+        }
+        self.invalidate_speculative_cache();
+        self.invalidate_view_cache();
+    }
+
+    /// Evicts the modules and their dependents from the loader cache of the space.
+    fn invalidate_space_modules(&self, space: &Space, ids: &[ModuleId]) -> bool {
+        let evicted = match space.context() {
+            Some(context) => context.cache.evict_modules(ids),
+            None => self.vm.evict_modules(ids),
+        };
+        if evicted {
+            self.invalidate_view_cache();
+        }
+        evicted
+    }
+
+    /// Invalidates the caches after the host changed the storage outside of the vm,
+    /// e.g. by a runtime migration: evicts the changed modules from the loader cache,
+    /// drops the view results which read the changed keys and the speculated transactions.
+    /// A changed config path starts a new epoch, see `EpochManager`.
+    /// Returns the write set of the vm: the epoch number of the started epoch.
+    /// The host commits it with the changes.
+    pub fn on_external_state_change(&self, keys: &[AccessKey]) -> WriteSet {
+        if keys.is_empty() {
+            return WriteSet::default();
+        }
+        let mut write_set = WriteSet::default();
+        if keys
+            .iter()
+            .any(|key| self.epochs.is_config_key(key.as_ref()))
+        {
+            let epoch_write = self.epoch_write(&Space::host());
+            write_set = key_write_set(&[epoch_write.clone()]);
+            self.start_new_epoch(epoch_write);
+        }
+        let modules = keys
+            .iter()
+            .filter_map(AccessKey::module_id)
+            .collect::<Vec<_>>();
+        self.invalidate_modules(&modules);
+        self.invalidate_view_paths(keys);
+        self.invalidate_speculative_cache();
+        write_set
+    }
+
+    /// Publishes the embedded standard library under the core code address.
+    #[cfg(feature = "embedded-stdlib")]
+    pub fn publish_embedded_stdlib(&self, gas: Gas) -> VmResult {
+        self.publish_module_package(
+            gas,
+            crate::stdlib::stdlib_package().into_tx(self.addresses.core_code_address),
+            false,
+        )
+    }
+
+    /// Executes the script approved by the `GovernanceOrigin` set with `with_governance_origin`.
+    /// The config address is passed as the first signer before the transaction senders.
+    /// Fails with `NO_ACCOUNT_ROLE` if the script is not approved.
+    /// Runs on the vm halted by the circuit breaker and resumes it if the script is executed,
+    /// the halt flag is deleted by the write set of the script.
+    pub fn execute_governance_script(
+        &self,
+        gas: Gas,
+        context: ExecutionContext,
+        tx: ScriptTx,
+        dry_run: bool,
+    ) -> VmResult {
+        self.execute_script_tx(gas, context, tx, dry_run, true, None)
+    }
+
+    /// Publishes the module approved by the `GovernanceOrigin` set with `with_governance_origin`.
+    /// Replaces the published module regardless of the compatibility, the immutability and
+    /// the publisher policy and emits `ForcedUpgradeEvent`.
+    /// Fails with `NO_ACCOUNT_ROLE` if the publish is not approved.
+    pub fn force_publish_module(&self, gas: Gas, module: ModuleTx, dry_run: bool) -> VmResult {
+        self.metrics.on_tx_start(TxKind::PublishModule);
+        let approval = match &self.governance {
+            Some(origin) => origin
+                .ensure_force_publish(&module)
+                .map_err(|reason| VmResult::new(StatusCode::NO_ACCOUNT_ROLE, Some(reason), 0)),
+            None => Err(VmResult::new(StatusCode::NO_ACCOUNT_ROLE, None, 0)),
+        };
+        let source_map = module.source_map().map(<[u8]>::to_vec);
+        let tenant = module.tenant();
+        let (module, sender) = module.into_inner();
+        let span = tx_span!(
+            "force_publish_module",
+            tx_hash = %hex::encode(module_hash(&module)),
+            sender = %sender,
+            max_gas = gas.max_gas_amount()
+        );
+        let space = match approval
+            .and_then(|_| self.check_gas(&gas))
+            .and_then(|_| self.space(tenant))
+        {
+            Ok(space) => space,
+            Err(result) => {
+                span.finish(&result);
+                self.metrics.on_tx_end(TxKind::PublishModule, &result);
+                return result;
+            }
+        };
+
+        let cost_table = self.cost_table(&space);
+        let mut cost_strategy =
+            CostStrategy::transaction(&cost_table, GasUnits::new(gas.max_gas_amount()));
+        let state_space = self.state.space(tenant);
+        let state = MeteredCache::new(&state_space, &self.metrics);
+        let mut session = self.new_session(&space, &state, &self.bank);
+
+        let mut old_hash = None;
+        let result = CompiledModule::deserialize(&module)
+            .map_err(|err| err.finish(Location::Undefined))
+            .and_then(|compiled| {
+                let id = compiled.self_id();
+                // The previous version is read outside of the session, so the read is charged
+                // as a cold access.
+                cost_strategy
+                    .deduct_gas(GasUnits::new(self.vm.access_costs().cold))
+                    .map_err(|err| err.finish(Location::Undefined))?;
+                old_hash = state_space.get_module(&id)?.map(|code| module_hash(&code));
+                cost_strategy.charge_intrinsic_gas(AbstractMemorySize::new(module.len() as u64))?;
+                session.publish_module_with_upgrade(
+                    module.clone(),
+                    sender,
+                    ModuleUpgrade::Arbitrary,
+                    &mut cost_strategy,
+                    &NoContextLog::new(),
+                )?;
+                Self::charge_global_write_gas_usage(&mut cost_strategy, &mut session, &sender)?;
+                Ok(id)
+            })
+            .and_then(|id| session.finish().map(|effects| (id, effects)));
+        // The forced upgrade event is emitted with the effects, so the publish fails
+        // if the event can't be emitted.
+        let (result, host_writes) = match result.and_then(|(id, effects)| {
+            let event = ForcedUpgradeEvent {
+                id,
+                old_hash,
+                new_hash: module_hash(&module),
+            };
+            let msg = bcs::to_bytes(&event).map_err(|_| {
+                PartialVMError::new(StatusCode::VALUE_SERIALIZATION_ERROR)
+                    .finish(Location::Undefined)
+            })?;
+            let tag = forced_upgrade_tag(self.addresses.core_code_address);
+            Ok((effects, (sender, tag, msg)))
+        }) {
+            Ok((effects, event)) => (Ok(effects), HostWrites::default().with_event(event)),
+            Err(err) => (Err(err), HostWrites::default()),
+        };
+
+        let mut result = self.handle_vm_result(
+            &space,
+            sender,
+            cost_strategy,
+            gas,
+            result,
+            None,
+            host_writes,
+            None,
+            dry_run,
+        );
+        if result.status_code == StatusCode::EXECUTED {
+            if !dry_run {
+                self.record_source_map(&module, source_map);
+            }
+        } else {
+            self.resolve_published_source(&mut result, &module, source_map.as_deref());
+        }
+        span.finish(&result);
+        self.metrics.on_tx_end(TxKind::PublishModule, &result);
+        result
+    }
+
+    /// Delivers the inbound message to the Move handler set with `with_message_handler`.
+    /// Fails with `FUNCTION_RESOLUTION_FAILURE` if no handler is set.
+    pub fn deliver_message(
+        &self,
+        gas: Gas,
+        context: ExecutionContext,
+        message: Message,
+        dry_run: bool,
+    ) -> VmResult {
+        self.metrics.on_tx_start(TxKind::Message);
+        if let Some(handler) = &self.message_handler {
+            if let Err(result) = self.check_halted(&[*handler.0.address()]) {
+                self.metrics.on_tx_end(TxKind::Message, &result);
+                return result;
+            }
+        }
+        let space = match self
+            .check_gas(&gas)
+            .and_then(|_| self.space(context.tenant))
+        {
+            Ok(space) => space,
+            Err(result) => {
+                self.metrics.on_tx_end(TxKind::Message, &result);
+                return result;
+            }
+        };
+        let (module, function) = match &self.message_handler {
+            Some(handler) => handler,
+            None => {
+                let result = VmResult::new(StatusCode::FUNCTION_RESOLUTION_FAILURE, None, 0);
+                self.metrics.on_tx_end(TxKind::Message, &result);
+                return result;
+            }
+        };
+        let sender = *module.address();
+
+        let bank = SessionBank::new(&self.bank, context.host_policy());
+        let state_space = self.state.space(space.tenant());
+        let state_session = StateSession::new(&state_space, context)
+            .with_core_address(self.addresses.core_code_address)
+            .with_config_address(self.addresses.config_address)
+            .with_oracle_address(self.addresses.oracle_address);
+        let state = MeteredCache::new(&state_session, &self.metrics);
+        let mut session = self.new_session(&space, &state, &bank);
+        let cost_table = self.cost_table(&space);
+        let mut cost_strategy = self.script_cost_strategy(&cost_table, gas.max_gas_amount());
+
+        let result = session
+            .execute_entry_function(
+                module,
+                function.as_ident_str(),
+                vec![],
+                vec![
+                    Value::vector_u8(message.peer),
+                    Value::vector_u8(message.payload),
+                ],
+                vec![],
+                &mut cost_strategy,
+                &NoContextLog::new(),
+            )
+            .and_then(|_| {
+                Self::charge_global_write_gas_usage(&mut cost_strategy, &mut session, &sender)
+            });
+
+        let abort_message = AbortMessage::take(session.extensions());
+        let mut result = self.handle_vm_result(
+            &space,
+            sender,
+            cost_strategy,
+            gas,
+            bank.check_denied()
+                .and(result)
+                .and_then(|_| session.finish())
+                .and_then(|effects| bank.check_access(&effects).map(|_| effects)),
+            abort_message,
+            HostWrites::default(),
+            None,
+            dry_run,
+        );
+        result.price_reads = state_session.take_price_reads();
+        self.metrics.on_tx_end(TxKind::Message, &result);
+        result
+    }
+
+    /// Publishes the `Account` resources for a fresh address by calling
+    /// `Account::create_account` of the standard library in a system session.
+    /// No gas is charged. Fails with `RESOURCE_ALREADY_EXISTS` if the account exists.
+    pub fn create_account(&self, context: ExecutionContext, address: AccountAddress) -> VmResult {
+        self.metrics.on_tx_start(TxKind::CreateAccount);
+        let space = match self
+            .check_halted(&[address])
+            .and_then(|_| self.space(context.tenant))
+        {
+            Ok(space) => space,
+            Err(result) => {
+                self.metrics.on_tx_end(TxKind::CreateAccount, &result);
+                return result;
+            }
+        };
+
+        let bank = SessionBank::new(&self.bank, context.host_policy());
+        let state_space = self.state.space(space.tenant());
+        let state_session = StateSession::new(&state_space, context)
+            .with_core_address(self.addresses.core_code_address)
+            .with_config_address(self.addresses.config_address)
+            .with_oracle_address(self.addresses.oracle_address);
+        let state = MeteredCache::new(&state_session, &self.metrics);
+        let mut session = self.new_session(&space, &state, &bank);
+        let cost_table = self.cost_table(&space);
+        let mut cost_strategy = CostStrategy::system(&cost_table, GasUnits::new(0));
+        let function = Identifier::new(CREATE_ACCOUNT).unwrap();
+
+        let result = session.execute_function(
+            &account_module(self.addresses.core_code_address),
+            function.as_ident_str(),
+            vec![],
+            vec![Value::address(address)],
+            address,
+            &mut cost_strategy,
+            &NoContextLog::new(),
+        );
+        let abort_message = AbortMessage::take(session.extensions());
+        let result = bank
+            .check_denied()
+            .and(result)
+            .and_then(|_| session.finish())
+            .and_then(|effects| bank.check_access(&effects).map(|_| effects));
+
+        let result = self.handle_vm_result(
+            &space,
+            address,
+            cost_strategy,
+            Gas::new(0, 0).expect("Valid gas"),
+            result,
+            abort_message,
+            HostWrites::default(),
+            None,
+            false,
+        );
+        self.metrics.on_tx_end(TxKind::CreateAccount, &result);
+        result
+    }
+
+    /// Returns the native balance of the currency together with the coins deposited to the vm.
+    pub fn balance(
+        &self,
+        address: &AccountAddress,
+        ticker: &Ticker,
+    ) -> Result<BalanceBreakdown, Error> {
+        let core_address = self.addresses.core_code_address;
+        let native = self.bank.native_balance(address, ticker).unwrap_or(0);
+        let wrapped = match coin_tag(core_address, ticker) {
+            Some(coin) => self.coin_balance(address, coin)?,
+            None => 0,
+        };
+        Ok(BalanceBreakdown { native, wrapped })
+    }
+
+    /// Returns the balance of the token with the `marker`, see `tokens`.
+    pub fn token_balance(
+        &self,
+        address: &AccountAddress,
+        marker: StructTag,
+    ) -> Result<u128, Error> {
+        self.coin_balance(address, token_tag(self.addresses.core_code_address, marker))
+    }
+
+    /// Returns the coins held in the `Account::Balance<coin>` resource.
+    fn coin_balance(&self, address: &AccountAddress, coin: StructTag) -> Result<u128, Error> {
+        let tag = balance_tag(self.addresses.core_code_address, coin);
+        match self
+            .state
+            .get_resource(address, &tag)
+            .map_err(|err| Error::msg(format!("{:?}", err)))?
+        {
+            Some(blob) => {
+                decode_balance(&blob).ok_or_else(|| Error::msg("Failed to decode Account::Balance"))
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Returns the tokens owned by the `address` in the order they were received.
+    pub fn nft_of(&self, address: &AccountAddress) -> Result<Vec<Nft>, Error> {
+        let core_address = self.addresses.core_code_address;
+        let storage = &self.state.storage(None);
+        load_token_store(storage, core_address, address)
+            .and_then(|store| {
+                store
+                    .ids
+                    .iter()
+                    .filter_map(|id| load_nft(storage, core_address, id).transpose())
+                    .collect()
+            })
+            .map_err(|err| Error::msg(format!("{:?}", err)))
+    }
+
+    /// Returns the version of the module or 0 if it was never published.
+    pub fn module_version(&self, id: &ModuleId) -> Result<u64, Error> {
+        load_module_versions(
+            &self.state.storage(None),
+            self.addresses.core_code_address,
+            id.address(),
+        )
+        .map(|versions| versions.version(id.name()))
+    }
+
+    /// Returns `true` if the module was published as immutable.
+    pub fn is_module_immutable(&self, id: &ModuleId) -> Result<bool, Error> {
+        load_immutable_modules(
+            &self.state.storage(None),
+            self.addresses.core_code_address,
+            id.address(),
+        )
+        .map(|immutable| immutable.contains(id.name()))
+    }
+
+    /// Returns the next sequence number of the lane of the account.
+    pub fn sequence_number(&self, address: &AccountAddress, lane: u64) -> Result<u64, Error> {
+        load_sequence_numbers(
+            &self.state.storage(None),
+            self.addresses.core_code_address,
+            address,
+        )
+        .map(|lanes| lanes.sequence_number(lane))
+    }
+
+    /// Executes the script without applying the effects, e.g. on the mempool admission.
+    /// With the speculative cache enabled the effects are kept under the signing message
+    /// of the transaction and the state root for `execute_speculated`.
+    /// The script must be simulated with the execution context of the block it lands in.
+    pub fn simulate_script(
+        &self,
+        gas: Gas,
+        context: ExecutionContext,
+        tx: ScriptTx,
+        state_root: Digest,
+    ) -> VmResult {
+        let state_root = self.speculative_cache.as_ref().map(|_| state_root);
+        self.execute_script_tx(gas, context, tx, true, false, state_root)
+    }
+
+    /// Applies the effects of the same transaction simulated on the state root with the same
+    /// execution context and gas limit if the oracle and the bank return the values read by
+    /// the simulation. Otherwise executes the script.
+    /// Other results simulated on the state root are dropped.
+    pub fn execute_speculated(
+        &self,
+        gas: Gas,
+        context: ExecutionContext,
+        tx: ScriptTx,
+        state_root: Digest,
+    ) -> VmResult {
+        let cache = match &self.speculative_cache {
+            Some(cache) => cache,
+            None => return self.execute_script(gas, context, tx, false),
+        };
+        let speculation = cache
+            .take(&(tx.signing_message(), state_root))
+            .filter(|speculation| {
+                speculation.matches(&context, gas.max_gas_amount())
+                    && self.host_reads_match(&speculation.reads)
+            });
+        cache.invalidate_root(&state_root);
+        let speculation = match speculation {
+            Some(speculation) => speculation,
+            None => return self.execute_script(gas, context, tx, false),
+        };
+
+        self.metrics.on_tx_start(TxKind::Script);
+        let fee_payer = tx.fee_payer().cloned();
+        let (space, lanes) = match self.space(context.tenant).and_then(|space| {
+            self.check_halted(tx.senders())
+                .and_then(|_| self.check_chain_id(tx.chain_id()))
+                .and_then(|_| self.check_gas(&gas))
+                .and_then(|_| self.check_tx_limits(&tx))
+                .and_then(|_| Self::check_validity_window(&tx, &context))
+                .and_then(|_| self.authenticate(context.tenant, &tx))
+                .and_then(|_| self.check_fee_payer(fee_payer.as_ref(), &gas))
+                .and_then(|_| {
+                    self.check_sequence_numbers(&space, Some((tx.senders(), tx.sequence_number())))
+                })
+                .map(|lanes| (space, lanes))
+        }) {
+            Ok(checked) => checked,
+            Err(result) => {
+                self.metrics.on_tx_end(TxKind::Script, &result);
+                return result;
+            }
+        };
+        let mut result = self.apply_vm_result(
+            &space,
+            speculation.sender,
+            speculation.gas_used,
+            speculation.result,
+            HostWrites::kept(lanes).with_fee(fee_payer.as_ref(), &gas),
+            speculation.abort_message,
+        );
+        result.price_reads = speculation.price_reads;
+        self.metrics.on_tx_end(TxKind::Script, &result);
+        result
+    }
+
+    /// Returns `true` if the oracle and the bank return the values read by the simulation.
+    fn host_reads_match(&self, reads: &HostReads) -> bool {
+        let core_address = self.addresses.core_code_address;
+        let oracle = reads.oracle.iter().all(|read| match read {
+            OracleRead::Price(tag, price) => {
+                self.state.get_resource(&core_address, tag).ok().as_ref() == Some(price)
+            }
+            OracleRead::Twap(tag, window, twap) => {
+                self.state.get_twap(tag, *window).ok() == Some(*twap)
+            }
+        });
+        oracle
+            && reads.balances.iter().all(|(wallet_id, balance)| {
+                NativeBalance::get_balance(&&self.bank, wallet_id) == *balance
+            })
+    }
+
+    /// Returns the gas meter of a new block limited by `VmConfig::max_block_gas`.
+    pub fn block_gas_meter(&self) -> Result<BlockGasMeter, Error> {
+        let config = load_vm_config_at(&self.state.storage(None), self.addresses.config_address)?;
+        Ok(BlockGasMeter::new(config.max_block_gas))
+    }
+
+    /// Returns bounds of the transaction gas of the current epoch, see `Gas::builder`.
+    /// Transactions whose gas is out of the bounds are not executed.
+    pub fn gas_bounds(&self) -> GasBounds {
+        *self.gas_bounds.read()
+    }
+
+    /// Executes the scripts of a block in order, charging the used gas to the block meter.
+    /// A script whose max gas amount exceeds the remaining gas of the block is not executed
+    /// and fails with `MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND`.
+    pub fn execute_block(
+        &self,
+        meter: &mut BlockGasMeter,
+        context: ExecutionContext,
+        txs: Vec<(Gas, ScriptTx)>,
+    ) -> Vec<VmResult> {
+        txs.into_iter()
+            .map(|(gas, tx)| {
+                if let Err(result) = meter.admit(&gas) {
+                    return result;
+                }
+                let result = self.execute_script(gas, context.clone(), tx, false);
+                meter.charge(&result);
+                result
+            })
+            .collect()
+    }
+
+    /// Drops all the speculative results, e.g. on a chain reorganization.
+    pub fn invalidate_speculative_cache(&self) {
+        if let Some(cache) = &self.speculative_cache {
+            cache.clear();
+        }
+    }
+
+    /// Returns the number of the speculative results.
+    pub fn speculative_cache_len(&self) -> usize {
+        self.speculative_cache
+            .as_ref()
+            .map(|cache| cache.len())
+            .unwrap_or(0)
+    }
+
+    /// Executes the public function without applying its effects
+    /// and returns its BCS serialized return values.
+    /// With the view cache enabled the results are cached under the call and the
+    /// `state_version`, which must change whenever the state changes outside of this vm.
+    pub fn view_function(
+        &self,
+        gas: Gas,
+        context: ExecutionContext,
+        call: &ViewCall,
+        state_version: u64,
+    ) -> Result<Vec<Vec<u8>>, VMStatus> {
+        let space = self
+            .space(context.tenant)
+            .map_err(|result| VMStatus::Error(result.status_code))?;
+        let timestamp = context.timestamp;
+        let cached = self.view_cache.as_ref().and_then(|cache| {
+            Some((
+                cache,
+                call.key(context.tenant, context.host_policy(), state_version)?,
+            ))
+        });
+        if let Some((cache, key)) = &cached {
+            if let Some(values) = cache.get(key, timestamp) {
+                return Ok(values);
+            }
+        }
+
+        let bank = SessionBank::new(&self.bank, context.host_policy());
+        let state_space = self.state.space(space.tenant());
+        let state_session = StateSession::new(&state_space, context)
+            .with_core_address(self.addresses.core_code_address)
+            .with_config_address(self.addresses.config_address)
+            .with_oracle_address(self.addresses.oracle_address);
+        let recorder = ReadRecorder::new(&state_session, cached.is_some());
+        let cost_table = self.cost_table(&space);
+        let mut cost_strategy = self.script_cost_strategy(&cost_table, gas.max_gas_amount());
+        let values = {
+            let mut session = self.new_session(&space, &recorder, &bank);
+            let values = session.execute_view_function(
+                &call.module,
+                call.function.as_ident_str(),
+                call.type_args.clone(),
+                call.args.iter().cloned().map(Value::from).collect(),
+                &mut cost_strategy,
+                &NoContextLog::new(),
+            );
+            bank.check_denied()
+                .and(values)
+                .map_err(|err| err.into_vm_status())?
+        };
+
+        if let Some((cache, key)) = cached {
+            let config_address = self.addresses.config_address;
+            // Average prices missing in the oracle are computed from the price histories.
+            let histories = state_session
+                .take_oracle_reads()
+                .into_iter()
+                .filter_map(|read| match read {
+                    OracleRead::Twap(tag, _, _) => Some(AccessKey::from((
+                        &config_address,
+                        &history_tag(config_address, tag.type_params),
+                    ))),
+                    OracleRead::Price(..) => None,
+                });
+            let reads = recorder
+                .into_reads()
+                .into_iter()
+                .map(|read| AccessKey::from((&read.address, &read.tag)))
+                .chain(histories)
+                .map(|key| ViewRead::Resource(key.as_ref().to_vec()))
+                .chain(
+                    bank.take_balance_reads()
+                        .into_iter()
+                        .map(|(wallet_id, _)| ViewRead::Balance(wallet_id.address)),
+                )
+                .collect();
+            cache.insert(key, values.clone(), reads, timestamp);
+        }
+        Ok(values)
+    }
+
+    /// Drops the cached view results which read the resources stored under the keys,
+    /// e.g. when the host changes the storage outside of the vm.
+    pub fn invalidate_view_paths(&self, keys: &[AccessKey]) {
+        if let Some(cache) = &self.view_cache {
+            for key in keys {
+                cache.invalidate_key(key.as_ref());
+            }
+        }
+    }
+
+    /// Drops the cached view results which read a native balance of the accounts,
+    /// e.g. when the host changes the balances outside of the vm.
+    pub fn invalidate_view_balances(&self, addresses: &[AccountAddress]) {
+        if let Some(cache) = &self.view_cache {
+            for address in addresses {
+                cache.invalidate_balances(address);
+            }
+        }
+    }
+
+    /// Drops all the cached view results.
+    pub fn invalidate_view_cache(&self) {
+        if let Some(cache) = &self.view_cache {
+            cache.clear();
+        }
+    }
+
+    /// Returns the number of the cached view results.
+    pub fn view_cache_len(&self) -> usize {
+        self.view_cache
+            .as_ref()
+            .map(|cache| cache.len())
+            .unwrap_or(0)
+    }
+
+    /// Returns up to `limit` resources of the account following the `cursor`,
+    /// or the first page if the `cursor` is `None`.
+    /// Pages are ordered by the storage key, so they stay stable while the account changes.
+    /// Fails if the storage does not support `Storage::next_key`.
+    pub fn resources_page(
+        &self,
+        address: &AccountAddress,
+        cursor: Option<&StructTag>,
+        limit: usize,
+    ) -> Result<ResourcesPage, Error> {
+        let storage = self.state.storage(None);
+        if !storage.supports_next_key() {
+            return Err(Error::msg(
+                "Resource pages require a storage supporting key iteration \
+                 and a key codec preserving the key order",
+            ));
+        }
+        let mut prefix = address.to_vec();
+        prefix.push(RESOURCE_TAG);
+        let next_key = |key: &[u8]| {
+            storage
+                .next_key(key)
+                .filter(|next| next.starts_with(&prefix))
+        };
+
+        let mut key = match cursor {
+            Some(tag) => AccessKey::from((address, tag)).as_ref().to_vec(),
+            None => prefix.clone(),
+        };
+        let mut resources = Vec::with_capacity(limit);
+        while resources.len() < limit {
+            key = match next_key(&key) {
+                Some(next) => next,
+                None => break,
+            };
+            let tag = bcs::from_bytes::<StructTag>(&key[prefix.len()..]).map_err(Error::msg)?;
+            if let Some(blob) = storage.get(&key) {
+                resources.push((tag, blob));
+            }
+        }
+
+        let cursor = if resources.len() == limit && next_key(&key).is_some() {
+            resources.last().map(|(tag, _)| tag.clone())
+        } else {
+            None
+        };
+        Ok(ResourcesPage { resources, cursor })
+    }
+
+    /// Returns `true` if the `Account` resource is published under the address.
+    pub fn account_exists(&self, address: &AccountAddress) -> VMResult<bool> {
+        self.state
+            .get_resource(address, &account_tag(self.addresses.core_code_address))
+            .map(|resource| resource.is_some())
+            .map_err(|err| err.finish(Location::Undefined))
+    }
+
+    /// Stores write set into storage and handle events.
+    /// Returns applied balance changes and the canonical write set.
+    ///
+    /// The effects are committed in two phases. The first one has no side effects: it checks
+    /// the balance changes against the native balances, computes the write set, including the
+    /// host writes and the bumped module versions, and all the events, and reserves the room
+    /// for the events in the event handler. Only this phase can discard the transaction.
+    ///
+    /// The second one applies the effects in the same order on every node: the storage writes
+    /// (resources ordered by the address and the struct tag followed by the locked coins ordered
+    /// by the currency, modules ordered by the module id, token writes ordered by the key, event
+    /// handle counters ordered by the address), then the balance changes ordered by the wallet
+    /// id, then the events in the emission order. A host failure in this phase is reported, the
+    /// transaction stays executed.
+    fn handle_tx_effects(
+        &self,
+        space: &Space,
+        tx_effects: SerializedEffects,
+        host_writes: Vec<KeyWrite>,
+        host_events: Vec<HostEvent>,
+        fee: Option<&BalanceChange>,
+    ) -> Result<(Vec<BalanceChange>, WriteSet), VMError> {
+        enter_span!(
+            DEBUG,
+            "handle_tx_effects",
+            resources = tx_effects.resources.len(),
+            modules = tx_effects.modules.len(),
+            events = tx_effects.events.len()
+        );
+        for change in &tx_effects.balance_changes {
+            self.bank.check_registered(&change.wallet_id)?;
+        }
+        if self.check_balance_conservation.load(Ordering::Relaxed) {
+            check_conservation(
+                &tx_effects.balance_changes,
+                &tx_effects.coin_flows,
+                &tx_effects.minted,
+            )?;
+        }
+        self.bank.check_changes(&tx_effects.balance_changes)?;
+        if fee.is_some() {
+            // The max fee is reserved during the execution, so the fee is expected to fit.
+            self.bank
+                .check_changes(tx_effects.balance_changes.iter().chain(fee))
+                .map_err(|_| {
+                    PartialVMError::new(StatusCode::OUT_OF_GAS)
+                        .with_message("Fee payer can't pay the fee after the effects".to_owned())
+                        .finish(Location::Undefined)
+                })?;
+        }
+        let core_address = self.addresses.core_code_address;
+        for (id, blob) in &tx_effects.modules {
+            if id.address() == &core_address && id.name().as_str() == COIN_BRIDGE_MODULE {
+                let module = CompiledModule::deserialize(blob)
+                    .map_err(|err| err.finish(Location::Undefined))?;
+                check_bridge_module(&module)?;
+            }
+        }
+        let storage = self.state.storage(space.tenant());
+        let (version_writes, published) = self.publish_writes(&storage, &tx_effects.modules)?;
+        let mut nft_writes = NftWrites::new(&storage, core_address);
+        nft_writes.apply(tx_effects.nft_ops)?;
+        let reconfiguration = tx_effects
+            .resources
+            .iter()
+            .any(|(ak, _)| self.epochs.is_config_key(ak.as_ref()))
+            || host_writes
+                .iter()
+                .any(|(key, _)| self.epochs.is_config_key(key.as_ref()));
+        let mut native_writes = nft_writes.into_writes();
+        native_writes.extend(version_writes);
+        native_writes.extend(host_writes);
+        native_writes.extend(
+            counter_writes(core_address, tx_effects.event_counters)
+                .into_iter()
+                .map(|(key, blob)| (key, Some(blob))),
+        );
+        if reconfiguration {
+            native_writes.push(self.epoch_write(space));
+        }
+        let mut writes = Vec::with_capacity(
+            tx_effects.resources.len() + tx_effects.modules.len() + native_writes.len(),
+        );
+        for (ak, range) in &tx_effects.resources {
+            let op = match range {
+                Some(range) => WriteOp::Value(tx_effects.buffer[range.clone()].to_vec()),
+                None => WriteOp::Deletion,
+            };
+            writes.push((ak.as_ref().to_vec(), op));
+        }
+        for (module_id, blob) in &tx_effects.modules {
+            let key = AccessKey::from(module_id);
+            writes.push((key.as_ref().to_vec(), WriteOp::Value(blob.to_vec())));
+        }
+        for (key, blob) in &native_writes {
+            let op = match blob {
+                Some(blob) => WriteOp::Value(blob.clone()),
+                None => WriteOp::Deletion,
+            };
+            writes.push((key.as_ref().to_vec(), op));
+        }
+        let write_set = WriteSet::new(writes);
+
+        // The events, including the deletion events of the full write set, are computed
+        // before any of them is emitted.
+        let published_tag = module_published_tag(core_address);
+        let mut events =
+            Vec::with_capacity(published.len() + host_events.len() + tx_effects.events.len());
+        for (address, msg) in published {
+            events.push((address, published_tag.clone(), msg, None, vec![]));
+        }
+        for (address, ty_tag, msg) in host_events {
+            events.push((address, ty_tag, msg, None, vec![]));
+        }
+        for (address, ty_tag, ty_layout, range, caller) in tx_effects.events {
+            let msg = tx_effects.buffer[range].to_vec();
+            let indexed_values =
+                self.event_indexed_values(space, &ty_tag, &ty_layout, &msg, &tx_effects.modules);
+            events.push((address, ty_tag, msg, caller, indexed_values));
+        }
+        if self.deletion_events {
+            for (address, ty_tag, msg) in self.deletion_events(&write_set)? {
+                events.push((address, ty_tag, msg, None, vec![]));
+            }
+        }
+
+        // The last check of the first phase: the reserved room is released once the events
+        // are emitted.
+        let count = events.len();
+        if !guard("EventHandler", || self.event_handler.can_accept(count))
+            .map_err(|err| err.finish(Location::Undefined))?
+        {
+            return Err(PartialVMError::new(StatusCode::EVENT_CHANNEL_FULL)
+                .with_message(format!("Event handler can't accept {} events", count))
+                .finish(Location::Undefined));
+        }
+
+        let tenant = space.tenant();
+        let state = self.state.space(tenant);
+        for (ak, range) in tx_effects.resources {
+            if let Some(cache) = &self.view_cache {
+                cache.invalidate_key(ak.as_ref());
+            }
+            match range {
+                None => state.delete(ak),
+                Some(range) => state.insert(ak, &tx_effects.buffer[range]),
+            }
+        }
+
+        let mut ids = Vec::with_capacity(tx_effects.modules.len());
+        for (module_id, blob) in tx_effects.modules {
+            state.insert(AccessKey::from(&module_id), self.compression.compress(blob));
+            ids.push(module_id);
+        }
+        self.invalidate_space_modules(space, &ids);
+
+        for (key, blob) in native_writes {
+            self.apply_write(space, key, blob);
+        }
+
+        let balance_changes = tx_effects.balance_changes;
+        for change in &balance_changes {
+            if let Err(err) = self.apply_balance_change(change) {
+                self.on_apply_failure("balance change", err);
+            }
+        }
+        if let Some(fee) = fee {
+            if let Err(err) = self.apply_balance_change(fee) {
+                self.on_apply_failure("fee", err);
+            }
+        }
+
+        for (address, ty_tag, msg, caller, indexed_values) in events {
+            if let Err(err) = self.emit_event(tenant, address, ty_tag, msg, caller, &indexed_values)
+            {
+                self.on_apply_failure("event", err);
+            }
+        }
+        if let Err(err) = guard("EventHandler", || self.event_handler.release(count)) {
+            self.on_apply_failure("event", err.finish(Location::Undefined));
+        }
+
+        // The epoch number is stored with the native writes.
+        if reconfiguration {
+            self.reload_config(space, self.next_epoch(space));
+        }
+
+        Ok((balance_changes, write_set))
+    }
+
+    /// Reports the host failure to apply an effect of the executed transaction.
+    /// The transaction is not discarded: the rest of its effects are already applied.
+    fn on_apply_failure(&self, effect: &str, err: VMError) {
+        log::error!(
+            "Failed to apply the {} of the executed transaction: {:?}",
+            effect,
+            err
+        );
+        self.metrics
+            .on_invariant_violation(&self.failed_result(&err, 0, None));
+    }
+
+    /// Applies the checked balance change to the native balance.
+    fn apply_balance_change(&self, change: &BalanceChange) -> VMResult<()> {
+        if let Some(cache) = &self.view_cache {
+            cache.invalidate_balances(&change.wallet_id.address);
+        }
+        match change.operation {
+            BalanceOperation::Deposit(amount) => self.bank.deposit(&change.wallet_id, amount),
+            BalanceOperation::Withdraw(amount) => self.bank.withdraw(&change.wallet_id, amount),
+        }
+    }
+
+    /// Applies the host write to the storage space.
+    fn apply_write(&self, space: &Space, key: AccessKey, blob: Option<Vec<u8>>) {
+        if let Some(cache) = &self.view_cache {
+            cache.invalidate_key(key.as_ref());
+        }
+        let state = self.state.space(space.tenant());
+        match blob {
+            Some(blob) => state.insert(key, &blob),
+            None => state.delete(key),
+        }
+    }
+
+    /// Returns the `ResourceDeleted` events of the keys deleted by the write set.
+    fn deletion_events(
+        &self,
+        write_set: &WriteSet,
+    ) -> VMResult<Vec<(AccountAddress, TypeTag, Vec<u8>)>> {
+        let tag = TypeTag::Struct(StructTag {
+            address: self.addresses.core_code_address,
+            module: Identifier::new(STORAGE_MODULE).unwrap(),
+            name: Identifier::new(RESOURCE_DELETED_EVENT).unwrap(),
+            type_params: vec![],
+        });
+        write_set
+            .deletions()
+            .filter_map(ResourceDeleted::from_key)
+            .map(|(address, event)| {
+                let msg = bcs::to_bytes(&event).map_err(|_| {
+                    PartialVMError::new(StatusCode::VALUE_SERIALIZATION_ERROR)
+                        .finish(Location::Undefined)
+                })?;
+                Ok((address, tag.clone(), msg))
+            })
+            .collect()
+    }
+
+    /// Handle vm result and return transaction status code.
+    /// `abort_message` is the message of the abort raised by the session, see `AbortMessage`.
+    #[allow(clippy::too_many_arguments)]
+    fn handle_vm_result(
+        &self,
+        space: &Space,
+        sender: AccountAddress,
+        cost_strategy: CostStrategy,
+        gas_meta: Gas,
+        result: Result<TransactionEffects, VMError>,
+        abort_message: Option<String>,
+        host_writes: HostWrites,
+        simulation: Option<Simulation>,
+        dry_run: bool,
+    ) -> VmResult {
+        let gas_used = GasUnits::new(gas_meta.max_gas_amount)
+            .sub(cost_strategy.remaining_gas())
+            .get();
+
+        if dry_run {
+            let status = match &result {
+                Ok(_) => VmResult::new(StatusCode::EXECUTED, None, gas_used),
+                Err(err) => self.failed_result(err, gas_used, abort_message.clone()),
+            };
+            if let (Some(simulation), Some(cache)) = (simulation, &self.speculative_cache) {
+                let speculation = Speculation {
+                    context: simulation.context,
+                    max_gas_amount: gas_meta.max_gas_amount,
+                    sender,
+                    gas_used,
+                    result: result.and_then(|effects| self.serialize_effects(effects)),
+                    abort_message,
+                    price_reads: simulation.price_reads,
+                    reads: simulation.reads,
+                };
+                cache.insert(simulation.key, speculation);
+            }
+            return status;
+        }
+
+        self.apply_vm_result(
+            space,
+            sender,
+            gas_used,
+            result.and_then(|effects| self.serialize_effects(effects)),
+            host_writes,
+            abort_message,
+        )
+    }
+
+    /// Serializes the effects reporting the allocations to the metrics.
+    fn serialize_effects(&self, effects: TransactionEffects) -> Result<SerializedEffects, VMError> {
+        let effects = SerializedEffects::new(effects, self.addresses.core_code_address)?;
+        self.metrics.on_effects_serialized(&effects.stats);
+        Ok(effects)
+    }
+
+    /// Applies the effects of the executed transaction.
+    /// The kept host writes and the fee are applied even if the transaction fails,
+    /// unless it is discarded. The fee is charged with the effects in the same batch:
+    /// if the payer can't pay it after the effects, the transaction is kept as `OUT_OF_GAS`
+    /// without them, if it can't pay it at all the transaction is discarded.
+    #[allow(clippy::too_many_arguments)]
+    fn apply_vm_result(
+        &self,
+        space: &Space,
+        sender: AccountAddress,
+        gas_used: u64,
+        result: Result<SerializedEffects, VMError>,
+        host_writes: HostWrites,
+        abort_message: Option<String>,
+    ) -> VmResult {
+        let HostWrites {
+            executed,
+            kept,
+            events,
+            fee,
+        } = host_writes;
+        let fee = fee.and_then(|(fee_payer, gas_unit_price, gas_currency)| {
+            self.bank.fee_change(
+                &fee_payer,
+                &gas_currency,
+                gas_used as u128 * gas_unit_price as u128,
+            )
+        });
+        let writes = executed.into_iter().chain(kept.iter().cloned()).collect();
+        match result.and_then(|mut e| {
+            let messages = core::mem::take(&mut e.messages);
+            self.handle_tx_effects(space, e, writes, events, fee.as_ref())
+                .map(|(balance_changes, write_set)| (balance_changes, write_set, messages))
+        }) {
+            Ok((balance_changes, write_set, messages)) => {
+                if let Some(queue) = &self.message_queue {
+                    messages
+                        .into_iter()
+                        .for_each(|message| queue.enqueue(message));
+                }
+                let mut result = VmResult::new(StatusCode::EXECUTED, None, gas_used);
+                result.balance_changes = balance_changes;
+                result.balance_changes.extend(fee);
+                result.write_set_digest = Some(write_set.digest());
+                result.deletions = write_set.deletions().map(<[u8]>::to_vec).collect();
+                result
+            }
+            Err(err) => {
+                let mut result = self.failed_result(&err, gas_used, abort_message);
+                let mut kept = kept;
+                if result.status_type() == StatusType::InvariantViolation {
+                    self.on_invariant_violation(&result);
+                    // The transaction is kept to halt every node at the same transaction.
+                    if self.circuit_breaker.is_some() {
+                        result.halted = true;
+                        kept.push(self.halt_write(true));
+                    }
+                }
+                if !result.is_discarded() {
+                    if let Some(fee) = &fee {
+                        if let Err(err) = self.bank.check_changes(Some(fee)) {
+                            log::warn!("Fee payer can't pay the fee: {:?}", err);
+                            return Self::error_result(
+                                StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE,
+                                None,
+                                gas_used,
+                            );
+                        }
+                    }
+                    let write_set = key_write_set(&kept);
+                    result.write_set_digest = Some(write_set.digest());
+                    result.deletions = write_set.deletions().map(<[u8]>::to_vec).collect();
+                    for (key, blob) in kept {
+                        self.apply_write(space, key, blob);
+                    }
+                    if let Some(fee) = fee {
+                        if let Err(err) = self.apply_balance_change(&fee) {
+                            self.on_apply_failure("fee", err);
+                        }
+                        result.balance_changes.push(fee);
+                    }
+                    if let Err(err) =
+                        self.emit_vm_status_event(space.tenant(), sender, err.into_vm_status())
+                    {
+                        log::warn!("Failed to emit vm status event:{:?}", err);
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    /// Reports the invariant violation.
+    fn on_invariant_violation(&self, result: &VmResult) {
+        log::error!("Invariant violation: {}", result);
+        self.metrics.on_invariant_violation(result);
+    }
+
+    /// Checks that the halted or uninitialized vm accepts the transaction signed by the senders.
+    fn check_halted(&self, senders: &[AccountAddress]) -> Result<(), VmResult> {
+        self.check_initialized(senders)?;
+        match &self.circuit_breaker {
+            Some(breaker)
+                if self.is_halted() && !breaker.allows(senders, &self.addresses.config_address) =>
+            {
+                Err(VmResult::new(StatusCode::VM_HALTED, None, 0))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the uninitialized vm accepts the transaction signed by the senders.
+    fn check_initialized(&self, senders: &[AccountAddress]) -> Result<(), VmResult> {
+        if self.uninitialized_mode
+            && !self.is_initialized()
+            && !senders.iter().all(|sender| {
+                sender == &self.addresses.core_code_address
+                    || sender == &self.addresses.config_address
+            })
+        {
+            Err(VmResult::new(StatusCode::VM_HALTED, None, 0))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks the gas of the transaction against the bounds of the current epoch.
+    fn check_gas(&self, gas: &Gas) -> Result<(), VmResult> {
+        self.gas_bounds()
+            .check(gas.max_gas_amount(), gas.gas_unit_price())
+            .and_then(|_| {
+                if self.bank.is_gas_currency(gas.gas_currency()) {
+                    Ok(())
+                } else {
+                    Err(GasError::UnsupportedGasCurrency(gas.gas_currency().clone()))
+                }
+            })
+            .map_err(|err| {
+                let status = match err {
+                    GasError::GasUnitPriceTooLow { .. } => {
+                        StatusCode::GAS_UNIT_PRICE_BELOW_MIN_BOUND
+                    }
+                    GasError::GasUnitPriceTooHigh { .. } => {
+                        StatusCode::GAS_UNIT_PRICE_ABOVE_MAX_BOUND
+                    }
+                    GasError::UnsupportedGasCurrency(_) => StatusCode::CURRENCY_INFO_DOES_NOT_EXIST,
+                    _ => StatusCode::MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND,
+                };
+                VmResult::new(status, None, 0)
+            })
+    }
+
+    /// Checks that the transaction is signed for the chain of the vm.
+    fn check_chain_id(&self, chain_id: Option<u8>) -> Result<(), VmResult> {
+        match self.chain_id {
+            Some(expected) if chain_id != Some(expected) => {
+                Err(VmResult::new(StatusCode::BAD_CHAIN_ID, None, 0))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates result of the failed transaction with the abort message and the error location.
+    fn failed_result(
+        &self,
+        err: &VMError,
+        gas_used: u64,
+        abort_message: Option<String>,
+    ) -> VmResult {
+        let mut result = Self::error_result(err.major_status(), err.sub_status(), gas_used)
+            .with_abort_message(abort_message);
+        result.location = ErrorLocation::of(err);
+        result.source_location = match (&result.location, &self.source_maps) {
+            (Some(location), Some(source_maps)) => {
+                match load_source_map(source_maps.as_ref(), &location.module) {
+                    Ok(source_map) => source_map.and_then(|map| location.resolve(&map)),
+                    Err(err) => {
+                        log::warn!("Failed to load source map:{:?}", err);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        result
+    }
+
+    /// Creates result of the failed transaction.
+    /// Discarded transactions are not included in the block, so no gas is charged.
+    fn error_result(status: StatusCode, sub_status: Option<u64>, gas_used: u64) -> VmResult {
+        let mut result = VmResult::new(status, sub_status, gas_used);
+        if result.is_discarded() {
+            result.gas_used = 0;
+        }
+        result
+    }
+
+    fn emit_vm_status_event(
+        &self,
+        tenant: Option<TenantId>,
+        sender: AccountAddress,
+        status: VMStatus,
+    ) -> Result<(), Error> {
+        let tag = TypeTag::Struct(StructTag {
+            address: self.addresses.core_code_address,
+            module: Identifier::new("VMStatus").unwrap(),
+            name: Identifier::new("VMStatus").unwrap(),
+            type_params: vec![],
+        });
+
+        let module = match &status {
+            VMStatus::Executed | VMStatus::Error(_) => None,
+            VMStatus::MoveAbort(loc, _)
+            | VMStatus::ExecutionFailure {
+                status_code: _,
+                location: loc,
+                function: _,
+                code_offset: _,
+            } => match loc {
+                AbortLocation::Module(module) => Some(module.to_owned()),
+                AbortLocation::Script => None,
+            },
+        };
+        let msg = bcs::to_bytes(&status)
+            .map_err(|err| Error::msg(format!("Failed to generate event message: {:?}", err)))?;
+
+        self.emit_event(tenant, sender, tag, msg, module, &[])
+            .map_err(|err| Error::msg(format!("Failed to emit event: {:?}", err)))
+    }
+
+    /// Returns the writes of the module versions bumped by the published modules
+    /// and the messages of the publish events by the module address.
+    #[allow(clippy::type_complexity)]
+    fn publish_writes<T: Storage>(
+        &self,
+        storage: &T,
+        modules: &[(ModuleId, Arc<[u8]>)],
+    ) -> Result<(Vec<KeyWrite>, Vec<(AccountAddress, Vec<u8>)>), VMError> {
+        let core_address = self.addresses.core_code_address;
+        let serialization_error = |_: bcs::Error| {
+            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                .finish(Location::Undefined)
+        };
+        let mut versions = BTreeMap::new();
+        let mut events = Vec::with_capacity(modules.len());
+        for (id, blob) in modules {
+            let address_versions = match versions.entry(*id.address()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(
+                    load_module_versions(storage, core_address, id.address()).map_err(|_| {
+                        PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE)
+                            .finish(Location::Undefined)
+                    })?,
+                ),
+            };
+            let event = ModulePublishedEvent {
+                id: id.clone(),
+                hash: module_hash(blob),
+                version: address_versions.bump(id.name()),
+            };
+            events.push((
+                *id.address(),
+                bcs::to_bytes(&event).map_err(serialization_error)?,
+            ));
+        }
+        let writes = versions
+            .into_iter()
+            .map(|(address, versions)| {
+                Ok((
+                    versions_key(core_address, &address),
+                    Some(bcs::to_bytes(&versions).map_err(serialization_error)?),
+                ))
+            })
+            .collect::<Result<Vec<_>, VMError>>()?;
+        Ok((writes, events))
+    }
+
+    /// Checks the release of the package against its published manifest.
+    fn check_package_release(
+        &self,
+        space: &Space,
+        sender: AccountAddress,
+        name: &Identifier,
+        policy: UpgradePolicy,
+        modules: &[Vec<u8>],
+    ) -> VMResult<Option<PackageRelease>> {
+        let packages = load_packages(
+            &self.state.storage(space.tenant()),
+            self.addresses.core_code_address,
+            &sender,
+        )
+        .map_err(|_| {
+            PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE)
+                .finish(Location::Undefined)
+        })?;
+        let names = modules
+            .iter()
+            .map(|module| {
+                CompiledModule::deserialize(module)
+                    .map(|module| module.name().to_owned())
+                    .map_err(|err| err.finish(Location::Undefined))
+            })
+            .collect::<VMResult<Vec<_>>>()?;
+
+        for module in &names {
+            if let Some(package) = packages.package_of(module) {
+                if &package.name != name {
+                    return Err(PartialVMError::new(StatusCode::DUPLICATE_MODULE_NAME)
+                        .with_message(format!(
+                            "Module {} belongs to package {}",
+                            module, package.name
+                        ))
+                        .finish(Location::Undefined));
+                }
+            }
+        }
+
+        let previous = match packages.package(name) {
+            None => {
+                return Ok(Some(PackageRelease {
+                    manifest: PackageManifest {
+                        name: name.to_owned(),
+                        version: 1,
+                        policy,
+                        modules: names,
+                    },
+                    upgrade: None,
+                }))
+            }
+            Some(previous) => previous,
+        };
+        let upgrade = previous.policy.module_upgrade().ok_or_else(|| {
+            PartialVMError::new(StatusCode::IMMUTABLE_MODULE_UPDATE).finish(Location::Undefined)
+        })?;
+        if policy < previous.policy {
+            return Err(
+                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE)
+                    .with_message("Upgrade policy can not be weakened".to_owned())
+                    .finish(Location::Undefined),
+            );
+        }
+        if let Some(module) = previous
+            .modules
+            .iter()
+            .find(|module| !names.contains(*module))
+        {
+            return Err(
+                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE)
+                    .with_message(format!("Module {} is removed from the package", module))
+                    .finish(Location::Undefined),
+            );
+        }
+
+        Ok(Some(PackageRelease {
+            manifest: PackageManifest {
+                name: name.to_owned(),
+                version: previous.version + 1,
+                policy,
+                modules: names,
+            },
+            upgrade: Some((upgrade, previous.modules.clone())),
+        }))
+    }
+
+    /// Returns the write of the package manifests of the sender with the released manifest.
+    fn package_write(
+        &self,
+        space: &Space,
+        sender: &AccountAddress,
+        manifest: PackageManifest,
+    ) -> VMResult<KeyWrite> {
+        let core_address = self.addresses.core_code_address;
+        let storage = self.state.storage(space.tenant());
+        let mut packages = load_packages(&storage, core_address, sender).map_err(|_| {
+            PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE)
+                .finish(Location::Undefined)
+        })?;
+        packages.insert(manifest);
+        let blob = bcs::to_bytes(&packages).map_err(|_| {
+            PartialVMError::new(StatusCode::VALUE_SERIALIZATION_ERROR).finish(Location::Undefined)
+        })?;
+        Ok((packages_key(core_address, sender), Some(blob)))
+    }
+
+    /// Returns the write of the immutable modules of the sender with the published module.
+    fn immutable_write(
+        &self,
+        space: &Space,
+        sender: &AccountAddress,
+        module: &[u8],
+    ) -> VMResult<KeyWrite> {
+        let core_address = self.addresses.core_code_address;
+        let name = CompiledModule::deserialize(module)
+            .map_err(|err| err.finish(Location::Undefined))?
+            .name()
+            .to_owned();
+        let storage = self.state.storage(space.tenant());
+        let mut immutable =
+            load_immutable_modules(&storage, core_address, sender).map_err(|_| {
+                PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE)
+                    .finish(Location::Undefined)
+            })?;
+        immutable.insert(&name);
+        let blob = bcs::to_bytes(&immutable).map_err(|_| {
+            PartialVMError::new(StatusCode::VALUE_SERIALIZATION_ERROR).finish(Location::Undefined)
+        })?;
+        Ok((immutable_key(core_address, sender), Some(blob)))
+    }
+
+    /// Stores the source map of the published module in the host-local source map storage
+    /// or removes the source map of the previous version.
+    fn record_source_map(&self, module: &[u8], source_map: Option<Vec<u8>>) {
+        let source_maps = match &self.source_maps {
+            Some(source_maps) => source_maps,
+            None => return,
+        };
+        let id = match CompiledModule::deserialize(module) {
+            Ok(module) => module.self_id(),
+            Err(err) => {
+                log::error!("Failed to deserialize published module:{:?}", err);
+                return;
+            }
+        };
+        match source_map {
+            Some(blob) => match SourceMap::decode(&blob) {
+                Ok(_) => source_maps.insert(source_map_key(&id).as_ref(), &blob),
+                Err(err) => {
+                    log::warn!("Invalid source map of {:?}:{:?}", id, err);
+                    source_maps.remove(source_map_key(&id).as_ref());
+                }
+            },
+            None => source_maps.remove(source_map_key(&id).as_ref()),
+        }
+    }
 
-        Ok(Mvm {
-            vm: MoveVM::new(),
-            cost_table: config.gas_schedule,
-            state: State::new(store, oracle),
-            event_handler,
-            bank: Bank::new(balance),
+    /// Resolves the source location of the error in the module being published
+    /// with the source map of the transaction instead of the stored one.
+    fn resolve_published_source(
+        &self,
+        result: &mut VmResult,
+        module: &[u8],
+        source_map: Option<&[u8]>,
+    ) {
+        let location = match result.location.clone() {
+            Some(location) => location,
+            None => return,
+        };
+        let published = CompiledModule::deserialize(module)
+            .map(|module| module.self_id() == location.module)
+            .unwrap_or(false);
+        if published {
+            result.source_location = source_map
+                .and_then(|blob| SourceMap::decode(blob).ok())
+                .and_then(|source_map| location.resolve(&source_map));
+        }
+    }
+
+    /// Passes the event emitted in the space of the tenant to the event handler together with
+    /// its topics.
+    fn emit_event(
+        &self,
+        tenant: Option<TenantId>,
+        address: AccountAddress,
+        ty_tag: TypeTag,
+        msg: Vec<u8>,
+        caller: Option<ModuleId>,
+        indexed_values: &[Vec<u8>],
+    ) -> VMResult<()> {
+        let topics = event_topics(&address, &ty_tag, indexed_values);
+        guard("EventHandler", || match tenant {
+            Some(tenant) => self
+                .event_handler
+                .on_tenant_event(tenant, address, ty_tag, msg, caller, topics),
+            None => self
+                .event_handler
+                .on_indexed_event(address, ty_tag, msg, caller, topics),
         })
+        .map_err(|err| err.finish(Location::Undefined))
     }
 
-    /// Stores write set into storage and handle events.
-    fn handle_tx_effects(&self, tx_effects: TransactionEffects) -> Result<(), VMError> {
-        for (addr, vals) in tx_effects.resources {
-            for (struct_tag, val_opt) in vals {
-                let ak = AccessKey::from((&addr, &struct_tag));
-                match val_opt {
-                    None => {
-                        self.state.delete(ak);
-                    }
-                    Some((ty_layout, val)) => {
-                        let blob = val.simple_serialize(&ty_layout).ok_or_else(|| {
-                            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
-                                .finish(Location::Undefined)
-                        })?;
-                        self.state.insert(ak, blob);
-                    }
-                };
-            }
+    /// Returns the serialized values of the indexed fields of the event
+    /// declared by the module of the event struct.
+    /// Modules published by the transaction are looked up in `published`.
+    fn event_indexed_values(
+        &self,
+        space: &Space,
+        ty_tag: &TypeTag,
+        layout: &MoveTypeLayout,
+        msg: &[u8],
+        published: &[(ModuleId, Arc<[u8]>)],
+    ) -> Vec<Vec<u8>> {
+        let tag = match ty_tag {
+            TypeTag::Struct(tag) => tag,
+            _ => return vec![],
+        };
+        let module_id = ModuleId::new(tag.address, tag.module.clone());
+        let module = match published.iter().find(|(id, _)| id == &module_id) {
+            Some((_, blob)) => blob.to_vec(),
+            None => match self.state.space(space.tenant()).get_module(&module_id) {
+                Ok(Some(blob)) => blob,
+                _ => return vec![],
+            },
+        };
+        match CompiledModule::deserialize(&module) {
+            Ok(module) => indexed_values(msg, layout, &indexed_fields(&module, tag.name.as_str())),
+            Err(_) => vec![],
         }
+    }
 
-        for (module_id, blob) in tx_effects.modules {
-            self.state.insert(AccessKey::from(&module_id), blob);
-        }
+    fn _publish_module<R, NB>(
+        &self,
+        space: &Space,
+        session: &mut Session<'_, '_, R, NB>,
+        module: Arc<[u8]>,
+        sender: AccountAddress,
+        upgrade: ModuleUpgrade,
+        cost_strategy: &mut CostStrategy,
+    ) -> VMResult<()>
+    where
+        R: RemoteCache,
+        NB: NativeBalance,
+    {
+        self.check_publisher(space, &module, &sender)?;
+        self.check_immutable(space, &module, &sender)?;
+        cost_strategy.charge_intrinsic_gas(AbstractMemorySize::new(module.len() as u64))?;
+
+        let result = session.publish_module_with_upgrade(
+            module,
+            sender,
+            upgrade,
+            cost_strategy,
+            &NoContextLog::new(),
+        );
+        Self::charge_global_write_gas_usage(cost_strategy, session, &sender)?;
+        result
+    }
 
-        for (address, ty_tag, ty_layout, val, caller) in tx_effects.events {
-            let msg = val.simple_serialize(&ty_layout).ok_or_else(|| {
-                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+    /// Checks that the on-chain publisher policy of the space allows the sender to publish
+    /// the module.
+    fn check_publisher(
+        &self,
+        space: &Space,
+        module: &[u8],
+        sender: &AccountAddress,
+    ) -> VMResult<()> {
+        let storage = self.state.storage(space.tenant());
+        let policy =
+            load_publisher_policy_at(&storage, self.addresses.config_address).map_err(|err| {
+                PartialVMError::new(StatusCode::STORAGE_ERROR)
+                    .with_message(err.to_string())
                     .finish(Location::Undefined)
             })?;
-            self.event_handler.on_event(address, ty_tag, msg, caller);
-        }
-
-        for (id, op) in tx_effects.wallet_ops.into_iter() {
-            match op {
-                BalanceOperation::Deposit(amount) => self.bank.deposit(&id, amount)?,
-                BalanceOperation::Withdraw(amount) => self.bank.withdraw(&id, amount)?,
+        let allowed = match policy.permission(sender) {
+            PublishPermission::Any => true,
+            PublishPermission::Denied => false,
+            permission => {
+                let module = CompiledModule::deserialize(module)
+                    .map_err(|err| err.finish(Location::Undefined))?;
+                permission.allows(module.name().as_str())
             }
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(PartialVMError::new(StatusCode::INVALID_MODULE_PUBLISHER)
+                .finish(Location::Undefined))
         }
+    }
 
-        Ok(())
+    /// Checks that the module does not replace an immutable one.
+    fn check_immutable(
+        &self,
+        space: &Space,
+        module: &[u8],
+        sender: &AccountAddress,
+    ) -> VMResult<()> {
+        let immutable = load_immutable_modules(
+            &self.state.storage(space.tenant()),
+            self.addresses.core_code_address,
+            sender,
+        )
+        .map_err(|_| {
+            PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE)
+                .finish(Location::Undefined)
+        })?;
+        if immutable.modules.is_empty() {
+            return Ok(());
+        }
+        let module =
+            CompiledModule::deserialize(module).map_err(|err| err.finish(Location::Undefined))?;
+        if immutable.contains(module.name()) {
+            Err(PartialVMError::new(StatusCode::IMMUTABLE_MODULE_UPDATE)
+                .finish(Location::Undefined))
+        } else {
+            Ok(())
+        }
     }
 
-    /// Handle vm result and return transaction status code.
-    fn handle_vm_result(
+    /// Executes the script. The governance script gets the config address as the first signer.
+    /// The governance script runs on the halted vm in any safe mode and resumes it if executed.
+    /// The dry run with the state root keeps its effects in the speculative cache.
+    fn execute_script_tx(
         &self,
-        sender: AccountAddress,
-        cost_strategy: CostStrategy,
-        gas_meta: Gas,
-        result: Result<TransactionEffects, VMError>,
+        gas: Gas,
+        context: ExecutionContext,
+        tx: ScriptTx,
         dry_run: bool,
+        governance: bool,
+        state_root: Option<Digest>,
     ) -> VmResult {
-        let gas_used = GasUnits::new(gas_meta.max_gas_amount)
-            .sub(cost_strategy.remaining_gas())
-            .get();
+        self.metrics.on_tx_start(TxKind::Script);
+        let span = tx_span!(
+            "execute_script",
+            tx_hash = %hex::encode(tx.hash()),
+            sender = %tx.senders().first().cloned().unwrap_or(NONE_ADDRESS),
+            max_gas = gas.max_gas_amount()
+        );
+        let fee_payer = tx.fee_payer().cloned();
+        let root = self.addresses.config_address;
+        let halted = if governance {
+            let mut senders = Vec::with_capacity(tx.senders().len() + 1);
+            senders.push(root);
+            senders.extend_from_slice(tx.senders());
+            self.check_initialized(&senders)
+        } else {
+            self.check_halted(tx.senders())
+        };
+        let resume = governance && self.is_halted();
+        let (space, lanes) = match halted
+            .and_then(|_| self.check_chain_id(tx.chain_id()))
+            .and_then(|_| self.check_gas(&gas))
+            .and_then(|_| self.check_tx_limits(&tx))
+            .and_then(|_| Self::check_validity_window(&tx, &context))
+            .and_then(|_| {
+                if governance {
+                    self.ensure_governance(&tx)
+                        .and_then(|_| self.authenticate_co_signers(context.tenant, &tx))
+                } else {
+                    self.authenticate(context.tenant, &tx)
+                }
+            })
+            .and_then(|_| self.check_fee_payer(fee_payer.as_ref(), &gas))
+            .and_then(|_| self.space(context.tenant))
+            .and_then(|space| {
+                self.check_sequence_numbers(&space, Some((tx.senders(), tx.sequence_number())))
+                    .map(|lanes| (space, lanes))
+            }) {
+            Ok(checked) => checked,
+            Err(result) => {
+                span.finish(&result);
+                self.metrics.on_tx_end(TxKind::Script, &result);
+                return result;
+            }
+        };
 
-        if dry_run {
-            return match result {
-                Ok(_) => VmResult::new(StatusCode::EXECUTED, None, gas_used),
-                Err(err) => VmResult::new(err.major_status(), err.sub_status(), gas_used),
-            };
+        let simulation = state_root
+            .filter(|_| dry_run)
+            .map(|state_root| ((tx.signing_message(), state_root), context.clone()));
+        let (script, args, type_args, mut senders) = tx.into_inner();
+        if governance {
+            senders.insert(0, root);
         }
+        let sender = senders.get(0).cloned().unwrap_or(NONE_ADDRESS);
 
-        match result.and_then(|e| self.handle_tx_effects(e)) {
-            Ok(_) => VmResult::new(StatusCode::EXECUTED, None, gas_used),
-            Err(err) => {
-                let status = err.major_status();
-                let sub_status = err.sub_status();
-                if let Err(err) = self.emit_vm_status_event(sender, err.into_vm_status()) {
-                    VmResult::new(status, sub_status, gas_used);
-                    log::warn!("Failed to emit vm status event:{:?}", err);
-                }
+        // Copy of the transaction for the out-of-gas re-run.
+        let audit = self.oog_audit.and_then(|extra_gas_percent| {
+            let args = args
+                .iter()
+                .map(|arg| arg.copy_value())
+                .collect::<Result<Vec<_>, _>>()
+                .ok()?;
+            let extra_gas = gas.max_gas_amount().saturating_mul(extra_gas_percent) / 100;
+            let gas_limit = gas
+                .max_gas_amount()
+                .saturating_add(core::cmp::max(extra_gas, 1));
+            Some((
+                gas_limit,
+                context.clone(),
+                args,
+                type_args.clone(),
+                senders.clone(),
+            ))
+        });
+
+        let bank = SessionBank::new(&self.bank, context.host_policy()).with_reserved_fee(
+            fee_payer.as_ref(),
+            gas.gas_currency(),
+            max_fee(&gas),
+        );
+        let state_space = self.state.space(space.tenant());
+        let state_session = StateSession::new(&state_space, context)
+            .with_core_address(self.addresses.core_code_address)
+            .with_config_address(self.addresses.config_address)
+            .with_oracle_address(self.addresses.oracle_address);
+        let recorder = ReadRecorder::new(&state_session, audit.is_some());
+        let state = MeteredCache::new(&recorder, &self.metrics);
+        let mut session = self.new_session(&space, &state, &bank);
+
+        let cost_table = self.cost_table(&space);
+        let mut cost_strategy = self.script_cost_strategy(&cost_table, gas.max_gas_amount());
+
+        let result = self
+            .create_missing_accounts(&space, &mut session, &cost_table, &senders)
+            .and_then(|_| {
+                session.execute_script(
+                    &script,
+                    type_args,
+                    args,
+                    senders,
+                    &mut cost_strategy,
+                    &NoContextLog::new(),
+                )
+            })
+            .and_then(|_| {
+                Self::charge_global_write_gas_usage(&mut cost_strategy, &mut session, &sender)
+            });
+
+        let abort_message = AbortMessage::take(session.extensions());
+        let result = bank
+            .check_denied()
+            .and(result)
+            .and_then(|_| session.finish())
+            .and_then(|effects| bank.check_access(&effects).map(|_| effects));
+        let price_reads = state_session.take_price_reads();
+        let simulation = simulation.map(|(key, context)| Simulation {
+            key,
+            context,
+            price_reads: price_reads.clone(),
+            reads: HostReads {
+                oracle: state_session.take_oracle_reads(),
+                balances: bank.take_balance_reads(),
+            },
+        });
+        let host_writes = if resume {
+            HostWrites::kept(lanes).with_executed(self.halt_write(false))
+        } else {
+            HostWrites::kept(lanes)
+        }
+        .with_fee(fee_payer.as_ref(), &gas);
+        let mut result = self.handle_vm_result(
+            &space,
+            sender,
+            cost_strategy,
+            gas,
+            result,
+            abort_message,
+            host_writes,
+            simulation,
+            dry_run,
+        );
+        result.price_reads = price_reads;
 
-                VmResult::new(status, sub_status, gas_used)
+        if let Some((gas_limit, context, args, type_args, senders)) = audit {
+            if result.status_code == StatusCode::OUT_OF_GAS {
+                self.audit_oog(
+                    &space,
+                    recorder.into_reads(),
+                    gas_limit,
+                    context,
+                    &script,
+                    args,
+                    type_args,
+                    senders,
+                );
             }
         }
+
+        span.finish(&result);
+        self.metrics.on_tx_end(TxKind::Script, &result);
+        result
     }
 
-    fn emit_vm_status_event(&self, sender: AccountAddress, status: VMStatus) -> Result<(), Error> {
-        let tag = TypeTag::Struct(StructTag {
-            address: CORE_CODE_ADDRESS,
-            module: Identifier::new("VMStatus").unwrap(),
-            name: Identifier::new("VMStatus").unwrap(),
-            type_params: vec![],
-        });
+    /// Checks the script senders with the authenticator.
+    fn authenticate(&self, tenant: Option<TenantId>, tx: &ScriptTx) -> Result<(), VmResult> {
+        self.authenticate_senders(tenant, &tx.signing_message(), tx.senders(), tx.proof())
+    }
 
-        let module = match &status {
-            VMStatus::Executed | VMStatus::Error(_) => None,
-            VMStatus::MoveAbort(loc, _)
-            | VMStatus::ExecutionFailure {
-                status_code: _,
-                location: loc,
-                function: _,
-                code_offset: _,
-            } => match loc {
-                AbortLocation::Module(module) => Some(module.to_owned()),
-                AbortLocation::Script => None,
-            },
+    /// Checks the proof of the batch with the `Authenticator`.
+    fn authenticate_batch(&self, tenant: Option<TenantId>, tx: &BatchTx) -> Result<(), VmResult> {
+        self.authenticate_senders(tenant, &tx.signing_message(), tx.senders(), tx.proof())
+    }
+
+    /// Checks the proof with the `Authenticator` given the keys of the senders stored
+    /// in the space of the tenant, see `auth::load_auth_key`.
+    fn authenticate_senders(
+        &self,
+        tenant: Option<TenantId>,
+        message: &Digest,
+        senders: &[AccountAddress],
+        proof: &[u8],
+    ) -> Result<(), VmResult> {
+        let authenticator = match &self.authenticator {
+            Some(authenticator) => authenticator,
+            None => return Ok(()),
         };
-        let msg = bcs::to_bytes(&status)
-            .map_err(|err| Error::msg(format!("Failed to generate event message: {:?}", err)))?;
+        let storage = self.state.storage(tenant);
+        let keys = senders
+            .iter()
+            .map(|sender| load_auth_key(&storage, self.addresses.core_code_address, sender))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| VmResult::new(err.major_status(), None, 0))?;
+        authenticator
+            .authenticate_with_keys(message, senders, &keys, proof)
+            .map_err(|reason| VmResult::new(StatusCode::INVALID_SIGNATURE, Some(reason), 0))
+    }
 
-        self.event_handler.on_event(sender, tag, msg, module);
-        Ok(())
+    /// Checks that the senders of the governance script besides the governance account are
+    /// authenticated. Without the `Authenticator` such scripts are rejected.
+    fn authenticate_co_signers(
+        &self,
+        tenant: Option<TenantId>,
+        tx: &ScriptTx,
+    ) -> Result<(), VmResult> {
+        if tx.senders().is_empty() {
+            Ok(())
+        } else if self.authenticator.is_some() {
+            self.authenticate(tenant, tx)
+        } else {
+            Err(VmResult::new(StatusCode::INVALID_SIGNATURE, None, 0))
+        }
     }
 
-    fn _publish_module<R, NB>(
+    /// Checks that the governance script is approved by the host.
+    fn ensure_governance(&self, tx: &ScriptTx) -> Result<(), VmResult> {
+        match &self.governance {
+            Some(origin) => origin
+                .ensure_governance(tx)
+                .map_err(|reason| VmResult::new(StatusCode::NO_ACCOUNT_ROLE, Some(reason), 0)),
+            None => Err(VmResult::new(StatusCode::NO_ACCOUNT_ROLE, None, 0)),
+        }
+    }
+
+    /// Checks the script against the transaction limits.
+    fn check_tx_limits(&self, tx: &ScriptTx) -> Result<(), VmResult> {
+        tx.check_limits(&self.tx_limits)
+            .map_err(|status| VmResult::new(status, None, 0))
+    }
+
+    /// Checks that the transaction is valid at the block timestamp.
+    fn check_validity_window(tx: &ScriptTx, context: &ExecutionContext) -> Result<(), VmResult> {
+        tx.check_validity_window(context.timestamp)
+            .map_err(|status| VmResult::new(status, None, 0))
+    }
+
+    /// Checks the sequence numbers of the transactions against the lanes of their first senders.
+    /// Returns the writes of the next sequence numbers, kept if the transaction is kept in the
+    /// block even when it fails.
+    fn check_sequence_numbers<'a, I>(
+        &self,
+        space: &Space,
+        txs: I,
+    ) -> Result<Vec<KeyWrite>, VmResult>
+    where
+        I: IntoIterator<Item = (&'a [AccountAddress], Option<(u64, u64)>)>,
+    {
+        let core_address = self.addresses.core_code_address;
+        let storage = self.state.storage(space.tenant());
+        let mut next = BTreeMap::new();
+        for (senders, sequence) in txs {
+            let (lane, sequence_number) = match sequence {
+                Some(sequence) => sequence,
+                None => continue,
+            };
+            let sender = senders.first().cloned().unwrap_or(NONE_ADDRESS);
+            let lanes = match next.entry(sender) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(
+                    load_sequence_numbers(&storage, core_address, &sender).map_err(|_| {
+                        VmResult::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE, None, 0)
+                    })?,
+                ),
+            };
+            let expected = lanes.sequence_number(lane);
+            if sequence_number < expected {
+                return Err(VmResult::new(StatusCode::SEQUENCE_NUMBER_TOO_OLD, None, 0));
+            }
+            if sequence_number > expected {
+                return Err(VmResult::new(StatusCode::SEQUENCE_NUMBER_TOO_NEW, None, 0));
+            }
+            let following = sequence_number
+                .checked_add(1)
+                .ok_or_else(|| VmResult::new(StatusCode::SEQUENCE_NUMBER_TOO_BIG, None, 0))?;
+            lanes.set(lane, following);
+        }
+        next.into_iter()
+            .map(|(sender, lanes)| {
+                let blob = bcs::to_bytes(&lanes)
+                    .map_err(|_| VmResult::new(StatusCode::VALUE_SERIALIZATION_ERROR, None, 0))?;
+                Ok((sequence_numbers_key(core_address, &sender), Some(blob)))
+            })
+            .collect()
+    }
+
+    /// Checks that the fee payer can pay the max transaction fee.
+    fn check_fee_payer(
+        &self,
+        fee_payer: Option<&AccountAddress>,
+        gas: &Gas,
+    ) -> Result<(), VmResult> {
+        match fee_payer {
+            Some(fee_payer) => {
+                if self
+                    .bank
+                    .can_pay_fee(fee_payer, gas.gas_currency(), max_fee(gas))
+                {
+                    Ok(())
+                } else {
+                    Err(VmResult::new(
+                        StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE,
+                        None,
+                        0,
+                    ))
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn script_cost_strategy<'a>(
+        &'a self,
+        cost_table: &'a CostTable,
+        gas_limit: u64,
+    ) -> CostStrategy<'a> {
+        let mut cost_strategy = CostStrategy::transaction(cost_table, GasUnits::new(gas_limit));
+        if let Some(limit) = self.instruction_limit {
+            cost_strategy = cost_strategy.with_instruction_limit(limit);
+        }
+        if let Some(interrupt) = &self.interrupt {
+            cost_strategy = cost_strategy.with_interrupt(interrupt.as_ref());
+        }
+        cost_strategy
+    }
+
+    /// Re-executes the out-of-gas script with the higher gas limit and compares resource reads.
+    /// Effects of the re-run are discarded.
+    #[allow(clippy::too_many_arguments)]
+    fn audit_oog(
         &self,
+        space: &Space,
+        oog_reads: Vec<ResourceRead>,
+        gas_limit: u64,
+        context: ExecutionContext,
+        script: &[u8],
+        args: Vec<Value>,
+        type_args: Vec<TypeTag>,
+        senders: Vec<AccountAddress>,
+    ) {
+        let bank = SessionBank::new(&self.bank, context.host_policy());
+        let state_space = self.state.space(space.tenant());
+        let state_session = StateSession::new(&state_space, context)
+            .with_core_address(self.addresses.core_code_address)
+            .with_config_address(self.addresses.config_address)
+            .with_oracle_address(self.addresses.oracle_address);
+        let recorder = ReadRecorder::new(&state_session, true);
+        let mut session = self.new_session(space, &recorder, &bank);
+        let cost_table = self.cost_table(space);
+        let mut cost_strategy = self.script_cost_strategy(&cost_table, gas_limit);
+
+        let _ = session.execute_script(
+            script,
+            type_args,
+            args,
+            senders,
+            &mut cost_strategy,
+            &NoContextLog::new(),
+        );
+        drop(session);
+
+        if let Some(divergence) = find_divergence(&oog_reads, &recorder.into_reads()) {
+            log::warn!(
+                "Nondeterministic out-of-gas path detected: {:?}",
+                divergence
+            );
+            self.metrics.on_oog_divergence(&divergence);
+        }
+    }
+
+    /// Publishes the `Account` resources for the senders which have native balance
+    /// but no account if lazy accounts are enabled. No gas is charged.
+    /// Tenants have no native balances, so their accounts are never created.
+    fn create_missing_accounts<R, NB>(
+        &self,
+        space: &Space,
         session: &mut Session<'_, '_, R, NB>,
-        module: Vec<u8>,
-        sender: AccountAddress,
-        cost_strategy: &mut CostStrategy,
+        cost_table: &CostTable,
+        senders: &[AccountAddress],
     ) -> VMResult<()>
     where
         R: RemoteCache,
         NB: NativeBalance,
     {
-        cost_strategy.charge_intrinsic_gas(AbstractMemorySize::new(module.len() as u64))?;
-
-        let result = session.publish_module(module, sender, cost_strategy, &NoContextLog::new());
-        Self::charge_global_write_gas_usage(cost_strategy, session, &sender)?;
-        result
+        if space.tenant().is_some() || !self.lazy_accounts.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let module = account_module(self.addresses.core_code_address);
+        let function = Identifier::new(CREATE_ACCOUNT).unwrap();
+        for sender in senders {
+            if self.account_exists(sender)? || !self.bank.has_native_balance(sender) {
+                continue;
+            }
+            session.execute_function(
+                &module,
+                function.as_ident_str(),
+                vec![],
+                vec![Value::address(*sender)],
+                *sender,
+                &mut CostStrategy::system(cost_table, GasUnits::new(0)),
+                &NoContextLog::new(),
+            )?;
+        }
+        Ok(())
     }
 
     fn charge_global_write_gas_usage<R, NB>(
@@ -216,24 +2872,115 @@ where
     }
 }
 
-impl<S, E, O, B> Vm for Mvm<S, E, O, B>
+impl<S, E, O, B, M> Mvm<S, E, O, B, M>
+where
+    S: Storage + StateProof,
+    E: EventHandler,
+    O: Oracle,
+    B: BalanceAccess,
+    M: Metrics,
+{
+    /// Returns the resource together with the proof of its storage key.
+    pub fn get_resource_with_proof(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> Result<ResourceProof, Error> {
+        let storage = self.state.storage(None);
+        let key = AccessKey::from((address, tag));
+        let proof = storage
+            .prove(key.as_ref())
+            .ok_or_else(|| Error::msg("Storage can't prove the resource"))?;
+        Ok(ResourceProof {
+            value: storage.get(key.as_ref()),
+            key: key.as_ref().to_vec(),
+            proof,
+        })
+    }
+}
+
+impl<S, E, O, B, M> Vm for Mvm<S, E, O, B, M>
 where
     S: Storage,
     E: EventHandler,
     O: Oracle,
     B: BalanceAccess,
+    M: Metrics,
 {
     fn publish_module(&self, gas: Gas, module: ModuleTx, dry_run: bool) -> VmResult {
+        self.metrics.on_tx_start(TxKind::PublishModule);
+        let immutable = module.is_immutable();
+        let chain_id = module.chain_id();
+        let source_map = module.source_map().map(<[u8]>::to_vec);
+        let tenant = module.tenant();
         let (module, sender) = module.into_inner();
+        let span = tx_span!(
+            "publish_module",
+            tx_hash = %hex::encode(module_hash(&module)),
+            sender = %sender,
+            max_gas = gas.max_gas_amount()
+        );
+        let space = match self
+            .check_halted(&[sender])
+            .and_then(|_| self.check_chain_id(chain_id))
+            .and_then(|_| self.check_gas(&gas))
+            .and_then(|_| self.space(tenant))
+        {
+            Ok(space) => space,
+            Err(result) => {
+                span.finish(&result);
+                self.metrics.on_tx_end(TxKind::PublishModule, &result);
+                return result;
+            }
+        };
+        let cost_table = self.cost_table(&space);
         let mut cost_strategy =
-            CostStrategy::transaction(&self.cost_table, GasUnits::new(gas.max_gas_amount()));
-        let mut session = self.vm.new_session(&self.state, &self.bank);
+            CostStrategy::transaction(&cost_table, GasUnits::new(gas.max_gas_amount()));
+        let state_space = self.state.space(tenant);
+        let state = MeteredCache::new(&state_space, &self.metrics);
+        let mut session = self.new_session(&space, &state, &self.bank);
 
         let result = self
-            ._publish_module(&mut session, module, sender, &mut cost_strategy)
+            ._publish_module(
+                &space,
+                &mut session,
+                module.clone(),
+                sender,
+                ModuleUpgrade::Forbidden,
+                &mut cost_strategy,
+            )
             .and_then(|_| session.finish());
+        let immutable_write = if immutable {
+            self.immutable_write(&space, &sender, &module).map(Some)
+        } else {
+            Ok(None)
+        };
+        let (result, host_writes) = match immutable_write {
+            Ok(write) => (result, HostWrites::executed(write.into_iter().collect())),
+            Err(err) => (Err(err), HostWrites::default()),
+        };
 
-        self.handle_vm_result(sender, cost_strategy, gas, result, dry_run)
+        let mut result = self.handle_vm_result(
+            &space,
+            sender,
+            cost_strategy,
+            gas,
+            result,
+            None,
+            host_writes,
+            None,
+            dry_run,
+        );
+        if result.status_code == StatusCode::EXECUTED {
+            if !dry_run {
+                self.record_source_map(&module, source_map);
+            }
+        } else {
+            self.resolve_published_source(&mut result, &module, source_map.as_deref());
+        }
+        span.finish(&result);
+        self.metrics.on_tx_end(TxKind::PublishModule, &result);
+        result
     }
 
     fn publish_module_package(
@@ -242,23 +2989,108 @@ where
         package: PublishPackageTx,
         dry_run: bool,
     ) -> VmResult {
+        self.metrics.on_tx_start(TxKind::PublishPackage);
+        let package_info = package.package().cloned();
+        let chain_id = package.chain_id();
+        let tenant = package.tenant();
         let (modules, sender) = package.into_inner();
+        let span = tx_span!(
+            "publish_module_package",
+            modules = modules.len(),
+            sender = %sender,
+            max_gas = gas.max_gas_amount()
+        );
+        let space = match self
+            .check_halted(&[sender])
+            .and_then(|_| self.check_chain_id(chain_id))
+            .and_then(|_| self.check_gas(&gas))
+            .and_then(|_| self.space(tenant))
+        {
+            Ok(space) => space,
+            Err(result) => {
+                span.finish(&result);
+                self.metrics.on_tx_end(TxKind::PublishPackage, &result);
+                return result;
+            }
+        };
+        let cost_table = self.cost_table(&space);
         let mut cost_strategy =
-            CostStrategy::transaction(&self.cost_table, GasUnits::new(gas.max_gas_amount()));
+            CostStrategy::transaction(&cost_table, GasUnits::new(gas.max_gas_amount()));
 
         // We need to create a new vm to publish module packages.
         // Because during batch publishing, the cache mutates.
         // This is not the correct behavior for the dry_run case or for rolling back a transaction.
-        let vm = MoveVM::new();
-        let mut session = vm.new_session(&self.state, &self.bank);
+        let vm = MoveVM::new_with_limits(self.vm.limits())
+            .with_verification_costs(self.vm.verification_costs())
+            .with_access_costs(self.vm.access_costs())
+            .with_core_address(self.addresses.core_code_address)
+            .with_natives(self.all_natives());
+        let state_space = self.state.space(tenant);
+        let state = MeteredCache::new(&state_space, &self.metrics);
+        let mut session = vm.new_session(&state, &self.bank);
 
-        for module in modules {
-            if let Err(err) = self._publish_module(&mut session, module, sender, &mut cost_strategy)
-            {
-                return self.handle_vm_result(sender, cost_strategy, gas, Err(err), dry_run);
+        let release = match &package_info {
+            Some((name, policy)) => {
+                self.check_package_release(&space, sender, name, *policy, &modules)
             }
+            None => Ok(None),
+        };
+        let result = release
+            .and_then(|release| {
+                modules
+                    .into_iter()
+                    .enumerate()
+                    .try_for_each(|(idx, module)| {
+                        let upgrade = release
+                            .as_ref()
+                            .map(|release| release.module_upgrade(idx))
+                            .unwrap_or(ModuleUpgrade::Forbidden);
+                        self._publish_module(
+                            &space,
+                            &mut session,
+                            module.into(),
+                            sender,
+                            upgrade,
+                            &mut cost_strategy,
+                        )
+                    })?;
+                Ok(release)
+            })
+            .and_then(|release| session.finish().map(|effects| (effects, release)));
+        let (result, release) = match result {
+            Ok((effects, release)) => (Ok(effects), release),
+            Err(err) => (Err(err), None),
+        };
+        let upgraded = release
+            .as_ref()
+            .map(|release| release.upgrade.is_some())
+            .unwrap_or(false);
+        let (result, host_writes) = match release
+            .map(|release| self.package_write(&space, &sender, release.manifest))
+            .transpose()
+        {
+            Ok(write) => (result, HostWrites::executed(write.into_iter().collect())),
+            Err(err) => (Err(err), HostWrites::default()),
+        };
+
+        let result = self.handle_vm_result(
+            &space,
+            sender,
+            cost_strategy,
+            gas,
+            result,
+            None,
+            host_writes,
+            None,
+            dry_run,
+        );
+        // The loader cache is cleared if the release replaced the package modules.
+        if upgraded && result.status_code == StatusCode::EXECUTED && !dry_run {
+            self.clear_space(&space);
         }
-        self.handle_vm_result(sender, cost_strategy, gas, session.finish(), dry_run)
+        span.finish(&result);
+        self.metrics.on_tx_end(TxKind::PublishPackage, &result);
+        result
     }
 
     fn execute_script(
@@ -268,38 +3100,194 @@ where
         tx: ScriptTx,
         dry_run: bool,
     ) -> VmResult {
-        let state_session = StateSession::new(&self.state, context);
-        let mut session = self.vm.new_session(&state_session, &self.bank);
+        self.execute_script_tx(gas, context, tx, dry_run, false, None)
+    }
 
-        let (script, args, type_args, senders) = tx.into_inner();
-        let sender = senders.get(0).cloned().unwrap_or(NONE_ADDRESS);
+    fn clear(&self) {
+        self.clear_space(&Space::host());
+        // The contexts of the tenants are reloaded from their storage spaces on the next call.
+        self.tenants.write().clear();
+    }
+}
 
-        let mut cost_strategy =
-            CostStrategy::transaction(&self.cost_table, GasUnits::new(gas.max_gas_amount()));
+impl<S, E, O, B, M> BatchVm for Mvm<S, E, O, B, M>
+where
+    S: Storage,
+    E: EventHandler,
+    O: Oracle,
+    B: BalanceAccess,
+    M: Metrics,
+{
+    fn execute_batch(
+        &self,
+        gas: Gas,
+        context: ExecutionContext,
+        tx: BatchTx,
+        dry_run: bool,
+    ) -> VmResult {
+        self.metrics.on_tx_start(TxKind::Batch);
+        let sender = tx.senders().first().cloned().unwrap_or(NONE_ADDRESS);
+        let span = tx_span!(
+            "execute_batch",
+            calls = tx.calls().len(),
+            sender = %sender,
+            max_gas = gas.max_gas_amount()
+        );
+        let fee_payer = tx.fee_payer().cloned();
+        // The checks run before the calls, so an empty batch is rejected like any other one.
+        let (space, lanes) = match self
+            .check_halted(tx.senders())
+            .and_then(|_| self.check_chain_id(tx.chain_id()))
+            .and_then(|_| {
+                tx.check_limits(&self.tx_limits)
+                    .map_err(|status| VmResult::new(status, None, 0))
+            })
+            .and_then(|_| {
+                tx.check_validity_window(context.timestamp)
+                    .map_err(|status| VmResult::new(status, None, 0))
+            })
+            .and_then(|_| self.authenticate_batch(context.tenant, &tx))
+            .and_then(|_| self.check_gas(&gas))
+            .and_then(|_| self.check_fee_payer(fee_payer.as_ref(), &gas))
+            .and_then(|_| self.space(context.tenant))
+            .and_then(|space| {
+                self.check_sequence_numbers(&space, Some((tx.senders(), tx.sequence_number())))
+                    .map(|lanes| (space, lanes))
+            }) {
+            Ok(checked) => checked,
+            Err(result) => {
+                span.finish(&result);
+                self.metrics.on_tx_end(TxKind::Batch, &result);
+                return result;
+            }
+        };
 
-        let result = session
-            .execute_script(
-                script,
-                type_args,
-                args,
-                senders,
-                &mut cost_strategy,
-                &NoContextLog::new(),
-            )
+        let (calls, senders) = tx.into_inner();
+
+        let bank = SessionBank::new(&self.bank, context.host_policy()).with_reserved_fee(
+            fee_payer.as_ref(),
+            gas.gas_currency(),
+            max_fee(&gas),
+        );
+        let state_space = self.state.space(space.tenant());
+        let state_session = StateSession::new(&state_space, context)
+            .with_core_address(self.addresses.core_code_address)
+            .with_config_address(self.addresses.config_address)
+            .with_oracle_address(self.addresses.oracle_address);
+        let state = MeteredCache::new(&state_session, &self.metrics);
+        let mut session = self.new_session(&space, &state, &bank);
+        let cost_table = self.cost_table(&space);
+        let mut cost_strategy = self.script_cost_strategy(&cost_table, gas.max_gas_amount());
+
+        let result = calls
+            .into_iter()
+            .try_for_each(|call| {
+                let (module, function, args, type_args) = call.into_inner();
+                session.execute_entry_function(
+                    &module,
+                    function.as_ident_str(),
+                    type_args,
+                    args,
+                    senders.clone(),
+                    &mut cost_strategy,
+                    &NoContextLog::new(),
+                )
+            })
             .and_then(|_| {
                 Self::charge_global_write_gas_usage(&mut cost_strategy, &mut session, &sender)
             });
 
-        self.handle_vm_result(
+        let abort_message = AbortMessage::take(session.extensions());
+        let host_writes = HostWrites::kept(lanes).with_fee(fee_payer.as_ref(), &gas);
+        let mut result = self.handle_vm_result(
+            &space,
             sender,
             cost_strategy,
             gas,
-            result.and_then(|_| session.finish()),
+            bank.check_denied()
+                .and(result)
+                .and_then(|_| session.finish())
+                .and_then(|effects| bank.check_access(&effects).map(|_| effects)),
+            abort_message,
+            host_writes,
+            None,
             dry_run,
-        )
+        );
+        result.price_reads = state_session.take_price_reads();
+        span.finish(&result);
+        self.metrics.on_tx_end(TxKind::Batch, &result);
+        result
     }
+}
 
-    fn clear(&self) {
-        self.vm.clear();
+/// Returns the canonical write set of the host writes.
+fn key_write_set(writes: &[KeyWrite]) -> WriteSet {
+    WriteSet::new(writes.iter().map(|(key, blob)| {
+        let op = match blob {
+            Some(blob) => WriteOp::Value(blob.clone()),
+            None => WriteOp::Deletion,
+        };
+        (key.as_ref().to_vec(), op)
+    }))
+}
+
+/// Returns the fee of the transaction which uses all the gas.
+fn max_fee(gas: &Gas) -> Balance {
+    gas.max_gas_amount() as u128 * gas.gas_unit_price() as u128
+}
+
+/// Write of a storage key, `None` deletes the key.
+type KeyWrite = (AccessKey, Option<Vec<u8>>);
+/// Event of the vm bookkeeping: the address, the type and the message.
+type HostEvent = (AccountAddress, TypeTag, Vec<u8>);
+
+/// Writes of the vm bookkeeping made by a transaction besides its session effects.
+#[derive(Debug, Default)]
+struct HostWrites {
+    /// Applied with the effects of the executed transaction, e.g. the package manifests.
+    executed: Vec<KeyWrite>,
+    /// Applied if the transaction is kept in the block even when it fails,
+    /// e.g. the sequence numbers.
+    kept: Vec<KeyWrite>,
+    /// Emitted with the events of the executed transaction, e.g. the forced upgrade events.
+    events: Vec<HostEvent>,
+    /// Fee payer, the gas unit price and the gas currency. Like the kept writes, the fee is
+    /// charged if the transaction is kept in the block.
+    fee: Option<(AccountAddress, u64, Ticker)>,
+}
+
+impl HostWrites {
+    fn executed(writes: Vec<KeyWrite>) -> HostWrites {
+        HostWrites {
+            executed: writes,
+            kept: vec![],
+            events: vec![],
+            fee: None,
+        }
+    }
+
+    fn kept(writes: Vec<KeyWrite>) -> HostWrites {
+        HostWrites {
+            executed: vec![],
+            kept: writes,
+            events: vec![],
+            fee: None,
+        }
+    }
+
+    fn with_executed(mut self, write: KeyWrite) -> HostWrites {
+        self.executed.push(write);
+        self
+    }
+
+    fn with_event(mut self, event: HostEvent) -> HostWrites {
+        self.events.push(event);
+        self
+    }
+
+    fn with_fee(mut self, fee_payer: Option<&AccountAddress>, gas: &Gas) -> HostWrites {
+        self.fee = fee_payer
+            .map(|fee_payer| (*fee_payer, gas.gas_unit_price(), gas.gas_currency().clone()));
+        self
     }
 }
@@ -0,0 +1,306 @@
+//! Non-fungible tokens.
+//!
+//! Natives of `0x1::NFT`:
+//! `mint(owner: address, id: vector<u8>, data: vector<u8>)`,
+//! `transfer(from: &signer, to: address, id: vector<u8>)` and
+//! `burn(owner: &signer, id: vector<u8>)`.
+//! Tokens are transferred and burned only by the signer owning them. The mint native does not
+//! authorize the minter, the `NFT` module must declare it private.
+//!
+//! Each token is stored under its own key derived from the token id, see `nft_access_path`.
+//! The ids owned by an account are listed in its `NFT::TokenStore` resource.
+//! Operations are kept in the extensions of the session and applied together with the
+//! transaction effects. The transaction fails with `RESOURCE_ALREADY_EXISTS` if a minted id
+//! exists and with `RESOURCE_DOES_NOT_EXIST` if a transferred or burned token is not owned
+//! by the signer.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+
+use diem_crypto::hash::HashValue;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::StructTag;
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::pop_arg;
+use move_vm_types::values::{SignerRef, Value};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use vm::errors::{Location, PartialVMError, PartialVMResult, VMError};
+
+use crate::access_path::AccessPath;
+use crate::account::signer_address;
+use crate::data::{AccessKey, Storage};
+
+/// Module of the token natives.
+pub const NFT_MODULE: &str = "NFT";
+/// Name of the mint native.
+pub const NFT_MINT: &str = "mint";
+/// Name of the transfer native.
+pub const NFT_TRANSFER: &str = "transfer";
+/// Name of the burn native.
+pub const NFT_BURN: &str = "burn";
+/// Resource listing the tokens of an account.
+pub const TOKEN_STORE: &str = "TokenStore";
+/// Path tag of the token keys. Codes and resources use tags 0 and 1.
+pub const NFT_TAG: u8 = 2;
+/// Gas charged for a minted token in internal gas units.
+pub const NFT_MINT_GAS: NativeGasParams = NativeGasParams {
+    base: 1000,
+    per_byte: 1,
+};
+/// Gas charged for a transferred token in internal gas units.
+pub const NFT_TRANSFER_GAS: NativeGasParams = NativeGasParams {
+    base: 500,
+    per_byte: 1,
+};
+/// Gas charged for a burned token in internal gas units.
+pub const NFT_BURN_GAS: NativeGasParams = NativeGasParams {
+    base: 500,
+    per_byte: 1,
+};
+
+/// Non-fungible token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Nft {
+    /// Unique id of the token.
+    pub id: Vec<u8>,
+    /// Owner of the token.
+    pub owner: AccountAddress,
+    /// Token data.
+    pub data: Vec<u8>,
+}
+
+/// Ids of the tokens owned by an account in the order they were received.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenStore {
+    pub ids: Vec<Vec<u8>>,
+}
+
+/// Token operation of the current transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum NftOp {
+    Mint {
+        owner: AccountAddress,
+        id: Vec<u8>,
+        data: Vec<u8>,
+    },
+    Transfer {
+        from: AccountAddress,
+        to: AccountAddress,
+        id: Vec<u8>,
+    },
+    Burn {
+        owner: AccountAddress,
+        id: Vec<u8>,
+    },
+}
+
+/// Token operations of the session, kept in its native extensions.
+#[derive(Debug, Default)]
+pub(crate) struct NftJournal(pub Vec<NftOp>);
+
+/// Returns the tag of `NFT::TokenStore`.
+pub fn token_store_tag(core_address: AccountAddress) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(NFT_MODULE).unwrap(),
+        name: Identifier::new(TOKEN_STORE).unwrap(),
+        type_params: vec![],
+    }
+}
+
+/// Returns the access path of the token with the `id`.
+pub fn nft_access_path(core_address: AccountAddress, id: &[u8]) -> AccessPath {
+    let mut path = Vec::with_capacity(1 + HashValue::LENGTH);
+    path.push(NFT_TAG);
+    path.extend_from_slice(HashValue::sha3_256_of(id).as_ref());
+    AccessPath::new(core_address, path)
+}
+
+/// Registers the token natives at the core address journaling the operations of the session.
+pub(crate) fn register_nft(
+    natives: NativeRegistry,
+    core_address: AccountAddress,
+) -> NativeRegistry {
+    natives
+        .register(
+            core_address,
+            NFT_MODULE,
+            NFT_MINT,
+            NFT_MINT_GAS,
+            |context: &mut dyn NativeContext, _: Vec<Type>, mut args: VecDeque<Value>| {
+                let data = pop_arg!(args, Vec<u8>);
+                let id = pop_arg!(args, Vec<u8>);
+                let owner = pop_arg!(args, AccountAddress);
+                record(context, NftOp::Mint { owner, id, data })
+            },
+        )
+        .register(
+            core_address,
+            NFT_MODULE,
+            NFT_TRANSFER,
+            NFT_TRANSFER_GAS,
+            |context: &mut dyn NativeContext, _: Vec<Type>, mut args: VecDeque<Value>| {
+                let id = pop_arg!(args, Vec<u8>);
+                let to = pop_arg!(args, AccountAddress);
+                let from = signer_address(pop_arg!(args, SignerRef))?;
+                record(context, NftOp::Transfer { from, to, id })
+            },
+        )
+        .register(
+            core_address,
+            NFT_MODULE,
+            NFT_BURN,
+            NFT_BURN_GAS,
+            |context: &mut dyn NativeContext, _: Vec<Type>, mut args: VecDeque<Value>| {
+                let id = pop_arg!(args, Vec<u8>);
+                let owner = signer_address(pop_arg!(args, SignerRef))?;
+                record(context, NftOp::Burn { owner, id })
+            },
+        )
+}
+
+fn record(context: &mut dyn NativeContext, op: NftOp) -> PartialVMResult<NativeResult> {
+    context
+        .extensions()
+        .get_or_default::<NftJournal>()
+        .0
+        .push(op);
+    Ok(NativeResult::ok(GasUnits::new(0), vec![]))
+}
+
+/// Loads the token with the `id`.
+pub fn load_nft<S: Storage>(
+    storage: &S,
+    core_address: AccountAddress,
+    id: &[u8],
+) -> Result<Option<Nft>, VMError> {
+    let key = AccessKey::from(&nft_access_path(core_address, id));
+    storage
+        .get(key.as_ref())
+        .map(|blob| decode(&blob))
+        .transpose()
+}
+
+/// Loads the token store of the `owner`. Returns an empty store if the owner has no tokens.
+pub fn load_token_store<S: Storage>(
+    storage: &S,
+    core_address: AccountAddress,
+    owner: &AccountAddress,
+) -> Result<TokenStore, VMError> {
+    let key = AccessKey::from((owner, &token_store_tag(core_address)));
+    storage
+        .get(key.as_ref())
+        .map(|blob| decode(&blob))
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+/// Writes of the token operations on top of the storage.
+pub(crate) struct NftWrites<'a, S> {
+    storage: &'a S,
+    core_address: AccountAddress,
+    tokens: BTreeMap<Vec<u8>, Option<Nft>>,
+    stores: BTreeMap<AccountAddress, TokenStore>,
+}
+
+impl<'a, S: Storage> NftWrites<'a, S> {
+    pub fn new(storage: &'a S, core_address: AccountAddress) -> NftWrites<'a, S> {
+        NftWrites {
+            storage,
+            core_address,
+            tokens: Default::default(),
+            stores: Default::default(),
+        }
+    }
+
+    /// Applies the operations in order. Fails if any of them is invalid.
+    pub fn apply(&mut self, ops: Vec<NftOp>) -> Result<(), VMError> {
+        for op in ops {
+            match op {
+                NftOp::Mint { owner, id, data } => {
+                    if self.token(&id)?.is_some() {
+                        return Err(error(StatusCode::RESOURCE_ALREADY_EXISTS));
+                    }
+                    self.store(&owner)?.ids.push(id.clone());
+                    self.tokens
+                        .insert(id.clone(), Some(Nft { id, owner, data }));
+                }
+                NftOp::Transfer { from, to, id } => {
+                    let mut token = self.take_owned(&from, &id)?;
+                    token.owner = to;
+                    self.store(&to)?.ids.push(id.clone());
+                    self.tokens.insert(id, Some(token));
+                }
+                NftOp::Burn { owner, id } => {
+                    self.take_owned(&owner, &id)?;
+                    self.tokens.insert(id, None);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the storage writes of the applied operations.
+    pub fn into_writes(self) -> Vec<(AccessKey, Option<Vec<u8>>)> {
+        let core_address = self.core_address;
+        let tokens = self.tokens.into_iter().map(|(id, token)| {
+            (
+                AccessKey::from(&nft_access_path(core_address, &id)),
+                token.map(|token| encode(&token)),
+            )
+        });
+        let stores = self.stores.into_iter().map(|(owner, store)| {
+            let key = AccessKey::from((&owner, &token_store_tag(core_address)));
+            if store.ids.is_empty() {
+                (key, None)
+            } else {
+                (key, Some(encode(&store)))
+            }
+        });
+        tokens.chain(stores).collect()
+    }
+
+    fn token(&mut self, id: &[u8]) -> Result<Option<Nft>, VMError> {
+        match self.tokens.get(id) {
+            Some(token) => Ok(token.clone()),
+            None => load_nft(self.storage, self.core_address, id),
+        }
+    }
+
+    fn store(&mut self, owner: &AccountAddress) -> Result<&mut TokenStore, VMError> {
+        if !self.stores.contains_key(owner) {
+            let store = load_token_store(self.storage, self.core_address, owner)?;
+            self.stores.insert(*owner, store);
+        }
+        Ok(self.stores.get_mut(owner).expect("Loaded store"))
+    }
+
+    /// Removes the token from the store of the owner.
+    fn take_owned(&mut self, owner: &AccountAddress, id: &[u8]) -> Result<Nft, VMError> {
+        let token = match self.token(id)? {
+            Some(token) if &token.owner == owner => token,
+            _ => return Err(error(StatusCode::RESOURCE_DOES_NOT_EXIST)),
+        };
+        let store = self.store(owner)?;
+        store.ids.retain(|owned| owned.as_slice() != id);
+        Ok(token)
+    }
+}
+
+fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+    bcs::to_bytes(value).expect("Token serialization must not fail")
+}
+
+fn decode<T: DeserializeOwned>(blob: &[u8]) -> Result<T, VMError> {
+    bcs::from_bytes(blob).map_err(|_| error(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE))
+}
+
+fn error(status: StatusCode) -> VMError {
+    PartialVMError::new(status).finish(Location::Undefined)
+}
@@ -0,0 +1,244 @@
+//! Oracle native.
+//!
+//! `0x1::Oracle::get_price<A, B>(): u128` returns the price of the `A_B` currency pair
+//! if the on-chain `OracleAcl` allows the calling module to read it.
+//! `0x1::Oracle::get_twap<A, B>(window: u64): u128` returns the time-weighted average price
+//! over the last `window` seconds under the same restrictions.
+//! Modules written for the Diem-style oracle read the price of a ticker with
+//! `borrow_global<Oracle::Price<Ticker>>(oracle_address)`, where `Ticker` is a struct named
+//! after the ticker and `oracle_address` is set by `AddressesConfig`. The resource holds a single
+//! `u128` and is synthesized from the host oracle.
+//! Prices read with `borrow_global` are restricted by the default access of the list: the storage
+//! doesn't know the calling module, so the module entries apply to the natives only.
+//!
+//! The average price is provided by the host oracle or computed from the `PriceHistory`
+//! recorded under the config address with `Mvm::record_price`.
+
+use alloc::collections::VecDeque;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::pop_arg;
+use move_vm_types::values::Value;
+use parity_scale_codec::{Decode, Encode};
+use vm::errors::{PartialVMError, PartialVMResult};
+
+use crate::data::{decode_price, pair_ticker, price_tag};
+use crate::vm_config::loader::oracle_acl_tag;
+use crate::vm_config::OracleAcl;
+
+/// Module of the oracle native.
+pub const ORACLE_MODULE: &str = "Oracle";
+/// Default address of the synthesized `Oracle::Price<Ticker>` resources.
+pub const ORACLE_ADDRESS: AccountAddress = CORE_CODE_ADDRESS;
+/// Name of the oracle native.
+pub const ORACLE_GET_PRICE: &str = "get_price";
+/// Name of the average price native.
+pub const ORACLE_GET_TWAP: &str = "get_twap";
+/// Abort code of a read denied by the access control list.
+pub const ORACLE_ACCESS_DENIED: u64 = 1;
+/// Abort code of a read of a missing price.
+pub const PRICE_NOT_FOUND: u64 = 2;
+/// Gas charged for a price read in internal gas units.
+pub const ORACLE_GET_PRICE_GAS: NativeGasParams = NativeGasParams {
+    base: 1000,
+    per_byte: 0,
+};
+/// Gas charged for an average price read in internal gas units.
+pub const ORACLE_GET_TWAP_GAS: NativeGasParams = NativeGasParams {
+    base: 2000,
+    per_byte: 0,
+};
+/// Maximum number of samples kept in a `PriceHistory`.
+pub const PRICE_HISTORY_CAPACITY: usize = 64;
+/// Module and struct name of the price history resource.
+const PRICE_HISTORY: &str = "PriceHistory";
+
+/// Ring buffer of the oracle prices of a currency pair ordered by time.
+/// Stored under the config address as `PriceHistory::PriceHistory<A, B>`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct PriceHistory {
+    samples: Vec<(u64, u128)>,
+}
+
+impl PriceHistory {
+    /// Returns `(timestamp, price)` samples ordered by time.
+    pub fn samples(&self) -> &[(u64, u128)] {
+        &self.samples
+    }
+
+    /// Appends the price observed at `timestamp`, dropping the oldest sample if the buffer is full.
+    /// A sample with the timestamp of the last one replaces it.
+    /// Returns `false` if `timestamp` precedes the last sample.
+    pub fn push(&mut self, timestamp: u64, price: u128) -> bool {
+        match self.samples.last_mut() {
+            Some(last) if last.0 > timestamp => return false,
+            Some(last) if last.0 == timestamp => last.1 = price,
+            _ => {
+                if self.samples.len() == PRICE_HISTORY_CAPACITY {
+                    self.samples.remove(0);
+                }
+                self.samples.push((timestamp, price));
+            }
+        }
+        true
+    }
+
+    /// Returns the average price over the `window` seconds preceding `now`.
+    /// Each price holds until the next sample. Time before the first sample is not counted.
+    pub fn twap(&self, now: u64, window: u64) -> Option<u128> {
+        let start = now.saturating_sub(window);
+        let mut sum = 0u128;
+        let mut covered = 0u64;
+        let mut last = None;
+        for (idx, (timestamp, price)) in self.samples.iter().enumerate() {
+            if *timestamp > now {
+                break;
+            }
+            let end = self
+                .samples
+                .get(idx + 1)
+                .map(|(next, _)| (*next).min(now))
+                .unwrap_or(now);
+            let from = (*timestamp).max(start);
+            if end > from {
+                let duration = end - from;
+                sum = sum.checked_add(price.checked_mul(duration as u128)?)?;
+                covered += duration;
+            }
+            last = Some(*price);
+        }
+        if covered == 0 {
+            last
+        } else {
+            Some(sum / covered as u128)
+        }
+    }
+}
+
+/// Returns the tag of the price history of the currency pair.
+pub fn history_tag(config_address: AccountAddress, type_params: Vec<TypeTag>) -> StructTag {
+    StructTag {
+        address: config_address,
+        module: Identifier::new(PRICE_HISTORY).unwrap(),
+        name: Identifier::new(PRICE_HISTORY).unwrap(),
+        type_params,
+    }
+}
+
+/// Registers `Oracle::get_price` and `Oracle::get_twap` at the core address.
+/// The access control list is read from the config address.
+pub(crate) fn register_oracle(
+    natives: NativeRegistry,
+    core_address: AccountAddress,
+    config_address: AccountAddress,
+) -> NativeRegistry {
+    natives
+        .register(
+            core_address,
+            ORACLE_MODULE,
+            ORACLE_GET_PRICE,
+            ORACLE_GET_PRICE_GAS,
+            move |ctx: &mut dyn NativeContext, ty_args: Vec<Type>, _: VecDeque<Value>| {
+                get_price(ctx, ty_args, core_address, config_address)
+            },
+        )
+        .register(
+            core_address,
+            ORACLE_MODULE,
+            ORACLE_GET_TWAP,
+            ORACLE_GET_TWAP_GAS,
+            move |ctx: &mut dyn NativeContext, ty_args: Vec<Type>, args: VecDeque<Value>| {
+                get_twap(ctx, ty_args, args, core_address, config_address)
+            },
+        )
+}
+
+fn get_price(
+    ctx: &mut dyn NativeContext,
+    ty_args: Vec<Type>,
+    core_address: AccountAddress,
+    config_address: AccountAddress,
+) -> PartialVMResult<NativeResult> {
+    let cost = GasUnits::new(0);
+    let (first, second) = match allowed_pair(ctx, ty_args, config_address)? {
+        Ok(pair) => pair,
+        Err(code) => return Ok(NativeResult::err(cost, code)),
+    };
+
+    let price = ctx
+        .read_remote_resource(&core_address, &price_tag(core_address, first, second))?
+        .and_then(|blob| decode_price(&blob));
+    Ok(match price {
+        Some(price) => NativeResult::ok(cost, vec![Value::u128(price)]),
+        None => NativeResult::err(cost, PRICE_NOT_FOUND),
+    })
+}
+
+fn get_twap(
+    ctx: &mut dyn NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+    core_address: AccountAddress,
+    config_address: AccountAddress,
+) -> PartialVMResult<NativeResult> {
+    let cost = GasUnits::new(0);
+    let window = pop_arg!(args, u64);
+    let (first, second) = match allowed_pair(ctx, ty_args, config_address)? {
+        Ok(pair) => pair,
+        Err(code) => return Ok(NativeResult::err(cost, code)),
+    };
+
+    Ok(
+        match ctx.get_twap(&price_tag(core_address, first, second), window)? {
+            Some(price) => NativeResult::ok(cost, vec![Value::u128(price)]),
+            None => NativeResult::err(cost, PRICE_NOT_FOUND),
+        },
+    )
+}
+
+/// Returns the currency pair of the type arguments,
+/// or the abort code if the caller is not allowed to read its price.
+fn allowed_pair(
+    ctx: &dyn NativeContext,
+    ty_args: Vec<Type>,
+    config_address: AccountAddress,
+) -> PartialVMResult<Result<(TypeTag, TypeTag), u64>> {
+    if ty_args.len() != 2 {
+        return Err(PartialVMError::new(
+            StatusCode::NUMBER_OF_TYPE_ARGUMENTS_MISMATCH,
+        ));
+    }
+    let first = ctx.type_to_type_tag(&ty_args[0])?;
+    let second = ctx.type_to_type_tag(&ty_args[1])?;
+    let ticker = match pair_ticker(&first, &second) {
+        Some(ticker) => ticker,
+        None => return Ok(Err(PRICE_NOT_FOUND)),
+    };
+
+    let acl = decode_oracle_acl(
+        ctx.read_remote_resource(&config_address, &oracle_acl_tag(config_address))?,
+    )?;
+    if !acl.access(ctx.caller()).allows(&ticker) {
+        return Ok(Err(ORACLE_ACCESS_DENIED));
+    }
+    Ok(Ok((first, second)))
+}
+
+/// Decodes the stored access control list, a missing list allows any access.
+pub(crate) fn decode_oracle_acl(blob: Option<Vec<u8>>) -> PartialVMResult<OracleAcl> {
+    match blob {
+        Some(blob) => OracleAcl::decode(&mut blob.as_slice()).map_err(|err| {
+            PartialVMError::new(StatusCode::STORAGE_ERROR).with_message(err.to_string())
+        }),
+        None => Ok(OracleAcl::default()),
+    }
+}
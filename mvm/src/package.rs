@@ -0,0 +1,143 @@
+//! Module packages.
+//!
+//! A package groups the modules of an account released together under a shared version.
+//! Manifests of the packages of an account are stored in its `Packages::Manifests` resource.
+//! The upgrade policy of a package is enforced on every publish of its modules:
+//! the modules of a package can only be replaced by publishing the package again.
+
+use alloc::vec::Vec;
+
+use anyhow::Error;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::{IdentStr, Identifier};
+use move_core_types::language_storage::StructTag;
+use move_vm_runtime::session::ModuleUpgrade;
+use serde::{Deserialize, Serialize};
+
+use crate::data::{AccessKey, Storage};
+
+/// Module of the package manifests.
+pub const PACKAGES_MODULE: &str = "Packages";
+/// Resource with the package manifests of an account.
+pub const MANIFESTS: &str = "Manifests";
+
+/// Upgrade policy of a package ordered from the weakest to the strictest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum UpgradePolicy {
+    /// Modules are replaced without checks.
+    Arbitrary,
+    /// Modules must keep their structs and public functions.
+    Compatible,
+    /// The package can not be upgraded.
+    Immutable,
+}
+
+impl UpgradePolicy {
+    /// Returns how the modules of the package are replaced, `None` if they can't be.
+    pub fn module_upgrade(&self) -> Option<ModuleUpgrade> {
+        match self {
+            UpgradePolicy::Arbitrary => Some(ModuleUpgrade::Arbitrary),
+            UpgradePolicy::Compatible => Some(ModuleUpgrade::Compatible),
+            UpgradePolicy::Immutable => None,
+        }
+    }
+}
+
+/// Manifest of a published package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageManifest {
+    pub name: Identifier,
+    /// Version of the package, starting at 1.
+    pub version: u64,
+    pub policy: UpgradePolicy,
+    /// Names of the package modules in the publish order.
+    pub modules: Vec<Identifier>,
+}
+
+impl PackageManifest {
+    /// Returns `true` if the module belongs to the package.
+    pub fn contains(&self, module: &IdentStr) -> bool {
+        self.modules
+            .iter()
+            .any(|name| name.as_ident_str() == module)
+    }
+}
+
+/// Package manifests of an account ordered by the package name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Packages {
+    pub packages: Vec<PackageManifest>,
+}
+
+impl Packages {
+    /// Returns the manifest of the package.
+    pub fn package(&self, name: &IdentStr) -> Option<&PackageManifest> {
+        self.packages
+            .binary_search_by(|package| package.name.as_ident_str().cmp(name))
+            .ok()
+            .map(|idx| &self.packages[idx])
+    }
+
+    /// Returns the manifest of the package the module belongs to.
+    pub fn package_of(&self, module: &IdentStr) -> Option<&PackageManifest> {
+        self.packages
+            .iter()
+            .find(|package| package.contains(module))
+    }
+
+    /// Replaces the manifest of the package.
+    pub fn insert(&mut self, manifest: PackageManifest) {
+        match self
+            .packages
+            .binary_search_by(|package| package.name.cmp(&manifest.name))
+        {
+            Ok(idx) => self.packages[idx] = manifest,
+            Err(idx) => self.packages.insert(idx, manifest),
+        }
+    }
+}
+
+/// Release of a package checked against its published manifest.
+pub(crate) struct PackageRelease {
+    pub manifest: PackageManifest,
+    /// How the modules of the previous release are replaced and their names.
+    pub upgrade: Option<(ModuleUpgrade, Vec<Identifier>)>,
+}
+
+impl PackageRelease {
+    /// Returns how the module at the index of the release replaces the published one.
+    pub fn module_upgrade(&self, idx: usize) -> ModuleUpgrade {
+        match (&self.upgrade, self.manifest.modules.get(idx)) {
+            (Some((upgrade, previous)), Some(name)) if previous.contains(name) => *upgrade,
+            _ => ModuleUpgrade::Forbidden,
+        }
+    }
+}
+
+/// Returns the tag of `Packages::Manifests`.
+pub fn packages_tag(core_address: AccountAddress) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(PACKAGES_MODULE).unwrap(),
+        name: Identifier::new(MANIFESTS).unwrap(),
+        type_params: vec![],
+    }
+}
+
+/// Returns the storage key of the package manifests of the account.
+pub fn packages_key(core_address: AccountAddress, address: &AccountAddress) -> AccessKey {
+    AccessKey::from((address, &packages_tag(core_address)))
+}
+
+/// Loads the package manifests of the account.
+pub fn load_packages<S: Storage>(
+    storage: &S,
+    core_address: AccountAddress,
+    address: &AccountAddress,
+) -> Result<Packages, Error> {
+    match storage.get(packages_key(core_address, address).as_ref()) {
+        Some(blob) => bcs::from_bytes(&blob).map_err(Error::msg),
+        None => Ok(Packages::default()),
+    }
+}
@@ -0,0 +1,36 @@
+//! Panic safety of the host calls.
+//!
+//! Calls into `Storage`, `Oracle`, `BalanceAccess` and `EventHandler` run through `guard`:
+//! with the `std` feature a panic of the host code is caught and reported as
+//! `UNKNOWN_INVARIANT_VIOLATION_ERROR` instead of unwinding through the vm.
+//! Without `std` panics can't be caught and the calls are made directly.
+//! Storage writes are not guarded: the vm can't recover from a half-applied write.
+
+use alloc::string::String;
+
+use move_core_types::vm_status::StatusCode;
+use move_vm_types::natives::function::PartialVMError;
+use vm::errors::PartialVMResult;
+
+/// Calls the host code, converting its panic into an invariant violation.
+#[cfg(feature = "std")]
+pub(crate) fn guard<R>(host: &str, f: impl FnOnce() -> R) -> PartialVMResult<R> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|payload| {
+        let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+            String::from(*msg)
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            String::from("unknown panic")
+        };
+        log::error!("{} panicked: {}", host, msg);
+        PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+            .with_message(format!("{} panicked: {}", host, msg))
+    })
+}
+
+/// Calls the host code.
+#[cfg(not(feature = "std"))]
+pub(crate) fn guard<R>(_host: &str, f: impl FnOnce() -> R) -> PartialVMResult<R> {
+    Ok(f())
+}
@@ -0,0 +1,36 @@
+//! Storage inclusion proofs.
+//!
+//! Storages backed by an authenticated structure, e.g. the merkle trie of the host chain,
+//! implement `StateProof` to prove the values of their keys.
+//! The proof format is defined by the storage: light clients check it with the verifier of
+//! the host chain against a trusted state root.
+
+use alloc::vec::Vec;
+
+use crate::hash::Digest;
+
+/// Storage providing inclusion proofs of its keys.
+pub trait StateProof {
+    /// Returns the proof of the current value of the key or of its absence.
+    /// Returns `None` if the storage can't prove the key, e.g. before the state root is computed.
+    fn prove(&self, key: &[u8]) -> Option<StorageProof>;
+}
+
+/// Proof of a storage key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageProof {
+    /// State root the proof is built against.
+    pub state_root: Digest,
+    /// Storage specific proof nodes.
+    pub nodes: Vec<Vec<u8>>,
+}
+
+/// Resource with the proof of its storage key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceProof {
+    /// Storage key of the resource.
+    pub key: Vec<u8>,
+    /// Serialized resource or `None` if the resource doesn't exist.
+    pub value: Option<Vec<u8>>,
+    pub proof: StorageProof,
+}
@@ -0,0 +1,187 @@
+//! Module publish history.
+//!
+//! Every publish of a module writes its next version, starting at 1.
+//! Versions of the modules of an account are stored in its `Modules::Versions` resource.
+//! Each publish emits `Modules::ModulePublished` at the module address with the BCS encoded
+//! `ModulePublishedEvent` message.
+//! Modules published as immutable are listed in the `Modules::Immutable` resource of the account
+//! and can never be replaced, except by the governance with `Mvm::force_publish_module`.
+//! A forced publish additionally emits `Modules::ForcedUpgrade` at the module address with the
+//! BCS encoded `ForcedUpgradeEvent` message.
+
+use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
+
+use anyhow::Error;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::{IdentStr, Identifier};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{AccessKey, Storage};
+use crate::hash::Digest;
+
+/// Module of the publish history.
+pub const MODULES_MODULE: &str = "Modules";
+/// Resource with the module versions of an account.
+pub const VERSIONS: &str = "Versions";
+/// Resource with the immutable modules of an account.
+pub const IMMUTABLE: &str = "Immutable";
+/// Name of the publish event.
+pub const MODULE_PUBLISHED_EVENT: &str = "ModulePublished";
+/// Name of the forced publish event.
+pub const FORCED_UPGRADE_EVENT: &str = "ForcedUpgrade";
+
+/// Message of the publish event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModulePublishedEvent {
+    pub id: ModuleId,
+    /// Digest of the uncompressed module bytecode, see `hash::module_hash`.
+    pub hash: Digest,
+    pub version: u64,
+}
+
+/// Message of the forced publish event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForcedUpgradeEvent {
+    pub id: ModuleId,
+    /// Digest of the replaced module bytecode or `None` if the module was not published.
+    pub old_hash: Option<Digest>,
+    /// Digest of the published module bytecode.
+    pub new_hash: Digest,
+}
+
+/// Version of a module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleVersion {
+    pub name: Identifier,
+    pub version: u64,
+}
+
+/// Versions of the modules of an account ordered by the module name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleVersions {
+    pub modules: Vec<ModuleVersion>,
+}
+
+impl ModuleVersions {
+    /// Returns the version of the module or 0 if it was never published.
+    pub fn version(&self, name: &IdentStr) -> u64 {
+        self.modules
+            .binary_search_by(|module| module.name.as_ident_str().cmp(name))
+            .map(|idx| self.modules[idx].version)
+            .unwrap_or(0)
+    }
+
+    /// Increments the version of the module and returns the new version.
+    pub fn bump(&mut self, name: &IdentStr) -> u64 {
+        match self
+            .modules
+            .binary_search_by(|module| module.name.as_ident_str().cmp(name))
+        {
+            Ok(idx) => {
+                self.modules[idx].version += 1;
+                self.modules[idx].version
+            }
+            Err(idx) => {
+                self.modules.insert(
+                    idx,
+                    ModuleVersion {
+                        name: name.to_owned(),
+                        version: 1,
+                    },
+                );
+                1
+            }
+        }
+    }
+}
+
+/// Immutable modules of an account ordered by the module name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImmutableModules {
+    pub modules: Vec<Identifier>,
+}
+
+impl ImmutableModules {
+    /// Returns `true` if the module is immutable.
+    pub fn contains(&self, name: &IdentStr) -> bool {
+        self.modules
+            .binary_search_by(|module| module.as_ident_str().cmp(name))
+            .is_ok()
+    }
+
+    /// Marks the module immutable.
+    pub fn insert(&mut self, name: &IdentStr) {
+        if let Err(idx) = self
+            .modules
+            .binary_search_by(|module| module.as_ident_str().cmp(name))
+        {
+            self.modules.insert(idx, name.to_owned());
+        }
+    }
+}
+
+/// Returns the tag of `Modules::Versions`.
+pub fn versions_tag(core_address: AccountAddress) -> StructTag {
+    modules_tag(core_address, VERSIONS)
+}
+
+/// Returns the tag of `Modules::Immutable`.
+pub fn immutable_tag(core_address: AccountAddress) -> StructTag {
+    modules_tag(core_address, IMMUTABLE)
+}
+
+/// Returns the type of the publish event.
+pub fn module_published_tag(core_address: AccountAddress) -> TypeTag {
+    TypeTag::Struct(modules_tag(core_address, MODULE_PUBLISHED_EVENT))
+}
+
+/// Returns the type of the forced publish event.
+pub fn forced_upgrade_tag(core_address: AccountAddress) -> TypeTag {
+    TypeTag::Struct(modules_tag(core_address, FORCED_UPGRADE_EVENT))
+}
+
+fn modules_tag(core_address: AccountAddress, name: &str) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(MODULES_MODULE).unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params: vec![],
+    }
+}
+
+/// Returns the storage key of the module versions of the account.
+pub fn versions_key(core_address: AccountAddress, address: &AccountAddress) -> AccessKey {
+    AccessKey::from((address, &versions_tag(core_address)))
+}
+
+/// Loads the module versions of the account.
+pub fn load_module_versions<S: Storage>(
+    storage: &S,
+    core_address: AccountAddress,
+    address: &AccountAddress,
+) -> Result<ModuleVersions, Error> {
+    match storage.get(versions_key(core_address, address).as_ref()) {
+        Some(blob) => bcs::from_bytes(&blob).map_err(Error::msg),
+        None => Ok(ModuleVersions::default()),
+    }
+}
+
+/// Returns the storage key of the immutable modules of the account.
+pub fn immutable_key(core_address: AccountAddress, address: &AccountAddress) -> AccessKey {
+    AccessKey::from((address, &immutable_tag(core_address)))
+}
+
+/// Loads the immutable modules of the account.
+pub fn load_immutable_modules<S: Storage>(
+    storage: &S,
+    core_address: AccountAddress,
+    address: &AccountAddress,
+) -> Result<ImmutableModules, Error> {
+    match storage.get(immutable_key(core_address, address).as_ref()) {
+        Some(blob) => bcs::from_bytes(&blob).map_err(Error::msg),
+        None => Ok(ImmutableModules::default()),
+    }
+}
@@ -0,0 +1,142 @@
+//! Source maps of the published modules.
+//!
+//! A module may be published with a BCS encoded `SourceMap`, see `ModuleTx::with_source_map`.
+//! Source maps are debug data and are not part of the consensus state: the host keeps them in
+//! the storage set with `Mvm::with_source_map_storage` under `source_map_access_path`, and the
+//! next publish of the module replaces or removes them.
+//! Failed transactions report the bytecode location of the error in `VmResult::location`
+//! and, if the module has a source map, the Move source line in `VmResult::source_location`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use anyhow::Error;
+use move_core_types::language_storage::ModuleId;
+use serde::{Deserialize, Serialize};
+use vm::errors::{Location, VMError};
+
+use crate::access_path::AccessPath;
+use crate::data::{AccessKey, Storage};
+
+/// Path tag of the source maps. Codes, resources and tokens use tags 0, 1 and 2.
+pub const SOURCE_MAP_TAG: u8 = 3;
+
+/// Source lines of a function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionSourceMap {
+    /// Function definition index.
+    pub index: u16,
+    /// Source line of each code offset of the function.
+    pub lines: Vec<u32>,
+}
+
+/// Source map of a module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceMap {
+    /// Name of the Move source file.
+    pub file: String,
+    /// Functions ordered by the definition index.
+    pub functions: Vec<FunctionSourceMap>,
+}
+
+impl SourceMap {
+    /// Decodes the BCS encoded source map.
+    pub fn decode(blob: &[u8]) -> Result<SourceMap, Error> {
+        bcs::from_bytes(blob).map_err(Error::msg)
+    }
+
+    /// Returns the source line of the code offset of the function.
+    pub fn line(&self, function: u16, code_offset: u16) -> Option<u32> {
+        let idx = self
+            .functions
+            .binary_search_by_key(&function, |function| function.index)
+            .ok()?;
+        self.functions[idx].lines.get(code_offset as usize).copied()
+    }
+}
+
+/// Bytecode location of an error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorLocation {
+    pub module: ModuleId,
+    /// Function definition index.
+    pub function: u16,
+    pub code_offset: u16,
+}
+
+impl ErrorLocation {
+    /// Returns the innermost module location of the error.
+    pub fn of(err: &VMError) -> Option<ErrorLocation> {
+        match err.location() {
+            Location::Module(module) => {
+                let (function, code_offset) = err.offsets().first()?;
+                Some(ErrorLocation {
+                    module: module.clone(),
+                    function: function.0,
+                    code_offset: *code_offset,
+                })
+            }
+            Location::Undefined | Location::Script => None,
+        }
+    }
+
+    /// Resolves the source location with the source map of the module.
+    pub fn resolve(&self, source_map: &SourceMap) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            file: source_map.file.clone(),
+            line: source_map.line(self.function, self.code_offset)?,
+        })
+    }
+}
+
+impl fmt::Display for ErrorLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}::{}#{}:{}",
+            self.module.address(),
+            self.module.name(),
+            self.function,
+            self.code_offset
+        )
+    }
+}
+
+/// Location of an error in the Move source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Returns the access path of the source map of the module.
+pub fn source_map_access_path(id: &ModuleId) -> AccessPath {
+    let name = id.name().as_bytes();
+    let mut path = Vec::with_capacity(1 + name.len());
+    path.push(SOURCE_MAP_TAG);
+    path.extend_from_slice(name);
+    AccessPath::new(*id.address(), path)
+}
+
+/// Returns the storage key of the source map of the module.
+pub fn source_map_key(id: &ModuleId) -> AccessKey {
+    AccessKey::from(&source_map_access_path(id))
+}
+
+/// Loads the source map of the module.
+pub fn load_source_map<S: Storage + ?Sized>(
+    storage: &S,
+    id: &ModuleId,
+) -> Result<Option<SourceMap>, Error> {
+    storage
+        .get(source_map_key(id).as_ref())
+        .map(|blob| SourceMap::decode(&blob))
+        .transpose()
+}
@@ -0,0 +1,302 @@
+//! Speculative execution cache.
+//!
+//! A script simulated with `Mvm::simulate_script` keeps its effects under the
+//! signing message of the transaction and the state root it was executed on.
+//! `Mvm::execute_speculated` applies the kept effects instead of executing the script again
+//! if the block is built on the same state with the same execution context, and the oracle
+//! and the bank still return the values read by the simulation.
+//! Entries are dropped once applied, when the effects of any transaction are applied on
+//! their state root and by `Mvm::invalidate_speculative_cache`.
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use move_core_types::value::MoveTypeLayout;
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::TransactionEffects;
+use move_vm_types::natives::balance::{Balance, MintBurnJournal, WalletId};
+use move_vm_types::values::Value;
+use spin::Mutex;
+use vm::errors::{Location, PartialVMError, VMError};
+
+use crate::auth::{key_writes, KeyRotations};
+use crate::bridge::{Message, Outbox};
+use crate::coin_bridge::{locked_writes, CoinFlow, CoinFlows};
+use crate::data::{AccessKey, ExecutionContext, OracleRead};
+use crate::event_handle::EventCounters;
+use crate::hash::Digest;
+use crate::metrics::SerializationStats;
+use crate::nft::{NftJournal, NftOp};
+use crate::types::{BalanceChange, PriceRead};
+
+/// Transaction effects with the serialized values.
+/// Unlike `TransactionEffects` they can be kept between transactions.
+/// Values are serialized into one buffer sized by a size estimation pass.
+pub(crate) struct SerializedEffects {
+    /// Serialized values of the resources and the events.
+    pub buffer: Vec<u8>,
+    /// Resources written by the session followed by the locked coins of the bridge and
+    /// the rotated keys.
+    pub resources: Vec<(AccessKey, Option<Range<usize>>)>,
+    pub modules: Vec<(ModuleId, Arc<[u8]>)>,
+    pub events: Vec<(
+        AccountAddress,
+        TypeTag,
+        MoveTypeLayout,
+        Range<usize>,
+        Option<ModuleId>,
+    )>,
+    /// Balance changes ordered by wallet id.
+    pub balance_changes: Vec<BalanceChange>,
+    /// Token operations in the execution order.
+    pub nft_ops: Vec<NftOp>,
+    /// Messages sent with `Bridge::send` in the sending order.
+    pub messages: Vec<Message>,
+    /// Creation numbers of the next event handles of the accounts.
+    pub event_counters: BTreeMap<AccountAddress, u64>,
+    /// Coin flows of the bridge by the currency.
+    pub coin_flows: BTreeMap<StructTag, CoinFlow>,
+    /// Currencies minted or burned with the capability of the native balances.
+    pub minted: BTreeSet<StructTag>,
+    pub stats: SerializationStats,
+}
+
+impl SerializedEffects {
+    pub fn new(
+        mut tx_effects: TransactionEffects,
+        core_address: AccountAddress,
+    ) -> Result<SerializedEffects, VMError> {
+        let mut stats = SerializationStats::default();
+        let values = tx_effects
+            .resources
+            .iter()
+            .flat_map(|(_, vals)| vals.iter())
+            .filter_map(|(_, val_opt)| val_opt.as_ref())
+            .map(|(ty_layout, val)| (ty_layout, val))
+            .chain(
+                tx_effects
+                    .events
+                    .iter()
+                    .map(|(_, _, ty_layout, val, _)| (ty_layout, val)),
+            );
+        for (ty_layout, val) in values {
+            let value_size = val
+                .serialized_size(ty_layout)
+                .ok_or_else(serialization_error)?;
+            stats.add_value(value_size);
+        }
+
+        let mut buffer = Vec::with_capacity(stats.bytes);
+        if stats.bytes > 0 {
+            stats.allocations = 1;
+        }
+        let mut resources = Vec::new();
+        for (addr, vals) in tx_effects.resources {
+            for (struct_tag, val_opt) in vals {
+                let key = AccessKey::from((&addr, struct_tag.as_ref()));
+                let range = match val_opt {
+                    None => None,
+                    Some((ty_layout, val)) => Some(serialize(&mut buffer, &ty_layout, &val)?),
+                };
+                resources.push((key, range));
+            }
+        }
+
+        let events = tx_effects
+            .events
+            .into_iter()
+            .map(|(address, ty_tag, ty_layout, val, caller)| {
+                let range = serialize(&mut buffer, &ty_layout, &val)?;
+                Ok((address, ty_tag, ty_layout, range, caller))
+            })
+            .collect::<Result<_, VMError>>()?;
+
+        let balance_changes = tx_effects
+            .wallet_ops
+            .into_iter()
+            .map(|(wallet_id, operation)| BalanceChange {
+                wallet_id,
+                operation,
+            })
+            .collect::<Vec<_>>();
+
+        let nft_ops = tx_effects
+            .extensions
+            .remove::<NftJournal>()
+            .map(|journal| journal.0)
+            .unwrap_or_default();
+        let messages = tx_effects
+            .extensions
+            .remove::<Outbox>()
+            .map(|outbox| outbox.0)
+            .unwrap_or_default();
+        let event_counters = tx_effects
+            .extensions
+            .remove::<EventCounters>()
+            .map(|counters| counters.0)
+            .unwrap_or_default();
+        let coin_flows = tx_effects
+            .extensions
+            .remove::<CoinFlows>()
+            .map(|flows| flows.0)
+            .unwrap_or_default();
+        let minted = tx_effects
+            .extensions
+            .remove::<MintBurnJournal>()
+            .map(|journal| journal.0)
+            .unwrap_or_default();
+        let rotations = tx_effects
+            .extensions
+            .remove::<KeyRotations>()
+            .map(|rotations| rotations.0)
+            .unwrap_or_default();
+        let host_resources = locked_writes(core_address, &coin_flows)?
+            .into_iter()
+            .chain(key_writes(core_address, rotations));
+        for (key, blob) in host_resources {
+            let range = blob.map(|blob| {
+                let start = buffer.len();
+                buffer.extend_from_slice(&blob);
+                start..buffer.len()
+            });
+            resources.push((key, range));
+        }
+
+        Ok(SerializedEffects {
+            buffer,
+            resources,
+            modules: tx_effects.modules,
+            events,
+            balance_changes,
+            nft_ops,
+            messages,
+            event_counters,
+            coin_flows,
+            minted,
+            stats,
+        })
+    }
+}
+
+fn serialize(
+    buffer: &mut Vec<u8>,
+    ty_layout: &MoveTypeLayout,
+    val: &Value,
+) -> Result<Range<usize>, VMError> {
+    let start = buffer.len();
+    val.simple_serialize_into(ty_layout, buffer)
+        .ok_or_else(serialization_error)?;
+    Ok(start..buffer.len())
+}
+
+fn serialization_error() -> VMError {
+    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR).finish(Location::Undefined)
+}
+
+/// Key of the speculative result: the signing message of the transaction and the state root.
+pub type SpeculationKey = (Digest, Digest);
+
+/// Host values read by the simulated transaction which the state root does not cover.
+#[derive(Debug, Default)]
+pub(crate) struct HostReads {
+    pub oracle: Vec<OracleRead>,
+    pub balances: Vec<(WalletId, Option<Balance>)>,
+}
+
+/// Dry run to keep in the speculative cache.
+pub(crate) struct Simulation {
+    pub key: SpeculationKey,
+    pub context: ExecutionContext,
+    pub price_reads: Vec<PriceRead>,
+    pub reads: HostReads,
+}
+
+/// Effects of the simulated transaction.
+pub(crate) struct Speculation {
+    pub context: ExecutionContext,
+    pub max_gas_amount: u64,
+    pub sender: AccountAddress,
+    pub gas_used: u64,
+    pub result: Result<SerializedEffects, VMError>,
+    pub abort_message: Option<String>,
+    pub price_reads: Vec<PriceRead>,
+    pub reads: HostReads,
+}
+
+impl Speculation {
+    /// Returns `true` if the speculation was executed with the context and the gas limit.
+    pub fn matches(&self, context: &ExecutionContext, max_gas_amount: u64) -> bool {
+        self.context.timestamp == context.timestamp
+            && self.context.block_height == context.block_height
+            && self.context.tenant == context.tenant
+            && self.max_gas_amount == max_gas_amount
+    }
+}
+
+/// Bounded cache of the speculative results. The oldest entries are evicted first.
+pub(crate) struct SpeculativeCache {
+    capacity: usize,
+    inner: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    results: BTreeMap<SpeculationKey, Speculation>,
+    order: VecDeque<SpeculationKey>,
+}
+
+impl SpeculativeCache {
+    pub fn new(capacity: usize) -> SpeculativeCache {
+        SpeculativeCache {
+            capacity,
+            inner: Mutex::new(Entries::default()),
+        }
+    }
+
+    pub fn insert(&self, key: SpeculationKey, speculation: Speculation) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.inner.lock();
+        if entries.results.insert(key, speculation).is_none() {
+            entries.order.push_back(key);
+        }
+        while entries.results.len() > self.capacity {
+            match entries.order.pop_front() {
+                Some(oldest) => {
+                    entries.results.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    pub fn take(&self, key: &SpeculationKey) -> Option<Speculation> {
+        let mut entries = self.inner.lock();
+        let speculation = entries.results.remove(key)?;
+        entries.order.retain(|entry| entry != key);
+        Some(speculation)
+    }
+
+    /// Drops the results executed on the state root.
+    pub fn invalidate_root(&self, state_root: &Digest) {
+        let mut entries = self.inner.lock();
+        entries.results.retain(|(_, root), _| root != state_root);
+        entries.order.retain(|(_, root)| root != state_root);
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.inner.lock();
+        entries.results.clear();
+        entries.order.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().results.len()
+    }
+}
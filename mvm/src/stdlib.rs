@@ -0,0 +1,13 @@
+//! Precompiled standard library.
+
+use core::convert::TryFrom;
+
+use crate::types::ModulePackage;
+
+/// Compiled standard library package, built by `build.rs`.
+const STDLIB: &[u8] = include_bytes!("../tests/assets/target/packages/stdlib.pac");
+
+/// Returns the embedded standard library package.
+pub fn stdlib_package() -> ModulePackage {
+    ModulePackage::try_from(STDLIB).expect("embedded stdlib must be a valid package")
+}
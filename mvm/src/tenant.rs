@@ -0,0 +1,167 @@
+//! Isolated execution contexts of the tenants.
+//!
+//! Several chains may delegate Move execution to one host vm, e.g. the parachains of a
+//! shared-security deployment. The tenant spaces are enabled with `State::with_tenant_spaces`.
+//! A transaction executed with `ExecutionContext::with_tenant`, or a module published with
+//! `ModuleTx::with_tenant`, resolves modules and resources in the storage space of its tenant:
+//! every storage key is prefixed with `TenantId::key_prefix`. Transactions without a tenant use
+//! the storage space of the host, prefixed with `HOST_KEY_TAG`, so no tenant key collides with
+//! a host key.
+//!
+//! The tenant is passed with every call, so transactions of different tenants may be executed
+//! concurrently. Every tenant has its own loader cache, epoch, gas schedule and publisher policy
+//! loaded from its storage space: a tenant transaction writing a config path starts a new epoch
+//! of the tenant only. Tenant transactions have no access to the native balances and the oracle
+//! of the host, see `ExecutionContext::host_policy`, so the missing accounts of their senders are
+//! not created. Their events are passed to `EventHandler::on_tenant_event`.
+//! Transaction fees are paid to the host bank.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use move_core_types::gas_schedule::CostTable;
+use move_vm_runtime::move_vm::SharedCache;
+use serde::{Deserialize, Serialize};
+use spin::RwLock;
+
+use crate::data::Storage;
+
+/// First byte of the storage keys of the tenants.
+pub const TENANT_KEY_TAG: u8 = 0xFE;
+/// First byte of the storage keys of the host if the tenant spaces are enabled.
+pub const HOST_KEY_TAG: u8 = 0xFD;
+
+/// Identifier of a tenant, e.g. the parachain id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TenantId(pub u32);
+
+impl TenantId {
+    /// Returns the prefix of the storage keys of the tenant.
+    pub fn key_prefix(&self) -> Vec<u8> {
+        let mut prefix = Vec::with_capacity(5);
+        prefix.push(TENANT_KEY_TAG);
+        prefix.extend_from_slice(&self.0.to_be_bytes());
+        prefix
+    }
+}
+
+/// Storage view of the storage space of a tenant or of the host:
+/// prefixes the keys with the prefix of the space.
+pub struct SpaceStorage<'a, S: Storage> {
+    inner: &'a S,
+    prefix: Vec<u8>,
+}
+
+impl<'a, S: Storage> SpaceStorage<'a, S> {
+    /// Wraps the `inner` storage. The empty prefix leaves the keys unchanged.
+    pub fn new(inner: &'a S, prefix: Vec<u8>) -> SpaceStorage<'a, S> {
+        SpaceStorage { inner, prefix }
+    }
+
+    /// Returns the prefix of the space.
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    fn with_key<R>(&self, key: &[u8], f: impl FnOnce(&[u8]) -> R) -> R {
+        if self.prefix.is_empty() {
+            return f(key);
+        }
+        let mut prefixed = Vec::with_capacity(self.prefix.len() + key.len());
+        prefixed.extend_from_slice(&self.prefix);
+        prefixed.extend_from_slice(key);
+        f(&prefixed)
+    }
+}
+
+impl<S: Storage> Storage for SpaceStorage<'_, S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.with_key(key, |key| self.inner.get(key))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) {
+        self.with_key(key, |key| self.inner.insert(key, value))
+    }
+
+    fn remove(&self, key: &[u8]) {
+        self.with_key(key, |key| self.inner.remove(key))
+    }
+
+    fn next_key(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.with_key(key, |key| self.inner.next_key(key))
+            .filter(|next| next.starts_with(&self.prefix))
+            .map(|next| next[self.prefix.len()..].to_vec())
+    }
+
+    fn supports_next_key(&self) -> bool {
+        self.inner.supports_next_key()
+    }
+}
+
+/// Loader cache, epoch and gas schedule of a tenant loaded from its storage space.
+pub(crate) struct TenantContext {
+    pub(crate) cache: Arc<SharedCache>,
+    epoch: AtomicU64,
+    cost_table: RwLock<Arc<CostTable>>,
+}
+
+impl TenantContext {
+    pub(crate) fn new(epoch: u64, cost_table: CostTable) -> TenantContext {
+        TenantContext {
+            cache: Arc::new(SharedCache::new()),
+            epoch: AtomicU64::new(epoch),
+            cost_table: RwLock::new(Arc::new(cost_table)),
+        }
+    }
+
+    /// Returns the current epoch of the tenant.
+    pub(crate) fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::SeqCst)
+    }
+
+    /// Starts the next epoch of the tenant and returns its number.
+    pub(crate) fn next_epoch(&self) -> u64 {
+        self.epoch.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Returns the gas schedule of the current epoch.
+    pub(crate) fn cost_table(&self) -> Arc<CostTable> {
+        self.cost_table.read().clone()
+    }
+
+    /// Replaces the gas schedule on the start of an epoch.
+    pub(crate) fn set_cost_table(&self, cost_table: CostTable) {
+        *self.cost_table.write() = Arc::new(cost_table);
+    }
+}
+
+/// Storage space of a transaction: the host space or the space of the tenant.
+#[derive(Clone, Default)]
+pub(crate) struct Space {
+    tenant: Option<(TenantId, Arc<TenantContext>)>,
+}
+
+impl Space {
+    /// Returns the space of the host.
+    pub(crate) fn host() -> Space {
+        Space::default()
+    }
+
+    /// Returns the space of the tenant.
+    pub(crate) fn of(tenant: TenantId, context: Arc<TenantContext>) -> Space {
+        Space {
+            tenant: Some((tenant, context)),
+        }
+    }
+
+    /// Returns the tenant of the space or `None` for the host space.
+    pub(crate) fn tenant(&self) -> Option<TenantId> {
+        self.tenant.as_ref().map(|(tenant, _)| *tenant)
+    }
+
+    /// Returns the context of the tenant or `None` for the host space.
+    pub(crate) fn context(&self) -> Option<&TenantContext> {
+        self.tenant.as_ref().map(|(_, context)| context.as_ref())
+    }
+}
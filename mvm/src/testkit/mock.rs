@@ -1,17 +1,22 @@
+use std::borrow::ToOwned;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::vec::Vec;
 
 use move_core_types::account_address::AccountAddress;
 use move_core_types::language_storage::{ModuleId, TypeTag};
 use move_core_types::vm_status::StatusCode;
 use move_vm_types::natives::balance::Balance;
-use mvm::data::{BalanceAccess, EventHandler, ExecutionContext, Oracle, Storage};
-use mvm::mvm::Mvm;
-use mvm::types::{ModuleTx, ScriptTx};
-use mvm::Vm;
 
-use crate::common::assets::gas;
+use crate::consensus::DeterministicSource;
+use crate::data::{BalanceAccess, EventHandler, ExecutionContext, Oracle, Storage};
+use crate::events::Topic;
+use crate::metrics::Metrics;
+use crate::mvm::Mvm;
+use crate::testkit::gas;
+use crate::types::{Gas, ModuleTx, PublishPackageTx, ScriptTx, Ticker, VmResult};
+use crate::Vm;
 
 #[derive(Clone, Debug)]
 pub struct StorageMock {
@@ -47,17 +52,38 @@ impl Storage for StorageMock {
         let mut data = self.data.borrow_mut();
         data.remove(key);
     }
+
+    fn next_key(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.data
+            .borrow()
+            .keys()
+            .filter(|next| next.as_slice() > key)
+            .min()
+            .cloned()
+    }
+
+    fn supports_next_key(&self) -> bool {
+        true
+    }
 }
 
 #[derive(Clone, Default)]
 pub struct EventHandlerMock {
     pub data: Rc<RefCell<Vec<(AccountAddress, TypeTag, Vec<u8>, Option<ModuleId>)>>>,
+    /// Topics of the events in `data`.
+    pub topics: Rc<RefCell<Vec<Vec<Topic>>>>,
 }
 
 impl EventHandlerMock {
     pub fn pop(&self) -> Option<(AccountAddress, TypeTag, Vec<u8>, Option<ModuleId>)> {
+        self.topics.borrow_mut().pop();
         self.data.borrow_mut().pop()
     }
+
+    pub fn clear(&self) {
+        self.topics.borrow_mut().clear();
+        self.data.borrow_mut().clear();
+    }
 }
 
 impl EventHandler for EventHandlerMock {
@@ -71,54 +97,86 @@ impl EventHandler for EventHandlerMock {
         let mut data = self.data.borrow_mut();
         data.push((address, ty_tag, message, caller));
     }
+
+    fn on_indexed_event(
+        &self,
+        address: AccountAddress,
+        ty_tag: TypeTag,
+        message: Vec<u8>,
+        caller: Option<ModuleId>,
+        topics: Vec<Topic>,
+    ) {
+        self.topics.borrow_mut().push(topics);
+        self.on_event(address, ty_tag, message, caller);
+    }
 }
 
 #[derive(Clone, Default)]
 pub struct OracleMock {
-    price_map: Rc<RefCell<HashMap<String, u128>>>,
+    price_map: Rc<RefCell<HashMap<Ticker, u128>>>,
+    twap_map: Rc<RefCell<HashMap<Ticker, u128>>>,
 }
 
 impl OracleMock {
     pub fn set_price(&self, ticker: &str, price: u128) {
-        self.price_map.borrow_mut().insert(ticker.to_owned(), price);
+        self.price_map
+            .borrow_mut()
+            .insert(Ticker::new(ticker).unwrap(), price);
     }
 
     pub fn remove_price(&self, ticker: &str) {
-        self.price_map.borrow_mut().remove(ticker);
+        self.price_map
+            .borrow_mut()
+            .remove(&Ticker::new(ticker).unwrap());
+    }
+
+    /// Sets the average price returned for any window.
+    pub fn set_twap(&self, ticker: &str, price: u128) {
+        self.twap_map
+            .borrow_mut()
+            .insert(Ticker::new(ticker).unwrap(), price);
     }
 }
 
+impl DeterministicSource for OracleMock {}
+
 impl Oracle for OracleMock {
-    fn get_price(&self, ticker: &str) -> Option<u128> {
+    fn get_price(&self, ticker: &Ticker) -> Option<u128> {
         self.price_map.borrow().get(ticker).cloned()
     }
+
+    fn get_twap(&self, ticker: &Ticker, _window: u64) -> Option<u128> {
+        self.twap_map.borrow().get(ticker).cloned()
+    }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct BankMock {
-    balances: Rc<RefCell<HashMap<AccountAddress, HashMap<String, Balance>>>>,
+    balances: Rc<RefCell<HashMap<AccountAddress, HashMap<Ticker, Balance>>>>,
 }
 
 impl BankMock {
     pub fn set_balance(&self, address: &AccountAddress, ticker: &str, amount: Balance) {
         let mut acc_map = self.balances.borrow_mut();
         let acc = acc_map.entry(*address).or_insert_with(HashMap::new);
-        *acc.entry(ticker.to_owned()).or_insert(amount) = amount;
+        *acc.entry(Ticker::new(ticker).unwrap()).or_insert(amount) = amount;
     }
 }
 
+impl DeterministicSource for BankMock {}
+
 impl BalanceAccess for BankMock {
-    fn get_balance(&self, address: &AccountAddress, ticker: &str) -> Option<Balance> {
+    fn get_balance(&self, address: &AccountAddress, ticker: &Ticker) -> Option<Balance> {
         self.balances
             .borrow()
             .get(address)
             .and_then(|acc| acc.get(ticker).cloned())
     }
 
-    fn deposit(&self, address: &AccountAddress, ticker: &str, amount: Balance) {
+    fn deposit(&self, address: &AccountAddress, ticker: &Ticker, amount: Balance) {
         let mut acc_map = self.balances.borrow_mut();
         let acc = acc_map.entry(*address).or_insert_with(HashMap::new);
-        let val = acc.entry(ticker.to_owned()).or_insert(0);
+        let val = acc.entry(ticker.clone()).or_insert(0);
         if *val < amount {
             panic!(
                 "Not enough currency in the account [{}::{}] You need {} units in stock {}",
@@ -128,28 +186,40 @@ impl BalanceAccess for BankMock {
         *val -= amount;
     }
 
-    fn withdraw(&self, address: &AccountAddress, ticker: &str, amount: Balance) {
+    fn withdraw(&self, address: &AccountAddress, ticker: &Ticker, amount: Balance) {
         let mut acc_map = self.balances.borrow_mut();
         let acc = acc_map.entry(*address).or_insert_with(HashMap::new);
-        let val = acc.entry(ticker.to_owned()).or_insert(0);
+        let val = acc.entry(ticker.clone()).or_insert(0);
         *val += amount;
     }
 }
 
 pub trait Utils {
     fn pub_mod(&self, module: ModuleTx);
+    fn pub_package(&self, package: PublishPackageTx);
     fn exec(&self, script: ScriptTx) {
         self.exec_with_context(ExecutionContext::new(100, 100), script)
     }
     fn exec_with_context(&self, context: ExecutionContext, script: ScriptTx);
+    /// Executes the script with the given gas and returns the result whatever the status is.
+    fn exec_with_gas(&self, script: ScriptTx, gas: Gas) -> VmResult;
+    /// Executes the script and panics unless it fails with the `expected` status.
+    fn exec_expect_failure(&self, script: ScriptTx, expected: StatusCode) -> VmResult {
+        let res = self.exec_with_gas(script, gas());
+        if res.status_code != expected {
+            panic!("Expected {:?}, transaction result: {:?}", expected, res);
+        }
+        res
+    }
 }
 
-impl<S, E, O, B> Utils for Mvm<S, E, O, B>
+impl<S, E, O, B, M> Utils for Mvm<S, E, O, B, M>
 where
     S: Storage,
     E: EventHandler,
     O: Oracle,
     B: BalanceAccess,
+    M: Metrics,
 {
     fn pub_mod(&self, module: ModuleTx) {
         let res = self.publish_module(gas(), module, false);
@@ -158,14 +228,21 @@ where
         }
     }
 
+    fn pub_package(&self, package: PublishPackageTx) {
+        let res = self.publish_module_package(gas(), package, false);
+        if res.status_code != StatusCode::EXECUTED {
+            panic!("Transaction failed: {:?}", res);
+        }
+    }
+
     fn exec_with_context(&self, context: ExecutionContext, script: ScriptTx) {
         let res = self.execute_script(gas(), context, script, false);
         if res.status_code != StatusCode::EXECUTED {
             panic!("Transaction failed: {:?}", res);
         }
     }
-}
 
-pub fn addr(address: &str) -> AccountAddress {
-    AccountAddress::from_hex_literal(address).unwrap()
+    fn exec_with_gas(&self, script: ScriptTx, gas: Gas) -> VmResult {
+        self.execute_script(gas, ExecutionContext::new(100, 100), script, false)
+    }
 }
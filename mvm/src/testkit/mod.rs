@@ -0,0 +1,132 @@
+//! Mocks and helpers for writing integration tests against `Mvm`.
+
+use std::string::String;
+use std::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use move_vm_types::natives::balance::Balance;
+
+use crate::builder::MvmBuilder;
+use crate::mvm::Mvm;
+use crate::testkit::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use crate::types::{Gas, ModulePackage, ModuleTx, PublishPackageTx, Ticker};
+
+pub mod mock;
+
+/// Move VM with mocked environment.
+pub type MockVm = Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock>;
+
+/// Gas used by the test helpers.
+pub fn gas() -> Gas {
+    Gas::new(10_000, 1).unwrap()
+}
+
+/// Parses address from the hex literal.
+pub fn addr(address: &str) -> AccountAddress {
+    AccountAddress::from_hex_literal(address).unwrap()
+}
+
+/// Parses the currency ticker.
+pub fn ticker(ticker: &str) -> Ticker {
+    Ticker::new(ticker).unwrap()
+}
+
+/// Creates a new vm with empty mocks.
+pub fn vm() -> (MockVm, StorageMock, EventHandlerMock, OracleMock, BankMock) {
+    VmBuilder::new().build()
+}
+
+/// Test vm builder.
+#[derive(Default)]
+pub struct VmBuilder {
+    packages: Vec<PublishPackageTx>,
+    modules: Vec<ModuleTx>,
+    balances: Vec<(AccountAddress, String, Balance)>,
+    prices: Vec<(String, u128)>,
+    tenant_spaces: bool,
+}
+
+impl VmBuilder {
+    /// Constructor.
+    pub fn new() -> VmBuilder {
+        VmBuilder::default()
+    }
+
+    /// Publishes the standard library package under the core code address.
+    pub fn with_stdlib(self, stdlib: ModulePackage) -> VmBuilder {
+        self.with_package(stdlib.into_tx(CORE_CODE_ADDRESS))
+    }
+
+    /// Publishes the embedded standard library.
+    #[cfg(feature = "embedded-stdlib")]
+    pub fn with_embedded_stdlib(self) -> VmBuilder {
+        self.with_stdlib(crate::stdlib::stdlib_package())
+    }
+
+    /// Publishes the package of modules.
+    pub fn with_package(mut self, package: PublishPackageTx) -> VmBuilder {
+        self.packages.push(package);
+        self
+    }
+
+    /// Publishes the module.
+    pub fn with_module(mut self, module: ModuleTx) -> VmBuilder {
+        self.modules.push(module);
+        self
+    }
+
+    /// Sets native balance of the account.
+    pub fn with_balance(
+        mut self,
+        address: AccountAddress,
+        ticker: &str,
+        amount: Balance,
+    ) -> VmBuilder {
+        self.balances.push((address, ticker.into(), amount));
+        self
+    }
+
+    /// Sets oracle price.
+    pub fn with_price(mut self, ticker: &str, price: u128) -> VmBuilder {
+        self.prices.push((ticker.into(), price));
+        self
+    }
+
+    /// Enables the storage spaces of the tenants.
+    pub fn with_tenant_spaces(mut self) -> VmBuilder {
+        self.tenant_spaces = true;
+        self
+    }
+
+    /// Creates vm and publishes all modules.
+    /// Panics if any of the modules can't be published.
+    pub fn build(self) -> (MockVm, StorageMock, EventHandlerMock, OracleMock, BankMock) {
+        let store = StorageMock::new();
+        let event = EventHandlerMock::default();
+        let oracle = OracleMock::default();
+        let bank = BankMock::default();
+        let mut builder = MvmBuilder::new(store.clone(), event.clone())
+            .with_oracle(oracle.clone())
+            .with_bank(bank.clone());
+        if self.tenant_spaces {
+            builder = builder.with_tenant_spaces();
+        }
+        let vm = builder.build().unwrap();
+
+        for package in self.packages {
+            vm.pub_package(package);
+        }
+        for module in self.modules {
+            vm.pub_mod(module);
+        }
+        for (address, ticker, amount) in self.balances {
+            bank.set_balance(&address, &ticker, amount);
+        }
+        for (ticker, price) in self.prices {
+            oracle.set_price(&ticker, price);
+        }
+
+        (vm, store, event, oracle, bank)
+    }
+}
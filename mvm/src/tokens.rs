@@ -0,0 +1,260 @@
+//! Fungible tokens.
+//!
+//! Tokens follow the token standard of the standard library:
+//! `Pontem::create_token<T>` registers the token `Pontem::Token<T>` and mints its fixed
+//! total supply to the creator, coins are held in `Account::Balance<Pontem::Token<T>>`
+//! and transferred with `Account::pay_from_sender`.
+//! `T` is a copyable marker struct declared by the creator, see `token_module`.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use anyhow::Error;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use vm::file_format::{
+    empty_module, AddressIdentifierIndex, Bytecode, CodeUnit, CompiledScriptMut, FieldDefinition,
+    FunctionHandle, FunctionHandleIndex, FunctionInstantiation, FunctionInstantiationIndex,
+    IdentifierIndex, Kind, ModuleHandle, ModuleHandleIndex, Signature, SignatureIndex,
+    SignatureToken, StructDefinition, StructFieldInformation, StructHandle, StructHandleIndex,
+    TypeSignature,
+};
+
+use crate::account::ACCOUNT_MODULE;
+use crate::types::{ModuleTx, ScriptArg, ScriptTx};
+
+/// Module of the token standard.
+pub const TOKEN_MODULE: &str = "Pontem";
+/// Struct wrapping the marker of a token.
+pub const TOKEN_STRUCT: &str = "Token";
+/// Struct of the coins.
+const COIN_STRUCT: &str = "T";
+const CREATE_TOKEN: &str = "create_token";
+const DEPOSIT_TO_SENDER: &str = "deposit_to_sender";
+const PAY_FROM_SENDER: &str = "pay_from_sender";
+
+/// Returns the tag of the token `Pontem::Token<marker>`.
+pub fn token_tag(core_address: AccountAddress, marker: StructTag) -> StructTag {
+    StructTag {
+        address: core_address,
+        module: Identifier::new(TOKEN_MODULE).unwrap(),
+        name: Identifier::new(TOKEN_STRUCT).unwrap(),
+        type_params: vec![TypeTag::Struct(marker)],
+    }
+}
+
+/// Module `address::module` declaring the token marker `struct name { dummy_field: bool }`.
+pub fn token_module(address: AccountAddress, module: &str, name: &str) -> Result<ModuleTx, Error> {
+    let mut m = empty_module();
+    m.address_identifiers = vec![address];
+    m.identifiers = vec![
+        Identifier::new(module)?,
+        Identifier::new(name)?,
+        Identifier::new("dummy_field")?,
+    ];
+    m.struct_handles = vec![StructHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(1),
+        is_nominal_resource: false,
+        type_parameters: vec![],
+    }];
+    m.struct_defs = vec![StructDefinition {
+        struct_handle: StructHandleIndex(0),
+        field_information: StructFieldInformation::Declared(vec![FieldDefinition {
+            name: IdentifierIndex(2),
+            signature: TypeSignature(SignatureToken::Bool),
+        }]),
+    }];
+
+    let mut code = vec![];
+    m.serialize(&mut code)?;
+    Ok(ModuleTx::new(code, address))
+}
+
+/// Script creating the token with the `marker` and depositing its total supply
+/// to the `creator` account.
+pub fn create_token_script(
+    core_address: AccountAddress,
+    creator: AccountAddress,
+    marker: StructTag,
+    total_supply: u128,
+    decimals: u8,
+    denom: &str,
+) -> Result<ScriptTx, Error> {
+    use SignatureToken::*;
+
+    let token = StructInstantiation(StructHandleIndex(1), vec![TypeParameter(0)]);
+    let coins = StructInstantiation(StructHandleIndex(0), vec![token.clone()]);
+    let script = CompiledScriptMut {
+        module_handles: vec![module_handle(0), module_handle(1)],
+        struct_handles: vec![
+            StructHandle {
+                module: ModuleHandleIndex(0),
+                name: IdentifierIndex(2),
+                is_nominal_resource: true,
+                type_parameters: vec![Kind::All],
+            },
+            StructHandle {
+                module: ModuleHandleIndex(0),
+                name: IdentifierIndex(3),
+                is_nominal_resource: true,
+                type_parameters: vec![Kind::Copyable],
+            },
+        ],
+        function_handles: vec![
+            FunctionHandle {
+                module: ModuleHandleIndex(0),
+                name: IdentifierIndex(4),
+                parameters: SignatureIndex(1),
+                return_: SignatureIndex(2),
+                type_parameters: vec![Kind::Copyable],
+            },
+            FunctionHandle {
+                module: ModuleHandleIndex(1),
+                name: IdentifierIndex(5),
+                parameters: SignatureIndex(3),
+                return_: SignatureIndex(0),
+                type_parameters: vec![Kind::All],
+            },
+        ],
+        function_instantiations: vec![
+            FunctionInstantiation {
+                handle: FunctionHandleIndex(0),
+                type_parameters: SignatureIndex(4),
+            },
+            FunctionInstantiation {
+                handle: FunctionHandleIndex(1),
+                type_parameters: SignatureIndex(5),
+            },
+        ],
+        signatures: vec![
+            Signature(vec![]),
+            Signature(vec![
+                Reference(Box::new(Signer)),
+                U128,
+                U8,
+                Vector(Box::new(U8)),
+            ]),
+            Signature(vec![coins]),
+            Signature(vec![
+                Reference(Box::new(Signer)),
+                StructInstantiation(StructHandleIndex(0), vec![TypeParameter(0)]),
+            ]),
+            Signature(vec![TypeParameter(0)]),
+            Signature(vec![token]),
+        ],
+        identifiers: identifiers(&[
+            TOKEN_MODULE,
+            ACCOUNT_MODULE,
+            COIN_STRUCT,
+            TOKEN_STRUCT,
+            CREATE_TOKEN,
+            DEPOSIT_TO_SENDER,
+        ]),
+        address_identifiers: vec![core_address],
+        constant_pool: vec![],
+        type_parameters: vec![Kind::Copyable],
+        parameters: SignatureIndex(1),
+        code: CodeUnit {
+            locals: SignatureIndex(2),
+            code: vec![
+                Bytecode::CopyLoc(0),
+                Bytecode::MoveLoc(1),
+                Bytecode::MoveLoc(2),
+                Bytecode::MoveLoc(3),
+                Bytecode::CallGeneric(FunctionInstantiationIndex(0)),
+                Bytecode::StLoc(4),
+                Bytecode::MoveLoc(0),
+                Bytecode::MoveLoc(4),
+                Bytecode::CallGeneric(FunctionInstantiationIndex(1)),
+                Bytecode::Ret,
+            ],
+        },
+    };
+
+    let mut code = vec![];
+    script.serialize(&mut code)?;
+    Ok(ScriptTx::new(
+        code,
+        vec![
+            ScriptArg::U128(total_supply),
+            ScriptArg::U8(decimals),
+            ScriptArg::VectorU8(denom.as_bytes().to_vec()),
+        ],
+        vec![TypeTag::Struct(marker)],
+        vec![creator],
+    ))
+}
+
+/// Script transferring `amount` coins of the `coin` currency from the `sender` to the `payee`.
+/// Tokens are transferred with the `Pontem::Token<marker>` coin, see `token_tag`.
+pub fn transfer_script(
+    core_address: AccountAddress,
+    sender: AccountAddress,
+    coin: StructTag,
+    payee: AccountAddress,
+    amount: u128,
+) -> Result<ScriptTx, Error> {
+    use SignatureToken::*;
+
+    let script = CompiledScriptMut {
+        module_handles: vec![module_handle(0)],
+        struct_handles: vec![],
+        function_handles: vec![FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(1),
+            parameters: SignatureIndex(1),
+            return_: SignatureIndex(0),
+            type_parameters: vec![Kind::All],
+        }],
+        function_instantiations: vec![FunctionInstantiation {
+            handle: FunctionHandleIndex(0),
+            type_parameters: SignatureIndex(2),
+        }],
+        signatures: vec![
+            Signature(vec![]),
+            Signature(vec![Reference(Box::new(Signer)), Address, U128]),
+            Signature(vec![TypeParameter(0)]),
+        ],
+        identifiers: identifiers(&[ACCOUNT_MODULE, PAY_FROM_SENDER]),
+        address_identifiers: vec![core_address],
+        constant_pool: vec![],
+        type_parameters: vec![Kind::All],
+        parameters: SignatureIndex(1),
+        code: CodeUnit {
+            locals: SignatureIndex(0),
+            code: vec![
+                Bytecode::MoveLoc(0),
+                Bytecode::MoveLoc(1),
+                Bytecode::MoveLoc(2),
+                Bytecode::CallGeneric(FunctionInstantiationIndex(0)),
+                Bytecode::Ret,
+            ],
+        },
+    };
+
+    let mut code = vec![];
+    script.serialize(&mut code)?;
+    Ok(ScriptTx::new(
+        code,
+        vec![ScriptArg::Address(payee), ScriptArg::U128(amount)],
+        vec![TypeTag::Struct(coin)],
+        vec![sender],
+    ))
+}
+
+/// Handle of the module named by the identifier `name` at the address 0.
+pub(crate) fn module_handle(name: u16) -> ModuleHandle {
+    ModuleHandle {
+        address: AddressIdentifierIndex(0),
+        name: IdentifierIndex(name),
+    }
+}
+
+pub(crate) fn identifiers(names: &[&str]) -> Vec<Identifier> {
+    names
+        .iter()
+        .map(|name| Identifier::new(*name).unwrap())
+        .collect()
+}
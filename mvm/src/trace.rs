@@ -0,0 +1,65 @@
+//! Tracing spans of the vm, enabled with the `trace` feature.
+//!
+//! Transactions run in `info` spans carrying the transaction hash, the sender, the gas limit
+//! and, once finished, the status and the gas used. Effect handling runs in `debug` spans,
+//! storage accesses in `trace` spans. Without the feature the spans are compiled out.
+
+use crate::types::VmResult;
+
+/// Enters the span of a transaction. The span is exited when the returned `TxSpan` is dropped.
+#[cfg(feature = "trace")]
+macro_rules! tx_span {
+    ($name:literal, $($field:tt)*) => {
+        $crate::trace::TxSpan(
+            tracing::info_span!(
+                $name,
+                $($field)*,
+                status = tracing::field::Empty,
+                gas_used = tracing::field::Empty
+            )
+            .entered(),
+        )
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! tx_span {
+    ($($tt:tt)*) => {
+        $crate::trace::TxSpan
+    };
+}
+
+/// Enters the span at the level until the end of the enclosing block.
+#[cfg(feature = "trace")]
+macro_rules! enter_span {
+    ($level:ident, $name:literal $(, $($field:tt)*)?) => {
+        let _span = tracing::span!(tracing::Level::$level, $name $(, $($field)*)?).entered();
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! enter_span {
+    ($($tt:tt)*) => {};
+}
+
+/// Entered span of a transaction.
+#[cfg(feature = "trace")]
+pub(crate) struct TxSpan(pub tracing::span::EnteredSpan);
+
+/// Entered span of a transaction.
+#[cfg(not(feature = "trace"))]
+pub(crate) struct TxSpan;
+
+impl TxSpan {
+    /// Records the status and the gas used by the transaction.
+    pub fn finish(&self, result: &VmResult) {
+        #[cfg(feature = "trace")]
+        {
+            self.0
+                .record("status", &tracing::field::debug(result.status_code));
+            self.0.record("gas_used", &result.gas_used);
+        }
+        #[cfg(not(feature = "trace"))]
+        let _ = result;
+    }
+}
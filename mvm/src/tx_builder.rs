@@ -0,0 +1,178 @@
+//! Payloads of the standard operations.
+//!
+//! The scripts are assembled from bytecode against the standard library at `core_address`,
+//! so integrators don't need to ship compiled script blobs with their pallets.
+//! The account resources of the standard library hold no authentication key, keys are rotated
+//! with the `Auth::rotate_key` native of the vm, see `auth`.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::vec::Vec;
+
+use anyhow::Error;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use vm::access::ModuleAccess;
+use vm::file_format::{
+    Bytecode, CodeUnit, CompiledScriptMut, FunctionHandle, FunctionHandleIndex,
+    FunctionInstantiation, FunctionInstantiationIndex, IdentifierIndex, Kind, ModuleHandleIndex,
+    Signature, SignatureIndex, SignatureToken,
+};
+use vm::CompiledModule;
+
+use crate::auth::{AUTH_MODULE, ROTATE_KEY};
+use crate::package::UpgradePolicy;
+use crate::tokens::{identifiers, module_handle, TOKEN_MODULE};
+use crate::types::{PublishPackageTx, ScriptArg, ScriptTx};
+
+const REGISTER_COIN: &str = "register_coin";
+
+/// Script transferring `amount` coins of the `coin` currency from the `sender` to the `payee`.
+pub fn transfer(
+    core_address: AccountAddress,
+    sender: AccountAddress,
+    coin: StructTag,
+    payee: AccountAddress,
+    amount: u128,
+) -> Result<ScriptTx, Error> {
+    crate::tokens::transfer_script(core_address, sender, coin, payee, amount)
+}
+
+/// Script registering the `coin` currency with the denomination and the number of decimals.
+/// The currency is registered by the `Pontem` module, the transaction needs no signer.
+pub fn register_currency(
+    core_address: AccountAddress,
+    coin: StructTag,
+    denom: &str,
+    decimals: u8,
+) -> Result<ScriptTx, Error> {
+    use SignatureToken::*;
+
+    let script = CompiledScriptMut {
+        module_handles: vec![module_handle(0)],
+        struct_handles: vec![],
+        function_handles: vec![FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(1),
+            parameters: SignatureIndex(1),
+            return_: SignatureIndex(0),
+            type_parameters: vec![Kind::Copyable],
+        }],
+        function_instantiations: vec![FunctionInstantiation {
+            handle: FunctionHandleIndex(0),
+            type_parameters: SignatureIndex(2),
+        }],
+        signatures: vec![
+            Signature(vec![]),
+            Signature(vec![Vector(Box::new(U8)), U8]),
+            Signature(vec![TypeParameter(0)]),
+        ],
+        identifiers: identifiers(&[TOKEN_MODULE, REGISTER_COIN]),
+        address_identifiers: vec![core_address],
+        constant_pool: vec![],
+        type_parameters: vec![Kind::Copyable],
+        parameters: SignatureIndex(1),
+        code: CodeUnit {
+            locals: SignatureIndex(0),
+            code: vec![
+                Bytecode::MoveLoc(0),
+                Bytecode::MoveLoc(1),
+                Bytecode::CallGeneric(FunctionInstantiationIndex(0)),
+                Bytecode::Ret,
+            ],
+        },
+    };
+
+    let mut code = vec![];
+    script.serialize(&mut code)?;
+    Ok(ScriptTx::new(
+        code,
+        vec![
+            ScriptArg::VectorU8(denom.as_bytes().to_vec()),
+            ScriptArg::U8(decimals),
+        ],
+        vec![TypeTag::Struct(coin)],
+        vec![],
+    ))
+}
+
+/// Script setting the authentication key of the `sender` to `new_key`.
+/// The key is passed to the `auth::Authenticator` for the following transactions of the sender,
+/// the transaction itself is authenticated with the current key.
+pub fn rotate_key(
+    core_address: AccountAddress,
+    sender: AccountAddress,
+    new_key: Vec<u8>,
+) -> Result<ScriptTx, Error> {
+    use SignatureToken::*;
+
+    let script = CompiledScriptMut {
+        module_handles: vec![module_handle(0)],
+        struct_handles: vec![],
+        function_handles: vec![FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(1),
+            parameters: SignatureIndex(1),
+            return_: SignatureIndex(0),
+            type_parameters: vec![],
+        }],
+        function_instantiations: vec![],
+        signatures: vec![
+            Signature(vec![]),
+            Signature(vec![Reference(Box::new(Signer)), Vector(Box::new(U8))]),
+        ],
+        identifiers: identifiers(&[AUTH_MODULE, ROTATE_KEY]),
+        address_identifiers: vec![core_address],
+        constant_pool: vec![],
+        type_parameters: vec![],
+        parameters: SignatureIndex(1),
+        code: CodeUnit {
+            locals: SignatureIndex(0),
+            code: vec![
+                Bytecode::MoveLoc(0),
+                Bytecode::MoveLoc(1),
+                Bytecode::Call(FunctionHandleIndex(0)),
+                Bytecode::Ret,
+            ],
+        },
+    };
+
+    let mut code = vec![];
+    script.serialize(&mut code)?;
+    Ok(ScriptTx::new(
+        code,
+        vec![ScriptArg::VectorU8(new_key)],
+        vec![],
+        vec![sender],
+    ))
+}
+
+/// Transaction publishing the modules under the `address`, optionally as a release of the named
+/// package. Fails if a module can't be deserialized or is declared at another address.
+pub fn publish_package(
+    address: AccountAddress,
+    modules: Vec<Vec<u8>>,
+    release: Option<(Identifier, UpgradePolicy)>,
+) -> Result<PublishPackageTx, Error> {
+    if modules.is_empty() {
+        return Err(Error::msg("Package has no modules"));
+    }
+    for module in &modules {
+        let module = CompiledModule::deserialize(module)
+            .map_err(|err| Error::msg(format!("Failed to deserialize module: {:?}", err)))?;
+        if *module.address() != address {
+            return Err(Error::msg(format!(
+                "Module {} is declared at {}, not at {}",
+                module.name(),
+                module.address(),
+                address
+            )));
+        }
+    }
+    let tx = PublishPackageTx::new(modules, address);
+    Ok(match release {
+        Some((name, policy)) => tx.with_package(name, policy),
+        None => tx,
+    })
+}
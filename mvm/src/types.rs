@@ -1,18 +1,28 @@
+use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use anyhow::*;
 use core::convert::TryFrom;
 use core::fmt;
 use move_core_types::account_address::AccountAddress;
 use move_core_types::identifier::Identifier;
-use move_core_types::language_storage::{StructTag, TypeTag};
-use move_core_types::vm_status::StatusCode;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use move_core_types::value::MoveValue;
+use move_core_types::vm_status::{StatusCode, StatusType};
 use move_lang::parser::ast::{ModuleAccess_, ModuleIdent_, Type, Type_};
 use move_lang::parser::lexer::{Lexer, Tok};
 use move_lang::parser::syntax::parse_type;
+use move_vm_types::natives::balance::{BalanceOperation, WalletId};
 use move_vm_types::values::Value;
-use parity_scale_codec::{Decode, Encode};
+use parity_scale_codec::{Decode, Encode, Input, Output};
 use serde::{Deserialize, Serialize};
 
+use crate::data::fee_ticker;
+use crate::hash::{args_hash, Digest};
+use crate::package::UpgradePolicy;
+use crate::source_map::{ErrorLocation, SourceLocation};
+use crate::tenant::TenantId;
+
 const GAS_AMOUNT_MAX_VALUE: u64 = u64::MAX / 1000;
 
 /// Stores gas metadata for vm execution.
@@ -22,6 +32,8 @@ pub struct Gas {
     pub(crate) max_gas_amount: u64,
     /// Price in `XFI` coins per unit of gas.
     pub(crate) gas_unit_price: u64,
+    /// Currency of the fee.
+    pub(crate) gas_currency: Ticker,
 }
 
 impl Gas {
@@ -36,9 +48,15 @@ impl Gas {
         Ok(Gas {
             max_gas_amount,
             gas_unit_price,
+            gas_currency: fee_ticker(),
         })
     }
 
+    /// Returns the builder validating the gas against the on-chain bounds.
+    pub fn builder() -> GasBuilder {
+        GasBuilder::default()
+    }
+
     /// Returns max gas units to be used in transaction execution.
     pub fn max_gas_amount(&self) -> u64 {
         self.max_gas_amount
@@ -48,19 +66,237 @@ impl Gas {
     pub fn gas_unit_price(&self) -> u64 {
         self.gas_unit_price
     }
+
+    /// Returns the currency of the fee.
+    pub fn gas_currency(&self) -> &Ticker {
+        &self.gas_currency
+    }
+}
+
+/// Bounds of the transaction gas, see `VmConfig::gas_bounds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GasBounds {
+    /// Minimum price per unit of gas.
+    pub min_gas_unit_price: u64,
+    /// Maximum price per unit of gas, `None` doesn't limit the price.
+    pub max_gas_unit_price: Option<u64>,
+    /// Maximum gas units of a transaction, `None` keeps only the static limit.
+    pub max_gas_per_tx: Option<u64>,
+}
+
+impl GasBounds {
+    /// Checks the max gas amount and the gas unit price against the bounds.
+    pub fn check(&self, max_gas_amount: u64, gas_unit_price: u64) -> Result<(), GasError> {
+        let limit = self
+            .max_gas_per_tx
+            .map(|max| max.min(GAS_AMOUNT_MAX_VALUE - 1))
+            .unwrap_or(GAS_AMOUNT_MAX_VALUE - 1);
+        if max_gas_amount > limit {
+            return Err(GasError::MaxGasAmountTooLarge {
+                max_gas_amount,
+                limit,
+            });
+        }
+        if gas_unit_price < self.min_gas_unit_price {
+            return Err(GasError::GasUnitPriceTooLow {
+                gas_unit_price,
+                min: self.min_gas_unit_price,
+            });
+        }
+        match self.max_gas_unit_price {
+            Some(max) if gas_unit_price > max => Err(GasError::GasUnitPriceTooHigh {
+                gas_unit_price,
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Error of the gas validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GasError {
+    /// The max gas amount is not set.
+    MissingMaxGasAmount,
+    /// The gas unit price is not set.
+    MissingGasUnitPrice,
+    /// The max gas amount exceeds the limit of a transaction.
+    MaxGasAmountTooLarge { max_gas_amount: u64, limit: u64 },
+    /// The gas unit price is below the minimum price.
+    GasUnitPriceTooLow { gas_unit_price: u64, min: u64 },
+    /// The gas unit price is above the maximum price.
+    GasUnitPriceTooHigh { gas_unit_price: u64, max: u64 },
+    /// Fees can't be paid in the currency.
+    UnsupportedGasCurrency(Ticker),
+}
+
+impl fmt::Display for GasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GasError::MissingMaxGasAmount => write!(f, "max_gas_amount is not set"),
+            GasError::MissingGasUnitPrice => write!(f, "gas_unit_price is not set"),
+            GasError::MaxGasAmountTooLarge {
+                max_gas_amount,
+                limit,
+            } => write!(
+                f,
+                "max_gas_amount {} exceeds the limit {}",
+                max_gas_amount, limit
+            ),
+            GasError::GasUnitPriceTooLow {
+                gas_unit_price,
+                min,
+            } => write!(
+                f,
+                "gas_unit_price {} is below the minimum {}",
+                gas_unit_price, min
+            ),
+            GasError::GasUnitPriceTooHigh {
+                gas_unit_price,
+                max,
+            } => write!(
+                f,
+                "gas_unit_price {} is above the maximum {}",
+                gas_unit_price, max
+            ),
+            GasError::UnsupportedGasCurrency(ticker) => {
+                write!(f, "Fees can't be paid in {}", ticker.as_str())
+            }
+        }
+    }
+}
+
+/// Builder of the `Gas` checked against the `GasBounds`.
+#[derive(Debug, Clone, Default)]
+pub struct GasBuilder {
+    max_gas_amount: Option<u64>,
+    gas_unit_price: Option<u64>,
+    gas_currency: Option<Ticker>,
+}
+
+impl GasBuilder {
+    /// Sets max gas units to be used in transaction execution.
+    pub fn max_gas_amount(mut self, max_gas_amount: u64) -> Self {
+        self.max_gas_amount = Some(max_gas_amount);
+        self
+    }
+
+    /// Sets price per unit of gas.
+    pub fn gas_unit_price(mut self, gas_unit_price: u64) -> Self {
+        self.gas_unit_price = Some(gas_unit_price);
+        self
+    }
+
+    /// Sets currency of the fee. Defaults to the fee currency, see `data::fee_ticker`.
+    /// The vm accepts the registered currencies, see `data::Bank::is_gas_currency`.
+    pub fn gas_currency(mut self, gas_currency: Ticker) -> Self {
+        self.gas_currency = Some(gas_currency);
+        self
+    }
+
+    /// Validates the gas against the bounds.
+    pub fn build(self, bounds: &GasBounds) -> Result<Gas, GasError> {
+        let max_gas_amount = self.max_gas_amount.ok_or(GasError::MissingMaxGasAmount)?;
+        let gas_unit_price = self.gas_unit_price.ok_or(GasError::MissingGasUnitPrice)?;
+        bounds.check(max_gas_amount, gas_unit_price)?;
+        Ok(Gas {
+            max_gas_amount,
+            gas_unit_price,
+            gas_currency: self.gas_currency.unwrap_or_else(fee_ticker),
+        })
+    }
+}
+
+/// Limits of the script transactions checked before the script is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxLimits {
+    /// Maximum size of the script bytecode in bytes.
+    pub max_script_size: usize,
+    /// Maximum number of the type arguments.
+    pub max_type_args: usize,
+    /// Maximum total size of the arguments in bytes.
+    pub max_args_size: usize,
+}
+
+impl Default for TxLimits {
+    fn default() -> Self {
+        TxLimits {
+            max_script_size: 64 * 1024,
+            max_type_args: 32,
+            max_args_size: 64 * 1024,
+        }
+    }
 }
 
 /// Module transaction.
-#[derive(Clone, Encode, Decode)]
+#[derive(Clone)]
 pub struct ModuleTx {
-    code: Vec<u8>,
+    code: Arc<[u8]>,
     sender: AccountAddress,
+    immutable: bool,
+    chain_id: Option<u8>,
+    source_map: Option<Vec<u8>>,
+    tenant: Option<TenantId>,
 }
 
 impl ModuleTx {
     /// Constructor.
-    pub fn new(code: Vec<u8>, sender: AccountAddress) -> ModuleTx {
-        ModuleTx { code, sender }
+    /// Accepts `Vec<u8>` as well as already shared `Arc<[u8]>` bytecode.
+    pub fn new<C: Into<Arc<[u8]>>>(code: C, sender: AccountAddress) -> ModuleTx {
+        ModuleTx {
+            code: code.into(),
+            sender,
+            immutable: false,
+            chain_id: None,
+            source_map: None,
+            tenant: None,
+        }
+    }
+
+    /// Publishes the module with the BCS encoded `source_map::SourceMap`.
+    /// The source map is debug data, it is not a part of the encoded transaction.
+    pub fn with_source_map(mut self, source_map: Vec<u8>) -> ModuleTx {
+        self.source_map = Some(source_map);
+        self
+    }
+
+    /// Returns the BCS encoded source map of the module.
+    pub fn source_map(&self) -> Option<&[u8]> {
+        self.source_map.as_deref()
+    }
+
+    /// Publishes the module in the storage space of the tenant, see `tenant`.
+    /// The tenant is set by the host, it is not a part of the encoded transaction.
+    pub fn with_tenant(mut self, tenant: TenantId) -> ModuleTx {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Returns the tenant of the module or `None` for the modules of the host.
+    pub fn tenant(&self) -> Option<TenantId> {
+        self.tenant
+    }
+
+    /// Sets the id of the chain the transaction is signed for.
+    pub fn with_chain_id(mut self, chain_id: u8) -> ModuleTx {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Returns the id of the chain the transaction is signed for.
+    pub fn chain_id(&self) -> Option<u8> {
+        self.chain_id
+    }
+
+    /// Publishes the module as immutable: it can never be replaced afterwards.
+    pub fn immutable(mut self) -> ModuleTx {
+        self.immutable = true;
+        self
+    }
+
+    /// Returns `true` if the module is published as immutable.
+    pub fn is_immutable(&self) -> bool {
+        self.immutable
     }
 
     /// Returns module bytecode.
@@ -69,45 +305,166 @@ impl ModuleTx {
     }
 
     /// Convert into internal data.
-    pub fn into_inner(self) -> (Vec<u8>, AccountAddress) {
+    pub fn into_inner(self) -> (Arc<[u8]>, AccountAddress) {
         (self.code, self.sender)
     }
 }
 
+impl Encode for ModuleTx {
+    fn encode_to<T: Output>(&self, dest: &mut T) {
+        self.code.as_ref().encode_to(dest);
+        self.sender.encode_to(dest);
+        // Trailing fields are only appended when set, so the plain modules keep the old encoding.
+        if self.immutable || self.chain_id.is_some() {
+            self.immutable.encode_to(dest);
+        }
+        if let Some(chain_id) = self.chain_id {
+            chain_id.encode_to(dest);
+        }
+    }
+}
+
+impl Decode for ModuleTx {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        let code = Vec::<u8>::decode(input)?;
+        let sender = AccountAddress::decode(input)?;
+        let mut tx = ModuleTx::new(code, sender);
+        if input.remaining_len()? != Some(0) {
+            tx.immutable = bool::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            tx.chain_id = Some(u8::decode(input)?);
+        }
+        Ok(tx)
+    }
+}
+
 impl fmt::Debug for ModuleTx {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Module")
             .field("code", &hex::encode(&self.code))
             .field("sender", &self.sender)
+            .field("immutable", &self.immutable)
+            .field("chain_id", &self.chain_id)
+            .field("source_map", &self.source_map.as_ref().map(hex::encode))
+            .field("tenant", &self.tenant)
             .finish()
     }
 }
 
 /// Script bytecode + passed arguments and type parameters.
 pub struct ScriptTx {
-    code: Vec<u8>,
+    code: Arc<[u8]>,
     args: Vec<Value>,
     type_args: Vec<TypeTag>,
     senders: Vec<AccountAddress>,
+    proof: Vec<u8>,
+    fee_payer: Option<AccountAddress>,
+    expiration_timestamp: Option<u64>,
+    not_before: Option<u64>,
+    chain_id: Option<u8>,
+    sequence: Option<(u64, u64)>,
+    args_size: usize,
+    args_hash: Digest,
 }
 
 /// Script transaction.
 impl ScriptTx {
     /// Constructor.
-    pub fn new(
-        code: Vec<u8>,
+    /// Accepts `Vec<u8>` as well as already shared `Arc<[u8]>` bytecode.
+    pub fn new<C: Into<Arc<[u8]>>>(
+        code: C,
         args: Vec<ScriptArg>,
         type_args: Vec<TypeTag>,
         senders: Vec<AccountAddress>,
     ) -> Self {
         ScriptTx {
-            code,
+            code: code.into(),
+            args_size: args.iter().map(ScriptArg::size).sum(),
+            args_hash: args_hash(&args),
             args: args.into_iter().map(ScriptArg::into).collect(),
             type_args,
             senders,
+            proof: vec![],
+            fee_payer: None,
+            expiration_timestamp: None,
+            not_before: None,
+            chain_id: None,
+            sequence: None,
+        }
+    }
+
+    /// Creates the transaction checked against the limits.
+    pub fn try_new<C: Into<Arc<[u8]>>>(
+        code: C,
+        args: Vec<ScriptArg>,
+        type_args: Vec<TypeTag>,
+        senders: Vec<AccountAddress>,
+        limits: &TxLimits,
+    ) -> Result<Self, StatusCode> {
+        let tx = ScriptTx::new(code, args, type_args, senders);
+        tx.check_limits(limits)?;
+        Ok(tx)
+    }
+
+    /// Checks the script size, the number of the type arguments and the size of the arguments.
+    pub fn check_limits(&self, limits: &TxLimits) -> Result<(), StatusCode> {
+        if self.code.len() > limits.max_script_size {
+            Err(StatusCode::EXCEEDED_MAX_TRANSACTION_SIZE)
+        } else if self.type_args.len() > limits.max_type_args {
+            Err(StatusCode::TOO_MANY_TYPE_ARGUMENTS)
+        } else if self.args_size > limits.max_args_size {
+            Err(StatusCode::EXCEEDED_MAX_ARGUMENTS_SIZE)
+        } else {
+            Ok(())
         }
     }
 
+    /// Sets the account which pays the transaction fee instead of the senders.
+    /// The vm withdraws `gas_used * gas_unit_price` fee coins from the fee payer.
+    /// The max fee is reserved during the execution: the script can't spend it.
+    pub fn with_fee_payer(mut self, fee_payer: AccountAddress) -> Self {
+        self.fee_payer = Some(fee_payer);
+        self
+    }
+
+    /// Attaches the authentication proof checked by the vm `Authenticator`.
+    pub fn with_proof(mut self, proof: Vec<u8>) -> Self {
+        self.proof = proof;
+        self
+    }
+
+    /// Sets the block timestamp from which the transaction is rejected with `TRANSACTION_EXPIRED`.
+    pub fn with_expiration(mut self, expiration_timestamp: u64) -> Self {
+        self.expiration_timestamp = Some(expiration_timestamp);
+        self
+    }
+
+    /// Sets the first block timestamp the transaction can be executed at.
+    /// Earlier blocks reject it with `TRANSACTION_NOT_YET_VALID`.
+    pub fn with_not_before(mut self, not_before: u64) -> Self {
+        self.not_before = Some(not_before);
+        self
+    }
+
+    /// Sets the id of the chain the transaction is signed for.
+    pub fn with_chain_id(mut self, chain_id: u8) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Sets the sequence number of the transaction on the lane of the first sender.
+    /// See `lanes` module.
+    pub fn with_sequence_number(mut self, lane: u64, sequence_number: u64) -> Self {
+        self.sequence = Some((lane, sequence_number));
+        self
+    }
+
+    /// Checks that the transaction can be executed in the block with the timestamp.
+    pub fn check_validity_window(&self, timestamp: u64) -> Result<(), StatusCode> {
+        check_validity_window(self.not_before, self.expiration_timestamp, timestamp)
+    }
+
     /// Script bytecode.
     pub fn code(&self) -> &[u8] {
         &self.code
@@ -123,8 +480,48 @@ impl ScriptTx {
         &self.type_args
     }
 
+    /// Script senders.
+    pub fn senders(&self) -> &[AccountAddress] {
+        &self.senders
+    }
+
+    /// Authentication proof.
+    pub fn proof(&self) -> &[u8] {
+        &self.proof
+    }
+
+    /// Sponsor of the transaction fee.
+    pub fn fee_payer(&self) -> Option<&AccountAddress> {
+        self.fee_payer.as_ref()
+    }
+
+    /// Block timestamp from which the transaction expires.
+    pub fn expiration_timestamp(&self) -> Option<u64> {
+        self.expiration_timestamp
+    }
+
+    /// First block timestamp the transaction is valid at.
+    pub fn not_before(&self) -> Option<u64> {
+        self.not_before
+    }
+
+    /// Id of the chain the transaction is signed for.
+    pub fn chain_id(&self) -> Option<u8> {
+        self.chain_id
+    }
+
+    /// Lane and sequence number of the transaction.
+    pub fn sequence_number(&self) -> Option<(u64, u64)> {
+        self.sequence
+    }
+
+    /// Digest of the arguments passed to the main function.
+    pub(crate) fn args_hash(&self) -> &Digest {
+        &self.args_hash
+    }
+
     /// Convert into internal data.
-    pub fn into_inner(self) -> (Vec<u8>, Vec<Value>, Vec<TypeTag>, Vec<AccountAddress>) {
+    pub fn into_inner(self) -> (Arc<[u8]>, Vec<Value>, Vec<TypeTag>, Vec<AccountAddress>) {
         (self.code, self.args, self.type_args, self.senders)
     }
 }
@@ -136,12 +533,252 @@ impl fmt::Debug for ScriptTx {
             .field("args", &self.args)
             .field("type_args", &self.type_args)
             .field("senders", &self.senders)
+            .field("proof", &hex::encode(&self.proof))
+            .field("fee_payer", &self.fee_payer)
+            .field("chain_id", &self.chain_id)
+            .field("sequence", &self.sequence)
+            .finish()
+    }
+}
+
+/// Call of an entry function of a published module, a step of `BatchTx`.
+pub struct EntryCall {
+    module: ModuleId,
+    function: Identifier,
+    args: Vec<Value>,
+    type_args: Vec<TypeTag>,
+    args_size: usize,
+    args_hash: Digest,
+}
+
+impl EntryCall {
+    /// Constructor.
+    /// The senders of the batch are passed to the leading `&signer` parameters of the function.
+    pub fn new(
+        module: ModuleId,
+        function: Identifier,
+        args: Vec<ScriptArg>,
+        type_args: Vec<TypeTag>,
+    ) -> Self {
+        EntryCall {
+            module,
+            function,
+            args_size: args.iter().map(ScriptArg::size).sum(),
+            args_hash: args_hash(&args),
+            args: args.into_iter().map(ScriptArg::into).collect(),
+            type_args,
+        }
+    }
+
+    /// Module of the called function.
+    pub fn module(&self) -> &ModuleId {
+        &self.module
+    }
+
+    /// Name of the called function.
+    pub fn function(&self) -> &Identifier {
+        &self.function
+    }
+
+    /// Parameters passed to the function after the signers.
+    pub fn args(&self) -> &[Value] {
+        &self.args
+    }
+
+    /// Type parameters passed to the function.
+    pub fn type_parameters(&self) -> &[TypeTag] {
+        &self.type_args
+    }
+
+    /// Digest of the arguments passed to the function.
+    pub(crate) fn args_hash(&self) -> &Digest {
+        &self.args_hash
+    }
+
+    /// Convert into internal data.
+    pub fn into_inner(self) -> (ModuleId, Identifier, Vec<Value>, Vec<TypeTag>) {
+        (self.module, self.function, self.args, self.type_args)
+    }
+}
+
+impl fmt::Debug for EntryCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntryCall")
+            .field("module", &self.module)
+            .field("function", &self.function)
+            .field("args", &self.args)
+            .field("type_args", &self.type_args)
             .finish()
     }
 }
 
+/// Sequence of entry function calls executed atomically by the same senders.
+///
+/// The batch is a single transaction: it is authenticated with one proof, has one validity
+/// window and one sequence number, and its checks run even if the batch has no calls.
+pub struct BatchTx {
+    calls: Vec<EntryCall>,
+    senders: Vec<AccountAddress>,
+    proof: Vec<u8>,
+    fee_payer: Option<AccountAddress>,
+    expiration_timestamp: Option<u64>,
+    not_before: Option<u64>,
+    chain_id: Option<u8>,
+    sequence: Option<(u64, u64)>,
+}
+
+impl BatchTx {
+    /// Constructor.
+    pub fn new(calls: Vec<EntryCall>, senders: Vec<AccountAddress>) -> Self {
+        BatchTx {
+            calls,
+            senders,
+            proof: vec![],
+            fee_payer: None,
+            expiration_timestamp: None,
+            not_before: None,
+            chain_id: None,
+            sequence: None,
+        }
+    }
+
+    /// Checks the number of the type arguments of each call and the total size of the arguments.
+    pub fn check_limits(&self, limits: &TxLimits) -> Result<(), StatusCode> {
+        if self
+            .calls
+            .iter()
+            .any(|call| call.type_args.len() > limits.max_type_args)
+        {
+            Err(StatusCode::TOO_MANY_TYPE_ARGUMENTS)
+        } else if self.calls.iter().map(|call| call.args_size).sum::<usize>() > limits.max_args_size
+        {
+            Err(StatusCode::EXCEEDED_MAX_ARGUMENTS_SIZE)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sets the account which pays the transaction fee of the batch.
+    pub fn with_fee_payer(mut self, fee_payer: AccountAddress) -> Self {
+        self.fee_payer = Some(fee_payer);
+        self
+    }
+
+    /// Attaches the authentication proof checked by the vm `Authenticator`.
+    pub fn with_proof(mut self, proof: Vec<u8>) -> Self {
+        self.proof = proof;
+        self
+    }
+
+    /// Sets the block timestamp from which the transaction is rejected with `TRANSACTION_EXPIRED`.
+    pub fn with_expiration(mut self, expiration_timestamp: u64) -> Self {
+        self.expiration_timestamp = Some(expiration_timestamp);
+        self
+    }
+
+    /// Sets the first block timestamp the transaction can be executed at.
+    /// Earlier blocks reject it with `TRANSACTION_NOT_YET_VALID`.
+    pub fn with_not_before(mut self, not_before: u64) -> Self {
+        self.not_before = Some(not_before);
+        self
+    }
+
+    /// Sets the id of the chain the transaction is signed for.
+    pub fn with_chain_id(mut self, chain_id: u8) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Sets the sequence number of the transaction on the lane of the first sender.
+    /// See `lanes` module.
+    pub fn with_sequence_number(mut self, lane: u64, sequence_number: u64) -> Self {
+        self.sequence = Some((lane, sequence_number));
+        self
+    }
+
+    /// Checks that the transaction can be executed in the block with the timestamp.
+    pub fn check_validity_window(&self, timestamp: u64) -> Result<(), StatusCode> {
+        check_validity_window(self.not_before, self.expiration_timestamp, timestamp)
+    }
+
+    /// Batch calls.
+    pub fn calls(&self) -> &[EntryCall] {
+        &self.calls
+    }
+
+    /// Batch senders.
+    pub fn senders(&self) -> &[AccountAddress] {
+        &self.senders
+    }
+
+    /// Authentication proof.
+    pub fn proof(&self) -> &[u8] {
+        &self.proof
+    }
+
+    /// Sponsor of the transaction fee.
+    pub fn fee_payer(&self) -> Option<&AccountAddress> {
+        self.fee_payer.as_ref()
+    }
+
+    /// Block timestamp from which the transaction expires.
+    pub fn expiration_timestamp(&self) -> Option<u64> {
+        self.expiration_timestamp
+    }
+
+    /// First block timestamp the transaction is valid at.
+    pub fn not_before(&self) -> Option<u64> {
+        self.not_before
+    }
+
+    /// Id of the chain the transaction is signed for.
+    pub fn chain_id(&self) -> Option<u8> {
+        self.chain_id
+    }
+
+    /// Lane and sequence number of the transaction.
+    pub fn sequence_number(&self) -> Option<(u64, u64)> {
+        self.sequence
+    }
+
+    /// Convert into internal data.
+    pub fn into_inner(self) -> (Vec<EntryCall>, Vec<AccountAddress>) {
+        (self.calls, self.senders)
+    }
+}
+
+impl fmt::Debug for BatchTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Batch")
+            .field("calls", &self.calls)
+            .field("senders", &self.senders)
+            .field("proof", &hex::encode(&self.proof))
+            .field("fee_payer", &self.fee_payer)
+            .field("expiration_timestamp", &self.expiration_timestamp)
+            .field("not_before", &self.not_before)
+            .field("chain_id", &self.chain_id)
+            .field("sequence", &self.sequence)
+            .finish()
+    }
+}
+
+/// Checks the block timestamp against the validity window of a transaction.
+fn check_validity_window(
+    not_before: Option<u64>,
+    expiration_timestamp: Option<u64>,
+    timestamp: u64,
+) -> Result<(), StatusCode> {
+    match (not_before, expiration_timestamp) {
+        (Some(not_before), _) if timestamp < not_before => {
+            Err(StatusCode::TRANSACTION_NOT_YET_VALID)
+        }
+        (_, Some(expiration)) if timestamp >= expiration => Err(StatusCode::TRANSACTION_EXPIRED),
+        _ => Ok(()),
+    }
+}
+
 /// Move VM result.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct VmResult {
     /// Execution status code.
     pub status_code: StatusCode,
@@ -149,6 +786,33 @@ pub struct VmResult {
     pub sub_status: Option<u64>,
     /// Gas used.
     pub gas_used: u64,
+    /// Oracle prices read by the transaction, in the order of the first read.
+    pub price_reads: Vec<PriceRead>,
+    /// Native balance changes applied by the transaction, ordered by wallet id,
+    /// followed by the fee of the sponsored transaction.
+    pub balance_changes: Vec<BalanceChange>,
+    /// Message of the `Errors::abort_with_message` abort.
+    #[serde(default)]
+    pub abort_message: Option<String>,
+    /// Bytecode location of the error in a module.
+    #[serde(default)]
+    pub location: Option<ErrorLocation>,
+    /// Source location of the error if the module is published with a source map.
+    #[serde(default)]
+    pub source_location: Option<SourceLocation>,
+    /// Digest of the canonical write set of the applied transaction, see `write_set::WriteSet`.
+    /// The write set of the failed transaction kept in the block holds the writes applied
+    /// anyway, e.g. the sequence numbers and the halt flag. `None` if the transaction is
+    /// discarded.
+    #[serde(default)]
+    pub write_set_digest: Option<Digest>,
+    /// Storage keys deleted by the applied transaction ordered by the key.
+    #[serde(default)]
+    pub deletions: Vec<Vec<u8>>,
+    /// The transaction tripped the circuit breaker. It is kept in the block with the write
+    /// of the halt flag, see `circuit_breaker`.
+    #[serde(default)]
+    pub halted: bool,
 }
 
 impl VmResult {
@@ -158,10 +822,193 @@ impl VmResult {
             status_code,
             sub_status,
             gas_used,
+            price_reads: vec![],
+            balance_changes: vec![],
+            abort_message: None,
+            location: None,
+            source_location: None,
+            write_set_digest: None,
+            deletions: vec![],
+            halted: false,
+        }
+    }
+
+    /// Attaches the abort message to the aborted result.
+    pub(crate) fn with_abort_message(mut self, abort_message: Option<String>) -> VmResult {
+        if self.status_code == StatusCode::ABORTED {
+            self.abort_message = abort_message;
+        }
+        self
+    }
+
+    /// Returns the class of the status code.
+    pub fn status_type(&self) -> StatusType {
+        self.status_code.status_type()
+    }
+
+    /// Returns `true` if the transaction was executed successfully.
+    pub fn is_success(&self) -> bool {
+        self.status_code == StatusCode::EXECUTED
+    }
+
+    /// Returns `true` if the transaction must be discarded: it is not included in the block
+    /// and no fee is charged.
+    /// Validation, invariant violation and unknown statuses are discarded,
+    /// executed, aborted, verification and deserialization failures are kept as in Diem.
+    /// The invariant violation which tripped the circuit breaker is kept to halt the vm.
+    pub fn is_discarded(&self) -> bool {
+        !self.halted
+            && matches!(
+                self.status_type(),
+                StatusType::Validation | StatusType::InvariantViolation | StatusType::Unknown
+            )
+    }
+}
+
+impl fmt::Display for VmResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} ({})", self.status_code, self.status_code as u64)?;
+        if let Some(sub_status) = self.sub_status {
+            write!(f, ", sub status: {}", sub_status)?;
         }
+        if let Some(abort_message) = &self.abort_message {
+            write!(f, ", message: {:?}", abort_message)?;
+        }
+        match (&self.source_location, &self.location) {
+            (Some(source_location), _) => write!(f, ", at {}", source_location)?,
+            (None, Some(location)) => write!(f, ", at {}", location)?,
+            (None, None) => {}
+        }
+        write!(f, ", gas used: {}", self.gas_used)
     }
 }
 
+/// Native balance change applied by the transaction.
+/// `Deposit` moves coins from the native balance to the vm, `Withdraw` moves them back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceChange {
+    pub wallet_id: WalletId,
+    pub operation: BalanceOperation,
+}
+
+/// Page of the resources of an account ordered by the storage key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourcesPage {
+    /// Resource tags with the serialized resources.
+    pub resources: Vec<(StructTag, Vec<u8>)>,
+    /// Cursor of the next page or `None` if this is the last page.
+    pub cursor: Option<StructTag>,
+}
+
+/// Balance of a currency held by an account.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceBreakdown {
+    /// Native balance provided by the host.
+    pub native: u128,
+    /// Coins deposited to the vm and held in the `Account::Balance` resource.
+    pub wrapped: u128,
+}
+
+impl BalanceBreakdown {
+    /// Returns the sum of the native and the wrapped balances.
+    pub fn total(&self) -> u128 {
+        self.native.saturating_add(self.wrapped)
+    }
+}
+
+/// Currency or price ticker, e.g. `PONT` or `ETH_BTC`.
+///
+/// Tickers are non-empty ASCII letters, digits and underscores of at most `Ticker::MAX_LEN`
+/// characters, kept in upper case so that `usdt` and `USDT` name the same currency.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Ticker(String);
+
+impl Ticker {
+    /// Max ticker length.
+    pub const MAX_LEN: usize = 32;
+
+    /// Validates the ticker and converts it to the canonical (upper) case.
+    pub fn new(ticker: &str) -> Result<Ticker> {
+        ensure!(!ticker.is_empty(), "Ticker must not be empty");
+        ensure!(
+            ticker.len() <= Ticker::MAX_LEN,
+            "Ticker length must not exceed {} characters",
+            Ticker::MAX_LEN
+        );
+        ensure!(
+            ticker
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_'),
+            "Ticker must contain only ASCII letters, digits and underscores: {}",
+            ticker
+        );
+        Ok(Ticker(ticker.to_ascii_uppercase()))
+    }
+
+    /// Returns the ticker as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<&str> for Ticker {
+    type Error = Error;
+
+    fn try_from(ticker: &str) -> Result<Self, Self::Error> {
+        Ticker::new(ticker)
+    }
+}
+
+impl TryFrom<String> for Ticker {
+    type Error = Error;
+
+    fn try_from(ticker: String) -> Result<Self, Self::Error> {
+        Ticker::new(&ticker)
+    }
+}
+
+impl From<Ticker> for String {
+    fn from(ticker: Ticker) -> Self {
+        ticker.0
+    }
+}
+
+impl AsRef<str> for Ticker {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Ticker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Encode for Ticker {
+    fn encode_to<T: Output>(&self, dest: &mut T) {
+        self.0.as_bytes().encode_to(dest);
+    }
+}
+
+impl Decode for Ticker {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        let bytes = Vec::<u8>::decode(input)?;
+        let ticker = core::str::from_utf8(&bytes).map_err(|_| "Invalid ticker encoding")?;
+        Ticker::new(ticker).map_err(|_| "Invalid ticker".into())
+    }
+}
+
+/// Oracle price read by the transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceRead {
+    /// Price ticker, e.g. `ETH_BTC`.
+    pub ticker: Ticker,
+    /// Price provided by the oracle. `None` if the oracle had no price.
+    pub price: Option<u128>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, PartialOrd)]
 pub enum ScriptArg {
     U8(u8),
@@ -176,6 +1023,23 @@ pub enum ScriptArg {
     VectorAddress(Vec<AccountAddress>),
 }
 
+impl ScriptArg {
+    /// Returns the size of the argument value in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            ScriptArg::U8(_) | ScriptArg::Bool(_) => 1,
+            ScriptArg::U64(_) => 8,
+            ScriptArg::U128(_) => 16,
+            ScriptArg::Address(_) => AccountAddress::LENGTH,
+            ScriptArg::VectorU8(val) => val.len(),
+            ScriptArg::VectorU64(val) => val.len() * 8,
+            ScriptArg::VectorU128(val) => val.len() * 16,
+            ScriptArg::VectorBool(val) => val.len(),
+            ScriptArg::VectorAddress(val) => val.len() * AccountAddress::LENGTH,
+        }
+    }
+}
+
 impl From<ScriptArg> for Value {
     fn from(arg: ScriptArg) -> Self {
         match arg {
@@ -193,6 +1057,64 @@ impl From<ScriptArg> for Value {
     }
 }
 
+impl TryFrom<MoveValue> for ScriptArg {
+    type Error = Error;
+
+    fn try_from(value: MoveValue) -> Result<Self, Self::Error> {
+        fn vector<T>(
+            values: Vec<MoveValue>,
+            f: impl Fn(MoveValue) -> Option<T>,
+        ) -> Result<Vec<T>, Error> {
+            values
+                .into_iter()
+                .map(|value| f(value).ok_or_else(|| Error::msg("Unsupported vector element")))
+                .collect()
+        }
+
+        Ok(match value {
+            MoveValue::U8(val) => ScriptArg::U8(val),
+            MoveValue::U64(val) => ScriptArg::U64(val),
+            MoveValue::U128(val) => ScriptArg::U128(val),
+            MoveValue::Bool(val) => ScriptArg::Bool(val),
+            MoveValue::Address(val) => ScriptArg::Address(val),
+            MoveValue::Vector(values) => match values.first() {
+                None | Some(MoveValue::U8(_)) => {
+                    ScriptArg::VectorU8(vector(values, |val| match val {
+                        MoveValue::U8(val) => Some(val),
+                        _ => None,
+                    })?)
+                }
+                Some(MoveValue::U64(_)) => ScriptArg::VectorU64(vector(values, |val| match val {
+                    MoveValue::U64(val) => Some(val),
+                    _ => None,
+                })?),
+                Some(MoveValue::U128(_)) => {
+                    ScriptArg::VectorU128(vector(values, |val| match val {
+                        MoveValue::U128(val) => Some(val),
+                        _ => None,
+                    })?)
+                }
+                Some(MoveValue::Bool(_)) => {
+                    ScriptArg::VectorBool(vector(values, |val| match val {
+                        MoveValue::Bool(val) => Some(val),
+                        _ => None,
+                    })?)
+                }
+                Some(MoveValue::Address(_)) => {
+                    ScriptArg::VectorAddress(vector(values, |val| match val {
+                        MoveValue::Address(val) => Some(val),
+                        _ => None,
+                    })?)
+                }
+                Some(_) => bail!("Unsupported vector element"),
+            },
+            MoveValue::Struct(_) | MoveValue::Signer(_) => {
+                bail!("Structs and signers can not be passed to scripts")
+            }
+        })
+    }
+}
+
 pub fn parse_type_params(tkn: &str) -> Result<Vec<TypeTag>> {
     let map_err = |err| Error::msg(format!("{:?}", err));
 
@@ -322,6 +1244,9 @@ impl ModulePackage {
         PublishPackageTx {
             modules: self.modules,
             address,
+            package: None,
+            chain_id: None,
+            tenant: None,
         }
     }
 }
@@ -338,9 +1263,56 @@ impl TryFrom<&[u8]> for ModulePackage {
 pub struct PublishPackageTx {
     modules: Vec<Vec<u8>>,
     address: AccountAddress,
+    package: Option<(Identifier, UpgradePolicy)>,
+    chain_id: Option<u8>,
+    tenant: Option<TenantId>,
 }
 
 impl PublishPackageTx {
+    pub fn new(modules: Vec<Vec<u8>>, address: AccountAddress) -> PublishPackageTx {
+        PublishPackageTx {
+            modules,
+            address,
+            package: None,
+            chain_id: None,
+            tenant: None,
+        }
+    }
+
+    /// Publishes the modules as a release of the named package with the upgrade policy.
+    /// See `package` module.
+    pub fn with_package(mut self, name: Identifier, policy: UpgradePolicy) -> PublishPackageTx {
+        self.package = Some((name, policy));
+        self
+    }
+
+    /// Returns the name and the upgrade policy of the package.
+    pub fn package(&self) -> Option<&(Identifier, UpgradePolicy)> {
+        self.package.as_ref()
+    }
+
+    /// Sets the id of the chain the transaction is signed for.
+    pub fn with_chain_id(mut self, chain_id: u8) -> PublishPackageTx {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Returns the id of the chain the transaction is signed for.
+    pub fn chain_id(&self) -> Option<u8> {
+        self.chain_id
+    }
+
+    /// Publishes the modules in the storage space of the tenant, see `tenant`.
+    pub fn with_tenant(mut self, tenant: TenantId) -> PublishPackageTx {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Returns the tenant of the modules or `None` for the modules of the host.
+    pub fn tenant(&self) -> Option<TenantId> {
+        self.tenant
+    }
+
     pub fn into_inner(self) -> (Vec<Vec<u8>>, AccountAddress) {
         (self.modules, self.address)
     }
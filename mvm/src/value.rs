@@ -0,0 +1,123 @@
+//! Conversions between Rust types and Move values.
+//!
+//! Types implementing `AsMoveValue` can be passed as script arguments and decoded from
+//! the resources and the events. Implement it for Rust structs with `#[derive(AsMoveValue)]`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+pub use anyhow::Error;
+pub use move_core_types::value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue};
+pub use mvm_derive::AsMoveValue;
+
+use move_core_types::account_address::AccountAddress;
+
+use crate::types::ScriptArg;
+
+/// Rust type with a Move representation.
+pub trait AsMoveValue: Sized {
+    /// Returns the layout of the Move value.
+    fn layout() -> MoveTypeLayout;
+
+    /// Converts into the Move value.
+    fn as_move_value(&self) -> MoveValue;
+
+    /// Converts from the Move value.
+    fn from_move_value(value: MoveValue) -> Result<Self, Error>;
+
+    /// Decodes the value from the serialized resource or event.
+    fn from_bytes(blob: &[u8]) -> Result<Self, Error> {
+        Self::from_move_value(MoveValue::simple_deserialize(blob, &Self::layout())?)
+    }
+
+    /// Serializes the value as a resource or an event.
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        self.as_move_value()
+            .simple_serialize()
+            .ok_or_else(|| Error::msg("Failed to serialize value"))
+    }
+
+    /// Converts into the script argument.
+    /// Only primitives and vectors of primitives can be passed to scripts.
+    fn to_script_arg(&self) -> Result<ScriptArg, Error> {
+        ScriptArg::try_from(self.as_move_value())
+    }
+}
+
+/// Fields of a struct value in the declaration order.
+pub struct StructFields(vec::IntoIter<MoveValue>);
+
+impl StructFields {
+    /// Takes the fields of the struct value with the expected number of fields.
+    pub fn new(value: MoveValue, count: usize) -> Result<StructFields, Error> {
+        match value {
+            MoveValue::Struct(value) if value.fields().len() == count => {
+                Ok(StructFields(value.into_inner().into_iter()))
+            }
+            MoveValue::Struct(value) => Err(Error::msg(format!(
+                "Expected struct with {} fields, found {}",
+                count,
+                value.fields().len()
+            ))),
+            value => Err(unexpected("struct", &value)),
+        }
+    }
+
+    /// Converts the next field.
+    pub fn next_field<T: AsMoveValue>(&mut self) -> Result<T, Error> {
+        let value = self
+            .0
+            .next()
+            .ok_or_else(|| Error::msg("Missing struct field"))?;
+        T::from_move_value(value)
+    }
+}
+
+fn unexpected(expected: &str, value: &MoveValue) -> Error {
+    Error::msg(format!("Expected {}, found {:?}", expected, value))
+}
+
+macro_rules! primitive {
+    ($ty:ty, $variant:ident, $name:expr) => {
+        impl AsMoveValue for $ty {
+            fn layout() -> MoveTypeLayout {
+                MoveTypeLayout::$variant
+            }
+
+            fn as_move_value(&self) -> MoveValue {
+                MoveValue::$variant(*self)
+            }
+
+            fn from_move_value(value: MoveValue) -> Result<Self, Error> {
+                match value {
+                    MoveValue::$variant(value) => Ok(value),
+                    value => Err(unexpected($name, &value)),
+                }
+            }
+        }
+    };
+}
+
+primitive!(bool, Bool, "bool");
+primitive!(u8, U8, "u8");
+primitive!(u64, U64, "u64");
+primitive!(u128, U128, "u128");
+primitive!(AccountAddress, Address, "address");
+
+impl<T: AsMoveValue> AsMoveValue for Vec<T> {
+    fn layout() -> MoveTypeLayout {
+        MoveTypeLayout::Vector(alloc::boxed::Box::new(T::layout()))
+    }
+
+    fn as_move_value(&self) -> MoveValue {
+        MoveValue::Vector(self.iter().map(AsMoveValue::as_move_value).collect())
+    }
+
+    fn from_move_value(value: MoveValue) -> Result<Self, Error> {
+        match value {
+            MoveValue::Vector(values) => values.into_iter().map(T::from_move_value).collect(),
+            value => Err(unexpected("vector", &value)),
+        }
+    }
+}
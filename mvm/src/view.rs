@@ -0,0 +1,212 @@
+//! View function results cache.
+//!
+//! `Mvm::view_function` executes a public function without applying its effects and returns
+//! its BCS serialized return values. With the view cache enabled the successful results are kept
+//! under the call, the session policy and the state version passed by the caller, e.g. the block
+//! height.
+//! Entries are dropped when their TTL expires, when the vm writes a resource or changes a native
+//! balance they read, on `Mvm::invalidate_view_paths`, `Mvm::invalidate_view_balances` and
+//! `Mvm::invalidate_view_cache`.
+//! Modules read by the call are not tracked: publishing modules clears the cache.
+
+use alloc::collections::btree_map::Entry;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, TypeTag};
+use spin::Mutex;
+
+use crate::data::SessionPolicy;
+use crate::tenant::TenantId;
+use crate::types::ScriptArg;
+
+/// Call of a view function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewCall {
+    pub module: ModuleId,
+    pub function: Identifier,
+    pub type_args: Vec<TypeTag>,
+    pub args: Vec<ScriptArg>,
+}
+
+impl ViewCall {
+    /// Creates call of the function without type arguments.
+    pub fn new(module: ModuleId, function: Identifier, args: Vec<ScriptArg>) -> ViewCall {
+        ViewCall {
+            module,
+            function,
+            type_args: vec![],
+            args,
+        }
+    }
+
+    /// Sets the type arguments of the call.
+    pub fn with_type_args(mut self, type_args: Vec<TypeTag>) -> ViewCall {
+        self.type_args = type_args;
+        self
+    }
+
+    /// Returns the cache key of the call of the tenant with the session policy
+    /// on the state version.
+    pub(crate) fn key(
+        &self,
+        tenant: Option<TenantId>,
+        policy: SessionPolicy,
+        state_version: u64,
+    ) -> Option<ViewKey> {
+        bcs::to_bytes(&(
+            tenant,
+            (policy.bank, policy.oracle),
+            &self.module,
+            &self.function,
+            &self.type_args,
+            &self.args,
+        ))
+        .ok()
+        .map(|call| (call, state_version))
+    }
+}
+
+/// Key of the cached result: the serialized call and the state version.
+pub(crate) type ViewKey = (Vec<u8>, u64);
+
+/// Value read by a view call.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ViewRead {
+    /// Storage key of a resource, including the price histories of the average prices.
+    Resource(Vec<u8>),
+    /// Native balances of the account.
+    Balance(AccountAddress),
+}
+
+struct ViewEntry {
+    values: Vec<Vec<u8>>,
+    /// Timestamp from which the entry is expired.
+    expires_at: u64,
+    /// Values read by the call.
+    reads: Vec<ViewRead>,
+    /// Insertion number of the entry, the oldest entries are evicted first.
+    seq: u64,
+}
+
+/// Bounded cache of the view results. The oldest entries are evicted first.
+pub(crate) struct ViewCache {
+    capacity: usize,
+    ttl: u64,
+    inner: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    results: BTreeMap<ViewKey, ViewEntry>,
+    /// Keys of the results by the insertion number.
+    order: BTreeMap<u64, ViewKey>,
+    /// Keys of the results by the values they read.
+    readers: BTreeMap<ViewRead, BTreeSet<ViewKey>>,
+    next_seq: u64,
+}
+
+impl Entries {
+    fn remove(&mut self, key: &ViewKey) {
+        let entry = match self.results.remove(key) {
+            Some(entry) => entry,
+            None => return,
+        };
+        self.order.remove(&entry.seq);
+        for read in entry.reads {
+            if let Entry::Occupied(mut readers) = self.readers.entry(read) {
+                readers.get_mut().remove(key);
+                if readers.get().is_empty() {
+                    readers.remove();
+                }
+            }
+        }
+    }
+
+    fn invalidate(&mut self, read: &ViewRead) {
+        if let Some(keys) = self.readers.remove(read) {
+            for key in keys {
+                self.remove(&key);
+            }
+        }
+    }
+}
+
+impl ViewCache {
+    pub fn new(capacity: usize, ttl: u64) -> ViewCache {
+        ViewCache {
+            capacity,
+            ttl,
+            inner: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Returns the result cached before the timestamp expired it.
+    pub fn get(&self, key: &ViewKey, timestamp: u64) -> Option<Vec<Vec<u8>>> {
+        let mut entries = self.inner.lock();
+        match entries.results.get(key) {
+            Some(entry) if timestamp < entry.expires_at => Some(entry.values.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: ViewKey, values: Vec<Vec<u8>>, reads: Vec<ViewRead>, timestamp: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.inner.lock();
+        entries.remove(&key);
+        let seq = entries.next_seq;
+        entries.next_seq += 1;
+        for read in &reads {
+            entries
+                .readers
+                .entry(read.clone())
+                .or_default()
+                .insert(key.clone());
+        }
+        entries.order.insert(seq, key.clone());
+        entries.results.insert(
+            key,
+            ViewEntry {
+                values,
+                expires_at: timestamp.saturating_add(self.ttl),
+                reads,
+                seq,
+            },
+        );
+        while entries.results.len() > self.capacity {
+            let oldest = match entries.order.values().next() {
+                Some(oldest) => oldest.clone(),
+                None => break,
+            };
+            entries.remove(&oldest);
+        }
+    }
+
+    /// Drops the results which read the resource stored under the key.
+    pub fn invalidate_key(&self, key: &[u8]) {
+        self.inner
+            .lock()
+            .invalidate(&ViewRead::Resource(key.to_vec()));
+    }
+
+    /// Drops the results which read a native balance of the account.
+    pub fn invalidate_balances(&self, address: &AccountAddress) {
+        self.inner.lock().invalidate(&ViewRead::Balance(*address));
+    }
+
+    pub fn clear(&self) {
+        *self.inner.lock() = Entries::default();
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().results.len()
+    }
+}
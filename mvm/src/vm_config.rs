@@ -1,26 +1,588 @@
 use crate::gas_schedule::cost_table;
+use crate::oracle::ORACLE_ADDRESS;
+use crate::types::{GasBounds, Ticker, TxLimits};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use anyhow::ensure;
+use move_core_types::account_address::AccountAddress;
 use move_core_types::gas_schedule::CostTable;
-use parity_scale_codec::{Decode, Encode};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_vm_runtime::move_vm::{StorageAccessCosts, VMLimits, VerificationCosts};
+use parity_scale_codec::{Decode, Encode, Error, Input};
 use serde::{Deserialize, Serialize};
 
+/// Default address of the on-chain vm configuration.
+pub const CONFIG_ADDRESS: AccountAddress = AccountAddress::new([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xA, 0x55,
+    0x0C, 0x18,
+]);
+
+/// Well-known addresses used by the vm.
+/// Chains which relocate the standard library or the governance account override the defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AddressesConfig {
+    /// Address of the standard library, its natives and the native currencies.
+    pub core_code_address: AccountAddress,
+    /// Address of the on-chain vm configuration.
+    pub config_address: AccountAddress,
+    /// Address of the synthesized `Oracle::Price<Ticker>` resources.
+    pub oracle_address: AccountAddress,
+}
+
+impl Default for AddressesConfig {
+    fn default() -> Self {
+        AddressesConfig {
+            core_code_address: CORE_CODE_ADDRESS,
+            config_address: CONFIG_ADDRESS,
+            oracle_address: ORACLE_ADDRESS,
+        }
+    }
+}
+
 /// Defines all the on chain configuration data needed by VM.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Encode, Decode)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Encode)]
+#[serde(default)]
 pub struct VmConfig {
     pub gas_schedule: CostTable,
+    /// Maximum depth of the call stack.
+    pub max_call_depth: u32,
+    /// Maximum depth of the type arguments of a generic call.
+    pub max_type_depth: u32,
+    /// Maximum size of the published module in bytes.
+    pub max_module_size: u32,
+    /// Maximum number of functions defined by the published module.
+    pub max_functions: u32,
+    /// Maximum number of structs defined by the published module.
+    pub max_structs: u32,
+    /// Maximum length of an identifier of the published module.
+    pub max_identifier_length: u32,
+    /// Verification gas per basic block of the published module.
+    pub verify_per_basic_block: u64,
+    /// Verification gas per control flow edge of the published module.
+    pub verify_per_edge: u64,
+    /// Verification gas per function local of the published module.
+    pub verify_per_local: u64,
+    /// Create the `Account` resources for the script senders which have native balance
+    /// but no account yet.
+    pub lazy_accounts: bool,
+    /// Maximum number of events emitted by a transaction.
+    pub max_events: u32,
+    /// Maximum total size of the events emitted by a transaction in bytes.
+    pub max_event_bytes: u32,
+    /// Gas of the first access to a resource in a transaction.
+    pub cold_access_cost: u64,
+    /// Gas of a repeated access to a resource in a transaction.
+    pub warm_access_cost: u64,
+    /// Chain id the transactions must be signed for.
+    /// Transactions without the matching chain id fail with `BAD_CHAIN_ID`.
+    /// `None` accepts the transactions of any chain.
+    pub chain_id: Option<u8>,
+    /// Maximum size of the script bytecode in bytes.
+    pub max_script_size: u32,
+    /// Maximum number of the script type arguments.
+    pub max_type_args: u32,
+    /// Maximum total size of the script arguments in bytes.
+    pub max_args_size: u32,
+    /// Maximum gas used by the transactions of a block, see `block_gas`.
+    /// `None` doesn't limit the block gas.
+    pub max_block_gas: Option<u64>,
+    /// Fail the transactions whose balance changes don't conserve the coins of a currency,
+    /// see `coin_bridge::check_conservation`.
+    pub check_balance_conservation: bool,
+    /// Minimum price per unit of gas, see `types::GasBuilder`.
+    pub min_gas_unit_price: u64,
+    /// Maximum price per unit of gas. `None` doesn't limit the price.
+    pub max_gas_unit_price: Option<u64>,
+    /// Maximum gas units of a transaction. `None` keeps only the static limit of `Gas`.
+    pub max_gas_per_tx: Option<u64>,
+}
+
+impl VmConfig {
+    /// Creates config with the given gas schedule and the default limits.
+    pub fn with_gas_schedule(gas_schedule: CostTable) -> VmConfig {
+        let limits = VMLimits::default();
+        let verification = VerificationCosts::default();
+        let access = StorageAccessCosts::default();
+        let tx_limits = TxLimits::default();
+        VmConfig {
+            gas_schedule,
+            max_call_depth: limits.max_call_depth as u32,
+            max_type_depth: limits.max_type_depth as u32,
+            max_module_size: limits.max_module_size as u32,
+            max_functions: limits.max_functions as u32,
+            max_structs: limits.max_structs as u32,
+            max_identifier_length: limits.max_identifier_length as u32,
+            verify_per_basic_block: verification.per_basic_block,
+            verify_per_edge: verification.per_edge,
+            verify_per_local: verification.per_local,
+            lazy_accounts: false,
+            max_events: limits.max_events as u32,
+            max_event_bytes: limits.max_event_bytes as u32,
+            cold_access_cost: access.cold,
+            warm_access_cost: access.warm,
+            chain_id: None,
+            max_script_size: tx_limits.max_script_size as u32,
+            max_type_args: tx_limits.max_type_args as u32,
+            max_args_size: tx_limits.max_args_size as u32,
+            max_block_gas: None,
+            check_balance_conservation: false,
+            min_gas_unit_price: 0,
+            max_gas_unit_price: None,
+            max_gas_per_tx: None,
+        }
+    }
+
+    /// Creates config with the given gas schedule which keeps the behaviour of the vm
+    /// storing only the gas schedule: the limits added later are not enforced
+    /// and the costs added later are not charged.
+    pub fn legacy(gas_schedule: CostTable) -> VmConfig {
+        let limits = VMLimits::default();
+        VmConfig {
+            gas_schedule,
+            max_call_depth: limits.max_call_depth as u32,
+            max_type_depth: limits.max_type_depth as u32,
+            max_module_size: u32::MAX,
+            max_functions: u32::MAX,
+            max_structs: u32::MAX,
+            max_identifier_length: u32::MAX,
+            verify_per_basic_block: 0,
+            verify_per_edge: 0,
+            verify_per_local: 0,
+            lazy_accounts: false,
+            max_events: u32::MAX,
+            max_event_bytes: u32::MAX,
+            cold_access_cost: 0,
+            warm_access_cost: 0,
+            chain_id: None,
+            max_script_size: u32::MAX,
+            max_type_args: u32::MAX,
+            max_args_size: u32::MAX,
+            max_block_gas: None,
+            check_balance_conservation: false,
+            min_gas_unit_price: 0,
+            max_gas_unit_price: None,
+            max_gas_per_tx: None,
+        }
+    }
+
+    /// Returns execution limits of the vm.
+    pub fn limits(&self) -> VMLimits {
+        VMLimits {
+            max_call_depth: self.max_call_depth as usize,
+            max_type_depth: self.max_type_depth as usize,
+            max_module_size: self.max_module_size as usize,
+            max_functions: self.max_functions as usize,
+            max_structs: self.max_structs as usize,
+            max_identifier_length: self.max_identifier_length as usize,
+            max_events: self.max_events as usize,
+            max_event_bytes: self.max_event_bytes as usize,
+        }
+    }
+
+    /// Returns limits of the script transactions.
+    pub fn tx_limits(&self) -> TxLimits {
+        TxLimits {
+            max_script_size: self.max_script_size as usize,
+            max_type_args: self.max_type_args as usize,
+            max_args_size: self.max_args_size as usize,
+        }
+    }
+
+    /// Returns bounds of the transaction gas.
+    pub fn gas_bounds(&self) -> GasBounds {
+        GasBounds {
+            min_gas_unit_price: self.min_gas_unit_price,
+            max_gas_unit_price: self.max_gas_unit_price,
+            max_gas_per_tx: self.max_gas_per_tx,
+        }
+    }
+
+    /// Returns an error if the limits make any execution fail.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(self.max_call_depth > 0, "Max call depth must be positive");
+        ensure!(self.max_type_depth > 0, "Max type depth must be positive");
+        ensure!(self.max_module_size > 0, "Max module size must be positive");
+        ensure!(self.max_script_size > 0, "Max script size must be positive");
+        if let Some(max) = self.max_gas_unit_price {
+            ensure!(
+                max >= self.min_gas_unit_price,
+                "Max gas unit price must not be below the min gas unit price"
+            );
+        }
+        ensure!(
+            self.max_identifier_length > 0,
+            "Max identifier length must be positive"
+        );
+        Ok(())
+    }
+
+    /// Returns gas costs of the module verification.
+    pub fn verification_costs(&self) -> VerificationCosts {
+        VerificationCosts {
+            per_basic_block: self.verify_per_basic_block,
+            per_edge: self.verify_per_edge,
+            per_local: self.verify_per_local,
+        }
+    }
+
+    /// Returns gas costs of the cold and warm storage accesses.
+    pub fn access_costs(&self) -> StorageAccessCosts {
+        StorageAccessCosts {
+            cold: self.cold_access_cost,
+            warm: self.warm_access_cost,
+        }
+    }
 }
 
 impl Default for VmConfig {
     fn default() -> Self {
-        VmConfig {
-            gas_schedule: cost_table(),
+        VmConfig::with_gas_schedule(cost_table())
+    }
+}
+
+impl Decode for VmConfig {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        // Fields are appended over time. Configs stored by older versions end early,
+        // the missing fields take the legacy values, so the stored config keeps its behaviour.
+        let mut config = VmConfig::legacy(CostTable::decode(input)?);
+        if input.remaining_len()? != Some(0) {
+            config.max_call_depth = u32::decode(input)?;
+            config.max_type_depth = u32::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.max_module_size = u32::decode(input)?;
+            config.max_functions = u32::decode(input)?;
+            config.max_structs = u32::decode(input)?;
+            config.max_identifier_length = u32::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.verify_per_basic_block = u64::decode(input)?;
+            config.verify_per_edge = u64::decode(input)?;
+            config.verify_per_local = u64::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.lazy_accounts = bool::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.max_events = u32::decode(input)?;
+            config.max_event_bytes = u32::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.cold_access_cost = u64::decode(input)?;
+            config.warm_access_cost = u64::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.chain_id = Option::<u8>::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.max_script_size = u32::decode(input)?;
+            config.max_type_args = u32::decode(input)?;
+            config.max_args_size = u32::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.max_block_gas = Option::<u64>::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.check_balance_conservation = bool::decode(input)?;
+        }
+        if input.remaining_len()? != Some(0) {
+            config.min_gas_unit_price = u64::decode(input)?;
+            config.max_gas_unit_price = Option::<u64>::decode(input)?;
+            config.max_gas_per_tx = Option::<u64>::decode(input)?;
+        }
+        Ok(config)
+    }
+}
+
+/// Permission to publish modules.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum PublishPermission {
+    /// Any module can be published.
+    Any,
+    /// Only the modules with the listed names can be published.
+    Modules(Vec<String>),
+    /// Publishing is denied.
+    Denied,
+}
+
+impl PublishPermission {
+    /// Returns `true` if the module with the given name can be published.
+    pub fn allows(&self, module: &str) -> bool {
+        match self {
+            PublishPermission::Any => true,
+            PublishPermission::Modules(modules) => modules.iter().any(|name| name == module),
+            PublishPermission::Denied => false,
+        }
+    }
+
+    /// Returns an error if a listed name is not a valid module name.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let PublishPermission::Modules(modules) = self {
+            for name in modules {
+                ensure!(Identifier::is_valid(name), "Invalid module name: {}", name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// On-chain policy restricting who can publish modules.
+/// Addresses without an explicit permission get the default one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublisherPolicy {
+    /// Permission of the addresses which are not listed.
+    pub default: PublishPermission,
+    /// Permissions by address.
+    pub accounts: BTreeMap<AccountAddress, PublishPermission>,
+}
+
+impl PublisherPolicy {
+    /// Creates policy with the given default permission.
+    pub fn new(default: PublishPermission) -> PublisherPolicy {
+        PublisherPolicy {
+            default,
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the permission of the address.
+    pub fn with_account(
+        mut self,
+        address: AccountAddress,
+        permission: PublishPermission,
+    ) -> PublisherPolicy {
+        self.accounts.insert(address, permission);
+        self
+    }
+
+    /// Returns the permission of the address.
+    pub fn permission(&self, address: &AccountAddress) -> &PublishPermission {
+        self.accounts.get(address).unwrap_or(&self.default)
+    }
+
+    /// Returns an error if a permission lists an invalid module name.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.default.validate()?;
+        for permission in self.accounts.values() {
+            permission.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for PublisherPolicy {
+    fn default() -> Self {
+        PublisherPolicy::new(PublishPermission::Any)
+    }
+}
+
+impl Encode for PublisherPolicy {
+    fn encode(&self) -> Vec<u8> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|(address, permission)| (address.to_u8(), permission))
+            .collect::<Vec<_>>();
+        (&self.default, accounts).encode()
+    }
+}
+
+impl Decode for PublisherPolicy {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let (default, accounts) = <(
+            PublishPermission,
+            Vec<([u8; AccountAddress::LENGTH], PublishPermission)>,
+        )>::decode(input)?;
+        Ok(PublisherPolicy {
+            default,
+            accounts: accounts
+                .into_iter()
+                .map(|(address, permission)| (AccountAddress::new(address), permission))
+                .collect(),
+        })
+    }
+}
+
+/// Oracle prices readable by a module.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum OracleAccess {
+    /// All prices can be read.
+    Any,
+    /// Only the listed prices can be read.
+    Tickers(Vec<Ticker>),
+    /// Prices can not be read.
+    Denied,
+}
+
+impl OracleAccess {
+    /// Returns `true` if the price can be read.
+    pub fn allows(&self, ticker: &Ticker) -> bool {
+        match self {
+            OracleAccess::Any => true,
+            OracleAccess::Tickers(tickers) => tickers.contains(ticker),
+            OracleAccess::Denied => false,
+        }
+    }
+}
+
+/// On-chain access control list of the `Oracle::get_price` native.
+/// Scripts and the modules which are not listed get the default access.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OracleAcl {
+    /// Access of the scripts and the modules which are not listed.
+    pub default: OracleAccess,
+    /// Access by module.
+    pub modules: BTreeMap<ModuleId, OracleAccess>,
+}
+
+impl OracleAcl {
+    /// Creates access control list with the given default access.
+    pub fn new(default: OracleAccess) -> OracleAcl {
+        OracleAcl {
+            default,
+            modules: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the access of the module.
+    pub fn with_module(mut self, module: ModuleId, access: OracleAccess) -> OracleAcl {
+        self.modules.insert(module, access);
+        self
+    }
+
+    /// Returns the access of the calling module, `None` for scripts.
+    pub fn access(&self, caller: Option<&ModuleId>) -> &OracleAccess {
+        caller
+            .and_then(|module| self.modules.get(module))
+            .unwrap_or(&self.default)
+    }
+}
+
+impl Default for OracleAcl {
+    fn default() -> Self {
+        OracleAcl::new(OracleAccess::Any)
+    }
+}
+
+impl Encode for OracleAcl {
+    fn encode(&self) -> Vec<u8> {
+        let modules = self
+            .modules
+            .iter()
+            .map(|(module, access)| {
+                (
+                    module.address().to_u8(),
+                    module.name().as_str().as_bytes(),
+                    access,
+                )
+            })
+            .collect::<Vec<_>>();
+        (&self.default, modules).encode()
+    }
+}
+
+impl Decode for OracleAcl {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let (default, modules) = <(
+            OracleAccess,
+            Vec<([u8; AccountAddress::LENGTH], Vec<u8>, OracleAccess)>,
+        )>::decode(input)?;
+        let modules = modules
+            .into_iter()
+            .map(|(address, name, access)| {
+                let name = String::from_utf8(name).map_err(|_| "Invalid module name encoding")?;
+                let name = Identifier::new(name).map_err(|_| "Invalid module name")?;
+                Ok((ModuleId::new(AccountAddress::new(address), name), access))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(OracleAcl { default, modules })
+    }
+}
+
+/// On-chain list of the currencies the bank natives operate on.
+/// Without the list stored on-chain any currency is accepted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct RegisteredCurrencies {
+    /// Registered currency codes.
+    pub currencies: Vec<Ticker>,
+}
+
+impl RegisteredCurrencies {
+    /// Creates list of the given currencies.
+    pub fn new(currencies: Vec<Ticker>) -> RegisteredCurrencies {
+        RegisteredCurrencies { currencies }
+    }
+
+    /// Returns registered currency codes.
+    pub fn currencies(&self) -> &[Ticker] {
+        &self.currencies
+    }
+
+    /// Returns an error if a currency is registered twice.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (idx, ticker) in self.currencies.iter().enumerate() {
+            ensure!(
+                !self.currencies[..idx].contains(ticker),
+                "Currency {} is registered twice",
+                ticker
+            );
         }
+        Ok(())
+    }
+
+    /// Returns `true` if the currency is registered.
+    pub fn contains(&self, ticker: &Ticker) -> bool {
+        self.currencies.contains(ticker)
+    }
+
+    /// Registers the currency. Returns `false` if it is already registered.
+    pub fn register(&mut self, ticker: Ticker) -> bool {
+        if self.contains(&ticker) {
+            false
+        } else {
+            self.currencies.push(ticker);
+            true
+        }
+    }
+}
+
+/// Snapshot of all on-chain configs of the vm.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigView {
+    /// Current epoch.
+    pub epoch: u64,
+    /// Gas schedule and execution limits.
+    pub vm_config: VmConfig,
+    /// Module publishing restrictions.
+    pub publisher_policy: PublisherPolicy,
+    /// Access control list of the oracle natives.
+    pub oracle_acl: OracleAcl,
+    /// Currencies accepted by the bank natives, `None` if any currency is accepted.
+    pub registered_currencies: Option<RegisteredCurrencies>,
+    /// The vm is halted by the invariant violation circuit breaker.
+    pub halted: bool,
+}
+
+impl ConfigView {
+    /// Returns an error if any of the configs is invalid.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.vm_config.validate()?;
+        self.publisher_policy.validate()?;
+        if let Some(currencies) = &self.registered_currencies {
+            currencies.validate()?;
+        }
+        Ok(())
     }
 }
 
 pub mod loader {
     use crate::access_path::AccessPath;
     use crate::data::Storage;
-    use crate::vm_config::VmConfig;
+    use crate::vm_config::{
+        ConfigView, OracleAcl, PublisherPolicy, RegisteredCurrencies, VmConfig, CONFIG_ADDRESS,
+    };
     use alloc::vec::Vec;
     use anyhow::{Error, Result};
     use move_core_types::account_address::AccountAddress;
@@ -29,29 +591,65 @@ pub mod loader {
     use parity_scale_codec::{Decode, Encode};
 
     const IDENTIFIER: &str = "MVMConfig";
-    const CONFIG_ADDRESS_STR: &str = "0xA550C18";
+    const PUBLISHER_POLICY: &str = "PublisherPolicy";
+    const EPOCH: &str = "Epoch";
+    const ORACLE_ACL: &str = "OracleAcl";
+    const REGISTERED_CURRENCIES: &str = "RegisteredCurrencies";
+    const HALTED: &str = "Halted";
+
+    /// Returns the access path of the vm config stored under the given address.
+    pub fn access_path_for_config(address: AccountAddress) -> AccessPath {
+        access_path(address, IDENTIFIER)
+    }
+
+    /// Returns the access path of the publisher policy stored under the given address.
+    pub fn access_path_for_publisher_policy(address: AccountAddress) -> AccessPath {
+        access_path(address, PUBLISHER_POLICY)
+    }
+
+    /// Returns the access path of the current epoch stored under the given address.
+    pub fn access_path_for_epoch(address: AccountAddress) -> AccessPath {
+        access_path(address, EPOCH)
+    }
+
+    /// Returns the resource tag of the oracle access control list stored under the given address.
+    pub fn oracle_acl_tag(address: AccountAddress) -> StructTag {
+        config_tag(address, ORACLE_ACL)
+    }
+
+    /// Returns the access path of the oracle access control list stored under the given address.
+    pub fn access_path_for_oracle_acl(address: AccountAddress) -> AccessPath {
+        access_path(address, ORACLE_ACL)
+    }
+
+    /// Returns the access path of the registered currencies stored under the given address.
+    pub fn access_path_for_registered_currencies(address: AccountAddress) -> AccessPath {
+        access_path(address, REGISTERED_CURRENCIES)
+    }
 
-    fn config_address() -> AccountAddress {
-        AccountAddress::from_hex_literal(CONFIG_ADDRESS_STR).expect("failed to get address")
+    /// Returns the access path of the circuit breaker halt flag stored under the given address.
+    pub fn access_path_for_halted(address: AccountAddress) -> AccessPath {
+        access_path(address, HALTED)
     }
 
-    fn make_access_path() -> AccessPath {
-        let address = config_address();
-        let id = Identifier::new(IDENTIFIER).expect("failed to get Identifier");
+    fn config_tag(address: AccountAddress, name: &str) -> StructTag {
+        let id = Identifier::new(name).expect("failed to get Identifier");
+        StructTag {
+            address,
+            module: id.clone(),
+            name: id,
+            type_params: vec![],
+        }
+    }
 
+    fn access_path(address: AccountAddress, name: &str) -> AccessPath {
         AccessPath::new(
             address,
-            AccessPath::resource_access_vec(&StructTag {
-                address,
-                module: id.clone(),
-                name: id,
-                type_params: vec![],
-            }),
+            AccessPath::resource_access_vec(&config_tag(address, name)),
         )
     }
 
-    fn make_storage_key() -> Vec<u8> {
-        let path = make_access_path();
+    fn make_storage_key(path: AccessPath) -> Vec<u8> {
         let mut key = Vec::with_capacity(AccountAddress::LENGTH + path.path.len());
         key.extend_from_slice(&path.address.to_u8());
         key.extend_from_slice(&path.path);
@@ -60,7 +658,16 @@ pub mod loader {
 
     /// Loads vm config from storage. Returns default configuration if the config does not exists in the storage.
     pub fn load_vm_config<S: Storage>(storage: &S) -> Result<VmConfig, Error> {
-        if let Some(blob) = storage.get(&make_storage_key()) {
+        load_vm_config_at(storage, CONFIG_ADDRESS)
+    }
+
+    /// Loads vm config stored under the given address.
+    /// Returns default configuration if the config does not exists in the storage.
+    pub fn load_vm_config_at<S: Storage>(
+        storage: &S,
+        address: AccountAddress,
+    ) -> Result<VmConfig, Error> {
+        if let Some(blob) = storage.get(&make_storage_key(access_path_for_config(address))) {
             let mut input = blob.as_slice();
             VmConfig::decode(&mut input).map_err(|_| Error::msg("failed to decode VMConfig."))
         } else {
@@ -68,8 +675,160 @@ pub mod loader {
         }
     }
 
+    /// Returns `true` if the vm config is stored under the given address.
+    pub fn has_vm_config_at<S: Storage>(storage: &S, address: AccountAddress) -> bool {
+        storage
+            .get(&make_storage_key(access_path_for_config(address)))
+            .is_some()
+    }
+
     /// Stores vm configuration to the storage.
     pub fn store_vm_config<S: Storage>(storage: &S, config: &VmConfig) {
-        storage.insert(&make_storage_key(), &config.encode());
+        store_vm_config_at(storage, CONFIG_ADDRESS, config)
+    }
+
+    /// Stores vm configuration under the given address.
+    pub fn store_vm_config_at<S: Storage>(storage: &S, address: AccountAddress, config: &VmConfig) {
+        storage.insert(
+            &make_storage_key(access_path_for_config(address)),
+            &config.encode(),
+        );
+    }
+
+    /// Loads publisher policy stored under the given address.
+    /// Returns the policy allowing any publisher if the policy does not exists in the storage.
+    pub fn load_publisher_policy_at<S: Storage>(
+        storage: &S,
+        address: AccountAddress,
+    ) -> Result<PublisherPolicy, Error> {
+        if let Some(blob) =
+            storage.get(&make_storage_key(access_path_for_publisher_policy(address)))
+        {
+            let mut input = blob.as_slice();
+            PublisherPolicy::decode(&mut input)
+                .map_err(|_| Error::msg("failed to decode PublisherPolicy."))
+        } else {
+            Ok(PublisherPolicy::default())
+        }
+    }
+
+    /// Stores publisher policy under the given address.
+    pub fn store_publisher_policy_at<S: Storage>(
+        storage: &S,
+        address: AccountAddress,
+        policy: &PublisherPolicy,
+    ) {
+        storage.insert(
+            &make_storage_key(access_path_for_publisher_policy(address)),
+            &policy.encode(),
+        );
+    }
+
+    /// Loads the current epoch stored under the given address.
+    /// Returns `0` if the epoch does not exists in the storage.
+    pub fn load_epoch_at<S: Storage>(storage: &S, address: AccountAddress) -> Result<u64, Error> {
+        if let Some(blob) = storage.get(&make_storage_key(access_path_for_epoch(address))) {
+            let mut input = blob.as_slice();
+            u64::decode(&mut input).map_err(|_| Error::msg("failed to decode epoch."))
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Stores the current epoch under the given address.
+    pub fn store_epoch_at<S: Storage>(storage: &S, address: AccountAddress, epoch: u64) {
+        storage.insert(
+            &make_storage_key(access_path_for_epoch(address)),
+            &epoch.encode(),
+        );
+    }
+
+    /// Loads oracle access control list stored under the given address.
+    /// Returns the list allowing any access if the list does not exists in the storage.
+    pub fn load_oracle_acl_at<S: Storage>(
+        storage: &S,
+        address: AccountAddress,
+    ) -> Result<OracleAcl, Error> {
+        if let Some(blob) = storage.get(&make_storage_key(access_path_for_oracle_acl(address))) {
+            let mut input = blob.as_slice();
+            OracleAcl::decode(&mut input).map_err(|_| Error::msg("failed to decode OracleAcl."))
+        } else {
+            Ok(OracleAcl::default())
+        }
+    }
+
+    /// Stores oracle access control list under the given address.
+    pub fn store_oracle_acl_at<S: Storage>(storage: &S, address: AccountAddress, acl: &OracleAcl) {
+        storage.insert(
+            &make_storage_key(access_path_for_oracle_acl(address)),
+            &acl.encode(),
+        );
+    }
+
+    /// Loads registered currencies stored under the given address.
+    /// Returns `None` if the list does not exists in the storage.
+    pub fn load_registered_currencies_at<S: Storage>(
+        storage: &S,
+        address: AccountAddress,
+    ) -> Result<Option<RegisteredCurrencies>, Error> {
+        storage
+            .get(&make_storage_key(access_path_for_registered_currencies(
+                address,
+            )))
+            .map(|blob| {
+                let mut input = blob.as_slice();
+                RegisteredCurrencies::decode(&mut input)
+                    .map_err(|_| Error::msg("failed to decode RegisteredCurrencies."))
+            })
+            .transpose()
+    }
+
+    /// Stores registered currencies under the given address.
+    pub fn store_registered_currencies_at<S: Storage>(
+        storage: &S,
+        address: AccountAddress,
+        currencies: &RegisteredCurrencies,
+    ) {
+        storage.insert(
+            &make_storage_key(access_path_for_registered_currencies(address)),
+            &currencies.encode(),
+        );
+    }
+
+    /// Loads the circuit breaker halt flag stored under the given address.
+    /// Returns `false` if the flag does not exists in the storage.
+    pub fn load_halted_at<S: Storage>(storage: &S, address: AccountAddress) -> Result<bool, Error> {
+        if let Some(blob) = storage.get(&make_storage_key(access_path_for_halted(address))) {
+            let mut input = blob.as_slice();
+            bool::decode(&mut input).map_err(|_| Error::msg("failed to decode halt flag."))
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Stores the circuit breaker halt flag under the given address.
+    /// The flag is removed from the storage when the vm is resumed.
+    pub fn store_halted_at<S: Storage>(storage: &S, address: AccountAddress, halted: bool) {
+        let key = make_storage_key(access_path_for_halted(address));
+        if halted {
+            storage.insert(&key, &halted.encode());
+        } else {
+            storage.remove(&key);
+        }
+    }
+
+    /// Loads all configs stored under the given address.
+    pub fn load_config_view_at<S: Storage>(
+        storage: &S,
+        address: AccountAddress,
+    ) -> Result<ConfigView, Error> {
+        Ok(ConfigView {
+            epoch: load_epoch_at(storage, address)?,
+            vm_config: load_vm_config_at(storage, address)?,
+            publisher_policy: load_publisher_policy_at(storage, address)?,
+            oracle_acl: load_oracle_acl_at(storage, address)?,
+            registered_currencies: load_registered_currencies_at(storage, address)?,
+            halted: load_halted_at(storage, address)?,
+        })
     }
 }
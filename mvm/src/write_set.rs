@@ -0,0 +1,112 @@
+//! Canonical write set of a transaction.
+//!
+//! The write set holds every storage write applied by the transaction: the resources and modules
+//! of the Move effects and the native writes, i.e. the tokens, the module versions, the package
+//! manifests, the event counters, the locked coins, the sequence numbers, the epoch number and
+//! the halt flag of the circuit breaker. A failed transaction kept in the block has the write set
+//! of the writes applied anyway, e.g. the sequence numbers. The writes of the vm made outside of
+//! the transactions are returned as write sets too, see `Mvm::on_external_state_change`.
+//! Writes are ordered by the storage key and a key is written at most once, so the BCS encoding
+//! of the write set and its digest are the same on every node executing the transaction.
+//! Modules are written uncompressed, independently of the storage compression.
+//!
+//! Deleted keys are first-class entries of the write set and are reported by the applied
+//! transactions, so pruning and archive nodes track the tombstones without diffing the state.
+//! With `Mvm::with_deletion_events` the vm also emits the `ResourceDeleted` event per deleted key.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use diem_crypto::hash::HashValue;
+use move_core_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+
+use crate::hash::Digest;
+
+/// Module of the deletion event.
+pub const STORAGE_MODULE: &str = "Storage";
+/// Name of the deletion event emitted under the address of the deleted key.
+pub const RESOURCE_DELETED_EVENT: &str = "ResourceDeleted";
+
+/// Message of the deletion event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceDeleted {
+    /// Deleted key without the address.
+    pub path: Vec<u8>,
+}
+
+impl ResourceDeleted {
+    /// Returns the address and the event of the deleted key
+    /// or `None` if the key doesn't start with an address.
+    pub fn from_key(key: &[u8]) -> Option<(AccountAddress, ResourceDeleted)> {
+        if key.len() < AccountAddress::LENGTH {
+            return None;
+        }
+        let (address, path) = key.split_at(AccountAddress::LENGTH);
+        let address = AccountAddress::try_from(address).ok()?;
+        Some((
+            address,
+            ResourceDeleted {
+                path: path.to_vec(),
+            },
+        ))
+    }
+}
+
+/// Storage write.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WriteOp {
+    Value(Vec<u8>),
+    Deletion,
+}
+
+/// Storage writes ordered by the key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteSet {
+    writes: Vec<(Vec<u8>, WriteOp)>,
+}
+
+impl WriteSet {
+    /// Creates the write set. The last write of a key overrides the previous ones.
+    pub fn new<I: IntoIterator<Item = (Vec<u8>, WriteOp)>>(writes: I) -> WriteSet {
+        let writes = writes.into_iter().collect::<BTreeMap<_, _>>();
+        WriteSet {
+            writes: writes.into_iter().collect(),
+        }
+    }
+
+    /// Returns the writes ordered by the key.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &WriteOp)> {
+        self.writes.iter().map(|(key, op)| (key.as_slice(), op))
+    }
+
+    /// Returns the deleted keys ordered by the key.
+    pub fn deletions(&self) -> impl Iterator<Item = &[u8]> {
+        self.iter()
+            .filter(|(_, op)| **op == WriteOp::Deletion)
+            .map(|(key, _)| key)
+    }
+
+    /// Returns the number of written keys.
+    pub fn len(&self) -> usize {
+        self.writes.len()
+    }
+
+    /// Returns `true` if the write set contains no writes.
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Returns the canonical BCS encoding of the write set.
+    pub fn encode(&self) -> Vec<u8> {
+        bcs::to_bytes(self).expect("write set must be serializable")
+    }
+
+    /// Returns the SHA3-256 digest of the canonical encoding.
+    pub fn digest(&self) -> Digest {
+        let hash = HashValue::sha3_256_of(&self.encode());
+        let digest: &Digest = hash.as_ref();
+        *digest
+    }
+}
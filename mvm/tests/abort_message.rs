@@ -0,0 +1,152 @@
+use common::assets::*;
+use common::bytecode::{native_call_script, native_module};
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::errors::{ABORT_WITH_MESSAGE, ERRORS_MODULE, MAX_ABORT_MESSAGE_LEN};
+use mvm::mvm::Mvm;
+use mvm::types::{Gas, ModuleTx, ScriptArg, ScriptTx};
+use mvm::view::ViewCall;
+use mvm::Vm;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+fn params() -> Vec<SignatureToken> {
+    vec![
+        SignatureToken::U64,
+        SignatureToken::Vector(Box::new(SignatureToken::U8)),
+    ]
+}
+
+fn errors_module() -> ModuleTx {
+    native_module(
+        CORE_CODE_ADDRESS,
+        ERRORS_MODULE,
+        ABORT_WITH_MESSAGE,
+        params(),
+        vec![],
+    )
+}
+
+fn abort_script(code: u64, message: Vec<u8>) -> ScriptTx {
+    native_call_script(
+        CORE_CODE_ADDRESS,
+        ERRORS_MODULE,
+        ABORT_WITH_MESSAGE,
+        params(),
+        vec![],
+        vec![ScriptArg::U64(code), ScriptArg::VectorU8(message)],
+    )
+}
+
+fn vm() -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    let vm = MvmBuilder::new(StorageMock::new(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap();
+    assert_eq!(
+        vm.publish_module(gas(), errors_module(), false).status_code,
+        StatusCode::EXECUTED
+    );
+    vm
+}
+
+#[test]
+fn test_abort_with_message() {
+    let vm = vm();
+
+    for dry_run in [true, false].iter() {
+        let res = vm.execute_script(
+            gas(),
+            ExecutionContext::new(100, 100),
+            abort_script(42, b"insufficient funds".to_vec()),
+            *dry_run,
+        );
+        assert_eq!(res.status_code, StatusCode::ABORTED);
+        assert_eq!(res.sub_status, Some(42));
+        assert_eq!(res.abort_message.as_deref(), Some("insufficient funds"));
+        assert!(res
+            .to_string()
+            .contains("sub status: 42, message: \"insufficient funds\""));
+    }
+}
+
+#[test]
+fn test_abort_message_is_truncated() {
+    let vm = vm();
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        abort_script(1, vec![b'a'; MAX_ABORT_MESSAGE_LEN + 10]),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.abort_message, Some("a".repeat(MAX_ABORT_MESSAGE_LEN)));
+}
+
+#[test]
+fn test_invalid_utf8_abort_message() {
+    let vm = vm();
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        abort_script(1, vec![b'o', 0xff, b'k']),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.abort_message.as_deref(), Some("o\u{fffd}k"));
+}
+
+#[test]
+fn test_abort_message_is_not_carried_over() {
+    let vm = vm();
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        abort_script(1, b"first".to_vec()),
+        false,
+    );
+    assert_eq!(res.abort_message.as_deref(), Some("first"));
+
+    let res = vm.execute_script(
+        Gas::new(1, 1).unwrap(),
+        ExecutionContext::new(100, 100),
+        abort_script(1, b"second".to_vec()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::OUT_OF_GAS);
+    assert_eq!(res.abort_message, None);
+}
+
+#[test]
+fn test_abort_message_is_kept_by_its_session() {
+    let vm = vm();
+
+    // The message of an aborted view is dropped with its session.
+    let call = ViewCall::new(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new(ERRORS_MODULE).unwrap()),
+        Identifier::new(ABORT_WITH_MESSAGE).unwrap(),
+        vec![ScriptArg::U64(7), ScriptArg::VectorU8(b"view".to_vec())],
+    );
+    let err = vm
+        .view_function(gas(), ExecutionContext::new(100, 100), &call, 0)
+        .unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::ABORTED);
+
+    let res = vm.execute_script(
+        Gas::new(1, 1).unwrap(),
+        ExecutionContext::new(100, 100),
+        abort_script(1, b"script".to_vec()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::OUT_OF_GAS);
+    assert_eq!(res.abort_message, None);
+}
@@ -0,0 +1,96 @@
+use common::assets::*;
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use move_core_types::vm_status::StatusCode;
+use mvm::account::account_tag;
+use mvm::builder::MvmBuilder;
+use mvm::data::{AccessKey, ExecutionContext, Storage};
+use mvm::testkit::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use mvm::testkit::MockVm;
+use mvm::vm_config::loader::store_vm_config;
+use mvm::vm_config::VmConfig;
+use mvm::Vm;
+
+mod common;
+
+#[test]
+fn test_create_account() {
+    let (vm, store, _, _, _) = vm();
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+
+    let address = AccountAddress::random();
+    let key = AccessKey::from((&address, &account_tag(CORE_CODE_ADDRESS)));
+    assert!(store.get(key.as_ref()).is_none());
+
+    let res = vm.create_account(ExecutionContext::new(100, 100), address);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(res.gas_used, 0);
+    assert!(store.get(key.as_ref()).is_some());
+
+    let res = vm.create_account(ExecutionContext::new(100, 100), address);
+    assert_eq!(res.status_code, StatusCode::RESOURCE_ALREADY_EXISTS);
+}
+
+fn lazy_vm(lazy_accounts: bool) -> (MockVm, StorageMock, BankMock) {
+    let store = StorageMock::new();
+    store_vm_config(
+        &store,
+        &VmConfig {
+            lazy_accounts,
+            ..VmConfig::default()
+        },
+    );
+    let bank = BankMock::default();
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(bank.clone())
+        .build()
+        .unwrap();
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+    vm.pub_mod(store_module());
+    (vm, store, bank)
+}
+
+#[test]
+fn test_lazy_accounts() {
+    let (vm, _, bank) = lazy_vm(true);
+    let with_balance = AccountAddress::random();
+    let without_balance = AccountAddress::random();
+    bank.set_balance(&with_balance, "PONT", 100);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(with_balance, 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(vm.account_exists(&with_balance).unwrap());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(without_balance, 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(!vm.account_exists(&without_balance).unwrap());
+
+    let (vm, _, bank) = lazy_vm(false);
+    bank.set_balance(&with_balance, "PONT", 100);
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(with_balance, 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(!vm.account_exists(&with_balance).unwrap());
+}
@@ -0,0 +1,77 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::mvm::Mvm;
+use mvm::vm_config::loader::store_vm_config_at;
+use mvm::vm_config::{AddressesConfig, VmConfig};
+use mvm::Vm;
+
+mod common;
+
+fn vm_with_addresses(
+    store: StorageMock,
+    addresses: AddressesConfig,
+) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    MvmBuilder::new(store, EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .with_addresses(addresses)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_relocated_config_address() {
+    let store = StorageMock::new();
+    store_vm_config_at(
+        &store,
+        addr("0x2"),
+        &VmConfig {
+            max_call_depth: 0,
+            ..VmConfig::default()
+        },
+    );
+
+    let vm = vm_with_addresses(store.clone(), AddressesConfig::default());
+    vm.pub_mod(store_module());
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let vm = vm_with_addresses(
+        store,
+        AddressesConfig {
+            config_address: addr("0x2"),
+            ..AddressesConfig::default()
+        },
+    );
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::CALL_STACK_OVERFLOW);
+}
+
+#[test]
+fn test_relocated_core_code_address() {
+    let vm = vm_with_addresses(
+        StorageMock::new(),
+        AddressesConfig {
+            core_code_address: addr("0x2"),
+            ..AddressesConfig::default()
+        },
+    );
+    assert_eq!(vm.addresses().core_code_address, addr("0x2"));
+
+    // Natives are resolved at the relocated address only.
+    let res = vm.publish_module(gas(), signer_module(), false);
+    assert_eq!(res.status_code, StatusCode::MISSING_DEPENDENCY);
+}
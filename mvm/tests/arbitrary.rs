@@ -0,0 +1,55 @@
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use mvm::access_path::AccessPath;
+use mvm::data::AccessKey;
+use mvm::types::VmResult;
+use proptest::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+fn bcs_roundtrip<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(value: T) {
+    let blob = bcs::to_bytes(&value).unwrap();
+    let decoded: T = bcs::from_bytes(&blob).unwrap();
+    assert_eq!(decoded, value);
+}
+
+proptest! {
+    #[test]
+    fn test_address_roundtrip(address in any::<AccountAddress>()) {
+        bcs_roundtrip(address);
+    }
+
+    #[test]
+    fn test_identifier_roundtrip(identifier in any::<Identifier>()) {
+        bcs_roundtrip(identifier);
+    }
+
+    #[test]
+    fn test_type_tag_roundtrip(tag in any::<TypeTag>()) {
+        bcs_roundtrip(tag);
+    }
+
+    #[test]
+    fn test_struct_tag_roundtrip(tag in any::<StructTag>()) {
+        bcs_roundtrip(tag);
+    }
+
+    #[test]
+    fn test_vm_result_roundtrip(result in any::<VmResult>()) {
+        let blob = bcs::to_bytes(&result).unwrap();
+        let decoded: VmResult = bcs::from_bytes(&blob).unwrap();
+        prop_assert_eq!(decoded.status_code, result.status_code);
+        prop_assert_eq!(decoded.sub_status, result.sub_status);
+        prop_assert_eq!(decoded.gas_used, result.gas_used);
+        prop_assert_eq!(decoded.price_reads, result.price_reads);
+        prop_assert_eq!(decoded.balance_changes, result.balance_changes);
+    }
+
+    #[test]
+    fn test_access_path_key(path in any::<AccessPath>()) {
+        let key = AccessKey::from(&path);
+        prop_assert_eq!(&key.as_ref()[..AccountAddress::LENGTH], path.address.as_ref());
+        prop_assert_eq!(&key.as_ref()[AccountAddress::LENGTH..], path.path.as_slice());
+    }
+}
@@ -0,0 +1,152 @@
+use common::assets::*;
+use common::bytecode::natives_module;
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use move_core_types::vm_status::StatusCode;
+use mvm::args::{ArgError, ArgsBuilder};
+use mvm::data::ExecutionContext;
+use mvm::metadata::ModuleMetadata;
+use mvm::testkit::mock::Utils;
+use mvm::testkit::vm;
+use mvm::types::{ScriptArg, ScriptTx};
+use mvm::Vm;
+use serde_json::json;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+fn vectors_metadata() -> ModuleMetadata {
+    let module = natives_module(
+        CORE_CODE_ADDRESS,
+        "Vectors",
+        vec![(
+            "sum",
+            vec![
+                SignatureToken::Reference(Box::new(SignatureToken::Signer)),
+                SignatureToken::Vector(Box::new(SignatureToken::U8)),
+                SignatureToken::Vector(Box::new(SignatureToken::U64)),
+                SignatureToken::Vector(Box::new(SignatureToken::Address)),
+                SignatureToken::Bool,
+                SignatureToken::U128,
+            ],
+            vec![],
+        )],
+    );
+    ModuleMetadata::from_bytes(module.code()).unwrap()
+}
+
+#[test]
+fn test_args_from_strings() {
+    let metadata = ModuleMetadata::from_bytes(store_module().code()).unwrap();
+    let builder = ArgsBuilder::new(&metadata, "store_u64").unwrap();
+    assert_eq!(builder.types(), &["u64".to_owned()]);
+    let args = builder.from_strings(&["13"]).unwrap();
+    assert_eq!(args, vec![ScriptArg::U64(13)]);
+
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+    let script = store_u64_script(addr("0x1"), 13);
+    let tx = ScriptTx::new(script.code().to_vec(), args, vec![], vec![addr("0x1")]);
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 100), tx, false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let builder = ArgsBuilder::new(&vectors_metadata(), "sum").unwrap();
+    let args = builder
+        .from_strings(&[
+            "0x0102",
+            "[1, 2, 3]",
+            "[0x1, 0x2]",
+            "true",
+            "340282366920938463463374607431768211455",
+        ])
+        .unwrap();
+    assert_eq!(
+        args,
+        vec![
+            ScriptArg::VectorU8(vec![1, 2]),
+            ScriptArg::VectorU64(vec![1, 2, 3]),
+            ScriptArg::VectorAddress(vec![addr("0x1"), addr("0x2")]),
+            ScriptArg::Bool(true),
+            ScriptArg::U128(u128::MAX),
+        ]
+    );
+    assert_eq!(
+        builder
+            .from_strings(&["[]", "[]", "[]", "false", "0"])
+            .unwrap()[0],
+        ScriptArg::VectorU8(vec![])
+    );
+}
+
+#[test]
+fn test_args_from_json() {
+    let builder = ArgsBuilder::new(&vectors_metadata(), "sum").unwrap();
+    let args = builder
+        .from_json(&[
+            json!([1, 2]),
+            json!([1, "2", 3]),
+            json!(["0x1"]),
+            json!(false),
+            json!("340282366920938463463374607431768211455"),
+        ])
+        .unwrap();
+    assert_eq!(
+        args,
+        vec![
+            ScriptArg::VectorU8(vec![1, 2]),
+            ScriptArg::VectorU64(vec![1, 2, 3]),
+            ScriptArg::VectorAddress(vec![addr("0x1")]),
+            ScriptArg::Bool(false),
+            ScriptArg::U128(u128::MAX),
+        ]
+    );
+    assert_eq!(
+        builder
+            .from_json(&[json!("0x0a"), json!([]), json!([]), json!(true), json!(1)])
+            .unwrap()[0],
+        ScriptArg::VectorU8(vec![10])
+    );
+}
+
+#[test]
+fn test_invalid_args() {
+    let metadata = ModuleMetadata::from_bytes(store_module().code()).unwrap();
+    assert_eq!(
+        ArgsBuilder::new(&metadata, "store_u256").unwrap_err(),
+        ArgError::UnknownFunction("store_u256".to_owned())
+    );
+
+    let builder = ArgsBuilder::new(&metadata, "store_u64").unwrap();
+    assert_eq!(
+        builder.from_strings(&["1", "2"]).unwrap_err(),
+        ArgError::ArgumentCount {
+            expected: 1,
+            actual: 2
+        }
+    );
+    match builder.from_strings(&["-1"]).unwrap_err() {
+        ArgError::InvalidValue { index, type_, .. } => {
+            assert_eq!(index, 0);
+            assert_eq!(type_, "u64");
+        }
+        err => panic!("Unexpected error: {}", err),
+    }
+
+    let builder = ArgsBuilder::new(&vectors_metadata(), "sum").unwrap();
+    match builder
+        .from_strings(&["0x01", "[1, x]", "[]", "true", "1"])
+        .unwrap_err()
+    {
+        ArgError::InvalidValue { index, reason, .. } => {
+            assert_eq!(index, 1);
+            assert!(reason.starts_with("item 1:"));
+        }
+        err => panic!("Unexpected error: {}", err),
+    }
+    match builder
+        .from_json(&[json!([]), json!([]), json!([]), json!("yes"), json!(1)])
+        .unwrap_err()
+    {
+        ArgError::InvalidValue { index, .. } => assert_eq!(index, 3),
+        err => panic!("Unexpected error: {}", err),
+    }
+}
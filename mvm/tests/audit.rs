@@ -0,0 +1,72 @@
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::audit::{find_divergence, OogDivergence, ResourceRead};
+use mvm::bench::workloads::{loop_tx, vm};
+use mvm::data::ExecutionContext;
+use mvm::types::Gas;
+use mvm::Vm;
+
+fn read(name: &str) -> ResourceRead {
+    ResourceRead {
+        address: AccountAddress::random(),
+        tag: StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("M").unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![],
+        },
+    }
+}
+
+#[test]
+fn test_find_divergence() {
+    let a = read("A");
+    let b = read("B");
+    let c = read("C");
+
+    assert_eq!(find_divergence(&[], &[a.clone()]), None);
+    assert_eq!(find_divergence(&[a.clone()], &[a.clone(), b.clone()]), None);
+    assert_eq!(
+        find_divergence(&[a.clone(), b.clone()], &[a.clone(), c.clone()]),
+        Some(OogDivergence {
+            index: 1,
+            expected: b.clone(),
+            actual: Some(c),
+        })
+    );
+    assert_eq!(
+        find_divergence(&[a.clone(), b.clone()], &[a]),
+        Some(OogDivergence {
+            index: 1,
+            expected: b,
+            actual: None,
+        })
+    );
+}
+
+#[test]
+fn test_oog_audit_keeps_result() {
+    let gas = || Gas::new(1_000, 1).unwrap();
+
+    let (plain, _, _) = vm();
+    let expected = plain.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        loop_tx(10_000),
+        true,
+    );
+    assert_eq!(expected.status_code, StatusCode::OUT_OF_GAS);
+
+    let (audited, _, _) = vm();
+    let audited = audited.with_oog_audit(10);
+    let res = audited.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        loop_tx(10_000),
+        true,
+    );
+    assert_eq!(res.status_code, expected.status_code);
+    assert_eq!(res.gas_used, expected.gas_used);
+}
@@ -0,0 +1,112 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::hash::Digest;
+use mvm::types::ScriptTx;
+use mvm::Vm;
+
+mod common;
+
+/// Accepts the transaction if the proof is the signing message followed by the sender addresses.
+fn authenticate(message: &Digest, senders: &[AccountAddress], proof: &[u8]) -> Result<(), u64> {
+    if proof_of(message, senders) == proof {
+        Ok(())
+    } else {
+        Err(42)
+    }
+}
+
+fn proof_of(message: &Digest, senders: &[AccountAddress]) -> Vec<u8> {
+    message
+        .iter()
+        .cloned()
+        .chain(senders.iter().flat_map(|sender| sender.to_vec()))
+        .collect()
+}
+
+fn sign(tx: ScriptTx) -> ScriptTx {
+    let proof = proof_of(&tx.signing_message(), tx.senders());
+    tx.with_proof(proof)
+}
+
+#[test]
+fn test_authenticator() {
+    let vm = MvmBuilder::new(StorageMock::new(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_authenticator(authenticate);
+    vm.pub_mod(store_module());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::INVALID_SIGNATURE);
+    assert_eq!(res.sub_status, Some(42));
+    assert_eq!(res.gas_used, 0);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13).with_proof(addr("0x2").to_vec()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::INVALID_SIGNATURE);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        sign(store_u64_script(addr("0x1"), 13)),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+#[test]
+fn test_proof_is_bound_to_tx_content() {
+    let vm = MvmBuilder::new(StorageMock::new(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_authenticator(authenticate);
+    vm.pub_mod(store_module());
+
+    let signed = sign(store_u64_script(addr("0x1"), 13));
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 14).with_proof(signed.proof().to_vec()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::INVALID_SIGNATURE);
+    assert_eq!(res.sub_status, Some(42));
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13)
+            .with_fee_payer(addr("0x2"))
+            .with_proof(signed.proof().to_vec()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::INVALID_SIGNATURE);
+
+    let signed = sign(store_u64_script(addr("0x1"), 13).with_chain_id(1));
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13)
+            .with_chain_id(2)
+            .with_proof(signed.proof().to_vec()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::INVALID_SIGNATURE);
+}
@@ -0,0 +1,183 @@
+use common::assets::*;
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_types::natives::balance::{BalanceOperation, WalletId};
+use mvm::data::{BalanceAccess, ExecutionContext};
+use mvm::testkit::mock::Utils;
+use mvm::types::{BalanceBreakdown, BalanceChange};
+use mvm::vm_config::loader::load_registered_currencies_at;
+use mvm::vm_config::{RegisteredCurrencies, CONFIG_ADDRESS};
+use mvm::Vm;
+
+mod common;
+
+fn wallet(address: AccountAddress, module: &str, name: &str) -> WalletId {
+    WalletId::new(
+        address,
+        StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new(module).unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![],
+        },
+    )
+}
+
+#[test]
+fn test_balance_changes() {
+    let (vm, _, _, _, bank) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+
+    let addr_1 = AccountAddress::random();
+    let addr_2 = AccountAddress::random();
+    bank.set_balance(&addr_1, "USDT", 1024);
+    bank.set_balance(&addr_1, "PONT", 64);
+    bank.set_balance(&addr_1, "BTC", 13);
+
+    // Dry run does not apply balance changes.
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        test_balance_script(addr_1, addr_2, 1024, 64, 13),
+        true,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(res.balance_changes.is_empty());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        test_balance_script(addr_1, addr_2, 1024, 64, 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let changes = res
+        .balance_changes
+        .iter()
+        .filter(|change| change.operation != BalanceOperation::Deposit(0))
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut expected = vec![
+        BalanceChange {
+            wallet_id: wallet(addr_1, "Coins", "USDT"),
+            operation: BalanceOperation::Deposit(512),
+        },
+        BalanceChange {
+            wallet_id: wallet(addr_1, "PONT", "T"),
+            operation: BalanceOperation::Deposit(3),
+        },
+        BalanceChange {
+            wallet_id: wallet(addr_2, "Coins", "USDT"),
+            operation: BalanceOperation::Withdraw(512),
+        },
+        BalanceChange {
+            wallet_id: wallet(addr_2, "PONT", "T"),
+            operation: BalanceOperation::Withdraw(3),
+        },
+    ];
+    expected.sort_by(|a, b| a.wallet_id.cmp(&b.wallet_id));
+    assert_eq!(changes, expected);
+}
+
+#[test]
+fn test_registered_currencies() {
+    let (vm, store, _, _, bank) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+
+    let addr_1 = AccountAddress::random();
+    let addr_2 = AccountAddress::random();
+    bank.set_balance(&addr_1, "USDT", 1024);
+    bank.set_balance(&addr_1, "PONT", 64);
+    bank.set_balance(&addr_1, "BTC", 13);
+
+    vm.register_currency(ticker("PONT")).unwrap();
+    vm.register_currency(ticker("USDT")).unwrap();
+    assert!(vm.register_currency(ticker("USDT")).is_err());
+    assert_eq!(
+        load_registered_currencies_at(&store, CONFIG_ADDRESS).unwrap(),
+        Some(RegisteredCurrencies::new(vec![
+            ticker("PONT"),
+            ticker("USDT")
+        ]))
+    );
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        test_balance_script(addr_1, addr_2, 1024, 64, 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::RESOURCE_DOES_NOT_EXIST);
+    assert!(res.balance_changes.is_empty());
+
+    vm.register_currency(ticker("BTC")).unwrap();
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        test_balance_script(addr_1, addr_2, 1024, 64, 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+#[test]
+fn test_balance_breakdown() {
+    let (vm, _, _, _, bank) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+    vm.exec(reg_coin_script(
+        TypeTag::Struct(StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("PONT").unwrap(),
+            name: Identifier::new("T").unwrap(),
+            type_params: vec![],
+        }),
+        "PONT",
+        2,
+    ));
+
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    bank.set_balance(&alice, "PONT", 100);
+    bank.set_balance(&bob, "PONT", 7);
+    vm.exec(test_transfer_script(alice, bob, 10));
+
+    assert_eq!(
+        vm.balance(&alice, &ticker("PONT")).unwrap(),
+        BalanceBreakdown {
+            native: 90,
+            wrapped: 0,
+        }
+    );
+    let bob_balance = vm.balance(&bob, &ticker("PONT")).unwrap();
+    assert_eq!(
+        bob_balance,
+        BalanceBreakdown {
+            native: 7,
+            wrapped: 10,
+        }
+    );
+    assert_eq!(bob_balance.total(), 17);
+    assert_eq!(
+        vm.balance(&bob, &ticker("ETH_BTC")).unwrap(),
+        BalanceBreakdown::default()
+    );
+}
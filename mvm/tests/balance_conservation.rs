@@ -0,0 +1,95 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::data::{BalanceAccess, ExecutionContext};
+use mvm::mvm::Mvm;
+use mvm::vm_config::loader::store_vm_config;
+use mvm::vm_config::VmConfig;
+use mvm::Vm;
+use parity_scale_codec::{Decode, Encode};
+
+mod common;
+
+fn vm_with_conservation_check(
+    bank: BankMock,
+) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    let store = StorageMock::new();
+    store_vm_config(
+        &store,
+        &VmConfig {
+            check_balance_conservation: true,
+            ..VmConfig::default()
+        },
+    );
+    let vm = MvmBuilder::new(store, EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(bank)
+        .build()
+        .unwrap();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+    vm
+}
+
+#[test]
+fn test_balance_conservation() {
+    let bank = BankMock::default();
+    let vm = vm_with_conservation_check(bank.clone());
+
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    bank.set_balance(&alice, "USDT", 1024);
+    bank.set_balance(&alice, "PONT", 64);
+    bank.set_balance(&alice, "BTC", 13);
+
+    // Coins moved between the native balances are conserved.
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        test_balance_script(alice, bob, 1024, 64, 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(bank.get_balance(&bob, &ticker("USDT")), Some(512));
+
+    // Coins minted in the vm with the capability of the native balances are exempt.
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        test_transfer_script(alice, bob, 10),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(bank.get_balance(&alice, &ticker("PONT")), Some(51));
+}
+
+#[test]
+fn test_balance_conservation_decode() {
+    let config = VmConfig {
+        check_balance_conservation: true,
+        ..VmConfig::default()
+    };
+    assert_eq!(
+        VmConfig::decode(&mut config.encode().as_slice()).unwrap(),
+        config
+    );
+
+    // Configs stored before the check don't enable it.
+    let config = VmConfig::default();
+    let mut encoded = config.encode();
+    let appended = (
+        config.check_balance_conservation,
+        config.min_gas_unit_price,
+        config.max_gas_unit_price,
+        config.max_gas_per_tx,
+    );
+    encoded.truncate(encoded.len() - appended.encode().len());
+    let config = VmConfig::decode(&mut encoded.as_slice()).unwrap();
+    assert!(!config.check_balance_conservation);
+}
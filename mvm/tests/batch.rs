@@ -0,0 +1,86 @@
+use common::assets::*;
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::data::{ExecutionContext, State};
+use mvm::testkit::mock::Utils;
+use mvm::types::BatchTx;
+use mvm::{BatchVm, Vm};
+
+mod common;
+
+fn store_tag() -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    }
+}
+
+fn stored_value<R: RemoteCache>(state: &R, address: &AccountAddress) -> Option<u64> {
+    state
+        .get_resource(address, &store_tag())
+        .unwrap()
+        .map(|blob| bcs::from_bytes::<StoreU64>(&blob).unwrap().val)
+}
+
+#[test]
+fn test_batch() {
+    let (vm, store, _, oracle, _) = vm();
+    let state = State::new(store, oracle);
+    vm.pub_mod(store_module());
+    vm.pub_mod(abort_module());
+
+    let batch = BatchTx::new(vec![store_u64_call(2)], vec![addr("0x2")]);
+    let res = vm.execute_batch(gas(), ExecutionContext::new(100, 100), batch, false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(stored_value(&state, &addr("0x2")), Some(2));
+
+    // `Abort::error` takes no signer, so it is called without the senders.
+    let batch = BatchTx::new(vec![store_u64_call(3), abort_call(0)], vec![addr("0x3")]);
+    let res = vm.execute_batch(gas(), ExecutionContext::new(100, 100), batch, false);
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+}
+
+#[test]
+fn test_batch_is_atomic() {
+    let (vm, store, _, oracle, _) = vm();
+    let state = State::new(store, oracle);
+    vm.pub_mod(store_module());
+    vm.pub_mod(abort_module());
+
+    let batch = BatchTx::new(vec![store_u64_call(2), abort_call(13)], vec![addr("0x2")]);
+    let res = vm.execute_batch(gas(), ExecutionContext::new(100, 100), batch, false);
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.sub_status, Some(13));
+    assert!(res.gas_used > 0);
+    assert_eq!(stored_value(&state, &addr("0x2")), None);
+}
+
+#[test]
+fn test_empty_batch_is_checked() {
+    let (vm, _, _, _, _) = vm();
+
+    let batch = BatchTx::new(vec![], vec![addr("0x2")]).with_expiration(10);
+    let res = vm.execute_batch(gas(), ExecutionContext::new(10, 1), batch, false);
+    assert_eq!(res.status_code, StatusCode::TRANSACTION_EXPIRED);
+
+    let batch = BatchTx::new(vec![], vec![addr("0x2")]).with_sequence_number(0, 1);
+    let res = vm.execute_batch(gas(), ExecutionContext::new(10, 1), batch, false);
+    assert_eq!(res.status_code, StatusCode::SEQUENCE_NUMBER_TOO_NEW);
+}
+
+#[test]
+fn test_batch_call_must_be_entry_function() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+
+    // `Store::store_u64` takes one signer, two senders can't be passed to it.
+    let batch = BatchTx::new(vec![store_u64_call(2)], vec![addr("0x2"), addr("0x3")]);
+    let res = vm.execute_batch(gas(), ExecutionContext::new(100, 100), batch, false);
+    assert_eq!(res.status_code, StatusCode::TYPE_MISMATCH);
+}
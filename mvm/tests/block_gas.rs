@@ -0,0 +1,112 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use move_core_types::vm_status::StatusCode;
+use mvm::block_gas::BlockGasMeter;
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::mvm::Mvm;
+use mvm::types::Gas;
+use mvm::vm_config::loader::store_vm_config;
+use mvm::vm_config::VmConfig;
+use parity_scale_codec::{Decode, Encode};
+
+mod common;
+
+fn vm_with_block_gas(
+    max_block_gas: Option<u64>,
+) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    let store = StorageMock::new();
+    store_vm_config(
+        &store,
+        &VmConfig {
+            max_block_gas,
+            ..VmConfig::default()
+        },
+    );
+    let vm = MvmBuilder::new(store, EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap();
+    vm.pub_mod(store_module());
+    vm
+}
+
+#[test]
+fn test_block_gas_limit() {
+    let vm = vm_with_block_gas(Some(10_000));
+    let mut meter = vm.block_gas_meter().unwrap();
+    assert_eq!(meter.limit(), Some(10_000));
+
+    let txs = (0..2)
+        .map(|idx| (gas(), store_u64_script(addr("0x1"), idx)))
+        .collect();
+    let results = vm.execute_block(&mut meter, ExecutionContext::new(100, 100), txs);
+
+    assert_eq!(results[0].status_code, StatusCode::EXECUTED);
+    assert_eq!(
+        results[1].status_code,
+        StatusCode::MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND
+    );
+    assert_eq!(results[1].gas_used, 0);
+    assert_eq!(meter.transactions(), &[results[0].gas_used]);
+    assert_eq!(meter.used(), results[0].gas_used);
+    assert_eq!(meter.remaining(), Some(10_000 - meter.used()));
+
+    // A transaction with a smaller gas limit still fits into the block.
+    let small = Gas::new(meter.remaining().unwrap(), 1).unwrap();
+    let results = vm.execute_block(
+        &mut meter,
+        ExecutionContext::new(100, 100),
+        vec![(small, store_u64_script(addr("0x1"), 2))],
+    );
+    assert_eq!(results[0].status_code, StatusCode::EXECUTED);
+    assert_eq!(meter.transactions().len(), 2);
+    assert_eq!(meter.used(), meter.transactions().iter().sum::<u64>());
+}
+
+#[test]
+fn test_unlimited_block_gas() {
+    let vm = vm_with_block_gas(None);
+    let mut meter = vm.block_gas_meter().unwrap();
+    assert_eq!(meter, BlockGasMeter::new(None));
+
+    let txs = (0..5)
+        .map(|idx| (gas(), store_u64_script(addr("0x1"), idx)))
+        .collect();
+    let results = vm.execute_block(&mut meter, ExecutionContext::new(100, 100), txs);
+    assert!(results
+        .iter()
+        .all(|result| result.status_code == StatusCode::EXECUTED));
+    assert_eq!(meter.remaining(), None);
+    assert_eq!(
+        meter.used(),
+        results.iter().map(|result| result.gas_used).sum::<u64>()
+    );
+}
+
+#[test]
+fn test_max_block_gas_decode() {
+    let config = VmConfig {
+        max_block_gas: Some(1_000_000),
+        ..VmConfig::default()
+    };
+    assert_eq!(
+        VmConfig::decode(&mut config.encode().as_slice()).unwrap(),
+        config
+    );
+
+    // Configs stored before the block gas limit don't limit the block.
+    let config = VmConfig::default();
+    let mut encoded = config.encode();
+    let appended = (
+        config.max_block_gas,
+        config.check_balance_conservation,
+        config.min_gas_unit_price,
+        config.max_gas_unit_price,
+        config.max_gas_per_tx,
+    );
+    encoded.truncate(encoded.len() - appended.encode().len());
+    let config = VmConfig::decode(&mut encoded.as_slice()).unwrap();
+    assert_eq!(config.max_block_gas, None);
+}
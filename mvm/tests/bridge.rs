@@ -0,0 +1,222 @@
+use std::sync::{Arc, Mutex};
+
+use common::assets::*;
+use common::bytecode::{
+    native_call_script, native_proxy_module, private_functions_module, value_module,
+};
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::bridge::{Message, BRIDGE_MODULE, BRIDGE_SEND};
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::testkit::MockVm;
+use mvm::types::{Gas, ModuleTx, ScriptArg, ScriptTx};
+use mvm::view::ViewCall;
+use mvm::Vm;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+const ECHO: &str = "echo";
+
+fn params() -> Vec<SignatureToken> {
+    vec![
+        SignatureToken::Vector(Box::new(SignatureToken::U8)),
+        SignatureToken::Vector(Box::new(SignatureToken::U8)),
+    ]
+}
+
+fn bridge_module() -> ModuleTx {
+    native_proxy_module(
+        CORE_CODE_ADDRESS,
+        BRIDGE_MODULE,
+        BRIDGE_SEND,
+        ECHO,
+        params(),
+        vec![],
+    )
+}
+
+fn send_script(peer: Vec<u8>, payload: Vec<u8>) -> ScriptTx {
+    native_call_script(
+        CORE_CODE_ADDRESS,
+        BRIDGE_MODULE,
+        BRIDGE_SEND,
+        params(),
+        vec![],
+        vec![ScriptArg::VectorU8(peer), ScriptArg::VectorU8(payload)],
+    )
+}
+
+fn message(peer: &[u8], payload: &[u8]) -> Message {
+    Message {
+        peer: peer.to_vec(),
+        payload: payload.to_vec(),
+    }
+}
+
+type Queue = Arc<Mutex<Vec<Message>>>;
+
+fn vm() -> (MockVm, Queue) {
+    let queue = Queue::default();
+    let messages = queue.clone();
+    let vm = MvmBuilder::new(StorageMock::new(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_message_queue(move |message: Message| messages.lock().unwrap().push(message));
+    assert_eq!(
+        vm.publish_module(gas(), bridge_module(), false).status_code,
+        StatusCode::EXECUTED
+    );
+    (vm, queue)
+}
+
+#[test]
+fn test_send_message() {
+    let (vm, queue) = vm();
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        send_script(vec![1], vec![2, 3]),
+        true,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(queue.lock().unwrap().is_empty());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        send_script(vec![1], vec![2, 3]),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(*queue.lock().unwrap(), vec![message(&[1], &[2, 3])]);
+}
+
+#[test]
+fn test_view_drops_messages() {
+    let (vm, queue) = vm();
+    let call = ViewCall::new(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new(BRIDGE_MODULE).unwrap()),
+        Identifier::new(ECHO).unwrap(),
+        vec![ScriptArg::VectorU8(vec![7]), ScriptArg::VectorU8(vec![8])],
+    );
+    assert!(vm
+        .view_function(gas(), ExecutionContext::new(100, 100), &call, 0)
+        .is_ok());
+    assert!(queue.lock().unwrap().is_empty());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        send_script(vec![1], vec![2, 3]),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(*queue.lock().unwrap(), vec![message(&[1], &[2, 3])]);
+}
+
+#[test]
+fn test_failed_tx_drops_messages() {
+    let (vm, queue) = vm();
+
+    let res = vm.execute_script(
+        Gas::new(1, 1).unwrap(),
+        ExecutionContext::new(100, 100),
+        send_script(vec![1], vec![2, 3]),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::OUT_OF_GAS);
+    assert!(queue.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_deliver_message() {
+    let (vm, queue) = vm();
+    let vm = vm.with_message_handler(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new(BRIDGE_MODULE).unwrap()),
+        Identifier::new(ECHO).unwrap(),
+    );
+
+    let res = vm.deliver_message(
+        gas(),
+        ExecutionContext::new(100, 100),
+        message(&[4], &[5, 6]),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(*queue.lock().unwrap(), vec![message(&[4], &[5, 6])]);
+}
+
+#[test]
+fn test_deliver_message_to_private_handler() {
+    let (vm, queue) = vm();
+    let handler = private_functions_module(CORE_CODE_ADDRESS, "Handler", &["on_message"]);
+    assert_eq!(
+        vm.publish_module(gas(), handler, false).status_code,
+        StatusCode::EXECUTED
+    );
+    let vm = vm.with_message_handler(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Handler").unwrap()),
+        Identifier::new("on_message").unwrap(),
+    );
+
+    let res = vm.deliver_message(
+        gas(),
+        ExecutionContext::new(100, 100),
+        message(&[4], &[5, 6]),
+        false,
+    );
+    assert_eq!(
+        res.status_code,
+        StatusCode::EXECUTE_ENTRY_FUNCTION_CALLED_ON_NON_ENTRY_FUNCTION
+    );
+    assert!(queue.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_deliver_message_to_non_entry_handler() {
+    let (vm, queue) = vm();
+    // `public fun double(x: u64): u64` is public but returns a value.
+    let handler = value_module(CORE_CODE_ADDRESS, "Handler");
+    assert_eq!(
+        vm.publish_module(gas(), handler, false).status_code,
+        StatusCode::EXECUTED
+    );
+    let vm = vm.with_message_handler(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Handler").unwrap()),
+        Identifier::new("double").unwrap(),
+    );
+
+    let res = vm.deliver_message(
+        gas(),
+        ExecutionContext::new(100, 100),
+        message(&[4], &[5, 6]),
+        false,
+    );
+    assert_eq!(
+        res.status_code,
+        StatusCode::EXECUTE_ENTRY_FUNCTION_CALLED_ON_NON_ENTRY_FUNCTION
+    );
+    assert!(queue.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_deliver_message_without_handler() {
+    let (vm, queue) = vm();
+
+    let res = vm.deliver_message(
+        gas(),
+        ExecutionContext::new(100, 100),
+        message(&[4], &[5, 6]),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::FUNCTION_RESOLUTION_FAILURE);
+    assert_eq!(res.gas_used, 0);
+    assert!(queue.lock().unwrap().is_empty());
+}
@@ -0,0 +1,164 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, Utils};
+use log::{LevelFilter, Log, Metadata, Record};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use move_vm_runtime::move_vm::VMLimits;
+use mvm::builder::MvmBuilder;
+use mvm::compression::Compression;
+use mvm::data::{AccessKey, ExecutionContext, MemoryStorage, NoOracle, State, Storage};
+use mvm::metrics::NoMetrics;
+use mvm::mvm::Mvm;
+use mvm::types::TxLimits;
+use mvm::vm_config::AddressesConfig;
+use mvm::Vm;
+
+mod common;
+
+#[test]
+fn test_build_with_defaults() {
+    let store = MemoryStorage::new();
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .build()
+        .unwrap();
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 13));
+
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    let blob = State::new(store, NoOracle)
+        .get_resource(&addr("0x1"), &tag)
+        .unwrap()
+        .unwrap();
+    assert_eq!(bcs::from_bytes::<StoreU64>(&blob).unwrap().val, 13);
+}
+
+#[test]
+fn test_build_with_components() {
+    let store = MemoryStorage::new();
+    let oracle = OracleMock::default();
+    oracle.set_price("ETH_BTC", 13);
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_oracle(oracle)
+        .with_bank(BankMock::default())
+        .with_compression(Compression::Lz4)
+        .with_instruction_limit(1)
+        .build()
+        .unwrap();
+    vm.pub_mod(store_module());
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+
+    let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+    let module = store.get(AccessKey::from(&id).as_ref());
+    assert!(module.is_some());
+    assert_ne!(module.as_deref(), Some(store_module().code()));
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        get_price_script(addr("0x1"), addr("0x2")),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::INSTRUCTION_LIMIT_EXCEEDED);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_constructors() {
+    let store = MemoryStorage::new();
+    let vm = Mvm::new_with_key_prefix(
+        store.clone(),
+        EventHandlerMock::default(),
+        OracleMock::default(),
+        BankMock::default(),
+        NoMetrics,
+        AddressesConfig::default(),
+        b"main".to_vec(),
+    )
+    .unwrap();
+    assert_eq!(vm.key_prefix(), b"main");
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 13));
+
+    let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+    assert!(store.get(AccessKey::from(&id).as_ref()).is_none());
+}
+
+#[test]
+fn test_build_with_limits() {
+    let vm = MvmBuilder::new(MemoryStorage::new(), EventHandlerMock::default())
+        .with_limits(VMLimits {
+            max_call_depth: 0,
+            ..VMLimits::default()
+        })
+        .build()
+        .unwrap();
+    vm.pub_mod(store_module());
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::CALL_STACK_OVERFLOW);
+
+    let vm = MvmBuilder::new(MemoryStorage::new(), EventHandlerMock::default())
+        .with_tx_limits(TxLimits {
+            max_script_size: 1,
+            ..TxLimits::default()
+        })
+        .build()
+        .unwrap();
+    vm.pub_mod(store_module());
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXCEEDED_MAX_TRANSACTION_SIZE);
+}
+
+/// Logger counting the records.
+struct CountingLogger(AtomicUsize);
+
+impl Log for CountingLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, _record: &Record) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CountingLogger = CountingLogger(AtomicUsize::new(0));
+
+#[test]
+fn test_build_with_logger() {
+    MvmBuilder::new(MemoryStorage::new(), EventHandlerMock::default())
+        .with_logger(&LOGGER, LevelFilter::Warn)
+        .build()
+        .unwrap();
+    let logged = LOGGER.0.load(Ordering::SeqCst);
+    log::warn!("warning");
+    assert!(LOGGER.0.load(Ordering::SeqCst) > logged);
+    assert_eq!(log::max_level(), LevelFilter::Warn);
+
+    // The logger is process-global and is installed once.
+    let res = MvmBuilder::new(MemoryStorage::new(), EventHandlerMock::default())
+        .with_logger(&LOGGER, LevelFilter::Warn)
+        .build();
+    assert!(res.is_err());
+}
@@ -0,0 +1,82 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::mvm::Mvm;
+use mvm::types::BatchTx;
+use mvm::vm_config::loader::store_vm_config;
+use mvm::vm_config::VmConfig;
+use mvm::{BatchVm, Vm};
+
+mod common;
+
+fn vm_with_chain_id(chain_id: u8) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    let store = StorageMock::new();
+    store_vm_config(
+        &store,
+        &VmConfig {
+            chain_id: Some(chain_id),
+            ..VmConfig::default()
+        },
+    );
+    MvmBuilder::new(store, EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_module_chain_id() {
+    let vm = vm_with_chain_id(1);
+
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::BAD_CHAIN_ID);
+    assert_eq!(res.gas_used, 0);
+
+    let res = vm.publish_module(gas(), store_module().with_chain_id(2), false);
+    assert_eq!(res.status_code, StatusCode::BAD_CHAIN_ID);
+
+    let res = vm.publish_module(gas(), store_module().with_chain_id(1), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+#[test]
+fn test_script_chain_id() {
+    let vm = vm_with_chain_id(1);
+    let res = vm.publish_module(gas(), store_module().with_chain_id(1), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let script = || store_u64_script(addr("0x1"), 1);
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 1), script(), false);
+    assert_eq!(res.status_code, StatusCode::BAD_CHAIN_ID);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 1),
+        script().with_chain_id(2),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::BAD_CHAIN_ID);
+    assert_eq!(res.gas_used, 0);
+
+    let batch = BatchTx::new(vec![store_u64_call(1)], vec![addr("0x1")]);
+    let res = vm.execute_batch(gas(), ExecutionContext::new(100, 1), batch, false);
+    assert_eq!(res.status_code, StatusCode::BAD_CHAIN_ID);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 1),
+        script().with_chain_id(1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+#[test]
+fn test_any_chain_id() {
+    let (vm, _, _, _, _) = common::vm();
+    let res = vm.publish_module(gas(), store_module().with_chain_id(7), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
@@ -0,0 +1,190 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use common::assets::*;
+use common::bytecode::{functions_module, native_call_script, native_module};
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::values::Value;
+use mvm::builder::MvmBuilder;
+use mvm::circuit_breaker::SafeMode;
+use mvm::data::{AccessKey, ExecutionContext};
+use mvm::metrics::Metrics;
+use mvm::mvm::Mvm;
+use mvm::types::{ScriptTx, VmResult};
+use mvm::vm_config::loader::{access_path_for_halted, load_halted_at};
+use mvm::vm_config::CONFIG_ADDRESS;
+use mvm::Vm;
+use vm::errors::PartialVMError;
+
+mod common;
+
+#[derive(Default)]
+struct MetricsMock {
+    violations: RefCell<Vec<StatusCode>>,
+}
+
+impl Metrics for MetricsMock {
+    fn on_invariant_violation(&self, result: &VmResult) {
+        self.violations.borrow_mut().push(result.status_code);
+    }
+}
+
+fn fail(
+    _: &mut dyn NativeContext,
+    _: Vec<Type>,
+    _: VecDeque<Value>,
+) -> Result<NativeResult, PartialVMError> {
+    Err(PartialVMError::new(StatusCode::STORAGE_ERROR))
+}
+
+/// Native `Broken::fail` failing with an invariant violation at `0x2` and the governance address.
+fn broken_natives() -> NativeRegistry {
+    NativeRegistry::new()
+        .register(
+            addr("0x2"),
+            "Broken",
+            "fail",
+            NativeGasParams::default(),
+            fail,
+        )
+        .register(
+            CONFIG_ADDRESS,
+            "Broken",
+            "fail",
+            NativeGasParams::default(),
+            fail,
+        )
+}
+
+fn fail_script() -> ScriptTx {
+    native_call_script(addr("0x2"), "Broken", "fail", vec![], vec![], vec![])
+}
+
+/// Governance script calling `0x2::Noop::noop`.
+fn noop_script() -> ScriptTx {
+    native_call_script(addr("0x2"), "Noop", "noop", vec![], vec![], vec![])
+}
+
+fn vm(mode: SafeMode) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock, MetricsMock> {
+    let vm = vm_with_store(StorageMock::new(), mode);
+    let module = native_module(addr("0x2"), "Broken", "fail", vec![], vec![]);
+    assert_eq!(
+        vm.publish_module(gas(), module, false).status_code,
+        StatusCode::EXECUTED
+    );
+    let module = functions_module(addr("0x2"), "Noop", &["noop"]);
+    assert_eq!(
+        vm.publish_module(gas(), module, false).status_code,
+        StatusCode::EXECUTED
+    );
+    vm
+}
+
+fn vm_with_store(
+    store: StorageMock,
+    mode: SafeMode,
+) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock, MetricsMock> {
+    MvmBuilder::new(store, EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .with_metrics(MetricsMock::default())
+        .build()
+        .unwrap()
+        .with_natives(broken_natives())
+        .with_circuit_breaker(mode)
+        .with_governance_origin(|_: &ScriptTx| Ok(()))
+}
+
+fn run(vm: &Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock, MetricsMock>) -> VmResult {
+    vm.execute_script(gas(), ExecutionContext::new(100, 100), fail_script(), false)
+}
+
+fn resume(vm: &Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock, MetricsMock>) -> VmResult {
+    vm.execute_governance_script(gas(), ExecutionContext::new(100, 100), noop_script(), false)
+}
+
+#[test]
+fn test_reject_all() {
+    let vm = vm(SafeMode::RejectAll);
+    assert!(!vm.is_halted());
+
+    // The transaction which trips the breaker is kept with the halt flag in its write set.
+    let res = run(&vm);
+    assert_eq!(res.status_code, StatusCode::STORAGE_ERROR);
+    assert!(res.halted);
+    assert!(!res.is_discarded());
+    assert_eq!(res.gas_used, 0);
+    assert!(vm.is_halted());
+    assert_eq!(
+        vm.metrics().violations.borrow().as_slice(),
+        &[StatusCode::STORAGE_ERROR]
+    );
+
+    let res = run(&vm);
+    assert_eq!(res.status_code, StatusCode::VM_HALTED);
+    assert_eq!(res.gas_used, 0);
+    assert_eq!(
+        vm.publish_module(gas(), store_module(), false).status_code,
+        StatusCode::VM_HALTED
+    );
+
+    // Only the governance resumes the vm.
+    let res = resume(&vm);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    let halt_key = AccessKey::from(&access_path_for_halted(CONFIG_ADDRESS));
+    assert_eq!(res.deletions, vec![halt_key.as_ref().to_vec()]);
+    assert!(!vm.is_halted());
+    assert_eq!(
+        vm.publish_module(gas(), store_module(), false).status_code,
+        StatusCode::EXECUTED
+    );
+}
+
+#[test]
+fn test_governance_only() {
+    let vm = vm(SafeMode::GovernanceOnly);
+    assert_eq!(run(&vm).status_code, StatusCode::STORAGE_ERROR);
+    assert!(vm.is_halted());
+
+    assert_eq!(
+        vm.publish_module(gas(), store_module(), false).status_code,
+        StatusCode::VM_HALTED
+    );
+    let governance_module = native_module(CONFIG_ADDRESS, "Broken", "fail", vec![], vec![]);
+    assert_eq!(
+        vm.publish_module(gas(), governance_module, false)
+            .status_code,
+        StatusCode::EXECUTED
+    );
+}
+
+#[test]
+fn test_halt_is_stored_on_chain() {
+    let store = StorageMock::new();
+    let vm = vm_with_store(store.clone(), SafeMode::RejectAll);
+    let module = native_module(addr("0x2"), "Broken", "fail", vec![], vec![]);
+    assert_eq!(
+        vm.publish_module(gas(), module, false).status_code,
+        StatusCode::EXECUTED
+    );
+    let module = functions_module(addr("0x2"), "Noop", &["noop"]);
+    assert_eq!(
+        vm.publish_module(gas(), module, false).status_code,
+        StatusCode::EXECUTED
+    );
+    assert_eq!(run(&vm).status_code, StatusCode::STORAGE_ERROR);
+    assert!(load_halted_at(&store, CONFIG_ADDRESS).unwrap());
+    assert!(vm.config_view().unwrap().halted);
+
+    let restarted = vm_with_store(store.clone(), SafeMode::RejectAll);
+    assert!(restarted.is_halted());
+    assert_eq!(run(&restarted).status_code, StatusCode::VM_HALTED);
+
+    assert_eq!(resume(&restarted).status_code, StatusCode::EXECUTED);
+    assert!(!load_halted_at(&store, CONFIG_ADDRESS).unwrap());
+    assert!(!vm.is_halted());
+}
@@ -1,4 +1,8 @@
+use std::sync::Arc;
+
+use move_core_types::account_address::AccountAddress;
 use move_core_types::identifier::Identifier;
+use mvm::types::ModuleTx;
 use parity_scale_codec::{Decode, Encode};
 
 #[test]
@@ -7,3 +11,48 @@ pub fn test_identifier() {
     let buffer = ident.encode();
     assert_eq!(ident, Identifier::decode(&mut buffer.as_ref()).unwrap())
 }
+
+#[test]
+pub fn test_module_tx() {
+    let code = vec![0xA1, 0x1C, 0xEB, 0x0B, 1, 2, 3];
+    let sender = AccountAddress::random();
+    let tx = ModuleTx::new(code.clone(), sender);
+
+    let buffer = tx.encode();
+    assert_eq!(buffer, (code.clone(), sender).encode());
+
+    let decoded = ModuleTx::decode(&mut buffer.as_ref()).unwrap();
+    assert_eq!(decoded.into_inner(), (Arc::from(code), sender));
+}
+
+#[test]
+pub fn test_immutable_module_tx() {
+    let code = vec![0xA1, 0x1C, 0xEB, 0x0B, 1, 2, 3];
+    let sender = AccountAddress::random();
+    let tx = ModuleTx::new(code.clone(), sender).immutable();
+
+    let buffer = tx.encode();
+    assert_eq!(buffer, (code, sender, true).encode());
+
+    let decoded = ModuleTx::decode(&mut buffer.as_ref()).unwrap();
+    assert!(decoded.is_immutable());
+    assert!(
+        !ModuleTx::decode(&mut ModuleTx::new(vec![1], sender).encode().as_ref())
+            .unwrap()
+            .is_immutable()
+    );
+}
+
+#[test]
+pub fn test_module_tx_chain_id() {
+    let code = vec![0xA1, 0x1C, 0xEB, 0x0B, 1, 2, 3];
+    let sender = AccountAddress::random();
+    let tx = ModuleTx::new(code.clone(), sender).with_chain_id(4);
+
+    let buffer = tx.encode();
+    assert_eq!(buffer, (code, sender, false, 4u8).encode());
+
+    let decoded = ModuleTx::decode(&mut buffer.as_ref()).unwrap();
+    assert_eq!(decoded.chain_id(), Some(4));
+    assert!(!decoded.is_immutable());
+}
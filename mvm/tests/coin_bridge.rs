@@ -0,0 +1,326 @@
+use common::assets::*;
+use common::bytecode::generic_call_script;
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use move_vm_types::natives::balance::{BalanceOperation, WalletId};
+use mvm::coin_bridge::{
+    load_locked, COIN_BRIDGE_MODULE, COIN_RESOURCE, INSUFFICIENT_BALANCE, NATIVE_BALANCE,
+    UNBACKED_COIN, UNWRAP, WRAP,
+};
+use mvm::data::{BalanceAccess, ExecutionContext, State};
+use mvm::testkit::mock::Utils;
+use mvm::types::{BalanceChange, ModuleTx, ScriptArg, ScriptTx};
+use mvm::Vm;
+use vm::file_format::{
+    empty_module, Bytecode, CodeUnit, FieldDefinition, FunctionDefinition, FunctionHandle,
+    FunctionHandleIndex, FunctionInstantiation, FunctionInstantiationIndex, IdentifierIndex, Kind,
+    ModuleHandleIndex, Signature, SignatureIndex, SignatureToken, StructDefInstantiation,
+    StructDefInstantiationIndex, StructDefinition, StructDefinitionIndex, StructFieldInformation,
+    StructHandle, StructHandleIndex, TypeSignature,
+};
+
+mod common;
+
+/// Module `0x1::CoinBridge` declaring:
+/// ```move
+/// resource struct Coin<Coin> { value: u64 }
+/// native public fun wrap<Coin>(account: &signer, amount: u64): Coin<Coin>;
+/// native public fun unwrap<Coin>(account: &signer, coin: Coin<Coin>);
+/// native public fun native_balance<Coin>(addr: address): u128;
+/// public fun deposit<Coin>(account: &signer, amount: u64) {
+///     move_to(account, wrap<Coin>(account, amount))
+/// }
+/// public fun withdraw<Coin>(account: &signer, addr: address) acquires Coin {
+///     unwrap<Coin>(account, move_from<Coin<Coin>>(addr))
+/// }
+/// public fun mint<Coin>(account: &signer, value: u64) {
+///     unwrap<Coin>(account, Coin<Coin> { value })
+/// }
+/// ```
+fn bridge_module() -> ModuleTx {
+    bridge_module_with(COIN_RESOURCE)
+}
+
+/// Bridge module declaring the coins as `coin`.
+fn bridge_module_with(coin: &str) -> ModuleTx {
+    use SignatureToken::*;
+
+    let mut m = empty_module();
+    m.address_identifiers = vec![CORE_CODE_ADDRESS];
+    m.identifiers = [
+        COIN_BRIDGE_MODULE,
+        coin,
+        "value",
+        WRAP,
+        UNWRAP,
+        NATIVE_BALANCE,
+        "deposit",
+        "withdraw",
+        "mint",
+    ]
+    .iter()
+    .map(|id| Identifier::new(*id).unwrap())
+    .collect();
+    m.struct_handles = vec![StructHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(1),
+        is_nominal_resource: true,
+        type_parameters: vec![Kind::All],
+    }];
+    m.struct_defs = vec![StructDefinition {
+        struct_handle: StructHandleIndex(0),
+        field_information: StructFieldInformation::Declared(vec![FieldDefinition {
+            name: IdentifierIndex(2),
+            signature: TypeSignature(U64),
+        }]),
+    }];
+    let signer = || Reference(Box::new(Signer));
+    let coin = || StructInstantiation(StructHandleIndex(0), vec![TypeParameter(0)]);
+    m.signatures = vec![
+        Signature(vec![]),
+        Signature(vec![signer(), U64]),
+        Signature(vec![coin()]),
+        Signature(vec![signer(), coin()]),
+        Signature(vec![Address]),
+        Signature(vec![U128]),
+        Signature(vec![TypeParameter(0)]),
+        Signature(vec![signer(), Address]),
+    ];
+    let handles = [
+        (3, 1, 2),
+        (4, 3, 0),
+        (5, 4, 5),
+        (6, 1, 0),
+        (7, 7, 0),
+        (8, 1, 0),
+    ];
+    m.function_handles = handles
+        .iter()
+        .map(|(name, params, returns)| FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(*name),
+            parameters: SignatureIndex(*params),
+            return_: SignatureIndex(*returns),
+            type_parameters: vec![Kind::All],
+        })
+        .collect();
+    m.function_instantiations = vec![
+        FunctionInstantiation {
+            handle: FunctionHandleIndex(0),
+            type_parameters: SignatureIndex(6),
+        },
+        FunctionInstantiation {
+            handle: FunctionHandleIndex(1),
+            type_parameters: SignatureIndex(6),
+        },
+    ];
+    let coin = StructDefInstantiationIndex(0);
+    m.struct_def_instantiations = vec![StructDefInstantiation {
+        def: StructDefinitionIndex(0),
+        type_parameters: SignatureIndex(6),
+    }];
+    let wrap = Bytecode::CallGeneric(FunctionInstantiationIndex(0));
+    let unwrap = Bytecode::CallGeneric(FunctionInstantiationIndex(1));
+    let natives = (0..3).map(|idx| FunctionDefinition {
+        function: FunctionHandleIndex(idx),
+        is_public: true,
+        acquires_global_resources: vec![],
+        code: None,
+    });
+    let functions = vec![
+        (
+            3,
+            vec![],
+            vec![
+                Bytecode::CopyLoc(0),
+                Bytecode::MoveLoc(0),
+                Bytecode::MoveLoc(1),
+                wrap,
+                Bytecode::MoveToGeneric(coin),
+                Bytecode::Ret,
+            ],
+        ),
+        (
+            4,
+            vec![StructDefinitionIndex(0)],
+            vec![
+                Bytecode::MoveLoc(0),
+                Bytecode::MoveLoc(1),
+                Bytecode::MoveFromGeneric(coin),
+                unwrap.clone(),
+                Bytecode::Ret,
+            ],
+        ),
+        (
+            5,
+            vec![],
+            vec![
+                Bytecode::MoveLoc(0),
+                Bytecode::MoveLoc(1),
+                Bytecode::PackGeneric(coin),
+                unwrap,
+                Bytecode::Ret,
+            ],
+        ),
+    ];
+    m.function_defs = natives
+        .chain(
+            functions
+                .into_iter()
+                .map(|(handle, acquires, code)| FunctionDefinition {
+                    function: FunctionHandleIndex(handle),
+                    is_public: true,
+                    acquires_global_resources: acquires,
+                    code: Some(CodeUnit {
+                        locals: SignatureIndex(0),
+                        code,
+                    }),
+                }),
+        )
+        .collect();
+
+    let mut code = vec![];
+    m.serialize(&mut code).unwrap();
+    ModuleTx::new(code, CORE_CODE_ADDRESS)
+}
+
+fn btc() -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Coins").unwrap(),
+        name: Identifier::new("BTC").unwrap(),
+        type_params: vec![],
+    }
+}
+
+/// Script calling `0x1::CoinBridge::function<BTC>` with the sender and the argument.
+fn call(function: &str, sender: AccountAddress, arg: ScriptArg) -> ScriptTx {
+    let signer = SignatureToken::Reference(Box::new(SignatureToken::Signer));
+    let param = match arg {
+        ScriptArg::Address(_) => SignatureToken::Address,
+        _ => SignatureToken::U64,
+    };
+    let tx = generic_call_script(
+        CORE_CODE_ADDRESS,
+        COIN_BRIDGE_MODULE,
+        function,
+        vec![signer, param],
+        vec![],
+        vec![],
+        vec![TypeTag::Struct(btc())],
+    );
+    ScriptTx::new(
+        tx.code().to_vec(),
+        vec![arg],
+        vec![TypeTag::Struct(btc())],
+        vec![sender],
+    )
+}
+
+fn wrapped<R: RemoteCache>(state: &R, address: &AccountAddress) -> Option<u64> {
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new(COIN_BRIDGE_MODULE).unwrap(),
+        name: Identifier::new(COIN_RESOURCE).unwrap(),
+        type_params: vec![TypeTag::Struct(btc())],
+    };
+    state
+        .get_resource(address, &tag)
+        .unwrap()
+        .map(|blob| bcs::from_bytes(&blob).unwrap())
+}
+
+#[test]
+fn test_wrap_and_unwrap() {
+    let (vm, store, _, oracle, bank) = vm();
+    let state = State::new(store.clone(), oracle);
+    vm.pub_mod(coins_module());
+    vm.pub_mod(bridge_module());
+
+    let alice = AccountAddress::random();
+    bank.set_balance(&alice, "BTC", 100);
+    let context = ExecutionContext::new(100, 100);
+
+    let res = vm.execute_script(
+        gas(),
+        context.clone(),
+        call("deposit", alice, ScriptArg::U64(60)),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(
+        res.balance_changes,
+        vec![BalanceChange {
+            wallet_id: WalletId::new(alice, btc()),
+            operation: BalanceOperation::Deposit(60),
+        }]
+    );
+    assert_eq!(wrapped(&state, &alice), Some(60));
+    assert_eq!(bank.get_balance(&alice, &ticker("BTC")), Some(40));
+    assert_eq!(load_locked(&store, CORE_CODE_ADDRESS, &btc()).unwrap(), 60);
+
+    let res = vm.execute_script(
+        gas(),
+        context.clone(),
+        call("withdraw", alice, ScriptArg::Address(alice)),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(wrapped(&state, &alice), None);
+    assert_eq!(bank.get_balance(&alice, &ticker("BTC")), Some(100));
+    assert_eq!(load_locked(&store, CORE_CODE_ADDRESS, &btc()).unwrap(), 0);
+}
+
+#[test]
+fn test_coin_conservation() {
+    let (vm, store, _, _, bank) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(bridge_module());
+
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    bank.set_balance(&alice, "BTC", 100);
+    let context = ExecutionContext::new(100, 100);
+
+    let res = vm.execute_script(
+        gas(),
+        context.clone(),
+        call("deposit", alice, ScriptArg::U64(101)),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.sub_status, Some(INSUFFICIENT_BALANCE));
+
+    let res = vm.execute_script(
+        gas(),
+        context.clone(),
+        call("deposit", alice, ScriptArg::U64(60)),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    // Coins minted in Move are not backed by the native balances.
+    let res = vm.execute_script(gas(), context, call("mint", bob, ScriptArg::U64(61)), false);
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.sub_status, Some(UNBACKED_COIN));
+    assert_eq!(bank.get_balance(&bob, &ticker("BTC")), None);
+    assert_eq!(load_locked(&store, CORE_CODE_ADDRESS, &btc()).unwrap(), 60);
+}
+
+#[test]
+fn test_bridge_declares_its_coin() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(coins_module());
+
+    // `unwrap` taking any other resource would unlock the coins packed outside the bridge.
+    let res = vm.publish_module(gas(), bridge_module_with("Diem"), false);
+    assert_eq!(res.status_code, StatusCode::TYPE_MISMATCH);
+    assert_eq!(
+        vm.publish_module(gas(), bridge_module(), false).status_code,
+        StatusCode::EXECUTED
+    );
+}
@@ -3,12 +3,11 @@ use std::convert::TryFrom;
 use serde::Deserialize;
 
 use move_core_types::account_address::AccountAddress;
-use move_core_types::language_storage::{TypeTag, CORE_CODE_ADDRESS};
-use mvm::types::{Gas, ModulePackage, ModuleTx, ScriptArg, ScriptTx};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, TypeTag, CORE_CODE_ADDRESS};
+use mvm::types::{EntryCall, ModulePackage, ModuleTx, ScriptArg, ScriptTx};
 
-pub fn gas() -> Gas {
-    Gas::new(10_000, 1).unwrap()
-}
+pub use mvm::testkit::{addr, gas, ticker};
 
 pub fn block_module() -> ModuleTx {
     ModuleTx::new(
@@ -129,6 +128,26 @@ pub fn store_u64_script(addr: AccountAddress, args: u64) -> ScriptTx {
     )
 }
 
+/// Call of `Store::store_u64`, see `store_module`.
+pub fn store_u64_call(val: u64) -> EntryCall {
+    EntryCall::new(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap()),
+        Identifier::new("store_u64").unwrap(),
+        vec![ScriptArg::U64(val)],
+        vec![],
+    )
+}
+
+/// Call of `Abort::error` aborting with the code, see `abort_module`.
+pub fn abort_call(code: u64) -> EntryCall {
+    EntryCall::new(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Abort").unwrap()),
+        Identifier::new("error").unwrap(),
+        vec![ScriptArg::U64(code)],
+        vec![],
+    )
+}
+
 pub fn test_transfer_script(alice: AccountAddress, bob: AccountAddress, amount: u128) -> ScriptTx {
     ScriptTx::new(
         include_bytes!("../assets/target/scripts/test_balance_transfer.mv").to_vec(),
@@ -0,0 +1,540 @@
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{TypeTag, CORE_CODE_ADDRESS};
+use mvm::types::{ModuleTx, ScriptArg, ScriptTx};
+use vm::file_format::{
+    empty_module, AddressIdentifierIndex, Bytecode, CodeUnit, CompiledScriptMut, FieldDefinition,
+    FieldHandle, FieldHandleIndex, FunctionDefinition, FunctionHandle, FunctionHandleIndex,
+    FunctionInstantiation, FunctionInstantiationIndex, IdentifierIndex, Kind, ModuleHandle,
+    ModuleHandleIndex, Signature, SignatureIndex, SignatureToken, StructDefinition,
+    StructDefinitionIndex, StructFieldInformation, StructHandle, StructHandleIndex, TypeSignature,
+};
+use vm::CompiledModule;
+
+/// Signature pool without duplicates.
+struct Signatures(Vec<Signature>);
+
+impl Signatures {
+    fn new() -> Signatures {
+        Signatures(vec![Signature(vec![])])
+    }
+
+    fn index(&mut self, sig: Vec<SignatureToken>) -> SignatureIndex {
+        let sig = Signature(sig);
+        match self.0.iter().position(|s| s == &sig) {
+            Some(idx) => SignatureIndex(idx as u16),
+            None => {
+                self.0.push(sig);
+                SignatureIndex(self.0.len() as u16 - 1)
+            }
+        }
+    }
+}
+
+/// Handle of the function with the identifier `name` declared in the module handle 0.
+fn function_handle(
+    signatures: &mut Signatures,
+    name: IdentifierIndex,
+    type_params: usize,
+    params: Vec<SignatureToken>,
+    returns: Vec<SignatureToken>,
+) -> FunctionHandle {
+    FunctionHandle {
+        module: ModuleHandleIndex(0),
+        name,
+        parameters: signatures.index(params),
+        return_: signatures.index(returns),
+        type_parameters: vec![Kind::All; type_params],
+    }
+}
+
+/// Instructions passing the arguments and the type parameters to the function handle 0.
+fn forward_call(
+    signatures: &mut Signatures,
+    instantiations: &mut Vec<FunctionInstantiation>,
+    type_params: usize,
+    params: usize,
+) -> Vec<Bytecode> {
+    let mut code: Vec<_> = (0..params)
+        .map(|idx| Bytecode::MoveLoc(idx as u8))
+        .collect();
+    if type_params == 0 {
+        code.push(Bytecode::Call(FunctionHandleIndex(0)));
+    } else {
+        let type_parameters = signatures.index(
+            (0..type_params)
+                .map(|idx| SignatureToken::TypeParameter(idx as u16))
+                .collect(),
+        );
+        instantiations.push(FunctionInstantiation {
+            handle: FunctionHandleIndex(0),
+            type_parameters,
+        });
+        code.push(Bytecode::CallGeneric(FunctionInstantiationIndex(
+            instantiations.len() as u16 - 1,
+        )));
+    }
+    code
+}
+
+/// Module `address::module` declaring `native public fun function(params): returns`.
+pub fn native_module(
+    address: AccountAddress,
+    module: &str,
+    function: &str,
+    params: Vec<SignatureToken>,
+    returns: Vec<SignatureToken>,
+) -> ModuleTx {
+    generic_native_module(address, module, function, 0, params, returns)
+}
+
+/// Module `address::module` declaring `native public fun function<T0, ..>(params): returns`.
+pub fn generic_native_module(
+    address: AccountAddress,
+    module: &str,
+    function: &str,
+    type_params: usize,
+    params: Vec<SignatureToken>,
+    returns: Vec<SignatureToken>,
+) -> ModuleTx {
+    let mut m = empty_module();
+    m.address_identifiers = vec![address];
+    m.identifiers = vec![
+        Identifier::new(module).unwrap(),
+        Identifier::new(function).unwrap(),
+    ];
+    let mut signatures = Signatures::new();
+    m.function_handles = vec![function_handle(
+        &mut signatures,
+        IdentifierIndex(1),
+        type_params,
+        params,
+        returns,
+    )];
+    m.signatures = signatures.0;
+    m.function_defs = vec![FunctionDefinition {
+        function: FunctionHandleIndex(0),
+        is_public: true,
+        acquires_global_resources: vec![],
+        code: None,
+    }];
+
+    let mut code = vec![];
+    m.serialize(&mut code).unwrap();
+    ModuleTx::new(code, address)
+}
+
+/// Module `address::module` declaring `native public fun function(params): returns`
+/// for each of the `functions`.
+pub fn natives_module(
+    address: AccountAddress,
+    module: &str,
+    functions: Vec<(&str, Vec<SignatureToken>, Vec<SignatureToken>)>,
+) -> ModuleTx {
+    let mut m = empty_module();
+    m.address_identifiers = vec![address];
+    m.identifiers = vec![Identifier::new(module).unwrap()];
+    let mut signatures = Signatures::new();
+    for (function, params, returns) in functions {
+        m.identifiers.push(Identifier::new(function).unwrap());
+        let name = IdentifierIndex(m.identifiers.len() as u16 - 1);
+        m.function_handles
+            .push(function_handle(&mut signatures, name, 0, params, returns));
+        m.function_defs.push(FunctionDefinition {
+            function: FunctionHandleIndex(m.function_handles.len() as u16 - 1),
+            is_public: true,
+            acquires_global_resources: vec![],
+            code: None,
+        });
+    }
+    m.signatures = signatures.0;
+
+    let mut code = vec![];
+    m.serialize(&mut code).unwrap();
+    ModuleTx::new(code, address)
+}
+
+/// Module `address::module` declaring `public fun function() {}` for each of the `functions`.
+pub fn functions_module(address: AccountAddress, module: &str, functions: &[&str]) -> ModuleTx {
+    let mut m = empty_module();
+    m.address_identifiers = vec![address];
+    m.identifiers = vec![Identifier::new(module).unwrap()];
+    let mut signatures = Signatures::new();
+    for (idx, function) in functions.iter().enumerate() {
+        m.identifiers.push(Identifier::new(*function).unwrap());
+        m.function_handles.push(function_handle(
+            &mut signatures,
+            IdentifierIndex(idx as u16 + 1),
+            0,
+            vec![],
+            vec![],
+        ));
+        m.function_defs.push(FunctionDefinition {
+            function: FunctionHandleIndex(idx as u16),
+            is_public: true,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code: vec![Bytecode::Ret],
+            }),
+        });
+    }
+    m.signatures = signatures.0;
+
+    let mut code = vec![];
+    m.serialize(&mut code).unwrap();
+    ModuleTx::new(code, address)
+}
+
+/// Module `address::module` declaring:
+/// ```move
+/// resource struct Value { value: u64 }
+/// public fun set(account: &signer, value: u64) { move_to(account, Value { value }) }
+/// public fun get(addr: address): u64 acquires Value { borrow_global<Value>(addr).value }
+/// public fun bump(addr: address) acquires Value { .. value + 1 .. }
+/// public fun double(x: u64): u64 { x + x }
+/// ```
+pub fn value_module(address: AccountAddress, module: &str) -> ModuleTx {
+    let mut m = empty_module();
+    m.address_identifiers = vec![address];
+    m.identifiers = ["Value", "value", "set", "get", "bump", "double"]
+        .iter()
+        .fold(vec![Identifier::new(module).unwrap()], |mut ids, id| {
+            ids.push(Identifier::new(*id).unwrap());
+            ids
+        });
+    m.struct_handles = vec![StructHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(1),
+        is_nominal_resource: true,
+        type_parameters: vec![],
+    }];
+    m.struct_defs = vec![StructDefinition {
+        struct_handle: StructHandleIndex(0),
+        field_information: StructFieldInformation::Declared(vec![FieldDefinition {
+            name: IdentifierIndex(2),
+            signature: TypeSignature(SignatureToken::U64),
+        }]),
+    }];
+    m.field_handles = vec![FieldHandle {
+        owner: StructDefinitionIndex(0),
+        field: 0,
+    }];
+
+    let value = StructDefinitionIndex(0);
+    let field = FieldHandleIndex(0);
+    let mut signatures = Signatures::new();
+    let functions = vec![
+        (
+            3,
+            vec![
+                SignatureToken::Reference(Box::new(SignatureToken::Signer)),
+                SignatureToken::U64,
+            ],
+            vec![],
+            vec![],
+            vec![
+                Bytecode::MoveLoc(0),
+                Bytecode::MoveLoc(1),
+                Bytecode::Pack(value),
+                Bytecode::MoveTo(value),
+                Bytecode::Ret,
+            ],
+        ),
+        (
+            4,
+            vec![SignatureToken::Address],
+            vec![SignatureToken::U64],
+            vec![value],
+            vec![
+                Bytecode::MoveLoc(0),
+                Bytecode::ImmBorrowGlobal(value),
+                Bytecode::ImmBorrowField(field),
+                Bytecode::ReadRef,
+                Bytecode::Ret,
+            ],
+        ),
+        (
+            5,
+            vec![SignatureToken::Address],
+            vec![],
+            vec![value],
+            vec![
+                Bytecode::CopyLoc(0),
+                Bytecode::ImmBorrowGlobal(value),
+                Bytecode::ImmBorrowField(field),
+                Bytecode::ReadRef,
+                Bytecode::LdU64(1),
+                Bytecode::Add,
+                Bytecode::MoveLoc(0),
+                Bytecode::MutBorrowGlobal(value),
+                Bytecode::MutBorrowField(field),
+                Bytecode::WriteRef,
+                Bytecode::Ret,
+            ],
+        ),
+        (
+            6,
+            vec![SignatureToken::U64],
+            vec![SignatureToken::U64],
+            vec![],
+            vec![
+                Bytecode::CopyLoc(0),
+                Bytecode::MoveLoc(0),
+                Bytecode::Add,
+                Bytecode::Ret,
+            ],
+        ),
+    ];
+    for (name, params, returns, acquires, code) in functions {
+        m.function_handles.push(function_handle(
+            &mut signatures,
+            IdentifierIndex(name),
+            0,
+            params,
+            returns,
+        ));
+        m.function_defs.push(FunctionDefinition {
+            function: FunctionHandleIndex(m.function_handles.len() as u16 - 1),
+            is_public: true,
+            acquires_global_resources: acquires,
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code,
+            }),
+        });
+    }
+    m.signatures = signatures.0;
+
+    let mut code = vec![];
+    m.serialize(&mut code).unwrap();
+    ModuleTx::new(code, address)
+}
+
+/// Module `address::module` declaring `fun function() {}` for each of the `functions`.
+pub fn private_functions_module(
+    address: AccountAddress,
+    module: &str,
+    functions: &[&str],
+) -> ModuleTx {
+    let module = functions_module(address, module, functions);
+    let mut m = CompiledModule::deserialize(module.code())
+        .unwrap()
+        .into_inner();
+    for def in &mut m.function_defs {
+        def.is_public = false;
+    }
+
+    let mut code = vec![];
+    m.serialize(&mut code).unwrap();
+    ModuleTx::new(code, address)
+}
+
+/// Module `address::module` declaring `native public fun function(params): returns`
+/// and `public fun proxy(params): returns` forwarding its arguments to the native.
+pub fn native_proxy_module(
+    address: AccountAddress,
+    module: &str,
+    function: &str,
+    proxy: &str,
+    params: Vec<SignatureToken>,
+    returns: Vec<SignatureToken>,
+) -> ModuleTx {
+    generic_native_proxy_module(address, module, function, proxy, 0, params, returns)
+}
+
+/// Module `address::module` declaring `native public fun function<T0, ..>(params): returns`
+/// and `public fun proxy<T0, ..>(params): returns` forwarding its arguments to the native.
+pub fn generic_native_proxy_module(
+    address: AccountAddress,
+    module: &str,
+    function: &str,
+    proxy: &str,
+    type_params: usize,
+    params: Vec<SignatureToken>,
+    returns: Vec<SignatureToken>,
+) -> ModuleTx {
+    let mut m = empty_module();
+    m.address_identifiers = vec![address];
+    m.identifiers = vec![
+        Identifier::new(module).unwrap(),
+        Identifier::new(function).unwrap(),
+        Identifier::new(proxy).unwrap(),
+    ];
+    let params_len = params.len();
+    let mut signatures = Signatures::new();
+    let handle = function_handle(
+        &mut signatures,
+        IdentifierIndex(1),
+        type_params,
+        params,
+        returns,
+    );
+    let proxy_handle = FunctionHandle {
+        name: IdentifierIndex(2),
+        ..handle.clone()
+    };
+    m.function_handles = vec![handle, proxy_handle];
+
+    let mut code = forward_call(
+        &mut signatures,
+        &mut m.function_instantiations,
+        type_params,
+        params_len,
+    );
+    code.push(Bytecode::Ret);
+    m.signatures = signatures.0;
+    m.function_defs = vec![
+        FunctionDefinition {
+            function: FunctionHandleIndex(0),
+            is_public: true,
+            acquires_global_resources: vec![],
+            code: None,
+        },
+        FunctionDefinition {
+            function: FunctionHandleIndex(1),
+            is_public: true,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code,
+            }),
+        },
+    ];
+
+    let mut code = vec![];
+    m.serialize(&mut code).unwrap();
+    ModuleTx::new(code, address)
+}
+
+/// Script passing its arguments to `address::module::function` and dropping the results.
+pub fn native_call_script(
+    address: AccountAddress,
+    module: &str,
+    function: &str,
+    params: Vec<SignatureToken>,
+    returns: Vec<SignatureToken>,
+    args: Vec<ScriptArg>,
+) -> ScriptTx {
+    generic_call_script(address, module, function, params, returns, args, vec![])
+}
+
+/// Script passing its arguments and type arguments to `address::module::function`
+/// and dropping the results.
+pub fn generic_call_script(
+    address: AccountAddress,
+    module: &str,
+    function: &str,
+    params: Vec<SignatureToken>,
+    returns: Vec<SignatureToken>,
+    args: Vec<ScriptArg>,
+    type_args: Vec<TypeTag>,
+) -> ScriptTx {
+    let type_params = type_args.len();
+    let params_len = params.len();
+    let returns_len = returns.len();
+    let mut signatures = Signatures::new();
+    let handle = function_handle(
+        &mut signatures,
+        IdentifierIndex(1),
+        type_params,
+        params,
+        returns,
+    );
+    let mut function_instantiations = vec![];
+    let mut code = forward_call(
+        &mut signatures,
+        &mut function_instantiations,
+        type_params,
+        params_len,
+    );
+    code.extend((0..returns_len).map(|_| Bytecode::Pop));
+    code.push(Bytecode::Ret);
+
+    let script = CompiledScriptMut {
+        module_handles: vec![ModuleHandle {
+            address: AddressIdentifierIndex(0),
+            name: IdentifierIndex(0),
+        }],
+        struct_handles: vec![],
+        parameters: handle.parameters,
+        function_handles: vec![handle],
+        function_instantiations,
+        signatures: signatures.0,
+        identifiers: vec![
+            Identifier::new(module).unwrap(),
+            Identifier::new(function).unwrap(),
+        ],
+        address_identifiers: vec![address],
+        constant_pool: vec![],
+        type_parameters: vec![Kind::All; type_params],
+        code: CodeUnit {
+            locals: SignatureIndex(0),
+            code,
+        },
+    };
+
+    let mut blob = vec![];
+    script.serialize(&mut blob).unwrap();
+    ScriptTx::new(blob, args, type_args, vec![])
+}
+
+/// Script `main(&signer, .., vector<signer>)` dropping `signer_references` signer references
+/// and passing the signer vector to `0x1::Vector::destroy_empty<signer>`.
+pub fn signer_vector_script(signer_references: usize, senders: Vec<AccountAddress>) -> ScriptTx {
+    let mut signatures = Signatures::new();
+    let signer_vector = SignatureToken::Vector(Box::new(SignatureToken::Signer));
+    let handle = function_handle(
+        &mut signatures,
+        IdentifierIndex(1),
+        1,
+        vec![SignatureToken::Vector(Box::new(
+            SignatureToken::TypeParameter(0),
+        ))],
+        vec![],
+    );
+    let mut params =
+        vec![SignatureToken::Reference(Box::new(SignatureToken::Signer)); signer_references];
+    params.push(signer_vector);
+    let parameters = signatures.index(params);
+    let type_parameters = signatures.index(vec![SignatureToken::Signer]);
+
+    let mut code = vec![];
+    for idx in 0..signer_references {
+        code.push(Bytecode::MoveLoc(idx as u8));
+        code.push(Bytecode::Pop);
+    }
+    code.push(Bytecode::MoveLoc(signer_references as u8));
+    code.push(Bytecode::CallGeneric(FunctionInstantiationIndex(0)));
+    code.push(Bytecode::Ret);
+
+    let script = CompiledScriptMut {
+        module_handles: vec![ModuleHandle {
+            address: AddressIdentifierIndex(0),
+            name: IdentifierIndex(0),
+        }],
+        struct_handles: vec![],
+        parameters,
+        function_handles: vec![handle],
+        function_instantiations: vec![FunctionInstantiation {
+            handle: FunctionHandleIndex(0),
+            type_parameters,
+        }],
+        signatures: signatures.0,
+        identifiers: vec![
+            Identifier::new("Vector").unwrap(),
+            Identifier::new("destroy_empty").unwrap(),
+        ],
+        address_identifiers: vec![CORE_CODE_ADDRESS],
+        constant_pool: vec![],
+        type_parameters: vec![],
+        code: CodeUnit {
+            locals: SignatureIndex(0),
+            code,
+        },
+    };
+
+    let mut blob = vec![];
+    script.serialize(&mut blob).unwrap();
+    ScriptTx::new(blob, vec![], vec![], senders)
+}
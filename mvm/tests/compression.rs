@@ -0,0 +1,49 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::builder::MvmBuilder;
+use mvm::compression::{decompress, Compression};
+use mvm::data::{AccessKey, State, Storage};
+
+mod common;
+
+#[test]
+fn test_compression_roundtrip() {
+    let module = store_module().code().to_vec();
+    let compressed = Compression::Lz4.compress(&module).into_owned();
+    assert_eq!(decompress(compressed).unwrap(), module);
+    assert_eq!(
+        Compression::None.compress(&module).as_ref(),
+        module.as_slice()
+    );
+    assert_eq!(decompress(module.clone()).unwrap(), module);
+}
+
+#[test]
+fn test_publish_compressed_module() {
+    let store = StorageMock::new();
+    let oracle = OracleMock::default();
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_oracle(oracle.clone())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_compression(Compression::Lz4);
+
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 13));
+
+    let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+    let state = State::new(store.clone(), oracle);
+    assert_eq!(
+        state.get_module(&id).unwrap().unwrap(),
+        store_module().code()
+    );
+
+    let stored = store.get(AccessKey::from(&id).as_ref()).unwrap();
+    if stored.as_slice() != store_module().code() {
+        assert!(stored.len() < store_module().code().len());
+    }
+}
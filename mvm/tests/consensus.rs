@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use common::assets::*;
+use common::bytecode::{native_call_script, native_module};
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::values::Value;
+use mvm::builder::MvmBuilder;
+use mvm::consensus::{ConsensusMode, Deterministic};
+use mvm::data::ExecutionContext;
+use mvm::host::{HOST_CALL, HOST_MODULE};
+use mvm::types::ScriptArg;
+use mvm::Vm;
+use vm::errors::PartialVMResult;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+fn vm() -> ConsensusMode<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    ConsensusMode::new(
+        StorageMock::new(),
+        EventHandlerMock::default(),
+        OracleMock::default(),
+        BankMock::default(),
+    )
+    .unwrap()
+}
+
+fn now(
+    _: &mut dyn NativeContext,
+    _: Vec<Type>,
+    _: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    Ok(NativeResult::ok(GasUnits::new(0), vec![Value::u64(0)]))
+}
+
+#[test]
+fn test_reject_nondeterministic_natives() {
+    let natives = NativeRegistry::new()
+        .register(addr("0x2"), "Math", "zero", NativeGasParams::default(), now)
+        .register_nondeterministic(addr("0x2"), "Time", "now", NativeGasParams::default(), now);
+    assert_eq!(natives.nondeterministic().count(), 1);
+    assert!(vm().with_natives(natives).is_err());
+
+    let natives = NativeRegistry::new().register(
+        addr("0x2"),
+        "Math",
+        "zero",
+        NativeGasParams::default(),
+        now,
+    );
+    assert!(vm().with_natives(natives).is_ok());
+}
+
+#[test]
+fn test_build_consensus() {
+    let builder = || {
+        MvmBuilder::new(StorageMock::new(), EventHandlerMock::default())
+            .with_oracle(OracleMock::default())
+            .with_bank(BankMock::default())
+    };
+    let natives = NativeRegistry::new().register_nondeterministic(
+        addr("0x2"),
+        "Time",
+        "now",
+        NativeGasParams::default(),
+        now,
+    );
+    assert!(builder().with_natives(natives).build_consensus().is_err());
+
+    let vm = builder().build_consensus().unwrap();
+    assert_eq!(
+        vm.publish_module(gas(), store_module(), false).status_code,
+        StatusCode::EXECUTED
+    );
+}
+
+#[test]
+fn test_deterministic_host_handler() {
+    let params = vec![
+        SignatureToken::U64,
+        SignatureToken::Vector(Box::new(SignatureToken::U8)),
+    ];
+    let returns = vec![SignatureToken::Vector(Box::new(SignatureToken::U8))];
+
+    let vm = vm().with_host_handler(
+        1,
+        Deterministic(|payload: &[u8]| -> Result<Vec<u8>, u64> { Ok(payload.to_vec()) }),
+    );
+    assert_eq!(
+        vm.publish_module(
+            gas(),
+            native_module(
+                CORE_CODE_ADDRESS,
+                HOST_MODULE,
+                HOST_CALL,
+                params.clone(),
+                returns.clone()
+            ),
+            false
+        )
+        .status_code,
+        StatusCode::EXECUTED
+    );
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        native_call_script(
+            CORE_CODE_ADDRESS,
+            HOST_MODULE,
+            HOST_CALL,
+            params,
+            returns,
+            vec![ScriptArg::U64(1), ScriptArg::VectorU8(vec![1, 2])],
+        ),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
@@ -0,0 +1,313 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, Utils};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_vm_runtime::data_cache::RemoteCache;
+use move_vm_types::natives::balance::WalletId;
+use mvm::builder::MvmBuilder;
+use mvm::data::{
+    decode_wallet_id, AccessKey, MemoryStorage, OverlayStorage, PrefixedStorage, RecordingStorage,
+    State, Storage,
+};
+
+mod common;
+
+#[test]
+fn test_memory_storage_is_thread_safe() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MemoryStorage>();
+}
+
+#[test]
+fn test_memory_storage_snapshot() {
+    let store = MemoryStorage::new();
+    store.insert(b"key", b"value");
+
+    let snapshot = store.snapshot();
+    store.insert(b"key", b"new value");
+    store.remove(b"key");
+    assert!(store.is_empty());
+    assert_eq!(snapshot.get(b"key"), Some(b"value".to_vec()));
+
+    store.restore(&snapshot);
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.get(b"key"), Some(b"value".to_vec()));
+}
+
+#[test]
+fn test_vm_with_memory_storage() {
+    let store = MemoryStorage::new();
+    let oracle = OracleMock::default();
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_oracle(oracle.clone())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap();
+
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 13));
+
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    let state = State::new(store, oracle);
+    let blob = state.get_resource(&addr("0x1"), &tag).unwrap().unwrap();
+    assert_eq!(bcs::from_bytes::<StoreU64>(&blob).unwrap().val, 13);
+}
+
+#[test]
+fn test_overlay_storage_commit() {
+    let base = MemoryStorage::new();
+    base.insert(b"removed", b"value");
+    base.insert(b"changed", b"value");
+
+    let overlay = OverlayStorage::new(base.clone());
+    overlay.remove(b"removed");
+    overlay.insert(b"changed", b"new value");
+    overlay.insert(b"added", b"value");
+
+    assert_eq!(overlay.get(b"removed"), None);
+    assert_eq!(overlay.get(b"changed"), Some(b"new value".to_vec()));
+    assert_eq!(overlay.get(b"added"), Some(b"value".to_vec()));
+    assert_eq!(base.get(b"removed"), Some(b"value".to_vec()));
+    assert_eq!(base.get(b"changed"), Some(b"value".to_vec()));
+    assert_eq!(base.get(b"added"), None);
+
+    overlay.commit();
+    assert!(overlay.is_clean());
+    assert_eq!(base.get(b"removed"), None);
+    assert_eq!(base.get(b"changed"), Some(b"new value".to_vec()));
+    assert_eq!(base.get(b"added"), Some(b"value".to_vec()));
+}
+
+#[test]
+fn test_overlay_storage_discard() {
+    let base = MemoryStorage::new();
+    base.insert(b"key", b"value");
+
+    let overlay = OverlayStorage::new(base.clone());
+    overlay.insert(b"key", b"new value");
+    overlay.discard();
+
+    assert!(overlay.is_clean());
+    assert_eq!(overlay.get(b"key"), Some(b"value".to_vec()));
+    assert_eq!(base.get(b"key"), Some(b"value".to_vec()));
+}
+
+#[test]
+fn test_recording_storage() {
+    let store = RecordingStorage::new(MemoryStorage::new());
+    store.insert(b"written", b"value");
+    store.remove(b"removed");
+    store.get(b"read");
+
+    assert!(store.reads().contains(&b"read".to_vec()));
+    assert_eq!(store.writes().len(), 2);
+    assert!(store.writes().contains(&b"written".to_vec()));
+    assert!(store.writes().contains(&b"removed".to_vec()));
+
+    let first = store.take();
+    assert!(store.reads().is_empty());
+    assert!(store.writes().is_empty());
+
+    store.get(b"written");
+    let second = store.take();
+    assert!(first.conflicts_with(&second));
+    assert!(!second.conflicts_with(&RecordingStorage::new(MemoryStorage::new()).take()));
+}
+
+#[test]
+fn test_recording_storage_with_vm() {
+    let store = RecordingStorage::new(MemoryStorage::new());
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap();
+    vm.pub_mod(store_module());
+    store.take();
+
+    vm.exec(store_u64_script(addr("0x1"), 13));
+    let rw_set = store.take();
+
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    let key = AccessKey::from((&addr("0x1"), &tag));
+    assert!(rw_set.writes.contains(key.as_ref()));
+}
+
+#[test]
+fn test_decode_wallet_id() {
+    let wallet_at = |address: AccountAddress, module: &str, name: &str| {
+        WalletId::new(
+            CORE_CODE_ADDRESS,
+            StructTag {
+                address,
+                module: Identifier::new(module).unwrap(),
+                name: Identifier::new(name).unwrap(),
+                type_params: vec![],
+            },
+        )
+    };
+    let wallet = |module: &str, name: &str| wallet_at(CORE_CODE_ADDRESS, module, name);
+    let decode = |wallet_id: &WalletId| decode_wallet_id(wallet_id, &CORE_CODE_ADDRESS);
+
+    assert_eq!(
+        decode(&wallet("PONT", "T")),
+        Some((CORE_CODE_ADDRESS, ticker("PONT")))
+    );
+    assert_eq!(
+        decode(&wallet("Coins", "USDT")),
+        Some((CORE_CODE_ADDRESS, ticker("USDT")))
+    );
+    assert_eq!(decode(&wallet("Coins", "Price")), None);
+    assert_eq!(decode(&wallet("PONT", "X")), None);
+    assert_eq!(decode(&wallet("Store", "USDT")), None);
+
+    // Currencies of a relocated standard library.
+    let core_address = addr("0x42");
+    assert_eq!(decode(&wallet_at(core_address, "PONT", "T")), None);
+    assert_eq!(
+        decode_wallet_id(&wallet_at(core_address, "PONT", "T"), &core_address),
+        Some((CORE_CODE_ADDRESS, ticker("PONT")))
+    );
+}
+
+#[derive(Default)]
+struct LogStorage {
+    log: std::cell::RefCell<Vec<(Vec<u8>, bool)>>,
+}
+
+impl Storage for LogStorage {
+    fn get(&self, _key: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn insert(&self, key: &[u8], _value: &[u8]) {
+        self.log.borrow_mut().push((key.to_vec(), true));
+    }
+
+    fn remove(&self, key: &[u8]) {
+        self.log.borrow_mut().push((key.to_vec(), false));
+    }
+}
+
+#[test]
+fn test_overlay_commit_is_ordered() {
+    let overlay = OverlayStorage::new(LogStorage::default());
+    overlay.insert(b"c", b"3");
+    overlay.remove(b"a");
+    overlay.insert(b"b", b"2");
+    overlay.commit();
+
+    assert_eq!(
+        *overlay.base().log.borrow(),
+        vec![
+            (b"a".to_vec(), false),
+            (b"b".to_vec(), true),
+            (b"c".to_vec(), true)
+        ]
+    );
+}
+
+#[test]
+fn test_memory_storage_next_key() {
+    let store = MemoryStorage::new();
+    store.insert(b"b", b"2");
+    store.insert(b"d", b"4");
+    store.insert(b"a", b"1");
+
+    assert_eq!(store.next_key(b""), Some(b"a".to_vec()));
+    assert_eq!(store.next_key(b"b"), Some(b"d".to_vec()));
+    assert_eq!(store.next_key(b"c"), Some(b"d".to_vec()));
+    assert_eq!(store.next_key(b"d"), None);
+}
+
+#[test]
+fn test_prefixed_storage() {
+    let store = MemoryStorage::new();
+    store.insert(b"a", b"0");
+    store.insert(b"\x01tb", b"1");
+    store.insert(b"\x01td", b"2");
+    store.insert(b"\x02tu", b"3");
+
+    let prefixed = PrefixedStorage::new(store.clone(), b"t".to_vec());
+    assert_eq!(prefixed.key_prefix(), b"\x01t");
+    assert_eq!(prefixed.get(b"b"), Some(b"1".to_vec()));
+    assert_eq!(prefixed.get(b"a"), None);
+    assert_eq!(prefixed.next_key(b""), Some(b"b".to_vec()));
+    assert_eq!(prefixed.next_key(b"b"), Some(b"d".to_vec()));
+    assert_eq!(prefixed.next_key(b"d"), None);
+
+    prefixed.insert(b"a", b"4");
+    prefixed.remove(b"b");
+    assert_eq!(store.get(b"\x01ta"), Some(b"4".to_vec()));
+    assert_eq!(store.get(b"\x01tb"), None);
+    assert_eq!(store.get(b"a"), Some(b"0".to_vec()));
+}
+
+#[test]
+fn test_prefixed_storage_prefixes_do_not_overlap() {
+    let store = MemoryStorage::new();
+    let short = PrefixedStorage::new(store.clone(), b"t".to_vec());
+    let long = PrefixedStorage::new(store.clone(), b"tb".to_vec());
+
+    short.insert(b"b", b"0");
+    long.insert(b"", b"1");
+    assert_eq!(short.get(b"b"), Some(b"0".to_vec()));
+    assert_eq!(long.get(b""), Some(b"1".to_vec()));
+    assert_eq!(short.next_key(b"b"), None);
+    assert_eq!(long.next_key(b""), None);
+
+    let long = PrefixedStorage::new(store, vec![7; 200]);
+    assert_eq!(&long.key_prefix()[..2], &[0xC8, 0x01]);
+    assert_eq!(long.key_prefix().len(), 202);
+}
+
+#[test]
+fn test_vms_with_key_prefixes() {
+    let store = MemoryStorage::new();
+    let new_vm = |prefix: &[u8]| {
+        MvmBuilder::new(store.clone(), EventHandlerMock::default())
+            .with_oracle(OracleMock::default())
+            .with_bank(BankMock::default())
+            .with_key_prefix(prefix.to_vec())
+            .build()
+            .unwrap()
+    };
+    let main = new_vm(b"main");
+    let test = new_vm(b"test");
+    assert_eq!(main.key_prefix(), b"main");
+
+    main.pub_mod(store_module());
+    main.exec(store_u64_script(addr("0x1"), 1));
+    test.pub_mod(store_module());
+    test.exec(store_u64_script(addr("0x1"), 2));
+
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    let value = |prefix: &[u8]| {
+        let state =
+            State::new(store.clone(), OracleMock::default()).with_key_prefix(prefix.to_vec());
+        let blob = state.get_resource(&addr("0x1"), &tag).unwrap().unwrap();
+        bcs::from_bytes::<StoreU64>(&blob).unwrap().val
+    };
+    assert_eq!(value(b"main"), 1);
+    assert_eq!(value(b"test"), 2);
+    assert!(State::new(store, OracleMock::default())
+        .get_resource(&addr("0x1"), &tag)
+        .unwrap()
+        .is_none());
+}
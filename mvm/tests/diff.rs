@@ -0,0 +1,86 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use common::mock::{StorageMock, Utils};
+use common::{assets::*, vm};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ResourceKey, StructTag, CORE_CODE_ADDRESS};
+use mvm::data::{AccessKey, Storage};
+use mvm::diff::{state_diff, Change};
+
+mod common;
+
+fn snapshot(store: &StorageMock) -> StorageMock {
+    StorageMock {
+        data: Rc::new(RefCell::new(store.data.borrow().clone())),
+    }
+}
+
+fn store_u64_key() -> ResourceKey {
+    ResourceKey::new(
+        addr("0x1"),
+        StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("Store").unwrap(),
+            name: Identifier::new("U64").unwrap(),
+            type_params: vec![],
+        },
+    )
+}
+
+#[test]
+fn test_added_resource() {
+    let (vm, store, _, _, _) = vm();
+    vm.pub_mod(store_module());
+
+    let before = snapshot(&store);
+    vm.exec(store_u64_script(addr("0x1"), 13));
+
+    let diff = state_diff(&before, &store, &[store_u64_key()]);
+    assert!(diff.removed.is_empty());
+    assert!(diff.changed.is_empty());
+    assert_eq!(diff.added.len(), 1);
+
+    let entry = diff.get(&store_u64_key()).unwrap();
+    assert!(entry.before::<StoreU64>().unwrap().is_none());
+    assert_eq!(entry.after::<StoreU64>().unwrap().unwrap().val, 13);
+}
+
+#[test]
+fn test_equal_states() {
+    let (vm, store, _, _, _) = vm();
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 13));
+
+    let diff = state_diff(&snapshot(&store), &store, &[store_u64_key()]);
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn test_removed_and_changed_resource() {
+    let (_, before, _, _, _) = vm();
+    let after = snapshot(&before);
+    let changed = store_u64_key();
+    let removed = ResourceKey::new(addr("0x2"), changed.type_.clone());
+
+    let changed_key = AccessKey::from((&changed.address, &changed.type_));
+    let removed_key = AccessKey::from((&removed.address, &removed.type_));
+    before.insert(changed_key.as_ref(), &bcs::to_bytes(&1u64).unwrap());
+    after.insert(changed_key.as_ref(), &bcs::to_bytes(&2u64).unwrap());
+    before.insert(removed_key.as_ref(), &bcs::to_bytes(&3u64).unwrap());
+
+    let diff = state_diff(&before, &after, &[changed.clone(), removed.clone()]);
+    assert!(diff.added.is_empty());
+    assert_eq!(
+        diff.get(&changed).unwrap().change,
+        Change::Changed {
+            before: bcs::to_bytes(&1u64).unwrap(),
+            after: bcs::to_bytes(&2u64).unwrap(),
+        }
+    );
+    assert_eq!(
+        diff.get(&removed).unwrap().before::<u64>().unwrap(),
+        Some(3)
+    );
+    assert_eq!(diff.get(&removed).unwrap().after::<u64>().unwrap(), None);
+}
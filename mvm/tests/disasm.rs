@@ -0,0 +1,37 @@
+use common::assets::*;
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use mvm::disasm::disassemble;
+
+mod common;
+
+#[test]
+fn test_disassemble_functions() {
+    let listing = disassemble(abort_module().code()).unwrap();
+    assert!(listing.starts_with("module 0x1::Abort {\n"));
+    assert!(listing.contains("    public fun error(u64) {\n"));
+    assert!(listing.contains("        0: MoveLoc(0)\n"));
+    assert!(listing.contains("        1: Abort\n"));
+    assert!(listing.ends_with("}\n"));
+}
+
+#[test]
+fn test_disassemble_resolves_names() {
+    let listing = disassemble(store_module().code()).unwrap();
+    assert!(listing.contains("    resource struct VectorU8 {\n        val: vector<u8>,\n    }\n"));
+    assert!(listing.contains("    public fun store_u64(&signer, u64) {\n"));
+    assert!(listing.contains(": Pack 0x1::Store::U64\n"));
+    assert!(listing.contains(": MoveTo 0x1::Store::U64\n"));
+}
+
+#[test]
+fn test_disassemble_stdlib() {
+    let (modules, _) = stdlib_package().into_tx(CORE_CODE_ADDRESS).into_inner();
+    for module in modules {
+        assert!(disassemble(&module).is_ok());
+    }
+}
+
+#[test]
+fn test_disassemble_invalid_module() {
+    assert!(disassemble(&[0x0, 0x1, 0x2]).is_err());
+}
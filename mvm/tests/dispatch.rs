@@ -0,0 +1,84 @@
+use std::convert::TryFrom;
+
+use common::assets::*;
+use move_core_types::vm_status::StatusCode;
+use mvm::data::ExecutionContext;
+use mvm::dispatch::{ErrorKind, VmError};
+use mvm::testkit::vm;
+use mvm::Vm;
+
+mod common;
+
+#[test]
+fn test_every_status_is_mapped() {
+    for code in 0..5000 {
+        if let Ok(status) = StatusCode::try_from(code) {
+            let error = ErrorKind::from_status(status);
+            assert_eq!(error.is_none(), status == StatusCode::EXECUTED);
+        }
+    }
+    assert_eq!(
+        ErrorKind::from_status(StatusCode::UNKNOWN_STATUS),
+        Some(ErrorKind::Unknown)
+    );
+    assert_eq!(
+        ErrorKind::from_status(StatusCode::MISSING_DEPENDENCY),
+        Some(ErrorKind::LinkerError)
+    );
+    assert_eq!(
+        ErrorKind::from_status(StatusCode::BAD_MAGIC),
+        Some(ErrorKind::DeserializationError)
+    );
+    assert_eq!(
+        ErrorKind::from_status(StatusCode::TYPE_MISMATCH),
+        Some(ErrorKind::VerificationError)
+    );
+}
+
+#[test]
+fn test_reverse_lookup() {
+    for error in ErrorKind::all() {
+        assert_eq!(ErrorKind::from_index(error.index()), Some(*error));
+        assert_eq!(ErrorKind::try_from(u8::from(*error)), Ok(*error));
+    }
+    assert_eq!(ErrorKind::from_index(18), None);
+    assert_eq!(ErrorKind::try_from(255), Err(255));
+}
+
+#[test]
+fn test_stable_indices() {
+    assert_eq!(ErrorKind::Unknown.index(), 0);
+    assert_eq!(ErrorKind::InvalidSignature.index(), 1);
+    assert_eq!(ErrorKind::LinkerError.index(), 20);
+    assert_eq!(ErrorKind::DeserializationError.index(), 40);
+    assert_eq!(ErrorKind::InvariantViolation.index(), 59);
+    assert_eq!(ErrorKind::Aborted.index(), 60);
+    assert_eq!(ErrorKind::OutOfGas.index(), 61);
+    assert_eq!(ErrorKind::ExecutionError.index(), 79);
+}
+
+#[test]
+fn test_error_of_result() {
+    let (vm, _, _, _, _) = vm();
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::LINKER_ERROR);
+    let error = VmError::from_result(&res).unwrap();
+    assert_eq!(error.kind, ErrorKind::LinkerError);
+    assert_eq!(error.index(), ErrorKind::LinkerError.index());
+    assert_eq!(error.sub_status, res.sub_status);
+    assert_eq!(ErrorKind::LinkerError.to_string(), "LinkerError");
+}
+
+#[test]
+fn test_error_carries_sub_status() {
+    let error = VmError::from_status(StatusCode::ABORTED, Some(13)).unwrap();
+    assert_eq!(error.kind, ErrorKind::Aborted);
+    assert_eq!(error.sub_status, Some(13));
+    assert_eq!(error.to_string(), "Aborted(13)");
+    assert_eq!(VmError::from_status(StatusCode::EXECUTED, Some(13)), None);
+}
@@ -0,0 +1,182 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::access_path::AccessPath;
+use mvm::builder::MvmBuilder;
+use mvm::data::{AccessKey, ExecutionContext};
+use mvm::epoch::{EPOCH_MODULE, NEW_EPOCH_EVENT};
+use mvm::mvm::Mvm;
+use mvm::vm_config::loader::{access_path_for_config, load_epoch_at, store_vm_config};
+use mvm::vm_config::{VmConfig, CONFIG_ADDRESS};
+use mvm::Vm;
+
+mod common;
+
+fn store_path() -> AccessPath {
+    AccessPath::new(
+        addr("0x2"),
+        AccessPath::resource_access_vec(&StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("Store").unwrap(),
+            name: Identifier::new("U64").unwrap(),
+            type_params: vec![],
+        }),
+    )
+}
+
+fn vm(
+    store: StorageMock,
+    events: EventHandlerMock,
+) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    MvmBuilder::new(store, events)
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_config_path(store_path())
+}
+
+#[test]
+fn test_new_epoch() {
+    let store = StorageMock::new();
+    let events = EventHandlerMock::default();
+    let vm = vm(store.clone(), events.clone());
+    vm.pub_mod(store_module());
+    events.clear();
+    assert_eq!(vm.current_epoch(), 0);
+
+    vm.exec(store_u64_script(addr("0x3"), 1));
+    assert_eq!(vm.current_epoch(), 0);
+    assert!(events.pop().is_none());
+
+    vm.exec(store_u64_script(addr("0x2"), 1));
+    assert_eq!(vm.current_epoch(), 1);
+    assert_eq!(load_epoch_at(&store, CONFIG_ADDRESS).unwrap(), 1);
+    let (address, tag, msg, _) = events.pop().unwrap();
+    assert_eq!(address, CONFIG_ADDRESS);
+    assert_eq!(
+        tag,
+        TypeTag::Struct(StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new(EPOCH_MODULE).unwrap(),
+            name: Identifier::new(NEW_EPOCH_EVENT).unwrap(),
+            type_params: vec![],
+        })
+    );
+    assert_eq!(bcs::from_bytes::<u64>(&msg).unwrap(), 1);
+
+    let vm = self::vm(store, EventHandlerMock::default());
+    assert_eq!(vm.current_epoch(), 1);
+}
+
+#[test]
+fn test_new_epoch_reloads_gas_schedule() {
+    let store = StorageMock::new();
+    let vm = vm(store.clone(), EventHandlerMock::default());
+    vm.pub_mod(store_module());
+
+    let gas_used = |address| {
+        let res = vm.execute_script(
+            gas(),
+            ExecutionContext::new(100, 100),
+            store_u64_script(addr(address), 1),
+            false,
+        );
+        assert_eq!(res.status_code, StatusCode::EXECUTED);
+        res.gas_used
+    };
+    let before = gas_used("0x3");
+
+    let mut config = VmConfig::default();
+    for cost in config.gas_schedule.instruction_table.iter_mut() {
+        cost.instruction_gas = GasUnits::new(cost.instruction_gas.get() * 100);
+    }
+    store_vm_config(&store, &config);
+    assert_eq!(gas_used("0x4"), before);
+
+    vm.exec(store_u64_script(addr("0x2"), 1));
+    assert!(gas_used("0x5") > before);
+}
+
+#[test]
+fn test_external_config_change_starts_new_epoch() {
+    let store = StorageMock::new();
+    let vm = vm(store.clone(), EventHandlerMock::default());
+    vm.pub_mod(store_module());
+
+    let gas_used = |address| {
+        let res = vm.execute_script(
+            gas(),
+            ExecutionContext::new(100, 100),
+            store_u64_script(addr(address), 1),
+            false,
+        );
+        assert_eq!(res.status_code, StatusCode::EXECUTED);
+        res.gas_used
+    };
+    let before = gas_used("0x3");
+    let modules = vm.cache_stats().modules;
+
+    let mut config = VmConfig::default();
+    for cost in config.gas_schedule.instruction_table.iter_mut() {
+        cost.instruction_gas = GasUnits::new(cost.instruction_gas.get() * 100);
+    }
+    store_vm_config(&store, &config);
+    let module = AccessKey::from(&ModuleId::new(
+        CORE_CODE_ADDRESS,
+        Identifier::new("Event").unwrap(),
+    ));
+    vm.on_external_state_change(&[module]);
+    assert_eq!(vm.current_epoch(), 0);
+    assert_eq!(gas_used("0x4"), before);
+
+    vm.on_external_state_change(&[AccessKey::from(&access_path_for_config(CONFIG_ADDRESS))]);
+    assert_eq!(vm.current_epoch(), 1);
+    assert_eq!(load_epoch_at(&store, CONFIG_ADDRESS).unwrap(), 1);
+    assert_eq!(vm.cache_stats().modules, modules);
+    assert!(gas_used("0x5") > before);
+}
+
+#[test]
+fn test_uninitialized_mode() {
+    let store = StorageMock::new();
+    let config_path = AccessPath::new(CONFIG_ADDRESS, store_path().path);
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_config_path(config_path)
+        .with_uninitialized_mode();
+    assert!(!vm.is_initialized());
+
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(CORE_CODE_ADDRESS, 1));
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x3"), 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::VM_HALTED);
+
+    store_vm_config(&store, &VmConfig::default());
+    assert!(!vm.is_initialized());
+    vm.exec(store_u64_script(CONFIG_ADDRESS, 1));
+    assert!(vm.is_initialized());
+    vm.exec(store_u64_script(addr("0x3"), 1));
+
+    let vm = self::vm(store, EventHandlerMock::default());
+    assert!(vm.is_initialized());
+}
+
+#[test]
+fn test_missing_config_is_not_restricted_by_default() {
+    let vm = vm(StorageMock::new(), EventHandlerMock::default());
+    assert!(!vm.is_initialized());
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x3"), 1));
+}
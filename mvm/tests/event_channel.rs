@@ -0,0 +1,94 @@
+use common::assets::*;
+use common::mock::{BankMock, OracleMock, StorageMock, Utils};
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::event_channel::{ChannelEventHandler, EventReceiver, OverflowPolicy};
+use mvm::mvm::Mvm;
+use mvm::Vm;
+use std::time::Duration;
+
+mod common;
+
+fn vm_with_channel(
+    capacity: usize,
+    policy: OverflowPolicy,
+) -> (
+    Mvm<StorageMock, ChannelEventHandler, OracleMock, BankMock>,
+    EventReceiver,
+) {
+    let (handler, receiver) = ChannelEventHandler::new(capacity, policy);
+    let vm = MvmBuilder::new(StorageMock::new(), handler)
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap();
+    vm.pub_mod(event_module());
+    vm.pub_mod(event_proxy_module());
+    while receiver.try_recv().is_some() {}
+    (vm, receiver)
+}
+
+fn emit(vm: &Mvm<StorageMock, ChannelEventHandler, OracleMock, BankMock>) -> StatusCode {
+    vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        emit_event_script(addr("0x1"), 13),
+        false,
+    )
+    .status_code
+}
+
+#[test]
+fn test_overflowing_tx_is_discarded() {
+    let (vm, receiver) = vm_with_channel(3, OverflowPolicy::AbortTx);
+
+    assert_eq!(emit(&vm), StatusCode::EXECUTED);
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        emit_event_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EVENT_CHANNEL_FULL);
+    assert!(res.is_discarded());
+    assert_eq!(receiver.pending(), 2);
+}
+
+#[test]
+fn test_block_is_bounded_by_timeout() {
+    let (vm, receiver) = vm_with_channel(1, OverflowPolicy::Block(Duration::from_millis(10)));
+
+    // The consumer runs on the executing thread: the events which don't fit are dropped.
+    assert_eq!(emit(&vm), StatusCode::EXECUTED);
+    assert_eq!(receiver.pending(), 1);
+}
+
+#[test]
+fn test_abort_tx_on_overflow() {
+    let (vm, receiver) = vm_with_channel(4, OverflowPolicy::AbortTx);
+
+    assert_eq!(emit(&vm), StatusCode::EXECUTED);
+    assert_eq!(emit(&vm), StatusCode::EXECUTED);
+    assert_eq!(receiver.pending(), 4);
+    assert_eq!(emit(&vm), StatusCode::EVENT_CHANNEL_FULL);
+    assert_eq!(receiver.pending(), 4);
+
+    let event = receiver.try_recv().unwrap();
+    assert_eq!(event.address, addr("0x1"));
+    assert_eq!(event.topics.len(), 2);
+    assert!(receiver.try_recv().is_some());
+    assert_eq!(emit(&vm), StatusCode::EXECUTED);
+    assert_eq!(receiver.pending(), 4);
+}
+
+#[test]
+fn test_drop_on_overflow() {
+    let (vm, receiver) = vm_with_channel(1, OverflowPolicy::Drop);
+
+    assert_eq!(emit(&vm), StatusCode::EXECUTED);
+    assert_eq!(receiver.pending(), 1);
+    assert!(receiver.try_recv().is_some());
+    assert!(receiver.try_recv().is_none());
+    assert_eq!(receiver.pending(), 0);
+}
@@ -0,0 +1,221 @@
+use common::bytecode::generic_call_script;
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::data::{ExecutionContext, State};
+use mvm::event_handle::{
+    event_key, load_event_counter, parse_event_key, EVENT_MODULE, NEW_EVENT_HANDLE,
+};
+use mvm::testkit::{addr, gas};
+use mvm::types::{ModuleTx, ScriptTx};
+use mvm::Vm;
+use vm::file_format::{
+    empty_module, Bytecode, CodeUnit, FieldDefinition, FunctionDefinition, FunctionHandle,
+    FunctionHandleIndex, FunctionInstantiation, FunctionInstantiationIndex, IdentifierIndex, Kind,
+    ModuleHandleIndex, Signature, SignatureIndex, SignatureToken, StructDefInstantiation,
+    StructDefInstantiationIndex, StructDefinition, StructDefinitionIndex, StructFieldInformation,
+    StructHandle, StructHandleIndex, TypeSignature,
+};
+
+mod common;
+
+/// Module `0x1::Event` declaring:
+/// ```move
+/// resource struct EventHandle<T> { counter: u64, guid: vector<u8> }
+/// native public fun new_event_handle<T>(account: &signer): EventHandle<T>;
+/// public fun create<T>(account: &signer) { move_to(account, new_event_handle<T>(account)) }
+/// ```
+fn event_module() -> ModuleTx {
+    use SignatureToken::*;
+
+    let mut m = empty_module();
+    m.address_identifiers = vec![CORE_CODE_ADDRESS];
+    m.identifiers = [
+        EVENT_MODULE,
+        "EventHandle",
+        "counter",
+        "guid",
+        NEW_EVENT_HANDLE,
+        "create",
+    ]
+    .iter()
+    .map(|id| Identifier::new(*id).unwrap())
+    .collect();
+    m.struct_handles = vec![StructHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(1),
+        is_nominal_resource: true,
+        type_parameters: vec![Kind::All],
+    }];
+    m.struct_defs = vec![StructDefinition {
+        struct_handle: StructHandleIndex(0),
+        field_information: StructFieldInformation::Declared(vec![
+            FieldDefinition {
+                name: IdentifierIndex(2),
+                signature: TypeSignature(U64),
+            },
+            FieldDefinition {
+                name: IdentifierIndex(3),
+                signature: TypeSignature(Vector(Box::new(U8))),
+            },
+        ]),
+    }];
+    m.signatures = vec![
+        Signature(vec![]),
+        Signature(vec![Reference(Box::new(Signer))]),
+        Signature(vec![StructInstantiation(
+            StructHandleIndex(0),
+            vec![TypeParameter(0)],
+        )]),
+        Signature(vec![TypeParameter(0)]),
+    ];
+    m.function_handles = vec![
+        FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(4),
+            parameters: SignatureIndex(1),
+            return_: SignatureIndex(2),
+            type_parameters: vec![Kind::All],
+        },
+        FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(5),
+            parameters: SignatureIndex(1),
+            return_: SignatureIndex(0),
+            type_parameters: vec![Kind::All],
+        },
+    ];
+    m.function_instantiations = vec![FunctionInstantiation {
+        handle: FunctionHandleIndex(0),
+        type_parameters: SignatureIndex(3),
+    }];
+    m.struct_def_instantiations = vec![StructDefInstantiation {
+        def: StructDefinitionIndex(0),
+        type_parameters: SignatureIndex(3),
+    }];
+    m.function_defs = vec![
+        FunctionDefinition {
+            function: FunctionHandleIndex(0),
+            is_public: true,
+            acquires_global_resources: vec![],
+            code: None,
+        },
+        FunctionDefinition {
+            function: FunctionHandleIndex(1),
+            is_public: true,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code: vec![
+                    Bytecode::CopyLoc(0),
+                    Bytecode::MoveLoc(0),
+                    Bytecode::CallGeneric(FunctionInstantiationIndex(0)),
+                    Bytecode::MoveToGeneric(StructDefInstantiationIndex(0)),
+                    Bytecode::Ret,
+                ],
+            }),
+        },
+    ];
+
+    let mut code = vec![];
+    m.serialize(&mut code).unwrap();
+    ModuleTx::new(code, CORE_CODE_ADDRESS)
+}
+
+/// Script calling `0x1::Event::create<T>` with the sender.
+fn create_handle(sender: AccountAddress, type_: TypeTag) -> ScriptTx {
+    let params = vec![SignatureToken::Reference(Box::new(SignatureToken::Signer))];
+    let tx = generic_call_script(
+        CORE_CODE_ADDRESS,
+        EVENT_MODULE,
+        "create",
+        params,
+        vec![],
+        vec![],
+        vec![type_.clone()],
+    );
+    ScriptTx::new(tx.code().to_vec(), vec![], vec![type_], vec![sender])
+}
+
+fn handle_key<R: RemoteCache>(state: &R, address: &AccountAddress, type_: TypeTag) -> Vec<u8> {
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new(EVENT_MODULE).unwrap(),
+        name: Identifier::new("EventHandle").unwrap(),
+        type_params: vec![type_],
+    };
+    let blob = state.get_resource(address, &tag).unwrap().unwrap();
+    let (counter, guid) = bcs::from_bytes::<(u64, Vec<u8>)>(&blob).unwrap();
+    assert_eq!(counter, 0);
+    guid
+}
+
+#[test]
+fn test_new_event_handle() {
+    let (vm, store, _, oracle, _) = vm();
+    let state = State::new(store.clone(), oracle);
+    let res = vm.publish_module(gas(), event_module(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let sender = addr("0x2");
+    let context = ExecutionContext::new(100, 100);
+    let res = vm.execute_script(
+        gas(),
+        context.clone(),
+        create_handle(sender, TypeTag::U64),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    let res = vm.execute_script(
+        gas(),
+        context.clone(),
+        create_handle(sender, TypeTag::Bool),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    assert_eq!(
+        handle_key(&state, &sender, TypeTag::U64),
+        event_key(&sender, 0)
+    );
+    assert_eq!(
+        handle_key(&state, &sender, TypeTag::Bool),
+        event_key(&sender, 1)
+    );
+    assert_eq!(
+        load_event_counter(&store, CORE_CODE_ADDRESS, &sender).unwrap(),
+        2
+    );
+
+    // The keys of a failed transaction are handed out again.
+    let res = vm.execute_script(
+        gas(),
+        context.clone(),
+        create_handle(sender, TypeTag::U64),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::RESOURCE_ALREADY_EXISTS);
+    let res = vm.execute_script(gas(), context, create_handle(sender, TypeTag::U8), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(
+        handle_key(&state, &sender, TypeTag::U8),
+        event_key(&sender, 2)
+    );
+
+    // Counters are kept per account.
+    assert_eq!(
+        load_event_counter(&store, CORE_CODE_ADDRESS, &addr("0x3")).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_event_key() {
+    let key = event_key(&addr("0x2"), 7);
+    assert_eq!(&key[..8], &7u64.to_le_bytes());
+    assert_eq!(parse_event_key(&key), Some((addr("0x2"), 7)));
+    assert_eq!(parse_event_key(&key[1..]), None);
+}
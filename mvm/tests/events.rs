@@ -0,0 +1,92 @@
+use common::mock::Utils;
+use common::{assets::*, vm};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::value::{MoveStructLayout, MoveTypeLayout};
+use mvm::events::{event_topics, field_topic, indexed_fields, indexed_values, type_topic};
+use vm::file_format::{
+    empty_module, FieldDefinition, IdentifierIndex, ModuleHandleIndex, SignatureToken,
+    StructDefinition, StructFieldInformation, StructHandle, StructHandleIndex, TypeSignature,
+};
+use vm::CompiledModule;
+
+mod common;
+
+#[test]
+fn test_event_topics() {
+    let (vm, _, event, _, _) = vm();
+    vm.pub_mod(event_module());
+    vm.pub_mod(event_proxy_module());
+    event.clear();
+
+    vm.exec(emit_event_script(addr("0x1"), 13));
+
+    let tag = TypeTag::Struct(StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("EventProxy").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    });
+    let topics = event.topics.borrow_mut().remove(0);
+    assert_eq!(topics, event_topics(&addr("0x1"), &tag, &[]));
+    assert_eq!(topics.len(), 2);
+    assert_eq!(topics[0], type_topic(&tag));
+    assert_ne!(
+        topics[1],
+        event_topics(&addr("0x2"), &tag, &[])[1],
+        "Address topic depends on the event address"
+    );
+}
+
+/// Module declaring `struct Transfer { indexed_from: address, amount: u64 }`.
+fn transfer_module() -> CompiledModule {
+    let mut m = empty_module();
+    m.identifiers = vec![
+        Identifier::new("Token").unwrap(),
+        Identifier::new("Transfer").unwrap(),
+        Identifier::new("indexed_from").unwrap(),
+        Identifier::new("amount").unwrap(),
+    ];
+    m.struct_handles = vec![StructHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(1),
+        is_nominal_resource: false,
+        type_parameters: vec![],
+    }];
+    m.struct_defs = vec![StructDefinition {
+        struct_handle: StructHandleIndex(0),
+        field_information: StructFieldInformation::Declared(vec![
+            FieldDefinition {
+                name: IdentifierIndex(2),
+                signature: TypeSignature(SignatureToken::Address),
+            },
+            FieldDefinition {
+                name: IdentifierIndex(3),
+                signature: TypeSignature(SignatureToken::U64),
+            },
+        ]),
+    }];
+    m.freeze().unwrap()
+}
+
+#[test]
+fn test_indexed_fields() {
+    let module = transfer_module();
+    assert_eq!(indexed_fields(&module, "Transfer"), vec![0]);
+    assert!(indexed_fields(&module, "Unknown").is_empty());
+
+    let from = AccountAddress::random();
+    let message = bcs::to_bytes(&(from, 100u64)).unwrap();
+    let layout = MoveTypeLayout::Struct(MoveStructLayout::new(vec![
+        MoveTypeLayout::Address,
+        MoveTypeLayout::U64,
+    ]));
+    let values = indexed_values(&message, &layout, &[0]);
+    assert_eq!(values, vec![bcs::to_bytes(&from).unwrap()]);
+
+    let tag = TypeTag::Bool;
+    let topics = event_topics(&from, &tag, &values);
+    assert_eq!(topics.len(), 3);
+    assert_eq!(topics[2], field_topic(&bcs::to_bytes(&from).unwrap()));
+}
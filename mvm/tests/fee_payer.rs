@@ -0,0 +1,169 @@
+use common::assets::*;
+use common::vm;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_types::natives::balance::BalanceOperation;
+use mvm::data::{fee_ticker, BalanceAccess, ExecutionContext, FEE_TICKER};
+use mvm::testkit::mock::Utils;
+use mvm::testkit::VmBuilder;
+use mvm::types::{Gas, GasBounds};
+use mvm::Vm;
+
+mod common;
+
+#[test]
+fn test_fee_payer() {
+    let sponsor = addr("0x2");
+    let (vm, _, _, _, bank) = VmBuilder::new()
+        .with_module(store_module())
+        .with_balance(sponsor, FEE_TICKER, 100_000)
+        .build();
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13).with_fee_payer(sponsor),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(res.gas_used > 0);
+    assert_eq!(
+        bank.get_balance(&sponsor, &fee_ticker()),
+        Some(100_000 - res.gas_used as u128)
+    );
+    // The fee is reported after the balance changes of the transaction.
+    let fee = res.balance_changes.last().unwrap();
+    assert_eq!(fee.wallet_id.address, sponsor);
+    assert_eq!(
+        fee.operation,
+        BalanceOperation::Deposit(res.gas_used as u128)
+    );
+    assert_eq!(bank.get_balance(&addr("0x1"), &fee_ticker()), None);
+
+    // Dry run does not charge the fee.
+    let balance = bank.get_balance(&sponsor, &fee_ticker());
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13).with_fee_payer(sponsor),
+        true,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(bank.get_balance(&sponsor, &fee_ticker()), balance);
+}
+
+#[test]
+fn test_fee_payer_insufficient_balance() {
+    let sponsor = addr("0x2");
+    let (vm, _, _, _, bank) = VmBuilder::new()
+        .with_module(store_module())
+        .with_balance(sponsor, FEE_TICKER, 10)
+        .build();
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13).with_fee_payer(sponsor),
+        false,
+    );
+    assert_eq!(
+        res.status_code,
+        StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE
+    );
+    assert_eq!(res.gas_used, 0);
+    assert_eq!(bank.get_balance(&sponsor, &fee_ticker()), Some(10));
+}
+
+#[test]
+fn test_fee_is_reserved() {
+    let (vm, _, _, _, bank) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+    vm.exec(reg_coin_script(
+        TypeTag::Struct(StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("PONT").unwrap(),
+            name: Identifier::new("T").unwrap(),
+            type_params: vec![],
+        }),
+        "PONT",
+        2,
+    ));
+
+    // The max fee is hidden from the transaction, so the transfer can't spend it.
+    let alice = addr("0x3");
+    let bob = addr("0x4");
+    bank.set_balance(&alice, FEE_TICKER, 10_005);
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        test_transfer_script(alice, bob, 10).with_fee_payer(alice),
+        false,
+    );
+    assert_ne!(res.status_code, StatusCode::EXECUTED);
+    assert!(!res.is_discarded());
+    assert!(res.gas_used > 0);
+    assert_eq!(
+        bank.get_balance(&alice, &fee_ticker()),
+        Some(10_005 - res.gas_used as u128)
+    );
+    assert_eq!(vm.balance(&bob, &fee_ticker()).unwrap().wrapped, 0);
+
+    bank.set_balance(&alice, FEE_TICKER, 10_005);
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        test_transfer_script(alice, bob, 5).with_fee_payer(alice),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+#[test]
+fn test_fee_is_paid_in_gas_currency() {
+    let sponsor = addr("0x2");
+    let (vm, _, _, _, bank) = VmBuilder::new()
+        .with_module(store_module())
+        .with_balance(sponsor, "USDT", 100_000)
+        .build();
+    let usdt_gas = || {
+        Gas::builder()
+            .max_gas_amount(10_000)
+            .gas_unit_price(1)
+            .gas_currency(ticker("USDT"))
+            .build(&GasBounds::default())
+            .unwrap()
+    };
+
+    // Only the fee currency is accepted until the currencies are registered.
+    let res = vm.execute_script(
+        usdt_gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13).with_fee_payer(sponsor),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::CURRENCY_INFO_DOES_NOT_EXIST);
+    assert!(res.is_discarded());
+    assert_eq!(bank.get_balance(&sponsor, &ticker("USDT")), Some(100_000));
+
+    vm.register_currency(fee_ticker()).unwrap();
+    vm.register_currency(ticker("USDT")).unwrap();
+    let res = vm.execute_script(
+        usdt_gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13).with_fee_payer(sponsor),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(res.gas_used > 0);
+    assert_eq!(
+        bank.get_balance(&sponsor, &ticker("USDT")),
+        Some(100_000 - res.gas_used as u128)
+    );
+    assert_eq!(bank.get_balance(&sponsor, &fee_ticker()), None);
+}
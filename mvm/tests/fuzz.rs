@@ -0,0 +1,44 @@
+use common::assets::*;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+use mvm::fuzz::{fuzz_bcs_roundtrip, fuzz_execute_script, fuzz_publish_module};
+use mvm::types::ScriptArg;
+use parity_scale_codec::Encode;
+
+mod common;
+
+#[test]
+fn test_fuzz_publish_module() {
+    fuzz_publish_module(&[]);
+    fuzz_publish_module(&[0xa1, 0x1c, 0xeb, 0x0b, 0xff, 0xff]);
+    fuzz_publish_module(store_module().code());
+    fuzz_publish_module(&store_module().encode());
+
+    let mut truncated = store_module().code().to_vec();
+    truncated.truncate(truncated.len() / 2);
+    fuzz_publish_module(&truncated);
+}
+
+#[test]
+fn test_fuzz_execute_script() {
+    fuzz_execute_script(&[]);
+    fuzz_execute_script(&[0xa1, 0x1c, 0xeb, 0x0b, 0x02, 0x00]);
+    fuzz_execute_script(error_script(addr("0x110")).code());
+}
+
+#[test]
+fn test_fuzz_bcs_roundtrip() {
+    fuzz_bcs_roundtrip(&[]);
+    fuzz_bcs_roundtrip(&[0xff; 64]);
+    fuzz_bcs_roundtrip(&bcs::to_bytes(&ScriptArg::VectorU64(vec![1, 2, 3])).unwrap());
+    fuzz_bcs_roundtrip(
+        &bcs::to_bytes(&TypeTag::Struct(StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("Coins").unwrap(),
+            name: Identifier::new("BTC").unwrap(),
+            type_params: vec![TypeTag::U64],
+        }))
+        .unwrap(),
+    );
+    fuzz_bcs_roundtrip(&store_module().encode());
+}
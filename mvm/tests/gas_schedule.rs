@@ -0,0 +1,104 @@
+use move_core_types::gas_schedule::{GasAlgebra, GasCost};
+use mvm::gas_schedule::{cost_table, GasSchedule, GasScheduleBuilder, NamedCost};
+
+#[test]
+fn test_default_schedule_round_trip() {
+    let table = cost_table();
+    let schedule = GasSchedule::from(&table);
+    assert_eq!(
+        schedule.instructions["MoveTo"],
+        NamedCost {
+            instruction: 825,
+            memory: 1
+        }
+    );
+    assert_eq!(
+        schedule.natives["sha3_256"],
+        NamedCost {
+            instruction: 64,
+            memory: 1
+        }
+    );
+
+    let blob = bcs::to_bytes(&schedule).unwrap();
+    let schedule: GasSchedule = bcs::from_bytes(&blob).unwrap();
+    assert_eq!(schedule.into_cost_table().unwrap(), table);
+    assert_eq!(GasScheduleBuilder::new().build().unwrap(), table);
+}
+
+#[test]
+fn test_named_parameters() {
+    let table = GasScheduleBuilder::new()
+        .instruction("Add", GasCost::new(100, 2))
+        .unwrap()
+        .native("sha3_256", GasCost::new(200, 3))
+        .unwrap()
+        .intrinsic_gas_per_byte(11)
+        .write_byte_cost(12)
+        .build()
+        .unwrap();
+
+    let default = cost_table();
+    // Add is the 0x16 instruction.
+    assert_eq!(table.instruction_table[0x15], GasCost::new(100, 2));
+    assert_eq!(table.native_table[1], GasCost::new(200, 3));
+    assert_eq!(table.gas_constants.intrinsic_gas_per_byte.get(), 11);
+    assert_eq!(
+        table.gas_constants.global_memory_per_byte_write_cost.get(),
+        12
+    );
+    assert_eq!(
+        table.instruction_table[0x16],
+        default.instruction_table[0x16]
+    );
+    assert_eq!(table.native_table[0], default.native_table[0]);
+}
+
+#[test]
+fn test_unknown_and_missing_names() {
+    assert!(GasScheduleBuilder::new()
+        .instruction("Jump", GasCost::new(1, 1))
+        .is_err());
+    assert!(GasScheduleBuilder::new()
+        .native("sha1", GasCost::new(1, 1))
+        .is_err());
+
+    let mut schedule = GasSchedule::from(&cost_table());
+    schedule.instructions.remove("Pop");
+    assert!(schedule.clone().into_cost_table().is_err());
+
+    let mut schedule = GasSchedule::from(&cost_table());
+    schedule.natives.insert(
+        "sha1".to_owned(),
+        NamedCost {
+            instruction: 1,
+            memory: 1,
+        },
+    );
+    assert!(schedule.into_cost_table().is_err());
+}
+
+#[test]
+fn test_json_round_trip() {
+    let schedule = GasSchedule::from(&cost_table());
+    assert!(schedule.validate().is_ok());
+
+    let json = schedule.to_json().unwrap();
+    assert!(json.contains("\"MoveTo\": {\n"));
+    assert!(json.contains("\"sha3_256\": {\n"));
+    assert_eq!(GasSchedule::from_json(&json).unwrap(), schedule);
+
+    let changed = json.replacen("\"instruction\": 825", "\"instruction\": 900", 1);
+    let changed = GasSchedule::from_json(&changed).unwrap();
+    assert_eq!(changed.instructions["MoveTo"].instruction, 900);
+}
+
+#[test]
+fn test_invalid_json_schedule() {
+    let mut schedule = GasSchedule::from(&cost_table());
+    schedule.natives.remove("emit_event");
+    assert!(schedule.validate().is_err());
+    assert!(GasSchedule::from_json(&schedule.to_json().unwrap()).is_err());
+
+    assert!(GasSchedule::from_json("{}").is_err());
+}
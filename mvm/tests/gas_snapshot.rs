@@ -0,0 +1,60 @@
+use mvm::bench::gas_snapshot::{canonical_snapshot, GasChange, GasSnapshot, UPDATE_GAS_SNAPSHOTS};
+
+#[test]
+fn test_canonical_gas_snapshot() {
+    canonical_snapshot()
+        .unwrap()
+        .check_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/snapshots/gas.txt"
+        ))
+        .unwrap();
+}
+
+#[test]
+fn test_gas_snapshot_diff() {
+    let expected =
+        GasSnapshot::parse("# gas used\ntransfer 120\nloop_10 30\n\npublish 7\n").unwrap();
+    assert_eq!(expected.gas_used("transfer"), Some(120));
+    assert_eq!(GasSnapshot::parse(&expected.to_string()).unwrap(), expected);
+
+    let mut actual = GasSnapshot::new();
+    actual.record("transfer", 120);
+    actual.record("loop_10", 31);
+    actual.record("loop_1000", 2900);
+    assert_eq!(
+        actual.diff(&expected),
+        vec![
+            GasChange {
+                name: "loop_10".to_string(),
+                expected: Some(30),
+                actual: Some(31),
+            },
+            GasChange {
+                name: "loop_1000".to_string(),
+                expected: None,
+                actual: Some(2900),
+            },
+            GasChange {
+                name: "publish".to_string(),
+                expected: Some(7),
+                actual: None,
+            },
+        ]
+    );
+
+    assert!(GasSnapshot::parse("transfer").is_err());
+    assert!(GasSnapshot::parse("transfer 12 13").is_err());
+    assert!(GasSnapshot::parse("transfer -1").is_err());
+}
+
+#[test]
+fn test_missing_golden_file() {
+    if std::env::var_os(UPDATE_GAS_SNAPSHOTS).is_some() {
+        return;
+    }
+    let path = std::env::temp_dir().join("mvm_missing_gas_snapshot.txt");
+    let _ = std::fs::remove_file(&path);
+    assert!(GasSnapshot::new().check_file(&path).is_err());
+    assert!(!path.exists());
+}
@@ -0,0 +1,211 @@
+use common::assets::*;
+use common::bytecode::functions_module;
+use common::mock::{BankMock, OracleMock, StorageMock};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::builder::MvmBuilder;
+use mvm::data::{ExecutionContext, State};
+use mvm::event_channel::{ChannelEventHandler, OverflowPolicy};
+use mvm::governance::GovernanceOrigin;
+use mvm::hash::{module_hash, Digest};
+use mvm::publish::{forced_upgrade_tag, ForcedUpgradeEvent};
+use mvm::testkit::mock::Utils;
+use mvm::testkit::{vm, MockVm};
+use mvm::types::{ModuleTx, ScriptArg, ScriptTx};
+use mvm::vm_config::CONFIG_ADDRESS;
+use mvm::Vm;
+
+mod common;
+
+/// `store_u64` script without senders.
+fn governance_script(val: u64) -> ScriptTx {
+    let tx = store_u64_script(addr("0x1"), val);
+    ScriptTx::new(
+        tx.code().to_vec(),
+        vec![ScriptArg::U64(val)],
+        vec![],
+        vec![],
+    )
+}
+
+fn store_u64_tag() -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    }
+}
+
+#[test]
+fn test_governance_script_signed_by_root() {
+    let (vm, store, _, oracle, _) = vm();
+    let vm = vm.with_governance_origin(
+        |tx: &ScriptTx| {
+            if tx.args().len() == 1 {
+                Ok(())
+            } else {
+                Err(1)
+            }
+        },
+    );
+    vm.pub_mod(store_module());
+
+    let res = vm.execute_governance_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        governance_script(13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let blob = State::new(store, oracle)
+        .get_resource(&CONFIG_ADDRESS, &store_u64_tag())
+        .unwrap()
+        .unwrap();
+    assert_eq!(bcs::from_bytes::<StoreU64>(&blob).unwrap().val, 13);
+}
+
+#[test]
+fn test_governance_script_requires_origin() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+
+    let res = vm.execute_governance_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        governance_script(13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::NO_ACCOUNT_ROLE);
+    assert_eq!(res.sub_status, None);
+
+    let vm = vm.with_governance_origin(|_: &ScriptTx| Err(42));
+    let res = vm.execute_governance_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        governance_script(13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::NO_ACCOUNT_ROLE);
+    assert_eq!(res.sub_status, Some(42));
+    assert_eq!(res.gas_used, 0);
+}
+
+#[test]
+fn test_governance_co_signers_are_authenticated() {
+    let (vm, _, _, _, _) = vm();
+    let vm = vm.with_governance_origin(|_: &ScriptTx| Ok(()));
+    vm.pub_mod(store_module());
+    let exec = |vm: &MockVm| {
+        vm.execute_governance_script(
+            gas(),
+            ExecutionContext::new(100, 100),
+            store_u64_script(addr("0x2"), 13),
+            false,
+        )
+    };
+
+    let res = exec(&vm);
+    assert_eq!(res.status_code, StatusCode::INVALID_SIGNATURE);
+    assert_eq!(res.sub_status, None);
+    assert_eq!(res.gas_used, 0);
+
+    let vm = vm.with_authenticator(|_: &Digest, _: &[AccountAddress], _: &[u8]| Err(42));
+    let res = exec(&vm);
+    assert_eq!(res.status_code, StatusCode::INVALID_SIGNATURE);
+    assert_eq!(res.sub_status, Some(42));
+}
+
+/// Governance approving forced publishes of the modules without the `Abort` module name.
+struct Council;
+
+impl GovernanceOrigin for Council {
+    fn ensure_governance(&self, _tx: &ScriptTx) -> Result<(), u64> {
+        Ok(())
+    }
+
+    fn ensure_force_publish(&self, module: &ModuleTx) -> Result<(), u64> {
+        if module.code() == abort_module().code() {
+            Err(7)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_force_publish_module() {
+    let (vm, _, events, _, _) = vm();
+    let vm = vm.with_governance_origin(Council);
+    let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+    let old = functions_module(CORE_CODE_ADDRESS, "Store", &["store"]);
+    let new = functions_module(CORE_CODE_ADDRESS, "Store", &["load"]);
+    vm.pub_mod(old.clone().immutable());
+
+    let res = vm.publish_module(gas(), new.clone(), false);
+    assert_eq!(res.status_code, StatusCode::IMMUTABLE_MODULE_UPDATE);
+    events.clear();
+
+    let res = vm.force_publish_module(gas(), new.clone(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.module_version(&id).unwrap(), 2);
+
+    let (address, tag, msg, _) = events.pop().unwrap();
+    assert_eq!(address, CORE_CODE_ADDRESS);
+    assert_eq!(tag, forced_upgrade_tag(CORE_CODE_ADDRESS));
+    assert_eq!(
+        bcs::from_bytes::<ForcedUpgradeEvent>(&msg).unwrap(),
+        ForcedUpgradeEvent {
+            id,
+            old_hash: Some(module_hash(old.code())),
+            new_hash: module_hash(new.code()),
+        }
+    );
+}
+
+#[test]
+fn test_force_publish_requires_approval() {
+    let (vm, _, events, _, _) = vm();
+    let res = vm.force_publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::NO_ACCOUNT_ROLE);
+
+    let vm = vm.with_governance_origin(|_: &ScriptTx| Ok(()));
+    let res = vm.force_publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::NO_ACCOUNT_ROLE);
+    assert_eq!(res.sub_status, Some(0));
+
+    let vm = vm.with_governance_origin(Council);
+    let res = vm.force_publish_module(gas(), abort_module(), false);
+    assert_eq!(res.status_code, StatusCode::NO_ACCOUNT_ROLE);
+    assert_eq!(res.sub_status, Some(7));
+    assert!(events.pop().is_none());
+
+    let res = vm.force_publish_module(gas(), store_module(), true);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(events.pop().is_none());
+}
+
+#[test]
+fn test_forced_upgrade_event_is_emitted_with_the_effects() {
+    let (handler, receiver) = ChannelEventHandler::new(1, OverflowPolicy::AbortTx);
+    let vm = MvmBuilder::new(StorageMock::new(), handler)
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_governance_origin(Council);
+    let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+    vm.pub_mod(functions_module(CORE_CODE_ADDRESS, "Store", &["store"]));
+    while receiver.try_recv().is_some() {}
+
+    // The module published and the forced upgrade events don't fit into the channel.
+    let new = functions_module(CORE_CODE_ADDRESS, "Store", &["load"]);
+    let res = vm.force_publish_module(gas(), new, false);
+    assert_eq!(res.status_code, StatusCode::EVENT_CHANNEL_FULL);
+    assert_eq!(vm.module_version(&id).unwrap(), 1);
+    assert!(receiver.try_recv().is_none());
+}
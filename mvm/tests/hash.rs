@@ -0,0 +1,24 @@
+use common::assets::*;
+use mvm::hash::{module_hash, script_hash};
+
+mod common;
+
+#[test]
+fn test_canonical_digest() {
+    assert_eq!(
+        hex::encode(script_hash(&[])),
+        "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+    );
+    assert_eq!(module_hash(b"abc"), script_hash(b"abc"));
+    assert_eq!(
+        hex::encode(module_hash(b"abc")),
+        "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+    );
+}
+
+#[test]
+fn test_tx_hash() {
+    let module = store_module();
+    assert_eq!(module.hash(), module_hash(module.code()));
+    assert_ne!(module.hash(), event_module().hash());
+}
@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex};
+
+use common::assets::*;
+use common::bytecode::{native_call_script, native_module};
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::move_vm::SharedCache;
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::host::{HOST_CALL, HOST_MODULE, UNKNOWN_HOST_HANDLER};
+use mvm::mvm::Mvm;
+use mvm::types::{ModuleTx, ScriptArg, ScriptTx};
+use mvm::Vm;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+fn params() -> Vec<SignatureToken> {
+    vec![
+        SignatureToken::U64,
+        SignatureToken::Vector(Box::new(SignatureToken::U8)),
+    ]
+}
+
+fn returns() -> Vec<SignatureToken> {
+    vec![SignatureToken::Vector(Box::new(SignatureToken::U8))]
+}
+
+fn host_module() -> ModuleTx {
+    native_module(
+        CORE_CODE_ADDRESS,
+        HOST_MODULE,
+        HOST_CALL,
+        params(),
+        returns(),
+    )
+}
+
+fn host_call_script(id: u64, payload: Vec<u8>) -> ScriptTx {
+    native_call_script(
+        CORE_CODE_ADDRESS,
+        HOST_MODULE,
+        HOST_CALL,
+        params(),
+        returns(),
+        vec![ScriptArg::U64(id), ScriptArg::VectorU8(payload)],
+    )
+}
+
+fn vm() -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    MvmBuilder::new(StorageMock::new(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_host_call() {
+    let payloads = Arc::new(Mutex::new(vec![]));
+    let calls = payloads.clone();
+    let vm = vm()
+        .with_host_handler(7, move |payload: &[u8]| -> Result<Vec<u8>, u64> {
+            calls.lock().unwrap().push(payload.to_vec());
+            Ok(payload.iter().rev().cloned().collect())
+        })
+        .with_host_handler(8, |_: &[u8]| -> Result<Vec<u8>, u64> { Err(42) });
+    assert_eq!(
+        vm.publish_module(gas(), host_module(), false).status_code,
+        StatusCode::EXECUTED
+    );
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        host_call_script(7, vec![1, 2, 3]),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(*payloads.lock().unwrap(), vec![vec![1, 2, 3]]);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        host_call_script(8, vec![]),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.sub_status, Some(42));
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        host_call_script(9, vec![]),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.sub_status, Some(UNKNOWN_HOST_HANDLER));
+}
+
+#[test]
+fn test_host_call_without_handlers() {
+    let vm = vm();
+    assert_eq!(
+        vm.publish_module(gas(), host_module(), false).status_code,
+        StatusCode::MISSING_DEPENDENCY
+    );
+}
+
+#[test]
+fn test_host_call_with_shared_cache() {
+    let cache = Arc::new(SharedCache::new());
+    let store = StorageMock::new();
+    let new_vm = |reply: u8| {
+        MvmBuilder::new(store.clone(), EventHandlerMock::default())
+            .with_oracle(OracleMock::default())
+            .with_bank(BankMock::default())
+            .build()
+            .unwrap()
+            .with_host_handler(7, move |_: &[u8]| -> Result<Vec<u8>, u64> {
+                Err(reply as u64)
+            })
+            .with_shared_cache(cache.clone())
+    };
+    let first = new_vm(1);
+    let second = new_vm(2);
+    assert_eq!(
+        first
+            .publish_module(gas(), host_module(), false)
+            .status_code,
+        StatusCode::EXECUTED
+    );
+
+    for (vm, reply) in [(&first, 1), (&second, 2), (&first, 1)].iter() {
+        let res = vm.execute_script(
+            gas(),
+            ExecutionContext::new(100, 100),
+            host_call_script(7, vec![]),
+            false,
+        );
+        assert_eq!(res.status_code, StatusCode::ABORTED);
+        assert_eq!(res.sub_status, Some(*reply));
+    }
+    assert_eq!(first.cache_stats(), second.cache_stats());
+}
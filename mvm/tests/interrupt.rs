@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use move_core_types::vm_status::StatusCode;
+use mvm::bench::workloads::{gas, loop_tx, vm};
+use mvm::data::ExecutionContext;
+use mvm::Vm;
+
+#[test]
+fn test_instruction_limit() {
+    let (vm, _, _) = vm();
+    let vm = vm.with_instruction_limit(1_000);
+
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 100), loop_tx(10), true);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        loop_tx(10_000),
+        true,
+    );
+    assert_eq!(res.status_code, StatusCode::INSTRUCTION_LIMIT_EXCEEDED);
+    assert!(!res.is_discarded());
+    assert!(res.gas_used > 0);
+}
+
+#[test]
+fn test_interrupt() {
+    let stop = Arc::new(AtomicBool::new(false));
+    let (vm, _, _) = vm();
+    let vm = vm.with_interrupt({
+        let stop = stop.clone();
+        move || stop.load(Ordering::Relaxed)
+    });
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        loop_tx(10_000),
+        true,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    stop.store(true, Ordering::Relaxed);
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        loop_tx(10_000),
+        true,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTION_INTERRUPTED);
+    assert!(res.is_discarded());
+    assert_eq!(res.gas_used, 0);
+
+    // Short scripts finish before the interrupt is checked.
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 100), loop_tx(10), true);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
@@ -0,0 +1,88 @@
+use common::assets::*;
+use common::mock::{EventHandlerMock, Utils};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::builder::MvmBuilder;
+use mvm::data::{AccessKey, MemoryStorage, NoOracle, State, Storage};
+use mvm::key_codec::{Blake2_128Concat, CodecStorage, KeyCodec, RawKeys, BLAKE2_128_LENGTH};
+
+mod common;
+
+#[test]
+fn test_blake2_128_concat() {
+    let encoded = Blake2_128Concat.encode(b"key");
+    assert_eq!(encoded.len(), BLAKE2_128_LENGTH + 3);
+    assert_eq!(&encoded[BLAKE2_128_LENGTH..], b"key");
+    assert_ne!(encoded, Blake2_128Concat.encode(b"kez"));
+    assert_eq!(Blake2_128Concat.decode(&encoded), Some(b"key".to_vec()));
+
+    let mut corrupted = encoded;
+    corrupted[0] ^= 1;
+    assert_eq!(Blake2_128Concat.decode(&corrupted), None);
+    assert_eq!(Blake2_128Concat.decode(b"key"), None);
+
+    assert_eq!(RawKeys.encode(b"key"), b"key".to_vec());
+    assert_eq!(RawKeys.decode(b"key"), Some(b"key".to_vec()));
+    assert!(RawKeys.preserves_order());
+    assert!(!Blake2_128Concat.preserves_order());
+}
+
+#[test]
+fn test_codec_storage() {
+    let store = MemoryStorage::new();
+    store.insert(b"foreign", b"0");
+    let codec_store = CodecStorage::new(store.clone(), Blake2_128Concat);
+    codec_store.insert(b"a", b"1");
+    codec_store.insert(b"b", b"2");
+
+    assert_eq!(codec_store.get(b"a"), Some(b"1".to_vec()));
+    assert_eq!(
+        store.get(&Blake2_128Concat.encode(b"b")),
+        Some(b"2".to_vec())
+    );
+    assert_eq!(store.get(b"a"), None);
+
+    // Iteration follows the order of the encoded keys and skips the foreign ones.
+    let mut keys = vec![];
+    let mut key = codec_store.next_key(b"");
+    while let Some(next) = key {
+        key = codec_store.next_key(&next);
+        keys.push(next);
+    }
+    keys.sort();
+    assert_eq!(keys, vec![b"a".to_vec(), b"b".to_vec()]);
+    assert!(!codec_store.supports_next_key());
+    assert!(CodecStorage::new(store.clone(), RawKeys).supports_next_key());
+
+    codec_store.remove(b"a");
+    assert_eq!(codec_store.get(b"a"), None);
+}
+
+#[test]
+fn test_vm_with_key_codec() {
+    let store = MemoryStorage::new();
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_key_codec(Blake2_128Concat)
+        .build()
+        .unwrap();
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 13));
+
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    let key = AccessKey::from((&addr("0x1"), &tag));
+    assert!(store.get(key.as_ref()).is_none());
+    assert!(store.get(&Blake2_128Concat.encode(key.as_ref())).is_some());
+
+    let blob = State::new(store, NoOracle)
+        .with_key_codec(Blake2_128Concat)
+        .get_resource(&addr("0x1"), &tag)
+        .unwrap()
+        .unwrap();
+    assert_eq!(bcs::from_bytes::<StoreU64>(&blob).unwrap().val, 13);
+}
@@ -0,0 +1,87 @@
+use common::assets::*;
+use common::mock::Utils;
+use common::vm;
+use move_core_types::vm_status::StatusCode;
+use mvm::data::ExecutionContext;
+use mvm::types::BatchTx;
+use mvm::{BatchVm, Vm};
+
+mod common;
+
+#[test]
+fn test_sequence_numbers() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+    let sender = addr("0x1");
+
+    let script = |lane, sequence_number| {
+        store_u64_script(sender, 1).with_sequence_number(lane, sequence_number)
+    };
+    let res = vm.execute_script(gas(), ExecutionContext::new(1, 1), script(0, 1), false);
+    assert_eq!(res.status_code, StatusCode::SEQUENCE_NUMBER_TOO_NEW);
+    assert_eq!(vm.sequence_number(&sender, 0).unwrap(), 0);
+
+    let res = vm.execute_script(gas(), ExecutionContext::new(1, 1), script(0, 0), true);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.sequence_number(&sender, 0).unwrap(), 0);
+
+    let res = vm.execute_script(gas(), ExecutionContext::new(1, 1), script(0, 0), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.sequence_number(&sender, 0).unwrap(), 1);
+
+    let res = vm.execute_script(gas(), ExecutionContext::new(1, 1), script(0, 0), false);
+    assert_eq!(res.status_code, StatusCode::SEQUENCE_NUMBER_TOO_OLD);
+    assert_eq!(res.gas_used, 0);
+
+    // Lanes are independent.
+    let res = vm.execute_script(gas(), ExecutionContext::new(1, 1), script(7, 0), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.sequence_number(&sender, 7).unwrap(), 1);
+    assert_eq!(vm.sequence_number(&sender, 0).unwrap(), 1);
+
+    // Scripts without sequence numbers are not ordered.
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(1, 1),
+        store_u64_script(sender, 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.sequence_number(&sender, 0).unwrap(), 1);
+}
+
+#[test]
+fn test_aborted_script_uses_sequence_number() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(abort_module());
+    let sender = addr("0x1");
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(1, 1),
+        error_script(sender).with_sequence_number(3, 0),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(vm.sequence_number(&sender, 3).unwrap(), 1);
+}
+
+#[test]
+fn test_batch_sequence_numbers() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+    let sender = addr("0x1");
+    let batch = |sequence_number| {
+        BatchTx::new(vec![store_u64_call(1)], vec![sender]).with_sequence_number(1, sequence_number)
+    };
+
+    let res = vm.execute_batch(gas(), ExecutionContext::new(1, 1), batch(1), false);
+    assert_eq!(res.status_code, StatusCode::SEQUENCE_NUMBER_TOO_NEW);
+
+    let res = vm.execute_batch(gas(), ExecutionContext::new(1, 1), batch(0), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.sequence_number(&sender, 1).unwrap(), 1);
+
+    let res = vm.execute_batch(gas(), ExecutionContext::new(1, 1), batch(0), false);
+    assert_eq!(res.status_code, StatusCode::SEQUENCE_NUMBER_TOO_OLD);
+}
@@ -0,0 +1,269 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use move_core_types::language_storage::TypeTag;
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::mvm::Mvm;
+use mvm::types::{ScriptArg, ScriptTx, TxLimits};
+use mvm::vm_config::loader::store_vm_config;
+use mvm::vm_config::VmConfig;
+use mvm::Vm;
+
+mod common;
+
+fn vm_with_config(config: VmConfig) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    let store = StorageMock::new();
+    store_vm_config(&store, &config);
+    MvmBuilder::new(store, EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_call_depth_limit() {
+    let vm = vm_with_config(VmConfig {
+        max_call_depth: 0,
+        ..VmConfig::default()
+    });
+    vm.pub_mod(store_module());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::CALL_STACK_OVERFLOW);
+}
+
+#[test]
+fn test_type_depth_limit() {
+    let vm = vm_with_config(VmConfig {
+        max_type_depth: 2,
+        ..VmConfig::default()
+    });
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        reg_coin_script(
+            TypeTag::Vector(Box::new(TypeTag::Vector(Box::new(TypeTag::U8)))),
+            "deep",
+            2,
+        ),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::VM_MAX_TYPE_DEPTH_REACHED);
+}
+
+fn emit_events(config: VmConfig) -> StatusCode {
+    let vm = vm_with_config(config);
+    vm.pub_mod(event_module());
+    vm.pub_mod(event_proxy_module());
+
+    // Emits two `U64` events of 8 bytes each.
+    vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        emit_event_script(addr("0x1"), 13),
+        false,
+    )
+    .status_code
+}
+
+#[test]
+fn test_event_limits() {
+    assert_eq!(
+        emit_events(VmConfig {
+            max_events: 1,
+            ..VmConfig::default()
+        }),
+        StatusCode::EVENT_LIMIT_EXCEEDED
+    );
+    assert_eq!(
+        emit_events(VmConfig {
+            max_event_bytes: 15,
+            ..VmConfig::default()
+        }),
+        StatusCode::EVENT_LIMIT_EXCEEDED
+    );
+    assert_eq!(
+        emit_events(VmConfig {
+            max_events: 2,
+            max_event_bytes: 16,
+            ..VmConfig::default()
+        }),
+        StatusCode::EXECUTED
+    );
+}
+
+#[test]
+fn test_module_size_limit() {
+    let module = store_module();
+    let vm = vm_with_config(VmConfig {
+        max_module_size: module.code().len() as u32 - 1,
+        ..VmConfig::default()
+    });
+    let res = vm.publish_module(gas(), module, false);
+    assert_eq!(res.status_code, StatusCode::MODULE_SIZE_LIMIT_EXCEEDED);
+}
+
+#[test]
+fn test_module_definition_limits() {
+    let vm = vm_with_config(VmConfig {
+        max_functions: 0,
+        ..VmConfig::default()
+    });
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::TOO_MANY_FUNCTIONS);
+
+    let vm = vm_with_config(VmConfig {
+        max_structs: 0,
+        ..VmConfig::default()
+    });
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::TOO_MANY_STRUCTS);
+
+    let vm = vm_with_config(VmConfig {
+        max_identifier_length: 2,
+        ..VmConfig::default()
+    });
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::IDENTIFIER_TOO_LONG);
+
+    let vm = vm_with_config(VmConfig::default());
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+#[test]
+fn test_verification_gas() {
+    let free = vm_with_config(VmConfig {
+        verify_per_basic_block: 0,
+        verify_per_edge: 0,
+        verify_per_local: 0,
+        ..VmConfig::default()
+    });
+    let free = free.publish_module(gas(), store_module(), false);
+    assert_eq!(free.status_code, StatusCode::EXECUTED);
+
+    let metered = vm_with_config(VmConfig {
+        verify_per_basic_block: 10_000,
+        ..VmConfig::default()
+    });
+    let metered = metered.publish_module(gas(), store_module(), false);
+    assert_eq!(metered.status_code, StatusCode::EXECUTED);
+    assert!(metered.gas_used > free.gas_used);
+}
+
+#[test]
+fn test_storage_access_gas() {
+    let store_u64 = |cold_access_cost, warm_access_cost| {
+        let vm = vm_with_config(VmConfig {
+            cold_access_cost,
+            warm_access_cost,
+            ..VmConfig::default()
+        });
+        vm.pub_mod(store_module());
+        let res = vm.execute_script(
+            gas(),
+            ExecutionContext::new(100, 100),
+            store_u64_script(addr("0x1"), 13),
+            false,
+        );
+        assert_eq!(res.status_code, StatusCode::EXECUTED);
+        res.gas_used
+    };
+
+    let free = store_u64(0, 0);
+    // The script accesses `Store::U64` once, so the access is cold.
+    assert_eq!(store_u64(1_000_000, 0), free + 1000);
+    assert_eq!(store_u64(0, 1_000_000), free);
+}
+
+#[test]
+fn test_script_tx_limits() {
+    let vm = vm_with_config(VmConfig {
+        max_type_args: 1,
+        max_args_size: 16,
+        ..VmConfig::default()
+    });
+    vm.pub_mod(store_module());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let script = ScriptTx::new(
+        store_u64_script(addr("0x1"), 13).code().to_vec(),
+        vec![ScriptArg::VectorU8(vec![0; 17])],
+        vec![],
+        vec![addr("0x1")],
+    );
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 100), script, false);
+    assert_eq!(res.status_code, StatusCode::EXCEEDED_MAX_ARGUMENTS_SIZE);
+    assert_eq!(res.gas_used, 0);
+
+    let script = ScriptTx::new(
+        store_u64_script(addr("0x1"), 13).code().to_vec(),
+        vec![ScriptArg::U64(13)],
+        vec![TypeTag::U8, TypeTag::U64],
+        vec![addr("0x1")],
+    );
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 100), script, false);
+    assert_eq!(res.status_code, StatusCode::TOO_MANY_TYPE_ARGUMENTS);
+
+    let vm = vm_with_config(VmConfig {
+        max_script_size: 8,
+        ..VmConfig::default()
+    });
+    vm.pub_mod(store_module());
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXCEEDED_MAX_TRANSACTION_SIZE);
+}
+
+#[test]
+fn test_script_tx_try_new() {
+    let limits = TxLimits {
+        max_args_size: 8,
+        ..TxLimits::default()
+    };
+    let code = store_u64_script(addr("0x1"), 13).code().to_vec();
+    assert!(ScriptTx::try_new(
+        code.clone(),
+        vec![ScriptArg::U64(13)],
+        vec![],
+        vec![addr("0x1")],
+        &limits
+    )
+    .is_ok());
+    assert_eq!(
+        ScriptTx::try_new(
+            code,
+            vec![ScriptArg::U64(13), ScriptArg::U8(1)],
+            vec![],
+            vec![addr("0x1")],
+            &limits
+        )
+        .err(),
+        Some(StatusCode::EXCEEDED_MAX_ARGUMENTS_SIZE)
+    );
+}
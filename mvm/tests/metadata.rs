@@ -0,0 +1,99 @@
+use common::assets::*;
+use common::bytecode::natives_module;
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use mvm::metadata::{FieldMetadata, FunctionMetadata, ModuleMetadata, Visibility};
+use vm::file_format::SignatureToken;
+
+mod common;
+
+#[test]
+fn test_struct_metadata() {
+    let metadata = ModuleMetadata::from_bytes(store_module().code()).unwrap();
+    assert_eq!(metadata.address, CORE_CODE_ADDRESS);
+    assert_eq!(metadata.name, "Store");
+    assert!(metadata.constants.is_empty());
+
+    let names = metadata
+        .structs
+        .iter()
+        .map(|st| st.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["U64", "U128", "Address", "VectorU8"]);
+
+    let vector_u8 = &metadata.structs[3];
+    assert!(vector_u8.is_resource);
+    assert!(!vector_u8.is_native);
+    assert_eq!(vector_u8.type_parameters, 0);
+    assert_eq!(
+        vector_u8.fields,
+        vec![FieldMetadata {
+            name: "val".to_owned(),
+            type_: "vector<u8>".to_owned(),
+        }]
+    );
+}
+
+#[test]
+fn test_stdlib_metadata() {
+    let (modules, _) = stdlib_package().into_tx(CORE_CODE_ADDRESS).into_inner();
+    for module in modules {
+        let metadata = ModuleMetadata::from_bytes(&module).unwrap();
+        let encoded = bcs::to_bytes(&metadata).unwrap();
+        assert_eq!(
+            bcs::from_bytes::<ModuleMetadata>(&encoded).unwrap(),
+            metadata
+        );
+    }
+}
+
+#[test]
+fn test_invalid_module() {
+    assert!(ModuleMetadata::from_bytes(&[0, 1, 2]).is_err());
+}
+
+#[test]
+fn test_function_metadata() {
+    let module = natives_module(
+        CORE_CODE_ADDRESS,
+        "Math",
+        vec![
+            (
+                "double",
+                vec![SignatureToken::U64],
+                vec![SignatureToken::U64],
+            ),
+            (
+                "store",
+                vec![
+                    SignatureToken::Reference(Box::new(SignatureToken::Signer)),
+                    SignatureToken::U64,
+                ],
+                vec![],
+            ),
+        ],
+    );
+    let metadata = ModuleMetadata::from_bytes(module.code()).unwrap();
+    assert_eq!(
+        metadata.functions,
+        vec![
+            FunctionMetadata {
+                name: "double".to_owned(),
+                visibility: Visibility::Public,
+                is_entry: false,
+                is_native: true,
+                type_parameters: 0,
+                parameters: vec!["u64".to_owned()],
+                returns: vec!["u64".to_owned()],
+            },
+            FunctionMetadata {
+                name: "store".to_owned(),
+                visibility: Visibility::Public,
+                is_entry: true,
+                is_native: true,
+                type_parameters: 0,
+                parameters: vec!["&signer".to_owned(), "u64".to_owned()],
+                returns: vec![],
+            },
+        ]
+    );
+}
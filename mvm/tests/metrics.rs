@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::metrics::{Metrics, SerializationStats, TxKind};
+use mvm::types::VmResult;
+
+mod common;
+
+#[derive(Default)]
+struct MetricsMock {
+    started: RefCell<Vec<TxKind>>,
+    finished: RefCell<Vec<(TxKind, StatusCode, u64)>>,
+    module_reads: RefCell<u64>,
+    resource_reads: RefCell<u64>,
+    serializations: RefCell<Vec<SerializationStats>>,
+}
+
+impl Metrics for MetricsMock {
+    fn on_tx_start(&self, kind: TxKind) {
+        self.started.borrow_mut().push(kind);
+    }
+
+    fn on_tx_end(&self, kind: TxKind, result: &VmResult) {
+        self.finished
+            .borrow_mut()
+            .push((kind, result.status_code, result.gas_used));
+    }
+
+    fn on_module_read(&self, _id: &ModuleId, _found: bool) {
+        *self.module_reads.borrow_mut() += 1;
+    }
+
+    fn on_resource_read(&self, _address: &AccountAddress, _tag: &StructTag, _found: bool) {
+        *self.resource_reads.borrow_mut() += 1;
+    }
+
+    fn on_effects_serialized(&self, stats: &SerializationStats) {
+        self.serializations.borrow_mut().push(stats.clone());
+    }
+}
+
+#[test]
+fn test_metrics() {
+    let vm = MvmBuilder::new(StorageMock::new(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .with_metrics(MetricsMock::default())
+        .build()
+        .unwrap();
+
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 13));
+
+    let metrics = vm.metrics();
+    assert_eq!(
+        metrics.started.borrow().as_slice(),
+        &[TxKind::PublishModule, TxKind::Script]
+    );
+
+    let finished = metrics.finished.borrow();
+    assert_eq!(finished.len(), 2);
+    assert_eq!(finished[0].0, TxKind::PublishModule);
+    assert_eq!(finished[1].0, TxKind::Script);
+    assert!(finished
+        .iter()
+        .all(|(_, status, gas_used)| *status == StatusCode::EXECUTED && *gas_used > 0));
+    assert!(*metrics.module_reads.borrow() > 0);
+
+    // The script stores one `Store::U64` resource.
+    assert_eq!(
+        metrics.serializations.borrow().last(),
+        Some(&SerializationStats {
+            values: 1,
+            bytes: 8,
+            allocations: 1,
+            per_value_allocations: 1,
+        })
+    );
+}
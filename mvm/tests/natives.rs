@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use common::assets::*;
+use common::bytecode::{native_call_script, native_module};
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::native_registry::{NativeGasParams, NativeRegistry};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::{NativeContext, NativeResult};
+use move_vm_types::pop_arg;
+use move_vm_types::values::Value;
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::mvm::Mvm;
+use mvm::types::{ModuleTx, ScriptArg, ScriptTx};
+use mvm::Vm;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+fn double_module() -> ModuleTx {
+    native_module(
+        addr("0x2"),
+        "Math",
+        "double",
+        vec![SignatureToken::U64],
+        vec![SignatureToken::U64],
+    )
+}
+
+fn double_script(value: u64) -> ScriptTx {
+    native_call_script(
+        addr("0x2"),
+        "Math",
+        "double",
+        vec![SignatureToken::U64],
+        vec![SignatureToken::U64],
+        vec![ScriptArg::U64(value)],
+    )
+}
+
+fn vm_with_natives(
+    natives: NativeRegistry,
+) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    MvmBuilder::new(StorageMock::new(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_natives(natives)
+}
+
+fn double_registry(calls: Arc<Mutex<Vec<u64>>>, gas: NativeGasParams) -> NativeRegistry {
+    NativeRegistry::new().register(
+        addr("0x2"),
+        "Math",
+        "double",
+        gas,
+        move |_: &mut dyn NativeContext, _: Vec<Type>, mut args: VecDeque<Value>| {
+            let value = pop_arg!(args, u64);
+            calls.lock().unwrap().push(value);
+            Ok(NativeResult::ok(
+                GasUnits::new(0),
+                vec![Value::u64(value * 2)],
+            ))
+        },
+    )
+}
+
+#[test]
+fn test_host_native() {
+    let calls = Arc::new(Mutex::new(vec![]));
+    let vm = vm_with_natives(double_registry(calls.clone(), NativeGasParams::default()));
+
+    let res = vm.publish_module(gas(), double_module(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        double_script(21),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(*calls.lock().unwrap(), vec![21]);
+}
+
+#[test]
+fn test_host_native_gas() {
+    let free = vm_with_natives(double_registry(
+        Arc::new(Mutex::new(vec![])),
+        NativeGasParams::default(),
+    ));
+    let paid = vm_with_natives(double_registry(
+        Arc::new(Mutex::new(vec![])),
+        NativeGasParams {
+            base: 1_000_000,
+            per_byte: 0,
+        },
+    ));
+
+    let mut gas_used = vec![];
+    for vm in &[free, paid] {
+        assert_eq!(
+            vm.publish_module(gas(), double_module(), false).status_code,
+            StatusCode::EXECUTED
+        );
+        let res = vm.execute_script(
+            gas(),
+            ExecutionContext::new(100, 100),
+            double_script(21),
+            false,
+        );
+        assert_eq!(res.status_code, StatusCode::EXECUTED);
+        gas_used.push(res.gas_used);
+    }
+    assert!(gas_used[1] >= gas_used[0] + 999);
+}
+
+#[test]
+fn test_unregistered_native() {
+    let vm = vm_with_natives(NativeRegistry::new());
+    let res = vm.publish_module(gas(), double_module(), false);
+    assert_eq!(res.status_code, StatusCode::MISSING_DEPENDENCY);
+}
@@ -0,0 +1,266 @@
+use common::bytecode::{native_call_script, native_proxy_module, natives_module};
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::data::AccessKey;
+use mvm::data::ExecutionContext;
+use mvm::nft::{
+    nft_access_path, token_store_tag, Nft, NFT_BURN, NFT_MINT, NFT_MODULE, NFT_TRANSFER,
+};
+use mvm::testkit::gas;
+use mvm::types::{ModuleTx, ScriptArg, ScriptTx};
+use mvm::view::ViewCall;
+use mvm::write_set::{ResourceDeleted, RESOURCE_DELETED_EVENT};
+use mvm::Vm;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+fn bytes() -> SignatureToken {
+    SignatureToken::Vector(Box::new(SignatureToken::U8))
+}
+
+fn mint_params() -> Vec<SignatureToken> {
+    vec![SignatureToken::Address, bytes(), bytes()]
+}
+
+fn signer() -> SignatureToken {
+    SignatureToken::Reference(Box::new(SignatureToken::Signer))
+}
+
+fn transfer_params() -> Vec<SignatureToken> {
+    vec![signer(), SignatureToken::Address, bytes()]
+}
+
+fn burn_params() -> Vec<SignatureToken> {
+    vec![signer(), bytes()]
+}
+
+fn nft_module() -> ModuleTx {
+    natives_module(
+        CORE_CODE_ADDRESS,
+        NFT_MODULE,
+        vec![
+            (NFT_MINT, mint_params(), vec![]),
+            (NFT_TRANSFER, transfer_params(), vec![]),
+            (NFT_BURN, burn_params(), vec![]),
+        ],
+    )
+}
+
+fn mint(owner: AccountAddress, id: &[u8], data: &[u8]) -> ScriptTx {
+    native_call_script(
+        CORE_CODE_ADDRESS,
+        NFT_MODULE,
+        NFT_MINT,
+        mint_params(),
+        vec![],
+        vec![
+            ScriptArg::Address(owner),
+            ScriptArg::VectorU8(id.to_vec()),
+            ScriptArg::VectorU8(data.to_vec()),
+        ],
+    )
+}
+
+/// Script calling a token native with `sender` as the signer.
+fn signed_call(
+    function: &str,
+    params: Vec<SignatureToken>,
+    sender: AccountAddress,
+    args: Vec<ScriptArg>,
+) -> ScriptTx {
+    let tx = native_call_script(
+        CORE_CODE_ADDRESS,
+        NFT_MODULE,
+        function,
+        params,
+        vec![],
+        vec![],
+    );
+    ScriptTx::new(tx.code().to_vec(), args, vec![], vec![sender])
+}
+
+fn transfer(from: AccountAddress, to: AccountAddress, id: &[u8]) -> ScriptTx {
+    signed_call(
+        NFT_TRANSFER,
+        transfer_params(),
+        from,
+        vec![ScriptArg::Address(to), ScriptArg::VectorU8(id.to_vec())],
+    )
+}
+
+fn burn(owner: AccountAddress, id: &[u8]) -> ScriptTx {
+    signed_call(
+        NFT_BURN,
+        burn_params(),
+        owner,
+        vec![ScriptArg::VectorU8(id.to_vec())],
+    )
+}
+
+fn nft(id: &[u8], owner: AccountAddress, data: &[u8]) -> Nft {
+    Nft {
+        id: id.to_vec(),
+        owner,
+        data: data.to_vec(),
+    }
+}
+
+#[test]
+fn test_nft() {
+    let (vm, _, _, _, _) = vm();
+    assert_eq!(
+        vm.publish_module(gas(), nft_module(), false).status_code,
+        StatusCode::EXECUTED
+    );
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    let exec = |tx: ScriptTx| {
+        vm.execute_script(gas(), ExecutionContext::new(100, 100), tx, false)
+            .status_code
+    };
+
+    assert_eq!(exec(mint(alice, b"1", b"first")), StatusCode::EXECUTED);
+    assert_eq!(exec(mint(alice, b"2", b"second")), StatusCode::EXECUTED);
+    assert_eq!(
+        exec(mint(bob, b"1", b"copy")),
+        StatusCode::RESOURCE_ALREADY_EXISTS
+    );
+    assert_eq!(
+        vm.nft_of(&alice).unwrap(),
+        vec![nft(b"1", alice, b"first"), nft(b"2", alice, b"second")]
+    );
+    assert!(vm.nft_of(&bob).unwrap().is_empty());
+
+    assert_eq!(exec(transfer(alice, bob, b"1")), StatusCode::EXECUTED);
+    assert_eq!(
+        exec(transfer(alice, bob, b"1")),
+        StatusCode::RESOURCE_DOES_NOT_EXIST
+    );
+    assert_eq!(
+        vm.nft_of(&alice).unwrap(),
+        vec![nft(b"2", alice, b"second")]
+    );
+    assert_eq!(vm.nft_of(&bob).unwrap(), vec![nft(b"1", bob, b"first")]);
+
+    assert_eq!(exec(burn(alice, b"1")), StatusCode::RESOURCE_DOES_NOT_EXIST);
+    assert_eq!(exec(burn(bob, b"1")), StatusCode::EXECUTED);
+    assert!(vm.nft_of(&bob).unwrap().is_empty());
+    assert_eq!(exec(mint(bob, b"1", b"again")), StatusCode::EXECUTED);
+    assert_eq!(vm.nft_of(&bob).unwrap(), vec![nft(b"1", bob, b"again")]);
+}
+
+#[test]
+fn test_only_owner_moves_token() {
+    let (vm, _, _, _, _) = vm();
+    vm.publish_module(gas(), nft_module(), false);
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    let exec = |tx: ScriptTx| {
+        vm.execute_script(gas(), ExecutionContext::new(100, 100), tx, false)
+            .status_code
+    };
+
+    assert_eq!(exec(mint(alice, b"1", b"data")), StatusCode::EXECUTED);
+    assert_eq!(
+        exec(transfer(bob, bob, b"1")),
+        StatusCode::RESOURCE_DOES_NOT_EXIST
+    );
+    assert_eq!(exec(burn(bob, b"1")), StatusCode::RESOURCE_DOES_NOT_EXIST);
+    assert_eq!(vm.nft_of(&alice).unwrap(), vec![nft(b"1", alice, b"data")]);
+    assert!(vm.nft_of(&bob).unwrap().is_empty());
+}
+
+#[test]
+fn test_nft_ops_of_failed_tx_are_dropped() {
+    let (vm, _, _, _, _) = vm();
+    vm.publish_module(gas(), nft_module(), false);
+    let alice = AccountAddress::random();
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        mint(alice, b"1", b"data"),
+        true,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(vm.nft_of(&alice).unwrap().is_empty());
+}
+
+#[test]
+fn test_burn_reports_deletions() {
+    let (vm, _, events, _, _) = vm();
+    let vm = vm.with_deletion_events();
+    vm.publish_module(gas(), nft_module(), false);
+    let alice = AccountAddress::random();
+    let exec = |tx: ScriptTx| vm.execute_script(gas(), ExecutionContext::new(100, 100), tx, false);
+
+    let res = exec(mint(alice, b"1", b"data"));
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(res.deletions.is_empty());
+    events.data.borrow_mut().clear();
+
+    let res = exec(burn(alice, b"1"));
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    let token = AccessKey::from(&nft_access_path(CORE_CODE_ADDRESS, b"1"));
+    let store = AccessKey::from((&alice, &token_store_tag(CORE_CODE_ADDRESS)));
+    let mut deleted = vec![token.as_ref().to_vec(), store.as_ref().to_vec()];
+    deleted.sort();
+    assert_eq!(res.deletions, deleted);
+
+    let emitted = events
+        .data
+        .borrow()
+        .iter()
+        .filter(|(_, tag, _, _)| tag.to_string().ends_with(RESOURCE_DELETED_EVENT))
+        .map(|(address, _, msg, _)| (*address, bcs::from_bytes(msg).unwrap()))
+        .collect::<Vec<_>>();
+    let expected = deleted
+        .iter()
+        .map(|key| ResourceDeleted::from_key(key).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(emitted, expected);
+}
+
+#[test]
+fn test_view_ops_do_not_leak_into_next_tx() {
+    let (vm, _, _, _, _) = vm();
+    let module = native_proxy_module(
+        CORE_CODE_ADDRESS,
+        NFT_MODULE,
+        NFT_MINT,
+        "mint_view",
+        mint_params(),
+        vec![],
+    );
+    vm.publish_module(gas(), module, false);
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+
+    let call = ViewCall::new(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new(NFT_MODULE).unwrap()),
+        Identifier::new("mint_view").unwrap(),
+        vec![
+            ScriptArg::Address(alice),
+            ScriptArg::VectorU8(b"1".to_vec()),
+            ScriptArg::VectorU8(b"view".to_vec()),
+        ],
+    );
+    assert!(vm
+        .view_function(gas(), ExecutionContext::new(100, 100), &call, 0)
+        .is_ok());
+    assert!(vm.nft_of(&alice).unwrap().is_empty());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        mint(bob, b"2", b"tx"),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(vm.nft_of(&alice).unwrap().is_empty());
+    assert_eq!(vm.nft_of(&bob).unwrap(), vec![nft(b"2", bob, b"tx")]);
+}
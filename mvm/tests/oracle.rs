@@ -0,0 +1,323 @@
+use common::assets::*;
+use common::bytecode::{generic_call_script, generic_native_module, generic_native_proxy_module};
+use common::mock::{OracleMock, Utils};
+use common::vm;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::data::{ExecutionContext, MemoryStorage, State, StateSession};
+use mvm::oracle::{
+    PriceHistory, ORACLE_ACCESS_DENIED, ORACLE_ADDRESS, ORACLE_GET_PRICE, ORACLE_GET_TWAP,
+    ORACLE_MODULE, PRICE_HISTORY_CAPACITY, PRICE_NOT_FOUND,
+};
+use mvm::testkit::MockVm;
+use mvm::types::{PriceRead, ScriptArg, ScriptTx, VmResult};
+use mvm::vm_config::loader::store_oracle_acl_at;
+use mvm::vm_config::{OracleAccess, OracleAcl, CONFIG_ADDRESS};
+use mvm::Vm;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+#[test]
+fn test_price_reads() {
+    let (vm, _, _, oracle, _) = vm();
+    vm.pub_mod(store_module());
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+
+    oracle.set_price("ETH_BTC", 13);
+    oracle.set_price("BTC_PONT", 234646734213);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        get_price_script(addr("0x1"), addr("0x2")),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(
+        res.price_reads,
+        vec![
+            PriceRead {
+                ticker: ticker("ETH_BTC"),
+                price: Some(13),
+            },
+            PriceRead {
+                ticker: ticker("BTC_PONT"),
+                price: Some(234646734213),
+            },
+        ]
+    );
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(res.price_reads.is_empty());
+}
+
+const PROXY: &str = "price";
+
+fn coin(name: &str) -> TypeTag {
+    TypeTag::Struct(StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Coins").unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params: vec![],
+    })
+}
+
+/// Script calling `0x1::Oracle::function<first, second>()`.
+fn oracle_script(function: &str, first: &str, second: &str) -> ScriptTx {
+    generic_call_script(
+        CORE_CODE_ADDRESS,
+        ORACLE_MODULE,
+        function,
+        vec![],
+        vec![SignatureToken::U128],
+        vec![],
+        vec![coin(first), coin(second)],
+    )
+}
+
+fn oracle_vm(acl: Option<OracleAcl>) -> MockVm {
+    let (vm, store, _, oracle, _) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(generic_native_proxy_module(
+        CORE_CODE_ADDRESS,
+        ORACLE_MODULE,
+        ORACLE_GET_PRICE,
+        PROXY,
+        2,
+        vec![],
+        vec![SignatureToken::U128],
+    ));
+    oracle.set_price("ETH_BTC", 13);
+    oracle.set_price("BTC_ETH", 7);
+    if let Some(acl) = acl {
+        store_oracle_acl_at(&store, CONFIG_ADDRESS, &acl);
+    }
+    vm
+}
+
+fn run(vm: &MockVm, script: ScriptTx) -> VmResult {
+    vm.execute_script(gas(), ExecutionContext::new(100, 100), script, false)
+}
+
+#[test]
+fn test_oracle_native() {
+    let vm = oracle_vm(None);
+
+    let res = run(&vm, oracle_script(ORACLE_GET_PRICE, "ETH", "BTC"));
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(
+        res.price_reads,
+        vec![PriceRead {
+            ticker: ticker("ETH_BTC"),
+            price: Some(13),
+        }]
+    );
+
+    let res = run(&vm, oracle_script(ORACLE_GET_PRICE, "ETH", "ETH"));
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.sub_status, Some(PRICE_NOT_FOUND));
+}
+
+#[test]
+fn test_oracle_acl() {
+    let oracle_module = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new(ORACLE_MODULE).unwrap());
+    let vm = oracle_vm(Some(OracleAcl::new(OracleAccess::Denied).with_module(
+        oracle_module,
+        OracleAccess::Tickers(vec![ticker("ETH_BTC")]),
+    )));
+
+    let res = run(&vm, oracle_script(ORACLE_GET_PRICE, "ETH", "BTC"));
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.sub_status, Some(ORACLE_ACCESS_DENIED));
+    assert!(res.price_reads.is_empty());
+
+    let res = run(&vm, oracle_script(PROXY, "ETH", "BTC"));
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let res = run(&vm, oracle_script(PROXY, "BTC", "ETH"));
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.sub_status, Some(ORACLE_ACCESS_DENIED));
+}
+
+/// Script calling `0x1::Oracle::get_twap<first, second>(window)`.
+fn twap_script(first: &str, second: &str, window: u64) -> ScriptTx {
+    generic_call_script(
+        CORE_CODE_ADDRESS,
+        ORACLE_MODULE,
+        ORACLE_GET_TWAP,
+        vec![SignatureToken::U64],
+        vec![SignatureToken::U128],
+        vec![ScriptArg::U64(window)],
+        vec![coin(first), coin(second)],
+    )
+}
+
+#[test]
+fn test_price_history() {
+    let mut history = PriceHistory::default();
+    assert_eq!(history.twap(100, 10), None);
+
+    assert!(history.push(10, 10));
+    assert!(history.push(20, 20));
+    assert!(!history.push(15, 30));
+    assert_eq!(history.twap(5, 10), None);
+    assert_eq!(history.twap(30, 20), Some(15));
+    assert_eq!(history.twap(30, 10), Some(20));
+    assert_eq!(history.twap(40, 100), Some(16));
+    assert_eq!(history.twap(20, 0), Some(20));
+
+    assert!(history.push(20, 40));
+    assert_eq!(history.samples(), &[(10, 10), (20, 40)]);
+
+    for timestamp in 0..PRICE_HISTORY_CAPACITY as u64 {
+        assert!(history.push(100 + timestamp, 1));
+    }
+    assert_eq!(history.samples().len(), PRICE_HISTORY_CAPACITY);
+    assert_eq!(history.samples()[0], (100, 1));
+}
+
+#[test]
+fn test_oracle_twap() {
+    let (vm, _, _, oracle, _) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(generic_native_module(
+        CORE_CODE_ADDRESS,
+        ORACLE_MODULE,
+        ORACLE_GET_TWAP,
+        2,
+        vec![SignatureToken::U64],
+        vec![SignatureToken::U128],
+    ));
+
+    let res = run(&vm, twap_script("ETH", "BTC", 50));
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.sub_status, Some(PRICE_NOT_FOUND));
+
+    assert!(vm.record_price(coin("ETH"), coin("BTC"), 10).is_err());
+    oracle.set_price("ETH_BTC", 13);
+    vm.record_price(coin("ETH"), coin("BTC"), 10).unwrap();
+    assert!(vm.record_price(coin("ETH"), coin("BTC"), 5).is_err());
+
+    let res = run(&vm, twap_script("ETH", "BTC", 50));
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    oracle.set_twap("BTC_ETH", 7);
+    let res = run(&vm, twap_script("BTC", "ETH", 50));
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+fn oracle_price_tag(ticker: &str) -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new(ORACLE_MODULE).unwrap(),
+        name: Identifier::new("Price").unwrap(),
+        type_params: vec![TypeTag::Struct(StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("Tickers").unwrap(),
+            name: Identifier::new(ticker).unwrap(),
+            type_params: vec![],
+        })],
+    }
+}
+
+#[test]
+fn test_oracle_price_resource() {
+    let oracle = OracleMock::default();
+    oracle.set_price("ETH_BTC", 13);
+    let state = State::new(MemoryStorage::new(), oracle);
+    let session = StateSession::new(&state, ExecutionContext::new(100, 100));
+
+    let price = session
+        .get_resource(&ORACLE_ADDRESS, &oracle_price_tag("ETH_BTC"))
+        .unwrap();
+    assert_eq!(price, Some(13u128.to_le_bytes().to_vec()));
+    assert_eq!(
+        bcs::from_bytes::<u128>(&price.unwrap()).unwrap(),
+        13,
+        "Price<Ticker> must decode as a struct with a single u128 field"
+    );
+    let missing = session
+        .get_resource(&ORACLE_ADDRESS, &oracle_price_tag("BTC_ETH"))
+        .unwrap();
+    assert_eq!(missing, None);
+    assert_eq!(
+        session.take_price_reads(),
+        vec![
+            PriceRead {
+                ticker: ticker("ETH_BTC"),
+                price: Some(13),
+            },
+            PriceRead {
+                ticker: ticker("BTC_ETH"),
+                price: None,
+            },
+        ]
+    );
+
+    let mut generic = oracle_price_tag("ETH_BTC");
+    generic.type_params = vec![TypeTag::U64];
+    assert_eq!(
+        session.get_resource(&ORACLE_ADDRESS, &generic).unwrap(),
+        None
+    );
+    assert!(session.take_price_reads().is_empty());
+}
+
+#[test]
+fn test_relocated_oracle_address() {
+    let oracle = OracleMock::default();
+    oracle.set_price("ETH_BTC", 13);
+    let state = State::new(MemoryStorage::new(), oracle).with_oracle_address(addr("0x5"));
+    let session =
+        StateSession::new(&state, ExecutionContext::new(100, 100)).with_oracle_address(addr("0x5"));
+
+    assert_eq!(
+        session
+            .get_resource(&addr("0x5"), &oracle_price_tag("ETH_BTC"))
+            .unwrap(),
+        Some(13u128.to_le_bytes().to_vec())
+    );
+    assert_eq!(
+        session
+            .get_resource(&ORACLE_ADDRESS, &oracle_price_tag("ETH_BTC"))
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_oracle_acl_restricts_price_resources() {
+    let oracle = OracleMock::default();
+    oracle.set_price("ETH_BTC", 13);
+    oracle.set_price("BTC_ETH", 7);
+    let storage = MemoryStorage::new();
+    let state = State::new(storage.clone(), oracle);
+    store_oracle_acl_at(
+        &storage,
+        CONFIG_ADDRESS,
+        &OracleAcl::new(OracleAccess::Tickers(vec![ticker("ETH_BTC")])),
+    );
+    let session = StateSession::new(&state, ExecutionContext::new(100, 100));
+
+    assert_eq!(
+        session
+            .get_resource(&ORACLE_ADDRESS, &oracle_price_tag("ETH_BTC"))
+            .unwrap(),
+        Some(13u128.to_le_bytes().to_vec())
+    );
+    let err = session
+        .get_resource(&ORACLE_ADDRESS, &oracle_price_tag("BTC_ETH"))
+        .unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::HOST_ACCESS_DENIED);
+    assert_eq!(session.take_price_reads().len(), 1);
+}
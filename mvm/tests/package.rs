@@ -0,0 +1,234 @@
+use common::assets::{addr, gas};
+use common::bytecode::{functions_module, native_call_script};
+use common::mock::{OracleMock, StorageMock};
+use common::vm;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::data::{ExecutionContext, State};
+use mvm::package::{load_packages, PackageManifest, UpgradePolicy};
+use mvm::types::{ModuleTx, PublishPackageTx, ScriptTx};
+use mvm::Vm;
+
+mod common;
+
+fn package(modules: Vec<ModuleTx>, policy: UpgradePolicy) -> PublishPackageTx {
+    PublishPackageTx::new(
+        modules
+            .into_iter()
+            .map(|module| module.code().to_vec())
+            .collect(),
+        addr("0x2"),
+    )
+    .with_package(Identifier::new("Dapp").unwrap(), policy)
+}
+
+fn module(name: &str, functions: &[&str]) -> ModuleTx {
+    functions_module(addr("0x2"), name, functions)
+}
+
+fn manifest(store: &StorageMock) -> Option<PackageManifest> {
+    load_packages(store, CORE_CODE_ADDRESS, &addr("0x2"))
+        .unwrap()
+        .package(&Identifier::new("Dapp").unwrap())
+        .cloned()
+}
+
+fn module_code(store: StorageMock, name: &str) -> Vec<u8> {
+    State::new(store, OracleMock::default())
+        .get_module(&ModuleId::new(addr("0x2"), Identifier::new(name).unwrap()))
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn test_publish_package_manifest() {
+    let (vm, store, _, _, _) = vm();
+    let release = package(
+        vec![module("Pool", &["swap"]), module("Router", &["route"])],
+        UpgradePolicy::Compatible,
+    );
+    let res = vm.publish_module_package(gas(), release, false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(
+        manifest(&store),
+        Some(PackageManifest {
+            name: Identifier::new("Dapp").unwrap(),
+            version: 1,
+            policy: UpgradePolicy::Compatible,
+            modules: vec![
+                Identifier::new("Pool").unwrap(),
+                Identifier::new("Router").unwrap()
+            ],
+        })
+    );
+
+    // Modules of the package are not published separately.
+    let res = vm.publish_module(gas(), module("Pool", &["swap", "add"]), false);
+    assert_eq!(res.status_code, StatusCode::DUPLICATE_MODULE_NAME);
+}
+
+#[test]
+fn test_compatible_upgrade() {
+    let (vm, store, _, _, _) = vm();
+    let res = vm.publish_module_package(
+        gas(),
+        package(vec![module("Pool", &["swap"])], UpgradePolicy::Compatible),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let res = vm.publish_module_package(
+        gas(),
+        package(vec![module("Pool", &[])], UpgradePolicy::Compatible),
+        false,
+    );
+    assert_eq!(
+        res.status_code,
+        StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE
+    );
+
+    let upgraded = module("Pool", &["swap", "add"]);
+    let res = vm.publish_module_package(
+        gas(),
+        package(
+            vec![upgraded.clone(), module("Router", &["route"])],
+            UpgradePolicy::Compatible,
+        ),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    let manifest = manifest(&store).unwrap();
+    assert_eq!(manifest.version, 2);
+    assert_eq!(manifest.modules.len(), 2);
+    assert_eq!(module_code(store, "Pool"), upgraded.code());
+}
+
+fn call(function: &str) -> ScriptTx {
+    native_call_script(addr("0x2"), "Pool", function, vec![], vec![], vec![])
+}
+
+#[test]
+fn test_upgraded_module_is_callable_in_next_transaction() {
+    let (vm, _, _, _, _) = vm();
+    let res = vm.publish_module_package(
+        gas(),
+        package(vec![module("Pool", &["swap"])], UpgradePolicy::Compatible),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 100), call("swap"), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    let pool = ModuleId::new(addr("0x2"), Identifier::new("Pool").unwrap());
+    let stats = vm.cache_stats();
+    assert!(stats.modules > 0);
+
+    let res = vm.publish_module_package(
+        gas(),
+        package(
+            vec![module("Pool", &["swap", "add"])],
+            UpgradePolicy::Compatible,
+        ),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.cache_stats().modules, 0);
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 100), call("add"), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let router = ModuleId::new(addr("0x2"), Identifier::new("Router").unwrap());
+    assert!(!vm.invalidate_modules(&[router]));
+    assert!(vm.invalidate_modules(&[pool]));
+    assert_eq!(vm.cache_stats().modules, 0);
+}
+
+#[test]
+fn test_new_module_does_not_drop_cache() {
+    let (vm, _, _, _, _) = vm();
+    let res = vm.publish_module_package(
+        gas(),
+        package(vec![module("Pool", &["swap"])], UpgradePolicy::Compatible),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 100), call("swap"), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    let stats = vm.cache_stats();
+
+    let res = vm.publish_module(gas(), module("Router", &["route"]), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.cache_stats(), stats);
+    let route = native_call_script(addr("0x2"), "Router", "route", vec![], vec![], vec![]);
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 100), route, false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(vm.cache_stats().modules > stats.modules);
+}
+
+#[test]
+fn test_package_policy() {
+    let (vm, store, _, _, _) = vm();
+    let res = vm.publish_module_package(
+        gas(),
+        package(
+            vec![module("Pool", &["swap"]), module("Router", &["route"])],
+            UpgradePolicy::Arbitrary,
+        ),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    // Modules can't be dropped from the package.
+    let res = vm.publish_module_package(
+        gas(),
+        package(vec![module("Pool", &[])], UpgradePolicy::Arbitrary),
+        false,
+    );
+    assert_eq!(
+        res.status_code,
+        StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE
+    );
+
+    let res = vm.publish_module_package(
+        gas(),
+        package(
+            vec![module("Pool", &[]), module("Router", &[])],
+            UpgradePolicy::Immutable,
+        ),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(manifest(&store).unwrap().policy, UpgradePolicy::Immutable);
+
+    let res = vm.publish_module_package(
+        gas(),
+        package(
+            vec![module("Pool", &[]), module("Router", &[])],
+            UpgradePolicy::Immutable,
+        ),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::IMMUTABLE_MODULE_UPDATE);
+    assert_eq!(manifest(&store).unwrap().version, 2);
+}
+
+#[test]
+fn test_policy_can_not_be_weakened() {
+    let (vm, _, _, _, _) = vm();
+    let res = vm.publish_module_package(
+        gas(),
+        package(vec![module("Pool", &["swap"])], UpgradePolicy::Compatible),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let res = vm.publish_module_package(
+        gas(),
+        package(vec![module("Pool", &[])], UpgradePolicy::Arbitrary),
+        false,
+    );
+    assert_eq!(
+        res.status_code,
+        StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE
+    );
+}
@@ -0,0 +1,170 @@
+use common::assets::*;
+use common::mock::{BankMock, OracleMock, StorageMock};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use move_vm_types::natives::balance::{BalanceOperation, WalletId};
+use mvm::builder::MvmBuilder;
+use mvm::data::{AccessKey, Bank, EventHandler, Oracle, State, Storage};
+use mvm::types::{BalanceChange, Ticker};
+use mvm::Vm;
+
+mod common;
+
+struct PanicStorage;
+
+impl Storage for PanicStorage {
+    fn get(&self, _key: &[u8]) -> Option<Vec<u8>> {
+        panic!("storage is unavailable");
+    }
+
+    fn insert(&self, _key: &[u8], _value: &[u8]) {}
+
+    fn remove(&self, _key: &[u8]) {}
+}
+
+struct PanicOracle;
+
+impl Oracle for PanicOracle {
+    fn get_price(&self, _ticker: &Ticker) -> Option<u128> {
+        panic!("oracle is unavailable");
+    }
+}
+
+struct PanicEventHandler;
+
+impl EventHandler for PanicEventHandler {
+    fn on_event(
+        &self,
+        _address: AccountAddress,
+        _ty_tag: TypeTag,
+        _message: Vec<u8>,
+        _caller: Option<ModuleId>,
+    ) {
+        panic!("event handler is unavailable");
+    }
+}
+
+fn coin(module: &str, name: &str) -> TypeTag {
+    TypeTag::Struct(StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new(module).unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params: vec![],
+    })
+}
+
+#[test]
+fn test_storage_panic() {
+    let state = State::new(PanicStorage, OracleMock::default());
+    let err = state
+        .get_module(&ModuleId::new(
+            CORE_CODE_ADDRESS,
+            Identifier::new("Store").unwrap(),
+        ))
+        .unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    let err = state.get_resource(&addr("0x2"), &tag).unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+}
+
+#[test]
+fn test_oracle_panic() {
+    let state = State::new(PanicStorage, PanicOracle);
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Coins").unwrap(),
+        name: Identifier::new("Price").unwrap(),
+        type_params: vec![coin("Coins", "ETH"), coin("Coins", "BTC")],
+    };
+    let err = state.get_resource(&CORE_CODE_ADDRESS, &tag).unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+}
+
+#[test]
+fn test_balance_access_panic() {
+    let bank_mock = BankMock::default();
+    bank_mock.set_balance(&addr("0x2"), "PONT", 10);
+    let bank = Bank::new(bank_mock);
+    let wallet = WalletId::new(
+        addr("0x2"),
+        StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("PONT").unwrap(),
+            name: Identifier::new("T").unwrap(),
+            type_params: vec![],
+        },
+    );
+
+    // `BankMock` panics on insufficient funds.
+    let err = bank.deposit(&wallet, 11).unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+    assert_eq!(bank.native_balance(&addr("0x2"), &ticker("PONT")), Some(10));
+
+    bank.deposit(&wallet, 10).unwrap();
+    assert_eq!(bank.native_balance(&addr("0x2"), &ticker("PONT")), Some(0));
+
+    // Balance changes are checked in order without touching the balances.
+    let withdraw = BalanceChange {
+        wallet_id: wallet.clone(),
+        operation: BalanceOperation::Withdraw(5),
+    };
+    let deposit = BalanceChange {
+        wallet_id: wallet.clone(),
+        operation: BalanceOperation::Deposit(5),
+    };
+    bank.check_changes(&[withdraw.clone(), deposit.clone()])
+        .unwrap();
+    let err = bank.check_changes(&[deposit, withdraw]).unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+    assert_eq!(bank.native_balance(&addr("0x2"), &ticker("PONT")), Some(0));
+
+    // The fee is checked like any other balance change.
+    assert!(bank.fee_change(&addr("0x2"), &ticker("PONT"), 0).is_none());
+    let fee = bank.fee_change(&addr("0x2"), &ticker("PONT"), 1).unwrap();
+    let err = bank.check_changes(Some(&fee)).unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+}
+
+#[test]
+fn test_event_handler_panic() {
+    let store = StorageMock::new();
+    let vm = MvmBuilder::new(store.clone(), PanicEventHandler)
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap();
+
+    // Events are emitted after the module is stored, the failure doesn't discard the transaction.
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+    assert!(store.get(AccessKey::from(&id).as_ref()).is_some());
+}
@@ -0,0 +1,108 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use diem_crypto::hash::HashValue;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use mvm::builder::MvmBuilder;
+use mvm::data::{AccessKey, Storage};
+use mvm::mvm::Mvm;
+use mvm::proof::{StateProof, StorageProof};
+
+mod common;
+
+/// Storage proving a key with the hash of the sorted key-value pairs.
+#[derive(Clone, Default)]
+struct ProvenStorage {
+    inner: StorageMock,
+    sealed: bool,
+}
+
+impl ProvenStorage {
+    fn root(&self) -> [u8; 32] {
+        let data = self.inner.data.borrow();
+        let mut pairs = data.iter().collect::<Vec<_>>();
+        pairs.sort();
+        let mut blob = vec![];
+        for (key, value) in pairs {
+            blob.extend_from_slice(key);
+            blob.extend_from_slice(value);
+        }
+        *HashValue::sha3_256_of(&blob).as_ref()
+    }
+}
+
+impl Storage for ProvenStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.inner.get(key)
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) {
+        self.inner.insert(key, value)
+    }
+
+    fn remove(&self, key: &[u8]) {
+        self.inner.remove(key)
+    }
+}
+
+impl StateProof for ProvenStorage {
+    fn prove(&self, key: &[u8]) -> Option<StorageProof> {
+        if !self.sealed {
+            return None;
+        }
+        Some(StorageProof {
+            state_root: self.root(),
+            nodes: vec![key.to_vec()],
+        })
+    }
+}
+
+fn store_tag() -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    }
+}
+
+fn vm(storage: ProvenStorage) -> Mvm<ProvenStorage, EventHandlerMock, OracleMock, BankMock> {
+    MvmBuilder::new(storage, EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_resource_with_proof() {
+    let storage = ProvenStorage {
+        sealed: true,
+        ..Default::default()
+    };
+    let vm = vm(storage.clone());
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 13));
+
+    let resource = vm
+        .get_resource_with_proof(&addr("0x1"), &store_tag())
+        .unwrap();
+    let key = AccessKey::from((&addr("0x1"), &store_tag()));
+    assert_eq!(resource.key, key.as_ref());
+    assert_eq!(resource.value, Some(13u64.to_le_bytes().to_vec()));
+    assert_eq!(resource.proof.state_root, storage.root());
+    assert_eq!(resource.proof.nodes, vec![key.as_ref().to_vec()]);
+
+    let missing = vm
+        .get_resource_with_proof(&addr("0x2"), &store_tag())
+        .unwrap();
+    assert_eq!(missing.value, None);
+}
+
+#[test]
+fn test_storage_without_proof() {
+    let vm = vm(ProvenStorage::default());
+    assert!(vm
+        .get_resource_with_proof(&addr("0x1"), &store_tag())
+        .is_err());
+}
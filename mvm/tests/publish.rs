@@ -0,0 +1,78 @@
+use common::mock::Utils;
+use common::{assets::*, vm};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::hash::module_hash;
+use mvm::publish::{module_published_tag, ModulePublishedEvent};
+use mvm::Vm;
+
+mod common;
+
+#[test]
+fn test_module_published_event() {
+    let (vm, _, events, _, _) = vm();
+    let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+    assert_eq!(vm.module_version(&id).unwrap(), 0);
+
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.module_version(&id).unwrap(), 1);
+
+    let (address, tag, msg, caller) = events.pop().unwrap();
+    assert_eq!(address, CORE_CODE_ADDRESS);
+    assert_eq!(tag, module_published_tag(CORE_CODE_ADDRESS));
+    assert_eq!(caller, None);
+    assert_eq!(
+        bcs::from_bytes::<ModulePublishedEvent>(&msg).unwrap(),
+        ModulePublishedEvent {
+            id: id.clone(),
+            hash: module_hash(store_module().code()),
+            version: 1,
+        }
+    );
+
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::DUPLICATE_MODULE_NAME);
+    assert_eq!(vm.module_version(&id).unwrap(), 1);
+
+    vm.pub_mod(event_module());
+    let event = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Event").unwrap());
+    assert_eq!(vm.module_version(&event).unwrap(), 1);
+    assert_eq!(vm.module_version(&id).unwrap(), 1);
+}
+
+#[test]
+fn test_dry_run_publish_keeps_version() {
+    let (vm, _, events, _, _) = vm();
+    let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+
+    let res = vm.publish_module(gas(), store_module(), true);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.module_version(&id).unwrap(), 0);
+    assert!(events.pop().is_none());
+}
+
+#[test]
+fn test_immutable_module() {
+    let (vm, _, _, _, _) = vm();
+    let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+
+    let res = vm.publish_module(gas(), store_module().immutable(), true);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(!vm.is_module_immutable(&id).unwrap());
+
+    let res = vm.publish_module(gas(), store_module().immutable(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(vm.is_module_immutable(&id).unwrap());
+    assert!(!vm
+        .is_module_immutable(&ModuleId::new(
+            CORE_CODE_ADDRESS,
+            Identifier::new("Event").unwrap()
+        ))
+        .unwrap());
+
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::IMMUTABLE_MODULE_UPDATE);
+    assert_eq!(vm.module_version(&id).unwrap(), 1);
+}
@@ -0,0 +1,83 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::mvm::Mvm;
+use mvm::vm_config::loader::{load_publisher_policy_at, store_publisher_policy_at};
+use mvm::vm_config::{PublishPermission, PublisherPolicy, CONFIG_ADDRESS};
+use mvm::Vm;
+use parity_scale_codec::{Decode, Encode};
+
+mod common;
+
+fn policy_vm(policy: &PublisherPolicy) -> Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock> {
+    let store = StorageMock::new();
+    store_publisher_policy_at(&store, CONFIG_ADDRESS, policy);
+    MvmBuilder::new(store, EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_policy_codec() {
+    let policy = PublisherPolicy::new(PublishPermission::Denied)
+        .with_account(addr("0x1"), PublishPermission::Any)
+        .with_account(
+            addr("0x2"),
+            PublishPermission::Modules(vec!["Store".to_owned()]),
+        );
+    let blob = policy.encode();
+    assert_eq!(
+        PublisherPolicy::decode(&mut blob.as_slice()).unwrap(),
+        policy
+    );
+
+    let store = StorageMock::new();
+    assert_eq!(
+        load_publisher_policy_at(&store, CONFIG_ADDRESS).unwrap(),
+        PublisherPolicy::default()
+    );
+    store_publisher_policy_at(&store, CONFIG_ADDRESS, &policy);
+    assert_eq!(
+        load_publisher_policy_at(&store, CONFIG_ADDRESS).unwrap(),
+        policy
+    );
+}
+
+#[test]
+fn test_allowlisted_modules() {
+    let vm = policy_vm(&PublisherPolicy::default().with_account(
+        CORE_CODE_ADDRESS,
+        PublishPermission::Modules(vec!["Store".to_owned()]),
+    ));
+
+    assert_eq!(
+        vm.publish_module(gas(), store_module(), false).status_code,
+        StatusCode::EXECUTED
+    );
+    assert_eq!(
+        vm.publish_module(gas(), event_module(), false).status_code,
+        StatusCode::INVALID_MODULE_PUBLISHER
+    );
+}
+
+#[test]
+fn test_denied_publisher() {
+    let vm = policy_vm(&PublisherPolicy::new(PublishPermission::Denied));
+    assert_eq!(
+        vm.publish_module(gas(), store_module(), false).status_code,
+        StatusCode::INVALID_MODULE_PUBLISHER
+    );
+
+    let vm = policy_vm(
+        &PublisherPolicy::new(PublishPermission::Denied)
+            .with_account(CORE_CODE_ADDRESS, PublishPermission::Any),
+    );
+    assert_eq!(
+        vm.publish_module(gas(), store_module(), false).status_code,
+        StatusCode::EXECUTED
+    );
+}
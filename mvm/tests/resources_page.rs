@@ -0,0 +1,116 @@
+use common::assets::addr;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use common::vm;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use mvm::builder::MvmBuilder;
+use mvm::data::{AccessKey, MemoryStorage, Storage};
+use mvm::key_codec::{Blake2_128Concat, RawKeys};
+
+mod common;
+
+/// Storage without key iteration.
+struct PointStorage(MemoryStorage);
+
+impl Storage for PointStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.get(key)
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) {
+        self.0.insert(key, value)
+    }
+
+    fn remove(&self, key: &[u8]) {
+        self.0.remove(key)
+    }
+}
+
+fn tag(name: &str) -> StructTag {
+    StructTag {
+        address: addr("0x1"),
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params: vec![],
+    }
+}
+
+fn insert(store: &StorageMock, address: &str, name: &str) {
+    let key = AccessKey::from((&addr(address), &tag(name)));
+    store.insert(key.as_ref(), name.as_bytes());
+}
+
+#[test]
+fn test_resources_page() {
+    let (vm, store, _, _, _) = vm();
+    for name in &["A", "B", "C", "D", "E"] {
+        insert(&store, "0x2", name);
+    }
+    insert(&store, "0x3", "A");
+    let module = ModuleId::new(addr("0x2"), Identifier::new("Store").unwrap());
+    store.insert(AccessKey::from(&module).as_ref(), &[1]);
+
+    let mut pages = vec![];
+    let mut cursor = None;
+    loop {
+        let page = vm.resources_page(&addr("0x2"), cursor.as_ref(), 2).unwrap();
+        pages.push(
+            page.resources
+                .iter()
+                .map(|(tag, blob)| {
+                    assert_eq!(tag.name.as_bytes(), blob.as_slice());
+                    tag.name.to_string()
+                })
+                .collect::<Vec<_>>(),
+        );
+        cursor = page.cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(pages, vec![vec!["A", "B"], vec!["C", "D"], vec!["E"]]);
+
+    // Resources removed between the pages are skipped.
+    let page = vm.resources_page(&addr("0x2"), None, 2).unwrap();
+    store.remove(AccessKey::from((&addr("0x2"), &tag("C"))).as_ref());
+    let page = vm
+        .resources_page(&addr("0x2"), page.cursor.as_ref(), 2)
+        .unwrap();
+    assert_eq!(page.resources.len(), 2);
+    assert_eq!(page.resources[0].0, tag("D"));
+    assert_eq!(page.cursor, None);
+
+    let page = vm.resources_page(&addr("0x4"), None, 2).unwrap();
+    assert!(page.resources.is_empty());
+    assert_eq!(page.cursor, None);
+}
+
+#[test]
+fn test_resources_page_requires_key_iteration() {
+    let store = MemoryStorage::default();
+    let vm = MvmBuilder::new(PointStorage(store.clone()), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap();
+    let key = AccessKey::from((&addr("0x2"), &tag("A")));
+    store.insert(key.as_ref(), b"A");
+
+    assert!(vm.resources_page(&addr("0x2"), None, 2).is_err());
+}
+
+#[test]
+fn test_resources_page_requires_ordered_codec() {
+    let store = MemoryStorage::default();
+    let vm = MvmBuilder::new(store, EventHandlerMock::default())
+        .with_key_codec(Blake2_128Concat)
+        .build()
+        .unwrap();
+    assert!(vm.resources_page(&addr("0x2"), None, 2).is_err());
+
+    let vm = MvmBuilder::new(MemoryStorage::default(), EventHandlerMock::default())
+        .with_key_codec(RawKeys)
+        .build()
+        .unwrap();
+    assert!(vm.resources_page(&addr("0x2"), None, 2).is_ok());
+}
@@ -0,0 +1,79 @@
+use common::assets::*;
+use common::mock::Utils;
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::vm_status::StatusCode;
+use mvm::data::{BalanceAccess, ExecutionContext, SessionPolicy};
+use mvm::Vm;
+
+mod common;
+
+#[test]
+fn test_sandbox_denies_bank() {
+    let (vm, _, _, _, bank) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    bank.set_balance(&alice, "USDT", 1024);
+    bank.set_balance(&alice, "PONT", 64);
+    bank.set_balance(&alice, "BTC", 13);
+
+    // The script aborts on the balance hidden from the sandbox.
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100).with_policy(SessionPolicy::sandbox()),
+        test_balance_script(alice, bob, 1024, 64, 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::HOST_ACCESS_DENIED);
+    assert!(res.balance_changes.is_empty());
+    assert_eq!(bank.get_balance(&alice, &ticker("USDT")), Some(1024));
+    assert_eq!(bank.get_balance(&bob, &ticker("USDT")), None);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        test_balance_script(alice, bob, 1024, 64, 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(bank.get_balance(&bob, &ticker("USDT")), Some(512));
+}
+
+#[test]
+fn test_sandbox_denies_oracle() {
+    let (vm, _, _, oracle, _) = vm();
+    vm.pub_mod(store_module());
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+
+    oracle.set_price("ETH_BTC", 13);
+    oracle.set_price("BTC_PONT", 234646734213);
+
+    let policy = SessionPolicy {
+        oracle: false,
+        ..SessionPolicy::default()
+    };
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100).with_policy(policy),
+        get_price_script(addr("0x1"), addr("0x2")),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::HOST_ACCESS_DENIED);
+    assert!(res.price_reads.is_empty());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        get_price_script(addr("0x1"), addr("0x2")),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
@@ -0,0 +1,41 @@
+use common::assets::*;
+use common::bytecode::signer_vector_script;
+use move_core_types::vm_status::StatusCode;
+use move_vm_types::values::DESTROY_NON_EMPTY_VEC;
+use mvm::testkit::mock::Utils;
+use mvm::testkit::{MockVm, VmBuilder};
+
+mod common;
+
+fn vm() -> MockVm {
+    VmBuilder::new().with_stdlib(stdlib_package()).build().0
+}
+
+#[test]
+fn test_signer_vector_takes_remaining_senders() {
+    let vm = vm();
+
+    vm.exec(signer_vector_script(0, vec![]));
+    vm.exec(signer_vector_script(1, vec![addr("0x1")]));
+
+    let res = vm.exec_expect_failure(
+        signer_vector_script(1, vec![addr("0x1"), addr("0x2")]),
+        StatusCode::ABORTED,
+    );
+    assert_eq!(res.sub_status, Some(DESTROY_NON_EMPTY_VEC));
+
+    let res = vm.exec_expect_failure(
+        signer_vector_script(0, vec![addr("0x1"), addr("0x2"), addr("0x3")]),
+        StatusCode::ABORTED,
+    );
+    assert_eq!(res.sub_status, Some(DESTROY_NON_EMPTY_VEC));
+}
+
+#[test]
+fn test_signer_references_require_senders() {
+    let vm = vm();
+    vm.exec_expect_failure(
+        signer_vector_script(2, vec![addr("0x1")]),
+        StatusCode::TYPE_MISMATCH,
+    );
+}
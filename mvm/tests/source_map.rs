@@ -0,0 +1,149 @@
+use common::assets::*;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::data::{ExecutionContext, MemoryStorage, Storage};
+use mvm::source_map::{
+    source_map_key, ErrorLocation, FunctionSourceMap, SourceLocation, SourceMap, SOURCE_MAP_TAG,
+};
+use mvm::testkit::mock::Utils;
+use mvm::testkit::vm;
+use mvm::Vm;
+
+mod common;
+
+fn abort_module_id() -> ModuleId {
+    ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Abort").unwrap())
+}
+
+fn abort_source_map() -> Vec<u8> {
+    bcs::to_bytes(&SourceMap {
+        file: "Abort.move".to_owned(),
+        functions: vec![FunctionSourceMap {
+            index: 0,
+            lines: vec![4, 4],
+        }],
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_error_location() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(abort_module());
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(0, 0),
+        error_script(AccountAddress::random()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(
+        res.location,
+        Some(ErrorLocation {
+            module: abort_module_id(),
+            function: 0,
+            code_offset: 1,
+        })
+    );
+    assert_eq!(res.source_location, None);
+    assert!(res.to_string().ends_with("::Abort#0:1"));
+}
+
+#[test]
+fn test_source_location() {
+    let (vm, _, _, _, _) = vm();
+    let vm = vm.with_source_map_storage(MemoryStorage::new());
+    vm.pub_mod(abort_module().with_source_map(abort_source_map()));
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(0, 0),
+        error_script(AccountAddress::random()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(
+        res.source_location,
+        Some(SourceLocation {
+            file: "Abort.move".to_owned(),
+            line: 4,
+        })
+    );
+    assert!(res.to_string().ends_with("at Abort.move:4"));
+}
+
+#[test]
+fn test_republish_removes_source_map() {
+    let (vm, state, _, _, _) = vm();
+    let source_maps = MemoryStorage::new();
+    let vm = vm.with_source_map_storage(source_maps.clone());
+    vm.pub_mod(abort_module().with_source_map(abort_source_map()));
+    let mut key = CORE_CODE_ADDRESS.to_vec();
+    key.push(SOURCE_MAP_TAG);
+    key.extend_from_slice(b"Abort");
+    assert_eq!(source_maps.get(&key), Some(abort_source_map()));
+    assert_eq!(state.get(&key), None);
+
+    vm.pub_mod(abort_module());
+    assert_eq!(source_maps.get(&key), None);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(0, 0),
+        error_script(AccountAddress::random()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert!(res.location.is_some());
+    assert_eq!(res.source_location, None);
+}
+
+#[test]
+fn test_invalid_source_map_is_not_stored() {
+    let (vm, _, _, _, _) = vm();
+    let source_maps = MemoryStorage::new();
+    let vm = vm.with_source_map_storage(source_maps.clone());
+    vm.pub_mod(abort_module().with_source_map(vec![0xff; 4]));
+    assert_eq!(
+        source_maps.get(source_map_key(&abort_module_id()).as_ref()),
+        None
+    );
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(0, 0),
+        error_script(AccountAddress::random()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert_eq!(res.source_location, None);
+}
+
+#[test]
+fn test_source_map_is_not_in_consensus_state() {
+    let (vm, state, _, _, _) = vm();
+    vm.pub_mod(abort_module().with_source_map(abort_source_map()));
+    assert_eq!(state.get(source_map_key(&abort_module_id()).as_ref()), None);
+
+    // Without the host-local storage only the publish transaction itself resolves its errors.
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(0, 0),
+        error_script(AccountAddress::random()),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::ABORTED);
+    assert!(res.location.is_some());
+    assert_eq!(res.source_location, None);
+}
+
+#[test]
+fn test_source_map_line() {
+    let source_map = SourceMap::decode(&abort_source_map()).unwrap();
+    assert_eq!(source_map.line(0, 1), Some(4));
+    assert_eq!(source_map.line(0, 2), None);
+    assert_eq!(source_map.line(1, 0), None);
+}
@@ -0,0 +1,170 @@
+use common::mock::Utils;
+use common::{assets::*, vm};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::data::{ExecutionContext, State};
+use mvm::hash::Digest;
+use mvm::testkit::ticker;
+use mvm::types::PriceRead;
+
+mod common;
+
+const ROOT: Digest = [2; 32];
+
+fn stored_u64<S: mvm::data::Storage, O: mvm::data::Oracle>(state: &State<S, O>) -> Option<u64> {
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    state
+        .get_resource(&CORE_CODE_ADDRESS, &tag)
+        .unwrap()
+        .map(|blob| bcs::from_bytes(&blob).unwrap())
+}
+
+#[test]
+fn test_apply_speculative_result() {
+    let (vm, store, _, oracle, _) = vm();
+    let vm = vm.with_speculative_cache(8);
+    let state = State::new(store, oracle);
+    vm.pub_mod(store_module());
+
+    let res = vm.simulate_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        ROOT,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(stored_u64(&state), None);
+    assert_eq!(vm.speculative_cache_len(), 1);
+
+    // The cached effects are applied instead of the script execution.
+    let applied = vm.execute_speculated(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        ROOT,
+    );
+    assert_eq!(applied.status_code, StatusCode::EXECUTED);
+    assert_eq!(applied.gas_used, res.gas_used);
+    assert_eq!(stored_u64(&state), Some(13));
+    assert_eq!(vm.speculative_cache_len(), 0);
+}
+
+#[test]
+fn test_execute_on_mismatch() {
+    let (vm, store, _, oracle, _) = vm();
+    let vm = vm.with_speculative_cache(8);
+    let state = State::new(store, oracle);
+    vm.pub_mod(store_module());
+
+    vm.simulate_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        ROOT,
+    );
+    vm.simulate_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 15),
+        ROOT,
+    );
+    assert_eq!(vm.speculative_cache_len(), 2);
+
+    // Other block context.
+    let res = vm.execute_speculated(
+        gas(),
+        ExecutionContext::new(200, 101),
+        store_u64_script(addr("0x1"), 13),
+        ROOT,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(stored_u64(&state), Some(13));
+    // The state root has changed, so its results are dropped.
+    assert_eq!(vm.speculative_cache_len(), 0);
+}
+
+#[test]
+fn test_other_tx_is_executed() {
+    let (vm, store, _, oracle, _) = vm();
+    let vm = vm.with_speculative_cache(8);
+    let state = State::new(store, oracle);
+    vm.pub_mod(store_module());
+
+    vm.simulate_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13),
+        ROOT,
+    );
+    let res = vm.execute_speculated(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 14),
+        ROOT,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(stored_u64(&state), Some(14));
+}
+
+#[test]
+fn test_execute_on_price_change() {
+    let (vm, _, _, oracle, _) = vm();
+    let vm = vm.with_speculative_cache(8);
+    vm.pub_mod(store_module());
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    oracle.set_price("ETH_BTC", 13);
+    oracle.set_price("BTC_PONT", 17);
+
+    let res = vm.simulate_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        get_price_script(addr("0x1"), addr("0x2")),
+        ROOT,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.speculative_cache_len(), 1);
+
+    oracle.set_price("ETH_BTC", 14);
+    let res = vm.execute_speculated(
+        gas(),
+        ExecutionContext::new(100, 100),
+        get_price_script(addr("0x1"), addr("0x2")),
+        ROOT,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(
+        res.price_reads[0],
+        PriceRead {
+            ticker: ticker("ETH_BTC"),
+            price: Some(14),
+        }
+    );
+}
+
+#[test]
+fn test_invalidate_speculative_cache() {
+    let (vm, _, _, _, _) = vm();
+    let vm = vm.with_speculative_cache(1);
+    vm.pub_mod(store_module());
+
+    for idx in 0..2 {
+        vm.simulate_script(
+            gas(),
+            ExecutionContext::new(100, 100),
+            store_u64_script(addr("0x1"), idx),
+            ROOT,
+        );
+    }
+    assert_eq!(vm.speculative_cache_len(), 1);
+
+    vm.invalidate_speculative_cache();
+    assert_eq!(vm.speculative_cache_len(), 0);
+}
@@ -0,0 +1,60 @@
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::vm_status::{StatusCode, StatusType};
+use mvm::builder::MvmBuilder;
+use mvm::mvm::Mvm;
+use mvm::types::ModuleTx;
+use mvm::vm_config::loader::store_publisher_policy_at;
+use mvm::vm_config::{PublishPermission, PublisherPolicy, CONFIG_ADDRESS};
+use mvm::Vm;
+
+mod common;
+
+fn vm(
+    store: StorageMock,
+) -> (
+    Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock>,
+    EventHandlerMock,
+) {
+    let events = EventHandlerMock::default();
+    let vm = MvmBuilder::new(store, events.clone())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap();
+    (vm, events)
+}
+
+#[test]
+fn test_discarded_tx_is_not_charged() {
+    let store = StorageMock::new();
+    store_publisher_policy_at(
+        &store,
+        CONFIG_ADDRESS,
+        &PublisherPolicy::new(PublishPermission::Denied),
+    );
+    let (vm, events) = vm(store);
+
+    let res = vm.publish_module(gas(), store_module(), false);
+    assert_eq!(res.status_code, StatusCode::INVALID_MODULE_PUBLISHER);
+    assert_eq!(res.status_type(), StatusType::Validation);
+    assert!(res.is_discarded());
+    assert_eq!(res.gas_used, 0);
+    assert!(events.pop().is_none());
+}
+
+#[test]
+fn test_kept_tx_is_charged() {
+    let (vm, events) = vm(StorageMock::new());
+
+    let module = ModuleTx::new(store_module().code().to_vec(), addr("0x2"));
+    let res = vm.publish_module(gas(), module, false);
+    assert_eq!(
+        res.status_code,
+        StatusCode::MODULE_ADDRESS_DOES_NOT_MATCH_SENDER
+    );
+    assert_eq!(res.status_type(), StatusType::Verification);
+    assert!(!res.is_discarded());
+    assert!(res.gas_used > 0);
+    assert!(events.pop().is_some());
+}
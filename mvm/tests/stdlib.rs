@@ -0,0 +1,28 @@
+use common::assets::*;
+use common::vm;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::data::State;
+
+mod common;
+
+#[test]
+fn test_publish_embedded_stdlib() {
+    let (vm, store, _, oracle, _) = vm();
+    let res = vm.publish_embedded_stdlib(gas());
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let state = State::new(store, oracle);
+    for name in &[
+        "Account", "Block", "Coins", "Event", "PONT", "Pontem", "Signer", "Time",
+    ] {
+        let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new(*name).unwrap());
+        assert!(
+            state.get_module(&id).unwrap().is_some(),
+            "{} not found",
+            name
+        );
+    }
+}
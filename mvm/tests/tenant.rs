@@ -0,0 +1,159 @@
+use common::assets::*;
+use common::mock::{StorageMock, Utils};
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::data::{AccessKey, BalanceAccess, ExecutionContext, Storage};
+use mvm::tenant::{TenantId, HOST_KEY_TAG};
+use mvm::testkit::VmBuilder;
+use mvm::Vm;
+
+mod common;
+
+fn store_u64_key(prefix: Vec<u8>) -> Vec<u8> {
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    let mut key = prefix;
+    key.extend_from_slice(AccessKey::from((&addr("0x1"), &tag)).as_ref());
+    key
+}
+
+fn store_u64(store: &StorageMock, prefix: Vec<u8>) -> Option<u64> {
+    store
+        .get(&store_u64_key(prefix))
+        .map(|blob| bcs::from_bytes::<StoreU64>(&blob).unwrap().val)
+}
+
+fn context(tenant: TenantId) -> ExecutionContext {
+    ExecutionContext::new(100, 100).with_tenant(tenant)
+}
+
+#[test]
+fn test_tenants_are_isolated() {
+    let (vm, store, _, _, _) = VmBuilder::new().with_tenant_spaces().build();
+    for (tenant, value) in [(TenantId(1), 1), (TenantId(2), 2)].iter() {
+        vm.pub_mod(store_module().with_tenant(*tenant));
+        let res = vm.execute_script(
+            gas(),
+            context(*tenant),
+            store_u64_script(addr("0x1"), *value),
+            false,
+        );
+        assert_eq!(res.status_code, StatusCode::EXECUTED);
+    }
+    assert_eq!(store_u64(&store, TenantId(1).key_prefix()), Some(1));
+    assert_eq!(store_u64(&store, TenantId(2).key_prefix()), Some(2));
+
+    // The host space has no modules.
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 3),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::LINKER_ERROR);
+}
+
+#[test]
+fn test_host_space_is_prefixed() {
+    let (vm, store, _, _, _) = VmBuilder::new().with_tenant_spaces().build();
+    vm.pub_mod(store_module());
+    vm.pub_mod(store_module().with_tenant(TenantId(1)));
+    vm.exec(store_u64_script(addr("0x1"), 7));
+
+    assert_eq!(store_u64(&store, vec![HOST_KEY_TAG]), Some(7));
+    assert_eq!(store_u64(&store, vec![]), None);
+    assert_eq!(store_u64(&store, TenantId(1).key_prefix()), None);
+}
+
+#[test]
+fn test_tenant_does_not_see_cached_modules_of_other_tenants() {
+    let (vm, store, _, _, _) = VmBuilder::new().with_tenant_spaces().build();
+    vm.pub_mod(store_module().with_tenant(TenantId(1)));
+    let res = vm.execute_script(
+        gas(),
+        context(TenantId(1)),
+        store_u64_script(addr("0x1"), 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let res = vm.execute_script(
+        gas(),
+        context(TenantId(2)),
+        store_u64_script(addr("0x1"), 2),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::LINKER_ERROR);
+    assert_eq!(store_u64(&store, TenantId(2).key_prefix()), None);
+}
+
+#[test]
+fn test_tenant_txs_keep_host_cache() {
+    let (vm, _, _, _, _) = VmBuilder::new().with_tenant_spaces().build();
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 1));
+    let cached = vm.cache_stats().modules;
+    assert!(cached > 0);
+
+    vm.pub_mod(store_module().with_tenant(TenantId(1)));
+    let res = vm.execute_script(
+        gas(),
+        context(TenantId(1)),
+        store_u64_script(addr("0x1"), 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.cache_stats().modules, cached);
+    assert_eq!(vm.current_epoch(), 0);
+}
+
+#[test]
+fn test_tenant_has_no_host_bank() {
+    let (vm, _, _, _, bank) = VmBuilder::new().with_tenant_spaces().build();
+    let tenant = TenantId(1);
+    for module in vec![
+        coins_module(),
+        pont_module(),
+        signer_module(),
+        event_module(),
+        pontem_module(),
+        account_module(),
+    ] {
+        vm.pub_mod(module.with_tenant(tenant));
+    }
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    bank.set_balance(&alice, "USDT", 1024);
+    bank.set_balance(&alice, "PONT", 64);
+    bank.set_balance(&alice, "BTC", 13);
+
+    let res = vm.execute_script(
+        gas(),
+        context(tenant),
+        test_balance_script(alice, bob, 1024, 64, 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::HOST_ACCESS_DENIED);
+    assert_eq!(bank.get_balance(&alice, &ticker("PONT")), Some(64));
+}
+
+#[test]
+fn test_tenant_requires_tenant_spaces() {
+    let (vm, _, _, _, _) = vm();
+    let res = vm.publish_module(gas(), store_module().with_tenant(TenantId(1)), false);
+    assert_eq!(res.status_code, StatusCode::STORAGE_ERROR);
+    let res = vm.execute_script(
+        gas(),
+        context(TenantId(1)),
+        store_u64_script(addr("0x1"), 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::STORAGE_ERROR);
+}
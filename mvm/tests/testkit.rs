@@ -0,0 +1,60 @@
+use common::assets::*;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::data::{BalanceAccess, State};
+use mvm::testkit::mock::Utils;
+use mvm::testkit::{vm, VmBuilder};
+use mvm::types::Gas;
+
+mod common;
+
+#[test]
+fn test_vm_builder() {
+    let alice = addr("0x1");
+    let (_, store, _, oracle, bank) = VmBuilder::new()
+        .with_stdlib(stdlib_package())
+        .with_module(store_module())
+        .with_balance(alice, "PONT", 100)
+        .build();
+
+    let state = State::new(store, oracle);
+    for name in &["Account", "Coins", "PONT", "Store"] {
+        assert!(state
+            .get_module(&ModuleId::new(
+                CORE_CODE_ADDRESS,
+                Identifier::new(*name).unwrap()
+            ))
+            .unwrap()
+            .is_some());
+    }
+    assert_eq!(bank.get_balance(&alice, &ticker("PONT")), Some(100));
+}
+
+#[test]
+fn test_exec_helpers() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+
+    let res = vm.exec_with_gas(store_u64_script(addr("0x1"), 13), gas());
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(res.gas_used > 0);
+
+    let res = vm.exec_expect_failure(
+        store_u64_script(addr("0x1"), 13),
+        StatusCode::RESOURCE_ALREADY_EXISTS,
+    );
+    assert!(res.gas_used > 0);
+
+    let res = vm.exec_with_gas(store_u64_script(addr("0x2"), 13), Gas::new(1, 1).unwrap());
+    assert_eq!(res.status_code, StatusCode::OUT_OF_GAS);
+}
+
+#[test]
+#[should_panic]
+fn test_exec_expect_failure_panics_on_success() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+    vm.exec_expect_failure(store_u64_script(addr("0x1"), 13), StatusCode::ABORTED);
+}
@@ -0,0 +1,65 @@
+use common::assets::*;
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::data::ExecutionContext;
+use mvm::testkit::mock::Utils;
+use mvm::tokens::{create_token_script, token_module, token_tag, transfer_script};
+use mvm::Vm;
+
+mod common;
+
+#[test]
+fn test_token() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+
+    let creator = AccountAddress::random();
+    let bob = AccountAddress::random();
+    let marker = StructTag {
+        address: creator,
+        module: Identifier::new("MyToken").unwrap(),
+        name: Identifier::new("MyToken").unwrap(),
+        type_params: vec![],
+    };
+    let res = vm.publish_module(
+        gas(),
+        token_module(creator, "MyToken", "MyToken").unwrap(),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        create_token_script(CORE_CODE_ADDRESS, creator, marker.clone(), 1000, 2, "MYT").unwrap(),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.token_balance(&creator, marker.clone()).unwrap(), 1000);
+    assert_eq!(vm.token_balance(&bob, marker.clone()).unwrap(), 0);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        transfer_script(
+            CORE_CODE_ADDRESS,
+            creator,
+            token_tag(CORE_CODE_ADDRESS, marker.clone()),
+            bob,
+            300,
+        )
+        .unwrap(),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.token_balance(&creator, marker.clone()).unwrap(), 700);
+    assert_eq!(vm.token_balance(&bob, marker).unwrap(), 300);
+}
@@ -0,0 +1,158 @@
+use common::assets::*;
+use common::bytecode::native_module;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::auth::{Authenticator, AUTH_MODULE, ROTATE_KEY};
+use mvm::builder::MvmBuilder;
+use mvm::data::ExecutionContext;
+use mvm::hash::Digest;
+use mvm::package::UpgradePolicy;
+use mvm::testkit::mock::Utils;
+use mvm::testkit::ticker;
+use mvm::tx_builder;
+use mvm::types::{BalanceBreakdown, ScriptTx};
+use mvm::Vm;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+fn pont() -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("PONT").unwrap(),
+        name: Identifier::new("T").unwrap(),
+        type_params: vec![],
+    }
+}
+
+#[test]
+fn test_register_currency_and_transfer() {
+    let (vm, _, _, _, bank) = vm();
+    vm.pub_mod(coins_module());
+    vm.pub_mod(pont_module());
+    vm.pub_mod(signer_module());
+    vm.pub_mod(event_module());
+    vm.pub_mod(pontem_module());
+    vm.pub_mod(account_module());
+    vm.exec(tx_builder::register_currency(CORE_CODE_ADDRESS, pont(), "PONT", 2).unwrap());
+
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    bank.set_balance(&alice, "PONT", 100);
+    vm.exec(test_transfer_script(alice, bob, 10));
+    vm.exec(tx_builder::transfer(CORE_CODE_ADDRESS, bob, pont(), alice, 4).unwrap());
+
+    assert_eq!(
+        vm.balance(&alice, &ticker("PONT")).unwrap(),
+        BalanceBreakdown {
+            native: 90,
+            wrapped: 4,
+        }
+    );
+    assert_eq!(
+        vm.balance(&bob, &ticker("PONT")).unwrap(),
+        BalanceBreakdown {
+            native: 0,
+            wrapped: 6,
+        }
+    );
+}
+
+#[test]
+fn test_publish_package() {
+    let (vm, _, _, _, _) = vm();
+    let (modules, _) = stdlib_package().into_tx(CORE_CODE_ADDRESS).into_inner();
+    let tx = tx_builder::publish_package(
+        CORE_CODE_ADDRESS,
+        modules,
+        Some((
+            Identifier::new("Stdlib").unwrap(),
+            UpgradePolicy::Compatible,
+        )),
+    )
+    .unwrap();
+    let res = vm.publish_module_package(gas(), tx, false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+#[test]
+fn test_invalid_package() {
+    let (modules, _) = stdlib_package().into_tx(CORE_CODE_ADDRESS).into_inner();
+    assert!(tx_builder::publish_package(addr("0x2"), modules, None).is_err());
+    assert!(tx_builder::publish_package(CORE_CODE_ADDRESS, vec![], None).is_err());
+    assert!(tx_builder::publish_package(CORE_CODE_ADDRESS, vec![vec![0, 1, 2]], None).is_err());
+}
+
+/// Accepts the transaction if the proof is the key of the sender. The initial key of an account
+/// is its address.
+struct KeyAuthenticator;
+
+impl Authenticator for KeyAuthenticator {
+    fn authenticate(&self, _: &Digest, _: &[AccountAddress], _: &[u8]) -> Result<(), u64> {
+        Err(1)
+    }
+
+    fn authenticate_with_keys(
+        &self,
+        _: &Digest,
+        senders: &[AccountAddress],
+        keys: &[Option<Vec<u8>>],
+        proof: &[u8],
+    ) -> Result<(), u64> {
+        let key = match (senders, keys) {
+            ([sender], [key]) => key.clone().unwrap_or_else(|| sender.to_vec()),
+            _ => return Err(1),
+        };
+        if key == proof {
+            Ok(())
+        } else {
+            Err(2)
+        }
+    }
+}
+
+#[test]
+fn test_rotate_key() {
+    let vm = MvmBuilder::new(StorageMock::new(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_authenticator(KeyAuthenticator);
+    vm.pub_mod(store_module());
+    vm.pub_mod(native_module(
+        CORE_CODE_ADDRESS,
+        AUTH_MODULE,
+        ROTATE_KEY,
+        vec![
+            SignatureToken::Reference(Box::new(SignatureToken::Signer)),
+            SignatureToken::Vector(Box::new(SignatureToken::U8)),
+        ],
+        vec![],
+    ));
+    let alice = addr("0x3");
+    let execute = |tx: ScriptTx| {
+        vm.execute_script(gas(), ExecutionContext::new(100, 100), tx, false)
+            .status_code
+    };
+
+    let rotate = tx_builder::rotate_key(CORE_CODE_ADDRESS, alice, vec![7; 32]).unwrap();
+    assert_eq!(
+        execute(rotate.with_proof(alice.to_vec())),
+        StatusCode::EXECUTED
+    );
+
+    // The old key is rejected once the key is rotated.
+    assert_eq!(
+        execute(store_u64_script(alice, 13).with_proof(alice.to_vec())),
+        StatusCode::INVALID_SIGNATURE
+    );
+    assert_eq!(
+        execute(store_u64_script(alice, 13).with_proof(vec![7; 32])),
+        StatusCode::EXECUTED
+    );
+}
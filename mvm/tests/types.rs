@@ -1,8 +1,12 @@
 use core::convert::TryFrom;
 use move_core_types::identifier::Identifier;
 use move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
 use move_vm_types::values::Value;
-use mvm::types::{parse_type_params, ModulePackage, Transaction};
+use mvm::types::{
+    parse_type_params, Gas, GasBounds, GasError, ModulePackage, Ticker, Transaction, VmResult,
+};
+use parity_scale_codec::{Decode, Encode};
 use vm::access::ModuleAccess;
 use vm::file_format::CompiledScript;
 use vm::CompiledModule;
@@ -130,3 +134,146 @@ fn test_parse_pac() {
         ]
     );
 }
+
+#[test]
+fn test_ticker() {
+    assert_eq!(Ticker::new("usdt").unwrap(), Ticker::new("USDT").unwrap());
+    assert_eq!(Ticker::new("eth_btc").unwrap().as_str(), "ETH_BTC");
+    assert!(Ticker::new("").is_err());
+    assert!(Ticker::new("US DT").is_err());
+    assert!(Ticker::new("ЮСДТ").is_err());
+    assert!(Ticker::new(&"A".repeat(Ticker::MAX_LEN + 1)).is_err());
+
+    let ticker = Ticker::try_from("pont").unwrap();
+    assert_eq!(
+        Ticker::decode(&mut ticker.encode().as_ref()).unwrap(),
+        ticker
+    );
+    assert!(Ticker::decode(&mut "pont".encode().as_ref()).is_ok());
+    assert!(Ticker::decode(&mut "po-nt".encode().as_ref()).is_err());
+
+    let blob = bcs::to_bytes(&ticker).unwrap();
+    assert_eq!(bcs::from_bytes::<Ticker>(&blob).unwrap(), ticker);
+    assert!(bcs::from_bytes::<Ticker>(&bcs::to_bytes("po-nt").unwrap()).is_err());
+}
+
+#[test]
+fn test_gas_builder() {
+    let bounds = GasBounds {
+        min_gas_unit_price: 2,
+        max_gas_unit_price: Some(10),
+        max_gas_per_tx: Some(1_000),
+    };
+    let gas = Gas::builder()
+        .max_gas_amount(1_000)
+        .gas_unit_price(2)
+        .build(&bounds)
+        .unwrap();
+    assert_eq!(gas.max_gas_amount(), 1_000);
+    assert_eq!(gas.gas_unit_price(), 2);
+    assert_eq!(gas.gas_currency(), &Ticker::new("PONT").unwrap());
+
+    let gas = Gas::builder()
+        .max_gas_amount(1_000)
+        .gas_unit_price(2)
+        .gas_currency(Ticker::new("USDT").unwrap())
+        .build(&bounds)
+        .unwrap();
+    assert_eq!(gas.gas_currency(), &Ticker::new("USDT").unwrap());
+
+    let build = |max_gas_amount: u64, gas_unit_price: u64| {
+        Gas::builder()
+            .max_gas_amount(max_gas_amount)
+            .gas_unit_price(gas_unit_price)
+            .build(&bounds)
+            .unwrap_err()
+    };
+    assert_eq!(
+        build(1_001, 2),
+        GasError::MaxGasAmountTooLarge {
+            max_gas_amount: 1_001,
+            limit: 1_000
+        }
+    );
+    assert_eq!(
+        build(1_000, 1),
+        GasError::GasUnitPriceTooLow {
+            gas_unit_price: 1,
+            min: 2
+        }
+    );
+    assert_eq!(
+        build(1_000, 11),
+        GasError::GasUnitPriceTooHigh {
+            gas_unit_price: 11,
+            max: 10
+        }
+    );
+    assert_eq!(
+        Gas::builder().gas_unit_price(2).build(&bounds).unwrap_err(),
+        GasError::MissingMaxGasAmount
+    );
+
+    // Without the on-chain bounds only the static limit of `Gas::new` applies.
+    let unbounded = Gas::builder()
+        .max_gas_amount(u64::MAX)
+        .gas_unit_price(0)
+        .build(&GasBounds::default());
+    assert!(matches!(
+        unbounded,
+        Err(GasError::MaxGasAmountTooLarge { .. })
+    ));
+}
+
+fn vm_result(status_code: StatusCode, sub_status: Option<u64>) -> VmResult {
+    VmResult {
+        status_code,
+        sub_status,
+        gas_used: 10,
+        price_reads: vec![],
+        balance_changes: vec![],
+        abort_message: None,
+        location: None,
+        source_location: None,
+        write_set_digest: None,
+        deletions: vec![],
+        halted: false,
+    }
+}
+
+#[test]
+fn test_vm_result_display() {
+    assert_eq!(
+        vm_result(StatusCode::EXECUTED, None).to_string(),
+        "EXECUTED (4001), gas used: 10"
+    );
+    assert_eq!(
+        vm_result(StatusCode::ABORTED, Some(42)).to_string(),
+        "ABORTED (4016), sub status: 42, gas used: 10"
+    );
+}
+
+#[test]
+fn test_vm_result_classification() {
+    let executed = vm_result(StatusCode::EXECUTED, None);
+    assert!(executed.is_success());
+    assert!(!executed.is_discarded());
+
+    let aborted = vm_result(StatusCode::ABORTED, Some(1));
+    assert!(!aborted.is_success());
+    assert!(!aborted.is_discarded());
+
+    assert!(!vm_result(StatusCode::OUT_OF_GAS, None).is_discarded());
+    assert!(!vm_result(StatusCode::CODE_DESERIALIZATION_ERROR, None).is_discarded());
+    assert!(vm_result(StatusCode::INVALID_SIGNATURE, None).is_discarded());
+    assert!(vm_result(StatusCode::STORAGE_ERROR, None).is_discarded());
+}
+
+#[test]
+fn test_vm_result_serde() {
+    let result = vm_result(StatusCode::ABORTED, Some(42));
+    let decoded: VmResult = bcs::from_bytes(&bcs::to_bytes(&result).unwrap()).unwrap();
+    assert_eq!(decoded.status_code, result.status_code);
+    assert_eq!(decoded.sub_status, result.sub_status);
+    assert_eq!(decoded.gas_used, result.gas_used);
+}
@@ -0,0 +1,54 @@
+use common::assets::*;
+use common::mock::Utils;
+use common::vm;
+use move_core_types::vm_status::StatusCode;
+use mvm::data::ExecutionContext;
+use mvm::types::BatchTx;
+use mvm::{BatchVm, Vm};
+
+mod common;
+
+#[test]
+fn test_expired_script() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+
+    let script = || store_u64_script(addr("0x1"), 1).with_expiration(100);
+    let res = vm.execute_script(gas(), ExecutionContext::new(100, 1), script(), false);
+    assert_eq!(res.status_code, StatusCode::TRANSACTION_EXPIRED);
+    assert_eq!(res.gas_used, 0);
+
+    let res = vm.execute_script(gas(), ExecutionContext::new(99, 1), script(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+#[test]
+fn test_not_yet_valid_script() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+
+    let script = || {
+        store_u64_script(addr("0x1"), 1)
+            .with_not_before(50)
+            .with_expiration(60)
+    };
+    let res = vm.execute_script(gas(), ExecutionContext::new(49, 1), script(), false);
+    assert_eq!(res.status_code, StatusCode::TRANSACTION_NOT_YET_VALID);
+    assert_eq!(res.gas_used, 0);
+
+    let res = vm.execute_script(gas(), ExecutionContext::new(50, 1), script(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+#[test]
+fn test_expired_batch() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+
+    let batch = || BatchTx::new(vec![store_u64_call(1)], vec![addr("0x1")]).with_expiration(10);
+    let res = vm.execute_batch(gas(), ExecutionContext::new(10, 1), batch(), false);
+    assert_eq!(res.status_code, StatusCode::TRANSACTION_EXPIRED);
+
+    let res = vm.execute_batch(gas(), ExecutionContext::new(9, 1), batch(), false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
@@ -0,0 +1,71 @@
+use common::assets::*;
+use common::mock::Utils;
+use common::vm;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_vm_runtime::data_cache::RemoteCache;
+use mvm::data::State;
+use mvm::types::{ScriptArg, ScriptTx};
+use mvm::value::{AsMoveValue, MoveValue};
+
+mod common;
+
+#[derive(Debug, PartialEq, AsMoveValue)]
+#[move_fields(val)]
+struct StoreU64 {
+    val: u64,
+}
+
+#[derive(Debug, PartialEq, AsMoveValue)]
+struct Order {
+    owner: AccountAddress,
+    amounts: Vec<u128>,
+    inner: StoreU64,
+    filled: bool,
+}
+
+#[test]
+fn test_decode_resource() {
+    let (vm, store, _, oracle, _) = vm();
+    vm.pub_mod(store_module());
+
+    let code = store_u64_script(addr("0x1"), 0).code().to_vec();
+    vm.exec(ScriptTx::new(
+        code,
+        vec![13u64.to_script_arg().unwrap()],
+        vec![],
+        vec![addr("0x1")],
+    ));
+
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    let blob = State::new(store, oracle)
+        .get_resource(&addr("0x1"), &tag)
+        .unwrap()
+        .unwrap();
+    assert_eq!(StoreU64::from_bytes(&blob).unwrap(), StoreU64 { val: 13 });
+}
+
+#[test]
+fn test_struct_roundtrip() {
+    let order = Order {
+        owner: addr("0x2"),
+        amounts: vec![1, 2],
+        inner: StoreU64 { val: 3 },
+        filled: true,
+    };
+    let blob = order.to_bytes().unwrap();
+    assert_eq!(Order::from_bytes(&blob).unwrap(), order);
+
+    assert!(StoreU64::from_move_value(MoveValue::U64(1)).is_err());
+    assert!(order.to_script_arg().is_err());
+    assert_eq!(
+        vec![addr("0x1")].to_script_arg().unwrap(),
+        ScriptArg::VectorAddress(vec![addr("0x1")])
+    );
+}
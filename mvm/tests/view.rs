@@ -0,0 +1,194 @@
+use common::assets::{addr, coins_module, gas};
+use common::bytecode::{native_call_script, value_module};
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock, Utils};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
+use mvm::data::{AccessKey, ExecutionContext, SessionPolicy, Storage};
+use mvm::mvm::Mvm;
+use mvm::types::{ScriptArg, ScriptTx};
+use mvm::view::ViewCall;
+use mvm::Vm;
+use vm::file_format::SignatureToken;
+
+mod common;
+
+type ViewVm = Mvm<StorageMock, EventHandlerMock, OracleMock, BankMock>;
+
+fn vm(ttl: u64) -> (ViewVm, StorageMock) {
+    let store = StorageMock::new();
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_view_cache(8, ttl);
+    vm.pub_mod(value_module(addr("0x2"), "Values"));
+    (vm, store)
+}
+
+fn module() -> ModuleId {
+    ModuleId::new(addr("0x2"), Identifier::new("Values").unwrap())
+}
+
+fn call(function: &str, args: Vec<ScriptArg>) -> ViewCall {
+    ViewCall::new(module(), Identifier::new(function).unwrap(), args)
+}
+
+fn value_key(address: &str) -> AccessKey {
+    let tag = StructTag {
+        address: addr("0x2"),
+        module: Identifier::new("Values").unwrap(),
+        name: Identifier::new("Value").unwrap(),
+        type_params: vec![],
+    };
+    AccessKey::from((&addr(address), &tag))
+}
+
+fn run(vm: &ViewVm, function: &str, params: Vec<SignatureToken>, args: Vec<ScriptArg>) {
+    let script = native_call_script(
+        addr("0x2"),
+        "Values",
+        function,
+        params,
+        vec![],
+        args.clone(),
+    );
+    let script = ScriptTx::new(script.code().to_vec(), args, vec![], vec![addr("0x1")]);
+    let res = vm.execute_script(gas(), ExecutionContext::new(1, 1), script, false);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+}
+
+fn get(vm: &ViewVm, timestamp: u64, state_version: u64) -> u64 {
+    let values = vm
+        .view_function(
+            gas(),
+            ExecutionContext::new(timestamp, 1),
+            &call("get", vec![ScriptArg::Address(addr("0x1"))]),
+            state_version,
+        )
+        .unwrap();
+    assert_eq!(values.len(), 1);
+    bcs::from_bytes(&values[0]).unwrap()
+}
+
+#[test]
+fn test_view_function() {
+    let (vm, _) = vm(100);
+    let values = vm
+        .view_function(
+            gas(),
+            ExecutionContext::new(1, 1),
+            &call("double", vec![ScriptArg::U64(21)]),
+            0,
+        )
+        .unwrap();
+    assert_eq!(values, vec![bcs::to_bytes(&42u64).unwrap()]);
+
+    let err = vm
+        .view_function(
+            gas(),
+            ExecutionContext::new(1, 1),
+            &call("get", vec![ScriptArg::Address(addr("0x1"))]),
+            0,
+        )
+        .unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::MISSING_DATA);
+    assert_eq!(vm.view_cache_len(), 1);
+}
+
+#[test]
+fn test_view_cache_invalidation() {
+    let (vm, _) = vm(100);
+    run(
+        &vm,
+        "set",
+        vec![
+            SignatureToken::Reference(Box::new(SignatureToken::Signer)),
+            SignatureToken::U64,
+        ],
+        vec![ScriptArg::U64(5)],
+    );
+    assert_eq!(get(&vm, 1, 0), 5);
+    assert_eq!(get(&vm, 1, 0), 5);
+    assert_eq!(vm.view_cache_len(), 1);
+    vm.view_function(
+        gas(),
+        ExecutionContext::new(1, 1),
+        &call("double", vec![ScriptArg::U64(1)]),
+        0,
+    )
+    .unwrap();
+    assert_eq!(vm.view_cache_len(), 2);
+
+    // Transactions writing the resource drop only the results which read it.
+    run(
+        &vm,
+        "bump",
+        vec![SignatureToken::Address],
+        vec![ScriptArg::Address(addr("0x1"))],
+    );
+    assert_eq!(vm.view_cache_len(), 1);
+    assert_eq!(get(&vm, 1, 0), 6);
+}
+
+#[test]
+fn test_view_cache_expiration() {
+    let (vm, store) = vm(100);
+    run(
+        &vm,
+        "set",
+        vec![
+            SignatureToken::Reference(Box::new(SignatureToken::Signer)),
+            SignatureToken::U64,
+        ],
+        vec![ScriptArg::U64(5)],
+    );
+    assert_eq!(get(&vm, 0, 0), 5);
+
+    // The host changes the storage behind the vm.
+    store.insert(value_key("0x1").as_ref(), &bcs::to_bytes(&7u64).unwrap());
+    assert_eq!(get(&vm, 99, 0), 5);
+    assert_eq!(get(&vm, 99, 1), 7);
+    assert_eq!(get(&vm, 100, 0), 7);
+
+    store.insert(value_key("0x1").as_ref(), &bcs::to_bytes(&8u64).unwrap());
+    vm.invalidate_view_paths(&[value_key("0x1")]);
+    assert_eq!(get(&vm, 100, 0), 8);
+}
+
+fn coin(name: &str) -> TypeTag {
+    TypeTag::Struct(StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Coins").unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params: vec![],
+    })
+}
+
+#[test]
+fn test_view_cache_is_keyed_by_policy() {
+    let (vm, _, _, oracle, _) = common::vm();
+    let vm = vm.with_view_cache(8, 100);
+    vm.pub_mod(coins_module());
+    oracle.set_price("ETH_BTC", 13);
+
+    let call = ViewCall::new(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Coins").unwrap()),
+        Identifier::new("get_price").unwrap(),
+        vec![],
+    )
+    .with_type_args(vec![coin("ETH"), coin("BTC")]);
+    let values = vm
+        .view_function(gas(), ExecutionContext::new(1, 1), &call, 0)
+        .unwrap();
+    assert_eq!(bcs::from_bytes::<u128>(&values[0]).unwrap(), 13);
+    assert_eq!(vm.view_cache_len(), 1);
+
+    // The sandboxed caller is not served the result read from the oracle.
+    let sandbox = ExecutionContext::new(1, 1).with_policy(SessionPolicy::sandbox());
+    let err = vm.view_function(gas(), sandbox, &call, 0).unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::HOST_ACCESS_DENIED);
+    assert_eq!(vm.view_cache_len(), 1);
+}
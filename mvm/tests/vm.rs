@@ -1,6 +1,8 @@
 #[macro_use]
 extern crate alloc;
 
+use std::sync::Arc;
+
 use common::mock::Utils;
 use common::{assets::*, mock::*, vm};
 use move_core_types::account_address::AccountAddress;
@@ -8,7 +10,9 @@ use move_core_types::identifier::Identifier;
 use move_core_types::language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS};
 use move_core_types::vm_status::StatusCode;
 use move_vm_runtime::data_cache::RemoteCache;
-use mvm::data::{BalanceAccess, ExecutionContext, State};
+use move_vm_runtime::move_vm::SharedCache;
+use mvm::builder::MvmBuilder;
+use mvm::data::{AccessKey, BalanceAccess, ExecutionContext, State, Storage};
 use mvm::types::Gas;
 use mvm::Vm;
 
@@ -70,6 +74,7 @@ fn test_store_event() {
 
     vm.pub_mod(event_module());
     vm.pub_mod(event_proxy_module());
+    event.clear();
 
     vm.exec(emit_event_script(addr("0x1"), test_value));
 
@@ -137,6 +142,43 @@ fn test_load_system_resources() {
     assert_eq!(store.val, timestamp);
 }
 
+#[test]
+fn test_system_resources_shadow_storage() {
+    let (vm, store, _, oracle, _) = vm();
+    let state = State::new(store.clone(), oracle);
+
+    vm.pub_mod(store_module());
+    vm.pub_mod(time_module());
+    vm.pub_mod(block_module());
+
+    // Stale values written by the host are ignored.
+    let block_tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Block").unwrap(),
+        name: Identifier::new("BlockMetadata").unwrap(),
+        type_params: vec![],
+    };
+    store.insert(
+        AccessKey::from((&CORE_CODE_ADDRESS, &block_tag)).as_ref(),
+        &1u64.to_le_bytes(),
+    );
+
+    vm.exec_with_context(
+        ExecutionContext::new(10, 1000),
+        store_sys_resources_script(addr("0x1"), addr("0x2")),
+    );
+
+    let tag = StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("Store").unwrap(),
+        name: Identifier::new("U64").unwrap(),
+        type_params: vec![],
+    };
+    let blob = state.get_resource(&addr("0x1"), &tag).unwrap().unwrap();
+    let store: StoreU64 = bcs::from_bytes(&blob).unwrap();
+    assert_eq!(store.val, 1000);
+}
+
 #[test]
 fn test_oracle() {
     let (vm, store, _, oracle, _) = vm();
@@ -257,13 +299,13 @@ fn test_balance() {
         addr_1, addr_2, init_usdt, init_pont, init_btc,
     ));
 
-    assert_eq!(bank.get_balance(&addr_1, "USDT"), Some(512));
-    assert_eq!(bank.get_balance(&addr_1, "PONT"), Some(61));
-    assert_eq!(bank.get_balance(&addr_1, "BTC"), Some(13));
+    assert_eq!(bank.get_balance(&addr_1, &ticker("USDT")), Some(512));
+    assert_eq!(bank.get_balance(&addr_1, &ticker("PONT")), Some(61));
+    assert_eq!(bank.get_balance(&addr_1, &ticker("BTC")), Some(13));
 
-    assert_eq!(bank.get_balance(&addr_2, "USDT"), Some(512));
-    assert_eq!(bank.get_balance(&addr_2, "PONT"), Some(3));
-    assert_eq!(bank.get_balance(&addr_2, "BTC"), None);
+    assert_eq!(bank.get_balance(&addr_2, &ticker("USDT")), Some(512));
+    assert_eq!(bank.get_balance(&addr_2, &ticker("PONT")), Some(3));
+    assert_eq!(bank.get_balance(&addr_2, &ticker("BTC")), None);
 }
 
 #[test]
@@ -302,7 +344,7 @@ fn test_transfer() {
     vm.exec(test_transfer_script(alice, bob, send_to_bob));
 
     assert_eq!(
-        bank.get_balance(&alice, "PONT"),
+        bank.get_balance(&alice, &ticker("PONT")),
         Some(alice_balance - send_to_bob)
     );
 
@@ -328,3 +370,107 @@ fn test_transfer() {
 
     assert_eq!(bob_account, send_to_bob);
 }
+
+#[test]
+fn test_cache_stats() {
+    let (vm, _, _, _, _) = vm();
+    assert_eq!(vm.cache_stats().type_layouts, 0);
+
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 1));
+    let stats = vm.cache_stats();
+    assert_eq!(stats.scripts, 1);
+    assert!(stats.modules > 0);
+    assert!(stats.type_layouts > 0);
+
+    vm.exec(store_u64_script(addr("0x1"), 2));
+    assert_eq!(vm.cache_stats(), stats);
+
+    vm.clear();
+    assert_eq!(vm.cache_stats().type_layouts, 0);
+}
+
+#[test]
+fn test_external_state_change() {
+    let (vm, store, _, _, _) = vm();
+    vm.pub_mod(store_module());
+    vm.exec(store_u64_script(addr("0x1"), 1));
+    let stats = vm.cache_stats();
+
+    let store_id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Store").unwrap());
+    let module_key = AccessKey::from(&store_id);
+    assert_eq!(module_key.module_id(), Some(store_id));
+    let resource_key = AccessKey::from((
+        &addr("0x1"),
+        &StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("Store").unwrap(),
+            name: Identifier::new("U64").unwrap(),
+            type_params: vec![],
+        },
+    ));
+    assert_eq!(resource_key.module_id(), None);
+
+    store.remove(resource_key.as_ref());
+    vm.on_external_state_change(&[resource_key]);
+    assert_eq!(vm.cache_stats(), stats);
+
+    store.insert(module_key.as_ref(), store_module().code());
+    vm.on_external_state_change(&[module_key]);
+    assert_eq!(vm.cache_stats().modules, 0);
+    vm.exec(store_u64_script(addr("0x1"), 2));
+}
+
+#[test]
+fn test_upgrade_evicts_module_and_dependents() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(event_module());
+    vm.pub_mod(event_proxy_module());
+    vm.pub_mod(store_module());
+    vm.exec(emit_event_script(addr("0x1"), 1));
+    vm.exec(store_u64_script(addr("0x1"), 1));
+    let stats = vm.cache_stats();
+    assert_eq!((stats.modules, stats.scripts), (3, 2));
+
+    // Store has no dependents: the events stay cached.
+    vm.pub_mod(store_module());
+    let after = vm.cache_stats();
+    assert_eq!((after.modules, after.scripts), (2, 1));
+    assert_eq!(after.generation, stats.generation + 1);
+    vm.exec(store_u64_script(addr("0x2"), 2));
+
+    // EventProxy and the event script depend on Event.
+    vm.pub_mod(event_module());
+    let after = vm.cache_stats();
+    assert_eq!((after.modules, after.scripts), (1, 1));
+    vm.exec(emit_event_script(addr("0x1"), 2));
+    assert_eq!(vm.cache_stats().modules, 3);
+}
+
+#[test]
+fn test_shared_cache() {
+    let cache = Arc::new(SharedCache::new());
+    let store = StorageMock::new();
+    let new_vm = || {
+        MvmBuilder::new(store.clone(), EventHandlerMock::default())
+            .with_oracle(OracleMock::default())
+            .with_bank(BankMock::default())
+            .build()
+            .unwrap()
+            .with_shared_cache(cache.clone())
+    };
+    let first = new_vm();
+    let second = new_vm();
+
+    first.pub_mod(store_module());
+    first.exec(store_u64_script(addr("0x1"), 1));
+    let stats = first.cache_stats();
+    assert_eq!(stats.scripts, 1);
+    assert_eq!(second.cache_stats(), stats);
+
+    second.exec(store_u64_script(addr("0x1"), 2));
+    assert_eq!(second.cache_stats(), stats);
+
+    second.clear();
+    assert_eq!(first.cache_stats().modules, 0);
+}
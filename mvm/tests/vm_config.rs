@@ -1,10 +1,23 @@
 extern crate alloc;
 mod common;
 
-use crate::common::mock::StorageMock;
+use crate::common::assets::store_module;
+use crate::common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use crate::common::vm;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::builder::MvmBuilder;
 use mvm::gas_schedule::cost_table;
-use mvm::vm_config::loader::{load_vm_config, store_vm_config};
-use mvm::vm_config::VmConfig;
+use mvm::testkit::ticker;
+use mvm::types::{Gas, GasBounds};
+use mvm::vm_config::loader::{load_config_view_at, load_vm_config, store_vm_config};
+use mvm::vm_config::{
+    OracleAccess, OracleAcl, PublishPermission, PublisherPolicy, RegisteredCurrencies, VmConfig,
+    CONFIG_ADDRESS,
+};
+use mvm::Vm;
+use parity_scale_codec::{Decode, Encode};
 
 #[test]
 fn load_store_test() {
@@ -13,6 +26,29 @@ fn load_store_test() {
 
     let vm_config = VmConfig {
         gas_schedule: cost_table,
+        max_call_depth: 16,
+        max_type_depth: 8,
+        max_module_size: 1024,
+        max_functions: 32,
+        max_structs: 16,
+        max_identifier_length: 64,
+        verify_per_basic_block: 10,
+        verify_per_edge: 5,
+        verify_per_local: 2,
+        lazy_accounts: true,
+        max_events: 8,
+        max_event_bytes: 512,
+        cold_access_cost: 2000,
+        warm_access_cost: 200,
+        chain_id: Some(2),
+        max_script_size: 4096,
+        max_type_args: 4,
+        max_args_size: 256,
+        max_block_gas: Some(1_000_000),
+        check_balance_conservation: true,
+        min_gas_unit_price: 1,
+        max_gas_unit_price: Some(100),
+        max_gas_per_tx: Some(500_000),
     };
     let mock = StorageMock::new();
     store_vm_config(&mock, &vm_config);
@@ -27,3 +63,180 @@ fn load_from_empty_store_test() {
     let loaded_vm_config = load_vm_config(&StorageMock::new()).unwrap();
     assert_eq!(VmConfig::default(), loaded_vm_config);
 }
+
+#[test]
+fn decode_config_without_limits_test() {
+    let mut cost_table = cost_table();
+    cost_table.instruction_table.remove(0);
+    let blob = cost_table.encode();
+
+    let vm_config = VmConfig::decode(&mut blob.as_slice()).unwrap();
+    assert_eq!(vm_config, VmConfig::legacy(cost_table));
+}
+
+#[test]
+fn decode_config_without_access_costs_test() {
+    let config = VmConfig::default();
+    let mut blob = config.gas_schedule.encode();
+    (config.max_call_depth, config.max_type_depth).encode_to(&mut blob);
+    (
+        config.max_module_size,
+        config.max_functions,
+        config.max_structs,
+        config.max_identifier_length,
+    )
+        .encode_to(&mut blob);
+    (
+        config.verify_per_basic_block,
+        config.verify_per_edge,
+        config.verify_per_local,
+    )
+        .encode_to(&mut blob);
+    config.lazy_accounts.encode_to(&mut blob);
+    (config.max_events, config.max_event_bytes).encode_to(&mut blob);
+
+    let decoded = VmConfig::decode(&mut blob.as_slice()).unwrap();
+    assert_eq!(decoded.limits(), config.limits());
+    assert_eq!(decoded.verification_costs(), config.verification_costs());
+    assert_eq!(decoded.access_costs().cold, 0);
+    assert_eq!(decoded.access_costs().warm, 0);
+    assert_eq!(decoded.tx_limits().max_script_size, u32::MAX as usize);
+    assert_eq!(decoded.tx_limits().max_type_args, u32::MAX as usize);
+    assert_eq!(decoded.max_block_gas, None);
+}
+
+#[test]
+fn config_round_trip_test() {
+    let currencies = RegisteredCurrencies::new(vec![ticker("PONT"), ticker("BTC")]);
+    let blob = currencies.encode();
+    assert_eq!(
+        RegisteredCurrencies::decode(&mut blob.as_slice()).unwrap(),
+        currencies
+    );
+    assert_eq!(currencies.currencies(), &[ticker("PONT"), ticker("BTC")]);
+
+    let policy = PublisherPolicy::new(PublishPermission::Denied).with_account(
+        CORE_CODE_ADDRESS,
+        PublishPermission::Modules(vec!["Coins".to_owned()]),
+    );
+    let blob = policy.encode();
+    assert_eq!(
+        PublisherPolicy::decode(&mut blob.as_slice()).unwrap(),
+        policy
+    );
+
+    let acl = OracleAcl::new(OracleAccess::Denied).with_module(
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Oracle").unwrap()),
+        OracleAccess::Tickers(vec![ticker("ETH_BTC")]),
+    );
+    let blob = acl.encode();
+    assert_eq!(OracleAcl::decode(&mut blob.as_slice()).unwrap(), acl);
+}
+
+#[test]
+fn config_validation_test() {
+    assert!(VmConfig::default().validate().is_ok());
+    let mut vm_config = VmConfig::default();
+    vm_config.max_call_depth = 0;
+    assert!(vm_config.validate().is_err());
+
+    assert!(PublisherPolicy::default().validate().is_ok());
+    let policy = PublisherPolicy::default().with_account(
+        CORE_CODE_ADDRESS,
+        PublishPermission::Modules(vec!["Not a module".to_owned()]),
+    );
+    assert!(policy.validate().is_err());
+
+    assert!(
+        RegisteredCurrencies::new(vec![ticker("PONT"), ticker("BTC")])
+            .validate()
+            .is_ok()
+    );
+    assert!(
+        RegisteredCurrencies::new(vec![ticker("PONT"), ticker("pont")])
+            .validate()
+            .is_err()
+    );
+}
+
+#[test]
+fn config_view_test() {
+    let (vm, store, _, _, _) = vm();
+    let view = vm.config_view().unwrap();
+    assert_eq!(view.epoch, 0);
+    assert_eq!(view.vm_config, VmConfig::default());
+    assert_eq!(view.publisher_policy, PublisherPolicy::default());
+    assert_eq!(view.oracle_acl, OracleAcl::default());
+    assert_eq!(view.registered_currencies, None);
+    assert!(view.validate().is_ok());
+
+    // The currencies and the epoch number are written by a transaction of the config address.
+    let res = vm.register_currency(ticker("PONT")).unwrap();
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert!(res.write_set_digest.is_some());
+    let view = vm.config_view().unwrap();
+    assert_eq!(view.epoch, 1);
+    assert_eq!(
+        view.registered_currencies,
+        Some(RegisteredCurrencies::new(vec![ticker("PONT")]))
+    );
+    assert_eq!(view, load_config_view_at(&store, CONFIG_ADDRESS).unwrap());
+}
+
+#[test]
+fn gas_bounds_test() {
+    let store = StorageMock::new();
+    store_vm_config(
+        &store,
+        &VmConfig {
+            min_gas_unit_price: 2,
+            max_gas_unit_price: Some(100),
+            max_gas_per_tx: Some(500_000),
+            ..VmConfig::default()
+        },
+    );
+    let vm = MvmBuilder::new(store, EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap();
+    let publish = |max_gas_amount: u64, gas_unit_price: u64| {
+        let gas = Gas::new(max_gas_amount, gas_unit_price).unwrap();
+        vm.publish_module(gas, store_module(), false).status_code
+    };
+
+    assert_eq!(
+        publish(10_000, 1),
+        StatusCode::GAS_UNIT_PRICE_BELOW_MIN_BOUND
+    );
+    assert_eq!(
+        publish(10_000, 101),
+        StatusCode::GAS_UNIT_PRICE_ABOVE_MAX_BOUND
+    );
+    assert_eq!(
+        publish(500_001, 2),
+        StatusCode::MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND
+    );
+    assert_eq!(publish(10_000, 2), StatusCode::EXECUTED);
+}
+
+#[test]
+fn decode_config_without_gas_bounds_test() {
+    let config = VmConfig {
+        min_gas_unit_price: 2,
+        max_gas_unit_price: Some(100),
+        max_gas_per_tx: Some(500_000),
+        ..VmConfig::default()
+    };
+    let mut blob = config.encode();
+    let bounds = (
+        config.min_gas_unit_price,
+        config.max_gas_unit_price,
+        config.max_gas_per_tx,
+    );
+    blob.truncate(blob.len() - bounds.encode().len());
+
+    // Configs stored before the bounds keep accepting any gas.
+    let decoded = VmConfig::decode(&mut blob.as_slice()).unwrap();
+    assert_eq!(decoded.gas_bounds(), GasBounds::default());
+}
@@ -0,0 +1,50 @@
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use mvm::bench::weights::{
+    bench_deep_call, bench_publish, bench_storage_write, deep_call_tx, measure,
+};
+use mvm::bench::workloads::{gas, vm};
+use mvm::data::ExecutionContext;
+use mvm::Vm;
+
+#[test]
+fn test_publish_weight() {
+    let small = bench_publish(1).unwrap();
+    let large = bench_publish(1000).unwrap();
+    assert_eq!(small.name, "publish");
+    assert!(large.component > small.component);
+    assert!(large.gas_used > small.gas_used);
+}
+
+#[test]
+fn test_deep_call_weight() {
+    let shallow = bench_deep_call(1).unwrap();
+    let deep = bench_deep_call(500).unwrap();
+    assert_eq!(deep.component, 500);
+    assert!(deep.gas_used > shallow.gas_used);
+    assert!(bench_deep_call(10_000).is_err());
+}
+
+#[test]
+fn test_storage_write_weight() {
+    let small = bench_storage_write(1).unwrap();
+    let large = bench_storage_write(10_000).unwrap();
+    assert!(large.gas_used > small.gas_used);
+    assert_eq!(
+        large.to_string(),
+        format!(
+            "storage_write 10000 {} {}",
+            large.gas_used,
+            large.elapsed.as_nanos()
+        )
+    );
+}
+
+#[test]
+fn test_failed_benchmark() {
+    let (vm, _, _) = vm();
+    let tx = deep_call_tx(CORE_CODE_ADDRESS, 1).unwrap();
+    let res = measure("missing_module", 1, || {
+        vm.execute_script(gas(), ExecutionContext::new(100, 100), tx, false)
+    });
+    assert!(res.is_err());
+}
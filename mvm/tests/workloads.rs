@@ -0,0 +1,32 @@
+use move_core_types::account_address::AccountAddress;
+use move_core_types::vm_status::StatusCode;
+use mvm::bench::workloads::{gas, loop_tx, publish_module_tx, transfer_tx, vm};
+use mvm::data::{BalanceAccess, ExecutionContext};
+use mvm::testkit::ticker;
+use mvm::Vm;
+
+#[test]
+fn test_workloads() {
+    let (vm, _, bank) = vm();
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    bank.set_balance(&alice, "PONT", 100);
+
+    let res = vm.publish_module(gas(), publish_module_tx(), true);
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        transfer_tx(alice, bob, 10),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(bank.get_balance(&alice, &ticker("PONT")), Some(90));
+
+    let short = vm.execute_script(gas(), ExecutionContext::new(100, 100), loop_tx(10), true);
+    assert_eq!(short.status_code, StatusCode::EXECUTED);
+    let long = vm.execute_script(gas(), ExecutionContext::new(100, 100), loop_tx(1000), true);
+    assert_eq!(long.status_code, StatusCode::EXECUTED);
+    assert!(long.gas_used > short.gas_used);
+}
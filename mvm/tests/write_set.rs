@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use common::assets::*;
+use common::mock::{BankMock, EventHandlerMock, OracleMock, StorageMock};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use move_core_types::vm_status::StatusCode;
+use mvm::access_path::AccessPath;
+use mvm::builder::MvmBuilder;
+use mvm::data::{AccessKey, ExecutionContext};
+use mvm::testkit::mock::Utils;
+use mvm::testkit::vm;
+use mvm::types::Gas;
+use mvm::vm_config::loader::access_path_for_config;
+use mvm::vm_config::CONFIG_ADDRESS;
+use mvm::write_set::{ResourceDeleted, WriteOp, WriteSet};
+use mvm::Vm;
+
+mod common;
+
+fn value(val: &[u8]) -> WriteOp {
+    WriteOp::Value(val.to_vec())
+}
+
+#[test]
+fn test_write_set_is_ordered_by_key() {
+    let write_set = WriteSet::new(vec![
+        (b"c".to_vec(), value(b"3")),
+        (b"a".to_vec(), WriteOp::Deletion),
+        (b"b".to_vec(), value(b"2")),
+    ]);
+    let keys = write_set.iter().map(|(key, _)| key).collect::<Vec<_>>();
+    assert_eq!(keys, vec![&b"a"[..], &b"b"[..], &b"c"[..]]);
+}
+
+#[test]
+fn test_last_write_wins() {
+    let write_set = WriteSet::new(vec![
+        (b"a".to_vec(), value(b"1")),
+        (b"a".to_vec(), WriteOp::Deletion),
+    ]);
+    assert_eq!(write_set.len(), 1);
+    assert_eq!(
+        write_set.iter().collect::<Vec<_>>(),
+        vec![(&b"a"[..], &WriteOp::Deletion)]
+    );
+}
+
+#[test]
+fn test_deletions() {
+    let write_set = WriteSet::new(vec![
+        (b"c".to_vec(), WriteOp::Deletion),
+        (b"b".to_vec(), value(b"2")),
+        (b"a".to_vec(), WriteOp::Deletion),
+    ]);
+    assert_eq!(
+        write_set.deletions().collect::<Vec<_>>(),
+        vec![&b"a"[..], &b"c"[..]]
+    );
+
+    let mut key = addr("0x2").to_vec();
+    key.extend_from_slice(b"path");
+    assert_eq!(
+        ResourceDeleted::from_key(&key),
+        Some((
+            addr("0x2"),
+            ResourceDeleted {
+                path: b"path".to_vec()
+            }
+        ))
+    );
+    assert_eq!(ResourceDeleted::from_key(b"short"), None);
+}
+
+#[test]
+fn test_digest_does_not_depend_on_write_order() {
+    let first = WriteSet::new(vec![
+        (b"a".to_vec(), value(b"1")),
+        (b"b".to_vec(), value(b"2")),
+    ]);
+    let second = WriteSet::new(vec![
+        (b"b".to_vec(), value(b"2")),
+        (b"a".to_vec(), value(b"1")),
+    ]);
+    assert_eq!(first.encode(), second.encode());
+    assert_eq!(first.digest(), second.digest());
+    assert_eq!(bcs::from_bytes::<WriteSet>(&first.encode()).unwrap(), first);
+
+    let other = WriteSet::new(vec![(b"a".to_vec(), value(b"1"))]);
+    assert_ne!(first.digest(), other.digest());
+}
+
+#[test]
+fn test_write_set_digest_of_transaction() {
+    let digest = || {
+        let (vm, _, _, _, _) = vm();
+        vm.pub_mod(store_module());
+        let res = vm.execute_script(
+            gas(),
+            ExecutionContext::new(100, 100),
+            store_u64_script(addr("0x1"), 13),
+            false,
+        );
+        assert_eq!(res.status_code, StatusCode::EXECUTED);
+        res.write_set_digest
+    };
+    assert!(digest().is_some());
+    assert_eq!(digest(), digest());
+}
+
+#[test]
+fn test_discarded_transaction_has_no_write_set() {
+    let (vm, _, _, _, _) = vm();
+    vm.pub_mod(store_module());
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13).with_sequence_number(0, 1),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::SEQUENCE_NUMBER_TOO_NEW);
+    assert_eq!(res.write_set_digest, None);
+}
+
+/// Returns the write set of the storage changes between the snapshots.
+fn diff(before: &HashMap<Vec<u8>, Vec<u8>>, after: &HashMap<Vec<u8>, Vec<u8>>) -> WriteSet {
+    let deleted = before
+        .keys()
+        .filter(|key| !after.contains_key(*key))
+        .map(|key| (key.clone(), WriteOp::Deletion));
+    let written = after
+        .iter()
+        .filter(|(key, val)| before.get(*key) != Some(*val))
+        .map(|(key, val)| (key.clone(), value(val)));
+    WriteSet::new(deleted.chain(written))
+}
+
+#[test]
+fn test_digest_covers_applied_writes() {
+    let store = StorageMock::new();
+    let config_path = AccessPath::new(
+        addr("0x2"),
+        AccessPath::resource_access_vec(&StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("Store").unwrap(),
+            name: Identifier::new("U64").unwrap(),
+            type_params: vec![],
+        }),
+    );
+    let vm = MvmBuilder::new(store.clone(), EventHandlerMock::default())
+        .with_oracle(OracleMock::default())
+        .with_bank(BankMock::default())
+        .build()
+        .unwrap()
+        .with_config_path(config_path);
+    vm.pub_mod(store_module());
+
+    // The config write starts a new epoch, the epoch number is a part of the write set.
+    let before = store.data.borrow().clone();
+    let res = vm.execute_script(
+        gas(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x2"), 13),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::EXECUTED);
+    assert_eq!(vm.current_epoch(), 1);
+    let applied = diff(&before, &store.data.borrow());
+    assert_eq!(res.write_set_digest, Some(applied.digest()));
+}
+
+#[test]
+fn test_digest_covers_kept_writes_of_failed_transaction() {
+    let (vm, store, _, _, _) = vm();
+    vm.pub_mod(store_module());
+
+    // The sequence number is written even though the transaction runs out of gas.
+    let before = store.data.borrow().clone();
+    let res = vm.execute_script(
+        Gas::new(1, 1).unwrap(),
+        ExecutionContext::new(100, 100),
+        store_u64_script(addr("0x1"), 13).with_sequence_number(0, 0),
+        false,
+    );
+    assert_eq!(res.status_code, StatusCode::OUT_OF_GAS);
+    let applied = diff(&before, &store.data.borrow());
+    assert_eq!(applied.len(), 1);
+    assert_eq!(res.write_set_digest, Some(applied.digest()));
+}
+
+#[test]
+fn test_digest_covers_external_epoch_change() {
+    let (vm, store, _, _, _) = vm();
+    let before = store.data.borrow().clone();
+    let write_set =
+        vm.on_external_state_change(&[AccessKey::from(&access_path_for_config(CONFIG_ADDRESS))]);
+    assert_eq!(vm.current_epoch(), 1);
+    assert_eq!(write_set, diff(&before, &store.data.borrow()));
+    assert_eq!(write_set.len(), 1);
+}